@@ -4,14 +4,15 @@
 
 use std::num::NonZeroUsize;
 
-use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+use clique_fusion::{
+    CHI2_2D_CONFIDENCE_95, CliqueIndex, Config as IndexConfig, EnumerationStrategy, Observation,
+    Unique,
+    synthetic::{Config, Scenario, SimulatedSensor, generate_observations},
+};
 use criterion::{Criterion, criterion_group, criterion_main};
 
 use uuid::Uuid;
 
-mod gen_data;
-use gen_data::{Config, generate_observations};
-
 /// Read and parse JSONL file
 fn five_pct_clustered() -> Vec<Unique<Observation, Uuid>> {
     let config = Config {
@@ -50,5 +51,62 @@ fn benchmark_incremental(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_bulk, benchmark_incremental);
+fn benchmark_enumeration_strategies(c: &mut Criterion) {
+    let observations: Vec<_> = five_pct_clustered();
+    let mut group = c.benchmark_group("enumeration_strategy");
+
+    for strategy in [
+        EnumerationStrategy::MaxDegreePivot,
+        EnumerationStrategy::RandomPivot,
+        EnumerationStrategy::DegeneracyOrdering,
+        EnumerationStrategy::NoPivot,
+    ] {
+        let config = IndexConfig::new(CHI2_2D_CONFIDENCE_95).enumeration_strategy(strategy);
+        group.bench_function(format!("{strategy:?}"), |b| {
+            b.iter(|| {
+                let _index = CliqueIndex::from_observations_with_config(
+                    observations.clone(),
+                    config.clone(),
+                );
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn benchmark_scenario(c: &mut Criterion) {
+    let sensor = SimulatedSensor {
+        error_radius: 5.0,
+        miss_probability: 0.1,
+        dropout_probability: 0.02,
+        bias_drift: 0.2,
+        clutter_rate: 5.0,
+    };
+    let scenario = Scenario {
+        spread: 500.0,
+        targets: 200,
+        sensors: vec![sensor; 3],
+        scans: 20,
+        scan_interval: 1.0,
+        retention: Some(5),
+        random_seed: 12345,
+    };
+    let simulation = scenario.generate();
+
+    c.bench_function("scenario_replay", |b| {
+        b.iter(|| {
+            let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+            simulation.replay(&mut index);
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    benchmark_bulk,
+    benchmark_incremental,
+    benchmark_enumeration_strategies,
+    benchmark_scenario
+);
 criterion_main!(benches);
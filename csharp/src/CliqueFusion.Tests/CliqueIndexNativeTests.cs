@@ -20,7 +20,8 @@ namespace CliqueFusion.Tests
         [Fact]
         public void CanCreateEmptyIndex()
         {
-            IntPtr index = CliqueIndexNative.CliqueIndex_new(5.99);
+            var status = CliqueIndexNative.CliqueIndex_new(5.99, out IntPtr index);
+            Assert.Equal(CliqueIndexNative.CliqueStatus.Ok, status);
             Assert.NotEqual(IntPtr.Zero, index);
             CliqueIndexNative.CliqueIndex_free(index);
         }
@@ -34,8 +35,9 @@ namespace CliqueFusion.Tests
             var obs = CreateObservation(Guid.NewGuid(), 1.0, 2.0, 1.0, 0.0, 1.0);
             var obsPtr = ToNativePointer(obs);
 
-            IntPtr index = CliqueIndexNative.CliqueIndex_new(5.99);
-            CliqueIndexNative.CliqueIndex_insert(index, obsPtr);
+            CliqueIndexNative.CliqueIndex_new(5.99, out IntPtr index);
+            var status = CliqueIndexNative.CliqueIndex_insert(index, obsPtr);
+            Assert.Equal(CliqueIndexNative.CliqueStatus.Ok, status);
 
             IntPtr cliques = CliqueIndexNative.CliqueIndex_cliques(index);
             Assert.NotEqual(IntPtr.Zero, cliques);
@@ -62,7 +64,8 @@ namespace CliqueFusion.Tests
             Marshal.StructureToPtr(obs2, arrayPtr + (size * 1), false);
             Marshal.StructureToPtr(obs3, arrayPtr + (size * 2), false);
 
-            IntPtr index = CliqueIndexNative.CliqueIndex_from_observations(5.99, arrayPtr, (UIntPtr)3);
+            var status = CliqueIndexNative.CliqueIndex_from_observations(5.99, arrayPtr, (UIntPtr)3, out IntPtr index);
+            Assert.Equal(CliqueIndexNative.CliqueStatus.Ok, status);
             Assert.NotEqual(IntPtr.Zero, index);
 
             IntPtr cliques = CliqueIndexNative.CliqueIndex_cliques(index);
@@ -73,6 +76,30 @@ namespace CliqueFusion.Tests
             Marshal.FreeHGlobal(arrayPtr);
         }
 
+        /// <summary>
+        /// Verifies that invalid arguments are reported with a status code.
+        /// </summary>
+        [Fact]
+        public void InvalidArgumentsReturnStatusCodes()
+        {
+            Assert.Equal(
+                CliqueIndexNative.CliqueStatus.InvalidChi2,
+                CliqueIndexNative.CliqueIndex_new(-1.0, out _));
+
+            CliqueIndexNative.CliqueIndex_new(5.99, out IntPtr index);
+            Assert.Equal(
+                CliqueIndexNative.CliqueStatus.NullPointer,
+                CliqueIndexNative.CliqueIndex_insert(index, IntPtr.Zero));
+
+            var obsPtr = ToNativePointer(CreateObservation(Guid.NewGuid(), 1.0, 2.0, -1.0, 0.0, 1.0));
+            Assert.Equal(
+                CliqueIndexNative.CliqueStatus.InvalidCovariance,
+                CliqueIndexNative.CliqueIndex_insert(index, obsPtr));
+
+            CliqueIndexNative.CliqueIndex_free(index);
+            Marshal.FreeHGlobal(obsPtr);
+        }
+
         /// <summary>
         /// Verifies that freeing a null clique set pointer is safe and does not crash.
         /// </summary>
@@ -143,6 +143,226 @@ namespace CliqueFusion.Tests
             Assert.Contains(cliques[0].ObservationIds, id => id == obs2.Id);
         }
 
+        /// <summary>
+        /// Verifies that enumerating cliques yields the same cliques as retrieving them all at once.
+        /// </summary>
+        [Fact]
+        public void EnumeratedCliquesMatchRetrievedCliques()
+        {
+            var observations = Enumerable.Range(0, 20)
+                .Select(_ => CreateObservation(1.0, 2.0, null))
+                .Append(CreateObservation(50.0, 50.0, null))
+                .Append(CreateObservation(50.1, 50.1, null))
+                .ToList();
+
+            using var index = new CliqueIndex(observations, Chi2Threshold);
+
+            var expected = index.GetCliques().Select(c => c.ObservationIds.ToHashSet()).ToList();
+            var enumerated = index.EnumerateCliques().Select(c => c.ObservationIds.ToHashSet()).ToList();
+
+            Assert.Equal(2, enumerated.Count);
+            Assert.All(enumerated, clique => Assert.Contains(expected, e => e.SetEquals(clique)));
+        }
+
+        /// <summary>
+        /// Verifies that the fused estimate of a clique combines its members.
+        /// </summary>
+        [Fact]
+        public void FusedEstimateCombinesCliqueMembers()
+        {
+            var obs1 = CreateObservation(1.0, 2.0, null);
+            var obs2 = CreateObservation(1.2, 2.2, null);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2 }, 30.0);
+
+            var estimate = Assert.Single(index.GetFusedEstimates());
+            Assert.Equal(2, estimate.ObservationIds.Count);
+            Assert.Equal(1.1, estimate.X, 9);
+            Assert.Equal(2.1, estimate.Y, 9);
+            Assert.Equal(0.5, estimate.CovarianceXX, 9);
+            Assert.Equal(0.5, estimate.CovarianceYY, 9);
+            Assert.Equal(0.04, estimate.Chi2Statistic, 9);
+            Assert.Equal(2u, estimate.DegreesOfFreedom);
+            Assert.Equal(Math.Exp(-0.02), estimate.PValue, 9);
+        }
+
+        /// <summary>
+        /// Verifies that the compatibility graph edges link compatible observations.
+        /// </summary>
+        [Fact]
+        public void EdgesLinkCompatibleObservations()
+        {
+            var obs1 = CreateObservation(1.0, 2.0, null);
+            var obs2 = CreateObservation(2.0, 2.0, null);
+            var obs3 = CreateObservation(50.0, 50.0, null);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2, obs3 }, Chi2Threshold);
+
+            var edge = Assert.Single(index.GetEdges());
+            Assert.Equal(new HashSet<Guid> { obs1.Id, obs2.Id }, new HashSet<Guid> { edge.A, edge.B });
+            Assert.Equal(0.5, edge.MahalanobisSquared, 9);
+        }
+
+        /// <summary>
+        /// Verifies that observations can be looked up and counted.
+        /// </summary>
+        [Fact]
+        public void CanLookUpAndCountObservations()
+        {
+            var obs1 = CreateObservation(1.0, 2.0, Guid.NewGuid());
+            var obs2 = CreateObservation(1.1, 2.1, null);
+            var obs3 = CreateObservation(50.0, 50.0, null);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2, obs3 }, Chi2Threshold);
+
+            Assert.Equal(2, index.Count);
+            Assert.Equal(3, index.TotalObservations);
+            Assert.True(index.Contains(obs3.Id));
+            Assert.False(index.Contains(Guid.NewGuid()));
+            Assert.Equal(obs1, index.Get(obs1.Id));
+            Assert.Null(index.Get(Guid.NewGuid()));
+        }
+
+        /// <summary>
+        /// Verifies that probing finds compatible observations without modifying the index.
+        /// </summary>
+        [Fact]
+        public void ProbingDoesNotModifyIndex()
+        {
+            var obs1 = CreateObservation(1.0, 2.0, null);
+            var obs2 = CreateObservation(50.0, 50.0, null);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2 }, Chi2Threshold);
+
+            var candidate = CreateObservation(1.1, 2.1, null);
+            Assert.Equal(new[] { obs1.Id }, index.Probe(candidate));
+            Assert.False(index.Contains(candidate.Id));
+            Assert.Equal(2, index.TotalObservations);
+        }
+
+        /// <summary>
+        /// Verifies that only the cliques within a region are retrieved.
+        /// </summary>
+        [Fact]
+        public void CliquesInRegionExcludeDistantCliques()
+        {
+            var obs1 = CreateObservation(1.0, 2.0, null);
+            var obs2 = CreateObservation(1.1, 2.1, null);
+            var obs3 = CreateObservation(50.0, 50.0, null);
+            var obs4 = CreateObservation(50.1, 50.1, null);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2, obs3, obs4 }, Chi2Threshold);
+
+            var clique = Assert.Single(index.GetCliquesInRegion(0.0, 0.0, 10.0, 10.0));
+            Assert.Equal(new HashSet<Guid> { obs1.Id, obs2.Id }, clique.ObservationIds.ToHashSet());
+            Assert.Empty(index.GetCliquesInRegion(20.0, 20.0, 30.0, 30.0));
+        }
+
+        /// <summary>
+        /// Verifies that the statistics summarise the index.
+        /// </summary>
+        [Fact]
+        public void StatsSummariseIndex()
+        {
+            var obs1 = CreateObservation(1.0, 2.0, null);
+            var obs2 = CreateObservation(1.1, 2.1, null);
+            var obs3 = CreateObservation(50.0, 50.0, null);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2, obs3 }, Chi2Threshold);
+
+            var stats = index.GetStats();
+            Assert.Equal(3, stats.TotalObservations);
+            Assert.Equal(2, stats.ConnectedObservations);
+            Assert.Equal(1, stats.CompatibilityEdges);
+            Assert.Equal(1, stats.Cliques);
+            Assert.Equal(2, stats.LargestClique);
+            Assert.Equal(0, stats.ApproximateObservations);
+        }
+
+        /// <summary>
+        /// Verifies that removing an observation breaks up the cliques it belonged to.
+        /// </summary>
+        [Fact]
+        public void RemovingObservationUpdatesCliques()
+        {
+            var obs1 = CreateObservation(1.0, 2.0, null);
+            var obs2 = CreateObservation(1.1, 2.1, null);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2 }, 30.0);
+
+            Assert.True(index.Remove(obs1.Id));
+            Assert.False(index.Remove(obs1.Id));
+            Assert.Empty(index.GetCliques());
+        }
+
+        /// <summary>
+        /// Verifies that removing a context removes every observation in it.
+        /// </summary>
+        [Fact]
+        public void RemovingContextRemovesItsObservations()
+        {
+            var retracted = Guid.NewGuid();
+            var obs1 = CreateObservation(1.0, 2.0, retracted);
+            var obs2 = CreateObservation(1.1, 2.1, Guid.NewGuid());
+            var obs3 = CreateObservation(50.0, 50.0, retracted);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2, obs3 }, Chi2Threshold);
+            Assert.Single(index.GetCliques());
+
+            Assert.True(index.RemoveContext(retracted));
+            Assert.Empty(index.GetCliques());
+            Assert.Equal(1, index.TotalObservations);
+            Assert.False(index.RemoveContext(retracted));
+        }
+
+        /// <summary>
+        /// Verifies that updating an observation in place moves it between cliques.
+        /// </summary>
+        [Fact]
+        public void UpdatingObservationUpdatesCliques()
+        {
+            var obs1 = CreateObservation(1.0, 2.0, null);
+            var obs2 = CreateObservation(50.0, 50.0, null);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2 }, 30.0);
+            Assert.Empty(index.GetCliques());
+
+            var refined = obs2 with { X = 1.1, Y = 2.1 };
+            Assert.True(index.Update(refined));
+            Assert.Single(index.GetCliques());
+            Assert.Equal(refined, index.Get(obs2.Id));
+
+            var unknown = CreateObservation(1.0, 2.0, null);
+            Assert.False(index.Update(unknown));
+            Assert.False(index.Contains(unknown.Id));
+
+            index.Upsert(unknown);
+            index.Upsert(obs2);
+            Assert.True(index.Contains(unknown.Id));
+            Assert.Equal(obs2, index.Get(obs2.Id));
+        }
+
+        /// <summary>
+        /// Verifies that changing the threshold recomputes the cliques.
+        /// </summary>
+        [Fact]
+        public void ChangingThresholdUpdatesCliques()
+        {
+            var obs1 = CreateObservation(0.0, 0.0);
+            var obs2 = CreateObservation(1.0, 0.0);
+
+            using var index = new CliqueIndex(new List<Observation> { obs1, obs2 }, Chi2Threshold);
+            Assert.Single(index.GetCliques());
+
+            index.SetChi2Threshold(0.1);
+            Assert.Empty(index.GetCliques());
+
+            Assert.Throws<ArgumentOutOfRangeException>(() => index.SetChi2Threshold(-1.0));
+
+            index.SetChi2Threshold(Chi2Threshold);
+            Assert.Single(index.GetCliques());
+        }
+
         /// <summary>
         /// Verifies that using a disposed index throws appropriate exceptions.
         /// </summary>
@@ -154,6 +374,66 @@ namespace CliqueFusion.Tests
 
             Assert.Throws<ObjectDisposedException>(() => index.Insert(CreateObservation(0, 0)));
             Assert.Throws<ObjectDisposedException>(() => index.GetCliques());
+            Assert.Throws<ObjectDisposedException>(() => index.Remove(Guid.NewGuid()));
+        }
+
+        /// <summary>
+        /// Verifies that inserting an observation with an existing ID throws.
+        /// </summary>
+        [Fact]
+        public void InsertingDuplicateIdThrows()
+        {
+            var obs = CreateObservation(1.0, 2.0, null);
+
+            using var index = new CliqueIndex(Chi2Threshold);
+            index.Insert(obs);
+
+            Assert.Throws<ArgumentException>(() => index.Insert(obs));
+        }
+
+        /// <summary>
+        /// Verifies that thresholds can be computed for arbitrary confidence levels.
+        /// </summary>
+        [Fact]
+        public void ThresholdForConfidenceMatchesPredefinedThresholds()
+        {
+            Assert.Equal(CliqueThresholds.Confidence95, CliqueThresholds.ForConfidence(0.95), 3);
+            Assert.Equal(3.8415, CliqueThresholds.ForConfidence(0.95, 1), 4);
+            Assert.Throws<ArgumentOutOfRangeException>(() => CliqueThresholds.ForConfidence(95.0));
+        }
+
+        /// <summary>
+        /// Verifies that the loaded native library is compatible with the bindings.
+        /// </summary>
+        [Fact]
+        public void NativeLibraryIsCompatible()
+        {
+            CliqueFusionLibrary.EnsureCompatible();
+            Assert.False(string.IsNullOrEmpty(CliqueFusionLibrary.Version));
+        }
+
+        /// <summary>
+        /// Verifies that the index can be exported as GeoJSON.
+        /// </summary>
+        [Fact]
+        public void ExportsGeoJson()
+        {
+            var obs = CreateObservation(1.0, 2.0);
+            using var index = new CliqueIndex(new List<Observation> { obs }, Chi2Threshold);
+
+            var geoJson = index.ToGeoJson();
+
+            Assert.Contains("FeatureCollection", geoJson);
+            Assert.Contains("\"kind\":\"observation\"", geoJson);
+        }
+
+        /// <summary>
+        /// Verifies that an invalid chi-squared threshold is rejected.
+        /// </summary>
+        [Fact]
+        public void CreatingWithInvalidThresholdThrows()
+        {
+            Assert.Throws<ArgumentOutOfRangeException>(() => new CliqueIndex(double.NaN));
         }
 
         /// <summary>
@@ -0,0 +1,65 @@
+// <copyright file="SharedCliqueIndexTests.cs" company="Daniel Eades">
+// Copyright (c) Daniel Eades. All rights reserved.
+// </copyright>
+
+namespace CliqueFusion.Tests
+{
+    using Xunit;
+
+    /// <summary>
+    /// Unit tests for the <see cref="SharedCliqueIndex"/> class.
+    /// </summary>
+    public class SharedCliqueIndexTests
+    {
+        private const double Chi2Threshold = 5.99;
+
+        /// <summary>
+        /// Verifies that observations can be inserted from several threads at once.
+        /// </summary>
+        [Fact]
+        public void ConcurrentInsertsAreAllApplied()
+        {
+            using var index = new SharedCliqueIndex(Chi2Threshold);
+
+            Parallel.For(0, 8, group =>
+            {
+                index.Insert(CreateObservation(group * 100.0, 0.0));
+                index.Insert(CreateObservation((group * 100.0) + 0.1, 0.0));
+                _ = index.GetCliques();
+            });
+
+            Assert.Equal(16, index.Count);
+            Assert.Equal(8, index.GetCliques().Count);
+        }
+
+        /// <summary>
+        /// Verifies removal, threshold changes and disposal.
+        /// </summary>
+        [Fact]
+        public void RemoveAndSetThreshold()
+        {
+            var obs1 = CreateObservation(0.0, 0.0);
+            var obs2 = CreateObservation(0.1, 0.0);
+
+            var index = new SharedCliqueIndex(Chi2Threshold);
+            index.Insert(obs1);
+            index.Insert(obs2);
+            Assert.True(index.Contains(obs1.Id));
+
+            index.SetChi2Threshold(0.001);
+            Assert.Empty(index.GetCliques());
+            Assert.Throws<ArgumentOutOfRangeException>(() => index.SetChi2Threshold(double.NaN));
+
+            Assert.True(index.Remove(obs1.Id));
+            Assert.False(index.Remove(obs1.Id));
+
+            index.Dispose();
+            Assert.Throws<ObjectDisposedException>(() => index.Count);
+        }
+
+        private static Observation CreateObservation(double x, double y)
+        {
+            return new Observation(Guid.NewGuid(), x, y, 1.0, 0.0, 1.0, null);
+        }
+    }
+}
@@ -15,6 +15,20 @@ namespace CliqueFusion.Native
     {
         private const string DllName = "clique_fusion_ffi";
 
+        /// <summary>
+        /// Gets the version of the native library.
+        /// </summary>
+        /// <returns>A pointer to a statically allocated, nul-terminated version string. This must not be freed.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr CliqueFusion_version();
+
+        /// <summary>
+        /// Gets the version of the C ABI exposed by the native library.
+        /// </summary>
+        /// <returns>The ABI version.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern uint CliqueFusion_abi_version();
+
         /// <summary>
         /// Gets the chi-squared threshold for 90% confidence.
         /// </summary>
@@ -36,13 +50,37 @@ namespace CliqueFusion.Native
         [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
         internal static extern double CliqueIndex_chi2_confidence_99();
 
+        /// <summary>
+        /// Computes the chi-squared threshold for an arbitrary confidence level.
+        /// </summary>
+        /// <param name="confidence">The confidence level, as a probability strictly between 0 and 1.</param>
+        /// <param name="dof">The number of degrees of freedom.</param>
+        /// <returns>The chi-squared threshold, or NaN if the arguments are invalid.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern double CliqueFusion_chi2_threshold(double confidence, uint dof);
+
+        /// <summary>
+        /// Gets a description of the most recent error on the calling thread.
+        /// </summary>
+        /// <returns>A pointer to a null-terminated UTF-8 string which must be freed with <see cref="CliqueFusion_string_free"/>, or null if the most recent call succeeded.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr CliqueFusion_last_error_message();
+
+        /// <summary>
+        /// Frees a string returned by the native library.
+        /// </summary>
+        /// <param name="ptr">Pointer to the string.</param>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void CliqueFusion_string_free(IntPtr ptr);
+
         /// <summary>
         /// Creates a new clique index.
         /// </summary>
         /// <param name="chi2">Chi-squared threshold for clustering.</param>
-        /// <returns>A pointer to the new CliqueIndex instance.</returns>
+        /// <param name="index">Receives a pointer to the new CliqueIndex instance.</param>
+        /// <returns>A status code indicating whether the index was created.</returns>
         [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-        internal static extern IntPtr CliqueIndex_new(double chi2);
+        internal static extern CliqueStatus CliqueIndex_new(double chi2, out IntPtr index);
 
         /// <summary>
         /// Creates a clique index from an array of observations.
@@ -50,20 +88,112 @@ namespace CliqueFusion.Native
         /// <param name="chi2">Chi-squared threshold for clustering.</param>
         /// <param name="observations">Pointer to an array of observations.</param>
         /// <param name="len">Number of observations.</param>
-        /// <returns>A pointer to the new CliqueIndex instance.</returns>
+        /// <param name="index">Receives a pointer to the new CliqueIndex instance.</param>
+        /// <returns>A status code indicating whether the index was created.</returns>
         [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-        internal static extern IntPtr CliqueIndex_from_observations(
+        internal static extern CliqueStatus CliqueIndex_from_observations(
             double chi2,
             IntPtr observations,
-            UIntPtr len);
+            UIntPtr len,
+            out IntPtr index);
 
         /// <summary>
         /// Inserts an observation into an existing clique index.
         /// </summary>
         /// <param name="index">Pointer to the clique index.</param>
         /// <param name="observation">Pointer to the observation.</param>
+        /// <returns>A status code indicating whether the observation was inserted.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_insert(IntPtr index, IntPtr observation);
+
+        /// <summary>
+        /// Replaces an observation in an existing clique index with a refined version of it.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="observation">Pointer to the observation.</param>
+        /// <returns>A status code indicating whether the observation was replaced.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_update(IntPtr index, IntPtr observation);
+
+        /// <summary>
+        /// Inserts an observation into an existing clique index, replacing any observation with the same ID.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="observation">Pointer to the observation.</param>
+        /// <returns>A status code indicating whether the observation was inserted.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_upsert(IntPtr index, IntPtr observation);
+
+        /// <summary>
+        /// Removes an observation from an existing clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="id">The observation UUID (16 bytes).</param>
+        /// <returns>A status code indicating whether the observation was removed.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_remove(IntPtr index, byte[] id);
+
+        /// <summary>
+        /// Removes every observation in a context from an existing clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="context">The context UUID (16 bytes).</param>
+        /// <returns>A status code indicating whether any observations were removed.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_remove_context(IntPtr index, byte[] context);
+
+        /// <summary>
+        /// Gets the chi-squared threshold used by a clique index to test compatibility.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <returns>The chi-squared threshold, or NaN if the pointer is null.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern double CliqueIndex_chi2(IntPtr index);
+
+        /// <summary>
+        /// Changes the chi-squared threshold of an existing clique index, recomputing its cliques.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="chi2">The new chi-squared threshold.</param>
+        /// <returns>A status code indicating whether the threshold was changed.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_set_chi2(IntPtr index, double chi2);
+
+        /// <summary>
+        /// Gets the number of observations which are compatible with at least one other observation.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <returns>The number of observations in the compatibility graph.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern UIntPtr CliqueIndex_len(IntPtr index);
+
+        /// <summary>
+        /// Gets the total number of observations in a clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <returns>The total number of observations.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern UIntPtr CliqueIndex_total_observations(IntPtr index);
+
+        /// <summary>
+        /// Checks whether an observation is in a clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="id">The observation UUID (16 bytes).</param>
+        /// <returns><c>true</c> if the observation is in the index.</returns>
         [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
-        internal static extern void CliqueIndex_insert(IntPtr index, IntPtr observation);
+        [return: MarshalAs(UnmanagedType.U1)]
+        internal static extern bool CliqueIndex_contains(IntPtr index, byte[] id);
+
+        /// <summary>
+        /// Looks up an observation in a clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="id">The observation UUID (16 bytes).</param>
+        /// <param name="observation">Receives the observation.</param>
+        /// <returns>A status code indicating whether the observation was found.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_get(IntPtr index, byte[] id, out ObservationC observation);
 
         /// <summary>
         /// Gets the cliques from a clique index.
@@ -73,6 +203,48 @@ namespace CliqueFusion.Native
         [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
         internal static extern IntPtr CliqueIndex_cliques(IntPtr index);
 
+        /// <summary>
+        /// Gets the cliques from a clique index with at least one member within a bounding box.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="minX">The minimum x ordinate of the bounding box.</param>
+        /// <param name="minY">The minimum y ordinate of the bounding box.</param>
+        /// <param name="maxX">The maximum x ordinate of the bounding box.</param>
+        /// <param name="maxY">The maximum y ordinate of the bounding box.</param>
+        /// <returns>A pointer to a CliqueSetC struct.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr CliqueIndex_cliques_in_region(IntPtr index, double minX, double minY, double maxX, double maxY);
+
+        /// <summary>
+        /// Creates a cursor over the cliques of a clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <returns>A pointer to the cursor.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr CliqueIndex_cliques_iter_new(IntPtr index);
+
+        /// <summary>
+        /// Copies the next clique from a cursor into a buffer of UUIDs.
+        /// </summary>
+        /// <param name="iter">Pointer to the cursor.</param>
+        /// <param name="buffer">Buffer receiving the UUIDs.</param>
+        /// <param name="capacity">Capacity of the buffer, in UUIDs.</param>
+        /// <param name="len">Receives the number of UUIDs written (or required, if the buffer is too small).</param>
+        /// <returns>A status code indicating whether a clique was written.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_cliques_iter_next(
+            IntPtr iter,
+            [Out] Guid[] buffer,
+            UIntPtr capacity,
+            out UIntPtr len);
+
+        /// <summary>
+        /// Frees a cursor over the cliques of a clique index.
+        /// </summary>
+        /// <param name="iter">Pointer to the cursor.</param>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void CliqueIndex_cliques_iter_free(IntPtr iter);
+
         /// <summary>
         /// Frees a clique set returned by the index.
         /// </summary>
@@ -80,6 +252,70 @@ namespace CliqueFusion.Native
         [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
         internal static extern void CliqueSetC_free(IntPtr ptr);
 
+        /// <summary>
+        /// Gets the fused estimate of each clique from a clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <returns>A pointer to a FusedSetC struct.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr CliqueIndex_fused_estimates(IntPtr index);
+
+        /// <summary>
+        /// Frees a fused estimate set returned by the index.
+        /// </summary>
+        /// <param name="ptr">Pointer to the FusedSetC.</param>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void FusedSetC_free(IntPtr ptr);
+
+        /// <summary>
+        /// Gets the edges of the compatibility graph from a clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <returns>A pointer to an EdgeListC struct.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr CliqueIndex_edges(IntPtr index);
+
+        /// <summary>
+        /// Frees an edge list returned by the index.
+        /// </summary>
+        /// <param name="ptr">Pointer to the EdgeListC.</param>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void EdgeListC_free(IntPtr ptr);
+
+        /// <summary>
+        /// Finds the observations which an observation would be compatible with, without inserting it.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="observation">Pointer to the observation.</param>
+        /// <param name="compatibleIds">Receives a pointer to a UuidListC struct.</param>
+        /// <returns>A status code indicating whether the list was written.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_probe(IntPtr index, IntPtr observation, out IntPtr compatibleIds);
+
+        /// <summary>
+        /// Frees a UUID list returned by the index.
+        /// </summary>
+        /// <param name="ptr">Pointer to the UuidListC.</param>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void UuidListC_free(IntPtr ptr);
+
+        /// <summary>
+        /// Gets inexpensive statistics of a clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <param name="stats">Receives the statistics.</param>
+        /// <returns>A status code indicating whether the statistics were written.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndex_stats(IntPtr index, out CliqueStatsC stats);
+
+        /// <summary>
+        /// Exports the clique index as a GeoJSON FeatureCollection.
+        /// </summary>
+        /// <param name="index">Pointer to the clique index.</param>
+        /// <returns>A nul-terminated UTF-8 string, to be freed with <see cref="CliqueFusion_string_free"/>, or null on failure.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr CliqueIndex_to_geojson(IntPtr index);
+
         /// <summary>
         /// Frees the clique index.
         /// </summary>
@@ -87,6 +323,119 @@ namespace CliqueFusion.Native
         [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
         internal static extern void CliqueIndex_free(IntPtr ptr);
 
+        /// <summary>
+        /// Creates a new thread-safe clique index.
+        /// </summary>
+        /// <param name="chi2">The chi-squared threshold.</param>
+        /// <param name="index">On success, receives a pointer to the new index.</param>
+        /// <returns>A status code indicating whether the index was created.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndexTS_new(double chi2, out IntPtr index);
+
+        /// <summary>
+        /// Inserts an observation into a thread-safe clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the index.</param>
+        /// <param name="observation">Pointer to the observation.</param>
+        /// <returns>A status code indicating whether the observation was inserted.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndexTS_insert(IntPtr index, IntPtr observation);
+
+        /// <summary>
+        /// Removes an observation from a thread-safe clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the index.</param>
+        /// <param name="id">The observation UUID (16 bytes).</param>
+        /// <returns>A status code indicating whether the observation was removed.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndexTS_remove(IntPtr index, byte[] id);
+
+        /// <summary>
+        /// Gets the chi-squared threshold used by a thread-safe clique index to test compatibility.
+        /// </summary>
+        /// <param name="index">Pointer to the index.</param>
+        /// <returns>The chi-squared threshold, or NaN if the pointer is null.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern double CliqueIndexTS_chi2(IntPtr index);
+
+        /// <summary>
+        /// Changes the chi-squared threshold of a thread-safe clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the index.</param>
+        /// <param name="chi2">The new chi-squared threshold.</param>
+        /// <returns>A status code indicating whether the threshold was changed.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern CliqueStatus CliqueIndexTS_set_chi2(IntPtr index, double chi2);
+
+        /// <summary>
+        /// Gets the number of observations in a thread-safe clique index which are compatible with at least one other observation.
+        /// </summary>
+        /// <param name="index">Pointer to the index.</param>
+        /// <returns>The number of observations in the compatibility graph.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern UIntPtr CliqueIndexTS_len(IntPtr index);
+
+        /// <summary>
+        /// Checks whether an observation is in a thread-safe clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the index.</param>
+        /// <param name="id">The observation UUID (16 bytes).</param>
+        /// <returns><c>true</c> if the observation is in the index.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.U1)]
+        internal static extern bool CliqueIndexTS_contains(IntPtr index, byte[] id);
+
+        /// <summary>
+        /// Takes a snapshot of the cliques of a thread-safe clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the index.</param>
+        /// <returns>Pointer to a clique set, to be freed with <see cref="CliqueSetC_free"/>.</returns>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr CliqueIndexTS_cliques(IntPtr index);
+
+        /// <summary>
+        /// Frees a thread-safe clique index.
+        /// </summary>
+        /// <param name="index">Pointer to the index.</param>
+        [DllImport(DllName, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void CliqueIndexTS_free(IntPtr index);
+
+        /// <summary>
+        /// Status codes returned by fallible native functions.
+        /// </summary>
+        internal enum CliqueStatus
+        {
+            /// <summary>The operation succeeded.</summary>
+            Ok = 0,
+
+            /// <summary>A required pointer argument was null.</summary>
+            NullPointer = 1,
+
+            /// <summary>No observation with the given ID exists in the index.</summary>
+            NotFound = 2,
+
+            /// <summary>The chi-squared threshold was not finite and strictly positive.</summary>
+            InvalidChi2 = 3,
+
+            /// <summary>An observation's covariance terms did not describe a positive semi-definite matrix.</summary>
+            InvalidCovariance = 4,
+
+            /// <summary>An observation's position was not finite.</summary>
+            InvalidPosition = 5,
+
+            /// <summary>An observation with the same ID already exists.</summary>
+            DuplicateId = 6,
+
+            /// <summary>The native library panicked.</summary>
+            Panic = 7,
+
+            /// <summary>A caller-supplied buffer was too small to hold the result.</summary>
+            BufferTooSmall = 8,
+
+            /// <summary>An iterator has no more items.</summary>
+            Exhausted = 9,
+        }
+
         /// <summary>
         /// C-compatible struct for observations.
         /// </summary>
@@ -167,5 +516,133 @@ namespace CliqueFusion.Native
             /// <summary>Length of the clique array.</summary>
             public UIntPtr len;
         }
+
+        /// <summary>
+        /// C-compatible fused estimate representation.
+        /// </summary>
+        [StructLayout(LayoutKind.Sequential)]
+        [SuppressMessage("StyleCop.CSharp.NamingRules", "SA1307", Justification = "Interop naming")]
+        [SuppressMessage("StyleCop.CSharp.NamingRules", "SA1310", Justification = "Interop naming")]
+        internal struct FusedEstimateC
+        {
+            /// <summary>Pointer to UUID array.</summary>
+            public IntPtr uuids;
+
+            /// <summary>Length of the UUID array.</summary>
+            public UIntPtr len;
+
+            /// <summary>Fused X position.</summary>
+            public double x;
+
+            /// <summary>Fused Y position.</summary>
+            public double y;
+
+            /// <summary>Fused X-X covariance.</summary>
+            public double cov_xx;
+
+            /// <summary>Fused X-Y covariance.</summary>
+            public double cov_xy;
+
+            /// <summary>Fused Y-Y covariance.</summary>
+            public double cov_yy;
+
+            /// <summary>Residual chi-squared statistic of the members.</summary>
+            public double chi2_statistic;
+
+            /// <summary>P-value of the residual statistic.</summary>
+            public double p_value;
+
+            /// <summary>Degrees of freedom of the residual statistic.</summary>
+            public uint degrees_of_freedom;
+        }
+
+        /// <summary>
+        /// C-compatible fused estimate set representation.
+        /// </summary>
+        [StructLayout(LayoutKind.Sequential)]
+        [SuppressMessage("StyleCop.CSharp.NamingRules", "SA1307", Justification = "Interop naming")]
+        internal struct FusedSetC
+        {
+            /// <summary>Pointer to fused estimate array.</summary>
+            public IntPtr estimates;
+
+            /// <summary>Length of the fused estimate array.</summary>
+            public UIntPtr len;
+        }
+
+        /// <summary>
+        /// C-compatible compatibility graph edge representation.
+        /// </summary>
+        [StructLayout(LayoutKind.Sequential)]
+        [SuppressMessage("StyleCop.CSharp.NamingRules", "SA1307", Justification = "Interop naming")]
+        internal struct EdgeC
+        {
+            /// <summary>First observation UUID.</summary>
+            public Guid a;
+
+            /// <summary>Second observation UUID.</summary>
+            public Guid b;
+
+            /// <summary>Squared Mahalanobis distance between the observations.</summary>
+            public double mahalanobis_squared;
+        }
+
+        /// <summary>
+        /// C-compatible UUID list representation.
+        /// </summary>
+        [StructLayout(LayoutKind.Sequential)]
+        [SuppressMessage("StyleCop.CSharp.NamingRules", "SA1307", Justification = "Interop naming")]
+        internal struct UuidListC
+        {
+            /// <summary>Pointer to UUID array.</summary>
+            public IntPtr uuids;
+
+            /// <summary>Length of the UUID array.</summary>
+            public UIntPtr len;
+        }
+
+        /// <summary>
+        /// C-compatible clique index statistics representation.
+        /// </summary>
+        [StructLayout(LayoutKind.Sequential)]
+        [SuppressMessage("StyleCop.CSharp.NamingRules", "SA1307", Justification = "Interop naming")]
+        [SuppressMessage("StyleCop.CSharp.NamingRules", "SA1310", Justification = "Interop naming")]
+        internal struct CliqueStatsC
+        {
+            /// <summary>Total number of observations.</summary>
+            public UIntPtr total_observations;
+
+            /// <summary>Number of observations compatible with at least one other.</summary>
+            public UIntPtr connected_observations;
+
+            /// <summary>Number of edges in the compatibility graph.</summary>
+            public UIntPtr compatibility_edges;
+
+            /// <summary>Number of maximal cliques.</summary>
+            public UIntPtr cliques;
+
+            /// <summary>Number of members of the largest clique.</summary>
+            public UIntPtr largest_clique;
+
+            /// <summary>Number of observations whose cliques have been approximated.</summary>
+            public UIntPtr approximate_observations;
+
+            /// <summary>Modification counter.</summary>
+            public ulong generation;
+        }
+
+        /// <summary>
+        /// C-compatible edge list representation.
+        /// </summary>
+        [StructLayout(LayoutKind.Sequential)]
+        [SuppressMessage("StyleCop.CSharp.NamingRules", "SA1307", Justification = "Interop naming")]
+        internal struct EdgeListC
+        {
+            /// <summary>Pointer to edge array.</summary>
+            public IntPtr edges;
+
+            /// <summary>Length of the edge array.</summary>
+            public UIntPtr len;
+        }
     }
 }
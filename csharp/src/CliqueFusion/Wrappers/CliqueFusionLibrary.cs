@@ -0,0 +1,45 @@
+// <copyright file="CliqueFusionLibrary.cs" company="Daniel Eades">
+// Copyright (c) Daniel Eades. All rights reserved.
+// </copyright>
+
+namespace CliqueFusion
+{
+    using System;
+    using System.Runtime.InteropServices;
+    using CliqueFusion.Native;
+
+    /// <summary>
+    /// Provides version information about the loaded native library.
+    /// </summary>
+    public static class CliqueFusionLibrary
+    {
+        /// <summary>
+        /// The version of the C ABI that these bindings were written against.
+        /// </summary>
+        public const uint ExpectedAbiVersion = 1;
+
+        /// <summary>
+        /// Gets the version of the loaded native library.
+        /// </summary>
+        public static string Version => Marshal.PtrToStringUTF8(CliqueIndexNative.CliqueFusion_version()) ?? string.Empty;
+
+        /// <summary>
+        /// Gets the version of the C ABI exposed by the loaded native library.
+        /// </summary>
+        public static uint AbiVersion => CliqueIndexNative.CliqueFusion_abi_version();
+
+        /// <summary>
+        /// Checks that the loaded native library is compatible with these bindings.
+        /// </summary>
+        /// <exception cref="InvalidOperationException">The native library exposes an incompatible ABI.</exception>
+        public static void EnsureCompatible()
+        {
+            var abiVersion = AbiVersion;
+            if (abiVersion != ExpectedAbiVersion)
+            {
+                throw new InvalidOperationException(
+                    $"Native clique-fusion library {Version} has ABI version {abiVersion}, but version {ExpectedAbiVersion} was expected.");
+            }
+        }
+    }
+}
@@ -25,13 +25,10 @@ namespace CliqueFusion
         /// with a specified chi-squared threshold.
         /// </summary>
         /// <param name="chi2Threshold">The chi-squared threshold used for clique compatibility.</param>
+        /// <exception cref="ArgumentOutOfRangeException">The threshold is not finite and positive.</exception>
         public CliqueIndex(double chi2Threshold)
         {
-            this.handle = CliqueIndexNative.CliqueIndex_new(chi2Threshold);
-            if (this.handle == IntPtr.Zero)
-            {
-                throw new InvalidOperationException("Failed to create CliqueIndex");
-            }
+            ThrowOnError(CliqueIndexNative.CliqueIndex_new(chi2Threshold, out this.handle));
         }
 
         /// <summary>
@@ -40,6 +37,8 @@ namespace CliqueFusion
         /// </summary>
         /// <param name="observations">The observations to initialize the index with.</param>
         /// <param name="chi2Threshold">The chi-squared threshold used for clique compatibility.</param>
+        /// <exception cref="ArgumentOutOfRangeException">The threshold is not finite and positive.</exception>
+        /// <exception cref="ArgumentException">An observation is invalid, or two observations share an ID.</exception>
         public CliqueIndex(IEnumerable<Observation> observations, double chi2Threshold)
         {
             if (observations is null)
@@ -50,7 +49,7 @@ namespace CliqueFusion
             var observationList = observations.ToList();
             if (observationList.Count == 0)
             {
-                this.handle = CliqueIndexNative.CliqueIndex_new(chi2Threshold);
+                ThrowOnError(CliqueIndexNative.CliqueIndex_new(chi2Threshold, out this.handle));
             }
             else
             {
@@ -65,18 +64,49 @@ namespace CliqueFusion
                         Marshal.StructureToPtr(nativeObs[i], ptr, false);
                     }
 
-                    this.handle = CliqueIndexNative.CliqueIndex_from_observations(
-                        chi2Threshold, arrayPtr, (UIntPtr)nativeObs.Length);
+                    ThrowOnError(CliqueIndexNative.CliqueIndex_from_observations(
+                        chi2Threshold, arrayPtr, (UIntPtr)nativeObs.Length, out this.handle));
                 }
                 finally
                 {
                     Marshal.FreeHGlobal(arrayPtr);
                 }
             }
+        }
+
+        /// <summary>
+        /// Gets the number of observations in the index which are compatible with at least one other observation.
+        /// </summary>
+        public int Count
+        {
+            get
+            {
+                this.ThrowIfDisposed();
+                return (int)CliqueIndexNative.CliqueIndex_len(this.handle);
+            }
+        }
 
-            if (this.handle == IntPtr.Zero)
+        /// <summary>
+        /// Gets the total number of observations in the index, including those which are not compatible with any other observation.
+        /// </summary>
+        public int TotalObservations
+        {
+            get
             {
-                throw new InvalidOperationException("Failed to create CliqueIndex from observations");
+                this.ThrowIfDisposed();
+                return (int)CliqueIndexNative.CliqueIndex_total_observations(this.handle);
+            }
+        }
+
+        /// <summary>
+        /// Gets the chi-squared threshold used to test compatibility.
+        /// </summary>
+        public double Chi2Threshold
+        {
+            get
+            {
+                this.ThrowIfDisposed();
+                return CliqueIndexNative.CliqueIndex_chi2(this.handle);
             }
         }
 
@@ -84,23 +114,172 @@ namespace CliqueFusion
         /// Inserts a new observation into the index.
         /// </summary>
         /// <param name="observation">The observation to insert.</param>
+        /// <exception cref="ArgumentException">The observation is invalid, or an observation with the same ID is already in the index.</exception>
         public void Insert(Observation observation)
         {
             this.ThrowIfDisposed();
+            ThrowOnError(this.WithNativeObservation(observation, CliqueIndexNative.CliqueIndex_insert));
+        }
+
+        /// <summary>
+        /// Replaces an observation in the index with a refined version of it (for example, with an updated position).
+        /// </summary>
+        /// <param name="observation">The refined observation, with the same ID as the observation it replaces.</param>
+        /// <returns><c>true</c> if the observation was replaced; <c>false</c> if no observation with the same ID was in the index.</returns>
+        /// <exception cref="ArgumentException">The observation is invalid.</exception>
+        public bool Update(Observation observation)
+        {
+            this.ThrowIfDisposed();
+
+            var status = this.WithNativeObservation(observation, CliqueIndexNative.CliqueIndex_update);
+            return status switch
+            {
+                CliqueIndexNative.CliqueStatus.Ok => true,
+                CliqueIndexNative.CliqueStatus.NotFound => false,
+                _ => throw ToException(status),
+            };
+        }
+
+        /// <summary>
+        /// Inserts an observation into the index, replacing any observation with the same ID.
+        /// </summary>
+        /// <param name="observation">The observation to insert.</param>
+        /// <exception cref="ArgumentException">The observation is invalid.</exception>
+        public void Upsert(Observation observation)
+        {
+            this.ThrowIfDisposed();
+            ThrowOnError(this.WithNativeObservation(observation, CliqueIndexNative.CliqueIndex_upsert));
+        }
+
+        /// <summary>
+        /// Finds the observations which an observation would be compatible with, without inserting it.
+        /// </summary>
+        /// <remarks>
+        /// This previews the effect of inserting the observation. An observation in the index with the same ID is
+        /// excluded. The index is not modified.
+        /// </remarks>
+        /// <param name="observation">The candidate observation.</param>
+        /// <returns>The IDs of the compatible observations, nearest first.</returns>
+        /// <exception cref="ArgumentException">The observation is invalid.</exception>
+        public IReadOnlyList<Guid> Probe(Observation observation)
+        {
+            this.ThrowIfDisposed();
+
+            var listPtr = IntPtr.Zero;
+            ThrowOnError(this.WithNativeObservation(
+                observation,
+                (index, obs) => CliqueIndexNative.CliqueIndex_probe(index, obs, out listPtr)));
 
-            var nativeObs = ToNative(observation);
-            var obsPtr = Marshal.AllocHGlobal(ObservationSize);
             try
             {
-                Marshal.StructureToPtr(nativeObs, obsPtr, false);
-                CliqueIndexNative.CliqueIndex_insert(this.handle, obsPtr);
+                var list = Marshal.PtrToStructure<CliqueIndexNative.UuidListC>(listPtr);
+                var ids = new List<Guid>((int)list.len);
+
+                for (int i = 0; i < (int)list.len; i++)
+                {
+                    var uuidPtr = IntPtr.Add(list.uuids, i * 16);
+                    ids.Add(Marshal.PtrToStructure<Guid>(uuidPtr));
+                }
+
+                return ids;
             }
             finally
             {
-                Marshal.FreeHGlobal(obsPtr);
+                CliqueIndexNative.UuidListC_free(listPtr);
             }
         }
 
+        /// <summary>
+        /// Checks whether an observation is in the index.
+        /// </summary>
+        /// <param name="id">The ID of the observation.</param>
+        /// <returns><c>true</c> if the observation is in the index.</returns>
+        public bool Contains(Guid id)
+        {
+            this.ThrowIfDisposed();
+            return CliqueIndexNative.CliqueIndex_contains(this.handle, id.ToByteArray());
+        }
+
+        /// <summary>
+        /// Looks up an observation in the index.
+        /// </summary>
+        /// <param name="id">The ID of the observation.</param>
+        /// <returns>The observation, or <c>null</c> if it is not in the index.</returns>
+        public Observation? Get(Guid id)
+        {
+            this.ThrowIfDisposed();
+
+            var status = CliqueIndexNative.CliqueIndex_get(this.handle, id.ToByteArray(), out var native);
+            if (status == CliqueIndexNative.CliqueStatus.NotFound)
+            {
+                return null;
+            }
+
+            ThrowOnError(status);
+            var context = new Guid(native.context);
+            return new Observation(
+                new Guid(native.id),
+                native.x,
+                native.y,
+                native.cov_xx,
+                native.cov_xy,
+                native.cov_yy,
+                context == Guid.Empty ? null : context);
+        }
+
+        /// <summary>
+        /// Changes the chi-squared threshold used to test compatibility, recomputing all cliques.
+        /// </summary>
+        /// <remarks>
+        /// This is comparable in cost to constructing a new index from the same observations.
+        /// </remarks>
+        /// <param name="chi2Threshold">The new chi-squared threshold.</param>
+        /// <exception cref="ArgumentOutOfRangeException">The threshold is not finite and strictly positive.</exception>
+        public void SetChi2Threshold(double chi2Threshold)
+        {
+            this.ThrowIfDisposed();
+            ThrowOnError(CliqueIndexNative.CliqueIndex_set_chi2(this.handle, chi2Threshold));
+        }
+
+        /// <summary>
+        /// Removes an observation from the index.
+        /// </summary>
+        /// <param name="id">The ID of the observation to remove.</param>
+        /// <returns><c>true</c> if the observation was removed; <c>false</c> if it was not in the index.</returns>
+        public bool Remove(Guid id)
+        {
+            this.ThrowIfDisposed();
+
+            var status = CliqueIndexNative.CliqueIndex_remove(this.handle, id.ToByteArray());
+            return status switch
+            {
+                CliqueIndexNative.CliqueStatus.Ok => true,
+                CliqueIndexNative.CliqueStatus.NotFound => false,
+                _ => throw ToException(status),
+            };
+        }
+
+        /// <summary>
+        /// Removes every observation in a context from the index, for example when an upstream frame is retracted.
+        /// </summary>
+        /// <remarks>
+        /// The observations are removed together, and the affected cliques are recomputed once.
+        /// </remarks>
+        /// <param name="context">The context of the observations to remove.</param>
+        /// <returns><c>true</c> if any observations were removed; <c>false</c> if none were in the context.</returns>
+        public bool RemoveContext(Guid context)
+        {
+            this.ThrowIfDisposed();
+
+            var status = CliqueIndexNative.CliqueIndex_remove_context(this.handle, context.ToByteArray());
+            return status switch
+            {
+                CliqueIndexNative.CliqueStatus.Ok => true,
+                CliqueIndexNative.CliqueStatus.NotFound => false,
+                _ => throw ToException(status),
+            };
+        }
+
         /// <summary>
         /// Retrieves the current set of maximal cliques.
         /// </summary>
@@ -109,7 +288,219 @@ namespace CliqueFusion
         {
             this.ThrowIfDisposed();
 
-            var cliquesPtr = CliqueIndexNative.CliqueIndex_cliques(this.handle);
+            return ReadCliqueSet(CliqueIndexNative.CliqueIndex_cliques(this.handle));
+        }
+
+        /// <summary>
+        /// Retrieves the maximal cliques with at least one member within a bounding box, such as a map viewport.
+        /// </summary>
+        /// <remarks>
+        /// Each clique is returned in full, including any members outside the bounds.
+        /// </remarks>
+        /// <param name="minX">The minimum x ordinate of the bounding box (inclusive).</param>
+        /// <param name="minY">The minimum y ordinate of the bounding box (inclusive).</param>
+        /// <param name="maxX">The maximum x ordinate of the bounding box (inclusive).</param>
+        /// <param name="maxY">The maximum y ordinate of the bounding box (inclusive).</param>
+        /// <returns>A list of cliques containing observation IDs.</returns>
+        public IReadOnlyList<Clique> GetCliquesInRegion(double minX, double minY, double maxX, double maxY)
+        {
+            this.ThrowIfDisposed();
+
+            return ReadCliqueSet(CliqueIndexNative.CliqueIndex_cliques_in_region(this.handle, minX, minY, maxX, maxY));
+        }
+
+        /// <summary>
+        /// Enumerates the current set of maximal cliques one at a time, without copying the entire set.
+        /// </summary>
+        /// <remarks>
+        /// The index must not be modified while the enumeration is in progress.
+        /// </remarks>
+        /// <returns>A lazily evaluated sequence of cliques.</returns>
+        public IEnumerable<Clique> EnumerateCliques()
+        {
+            this.ThrowIfDisposed();
+
+            var iter = CliqueIndexNative.CliqueIndex_cliques_iter_new(this.handle);
+            try
+            {
+                var buffer = new Guid[16];
+                while (true)
+                {
+                    this.ThrowIfDisposed();
+                    var status = CliqueIndexNative.CliqueIndex_cliques_iter_next(iter, buffer, (UIntPtr)buffer.Length, out var len);
+                    if (status == CliqueIndexNative.CliqueStatus.Exhausted)
+                    {
+                        yield break;
+                    }
+
+                    if (status == CliqueIndexNative.CliqueStatus.BufferTooSmall)
+                    {
+                        buffer = new Guid[(int)len];
+                        continue;
+                    }
+
+                    ThrowOnError(status);
+                    yield return new Clique(buffer.Take((int)len).ToArray());
+                }
+            }
+            finally
+            {
+                CliqueIndexNative.CliqueIndex_cliques_iter_free(iter);
+            }
+        }
+
+        /// <summary>
+        /// Retrieves the fused estimate of each of the current cliques.
+        /// </summary>
+        /// <returns>A list of fused estimates, in the same order as <see cref="GetCliques"/>.</returns>
+        public IReadOnlyList<FusedEstimate> GetFusedEstimates()
+        {
+            this.ThrowIfDisposed();
+
+            var fusedPtr = CliqueIndexNative.CliqueIndex_fused_estimates(this.handle);
+            if (fusedPtr == IntPtr.Zero)
+            {
+                return Array.Empty<FusedEstimate>();
+            }
+
+            try
+            {
+                var fusedSet = Marshal.PtrToStructure<CliqueIndexNative.FusedSetC>(fusedPtr);
+                var estimates = new List<FusedEstimate>();
+
+                for (int i = 0; i < (int)fusedSet.len; i++)
+                {
+                    var estimatePtr = IntPtr.Add(fusedSet.estimates, i * Marshal.SizeOf<CliqueIndexNative.FusedEstimateC>());
+                    var estimate = Marshal.PtrToStructure<CliqueIndexNative.FusedEstimateC>(estimatePtr);
+
+                    var ids = new List<Guid>();
+                    for (int j = 0; j < (int)estimate.len; j++)
+                    {
+                        var uuidPtr = IntPtr.Add(estimate.uuids, j * 16);
+                        ids.Add(Marshal.PtrToStructure<Guid>(uuidPtr));
+                    }
+
+                    estimates.Add(new FusedEstimate(
+                        ids,
+                        estimate.x,
+                        estimate.y,
+                        estimate.cov_xx,
+                        estimate.cov_xy,
+                        estimate.cov_yy,
+                        estimate.chi2_statistic,
+                        estimate.degrees_of_freedom,
+                        estimate.p_value));
+                }
+
+                return estimates;
+            }
+            finally
+            {
+                CliqueIndexNative.FusedSetC_free(fusedPtr);
+            }
+        }
+
+        /// <summary>
+        /// Retrieves the edges of the compatibility graph.
+        /// </summary>
+        /// <returns>A list of edges between mutually compatible observations.</returns>
+        public IReadOnlyList<Edge> GetEdges()
+        {
+            this.ThrowIfDisposed();
+
+            var edgesPtr = CliqueIndexNative.CliqueIndex_edges(this.handle);
+            if (edgesPtr == IntPtr.Zero)
+            {
+                return Array.Empty<Edge>();
+            }
+
+            try
+            {
+                var edgeList = Marshal.PtrToStructure<CliqueIndexNative.EdgeListC>(edgesPtr);
+                var edges = new List<Edge>();
+
+                for (int i = 0; i < (int)edgeList.len; i++)
+                {
+                    var edgePtr = IntPtr.Add(edgeList.edges, i * Marshal.SizeOf<CliqueIndexNative.EdgeC>());
+                    var edge = Marshal.PtrToStructure<CliqueIndexNative.EdgeC>(edgePtr);
+                    edges.Add(new Edge(edge.a, edge.b, edge.mahalanobis_squared));
+                }
+
+                return edges;
+            }
+            finally
+            {
+                CliqueIndexNative.EdgeListC_free(edgesPtr);
+            }
+        }
+
+        /// <summary>
+        /// Retrieves inexpensive statistics of the index, without copying any cliques.
+        /// </summary>
+        /// <returns>The current statistics.</returns>
+        public CliqueStats GetStats()
+        {
+            this.ThrowIfDisposed();
+
+            ThrowOnError(CliqueIndexNative.CliqueIndex_stats(this.handle, out var stats));
+            return new CliqueStats(
+                (int)stats.total_observations,
+                (int)stats.connected_observations,
+                (int)stats.compatibility_edges,
+                (int)stats.cliques,
+                (int)stats.largest_clique,
+                (int)stats.approximate_observations,
+                stats.generation);
+        }
+
+        /// <summary>
+        /// Exports the observations, their confidence ellipses, and the cliques as a GeoJSON FeatureCollection.
+        /// </summary>
+        /// <remarks>
+        /// Observation IDs are formatted from the bytes passed to the native library (see <see cref="Guid.ToByteArray()"/>),
+        /// so may not match <see cref="Guid.ToString()"/>.
+        /// </remarks>
+        /// <returns>The GeoJSON document.</returns>
+        public string ToGeoJson()
+        {
+            this.ThrowIfDisposed();
+
+            var ptr = CliqueIndexNative.CliqueIndex_to_geojson(this.handle);
+            if (ptr == IntPtr.Zero)
+            {
+                throw new InvalidOperationException("Failed to export the index as GeoJSON.");
+            }
+
+            try
+            {
+                return Marshal.PtrToStringUTF8(ptr)!;
+            }
+            finally
+            {
+                CliqueIndexNative.CliqueFusion_string_free(ptr);
+            }
+        }
+
+        /// <summary>
+        /// Releases all native resources associated with this instance.
+        /// </summary>
+        public void Dispose()
+        {
+            if (!this.disposed && this.handle != IntPtr.Zero)
+            {
+                CliqueIndexNative.CliqueIndex_free(this.handle);
+                this.handle = IntPtr.Zero;
+                this.disposed = true;
+            }
+        }
+
+        /// <summary>
+        /// Copies a native clique set into managed memory, and frees it.
+        /// </summary>
+        /// <param name="cliquesPtr">The clique set, which may be null.</param>
+        /// <returns>The cliques.</returns>
+        internal static IReadOnlyList<Clique> ReadCliqueSet(IntPtr cliquesPtr)
+        {
             if (cliquesPtr == IntPtr.Zero)
             {
                 return Array.Empty<Clique>();
@@ -144,20 +535,60 @@ namespace CliqueFusion
         }
 
         /// <summary>
-        /// Releases all native resources associated with this instance.
+        /// Converts an observation into its native representation.
         /// </summary>
-        public void Dispose()
+        /// <param name="o">The observation.</param>
+        /// <returns>The native observation.</returns>
+        internal static CliqueIndexNative.ObservationC ToNative(Observation o) =>
+            new(o.Id, o.X, o.Y, o.CovarianceXX, o.CovarianceXY, o.CovarianceYY, o.Context);
+
+        /// <summary>
+        /// Throws an exception describing the most recent native error if a call failed.
+        /// </summary>
+        /// <param name="status">The status returned by the native call.</param>
+        internal static void ThrowOnError(CliqueIndexNative.CliqueStatus status)
         {
-            if (!this.disposed && this.handle != IntPtr.Zero)
+            if (status != CliqueIndexNative.CliqueStatus.Ok)
             {
-                CliqueIndexNative.CliqueIndex_free(this.handle);
-                this.handle = IntPtr.Zero;
-                this.disposed = true;
+                throw ToException(status);
             }
         }
 
-        private static CliqueIndexNative.ObservationC ToNative(Observation o) =>
-            new(o.Id, o.X, o.Y, o.CovarianceXX, o.CovarianceXY, o.CovarianceYY, o.Context);
+        /// <summary>
+        /// Creates an exception describing the most recent native error.
+        /// </summary>
+        /// <param name="status">The status returned by the native call.</param>
+        /// <returns>The exception.</returns>
+        internal static Exception ToException(CliqueIndexNative.CliqueStatus status)
+        {
+            var message = LastErrorMessage() ?? $"Native call failed: {status}";
+            return status switch
+            {
+                CliqueIndexNative.CliqueStatus.InvalidChi2 => new ArgumentOutOfRangeException("chi2Threshold", message),
+                CliqueIndexNative.CliqueStatus.InvalidCovariance or
+                CliqueIndexNative.CliqueStatus.InvalidPosition or
+                CliqueIndexNative.CliqueStatus.DuplicateId => new ArgumentException(message),
+                _ => new InvalidOperationException(message),
+            };
+        }
+
+        private static string? LastErrorMessage()
+        {
+            var ptr = CliqueIndexNative.CliqueFusion_last_error_message();
+            if (ptr == IntPtr.Zero)
+            {
+                return null;
+            }
+
+            try
+            {
+                return Marshal.PtrToStringUTF8(ptr);
+            }
+            finally
+            {
+                CliqueIndexNative.CliqueFusion_string_free(ptr);
+            }
+        }
 
         private void ThrowIfDisposed()
         {
@@ -166,5 +597,21 @@ namespace CliqueFusion
                 throw new ObjectDisposedException(nameof(CliqueIndex));
             }
         }
+
+        private CliqueIndexNative.CliqueStatus WithNativeObservation(
+            Observation observation,
+            Func<IntPtr, IntPtr, CliqueIndexNative.CliqueStatus> call)
+        {
+            var obsPtr = Marshal.AllocHGlobal(ObservationSize);
+            try
+            {
+                Marshal.StructureToPtr(ToNative(observation), obsPtr, false);
+                return call(this.handle, obsPtr);
+            }
+            finally
+            {
+                Marshal.FreeHGlobal(obsPtr);
+            }
+        }
     }
 }
@@ -0,0 +1,25 @@
+// <copyright file="CliqueStats.cs" company="Daniel Eades">
+// Copyright (c) Daniel Eades. All rights reserved.
+// </copyright>
+
+namespace CliqueFusion
+{
+    /// <summary>
+    /// Inexpensive statistics of a clique index, for monitoring.
+    /// </summary>
+    /// <param name="TotalObservations">The total number of observations in the index.</param>
+    /// <param name="ConnectedObservations">The number of observations which are compatible with at least one other observation.</param>
+    /// <param name="CompatibilityEdges">The number of edges in the compatibility graph.</param>
+    /// <param name="Cliques">The number of maximal cliques.</param>
+    /// <param name="LargestClique">The number of members of the largest clique, or zero if there are no cliques.</param>
+    /// <param name="ApproximateObservations">The number of observations whose cliques have been approximated.</param>
+    /// <param name="Generation">A counter which is incremented each time the index is modified.</param>
+    public record CliqueStats(
+        int TotalObservations,
+        int ConnectedObservations,
+        int CompatibilityEdges,
+        int Cliques,
+        int LargestClique,
+        int ApproximateObservations,
+        ulong Generation);
+}
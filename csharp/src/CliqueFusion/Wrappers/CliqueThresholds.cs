@@ -4,6 +4,7 @@
 
 namespace CliqueFusion
 {
+    using System;
     using CliqueFusion.Native;
 
     /// <summary>
@@ -26,5 +27,26 @@ namespace CliqueFusion
         /// Gets the chi-squared threshold corresponding to 99% confidence.
         /// </summary>
         public static double Confidence99 => CliqueIndexNative.CliqueIndex_chi2_confidence_99();
+
+        /// <summary>
+        /// Computes the chi-squared threshold corresponding to an arbitrary confidence level.
+        /// </summary>
+        /// <param name="confidence">The confidence level, as a probability strictly between 0 and 1 (e.g. 0.95 for 95%).</param>
+        /// <param name="degreesOfFreedom">The number of degrees of freedom; 2 for 2D observations.</param>
+        /// <returns>The chi-squared threshold.</returns>
+        /// <exception cref="ArgumentOutOfRangeException">The confidence is not strictly between 0 and 1, or there are no degrees of freedom.</exception>
+        public static double ForConfidence(double confidence, uint degreesOfFreedom = 2)
+        {
+            var threshold = CliqueIndexNative.CliqueFusion_chi2_threshold(confidence, degreesOfFreedom);
+            if (double.IsNaN(threshold))
+            {
+                throw new ArgumentOutOfRangeException(
+                    nameof(confidence),
+                    confidence,
+                    "Confidence must be strictly between 0 and 1, with at least one degree of freedom.");
+            }
+
+            return threshold;
+        }
     }
 }
@@ -0,0 +1,16 @@
+// <copyright file="Edge.cs" company="Daniel Eades">
+// Copyright (c) Daniel Eades. All rights reserved.
+// </copyright>
+
+namespace CliqueFusion
+{
+    using System;
+
+    /// <summary>
+    /// Represents an edge of the compatibility graph — a pair of mutually compatible observations.
+    /// </summary>
+    /// <param name="A">The ID of the first observation.</param>
+    /// <param name="B">The ID of the second observation.</param>
+    /// <param name="MahalanobisSquared">The squared Mahalanobis distance between the observations, under the sum of their covariances.</param>
+    public record Edge(Guid A, Guid B, double MahalanobisSquared);
+}
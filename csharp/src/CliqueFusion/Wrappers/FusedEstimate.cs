@@ -0,0 +1,30 @@
+// <copyright file="FusedEstimate.cs" company="Daniel Eades">
+// Copyright (c) Daniel Eades. All rights reserved.
+// </copyright>
+
+namespace CliqueFusion
+{
+    using System;
+    using System.Collections.Generic;
+
+    /// <summary>
+    /// Represents the fused estimate of a clique — the information-weighted combination of the
+    /// positions and uncertainties of its member observations.
+    /// </summary>
+    /// <remarks>
+    /// <see cref="Chi2Statistic"/> is the residual chi-squared statistic of the members about the
+    /// fused position, and <see cref="PValue"/> the probability of a statistic at least this large
+    /// if the members are consistent. Cliques with a small p-value are pairwise compatible, but
+    /// jointly inconsistent.
+    /// </remarks>
+    public record FusedEstimate(
+        IReadOnlyList<Guid> ObservationIds,
+        double X,
+        double Y,
+        double CovarianceXX,
+        double CovarianceXY,
+        double CovarianceYY,
+        double Chi2Statistic,
+        uint DegreesOfFreedom,
+        double PValue);
+}
@@ -0,0 +1,139 @@
+// <copyright file="SharedCliqueIndex.cs" company="Daniel Eades">
+// Copyright (c) Daniel Eades. All rights reserved.
+// </copyright>
+
+namespace CliqueFusion
+{
+    using System;
+    using System.Collections.Generic;
+    using System.Runtime.InteropServices;
+    using System.Threading;
+    using CliqueFusion.Native;
+
+    /// <summary>
+    /// A clique index which may be shared between threads.
+    /// </summary>
+    /// <remarks>
+    /// Unlike <see cref="CliqueIndex"/>, the native index is guarded by a read-write lock, so any
+    /// member other than <see cref="Dispose"/> may be called concurrently from multiple threads.
+    /// Queries run concurrently with each other, while modifications take exclusive access.
+    /// </remarks>
+    public sealed class SharedCliqueIndex : IDisposable
+    {
+        private static readonly int ObservationSize = Marshal.SizeOf<CliqueIndexNative.ObservationC>();
+        private IntPtr handle;
+
+        /// <summary>
+        /// Initializes a new instance of the <see cref="SharedCliqueIndex"/> class
+        /// with a specified chi-squared threshold.
+        /// </summary>
+        /// <param name="chi2Threshold">The chi-squared threshold used for clique compatibility.</param>
+        /// <exception cref="ArgumentOutOfRangeException">The threshold is not finite and positive.</exception>
+        public SharedCliqueIndex(double chi2Threshold)
+        {
+            CliqueIndex.ThrowOnError(CliqueIndexNative.CliqueIndexTS_new(chi2Threshold, out this.handle));
+        }
+
+        /// <summary>
+        /// Gets the number of observations in the index which are compatible with at least one other observation.
+        /// </summary>
+        public int Count => (int)CliqueIndexNative.CliqueIndexTS_len(this.Handle);
+
+        /// <summary>
+        /// Gets the chi-squared threshold used to test compatibility.
+        /// </summary>
+        public double Chi2Threshold => CliqueIndexNative.CliqueIndexTS_chi2(this.Handle);
+
+        private IntPtr Handle
+        {
+            get
+            {
+                var handle = this.handle;
+                if (handle == IntPtr.Zero)
+                {
+                    throw new ObjectDisposedException(nameof(SharedCliqueIndex));
+                }
+
+                return handle;
+            }
+        }
+
+        /// <summary>
+        /// Inserts a new observation into the index.
+        /// </summary>
+        /// <param name="observation">The observation to insert.</param>
+        /// <exception cref="ArgumentException">The observation is invalid, or an observation with the same ID is already in the index.</exception>
+        public void Insert(Observation observation)
+        {
+            var handle = this.Handle;
+
+            var obsPtr = Marshal.AllocHGlobal(ObservationSize);
+            try
+            {
+                Marshal.StructureToPtr(CliqueIndex.ToNative(observation), obsPtr, false);
+                CliqueIndex.ThrowOnError(CliqueIndexNative.CliqueIndexTS_insert(handle, obsPtr));
+            }
+            finally
+            {
+                Marshal.FreeHGlobal(obsPtr);
+            }
+        }
+
+        /// <summary>
+        /// Checks whether an observation is in the index.
+        /// </summary>
+        /// <param name="id">The ID of the observation.</param>
+        /// <returns><c>true</c> if the observation is in the index.</returns>
+        public bool Contains(Guid id) => CliqueIndexNative.CliqueIndexTS_contains(this.Handle, id.ToByteArray());
+
+        /// <summary>
+        /// Removes an observation from the index.
+        /// </summary>
+        /// <param name="id">The ID of the observation to remove.</param>
+        /// <returns><c>true</c> if the observation was removed; <c>false</c> if it was not in the index.</returns>
+        public bool Remove(Guid id)
+        {
+            var status = CliqueIndexNative.CliqueIndexTS_remove(this.Handle, id.ToByteArray());
+            return status switch
+            {
+                CliqueIndexNative.CliqueStatus.Ok => true,
+                CliqueIndexNative.CliqueStatus.NotFound => false,
+                _ => throw CliqueIndex.ToException(status),
+            };
+        }
+
+        /// <summary>
+        /// Changes the chi-squared threshold used to test compatibility, recomputing all cliques.
+        /// </summary>
+        /// <param name="chi2Threshold">The new chi-squared threshold.</param>
+        /// <exception cref="ArgumentOutOfRangeException">The threshold is not finite and strictly positive.</exception>
+        public void SetChi2Threshold(double chi2Threshold)
+        {
+            CliqueIndex.ThrowOnError(CliqueIndexNative.CliqueIndexTS_set_chi2(this.Handle, chi2Threshold));
+        }
+
+        /// <summary>
+        /// Retrieves a snapshot of the current set of maximal cliques.
+        /// </summary>
+        /// <returns>A list of cliques containing observation IDs.</returns>
+        public IReadOnlyList<Clique> GetCliques()
+        {
+            return CliqueIndex.ReadCliqueSet(CliqueIndexNative.CliqueIndexTS_cliques(this.Handle));
+        }
+
+        /// <summary>
+        /// Releases all native resources associated with this instance.
+        /// </summary>
+        /// <remarks>
+        /// This must not be called while other threads are still using the index.
+        /// </remarks>
+        public void Dispose()
+        {
+            var handle = Interlocked.Exchange(ref this.handle, IntPtr.Zero);
+            if (handle != IntPtr.Zero)
+            {
+                CliqueIndexNative.CliqueIndexTS_free(handle);
+            }
+        }
+    }
+}
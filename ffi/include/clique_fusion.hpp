@@ -0,0 +1,443 @@
+// Header-only C++ wrapper over the clique-fusion C API.
+//
+// Requires C++20 (for std::span). Link against the `clique_fusion_ffi` library.
+//
+// The raw C declarations live in `clique::ffi`. The types in `clique` own their native handles,
+// and translate failing status codes into exceptions derived from `clique::Error`.
+
+#pragma once
+
+#include <array>
+#include <cstddef>
+#include <cstdint>
+#include <iterator>
+#include <optional>
+#include <span>
+#include <stdexcept>
+#include <string>
+#include <utility>
+#include <vector>
+
+namespace clique {
+
+/// A 16-byte UUID, in RFC 4122 (big-endian) byte order.
+using Uuid = std::array<std::uint8_t, 16>;
+
+namespace ffi {
+
+extern "C" {
+
+/// Status codes returned by fallible C functions. Mirrors `CliqueStatus`.
+enum class Status : int {
+    Ok = 0,
+    NullPointer = 1,
+    NotFound = 2,
+    InvalidChi2 = 3,
+    InvalidCovariance = 4,
+    InvalidPosition = 5,
+    DuplicateId = 6,
+    Panic = 7,
+    BufferTooSmall = 8,
+    Exhausted = 9,
+};
+
+struct CliqueIndex;
+
+struct ObservationC {
+    Uuid id;
+    double x;
+    double y;
+    double cov_xx;
+    double cov_xy;
+    double cov_yy;
+    Uuid context;
+};
+
+struct CliqueC {
+    const Uuid* uuids;
+    std::size_t len;
+};
+
+struct CliqueSetC {
+    const CliqueC* cliques;
+    std::size_t len;
+};
+
+struct FlatCliquesC {
+    const Uuid* uuids;
+    std::size_t uuids_len;
+    const std::size_t* offsets;
+    std::size_t len;
+};
+
+struct UuidListC {
+    const Uuid* uuids;
+    std::size_t len;
+};
+
+struct CliqueStatsC {
+    std::size_t total_observations;
+    std::size_t connected_observations;
+    std::size_t compatibility_edges;
+    std::size_t cliques;
+    std::size_t largest_clique;
+    std::size_t approximate_observations;
+    std::uint64_t generation;
+};
+
+const char* CliqueFusion_version();
+std::uint32_t CliqueFusion_abi_version();
+double CliqueFusion_chi2_threshold(double confidence, std::uint32_t dof);
+const char* CliqueFusion_last_error_message();
+void CliqueFusion_string_free(const char* ptr);
+
+Status CliqueIndex_new(double chi2, CliqueIndex** out_index);
+Status CliqueIndex_from_observations(
+    double chi2, const ObservationC* observations, std::size_t len, CliqueIndex** out_index);
+Status CliqueIndex_insert(CliqueIndex* index, const ObservationC* observation);
+Status CliqueIndex_update(CliqueIndex* index, const ObservationC* observation);
+Status CliqueIndex_upsert(CliqueIndex* index, const ObservationC* observation);
+Status CliqueIndex_remove(CliqueIndex* index, const Uuid* id);
+Status CliqueIndex_remove_context(CliqueIndex* index, const Uuid* context);
+Status CliqueIndex_set_chi2(CliqueIndex* index, double chi2);
+double CliqueIndex_chi2(const CliqueIndex* index);
+Status CliqueIndex_get(const CliqueIndex* index, const Uuid* id, ObservationC* out_observation);
+std::size_t CliqueIndex_len(const CliqueIndex* index);
+std::size_t CliqueIndex_total_observations(const CliqueIndex* index);
+bool CliqueIndex_contains(const CliqueIndex* index, const Uuid* id);
+Status CliqueIndex_stats(const CliqueIndex* index, CliqueStatsC* out_stats);
+Status CliqueIndex_probe(
+    const CliqueIndex* index, const ObservationC* observation, UuidListC** out_compatible_ids);
+void UuidListC_free(UuidListC* list);
+CliqueSetC* CliqueIndex_cliques(const CliqueIndex* index);
+Status CliqueIndex_cliques_flat(const CliqueIndex* index, FlatCliquesC* out_cliques);
+CliqueSetC* CliqueIndex_cliques_in_region(
+    const CliqueIndex* index, double min_x, double min_y, double max_x, double max_y);
+char* CliqueIndex_to_geojson(const CliqueIndex* index);
+void CliqueSetC_free(CliqueSetC* set);
+void CliqueIndex_free(CliqueIndex* index);
+
+}  // extern "C"
+
+}  // namespace ffi
+
+/// The version of the C ABI this header was written against.
+inline constexpr std::uint32_t expected_abi_version = 1;
+
+/// An observation, as exchanged with the library.
+///
+/// A nil (all-zero) `context` means the observation has no context.
+using Observation = ffi::ObservationC;
+
+/// The base class of all exceptions thrown by this wrapper.
+class Error : public std::runtime_error {
+public:
+    Error(ffi::Status status, const std::string& message)
+        : std::runtime_error(message), status_(status) {}
+
+    /// The status code returned by the library.
+    [[nodiscard]] ffi::Status status() const noexcept { return status_; }
+
+private:
+    ffi::Status status_;
+};
+
+/// A chi-squared threshold, covariance matrix or position was invalid.
+class InvalidArgument : public Error {
+    using Error::Error;
+};
+
+/// An observation with the same ID is already in the index.
+class DuplicateId : public Error {
+    using Error::Error;
+};
+
+/// No observation with the given ID is in the index.
+class NotFound : public Error {
+    using Error::Error;
+};
+
+namespace detail {
+
+inline std::string last_error_message() {
+    const char* message = ffi::CliqueFusion_last_error_message();
+    if (message == nullptr) {
+        return "unknown error";
+    }
+    std::string result(message);
+    ffi::CliqueFusion_string_free(message);
+    return result;
+}
+
+/// Throw the exception corresponding to a failing status code.
+inline void check(ffi::Status status) {
+    switch (status) {
+        case ffi::Status::Ok:
+            return;
+        case ffi::Status::InvalidChi2:
+        case ffi::Status::InvalidCovariance:
+        case ffi::Status::InvalidPosition:
+            throw InvalidArgument(status, last_error_message());
+        case ffi::Status::DuplicateId:
+            throw DuplicateId(status, last_error_message());
+        case ffi::Status::NotFound:
+            throw NotFound(status, last_error_message());
+        default:
+            throw Error(status, last_error_message());
+    }
+}
+
+}  // namespace detail
+
+/// Returns the version of the native library, e.g. `"0.1.0"`.
+inline std::string version() { return ffi::CliqueFusion_version(); }
+
+/// Returns the version of the C ABI exposed by the native library.
+inline std::uint32_t abi_version() { return ffi::CliqueFusion_abi_version(); }
+
+/// Compute the chi-squared threshold for an arbitrary confidence level in `(0, 1)`.
+///
+/// Throws `InvalidArgument` if the confidence or degrees of freedom are out of range.
+inline double chi2_threshold(double confidence, std::uint32_t degrees_of_freedom = 2) {
+    double threshold = ffi::CliqueFusion_chi2_threshold(confidence, degrees_of_freedom);
+    if (threshold != threshold) {
+        throw InvalidArgument(
+            ffi::Status::InvalidChi2, "confidence must be in (0, 1), with at least one degree of freedom");
+    }
+    return threshold;
+}
+
+/// An owned snapshot of the maximal cliques of an `Index`.
+///
+/// Each clique is exposed as a `std::span<const Uuid>` of its members.
+class CliqueSet {
+public:
+    /// Iterates over the cliques in the set.
+    class iterator {
+    public:
+        using iterator_category = std::forward_iterator_tag;
+        using value_type = std::span<const Uuid>;
+        using difference_type = std::ptrdiff_t;
+        using pointer = void;
+        using reference = value_type;
+
+        iterator() = default;
+        explicit iterator(const ffi::CliqueC* clique) : clique_(clique) {}
+
+        value_type operator*() const { return {clique_->uuids, clique_->len}; }
+
+        iterator& operator++() {
+            ++clique_;
+            return *this;
+        }
+
+        iterator operator++(int) {
+            iterator previous = *this;
+            ++clique_;
+            return previous;
+        }
+
+        bool operator==(const iterator&) const = default;
+
+    private:
+        const ffi::CliqueC* clique_ = nullptr;
+    };
+
+    explicit CliqueSet(ffi::CliqueSetC* set) : set_(set) {
+        if (set_ == nullptr) {
+            throw Error(ffi::Status::Panic, "failed to retrieve cliques");
+        }
+    }
+
+    CliqueSet(const CliqueSet&) = delete;
+    CliqueSet& operator=(const CliqueSet&) = delete;
+    CliqueSet(CliqueSet&& other) noexcept : set_(std::exchange(other.set_, nullptr)) {}
+    CliqueSet& operator=(CliqueSet&& other) noexcept {
+        if (this != &other) {
+            ffi::CliqueSetC_free(set_);
+            set_ = std::exchange(other.set_, nullptr);
+        }
+        return *this;
+    }
+    ~CliqueSet() { ffi::CliqueSetC_free(set_); }
+
+    /// The number of cliques.
+    [[nodiscard]] std::size_t size() const noexcept { return set_->len; }
+
+    /// Returns `true` if there are no cliques.
+    [[nodiscard]] bool empty() const noexcept { return size() == 0; }
+
+    /// The members of the clique at position `i`.
+    std::span<const Uuid> operator[](std::size_t i) const { return *iterator(set_->cliques + i); }
+
+    [[nodiscard]] iterator begin() const noexcept { return iterator(set_->cliques); }
+    [[nodiscard]] iterator end() const noexcept { return iterator(set_->cliques + set_->len); }
+
+private:
+    ffi::CliqueSetC* set_;
+};
+
+/// An index which tracks the cliques of mutually compatible observations.
+///
+/// This is not thread-safe; concurrent use must be synchronized by the caller.
+class Index {
+public:
+    /// Construct an empty index with the given chi-squared threshold.
+    ///
+    /// Throws `InvalidArgument` if `chi2` is not finite and strictly positive.
+    explicit Index(double chi2) { detail::check(ffi::CliqueIndex_new(chi2, &index_)); }
+
+    /// Construct an index from a batch of observations.
+    ///
+    /// This is much faster than inserting the observations one at a time.
+    ///
+    /// Throws `InvalidArgument` if `chi2` or any observation is invalid, and `DuplicateId` if two
+    /// observations share an ID.
+    Index(double chi2, std::span<const Observation> observations) {
+        detail::check(ffi::CliqueIndex_from_observations(
+            chi2, observations.data(), observations.size(), &index_));
+    }
+
+    Index(const Index&) = delete;
+    Index& operator=(const Index&) = delete;
+    Index(Index&& other) noexcept : index_(std::exchange(other.index_, nullptr)) {}
+    Index& operator=(Index&& other) noexcept {
+        if (this != &other) {
+            ffi::CliqueIndex_free(index_);
+            index_ = std::exchange(other.index_, nullptr);
+        }
+        return *this;
+    }
+    ~Index() { ffi::CliqueIndex_free(index_); }
+
+    /// Insert an observation.
+    ///
+    /// Throws `InvalidArgument` if the observation is invalid, and `DuplicateId` if an observation
+    /// with the same ID is already in the index. The index is unchanged if an exception is thrown.
+    void insert(const Observation& observation) {
+        detail::check(ffi::CliqueIndex_insert(index_, &observation));
+    }
+
+    /// Insert a batch of observations, in order.
+    ///
+    /// If an exception is thrown, the observations preceding the failing one remain inserted.
+    void insert(std::span<const Observation> observations) {
+        for (const Observation& observation : observations) {
+            insert(observation);
+        }
+    }
+
+    /// Replace an observation with a refined version of it (with the same ID).
+    ///
+    /// Throws `InvalidArgument` if the observation is invalid, and `NotFound` if no observation
+    /// with the same ID is in the index. The index is unchanged if an exception is thrown.
+    void update(const Observation& observation) {
+        detail::check(ffi::CliqueIndex_update(index_, &observation));
+    }
+
+    /// Insert an observation, replacing any observation with the same ID.
+    ///
+    /// Throws `InvalidArgument` if the observation is invalid, in which case the index is
+    /// unchanged.
+    void upsert(const Observation& observation) {
+        detail::check(ffi::CliqueIndex_upsert(index_, &observation));
+    }
+
+    /// Remove an observation. Returns `false` if it was not in the index.
+    bool remove(const Uuid& id) {
+        ffi::Status status = ffi::CliqueIndex_remove(index_, &id);
+        if (status == ffi::Status::NotFound) {
+            return false;
+        }
+        detail::check(status);
+        return true;
+    }
+
+    /// Remove every observation in a context, recomputing the affected cliques once. Returns
+    /// `false` if no observation in the context was in the index.
+    bool remove_context(const Uuid& context) {
+        ffi::Status status = ffi::CliqueIndex_remove_context(index_, &context);
+        if (status == ffi::Status::NotFound) {
+            return false;
+        }
+        detail::check(status);
+        return true;
+    }
+
+    /// Look up an observation by ID.
+    [[nodiscard]] std::optional<Observation> get(const Uuid& id) const {
+        Observation observation{};
+        ffi::Status status = ffi::CliqueIndex_get(index_, &id, &observation);
+        if (status == ffi::Status::NotFound) {
+            return std::nullopt;
+        }
+        detail::check(status);
+        return observation;
+    }
+
+    /// The IDs of the observations which `observation` would be compatible with, nearest first,
+    /// without inserting it. An observation with the same ID is excluded.
+    ///
+    /// Throws `InvalidArgument` if the observation is invalid.
+    [[nodiscard]] std::vector<Uuid> probe(const Observation& observation) const {
+        ffi::UuidListC* list = nullptr;
+        detail::check(ffi::CliqueIndex_probe(index_, &observation, &list));
+        std::vector<Uuid> ids(list->uuids, list->uuids + list->len);
+        ffi::UuidListC_free(list);
+        return ids;
+    }
+
+    /// Returns `true` if an observation with the given ID is in the index.
+    [[nodiscard]] bool contains(const Uuid& id) const { return ffi::CliqueIndex_contains(index_, &id); }
+
+    /// The chi-squared threshold used to test compatibility.
+    [[nodiscard]] double chi2() const { return ffi::CliqueIndex_chi2(index_); }
+
+    /// Change the chi-squared threshold, recomputing all cliques.
+    ///
+    /// Throws `InvalidArgument` if `chi2` is not finite and strictly positive.
+    void set_chi2(double chi2) { detail::check(ffi::CliqueIndex_set_chi2(index_, chi2)); }
+
+    /// The number of observations which are compatible with at least one other observation.
+    [[nodiscard]] std::size_t size() const { return ffi::CliqueIndex_len(index_); }
+
+    /// The total number of observations in the index.
+    [[nodiscard]] std::size_t total_observations() const {
+        return ffi::CliqueIndex_total_observations(index_);
+    }
+
+    /// Inexpensive statistics of the index, without copying any cliques.
+    [[nodiscard]] ffi::CliqueStatsC stats() const {
+        ffi::CliqueStatsC stats{};
+        detail::check(ffi::CliqueIndex_stats(index_, &stats));
+        return stats;
+    }
+
+    /// A snapshot of the current maximal cliques.
+    [[nodiscard]] CliqueSet cliques() const { return CliqueSet(ffi::CliqueIndex_cliques(index_)); }
+
+    /// A snapshot of the cliques with at least one member within the given bounds (inclusive).
+    [[nodiscard]] CliqueSet cliques_in_region(
+        double min_x, double min_y, double max_x, double max_y) const {
+        return CliqueSet(ffi::CliqueIndex_cliques_in_region(index_, min_x, min_y, max_x, max_y));
+    }
+
+    /// The observations, confidence ellipses and cliques as a GeoJSON `FeatureCollection`.
+    [[nodiscard]] std::string to_geojson() const {
+        char* json = ffi::CliqueIndex_to_geojson(index_);
+        if (json == nullptr) {
+            throw Error(ffi::Status::Panic, "failed to export GeoJSON");
+        }
+        std::string result(json);
+        ffi::CliqueFusion_string_free(json);
+        return result;
+    }
+
+    /// The underlying handle, for use with C functions not covered by this wrapper.
+    [[nodiscard]] ffi::CliqueIndex* native_handle() const noexcept { return index_; }
+
+private:
+    ffi::CliqueIndex* index_ = nullptr;
+};
+
+}  // namespace clique
@@ -0,0 +1,147 @@
+//! Variants of the query functions which write into caller-allocated memory.
+//!
+//! These never allocate memory which the caller must release, for consumers which cannot
+//! free memory allocated by the library. Each can be called first with zero capacity (and null
+//! buffers) to query the required capacity.
+
+use clique_fusion::CliqueIndex;
+use uuid::Uuid;
+
+use crate::{CliqueStatus, EdgeC, Error, UuidC, guard};
+
+fn buffer_too_small(required: usize, capacity: usize, what: &str) -> Error {
+    Error::new(
+        CliqueStatus::BufferTooSmall,
+        format_args!("{required} {what} required, but the buffer has capacity for {capacity}"),
+    )
+}
+
+/// Copy the current set of maximal cliques into caller-supplied buffers.
+///
+/// The members of every clique are written contiguously to `uuids`, and the number of members of
+/// each clique is written to `clique_lengths`, in the same order. The total number of UUIDs and the
+/// number of cliques are written to `out_uuids_len` and `out_cliques_len` respectively.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `uuids` must be valid for writes of `uuids_capacity` 16-byte UUIDs (it may be null if
+///   `uuids_capacity == 0`).
+/// - `clique_lengths` must be valid for writes of `cliques_capacity` `size_t`s (it may be null if
+///   `cliques_capacity == 0`).
+/// - `out_uuids_len` and `out_cliques_len` must be valid pointers to writable memory for a `size_t`.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `clique_index_ptr`, `out_uuids_len` or
+///   `out_cliques_len` is null, or if a buffer is null and its capacity is non-zero.
+/// - Returns [`CliqueStatus::BufferTooSmall`] if either buffer is too small. The required
+///   capacities are written to `out_uuids_len` and `out_cliques_len`, and the buffers are not
+///   written to.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_cliques_into(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
+    uuids: *mut UuidC,
+    uuids_capacity: usize,
+    clique_lengths: *mut usize,
+    cliques_capacity: usize,
+    out_uuids_len: *mut usize,
+    out_cliques_len: *mut usize,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if out_uuids_len.is_null() {
+            return Err(Error::null_pointer("out_uuids_len"));
+        }
+        if out_cliques_len.is_null() {
+            return Err(Error::null_pointer("out_cliques_len"));
+        }
+        if uuids.is_null() && uuids_capacity > 0 {
+            return Err(Error::null_pointer("uuids"));
+        }
+        if clique_lengths.is_null() && cliques_capacity > 0 {
+            return Err(Error::null_pointer("clique_lengths"));
+        }
+        let cliques = unsafe { &*clique_index_ptr }.cliques();
+
+        let uuids_len = cliques.iter().map(std::collections::HashSet::len).sum();
+        unsafe {
+            out_uuids_len.write(uuids_len);
+            out_cliques_len.write(cliques.len());
+        }
+        if uuids_len > uuids_capacity {
+            return Err(buffer_too_small(uuids_len, uuids_capacity, "UUIDs"));
+        }
+        if cliques.len() > cliques_capacity {
+            return Err(buffer_too_small(
+                cliques.len(),
+                cliques_capacity,
+                "clique lengths",
+            ));
+        }
+
+        let mut offset = 0;
+        for (i, clique) in cliques.iter().enumerate() {
+            for id in clique {
+                unsafe { uuids.add(offset).write(*id.as_bytes()) };
+                offset += 1;
+            }
+            unsafe { clique_lengths.add(i).write(clique.len()) };
+        }
+        Ok(())
+    })
+}
+
+/// Copy the edges of the compatibility graph into a caller-supplied buffer.
+///
+/// On success, the number of edges written to `edges` is written to `out_len`.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `edges` must be valid for writes of `capacity` `EdgeC`s (it may be null if `capacity == 0`).
+/// - `out_len` must be a valid pointer to writable memory for a `size_t`.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `clique_index_ptr` or `out_len` is null, or if
+///   `edges` is null and `capacity > 0`.
+/// - Returns [`CliqueStatus::BufferTooSmall`] if the graph has more than `capacity` edges. The
+///   required capacity is written to `out_len`, and `edges` is not written to.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_edges_into(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
+    edges: *mut EdgeC,
+    capacity: usize,
+    out_len: *mut usize,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if out_len.is_null() {
+            return Err(Error::null_pointer("out_len"));
+        }
+        if edges.is_null() && capacity > 0 {
+            return Err(Error::null_pointer("edges"));
+        }
+        let graph_edges = unsafe { &*clique_index_ptr }.compatibility_edges();
+
+        unsafe { out_len.write(graph_edges.len()) };
+        if graph_edges.len() > capacity {
+            return Err(buffer_too_small(graph_edges.len(), capacity, "edges"));
+        }
+
+        for (i, (a, b, mahalanobis_squared)) in graph_edges.into_iter().enumerate() {
+            let edge = EdgeC {
+                a: *a.as_bytes(),
+                b: *b.as_bytes(),
+                mahalanobis_squared,
+            };
+            unsafe { edges.add(i).write(edge) };
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,195 @@
+//! Status codes and thread-local error reporting for the FFI surface.
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{CString, c_char},
+    fmt::Display,
+    panic::{AssertUnwindSafe, catch_unwind},
+};
+
+use clique_fusion::{InvalidChi2, InvalidCovarianceMatrix, InvalidPosition};
+
+/// Status codes returned by fallible FFI functions.
+///
+/// When a function returns anything other than [`CliqueStatus::Ok`], a description of the failure
+/// can be retrieved with [`CliqueFusion_last_error_message`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum CliqueStatus {
+    /// The operation succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// No observation with the given ID exists in the index.
+    NotFound = 2,
+    /// The chi-squared threshold was not finite and strictly positive.
+    InvalidChi2 = 3,
+    /// An observation's covariance terms did not describe a positive semi-definite matrix.
+    InvalidCovariance = 4,
+    /// An observation's position was not finite.
+    InvalidPosition = 5,
+    /// An observation with the same ID already exists.
+    DuplicateId = 6,
+    /// The library panicked. The state of any index involved in the call is unspecified.
+    Panic = 7,
+    /// A caller-supplied buffer was too small to hold the result.
+    BufferTooSmall = 8,
+    /// An iterator has no more items.
+    Exhausted = 9,
+}
+
+/// A failed FFI call: a status code, and a human-readable description of the failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub status: CliqueStatus,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(status: CliqueStatus, message: impl Display) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn null_pointer(argument: &str) -> Self {
+        Self::new(
+            CliqueStatus::NullPointer,
+            format_args!("`{argument}` must not be null"),
+        )
+    }
+}
+
+impl From<InvalidChi2> for Error {
+    fn from(error: InvalidChi2) -> Self {
+        Self::new(CliqueStatus::InvalidChi2, error)
+    }
+}
+
+impl From<InvalidCovarianceMatrix> for Error {
+    fn from(error: InvalidCovarianceMatrix) -> Self {
+        Self::new(CliqueStatus::InvalidCovariance, error)
+    }
+}
+
+impl From<InvalidPosition> for Error {
+    fn from(error: InvalidPosition) -> Self {
+        Self::new(CliqueStatus::InvalidPosition, error)
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: Option<String>) {
+    // Interior nul bytes cannot be represented in a C string, so strip them rather than losing the message
+    let message = message.map(|message| {
+        CString::new(message.replace('\0', "")).expect("nul bytes have been removed")
+    });
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Run `f`, recording any error it returns as the calling thread's last error.
+///
+/// Panics are converted into [`CliqueStatus::Panic`] rather than unwinding across the FFI boundary.
+pub fn guard(f: impl FnOnce() -> Result<(), Error>) -> CliqueStatus {
+    let result = catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| Err(Error::new(CliqueStatus::Panic, panic_message(&*payload))));
+    match result {
+        Ok(()) => {
+            set_last_error(None);
+            CliqueStatus::Ok
+        }
+        Err(error) => {
+            set_last_error(Some(error.message));
+            error.status
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let detail = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause");
+    format!("panic in clique-fusion: {detail}")
+}
+
+/// Returns a description of the most recent error on the calling thread.
+///
+/// Every function which returns a [`CliqueStatus`] updates this; a successful call clears it.
+///
+/// # Safety
+///
+/// The returned string is a copy owned by the caller, and must be freed with
+/// [`CliqueFusion_string_free`].
+///
+/// # Errors
+///
+/// Returns a null pointer if the most recent call on this thread succeeded (or no call has been made).
+#[unsafe(no_mangle)]
+pub extern "C" fn CliqueFusion_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.clone().into_raw())
+    })
+}
+
+/// Free a string returned by this library.
+///
+/// # Safety
+///
+/// `ptr` must have been returned by [`CliqueFusion_last_error_message`] or
+/// [`CliqueIndex_to_geojson`](crate::CliqueIndex_to_geojson), and not already freed.
+/// Passing a null pointer is a no-op.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueFusion_string_free(ptr: *const c_char) {
+    if !ptr.is_null() {
+        drop(unsafe { CString::from_raw(ptr.cast_mut()) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    fn last_error_message() -> Option<String> {
+        let ptr = CliqueFusion_last_error_message();
+        if ptr.is_null() {
+            return None;
+        }
+        let message = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_owned();
+        unsafe { CliqueFusion_string_free(ptr) };
+        Some(message)
+    }
+
+    #[test]
+    fn errors_are_recorded_and_cleared() {
+        let status = guard(|| Err(Error::null_pointer("index")));
+        assert_eq!(status, CliqueStatus::NullPointer);
+        assert_eq!(
+            last_error_message().as_deref(),
+            Some("`index` must not be null")
+        );
+
+        assert_eq!(guard(|| Ok(())), CliqueStatus::Ok);
+        assert_eq!(last_error_message(), None);
+    }
+
+    #[test]
+    fn panics_are_caught() {
+        let status = guard(|| panic!("boom"));
+        assert_eq!(status, CliqueStatus::Panic);
+        assert_eq!(
+            last_error_message().as_deref(),
+            Some("panic in clique-fusion: boom")
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! A parallel FFI surface for indexes keyed by 64-bit integers rather than UUIDs.
+//!
+//! These functions mirror their `CliqueIndex_*` counterparts, for hosts whose native identifiers
+//! are integers (such as database row IDs).
+
+use std::{collections::HashSet, panic::catch_unwind};
+
+use clique_fusion::{CliqueIndex, Config, Observation, Unique};
+
+use crate::{
+    CliqueStatus, Error, UuidC, boxed_slice_from_raw, build_observation, duplicate_id, guard,
+    not_found,
+};
+
+#[derive(Debug, Clone)]
+#[repr(C)]
+/// C-compatible observation data with covariance and optional context, identified by an integer.
+pub struct ObservationU64C {
+    /// Observation ID.
+    pub id: u64,
+    /// X coordinate.
+    pub x: f64,
+    /// Y coordinate.
+    pub y: f64,
+    /// Covariance XX term.
+    pub cov_xx: f64,
+    /// Covariance XY term.
+    pub cov_xy: f64,
+    /// Covariance YY term.
+    pub cov_yy: f64,
+    /// Optional context UUID; a nil UUID is treated as no context.
+    pub context: UuidC,
+}
+
+impl ObservationU64C {
+    /// Validate and convert into an [`Observation`].
+    fn to_observation(&self) -> Result<Unique<Observation, u64>, Error> {
+        let data = build_observation(
+            self.x,
+            self.y,
+            [self.cov_xx, self.cov_xy, self.cov_yy],
+            self.context,
+        )?;
+        Ok(Unique { id: self.id, data })
+    }
+}
+
+/// Initialise a new integer-keyed [`CliqueIndex`].
+///
+/// On success, the new index is written to `out_index`.
+///
+/// # Safety
+///
+/// - `out_index` must be a valid pointer to writable memory for a `CliqueIndex<u64>*`.
+/// - The index written to `out_index` must be freed with `CliqueIndexU64_free` when no longer needed.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `out_index` is null.
+/// - Returns [`CliqueStatus::InvalidChi2`] if `chi2` is not finite and strictly positive.
+///
+/// `out_index` is not written to unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexU64_new(
+    chi2: f64,
+    out_index: *mut *mut CliqueIndex<u64>,
+) -> CliqueStatus {
+    guard(|| {
+        if out_index.is_null() {
+            return Err(Error::null_pointer("out_index"));
+        }
+        let index = CliqueIndex::try_new(chi2)?;
+        unsafe { out_index.write(Box::into_raw(Box::new(index))) };
+        Ok(())
+    })
+}
+
+/// Initialise a new integer-keyed [`CliqueIndex`] from a list of observations.
+///
+/// This is faster than creating an empty index and adding the observations one at a time.
+///
+/// On success, the new index is written to `out_index`.
+///
+/// # Safety
+///
+/// - `observations` must be a valid pointer to `len` contiguous `ObservationU64C` structs.
+/// - `observations` must not be null unless `len == 0`.
+/// - `out_index` must be a valid pointer to writable memory for a `CliqueIndex<u64>*`.
+/// - The index written to `out_index` must be freed with `CliqueIndexU64_free` when no longer needed.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `out_index` is null, or if `observations` is null and `len > 0`.
+/// - Returns [`CliqueStatus::InvalidChi2`] if `chi2` is not finite and strictly positive.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if any observation is invalid.
+/// - Returns [`CliqueStatus::DuplicateId`] if two observations share the same ID.
+///
+/// `out_index` is not written to unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexU64_from_observations(
+    chi2: f64,
+    observations: *const ObservationU64C,
+    len: usize,
+    out_index: *mut *mut CliqueIndex<u64>,
+) -> CliqueStatus {
+    guard(|| {
+        if out_index.is_null() {
+            return Err(Error::null_pointer("out_index"));
+        }
+        if observations.is_null() && len > 0 {
+            return Err(Error::null_pointer("observations"));
+        }
+        let config = Config::try_new(chi2)?;
+        let obs_slice = if len == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(observations, len) }
+        };
+
+        let mut ids = HashSet::with_capacity(len);
+        let mut rust_obs = Vec::with_capacity(len);
+        for (i, obs_c) in obs_slice.iter().enumerate() {
+            let observation = obs_c.to_observation().map_err(|error| {
+                Error::new(
+                    error.status,
+                    format_args!("observation {i}: {}", error.message),
+                )
+            })?;
+            if !ids.insert(observation.id) {
+                return Err(duplicate_id(observation.id));
+            }
+            rust_obs.push(observation);
+        }
+
+        let index = CliqueIndex::from_observations_with_config(rust_obs, config);
+        unsafe { out_index.write(Box::into_raw(Box::new(index))) };
+        Ok(())
+    })
+}
+
+/// Insert an observation into an existing integer-keyed [`CliqueIndex`].
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<u64>`.
+/// - `observation` must be a valid pointer to an `ObservationU64C`.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if the observation is invalid.
+/// - Returns [`CliqueStatus::DuplicateId`] if an observation with the same ID is already in the index.
+///
+/// The index is not modified unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexU64_insert(
+    clique_index_ptr: *mut CliqueIndex<u64>,
+    observation: *const ObservationU64C,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if observation.is_null() {
+            return Err(Error::null_pointer("observation"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        let rust_obs = unsafe { &*observation }.to_observation()?;
+        if clique_index.contains(&rust_obs.id) {
+            return Err(duplicate_id(rust_obs.id));
+        }
+        clique_index.insert(rust_obs);
+        Ok(())
+    })
+}
+
+/// Remove an observation from an existing integer-keyed [`CliqueIndex`], updating the affected cliques.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<u64>`.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `clique_index_ptr` is null.
+/// - Returns [`CliqueStatus::NotFound`] if no observation with the given ID is in the index.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexU64_remove(
+    clique_index_ptr: *mut CliqueIndex<u64>,
+    id: u64,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        clique_index
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| not_found(id))
+    })
+}
+
+/// Returns the number of observations in the integer-keyed [`CliqueIndex`] which are compatible with
+/// at least one other observation.
+///
+/// # Safety
+///
+/// `clique_index_ptr` must be null, or a valid pointer to a `CliqueIndex<u64>`.
+///
+/// # Errors
+///
+/// Returns 0 if `clique_index_ptr` is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexU64_len(clique_index_ptr: *const CliqueIndex<u64>) -> usize {
+    if clique_index_ptr.is_null() {
+        return 0;
+    }
+    unsafe { &*clique_index_ptr }.len()
+}
+
+/// A single clique of integer IDs.
+#[derive(Debug)]
+#[repr(C)]
+pub struct CliqueU64C {
+    /// Pointer to an array of observation IDs.
+    pub ids: *const u64,
+    /// Number of IDs in the clique.
+    pub len: usize,
+}
+
+/// A set of maximal cliques returned by `CliqueIndexU64_cliques`.
+#[derive(Debug)]
+#[repr(C)]
+pub struct CliqueSetU64C {
+    /// Pointer to an array of `CliqueU64C` structures.
+    pub cliques: *const CliqueU64C,
+    /// Number of cliques in the set.
+    pub len: usize,
+}
+
+/// Returns the current set of maximal cliques from the integer-keyed [`CliqueIndex`].
+///
+/// # Safety
+///
+/// - `ptr` must be a valid, non-null pointer to a [`CliqueIndex<u64>`] allocated by this library.
+/// - The caller takes ownership of the returned pointer and is responsible for freeing it using
+///   [`CliqueSetU64C_free`].
+///
+/// # Errors
+///
+/// If `ptr` is null, or the library panics, this function returns a null pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexU64_cliques(
+    ptr: *const CliqueIndex<u64>,
+) -> *mut CliqueSetU64C {
+    if ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let index = unsafe { &*ptr };
+    catch_unwind(|| {
+        let cliques: Box<[CliqueU64C]> = index
+            .cliques()
+            .iter()
+            .map(|clique| {
+                let ids: Box<[u64]> = clique.iter().copied().collect();
+                let len = ids.len();
+                // Leak the IDs; they are reclaimed by `CliqueSetU64C_free`
+                CliqueU64C {
+                    ids: Box::into_raw(ids).cast::<u64>(),
+                    len,
+                }
+            })
+            .collect();
+
+        let len = cliques.len();
+        Box::into_raw(Box::new(CliqueSetU64C {
+            cliques: Box::into_raw(cliques).cast::<CliqueU64C>(),
+            len,
+        }))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Frees memory previously allocated by `CliqueIndexU64_cliques`.
+///
+/// # Safety
+///
+/// `ptr` must be null, or a pointer returned by `CliqueIndexU64_cliques` which has not already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueSetU64C_free(ptr: *mut CliqueSetU64C) {
+    if ptr.is_null() {
+        return;
+    }
+
+    let boxed = unsafe { Box::from_raw(ptr) };
+    let cliques = unsafe { boxed_slice_from_raw(boxed.cliques, boxed.len) };
+    for clique in cliques {
+        drop(unsafe { boxed_slice_from_raw(clique.ids, clique.len) });
+    }
+}
+
+/// Free the memory associated with an integer-keyed [`CliqueIndex`].
+///
+/// # Safety
+///
+/// `ptr` must have been returned by `CliqueIndexU64_new` or `CliqueIndexU64_from_observations`, and
+/// not already freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexU64_free(ptr: *mut CliqueIndex<u64>) {
+    if !ptr.is_null() {
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+}
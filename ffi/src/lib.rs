@@ -1,266 +1,1503 @@
 //! C FFI bindings for the `clique_fusion` crate.
 
 use clique_fusion::{
-    CHI2_2D_CONFIDENCE_90, CHI2_2D_CONFIDENCE_95, CHI2_2D_CONFIDENCE_99, CliqueIndex,
-    CovarianceMatrix, Observation, Unique,
+    BoundingBox, CHI2_2D_CONFIDENCE_90, CHI2_2D_CONFIDENCE_95, CHI2_2D_CONFIDENCE_99, CliqueIndex,
+    CliqueSet, Config, CovarianceMatrix, Observation, Unique, chi2_threshold,
+};
+use std::{
+    collections::HashSet,
+    ffi::{CStr, CString, c_char},
+    fmt::Display,
+    panic::catch_unwind,
 };
 use uuid::Uuid;
 
+mod error;
+pub use error::{CliqueFusion_last_error_message, CliqueFusion_string_free, CliqueStatus};
+use error::{Error, guard};
+
+mod buffers;
+pub use buffers::{CliqueIndex_cliques_into, CliqueIndex_edges_into};
+
+mod integer_keys;
+pub use integer_keys::{
+    CliqueIndexU64_cliques, CliqueIndexU64_free, CliqueIndexU64_from_observations,
+    CliqueIndexU64_insert, CliqueIndexU64_len, CliqueIndexU64_new, CliqueIndexU64_remove,
+    CliqueSetU64C, CliqueSetU64C_free, CliqueU64C, ObservationU64C,
+};
+
+mod synchronized;
+pub use synchronized::{
+    CliqueIndexTS_chi2, CliqueIndexTS_cliques, CliqueIndexTS_contains, CliqueIndexTS_free,
+    CliqueIndexTS_insert, CliqueIndexTS_len, CliqueIndexTS_new, CliqueIndexTS_remove,
+    CliqueIndexTS_set_chi2, SynchronizedCliqueIndex,
+};
+
+/// The version of the C ABI exposed by this library.
+///
+/// This is incremented whenever the layout of a `#[repr(C)]` type, or the signature or semantics
+/// of an exported function, changes in a way which is incompatible with existing callers. Adding
+/// new functions or types does not change it.
+pub const ABI_VERSION: u32 = 1;
+
+const VERSION: &CStr =
+    match CStr::from_bytes_with_nul(concat!(env!("CARGO_PKG_VERSION"), "\0").as_bytes()) {
+        Ok(version) => version,
+        Err(_) => panic!("the package version must not contain nul bytes"),
+    };
+
+/// Returns the version of this library, as a nul-terminated semver string (e.g. `"0.1.0"`).
+///
+/// The returned string is statically allocated, and must not be freed.
+#[unsafe(no_mangle)]
+pub const extern "C" fn CliqueFusion_version() -> *const c_char {
+    VERSION.as_ptr()
+}
+
+/// Returns the version of the C ABI exposed by this library.
+///
+/// Hosts which load the library dynamically should check this against the version they were built
+/// against before calling any function which exchanges `#[repr(C)]` structures.
+/// See [`ABI_VERSION`].
+#[unsafe(no_mangle)]
+pub const extern "C" fn CliqueFusion_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+#[unsafe(no_mangle)]
+/// Returns the chi-squared confidence threshold at 90% for 2D observations.
+pub const extern "C" fn CliqueIndex_chi2_confidence_90() -> f64 {
+    CHI2_2D_CONFIDENCE_90
+}
+
+#[unsafe(no_mangle)]
+/// Returns the chi-squared confidence threshold at 95% for 2D observations.
+pub const extern "C" fn CliqueIndex_chi2_confidence_95() -> f64 {
+    CHI2_2D_CONFIDENCE_95
+}
+
+#[unsafe(no_mangle)]
+/// Returns the chi-squared confidence threshold at 99% for 2D observations.
+pub const extern "C" fn CliqueIndex_chi2_confidence_99() -> f64 {
+    CHI2_2D_CONFIDENCE_99
+}
+
+/// Returns the chi-squared threshold for an arbitrary confidence level.
+///
+/// `confidence` is a probability (e.g. `0.95` for 95% confidence), and `dof` is the number of degrees
+/// of freedom, which is 2 for 2D observations.
+///
+/// # Errors
+///
+/// Returns NaN if `confidence` is not strictly between 0 and 1, or if `dof` is 0.
+#[unsafe(no_mangle)]
+pub extern "C" fn CliqueFusion_chi2_threshold(confidence: f64, dof: u32) -> f64 {
+    chi2_threshold(confidence, dof).unwrap_or(f64::NAN)
+}
+
+type UuidC = [u8; 16];
+
+#[derive(Debug, Clone)]
+#[repr(C)]
+/// C-compatible observation data with covariance and optional context.
+pub struct ObservationC {
+    /// Observation UUID (16 bytes).
+    pub id: UuidC,
+    /// X coordinate.
+    pub x: f64,
+    /// Y coordinate.
+    pub y: f64,
+    /// Covariance XX term.
+    pub cov_xx: f64,
+    /// Covariance XY term.
+    pub cov_xy: f64,
+    /// Covariance YY term.
+    pub cov_yy: f64,
+    /// Optional context UUID; a nil UUID is treated as no context.
+    pub context: UuidC,
+}
+
+const fn parse_uuid(bytes: UuidC) -> Option<Uuid> {
+    let uuid = Uuid::from_bytes(bytes);
+    if uuid.is_nil() { None } else { Some(uuid) }
+}
+
+impl ObservationC {
+    fn from_observation(id: Uuid, observation: &Observation) -> Self {
+        let error = observation.error_covariance();
+        Self {
+            id: *id.as_bytes(),
+            x: observation.x(),
+            y: observation.y(),
+            cov_xx: error.xx(),
+            cov_xy: error.xy(),
+            cov_yy: error.yy(),
+            context: observation
+                .context()
+                .map_or([0; 16], |context| *context.as_bytes()),
+        }
+    }
+
+    /// Validate and convert into an [`Observation`].
+    fn to_observation(&self) -> Result<Unique<Observation, Uuid>, Error> {
+        let id = Uuid::from_bytes(self.id);
+        let data = build_observation(
+            self.x,
+            self.y,
+            [self.cov_xx, self.cov_xy, self.cov_yy],
+            self.context,
+        )?;
+        Ok(Unique { id, data })
+    }
+}
+
+#[derive(Debug, Clone)]
+#[repr(C)]
+/// C-compatible observation data, extending [`ObservationC`] with a timestamp, weight and payload tag.
+pub struct ObservationV2C {
+    /// Observation UUID (16 bytes).
+    pub id: UuidC,
+    /// X coordinate.
+    pub x: f64,
+    /// Y coordinate.
+    pub y: f64,
+    /// Covariance XX term.
+    pub cov_xx: f64,
+    /// Covariance XY term.
+    pub cov_xy: f64,
+    /// Covariance YY term.
+    pub cov_yy: f64,
+    /// Optional context UUID; a nil UUID is treated as no context.
+    pub context: UuidC,
+    /// Optional time of the observation; NaN is treated as no timestamp.
+    pub timestamp: f64,
+    /// Relative weight of the observation. Use `1.0` if not required.
+    pub weight: f64,
+    /// Optional caller-defined payload tag; zero is treated as no tag.
+    pub payload_tag: u64,
+}
+
+impl ObservationV2C {
+    fn from_observation(id: Uuid, observation: &Observation) -> Self {
+        let ObservationC {
+            id,
+            x,
+            y,
+            cov_xx,
+            cov_xy,
+            cov_yy,
+            context,
+        } = ObservationC::from_observation(id, observation);
+        Self {
+            id,
+            x,
+            y,
+            cov_xx,
+            cov_xy,
+            cov_yy,
+            context,
+            timestamp: observation.timestamp().unwrap_or(f64::NAN),
+            weight: observation.weight(),
+            payload_tag: observation.tag().unwrap_or(0),
+        }
+    }
+
+    /// Validate and convert into an [`Observation`].
+    fn to_observation(&self) -> Result<Unique<Observation, Uuid>, Error> {
+        let id = Uuid::from_bytes(self.id);
+        let error = CovarianceMatrix::new(self.cov_xx, self.cov_yy, self.cov_xy)?;
+
+        let mut observation_builder = Observation::builder(self.x, self.y)
+            .error(error)
+            .weight(self.weight);
+        if let Some(context) = parse_uuid(self.context) {
+            observation_builder = observation_builder.context(context);
+        }
+        if !self.timestamp.is_nan() {
+            observation_builder = observation_builder.timestamp(self.timestamp);
+        }
+        if self.payload_tag != 0 {
+            observation_builder = observation_builder.tag(self.payload_tag);
+        }
+        let data = observation_builder.try_build()?;
+        Ok(Unique { id, data })
+    }
+}
+
+/// Validate the fields of a C observation and build an [`Observation`].
+///
+/// `covariance` is given as `[xx, xy, yy]`.
+fn build_observation(
+    x: f64,
+    y: f64,
+    [xx, xy, yy]: [f64; 3],
+    context: UuidC,
+) -> Result<Observation, Error> {
+    let error = CovarianceMatrix::new(xx, yy, xy)?;
+
+    let mut observation_builder = Observation::builder(x, y).error(error);
+    if let Some(context) = parse_uuid(context) {
+        observation_builder = observation_builder.context(context);
+    }
+    Ok(observation_builder.try_build()?)
+}
+
+/// Initialise a new [`CliqueIndex`].
+///
+/// On success, the new index is written to `out_index`.
+///
+/// # Safety
+///
+/// - `out_index` must be a valid pointer to writable memory for a `CliqueIndex<Uuid>*`.
+/// - The index written to `out_index` must be freed with `CliqueIndex_free` when no longer needed.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `out_index` is null.
+/// - Returns [`CliqueStatus::InvalidChi2`] if `chi2` is not finite and strictly positive.
+///
+/// `out_index` is not written to unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_new(
+    chi2: f64,
+    out_index: *mut *mut CliqueIndex<Uuid>,
+) -> CliqueStatus {
+    guard(|| {
+        if out_index.is_null() {
+            return Err(Error::null_pointer("out_index"));
+        }
+        let index = CliqueIndex::try_new(chi2)?;
+        unsafe { out_index.write(Box::into_raw(Box::new(index))) };
+        Ok(())
+    })
+}
+
+/// Initialise a new [`CliqueIndex`] from a list of observations.
+///
+/// This is faster than creating an empty index and adding the observations one at a time.
+///
+/// On success, the new index is written to `out_index`.
+///
+/// # Safety
+///
+/// - `observations` must be a valid pointer to `len` contiguous `ObservationC` structs.
+/// - `observations` must not be null unless `len == 0`.
+/// - The memory referenced by `observations` must remain valid for the duration of the call.
+/// - `out_index` must be a valid pointer to writable memory for a `CliqueIndex<Uuid>*`.
+/// - The index written to `out_index` must be freed with `CliqueIndex_free` when no longer needed.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `out_index` is null, or if `observations` is null and `len > 0`.
+/// - Returns [`CliqueStatus::InvalidChi2`] if `chi2` is not finite and strictly positive.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if any observation is invalid.
+/// - Returns [`CliqueStatus::DuplicateId`] if two observations share the same ID.
+///
+/// `out_index` is not written to unless the function returns [`CliqueStatus::Ok`].
+///
+/// # Example
+/// ```c
+/// CliqueIndex* idx = NULL;
+/// if (CliqueIndex_from_observations(chi2, obs_array, len, &idx) != 0) {
+///     // Handle error
+/// }
+/// ```
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_from_observations(
+    chi2: f64,
+    observations: *const ObservationC,
+    len: usize,
+    out_index: *mut *mut CliqueIndex<Uuid>,
+) -> CliqueStatus {
+    guard(|| {
+        if out_index.is_null() {
+            return Err(Error::null_pointer("out_index"));
+        }
+        if observations.is_null() && len > 0 {
+            return Err(Error::null_pointer("observations"));
+        }
+        let config = Config::try_new(chi2)?;
+        let obs_slice = if len == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(observations, len) }
+        };
+
+        let mut ids = HashSet::with_capacity(len);
+        let mut rust_obs = Vec::with_capacity(len);
+        for (i, obs_c) in obs_slice.iter().enumerate() {
+            let observation = obs_c.to_observation().map_err(|error| {
+                Error::new(
+                    error.status,
+                    format_args!("observation {i}: {}", error.message),
+                )
+            })?;
+            if !ids.insert(observation.id) {
+                return Err(duplicate_id(observation.id));
+            }
+            rust_obs.push(observation);
+        }
+
+        let index = CliqueIndex::from_observations_with_config(rust_obs, config);
+        unsafe { out_index.write(Box::into_raw(Box::new(index))) };
+        Ok(())
+    })
+}
+
+/// Insert an observation into an existing [`CliqueIndex`].
+///
+/// Note that it is quicker to create a [`CliqueIndex`] from a batch of observations using
+/// [`CliqueIndex_from_observations`], but this function is useful for incrementally adding observations.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `observation` must be a valid pointer to an `ObservationC`.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if the observation is invalid.
+/// - Returns [`CliqueStatus::DuplicateId`] if an observation with the same ID is already in the index.
+///
+/// The index is not modified unless the function returns [`CliqueStatus::Ok`].
+///
+/// This function does not take ownership of `clique_index_ptr`; it modifies the pointed-to object
+/// in-place. The pointer remains valid after the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_insert(
+    clique_index_ptr: *mut CliqueIndex<Uuid>,
+    observation: *const ObservationC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if observation.is_null() {
+            return Err(Error::null_pointer("observation"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        let rust_obs = unsafe { &*observation }.to_observation()?;
+        if clique_index.contains(&rust_obs.id) {
+            return Err(duplicate_id(rust_obs.id));
+        }
+        clique_index.insert(rust_obs);
+        Ok(())
+    })
+}
+
+/// Insert an extended observation into an existing [`CliqueIndex`].
+///
+/// This is equivalent to [`CliqueIndex_insert`], but additionally records the observation's
+/// timestamp, weight and payload tag.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `observation` must be a valid pointer to an `ObservationV2C`.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if the observation is invalid.
+/// - Returns [`CliqueStatus::DuplicateId`] if an observation with the same ID is already in the index.
+///
+/// The index is not modified unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_insert_v2(
+    clique_index_ptr: *mut CliqueIndex<Uuid>,
+    observation: *const ObservationV2C,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if observation.is_null() {
+            return Err(Error::null_pointer("observation"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        let rust_obs = unsafe { &*observation }.to_observation()?;
+        if clique_index.contains(&rust_obs.id) {
+            return Err(duplicate_id(rust_obs.id));
+        }
+        clique_index.insert(rust_obs);
+        Ok(())
+    })
+}
+
+/// Replace an observation in an existing [`CliqueIndex`] with a refined version of it (for
+/// example, with an updated position or error), updating the affected cliques.
+///
+/// The observation with the same ID is replaced.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `observation` must be a valid pointer to an `ObservationC`.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if the observation is invalid.
+/// - Returns [`CliqueStatus::NotFound`] if no observation with the same ID is in the index.
+///
+/// The index is not modified unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_update(
+    clique_index_ptr: *mut CliqueIndex<Uuid>,
+    observation: *const ObservationC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if observation.is_null() {
+            return Err(Error::null_pointer("observation"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        let rust_obs = unsafe { &*observation }.to_observation()?;
+        let id = rust_obs.id;
+        clique_index
+            .update(rust_obs)
+            .map(drop)
+            .map_err(|error| index_error(&error, id))
+    })
+}
+
+/// Insert an observation into an existing [`CliqueIndex`], replacing any observation with the same
+/// ID, as for [`CliqueIndex_update`].
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `observation` must be a valid pointer to an `ObservationC`.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if the observation is invalid.
+///
+/// The index is not modified unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_upsert(
+    clique_index_ptr: *mut CliqueIndex<Uuid>,
+    observation: *const ObservationC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if observation.is_null() {
+            return Err(Error::null_pointer("observation"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        let rust_obs = unsafe { &*observation }.to_observation()?;
+        let id = rust_obs.id;
+        clique_index
+            .upsert(rust_obs)
+            .map(drop)
+            .map_err(|error| index_error(&error, id))
+    })
+}
+
+/// Convert an error from modifying an index into a status.
+///
+/// Indices created through this interface are configured by their threshold alone, so they never
+/// reject observations for their covariances or clique capacity.
+fn index_error(error: &clique_fusion::Error, id: impl Display) -> Error {
+    match error {
+        clique_fusion::Error::NotFound => not_found(id),
+        clique_fusion::Error::DuplicateId => duplicate_id(id),
+        error => unreachable!("unexpected error for observation {id}: {error}"),
+    }
+}
+
+fn not_found(id: impl Display) -> Error {
+    Error::new(
+        CliqueStatus::NotFound,
+        format_args!("no observation with ID {id} in the index"),
+    )
+}
+
+fn duplicate_id(id: impl Display) -> Error {
+    Error::new(
+        CliqueStatus::DuplicateId,
+        format_args!("an observation with ID {id} already exists"),
+    )
+}
+
+/// Remove an observation from an existing [`CliqueIndex`], updating the affected cliques.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `id` must be a valid pointer to a 16-byte UUID.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::NotFound`] if no observation with the given ID is in the index.
+///
+/// Returns [`CliqueStatus::Ok`] if the observation was removed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_remove(
+    clique_index_ptr: *mut CliqueIndex<Uuid>,
+    id: *const UuidC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if id.is_null() {
+            return Err(Error::null_pointer("id"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        let id = Uuid::from_bytes(unsafe { *id });
+        clique_index
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| not_found(id))
+    })
+}
+
+/// Remove every observation in the given context from an existing [`CliqueIndex`], updating the
+/// affected cliques.
+///
+/// The observations are removed together, and the cliques are recomputed once. See
+/// [`CliqueIndex::remove_context`].
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `context` must be a valid pointer to a 16-byte UUID.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::NotFound`] if no observation in the given context is in the index.
+///   Observations without a context are never removed, so this is always the case for a nil UUID.
+///
+/// Returns [`CliqueStatus::Ok`] if at least one observation was removed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_remove_context(
+    clique_index_ptr: *mut CliqueIndex<Uuid>,
+    context: *const UuidC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if context.is_null() {
+            return Err(Error::null_pointer("context"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        let Some(context) = parse_uuid(unsafe { *context }) else {
+            return Err(Error::new(
+                CliqueStatus::NotFound,
+                "observations without a context can't be removed by context",
+            ));
+        };
+        if clique_index.remove_context(context).is_empty() {
+            return Err(Error::new(
+                CliqueStatus::NotFound,
+                format_args!("no observation with context {context} in the index"),
+            ));
+        }
+        Ok(())
+    })
+}
+
+/// Returns the chi-squared threshold used by the [`CliqueIndex`] to test compatibility.
+///
+/// # Safety
+///
+/// `clique_index_ptr` must be null, or a valid pointer to a `CliqueIndex<Uuid>`.
+///
+/// # Errors
+///
+/// Returns NaN if `clique_index_ptr` is null.
+#[unsafe(no_mangle)]
+pub const unsafe extern "C" fn CliqueIndex_chi2(clique_index_ptr: *const CliqueIndex<Uuid>) -> f64 {
+    if clique_index_ptr.is_null() {
+        return f64::NAN;
+    }
+    unsafe { &*clique_index_ptr }.chi2()
+}
+
+/// Change the chi-squared threshold of an existing [`CliqueIndex`], recomputing its cliques.
+///
+/// This is comparable in cost to constructing a new index, but avoids the caller having to retain
+/// and resubmit the observations.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
+///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `clique_index_ptr` is null.
+/// - Returns [`CliqueStatus::InvalidChi2`] if `chi2` is not finite and strictly positive.
+///
+/// The index is not modified unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_set_chi2(
+    clique_index_ptr: *mut CliqueIndex<Uuid>,
+    chi2: f64,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        let clique_index = unsafe { &mut *clique_index_ptr };
+        clique_index.set_chi2(chi2)?;
+        Ok(())
+    })
+}
+
+/// Returns the number of observations in the [`CliqueIndex`] which are compatible with at least one
+/// other observation.
+///
+/// See also [`CliqueIndex_total_observations`].
+///
+/// # Safety
+///
+/// `clique_index_ptr` must be null, or a valid pointer to a `CliqueIndex<Uuid>`.
+///
+/// # Errors
+///
+/// Returns 0 if `clique_index_ptr` is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_len(clique_index_ptr: *const CliqueIndex<Uuid>) -> usize {
+    if clique_index_ptr.is_null() {
+        return 0;
+    }
+    unsafe { &*clique_index_ptr }.len()
+}
+
+/// Returns the total number of observations in the [`CliqueIndex`], including those which are not
+/// compatible with any other observation.
+///
+/// # Safety
+///
+/// `clique_index_ptr` must be null, or a valid pointer to a `CliqueIndex<Uuid>`.
+///
+/// # Errors
+///
+/// Returns 0 if `clique_index_ptr` is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_total_observations(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
+) -> usize {
+    if clique_index_ptr.is_null() {
+        return 0;
+    }
+    unsafe { &*clique_index_ptr }.total_observations()
+}
+
+/// Returns `true` if an observation with the given ID is in the [`CliqueIndex`].
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be null, or a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `id` must be null, or a valid pointer to a 16-byte UUID.
+///
+/// # Errors
+///
+/// Returns `false` if either pointer is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_contains(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
+    id: *const UuidC,
+) -> bool {
+    if clique_index_ptr.is_null() || id.is_null() {
+        return false;
+    }
+    let id = Uuid::from_bytes(unsafe { *id });
+    unsafe { &*clique_index_ptr }.contains(&id)
+}
+
+/// Look up an observation in the [`CliqueIndex`] by ID, writing it to `out_observation`.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `id` must be a valid pointer to a 16-byte UUID.
+/// - `out_observation` must be a valid pointer to writable memory for an `ObservationC`.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if any pointer is null.
+/// - Returns [`CliqueStatus::NotFound`] if no observation with the given ID is in the index.
+///
+/// `out_observation` is not written to unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_get(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
+    id: *const UuidC,
+    out_observation: *mut ObservationC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if id.is_null() {
+            return Err(Error::null_pointer("id"));
+        }
+        if out_observation.is_null() {
+            return Err(Error::null_pointer("out_observation"));
+        }
+        let clique_index = unsafe { &*clique_index_ptr };
+        let id = Uuid::from_bytes(unsafe { *id });
+        let observation = clique_index.get(&id).ok_or_else(|| not_found(id))?;
+        unsafe { out_observation.write(ObservationC::from_observation(id, observation)) };
+        Ok(())
+    })
+}
+
+/// Look up an observation in the [`CliqueIndex`] by ID, writing it to `out_observation` in the
+/// extended [`ObservationV2C`] format.
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `id` must be a valid pointer to a 16-byte UUID.
+/// - `out_observation` must be a valid pointer to writable memory for an `ObservationV2C`.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if any pointer is null.
+/// - Returns [`CliqueStatus::NotFound`] if no observation with the given ID is in the index.
+///
+/// `out_observation` is not written to unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_get_v2(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
+    id: *const UuidC,
+    out_observation: *mut ObservationV2C,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if id.is_null() {
+            return Err(Error::null_pointer("id"));
+        }
+        if out_observation.is_null() {
+            return Err(Error::null_pointer("out_observation"));
+        }
+        let clique_index = unsafe { &*clique_index_ptr };
+        let id = Uuid::from_bytes(unsafe { *id });
+        let observation = clique_index.get(&id).ok_or_else(|| not_found(id))?;
+        unsafe { out_observation.write(ObservationV2C::from_observation(id, observation)) };
+        Ok(())
+    })
+}
+
+/// A single clique: a set of UUIDs (observations) belonging to the same maximal clique.
+///
+/// # Fields
+/// - `uuids`: A pointer to an array of 16-byte UUIDs. Must be valid for reads.
+/// - `len`: The number of UUIDs in this clique.
+#[derive(Debug)]
+#[repr(C)]
+pub struct CliqueC {
+    /// Pointer to an array of 16-byte UUIDs.
+    pub uuids: *const UuidC,
+    /// Number of UUIDs in the clique.
+    pub len: usize,
+}
+
+/// A set of maximal cliques returned by `CliqueIndex_cliques`.
+///
+/// # Fields
+/// - `cliques`: Pointer to an array of [`CliqueC`] structures.
+/// - `len`: Number of cliques in the set.
+#[derive(Debug)]
+#[repr(C)]
+pub struct CliqueSetC {
+    /// Pointer to an array of `CliqueC` structures.
+    pub cliques: *const CliqueC,
+    /// Number of cliques in the set.
+    pub len: usize,
+}
+
+/// Frees memory previously allocated by `CliqueIndex_cliques`.
+///
+/// This deallocates all memory owned by the `CliqueSetC`, including:
+/// - Each inner list of UUIDs (allocated as `Vec<[u8; 16]>`)
+/// - The outer array of `CliqueC`
+///
+/// # Safety
+///
+/// - `ptr` must be a valid pointer returned by `CliqueIndex_cliques` and must not be used again after calling this.
+/// - The caller must ensure that no aliasing or use-after-free occurs.
+/// - This function **must not** be called on any pointer not allocated by the library.
+///
+/// # Example (C side)
+/// ```c
+/// CliqueSetC* result = CliqueIndex_cliques(index);
+/// // ... use result ...
+/// CliqueSetC_free(result);
+/// ```
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueSetC_free(ptr: *mut CliqueSetC) {
+    if ptr.is_null() {
+        return;
+    }
+
+    let boxed = unsafe { Box::from_raw(ptr) };
+
+    // Fully reconstruct the outer Box<[CliqueC]>
+    let cliques = unsafe { boxed_slice_from_raw(boxed.cliques, boxed.len) };
+
+    for clique in cliques {
+        // Reconstruct and drop the inner UUID arrays
+        drop(unsafe { boxed_slice_from_raw(clique.uuids, clique.len) });
+    }
+
+    // `boxed` is dropped here, releasing CliqueSetC itself
+}
+
+/// Returns the current set of maximal cliques from the [`CliqueIndex`].
+///
+/// Each clique is represented as a set of UUIDs. The result is wrapped in a
+/// [`CliqueSetC`] structure containing an array of [`CliqueC`] entries.
+///
+/// # Safety
+///
+/// - `ptr` must be a valid, non-null pointer to a [`CliqueIndex<Uuid>`] allocated by this library.
+/// - The caller takes ownership of the returned pointer and is responsible for freeing it using
+///   [`CliqueSetC_free`] to avoid memory leaks.
+/// - The returned structure points to heap-allocated memory and must not be mutated.
+///
+/// # Errors
+///
+/// If `ptr` is null, or the library panics, this function returns a null pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_cliques(ptr: *const CliqueIndex<Uuid>) -> *mut CliqueSetC {
+    if ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    // SAFETY: We checked for null above.
+    let index = unsafe { &*ptr };
+    catch_unwind(|| build_clique_set(index.cliques())).unwrap_or(std::ptr::null_mut())
+}
+
+/// The current set of maximal cliques of a [`CliqueIndex`] in a flat layout, borrowed from the
+/// index by `CliqueIndex_cliques_flat`.
+///
+/// The members of the `i`th clique are `uuids[offsets[i]]` up to (but excluding)
+/// `uuids[offsets[i + 1]]`, so `offsets` has `len + 1` entries.
+#[derive(Debug)]
+#[repr(C)]
+pub struct FlatCliquesC {
+    /// Pointer to the members of all of the cliques, stored contiguously.
+    pub uuids: *const UuidC,
+    /// Number of UUIDs.
+    pub uuids_len: usize,
+    /// Pointer to the offset of each clique into `uuids`, followed by `uuids_len`.
+    pub offsets: *const usize,
+    /// Number of cliques.
+    pub len: usize,
+}
+
+/// Write the current set of maximal cliques of the [`CliqueIndex`] to `out_cliques`, in a flat
+/// layout which is borrowed from the index.
+///
+/// The layout is cached by the index until its cliques next change, so polling an unchanged index
+/// doesn't allocate or copy anything. See [`CliqueIndex::flat_cliques`].
+///
+/// # Safety
+///
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `out_cliques` must be a valid pointer to writable memory for a `FlatCliquesC`.
+/// - The pointers written to `out_cliques` are owned by the index. They must not be freed, and
+///   are only valid until the index is next modified or freed.
+///
+/// # Errors
+///
+/// Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+///
+/// `out_cliques` is not written to unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_cliques_flat(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
+    out_cliques: *mut FlatCliquesC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if out_cliques.is_null() {
+            return Err(Error::null_pointer("out_cliques"));
+        }
+        let flat = unsafe { &*clique_index_ptr }.flat_cliques();
+        let cliques = FlatCliquesC {
+            // `Uuid` is a transparent wrapper around its 16 bytes
+            uuids: flat.ids().as_ptr().cast::<UuidC>(),
+            uuids_len: flat.ids().len(),
+            offsets: flat.offsets().as_ptr(),
+            len: flat.len(),
+        };
+        unsafe { out_cliques.write(cliques) };
+        Ok(())
+    })
+}
+
+/// Returns the cliques of the [`CliqueIndex`] with at least one member within the given bounding
+/// box (inclusive).
+///
+/// Each clique is returned in full, including any members outside the bounds. See
+/// [`CliqueIndex::cliques_in_region`].
+///
+/// # Safety
+///
+/// - `ptr` must be a valid, non-null pointer to a [`CliqueIndex<Uuid>`] allocated by this library.
+/// - The caller takes ownership of the returned pointer and is responsible for freeing it using
+///   [`CliqueSetC_free`] to avoid memory leaks.
+///
+/// # Errors
+///
+/// If `ptr` is null, or the library panics, this function returns a null pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_cliques_in_region(
+    ptr: *const CliqueIndex<Uuid>,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+) -> *mut CliqueSetC {
+    if ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    // SAFETY: We checked for null above.
+    let index = unsafe { &*ptr };
+    catch_unwind(|| {
+        let bounds = BoundingBox::from_corners((min_x, min_y), (max_x, max_y));
+        build_clique_set(&index.cliques_in_region(bounds))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+fn build_clique_set(cliques: &CliqueSet<Uuid>) -> *mut CliqueSetC {
+    // Build a vector of `CliqueC` entries with raw UUID arrays.
+    let clique_cs: Box<[CliqueC]> = cliques
+        .iter()
+        .map(|clique| {
+            let uuids: Box<[UuidC]> = clique.iter().map(|id| *id.as_bytes()).collect();
+            let len = uuids.len();
+            // Leak the UUIDs; they are reclaimed by `CliqueSetC_free`
+            let ptr = Box::into_raw(uuids).cast::<UuidC>();
+            CliqueC { uuids: ptr, len }
+        })
+        .collect();
+
+    // Leak the `CliqueC` array; it is reclaimed by `CliqueSetC_free`
+    let len = clique_cs.len();
+    let clique_ptr = Box::into_raw(clique_cs).cast::<CliqueC>();
+
+    // Box and return the outer structure
+    let result = Box::new(CliqueSetC {
+        cliques: clique_ptr,
+        len,
+    });
+
+    Box::into_raw(result)
+}
+
+/// A cursor over the cliques of a [`CliqueIndex`], created by `CliqueIndex_cliques_iter_new`.
+///
+/// This is opaque to C callers.
+#[derive(Debug)]
+pub struct CliqueIter {
+    index: *const CliqueIndex<Uuid>,
+    next: usize,
+}
+
+/// Create a cursor which yields the cliques of the [`CliqueIndex`] one at a time.
+///
+/// Unlike [`CliqueIndex_cliques`], this does not allocate a copy of the entire clique set.
+///
+/// # Safety
+///
+/// - `ptr` must be a valid pointer to a [`CliqueIndex<Uuid>`] allocated by this library.
+/// - The index must outlive the cursor, and must not be modified while the cursor is in use.
+/// - The returned pointer must be freed with [`CliqueIndex_cliques_iter_free`].
+///
+/// # Errors
+///
+/// If `ptr` is null, this function returns a null pointer.
 #[unsafe(no_mangle)]
-/// Returns the chi-squared confidence threshold at 90% for 2D observations.
-pub const extern "C" fn CliqueIndex_chi2_confidence_90() -> f64 {
-    CHI2_2D_CONFIDENCE_90
+pub unsafe extern "C" fn CliqueIndex_cliques_iter_new(
+    ptr: *const CliqueIndex<Uuid>,
+) -> *mut CliqueIter {
+    if ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(CliqueIter {
+        index: ptr,
+        next: 0,
+    }))
 }
 
+/// Copy the next clique from the cursor into a caller-supplied buffer of UUIDs.
+///
+/// On success, the number of UUIDs written to `buffer` is written to `out_len`.
+///
+/// # Safety
+///
+/// - `iter` must be a valid pointer returned by [`CliqueIndex_cliques_iter_new`], whose index is
+///   still valid and has not been modified since the cursor was created.
+/// - `buffer` must be valid for writes of `capacity` 16-byte UUIDs (it may be null if `capacity == 0`).
+/// - `out_len` must be a valid pointer to writable memory for a `size_t`.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `iter` or `out_len` is null, or if `buffer` is null
+///   and `capacity > 0`.
+/// - Returns [`CliqueStatus::Exhausted`] if there are no more cliques.
+/// - Returns [`CliqueStatus::BufferTooSmall`] if the next clique has more than `capacity`
+///   members. The required capacity is written to `out_len`, and the cursor is not advanced, so
+///   the call may be retried with a larger buffer.
 #[unsafe(no_mangle)]
-/// Returns the chi-squared confidence threshold at 95% for 2D observations.
-pub const extern "C" fn CliqueIndex_chi2_confidence_95() -> f64 {
-    CHI2_2D_CONFIDENCE_95
+pub unsafe extern "C" fn CliqueIndex_cliques_iter_next(
+    iter: *mut CliqueIter,
+    buffer: *mut UuidC,
+    capacity: usize,
+    out_len: *mut usize,
+) -> CliqueStatus {
+    guard(|| {
+        if iter.is_null() {
+            return Err(Error::null_pointer("iter"));
+        }
+        if out_len.is_null() {
+            return Err(Error::null_pointer("out_len"));
+        }
+        if buffer.is_null() && capacity > 0 {
+            return Err(Error::null_pointer("buffer"));
+        }
+        let iter = unsafe { &mut *iter };
+        let index = unsafe { &*iter.index };
+
+        let Some(clique) = index.cliques().get(iter.next) else {
+            return Err(Error::new(CliqueStatus::Exhausted, "no more cliques"));
+        };
+        if clique.len() > capacity {
+            unsafe { out_len.write(clique.len()) };
+            return Err(Error::new(
+                CliqueStatus::BufferTooSmall,
+                format_args!(
+                    "clique has {} members, but the buffer has capacity for {capacity}",
+                    clique.len()
+                ),
+            ));
+        }
+
+        for (i, id) in clique.iter().enumerate() {
+            unsafe { buffer.add(i).write(*id.as_bytes()) };
+        }
+        unsafe { out_len.write(clique.len()) };
+        iter.next += 1;
+        Ok(())
+    })
 }
 
+/// Free a cursor created by [`CliqueIndex_cliques_iter_new`].
+///
+/// # Safety
+///
+/// `iter` must have been returned by [`CliqueIndex_cliques_iter_new`] and not already freed.
+/// Passing a null pointer is a no-op.
 #[unsafe(no_mangle)]
-/// Returns the chi-squared confidence threshold at 99% for 2D observations.
-pub const extern "C" fn CliqueIndex_chi2_confidence_99() -> f64 {
-    CHI2_2D_CONFIDENCE_99
+pub unsafe extern "C" fn CliqueIndex_cliques_iter_free(iter: *mut CliqueIter) {
+    if !iter.is_null() {
+        drop(unsafe { Box::from_raw(iter) });
+    }
 }
 
-type UuidC = [u8; 16];
-
-#[derive(Debug, Clone)]
+/// The fused estimate of a single clique.
+#[derive(Debug)]
 #[repr(C)]
-/// C-compatible observation data with covariance and optional context.
-pub struct ObservationC {
-    /// Observation UUID (16 bytes).
-    pub id: UuidC,
-    /// X coordinate.
+pub struct FusedEstimateC {
+    /// Pointer to an array of 16-byte UUIDs of the fused observations.
+    pub uuids: *const UuidC,
+    /// Number of UUIDs.
+    pub len: usize,
+    /// Fused X coordinate.
     pub x: f64,
-    /// Y coordinate.
+    /// Fused Y coordinate.
     pub y: f64,
-    /// Covariance XX term.
+    /// Fused covariance XX term.
     pub cov_xx: f64,
-    /// Covariance XY term.
+    /// Fused covariance XY term.
     pub cov_xy: f64,
-    /// Covariance YY term.
+    /// Fused covariance YY term.
     pub cov_yy: f64,
-    /// Optional context UUID; a nil UUID is treated as no context.
-    pub context: UuidC,
+    /// Residual chi-squared statistic of the members about the fused position.
+    pub chi2_statistic: f64,
+    /// Probability of a statistic at least this large if the members are consistent.
+    pub p_value: f64,
+    /// Degrees of freedom of the statistic.
+    pub degrees_of_freedom: u32,
 }
 
-const fn parse_uuid(bytes: UuidC) -> Option<Uuid> {
-    let uuid = Uuid::from_bytes(bytes);
-    if uuid.is_nil() { None } else { Some(uuid) }
+/// A set of fused estimates returned by `CliqueIndex_fused_estimates`.
+#[derive(Debug)]
+#[repr(C)]
+pub struct FusedSetC {
+    /// Pointer to an array of `FusedEstimateC` structures.
+    pub estimates: *const FusedEstimateC,
+    /// Number of estimates in the set.
+    pub len: usize,
 }
 
-impl From<ObservationC> for Unique<Observation, Uuid> {
-    fn from(obs_c: ObservationC) -> Self {
-        let id = Uuid::from_bytes(obs_c.id);
-        let error = CovarianceMatrix::new_unchecked(obs_c.cov_xx, obs_c.cov_yy, obs_c.cov_xy);
-
-        let mut observation_builder = Observation::builder(obs_c.x, obs_c.y).error(error);
-        if let Some(context) = parse_uuid(obs_c.context) {
-            observation_builder = observation_builder.context(context);
-        }
-        Self {
-            id,
-            data: observation_builder.build(),
-        }
+/// Returns the fused estimate of each of the current cliques in the [`CliqueIndex`].
+///
+/// The estimates are returned in the same order as the cliques returned by [`CliqueIndex_cliques`].
+///
+/// # Safety
+///
+/// - `ptr` must be a valid, non-null pointer to a [`CliqueIndex<Uuid>`] allocated by this library.
+/// - The caller takes ownership of the returned pointer and is responsible for freeing it using
+///   [`FusedSetC_free`] to avoid memory leaks.
+/// - The returned structure points to heap-allocated memory and must not be mutated.
+///
+/// # Errors
+///
+/// If `ptr` is null, or the library panics, this function returns a null pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_fused_estimates(
+    ptr: *const CliqueIndex<Uuid>,
+) -> *mut FusedSetC {
+    if ptr.is_null() {
+        return std::ptr::null_mut();
     }
+
+    // SAFETY: We checked for null above.
+    let index = unsafe { &*ptr };
+    catch_unwind(|| build_fused_set(index)).unwrap_or(std::ptr::null_mut())
 }
 
-/// Initialise a new [`CliqueIndex`].
+fn build_fused_set(index: &CliqueIndex<Uuid>) -> *mut FusedSetC {
+    let estimates: Box<[FusedEstimateC]> = index
+        .fused_estimates()
+        .into_iter()
+        .map(|estimate| {
+            let uuids: Box<[UuidC]> = estimate.members.iter().map(|id| *id.as_bytes()).collect();
+            let len = uuids.len();
+            // Leak the UUIDs; they are reclaimed by `FusedSetC_free`
+            let uuids = Box::into_raw(uuids).cast::<UuidC>();
+            FusedEstimateC {
+                uuids,
+                len,
+                x: estimate.x,
+                y: estimate.y,
+                cov_xx: estimate.covariance.xx(),
+                cov_xy: estimate.covariance.xy(),
+                cov_yy: estimate.covariance.yy(),
+                chi2_statistic: estimate.goodness_of_fit.statistic,
+                p_value: estimate.goodness_of_fit.p_value,
+                degrees_of_freedom: estimate.goodness_of_fit.degrees_of_freedom,
+            }
+        })
+        .collect();
+
+    // Leak the `FusedEstimateC` array; it is reclaimed by `FusedSetC_free`
+    let len = estimates.len();
+    let estimates = Box::into_raw(estimates).cast::<FusedEstimateC>();
+
+    Box::into_raw(Box::new(FusedSetC { estimates, len }))
+}
+
+/// Frees memory previously allocated by `CliqueIndex_fused_estimates`.
+///
+/// # Safety
+///
+/// - `ptr` must be a valid pointer returned by `CliqueIndex_fused_estimates` and must not be used again after calling this.
+/// - This function **must not** be called on any pointer not allocated by the library.
 #[unsafe(no_mangle)]
-pub extern "C" fn CliqueIndex_new(chi2: f64) -> *mut CliqueIndex<Uuid> {
-    Box::into_raw(Box::new(CliqueIndex::new(chi2)))
+pub unsafe extern "C" fn FusedSetC_free(ptr: *mut FusedSetC) {
+    if ptr.is_null() {
+        return;
+    }
+
+    let boxed = unsafe { Box::from_raw(ptr) };
+    let estimates = unsafe { boxed_slice_from_raw(boxed.estimates, boxed.len) };
+    for estimate in estimates {
+        drop(unsafe { boxed_slice_from_raw(estimate.uuids, estimate.len) });
+    }
 }
 
-/// Initialise a new [`CliqueIndex`] from a list of observations.
+/// Inexpensive statistics of a [`CliqueIndex`], returned by `CliqueIndex_stats`.
+#[derive(Debug, Default)]
+#[repr(C)]
+pub struct CliqueStatsC {
+    /// The total number of observations in the index.
+    pub total_observations: usize,
+    /// The number of observations which are compatible with at least one other observation.
+    pub connected_observations: usize,
+    /// The number of edges in the compatibility graph.
+    pub compatibility_edges: usize,
+    /// The number of maximal cliques.
+    pub cliques: usize,
+    /// The number of members of the largest clique, or zero if there are no cliques.
+    pub largest_clique: usize,
+    /// The number of observations whose cliques have been approximated.
+    pub approximate_observations: usize,
+    /// A counter which is incremented each time the index is modified.
+    pub generation: u64,
+}
+
+/// Write inexpensive statistics of the [`CliqueIndex`] to `out_stats`, for monitoring.
 ///
-/// This is faster than creating an empty index and adding the observations one at a time.
+/// This does not copy any cliques, so is cheap enough to poll.
 ///
 /// # Safety
 ///
-/// - `observations` must be a valid pointer to `len` contiguous `ObservationC` structs.
-/// - `observations` must not be null unless `len == 0`.
-/// - The memory referenced by `observations` must remain valid for the duration of the call.
-/// - The returned pointer must be freed with `CliqueIndex_free` when no longer needed.
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `out_stats` must be a valid pointer to writable memory for a `CliqueStatsC`.
 ///
 /// # Errors
 ///
-/// - If `observations` is null and `len > 0`, this function returns a null pointer.
-///   The caller should check the return value before using it.
+/// Returns [`CliqueStatus::NullPointer`] if either pointer is null.
 ///
-/// # Example
-/// ```c
-/// CliqueIndex* idx = CliqueIndex_from_observations(chi2, obs_array, len);
-/// if (idx == NULL) {
-///     // Handle error
-/// }
-/// ```
+/// `out_stats` is not written to unless the function returns [`CliqueStatus::Ok`].
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn CliqueIndex_from_observations(
-    chi2: f64,
-    observations: *const ObservationC,
-    len: usize,
-) -> *mut CliqueIndex<Uuid> {
-    if observations.is_null() {
-        return std::ptr::null_mut();
-    }
-    let obs_slice = unsafe { std::slice::from_raw_parts(observations, len) };
-    let rust_obs = obs_slice
-        .iter()
-        .cloned()
-        .map(Unique::<Observation, Uuid>::from)
-        .collect();
-    Box::into_raw(Box::new(CliqueIndex::from_observations(rust_obs, chi2)))
+pub unsafe extern "C" fn CliqueIndex_stats(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
+    out_stats: *mut CliqueStatsC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if out_stats.is_null() {
+            return Err(Error::null_pointer("out_stats"));
+        }
+        let health = unsafe { &*clique_index_ptr }.health();
+        let stats = CliqueStatsC {
+            total_observations: health.total_observations,
+            connected_observations: health.connected_observations,
+            compatibility_edges: health.compatibility_edges,
+            cliques: health.cliques,
+            largest_clique: health.largest_clique,
+            approximate_observations: health.approximate_observations,
+            generation: health.generation,
+        };
+        unsafe { out_stats.write(stats) };
+        Ok(())
+    })
 }
 
-#[unsafe(no_mangle)]
-/// Insert an observation into an existing [`CliqueIndex`].
+/// A list of observation UUIDs, returned by `CliqueIndex_probe`.
+#[derive(Debug)]
+#[repr(C)]
+pub struct UuidListC {
+    /// Pointer to an array of 16-byte UUIDs.
+    pub uuids: *const UuidC,
+    /// Number of UUIDs in the list.
+    pub len: usize,
+}
+
+/// Find the observations in the [`CliqueIndex`] which an observation would be compatible with,
+/// without inserting it.
 ///
-/// Note that it is quicker to create a [`CliqueIndex`] from a batch of observations using
-/// [`CliqueIndex_from_observations`], but this function is useful for incrementally adding observations.
+/// On success, the UUIDs of the compatible observations are written to `out_compatible_ids`,
+/// nearest first. An observation in the index with the same ID is excluded. The index is not
+/// modified.
+///
+/// See [`CliqueIndex::probe`].
 ///
 /// # Safety
 ///
-/// - `clique_index_ptr` must be a valid, non-null pointer to a `CliqueIndex<Uuid>`.
-/// - `observation` must be a valid, non-null pointer to an `ObservationC`.
-/// - The caller must ensure that no other references (mutable or immutable) to the `CliqueIndex`
-///   exist for the duration of the call (i.e., uphold Rust aliasing rules).
+/// - `clique_index_ptr` must be a valid pointer to a `CliqueIndex<Uuid>`.
+/// - `observation` must be a valid pointer to an `ObservationC`.
+/// - `out_compatible_ids` must be a valid pointer to writable memory for a `UuidListC*`.
+/// - The list written to `out_compatible_ids` must be freed with [`UuidListC_free`].
 ///
 /// # Errors
 ///
-/// - If either pointer is null, this function does nothing.
+/// - Returns [`CliqueStatus::NullPointer`] if any pointer is null.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if the observation is invalid.
 ///
-/// This function does not take ownership of `clique_index_ptr`; it modifies the pointed-to object
-/// in-place. The pointer remains valid after the call.
-pub unsafe extern "C" fn CliqueIndex_insert(
-    clique_index_ptr: *mut CliqueIndex<Uuid>,
+/// `out_compatible_ids` is not written to unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndex_probe(
+    clique_index_ptr: *const CliqueIndex<Uuid>,
     observation: *const ObservationC,
-) {
-    if clique_index_ptr.is_null() || observation.is_null() {
+    out_compatible_ids: *mut *mut UuidListC,
+) -> CliqueStatus {
+    guard(|| {
+        if clique_index_ptr.is_null() {
+            return Err(Error::null_pointer("clique_index_ptr"));
+        }
+        if observation.is_null() {
+            return Err(Error::null_pointer("observation"));
+        }
+        if out_compatible_ids.is_null() {
+            return Err(Error::null_pointer("out_compatible_ids"));
+        }
+        let clique_index = unsafe { &*clique_index_ptr };
+        let rust_obs = unsafe { &*observation }.to_observation()?;
+
+        let uuids: Box<[UuidC]> = clique_index
+            .probe(&rust_obs)
+            .into_iter()
+            .map(|(id, _)| *id.as_bytes())
+            .collect();
+
+        // Leak the UUID array; it is reclaimed by `UuidListC_free`
+        let len = uuids.len();
+        let uuids = Box::into_raw(uuids).cast::<UuidC>();
+        unsafe { out_compatible_ids.write(Box::into_raw(Box::new(UuidListC { uuids, len }))) };
+        Ok(())
+    })
+}
+
+/// Frees memory previously allocated by `CliqueIndex_probe`.
+///
+/// # Safety
+///
+/// - `ptr` must be a valid pointer returned by `CliqueIndex_probe` and must not be used again after calling this.
+/// - This function **must not** be called on any pointer not allocated by the library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn UuidListC_free(ptr: *mut UuidListC) {
+    if ptr.is_null() {
         return;
     }
 
-    let clique_index = unsafe { &mut *clique_index_ptr };
-    let rust_obs = Unique::<Observation, Uuid>::from(unsafe { (*observation).clone() });
-    clique_index.insert(rust_obs);
+    let boxed = unsafe { Box::from_raw(ptr) };
+    drop(unsafe { boxed_slice_from_raw(boxed.uuids, boxed.len) });
 }
 
-/// A single clique: a set of UUIDs (observations) belonging to the same maximal clique.
-///
-/// # Fields
-/// - `uuids`: A pointer to an array of 16-byte UUIDs. Must be valid for reads.
-/// - `len`: The number of UUIDs in this clique.
+/// A single edge of the compatibility graph, between two mutually compatible observations.
 #[derive(Debug)]
 #[repr(C)]
-pub struct CliqueC {
-    /// Pointer to an array of 16-byte UUIDs.
-    pub uuids: *const UuidC,
-    /// Number of UUIDs in the clique.
-    pub len: usize,
+pub struct EdgeC {
+    /// UUID of the first observation.
+    pub a: UuidC,
+    /// UUID of the second observation.
+    pub b: UuidC,
+    /// Squared Mahalanobis distance between the observations, under the sum of their covariances.
+    pub mahalanobis_squared: f64,
 }
 
-/// A set of maximal cliques returned by `CliqueIndex_cliques`.
-///
-/// # Fields
-/// - `cliques`: Pointer to an array of [`CliqueC`] structures.
-/// - `len`: Number of cliques in the set.
+/// The edges of the compatibility graph, returned by `CliqueIndex_edges`.
 #[derive(Debug)]
 #[repr(C)]
-pub struct CliqueSetC {
-    /// Pointer to an array of `CliqueC` structures.
-    pub cliques: *const CliqueC,
-    /// Number of cliques in the set.
+pub struct EdgeListC {
+    /// Pointer to an array of `EdgeC` structures.
+    pub edges: *const EdgeC,
+    /// Number of edges in the list.
     pub len: usize,
 }
 
-/// Frees memory previously allocated by `CliqueIndex_cliques`.
+/// Returns the edges of the compatibility graph of the [`CliqueIndex`].
 ///
-/// This deallocates all memory owned by the `CliqueSetC`, including:
-/// - Each inner list of UUIDs (allocated as `Vec<[u8; 16]>`)
-/// - The outer array of `CliqueC`
+/// Each edge links a pair of mutually compatible observations, and appears exactly once.
 ///
 /// # Safety
 ///
-/// - `ptr` must be a valid pointer returned by `CliqueIndex_cliques` and must not be used again after calling this.
-/// - The caller must ensure that no aliasing or use-after-free occurs.
-/// - This function **must not** be called on any pointer not allocated by the library.
+/// - `ptr` must be a valid, non-null pointer to a [`CliqueIndex<Uuid>`] allocated by this library.
+/// - The caller takes ownership of the returned pointer and is responsible for freeing it using
+///   [`EdgeListC_free`] to avoid memory leaks.
 ///
-/// # Example (C side)
-/// ```c
-/// CliqueSetC* result = CliqueIndex_cliques(index);
-/// // ... use result ...
-/// CliqueSetC_free(result);
-/// ```
+/// # Errors
+///
+/// If `ptr` is null, or the library panics, this function returns a null pointer.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn CliqueSetC_free(ptr: *mut CliqueSetC) {
+pub unsafe extern "C" fn CliqueIndex_edges(ptr: *const CliqueIndex<Uuid>) -> *mut EdgeListC {
     if ptr.is_null() {
-        return;
+        return std::ptr::null_mut();
     }
 
-    let boxed = unsafe { Box::from_raw(ptr) };
+    // SAFETY: We checked for null above.
+    let index = unsafe { &*ptr };
+    catch_unwind(|| {
+        let edges: Box<[EdgeC]> = index
+            .compatibility_edges()
+            .into_iter()
+            .map(|(a, b, mahalanobis_squared)| EdgeC {
+                a: *a.as_bytes(),
+                b: *b.as_bytes(),
+                mahalanobis_squared,
+            })
+            .collect();
 
-    // Fully reconstruct the outer Vec<CliqueC>
-    let cliques_vec =
-        unsafe { Vec::from_raw_parts(boxed.cliques.cast_mut(), boxed.len, boxed.len) };
+        // Leak the `EdgeC` array; it is reclaimed by `EdgeListC_free`
+        let len = edges.len();
+        let edges = Box::into_raw(edges).cast::<EdgeC>();
+        Box::into_raw(Box::new(EdgeListC { edges, len }))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
 
-    for clique in cliques_vec {
-        // Reconstruct and drop the inner UUID arrays
-        let _ = unsafe { Vec::from_raw_parts(clique.uuids.cast_mut(), clique.len, clique.len) };
+/// Frees memory previously allocated by `CliqueIndex_edges`.
+///
+/// # Safety
+///
+/// - `ptr` must be a valid pointer returned by `CliqueIndex_edges` and must not be used again after calling this.
+/// - This function **must not** be called on any pointer not allocated by the library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn EdgeListC_free(ptr: *mut EdgeListC) {
+    if ptr.is_null() {
+        return;
     }
 
-    // `boxed` is dropped here, releasing CliqueSetC itself
+    let boxed = unsafe { Box::from_raw(ptr) };
+    drop(unsafe { boxed_slice_from_raw(boxed.edges, boxed.len) });
 }
 
-/// Returns the current set of maximal cliques from the [`CliqueIndex`].
+/// Reconstruct a boxed slice previously leaked with [`Box::into_raw`].
 ///
-/// Each clique is represented as a set of UUIDs. The result is wrapped in a
-/// [`CliqueSetC`] structure containing an array of [`CliqueC`] entries.
+/// # Safety
+///
+/// `ptr` and `len` must describe a boxed slice leaked by this library, which has not already been freed.
+unsafe fn boxed_slice_from_raw<T>(ptr: *const T, len: usize) -> Box<[T]> {
+    unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr.cast_mut(), len)) }
+}
+
+/// Export the [`CliqueIndex`] as a GeoJSON `FeatureCollection` of its observations, their confidence
+/// ellipses, and its cliques.
+///
+/// See [`CliqueIndex::to_geojson`] for a description of the features.
 ///
 /// # Safety
 ///
 /// - `ptr` must be a valid, non-null pointer to a [`CliqueIndex<Uuid>`] allocated by this library.
-/// - The caller takes ownership of the returned pointer and is responsible for freeing it using
-///   [`CliqueSetC_free`] to avoid memory leaks.
-/// - The returned structure points to heap-allocated memory and must not be mutated.
+/// - The caller takes ownership of the returned nul-terminated string, and must free it with
+///   [`CliqueFusion_string_free`].
 ///
 /// # Errors
 ///
-/// If `ptr` is null, this function returns a null pointer.
+/// If `ptr` is null, or the library panics, this function returns a null pointer.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn CliqueIndex_cliques(ptr: *const CliqueIndex<Uuid>) -> *mut CliqueSetC {
+pub unsafe extern "C" fn CliqueIndex_to_geojson(ptr: *const CliqueIndex<Uuid>) -> *mut c_char {
     if ptr.is_null() {
         return std::ptr::null_mut();
     }
 
-    // SAFETY: We checked for null above.
     let index = unsafe { &*ptr };
-    let cliques = index.cliques();
-
-    // Build a vector of `CliqueC` entries with raw UUID arrays.
-    let mut clique_cs: Vec<CliqueC> = cliques
-        .iter()
-        .map(|clique| {
-            let mut uuid_vec: Vec<[u8; 16]> = clique.iter().map(|id| *id.as_bytes()).collect();
-            let len = uuid_vec.len();
-            let ptr = uuid_vec.as_mut_ptr();
-            std::mem::forget(uuid_vec); // Prevent Rust from freeing the UUIDs
-            CliqueC { uuids: ptr, len }
-        })
-        .collect();
-
-    // Get raw pointer to the `CliqueC` array
-    let len = clique_cs.len();
-    let clique_ptr = clique_cs.as_mut_ptr();
-    std::mem::forget(clique_cs); // Prevent Rust from freeing the vector
-
-    // Box and return the outer structure
-    let result = Box::new(CliqueSetC {
-        cliques: clique_ptr,
-        len,
-    });
-
-    Box::into_raw(result)
+    catch_unwind(|| {
+        CString::new(index.to_geojson()).map_or(std::ptr::null_mut(), CString::into_raw)
+    })
+    .unwrap_or(std::ptr::null_mut())
 }
 
 /// Free the memory associated with a [`CliqueIndex`].
@@ -281,7 +1518,6 @@ pub unsafe extern "C" fn CliqueIndex_free(ptr: *mut CliqueIndex<Uuid>) {
 mod tests {
     use super::*;
     use approx::assert_relative_eq;
-    use clique_fusion::Observation;
     use uuid::Uuid;
 
     fn sample_uuid() -> Uuid {
@@ -322,7 +1558,7 @@ mod tests {
             context: nil_uuid(),
         };
 
-        let unique: Unique<Observation, Uuid> = obs_c.into();
+        let unique = obs_c.to_observation().unwrap();
 
         assert_eq!(unique.id, id);
         assert_relative_eq!(unique.data.x(), 1.0, epsilon = 1e-12);
@@ -345,11 +1581,42 @@ mod tests {
             context: uuidc_from_uuid(ctx),
         };
 
-        let unique: Unique<Observation, Uuid> = obs_c.into();
+        let unique = obs_c.to_observation().unwrap();
 
         assert_eq!(unique.id, id);
         assert_relative_eq!(unique.data.x(), 3.0, epsilon = 1e-12);
         assert_relative_eq!(unique.data.y(), 4.0, epsilon = 1e-12);
         assert_eq!(unique.data.context(), Some(ctx));
     }
+
+    #[test]
+    fn test_observationc_to_unique_rejects_invalid_input() {
+        let valid = ObservationC {
+            id: uuidc_from_uuid(sample_uuid()),
+            x: 1.0,
+            y: 2.0,
+            cov_xx: 0.1,
+            cov_xy: 0.0,
+            cov_yy: 0.2,
+            context: nil_uuid(),
+        };
+
+        let invalid_covariance = ObservationC {
+            cov_xx: -1.0,
+            ..valid
+        };
+        assert_eq!(
+            invalid_covariance.to_observation().unwrap_err().status,
+            CliqueStatus::InvalidCovariance
+        );
+
+        let invalid_position = ObservationC {
+            x: f64::NAN,
+            ..valid
+        };
+        assert_eq!(
+            invalid_position.to_observation().unwrap_err().status,
+            CliqueStatus::InvalidPosition
+        );
+    }
 }
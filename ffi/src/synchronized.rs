@@ -0,0 +1,265 @@
+//! A thread-safe FFI surface, for hosts which share a single index between threads.
+//!
+//! The `CliqueIndex_*` functions require the caller to uphold Rust's aliasing rules, so concurrent
+//! use of a handle must be synchronized by the caller. The `CliqueIndexTS_*` functions instead
+//! operate on a [`SynchronizedCliqueIndex`], which guards the index with a read-write lock. Queries
+//! may run concurrently with each other, while mutations take exclusive access.
+
+use std::{
+    panic::catch_unwind,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use clique_fusion::CliqueIndex;
+use uuid::Uuid;
+
+use crate::{
+    CliqueSetC, CliqueStatus, Error, ObservationC, UuidC, build_clique_set, duplicate_id, guard,
+    not_found,
+};
+
+/// A [`CliqueIndex`] which may be shared between threads, created by `CliqueIndexTS_new`.
+///
+/// This is opaque to C callers.
+#[derive(Debug)]
+pub struct SynchronizedCliqueIndex(RwLock<CliqueIndex<Uuid>>);
+
+impl SynchronizedCliqueIndex {
+    // A panic while the lock is held is reported to the caller as `CliqueStatus::Panic`, after which
+    // the state of the index is documented as unspecified. There's no need to poison the handle too.
+
+    fn read(&self) -> RwLockReadGuard<'_, CliqueIndex<Uuid>> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, CliqueIndex<Uuid>> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Initialise a new thread-safe [`CliqueIndex`].
+///
+/// On success, the new index is written to `out_index`.
+///
+/// # Safety
+///
+/// - `out_index` must be a valid pointer to writable memory for a `SynchronizedCliqueIndex*`.
+/// - The index written to `out_index` must be freed with `CliqueIndexTS_free` when no longer needed.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `out_index` is null.
+/// - Returns [`CliqueStatus::InvalidChi2`] if `chi2` is not finite and strictly positive.
+///
+/// `out_index` is not written to unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_new(
+    chi2: f64,
+    out_index: *mut *mut SynchronizedCliqueIndex,
+) -> CliqueStatus {
+    guard(|| {
+        if out_index.is_null() {
+            return Err(Error::null_pointer("out_index"));
+        }
+        let index = SynchronizedCliqueIndex(RwLock::new(CliqueIndex::try_new(chi2)?));
+        unsafe { out_index.write(Box::into_raw(Box::new(index))) };
+        Ok(())
+    })
+}
+
+/// Insert an observation into a thread-safe [`CliqueIndex`].
+///
+/// This may be called concurrently with any other `CliqueIndexTS_*` function on the same index,
+/// except `CliqueIndexTS_free`.
+///
+/// # Safety
+///
+/// - `index` must be a valid pointer to a `SynchronizedCliqueIndex`.
+/// - `observation` must be a valid pointer to an `ObservationC`.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::InvalidCovariance`] or [`CliqueStatus::InvalidPosition`] if the observation is invalid.
+/// - Returns [`CliqueStatus::DuplicateId`] if an observation with the same ID is already in the index.
+///
+/// The index is not modified unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_insert(
+    index: *const SynchronizedCliqueIndex,
+    observation: *const ObservationC,
+) -> CliqueStatus {
+    guard(|| {
+        if index.is_null() {
+            return Err(Error::null_pointer("index"));
+        }
+        if observation.is_null() {
+            return Err(Error::null_pointer("observation"));
+        }
+        let rust_obs = unsafe { &*observation }.to_observation()?;
+        let mut clique_index = unsafe { &*index }.write();
+        if clique_index.contains(&rust_obs.id) {
+            return Err(duplicate_id(rust_obs.id));
+        }
+        clique_index.insert(rust_obs);
+        drop(clique_index);
+        Ok(())
+    })
+}
+
+/// Remove an observation from a thread-safe [`CliqueIndex`], updating the affected cliques.
+///
+/// This may be called concurrently with any other `CliqueIndexTS_*` function on the same index,
+/// except `CliqueIndexTS_free`.
+///
+/// # Safety
+///
+/// - `index` must be a valid pointer to a `SynchronizedCliqueIndex`.
+/// - `id` must be a valid pointer to a 16-byte UUID.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if either pointer is null.
+/// - Returns [`CliqueStatus::NotFound`] if no observation with the given ID is in the index.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_remove(
+    index: *const SynchronizedCliqueIndex,
+    id: *const UuidC,
+) -> CliqueStatus {
+    guard(|| {
+        if index.is_null() {
+            return Err(Error::null_pointer("index"));
+        }
+        if id.is_null() {
+            return Err(Error::null_pointer("id"));
+        }
+        let id = Uuid::from_bytes(unsafe { *id });
+        unsafe { &*index }
+            .write()
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| not_found(id))
+    })
+}
+
+/// Returns the chi-squared threshold used by a thread-safe [`CliqueIndex`] to test compatibility.
+///
+/// # Safety
+///
+/// `index` must be null, or a valid pointer to a `SynchronizedCliqueIndex`.
+///
+/// # Errors
+///
+/// Returns NaN if `index` is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_chi2(index: *const SynchronizedCliqueIndex) -> f64 {
+    if index.is_null() {
+        return f64::NAN;
+    }
+    unsafe { &*index }.read().chi2()
+}
+
+/// Change the chi-squared threshold of a thread-safe [`CliqueIndex`], recomputing its cliques.
+///
+/// This may be called concurrently with any other `CliqueIndexTS_*` function on the same index,
+/// except `CliqueIndexTS_free`.
+///
+/// # Safety
+///
+/// `index` must be a valid pointer to a `SynchronizedCliqueIndex`.
+///
+/// # Errors
+///
+/// - Returns [`CliqueStatus::NullPointer`] if `index` is null.
+/// - Returns [`CliqueStatus::InvalidChi2`] if `chi2` is not finite and strictly positive.
+///
+/// The index is not modified unless the function returns [`CliqueStatus::Ok`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_set_chi2(
+    index: *const SynchronizedCliqueIndex,
+    chi2: f64,
+) -> CliqueStatus {
+    guard(|| {
+        if index.is_null() {
+            return Err(Error::null_pointer("index"));
+        }
+        unsafe { &*index }.write().set_chi2(chi2)?;
+        Ok(())
+    })
+}
+
+/// Returns the number of observations in a thread-safe [`CliqueIndex`] which are compatible with at
+/// least one other observation.
+///
+/// # Safety
+///
+/// `index` must be null, or a valid pointer to a `SynchronizedCliqueIndex`.
+///
+/// # Errors
+///
+/// Returns 0 if `index` is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_len(index: *const SynchronizedCliqueIndex) -> usize {
+    if index.is_null() {
+        return 0;
+    }
+    unsafe { &*index }.read().len()
+}
+
+/// Returns `true` if an observation with the given ID is in a thread-safe [`CliqueIndex`].
+///
+/// # Safety
+///
+/// - `index` must be null, or a valid pointer to a `SynchronizedCliqueIndex`.
+/// - `id` must be null, or a valid pointer to a 16-byte UUID.
+///
+/// # Errors
+///
+/// Returns `false` if either pointer is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_contains(
+    index: *const SynchronizedCliqueIndex,
+    id: *const UuidC,
+) -> bool {
+    if index.is_null() || id.is_null() {
+        return false;
+    }
+    let id = Uuid::from_bytes(unsafe { *id });
+    unsafe { &*index }.read().contains(&id)
+}
+
+/// Returns a snapshot of the current set of maximal cliques from a thread-safe [`CliqueIndex`].
+///
+/// # Safety
+///
+/// - `index` must be a valid pointer to a `SynchronizedCliqueIndex`.
+/// - The caller takes ownership of the returned pointer and is responsible for freeing it using
+///   [`CliqueSetC_free`](crate::CliqueSetC_free).
+///
+/// # Errors
+///
+/// If `index` is null, or the library panics, this function returns a null pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_cliques(
+    index: *const SynchronizedCliqueIndex,
+) -> *mut CliqueSetC {
+    if index.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let index = unsafe { &*index };
+    catch_unwind(|| build_clique_set(index.read().cliques())).unwrap_or(std::ptr::null_mut())
+}
+
+/// Free the memory associated with a thread-safe [`CliqueIndex`].
+///
+/// # Safety
+///
+/// - `index` must have been returned by `CliqueIndexTS_new` and not already freed.
+/// - No other thread may be using the index during, or after, the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn CliqueIndexTS_free(index: *mut SynchronizedCliqueIndex) {
+    if !index.is_null() {
+        drop(unsafe { Box::from_raw(index) });
+    }
+}
@@ -0,0 +1,77 @@
+// Smoke test for the C++ wrapper header.
+//
+// Build and run from the repository root, after `cargo build --package clique-fusion-ffi`:
+//
+//     g++ -std=c++20 -Iffi/include ffi/tests/cpp/smoke_test.cpp -Ltarget/debug -lclique_fusion_ffi
+//     LD_LIBRARY_PATH=target/debug ./a.out
+
+#include <clique_fusion.hpp>
+
+#include <cstdlib>
+#include <iostream>
+#include <vector>
+
+namespace {
+
+void require(bool condition, const char* what) {
+    if (!condition) {
+        std::cerr << "check failed: " << what << '\n';
+        std::exit(EXIT_FAILURE);
+    }
+}
+
+clique::Observation observation(std::uint8_t id, double x, double y) {
+    clique::Observation obs{};
+    obs.id[15] = id;
+    obs.x = x;
+    obs.y = y;
+    obs.cov_xx = 1.0;
+    obs.cov_yy = 1.0;
+    return obs;
+}
+
+}  // namespace
+
+int main() {
+    require(clique::abi_version() == clique::expected_abi_version, "ABI version");
+
+    std::vector<clique::Observation> observations{
+        observation(1, 0.0, 0.0),
+        observation(2, 0.5, 0.0),
+        observation(3, 100.0, 100.0),
+    };
+    std::span<const clique::Observation> all(observations);
+
+    clique::Index index(clique::chi2_threshold(0.95), all.first(2));
+    index.insert(all.subspan(2));
+    require(index.total_observations() == 3, "total observations");
+    require(index.size() == 2, "compatible observations");
+
+    clique::CliqueSet cliques = index.cliques();
+    require(cliques.size() == 1, "clique count");
+    for (std::span<const clique::Uuid> clique : cliques) {
+        require(clique.size() == 2, "clique size");
+    }
+
+    try {
+        index.insert(observations[0]);
+        require(false, "duplicate insert throws");
+    } catch (const clique::DuplicateId&) {
+    }
+
+    try {
+        clique::Index invalid(-1.0);
+        require(false, "invalid chi2 throws");
+    } catch (const clique::InvalidArgument&) {
+    }
+
+    require(index.get(observations[0].id).has_value(), "get");
+    require(index.remove(observations[0].id), "remove");
+    require(!index.remove(observations[0].id), "remove missing");
+    require(!index.contains(observations[0].id), "contains");
+
+    clique::Index moved = std::move(index);
+    require(moved.to_geojson().find("FeatureCollection") != std::string::npos, "GeoJSON");
+
+    std::cout << "clique-fusion " << clique::version() << ": ok\n";
+}
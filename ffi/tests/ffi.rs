@@ -1,11 +1,28 @@
 //! Integration tests for the C FFI surface.
 
+use approx::assert_relative_eq;
 use clique_fusion::CHI2_2D_CONFIDENCE_95;
 use clique_fusion_ffi::{
-    CliqueC, CliqueIndex_cliques, CliqueIndex_free, CliqueIndex_from_observations, CliqueSetC_free,
-    ObservationC,
+    ABI_VERSION, CliqueC, CliqueFusion_abi_version, CliqueFusion_chi2_threshold,
+    CliqueFusion_last_error_message, CliqueFusion_string_free, CliqueFusion_version,
+    CliqueIndex_chi2, CliqueIndex_chi2_confidence_95, CliqueIndex_cliques,
+    CliqueIndex_cliques_flat, CliqueIndex_cliques_in_region, CliqueIndex_cliques_into,
+    CliqueIndex_cliques_iter_free, CliqueIndex_cliques_iter_new, CliqueIndex_cliques_iter_next,
+    CliqueIndex_contains, CliqueIndex_edges, CliqueIndex_edges_into, CliqueIndex_free,
+    CliqueIndex_from_observations, CliqueIndex_fused_estimates, CliqueIndex_get,
+    CliqueIndex_get_v2, CliqueIndex_insert, CliqueIndex_insert_v2, CliqueIndex_len,
+    CliqueIndex_new, CliqueIndex_probe, CliqueIndex_remove, CliqueIndex_remove_context,
+    CliqueIndex_set_chi2, CliqueIndex_stats, CliqueIndex_to_geojson,
+    CliqueIndex_total_observations, CliqueIndex_update, CliqueIndex_upsert, CliqueIndexTS_chi2,
+    CliqueIndexTS_cliques, CliqueIndexTS_contains, CliqueIndexTS_free, CliqueIndexTS_insert,
+    CliqueIndexTS_len, CliqueIndexTS_new, CliqueIndexTS_remove, CliqueIndexTS_set_chi2,
+    CliqueIndexU64_cliques, CliqueIndexU64_free, CliqueIndexU64_from_observations,
+    CliqueIndexU64_insert, CliqueIndexU64_len, CliqueIndexU64_new, CliqueIndexU64_remove,
+    CliqueSetC_free, CliqueSetU64C_free, CliqueStatsC, CliqueStatus, EdgeC, EdgeListC_free,
+    FlatCliquesC, FusedSetC_free, ObservationC, ObservationU64C, ObservationV2C,
+    SynchronizedCliqueIndex, UuidListC_free,
 };
-use std::slice;
+use std::{collections::HashSet, ffi::CStr, ptr, slice};
 use uuid::Uuid;
 
 type UuidC = [u8; 16];
@@ -39,13 +56,18 @@ fn test_create_insert_cliques_free() {
 
     let observations = [obs1, obs2, obs3];
 
-    let index_ptr =
-        unsafe { CliqueIndex_from_observations(chi2, observations.as_ptr(), observations.len()) };
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            chi2,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
 
-    assert!(
-        !index_ptr.is_null(),
-        "CliqueIndex_from_observations returned null"
-    );
+    assert_eq!(status, CliqueStatus::Ok);
+    assert!(!index_ptr.is_null());
 
     let clique_set_ptr = unsafe { CliqueIndex_cliques(index_ptr) };
     assert!(
@@ -88,3 +110,1057 @@ fn test_create_insert_cliques_free() {
         CliqueIndex_free(index_ptr);
     }
 }
+
+#[test]
+fn test_remove() {
+    let id1 = Uuid::new_v4();
+    let id2 = Uuid::new_v4();
+    let observations = [
+        make_observation(id1, 1.0, 1.0),
+        make_observation(id2, 1.05, 1.05),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let id = uuid_to_uuidc(id1);
+    assert_eq!(
+        unsafe { CliqueIndex_remove(index_ptr, &raw const id) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_remove(index_ptr, &raw const id) },
+        CliqueStatus::NotFound
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_remove(index_ptr, ptr::null()) },
+        CliqueStatus::NullPointer
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_remove(ptr::null_mut(), &raw const id) },
+        CliqueStatus::NullPointer
+    );
+
+    let clique_set_ptr = unsafe { CliqueIndex_cliques(index_ptr) };
+    assert_eq!(unsafe { (*clique_set_ptr).len }, 0);
+
+    unsafe {
+        CliqueSetC_free(clique_set_ptr);
+        CliqueIndex_free(index_ptr);
+    }
+}
+
+#[test]
+fn test_remove_context() {
+    let retracted = Uuid::new_v4();
+    let retained = Uuid::new_v4();
+    let with_context = |x, context: Uuid| ObservationC {
+        context: uuid_to_uuidc(context),
+        ..make_observation(Uuid::new_v4(), x, 0.0)
+    };
+    let observations = [
+        with_context(0.0, retracted),
+        with_context(10.0, retracted),
+        with_context(0.1, retained),
+        with_context(10.1, retained),
+        make_observation(Uuid::new_v4(), 10.2, 0.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let context = uuid_to_uuidc(retracted);
+    assert_eq!(
+        unsafe { CliqueIndex_remove_context(index_ptr, &raw const context) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(unsafe { CliqueIndex_total_observations(index_ptr) }, 3);
+    assert_eq!(
+        unsafe { CliqueIndex_remove_context(index_ptr, &raw const context) },
+        CliqueStatus::NotFound
+    );
+
+    // observations without a context aren't removed
+    let nil = [0u8; 16];
+    assert_eq!(
+        unsafe { CliqueIndex_remove_context(index_ptr, &raw const nil) },
+        CliqueStatus::NotFound
+    );
+    assert_eq!(unsafe { CliqueIndex_total_observations(index_ptr) }, 3);
+
+    assert_eq!(
+        unsafe { CliqueIndex_remove_context(index_ptr, ptr::null()) },
+        CliqueStatus::NullPointer
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_remove_context(ptr::null_mut(), &raw const context) },
+        CliqueStatus::NullPointer
+    );
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_update_and_upsert() {
+    let id1 = Uuid::new_v4();
+    let id2 = Uuid::new_v4();
+    let observations = [
+        make_observation(id1, 1.0, 1.0),
+        make_observation(id2, 10.0, 10.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+    let cliques = |index_ptr| unsafe {
+        let clique_set_ptr = CliqueIndex_cliques(index_ptr);
+        let len = (*clique_set_ptr).len;
+        CliqueSetC_free(clique_set_ptr);
+        len
+    };
+    assert_eq!(cliques(index_ptr), 0);
+
+    // a refined position brings the observations together
+    let refined = make_observation(id2, 1.05, 1.05);
+    assert_eq!(
+        unsafe { CliqueIndex_update(index_ptr, &raw const refined) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(cliques(index_ptr), 1);
+    assert_eq!(unsafe { CliqueIndex_total_observations(index_ptr) }, 2);
+
+    let unknown = make_observation(Uuid::new_v4(), 1.0, 1.0);
+    assert_eq!(
+        unsafe { CliqueIndex_update(index_ptr, &raw const unknown) },
+        CliqueStatus::NotFound
+    );
+    assert_eq!(unsafe { CliqueIndex_total_observations(index_ptr) }, 2);
+
+    // upserting inserts new observations, and replaces existing ones
+    assert_eq!(
+        unsafe { CliqueIndex_upsert(index_ptr, &raw const unknown) },
+        CliqueStatus::Ok
+    );
+    let moved = make_observation(id2, 10.0, 10.0);
+    assert_eq!(
+        unsafe { CliqueIndex_upsert(index_ptr, &raw const moved) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(unsafe { CliqueIndex_total_observations(index_ptr) }, 3);
+    assert_eq!(cliques(index_ptr), 1);
+
+    let mut invalid = make_observation(id1, 1.0, 1.0);
+    invalid.cov_xx = -1.0;
+    assert_eq!(
+        unsafe { CliqueIndex_update(index_ptr, &raw const invalid) },
+        CliqueStatus::InvalidCovariance
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_upsert(index_ptr, ptr::null()) },
+        CliqueStatus::NullPointer
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_update(ptr::null_mut(), &raw const refined) },
+        CliqueStatus::NullPointer
+    );
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_set_chi2() {
+    let observations = [
+        make_observation(Uuid::new_v4(), 0.0, 0.0),
+        make_observation(Uuid::new_v4(), 1.0, 0.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+    assert_eq!(unsafe { CliqueIndex_len(index_ptr) }, 2);
+
+    // The squared Mahalanobis distance between the observations is 0.5
+    assert_eq!(
+        unsafe { CliqueIndex_set_chi2(index_ptr, 0.1) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(unsafe { CliqueIndex_len(index_ptr) }, 0);
+    assert_eq!(unsafe { CliqueIndex_total_observations(index_ptr) }, 2);
+    assert_eq!(
+        unsafe { CliqueIndex_chi2(index_ptr) }.to_bits(),
+        0.1_f64.to_bits()
+    );
+
+    assert_eq!(
+        unsafe { CliqueIndex_set_chi2(index_ptr, -1.0) },
+        CliqueStatus::InvalidChi2
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_chi2(index_ptr) }.to_bits(),
+        0.1_f64.to_bits()
+    );
+    assert!(unsafe { CliqueIndex_chi2(ptr::null()) }.is_nan());
+    assert_eq!(
+        unsafe { CliqueIndex_set_chi2(ptr::null_mut(), CHI2_2D_CONFIDENCE_95) },
+        CliqueStatus::NullPointer
+    );
+
+    assert_eq!(
+        unsafe { CliqueIndex_set_chi2(index_ptr, CHI2_2D_CONFIDENCE_95) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(unsafe { CliqueIndex_len(index_ptr) }, 2);
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_status_codes() {
+    let mut index_ptr = ptr::null_mut();
+    assert_eq!(
+        unsafe { CliqueIndex_new(-1.0, &raw mut index_ptr) },
+        CliqueStatus::InvalidChi2
+    );
+    assert!(index_ptr.is_null());
+    assert_eq!(
+        unsafe { CliqueIndex_new(CHI2_2D_CONFIDENCE_95, ptr::null_mut()) },
+        CliqueStatus::NullPointer
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_new(CHI2_2D_CONFIDENCE_95, &raw mut index_ptr) },
+        CliqueStatus::Ok
+    );
+
+    let observation = make_observation(Uuid::new_v4(), 0.0, 0.0);
+    assert_eq!(
+        unsafe { CliqueIndex_insert(index_ptr, &raw const observation) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_insert(index_ptr, &raw const observation) },
+        CliqueStatus::DuplicateId
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_insert(index_ptr, ptr::null()) },
+        CliqueStatus::NullPointer
+    );
+
+    let invalid = ObservationC {
+        cov_xy: 10.0,
+        ..make_observation(Uuid::new_v4(), 0.0, 0.0)
+    };
+    assert_eq!(
+        unsafe { CliqueIndex_insert(index_ptr, &raw const invalid) },
+        CliqueStatus::InvalidCovariance
+    );
+    let message_ptr = CliqueFusion_last_error_message();
+    let message = unsafe { CStr::from_ptr(message_ptr) }.to_str().unwrap();
+    assert!(message.contains("xy: 10"), "unexpected message: {message}");
+    unsafe { CliqueFusion_string_free(message_ptr) };
+
+    let duplicated = [observation.clone(), observation];
+    let mut batch_ptr = ptr::null_mut();
+    assert_eq!(
+        unsafe {
+            CliqueIndex_from_observations(
+                CHI2_2D_CONFIDENCE_95,
+                duplicated.as_ptr(),
+                duplicated.len(),
+                &raw mut batch_ptr,
+            )
+        },
+        CliqueStatus::DuplicateId
+    );
+    assert!(batch_ptr.is_null());
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_fused_estimates() {
+    let id1 = Uuid::new_v4();
+    let id2 = Uuid::new_v4();
+    let observations = [
+        make_observation(id1, 1.0, 1.0),
+        make_observation(id2, 1.2, 1.4),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let fused_ptr = unsafe { CliqueIndex_fused_estimates(index_ptr) };
+    assert!(!fused_ptr.is_null());
+
+    let fused = unsafe { &*fused_ptr };
+    let estimates = unsafe { slice::from_raw_parts(fused.estimates, fused.len) };
+    assert_eq!(estimates.len(), 1);
+
+    let estimate = &estimates[0];
+    let ids = unsafe { slice::from_raw_parts(estimate.uuids, estimate.len) };
+    assert_eq!(ids.len(), 2);
+    assert!((estimate.x - 1.1).abs() < 1e-12);
+    assert!((estimate.y - 1.2).abs() < 1e-12);
+    assert!((estimate.cov_xx - 0.5).abs() < 1e-12);
+    assert!((estimate.cov_yy - 0.5).abs() < 1e-12);
+    assert!(estimate.cov_xy.abs() < 1e-12);
+    assert!((estimate.chi2_statistic - 0.1).abs() < 1e-12);
+    assert_eq!(estimate.degrees_of_freedom, 2);
+    assert!((estimate.p_value - (-0.05_f64).exp()).abs() < 1e-12);
+
+    unsafe {
+        FusedSetC_free(fused_ptr);
+        CliqueIndex_free(index_ptr);
+    }
+    assert!(unsafe { CliqueIndex_fused_estimates(ptr::null()) }.is_null());
+}
+
+#[test]
+fn test_edges() {
+    let id1 = Uuid::new_v4();
+    let id2 = Uuid::new_v4();
+    let id3 = Uuid::new_v4();
+    let observations = [
+        make_observation(id1, 0.0, 0.0),
+        make_observation(id2, 1.0, 0.0),
+        make_observation(id3, 50.0, 50.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let edges_ptr = unsafe { CliqueIndex_edges(index_ptr) };
+    assert!(!edges_ptr.is_null());
+
+    let edge_list = unsafe { &*edges_ptr };
+    let edges = unsafe { slice::from_raw_parts(edge_list.edges, edge_list.len) };
+    assert_eq!(edges.len(), 1);
+
+    let edge = &edges[0];
+    let mut ids = [Uuid::from_bytes(edge.a), Uuid::from_bytes(edge.b)];
+    ids.sort();
+    let mut expected = [id1, id2];
+    expected.sort();
+    assert_eq!(ids, expected);
+    // Distance of 1 under a combined covariance of 2I
+    assert!((edge.mahalanobis_squared - 0.5).abs() < 1e-12);
+
+    unsafe {
+        EdgeListC_free(edges_ptr);
+        CliqueIndex_free(index_ptr);
+    }
+}
+
+#[test]
+fn test_lookup_and_counts() {
+    let id1 = Uuid::new_v4();
+    let id2 = Uuid::new_v4();
+    let id3 = Uuid::new_v4();
+    let observations = [
+        make_observation(id1, 0.0, 0.0),
+        make_observation(id2, 1.0, 0.0),
+        make_observation(id3, 50.0, 50.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    assert_eq!(unsafe { CliqueIndex_len(index_ptr) }, 2);
+    assert_eq!(unsafe { CliqueIndex_total_observations(index_ptr) }, 3);
+    assert_eq!(unsafe { CliqueIndex_len(ptr::null()) }, 0);
+
+    let id = uuid_to_uuidc(id3);
+    let missing = uuid_to_uuidc(Uuid::new_v4());
+    assert!(unsafe { CliqueIndex_contains(index_ptr, &raw const id) });
+    assert!(!unsafe { CliqueIndex_contains(index_ptr, &raw const missing) });
+
+    let mut observation = make_observation(Uuid::nil(), 0.0, 0.0);
+    assert_eq!(
+        unsafe { CliqueIndex_get(index_ptr, &raw const id, &raw mut observation) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(observation.id, id);
+    assert!((observation.x - 50.0).abs() < f64::EPSILON);
+    assert!((observation.cov_yy - 1.0).abs() < f64::EPSILON);
+    assert_eq!(
+        unsafe { CliqueIndex_get(index_ptr, &raw const missing, &raw mut observation) },
+        CliqueStatus::NotFound
+    );
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_probe() {
+    let near = Uuid::new_v4();
+    let nearer = Uuid::new_v4();
+    let observations = [
+        make_observation(near, 1.0, 1.0),
+        make_observation(nearer, 0.2, 0.0),
+        make_observation(Uuid::new_v4(), 50.0, 50.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let candidate = make_observation(Uuid::new_v4(), 0.0, 0.0);
+    let mut list_ptr = ptr::null_mut();
+    assert_eq!(
+        unsafe { CliqueIndex_probe(index_ptr, &raw const candidate, &raw mut list_ptr) },
+        CliqueStatus::Ok
+    );
+    let compatible: Vec<Uuid> = unsafe {
+        let list = &*list_ptr;
+        slice::from_raw_parts(list.uuids, list.len)
+            .iter()
+            .map(|bytes| Uuid::from_bytes(*bytes))
+            .collect()
+    };
+    assert_eq!(compatible, vec![nearer, near]);
+    unsafe { UuidListC_free(list_ptr) };
+
+    // the index is not modified
+    assert_eq!(unsafe { CliqueIndex_total_observations(index_ptr) }, 3);
+    assert!(!unsafe { CliqueIndex_contains(index_ptr, &raw const candidate.id) });
+
+    assert_eq!(
+        unsafe { CliqueIndex_probe(index_ptr, ptr::null(), &raw mut list_ptr) },
+        CliqueStatus::NullPointer
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_probe(index_ptr, &raw const candidate, ptr::null_mut()) },
+        CliqueStatus::NullPointer
+    );
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_cliques_in_region() {
+    let observations = [
+        make_observation(Uuid::new_v4(), 0.0, 0.0),
+        make_observation(Uuid::new_v4(), 0.5, 0.0),
+        make_observation(Uuid::new_v4(), 100.0, 0.0),
+        make_observation(Uuid::new_v4(), 100.5, 0.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let clique_set_ptr = unsafe { CliqueIndex_cliques_in_region(index_ptr, -1.0, -1.0, 1.0, 1.0) };
+    assert!(!clique_set_ptr.is_null());
+    let members: HashSet<Uuid> = unsafe {
+        let clique_set = &*clique_set_ptr;
+        assert_eq!(clique_set.len, 1);
+        let clique = &*clique_set.cliques;
+        slice::from_raw_parts(clique.uuids, clique.len)
+            .iter()
+            .map(|bytes| Uuid::from_bytes(*bytes))
+            .collect()
+    };
+    assert_eq!(
+        members,
+        HashSet::from([
+            Uuid::from_bytes(observations[0].id),
+            Uuid::from_bytes(observations[1].id)
+        ])
+    );
+    unsafe { CliqueSetC_free(clique_set_ptr) };
+
+    // the corners may be given in any order
+    let clique_set_ptr =
+        unsafe { CliqueIndex_cliques_in_region(index_ptr, 200.0, 10.0, 50.0, -10.0) };
+    assert_eq!(unsafe { (*clique_set_ptr).len }, 1);
+    unsafe { CliqueSetC_free(clique_set_ptr) };
+
+    let clique_set_ptr =
+        unsafe { CliqueIndex_cliques_in_region(index_ptr, 10.0, 10.0, 20.0, 20.0) };
+    assert_eq!(unsafe { (*clique_set_ptr).len }, 0);
+    unsafe { CliqueSetC_free(clique_set_ptr) };
+
+    assert!(unsafe { CliqueIndex_cliques_in_region(ptr::null(), 0.0, 0.0, 1.0, 1.0) }.is_null());
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_stats() {
+    let observations = [
+        make_observation(Uuid::new_v4(), 0.0, 0.0),
+        make_observation(Uuid::new_v4(), 0.5, 0.0),
+        make_observation(Uuid::new_v4(), 0.0, 0.5),
+        make_observation(Uuid::new_v4(), 50.0, 50.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let mut summary = CliqueStatsC::default();
+    assert_eq!(
+        unsafe { CliqueIndex_stats(index_ptr, &raw mut summary) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(summary.total_observations, 4);
+    assert_eq!(summary.connected_observations, 3);
+    assert_eq!(summary.compatibility_edges, 3);
+    assert_eq!(summary.cliques, 1);
+    assert_eq!(summary.largest_clique, 3);
+    assert_eq!(summary.approximate_observations, 0);
+
+    let generation = summary.generation;
+    let observation = make_observation(Uuid::new_v4(), 50.5, 50.0);
+    assert_eq!(
+        unsafe { CliqueIndex_insert(index_ptr, &raw const observation) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_stats(index_ptr, &raw mut summary) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(summary.cliques, 2);
+    assert!(summary.generation > generation);
+
+    assert_eq!(
+        unsafe { CliqueIndex_stats(ptr::null(), &raw mut summary) },
+        CliqueStatus::NullPointer
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_stats(index_ptr, ptr::null_mut()) },
+        CliqueStatus::NullPointer
+    );
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_clique_iteration() {
+    let ids = [
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+    ];
+    let observations = [
+        make_observation(ids[0], 0.0, 0.0),
+        make_observation(ids[1], 0.5, 0.0),
+        make_observation(ids[2], 50.0, 50.0),
+        make_observation(ids[3], 50.5, 50.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let iter = unsafe { CliqueIndex_cliques_iter_new(index_ptr) };
+    assert!(!iter.is_null());
+
+    let mut buffer = [[0u8; 16]; 4];
+    let mut len = 0;
+
+    // A buffer which is too small reports the required capacity without advancing
+    assert_eq!(
+        unsafe { CliqueIndex_cliques_iter_next(iter, buffer.as_mut_ptr(), 1, &raw mut len) },
+        CliqueStatus::BufferTooSmall
+    );
+    assert_eq!(len, 2);
+
+    let mut seen = Vec::new();
+    loop {
+        let status = unsafe {
+            CliqueIndex_cliques_iter_next(iter, buffer.as_mut_ptr(), buffer.len(), &raw mut len)
+        };
+        if status == CliqueStatus::Exhausted {
+            break;
+        }
+        assert_eq!(status, CliqueStatus::Ok);
+        assert_eq!(len, 2);
+        seen.extend(buffer[..len].iter().map(|bytes| Uuid::from_bytes(*bytes)));
+    }
+
+    seen.sort();
+    let mut expected = ids.to_vec();
+    expected.sort();
+    assert_eq!(seen, expected);
+
+    unsafe {
+        CliqueIndex_cliques_iter_free(iter);
+        CliqueIndex_free(index_ptr);
+    }
+}
+
+#[test]
+fn test_chi2_threshold() {
+    assert_relative_eq!(
+        CliqueFusion_chi2_threshold(0.95, 2),
+        CliqueIndex_chi2_confidence_95(),
+        epsilon = 1e-3
+    );
+    assert_relative_eq!(
+        CliqueFusion_chi2_threshold(0.95, 1),
+        3.841_459,
+        epsilon = 1e-5
+    );
+    assert!(CliqueFusion_chi2_threshold(95.0, 2).is_nan());
+    assert!(CliqueFusion_chi2_threshold(0.95, 0).is_nan());
+}
+
+#[test]
+fn test_u64_index() {
+    let make = |id, x| ObservationU64C {
+        id,
+        x,
+        y: 0.0,
+        cov_xx: 1.0,
+        cov_xy: 0.0,
+        cov_yy: 1.0,
+        context: [0u8; 16],
+    };
+    let observations = [make(1, 0.0), make(2, 0.5)];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndexU64_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let obs3 = make(3, 1.0);
+    assert_eq!(
+        unsafe { CliqueIndexU64_insert(index_ptr, &raw const obs3) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(
+        unsafe { CliqueIndexU64_insert(index_ptr, &raw const obs3) },
+        CliqueStatus::DuplicateId
+    );
+    assert_eq!(unsafe { CliqueIndexU64_len(index_ptr) }, 3);
+
+    let clique_set_ptr = unsafe { CliqueIndexU64_cliques(index_ptr) };
+    let clique_set = unsafe { &*clique_set_ptr };
+    assert_eq!(clique_set.len, 1);
+    let clique = unsafe { &*clique_set.cliques };
+    let mut ids = unsafe { slice::from_raw_parts(clique.ids, clique.len) }.to_vec();
+    ids.sort_unstable();
+    assert_eq!(ids, [1, 2, 3]);
+    unsafe { CliqueSetU64C_free(clique_set_ptr) };
+
+    assert_eq!(
+        unsafe { CliqueIndexU64_remove(index_ptr, 3) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(
+        unsafe { CliqueIndexU64_remove(index_ptr, 3) },
+        CliqueStatus::NotFound
+    );
+
+    let mut empty_ptr = ptr::null_mut();
+    assert_eq!(
+        unsafe { CliqueIndexU64_new(CHI2_2D_CONFIDENCE_95, &raw mut empty_ptr) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(unsafe { CliqueIndexU64_len(empty_ptr) }, 0);
+
+    unsafe {
+        CliqueIndexU64_free(empty_ptr);
+        CliqueIndexU64_free(index_ptr);
+    }
+}
+
+#[test]
+fn test_synchronized_index() {
+    struct SendPtr(*mut SynchronizedCliqueIndex);
+    unsafe impl Send for SendPtr {}
+    unsafe impl Sync for SendPtr {}
+
+    let mut index_ptr = ptr::null_mut();
+    assert_eq!(
+        unsafe { CliqueIndexTS_new(CHI2_2D_CONFIDENCE_95, &raw mut index_ptr) },
+        CliqueStatus::Ok
+    );
+    let shared = SendPtr(index_ptr);
+
+    // Insert pairs of compatible observations from several threads at once
+    let observations: Vec<_> = (0..4)
+        .map(|group| {
+            let x = f64::from(group) * 100.0;
+            [
+                make_observation(Uuid::new_v4(), x, 0.0),
+                make_observation(Uuid::new_v4(), x + 0.1, 0.0),
+            ]
+        })
+        .collect();
+    std::thread::scope(|scope| {
+        for pair in &observations {
+            let shared = &shared;
+            scope.spawn(move || {
+                for observation in pair {
+                    let status = unsafe { CliqueIndexTS_insert(shared.0, observation) };
+                    assert_eq!(status, CliqueStatus::Ok);
+                }
+            });
+        }
+    });
+
+    assert_eq!(unsafe { CliqueIndexTS_len(index_ptr) }, 8);
+    let id = observations[0][0].id;
+    assert!(unsafe { CliqueIndexTS_contains(index_ptr, &raw const id) });
+
+    let clique_set_ptr = unsafe { CliqueIndexTS_cliques(index_ptr) };
+    assert_eq!(unsafe { (*clique_set_ptr).len }, 4);
+    unsafe { CliqueSetC_free(clique_set_ptr) };
+
+    assert_eq!(
+        unsafe { CliqueIndexTS_remove(index_ptr, &raw const id) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(
+        unsafe { CliqueIndexTS_remove(index_ptr, &raw const id) },
+        CliqueStatus::NotFound
+    );
+    assert_eq!(
+        unsafe { CliqueIndexTS_set_chi2(index_ptr, f64::NAN) },
+        CliqueStatus::InvalidChi2
+    );
+    assert_eq!(
+        unsafe { CliqueIndexTS_set_chi2(index_ptr, 0.001) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(
+        unsafe { CliqueIndexTS_chi2(index_ptr) }.to_bits(),
+        0.001_f64.to_bits()
+    );
+    assert_eq!(unsafe { CliqueIndexTS_len(index_ptr) }, 0);
+
+    unsafe { CliqueIndexTS_free(index_ptr) };
+}
+
+#[test]
+fn test_versions() {
+    let version = unsafe { CStr::from_ptr(CliqueFusion_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    assert_eq!(CliqueFusion_abi_version(), ABI_VERSION);
+}
+
+#[test]
+fn test_insert_v2() {
+    let mut index_ptr = ptr::null_mut();
+    assert_eq!(
+        unsafe { CliqueIndex_new(CHI2_2D_CONFIDENCE_95, &raw mut index_ptr) },
+        CliqueStatus::Ok
+    );
+
+    let id = Uuid::new_v4();
+    let observation = ObservationV2C {
+        id: uuid_to_uuidc(id),
+        x: 1.0,
+        y: 2.0,
+        cov_xx: 1.0,
+        cov_xy: 0.0,
+        cov_yy: 1.0,
+        context: [0u8; 16],
+        timestamp: 1_700_000_000.5,
+        weight: 0.5,
+        payload_tag: 42,
+    };
+    assert_eq!(
+        unsafe { CliqueIndex_insert_v2(index_ptr, &raw const observation) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_insert_v2(index_ptr, &raw const observation) },
+        CliqueStatus::DuplicateId
+    );
+
+    let mut out = std::mem::MaybeUninit::<ObservationV2C>::uninit();
+    let id_c = uuid_to_uuidc(id);
+    assert_eq!(
+        unsafe { CliqueIndex_get_v2(index_ptr, &raw const id_c, out.as_mut_ptr()) },
+        CliqueStatus::Ok
+    );
+    let out = unsafe { out.assume_init() };
+    assert_eq!(out.id, id_c);
+    assert_relative_eq!(out.timestamp, 1_700_000_000.5);
+    assert_relative_eq!(out.weight, 0.5);
+    assert_eq!(out.payload_tag, 42);
+
+    // Observations inserted through the original API have no timestamp or tag
+    let plain_id = Uuid::new_v4();
+    let plain = make_observation(plain_id, 5.0, 5.0);
+    assert_eq!(
+        unsafe { CliqueIndex_insert(index_ptr, &raw const plain) },
+        CliqueStatus::Ok
+    );
+    let mut out = std::mem::MaybeUninit::<ObservationV2C>::uninit();
+    let plain_id = uuid_to_uuidc(plain_id);
+    assert_eq!(
+        unsafe { CliqueIndex_get_v2(index_ptr, &raw const plain_id, out.as_mut_ptr()) },
+        CliqueStatus::Ok
+    );
+    let out = unsafe { out.assume_init() };
+    assert!(out.timestamp.is_nan());
+    assert_relative_eq!(out.weight, 1.0);
+    assert_eq!(out.payload_tag, 0);
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_caller_buffers() {
+    let observations = [
+        make_observation(Uuid::new_v4(), 0.0, 0.0),
+        make_observation(Uuid::new_v4(), 0.5, 0.0),
+        make_observation(Uuid::new_v4(), 1.0, 0.0),
+        make_observation(Uuid::new_v4(), 50.0, 50.0),
+        make_observation(Uuid::new_v4(), 50.5, 50.0),
+    ];
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    // Query the required capacities
+    let mut uuids_len = 0;
+    let mut cliques_len = 0;
+    let status = unsafe {
+        CliqueIndex_cliques_into(
+            index_ptr,
+            ptr::null_mut(),
+            0,
+            ptr::null_mut(),
+            0,
+            &raw mut uuids_len,
+            &raw mut cliques_len,
+        )
+    };
+    assert_eq!(status, CliqueStatus::BufferTooSmall);
+    assert_eq!((uuids_len, cliques_len), (5, 2));
+
+    let mut uuids = vec![[0u8; 16]; uuids_len];
+    let mut lengths = vec![0usize; cliques_len];
+    let status = unsafe {
+        CliqueIndex_cliques_into(
+            index_ptr,
+            uuids.as_mut_ptr(),
+            uuids.len(),
+            lengths.as_mut_ptr(),
+            lengths.len(),
+            &raw mut uuids_len,
+            &raw mut cliques_len,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+    lengths.sort_unstable();
+    assert_eq!(lengths, [2, 3]);
+    uuids.sort_unstable();
+    let mut expected: Vec<_> = observations.iter().map(|o| o.id).collect();
+    expected.sort_unstable();
+    assert_eq!(uuids, expected);
+
+    let mut edges_len = 0;
+    assert_eq!(
+        unsafe { CliqueIndex_edges_into(index_ptr, ptr::null_mut(), 0, &raw mut edges_len) },
+        CliqueStatus::BufferTooSmall
+    );
+    assert_eq!(edges_len, 4);
+    let mut edges = Vec::with_capacity(edges_len);
+    assert_eq!(
+        unsafe {
+            CliqueIndex_edges_into(index_ptr, edges.as_mut_ptr(), edges_len, &raw mut edges_len)
+        },
+        CliqueStatus::Ok
+    );
+    unsafe { edges.set_len(edges_len) };
+    assert!(
+        edges
+            .iter()
+            .all(|edge: &EdgeC| edge.mahalanobis_squared <= CHI2_2D_CONFIDENCE_95)
+    );
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_flat_cliques() {
+    let observations = [
+        make_observation(Uuid::new_v4(), 0.0, 0.0),
+        make_observation(Uuid::new_v4(), 0.5, 0.0),
+        make_observation(Uuid::new_v4(), 0.0, 0.5),
+        make_observation(Uuid::new_v4(), 50.0, 50.0),
+        make_observation(Uuid::new_v4(), 50.5, 50.0),
+    ];
+
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let mut flat = FlatCliquesC {
+        uuids: ptr::null(),
+        uuids_len: 0,
+        offsets: ptr::null(),
+        len: 0,
+    };
+    assert_eq!(
+        unsafe { CliqueIndex_cliques_flat(index_ptr, &raw mut flat) },
+        CliqueStatus::Ok
+    );
+    assert_eq!((flat.len, flat.uuids_len), (2, 5));
+    let offsets = unsafe { slice::from_raw_parts(flat.offsets, flat.len + 1) };
+    let uuids = unsafe { slice::from_raw_parts(flat.uuids, flat.uuids_len) };
+    let mut lengths: Vec<_> = offsets.windows(2).map(|w| w[1] - w[0]).collect();
+    lengths.sort_unstable();
+    assert_eq!(lengths, [2, 3]);
+    let members: HashSet<_> = uuids.iter().copied().collect();
+    let expected: HashSet<_> = observations.iter().map(|o| o.id).collect();
+    assert_eq!(members, expected);
+
+    // polling an unchanged index returns the same buffers
+    let previous = flat.uuids;
+    assert_eq!(
+        unsafe { CliqueIndex_cliques_flat(index_ptr, &raw mut flat) },
+        CliqueStatus::Ok
+    );
+    assert_eq!(flat.uuids, previous);
+
+    assert_eq!(
+        unsafe { CliqueIndex_cliques_flat(ptr::null(), &raw mut flat) },
+        CliqueStatus::NullPointer
+    );
+    assert_eq!(
+        unsafe { CliqueIndex_cliques_flat(index_ptr, ptr::null_mut()) },
+        CliqueStatus::NullPointer
+    );
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
+
+#[test]
+fn test_to_geojson() {
+    let observations = [
+        make_observation(Uuid::new_v4(), 0.0, 0.0),
+        make_observation(Uuid::new_v4(), 0.5, 0.0),
+    ];
+    let mut index_ptr = ptr::null_mut();
+    let status = unsafe {
+        CliqueIndex_from_observations(
+            CHI2_2D_CONFIDENCE_95,
+            observations.as_ptr(),
+            observations.len(),
+            &raw mut index_ptr,
+        )
+    };
+    assert_eq!(status, CliqueStatus::Ok);
+
+    let geojson_ptr = unsafe { CliqueIndex_to_geojson(index_ptr) };
+    assert!(!geojson_ptr.is_null());
+    let geojson = unsafe { CStr::from_ptr(geojson_ptr) }.to_str().unwrap();
+    assert!(
+        geojson.starts_with(r#"{"features":"#) || geojson.contains(r#""type":"FeatureCollection""#)
+    );
+    assert!(geojson.contains(&Uuid::from_bytes(observations[0].id).to_string()));
+    unsafe { CliqueFusion_string_free(geojson_ptr) };
+
+    assert!(unsafe { CliqueIndex_to_geojson(ptr::null()) }.is_null());
+
+    unsafe { CliqueIndex_free(index_ptr) };
+}
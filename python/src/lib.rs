@@ -0,0 +1,771 @@
+//! Python bindings for the `clique_fusion` crate.
+//!
+//! Observations are identified by (non-negative) Python integers.
+
+use std::{borrow::Cow, collections::HashSet};
+
+use clique_fusion::{
+    CHI2_2D_CONFIDENCE_90, CHI2_2D_CONFIDENCE_95, CHI2_2D_CONFIDENCE_99, CliqueIndex as Index,
+    CovarianceMatrix as Covariance, Observation as Obs, Unique,
+};
+use numpy::{AllowTypeChange, Element, IntoPyArray, PyArray1, PyArrayLike1, PyReadonlyArray1};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyDict};
+use uuid::Uuid;
+
+/// Convert any displayable error into a Python `ValueError`.
+fn value_error(error: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// The covariance of an observation's position error.
+#[pyclass(frozen, module = "clique_fusion")]
+#[derive(Debug, Clone, Copy)]
+pub struct CovarianceMatrix(Covariance);
+
+#[pymethods]
+impl CovarianceMatrix {
+    /// Construct a covariance matrix from its terms.
+    ///
+    /// Raises `ValueError` if the terms do not describe a positive semi-definite matrix.
+    #[new]
+    #[pyo3(signature = (xx, yy, xy = 0.0))]
+    fn new(xx: f64, yy: f64, xy: f64) -> PyResult<Self> {
+        Covariance::new(xx, yy, xy).map(Self).map_err(value_error)
+    }
+
+    /// A circular error, such that 95% of the probability mass lies within `radius`.
+    #[staticmethod]
+    fn from_circular_95_confidence(radius: f64) -> PyResult<Self> {
+        Covariance::from_circular_95_confidence(radius)
+            .map(Self)
+            .map_err(value_error)
+    }
+
+    /// The identity matrix.
+    #[staticmethod]
+    fn identity() -> Self {
+        Self(Covariance::identity())
+    }
+
+    /// The variance in x.
+    #[getter]
+    fn xx(&self) -> f64 {
+        self.0.xx()
+    }
+
+    /// The covariance of x and y.
+    #[getter]
+    fn xy(&self) -> f64 {
+        self.0.xy()
+    }
+
+    /// The variance in y.
+    #[getter]
+    fn yy(&self) -> f64 {
+        self.0.yy()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CovarianceMatrix(xx={}, yy={}, xy={})",
+            self.0.xx(),
+            self.0.yy(),
+            self.0.xy()
+        )
+    }
+}
+
+/// An observation of an object at a fixed location, with uncertainty.
+#[pyclass(frozen, module = "clique_fusion")]
+#[derive(Debug, Clone)]
+pub struct Observation(Obs);
+
+#[pymethods]
+impl Observation {
+    /// Construct an observation.
+    ///
+    /// Raises `ValueError` if the position is not finite.
+    #[new]
+    #[pyo3(signature = (x, y, error, context = None, timestamp = None, sensor = None))]
+    fn new(
+        x: f64,
+        y: f64,
+        error: CovarianceMatrix,
+        context: Option<Uuid>,
+        timestamp: Option<f64>,
+        sensor: Option<Uuid>,
+    ) -> PyResult<Self> {
+        let mut builder = Obs::builder(x, y).error(error.0);
+        if let Some(context) = context {
+            builder = builder.context(context);
+        }
+        if let Some(timestamp) = timestamp {
+            builder = builder.timestamp(timestamp);
+        }
+        if let Some(sensor) = sensor {
+            builder = builder.sensor(sensor);
+        }
+        builder.try_build().map(Self).map_err(value_error)
+    }
+
+    /// The x ordinate.
+    #[getter]
+    fn x(&self) -> f64 {
+        self.0.x()
+    }
+
+    /// The y ordinate.
+    #[getter]
+    fn y(&self) -> f64 {
+        self.0.y()
+    }
+
+    /// The covariance of the position error.
+    #[getter]
+    const fn error(&self) -> CovarianceMatrix {
+        CovarianceMatrix(self.0.error_covariance())
+    }
+
+    /// The context of the observation, if any.
+    #[getter]
+    const fn context(&self) -> Option<Uuid> {
+        self.0.context()
+    }
+
+    /// The sensor which made the observation, if known.
+    #[getter]
+    const fn sensor(&self) -> Option<Uuid> {
+        self.0.sensor()
+    }
+
+    /// The time at which the observation was made, if known.
+    #[getter]
+    const fn timestamp(&self) -> Option<f64> {
+        self.0.timestamp()
+    }
+
+    /// Returns `True` if the observations are statistically compatible at the given threshold.
+    fn is_compatible_with(&self, other: &Self, chi2: f64) -> bool {
+        self.0.is_compatible_with(&other.0, chi2)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Observation(x={}, y={})", self.0.x(), self.0.y())
+    }
+}
+
+/// The fused estimate of the position of the object observed by the members of a clique.
+#[pyclass(frozen, get_all, module = "clique_fusion")]
+#[derive(Debug, Clone)]
+pub struct FusedEstimate {
+    /// The IDs of the observations which were fused.
+    members: HashSet<u64>,
+    /// The fused x ordinate.
+    x: f64,
+    /// The fused y ordinate.
+    y: f64,
+    /// The covariance of the fused position error.
+    error: CovarianceMatrix,
+    /// The distinct sensors which made the fused observations.
+    sensors: HashSet<Uuid>,
+    /// The residual chi-squared statistic of the members about the fused position.
+    chi2_statistic: f64,
+    /// The degrees of freedom of the residual statistic.
+    degrees_of_freedom: u32,
+    /// The probability of a residual statistic at least this large if the members are consistent.
+    p_value: f64,
+}
+
+#[pymethods]
+impl FusedEstimate {
+    fn __repr__(&self) -> String {
+        format!(
+            "FusedEstimate(x={}, y={}, members={})",
+            self.x,
+            self.y,
+            self.members.len()
+        )
+    }
+}
+
+/// An index which tracks the cliques of mutually compatible observations.
+#[pyclass(module = "clique_fusion")]
+#[derive(Debug)]
+pub struct CliqueIndex(Index<u64>);
+
+#[pymethods]
+impl CliqueIndex {
+    /// Construct an empty index with the given chi-squared threshold.
+    ///
+    /// Raises `ValueError` if `chi2` is not finite and positive.
+    #[new]
+    #[pyo3(signature = (chi2 = CHI2_2D_CONFIDENCE_95))]
+    fn new(chi2: f64) -> PyResult<Self> {
+        Index::try_new(chi2).map(Self).map_err(value_error)
+    }
+
+    /// Construct an index from a batch of `(id, Observation)` pairs.
+    ///
+    /// This is much faster than inserting the observations one at a time.
+    #[staticmethod]
+    #[pyo3(signature = (observations, chi2 = CHI2_2D_CONFIDENCE_95))]
+    fn from_observations(observations: Vec<(u64, Observation)>, chi2: f64) -> PyResult<Self> {
+        let observations = observations
+            .into_iter()
+            .map(|(id, observation)| Unique {
+                id,
+                data: observation.0,
+            })
+            .collect();
+        build_index(observations, chi2)
+    }
+
+    /// Construct an index from parallel sequences of IDs, positions and covariance terms.
+    ///
+    /// Raises `ValueError` if the sequences differ in length, or any observation is invalid.
+    ///
+    /// For large batches held in NumPy arrays, prefer [`from_numpy`](Self::from_numpy), which
+    /// avoids converting each element to a Python object.
+    #[staticmethod]
+    #[pyo3(signature = (ids, x, y, xx, xy, yy, chi2 = CHI2_2D_CONFIDENCE_95))]
+    #[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+    fn from_arrays(
+        py: Python<'_>,
+        ids: Vec<u64>,
+        x: Vec<f64>,
+        y: Vec<f64>,
+        xx: Vec<f64>,
+        xy: Vec<f64>,
+        yy: Vec<f64>,
+        chi2: f64,
+    ) -> PyResult<Self> {
+        let columns = Columns {
+            ids: &ids,
+            x: &x,
+            y: &y,
+            xx: &xx,
+            xy: &xy,
+            yy: &yy,
+        };
+        py.detach(|| columns.build_index(chi2))
+            .map(Self)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Construct an index from contiguous one-dimensional NumPy arrays of IDs (`uint64`), positions
+    /// and covariance terms (`float64`).
+    ///
+    /// The arrays are read in place, and the GIL is released while the index is built.
+    ///
+    /// Raises `TypeError` if an array has the wrong dtype or dimensionality, and `ValueError` if an
+    /// array is not contiguous, the arrays differ in length, or any observation is invalid.
+    #[staticmethod]
+    #[pyo3(signature = (ids, x, y, xx, xy, yy, chi2 = CHI2_2D_CONFIDENCE_95))]
+    #[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+    fn from_numpy(
+        py: Python<'_>,
+        ids: PyReadonlyArray1<'_, u64>,
+        x: PyReadonlyArray1<'_, f64>,
+        y: PyReadonlyArray1<'_, f64>,
+        xx: PyReadonlyArray1<'_, f64>,
+        xy: PyReadonlyArray1<'_, f64>,
+        yy: PyReadonlyArray1<'_, f64>,
+        chi2: f64,
+    ) -> PyResult<Self> {
+        let columns = Columns {
+            ids: ids.as_slice().map_err(value_error)?,
+            x: x.as_slice().map_err(value_error)?,
+            y: y.as_slice().map_err(value_error)?,
+            xx: xx.as_slice().map_err(value_error)?,
+            xy: xy.as_slice().map_err(value_error)?,
+            yy: yy.as_slice().map_err(value_error)?,
+        };
+        py.detach(|| columns.build_index(chi2))
+            .map(Self)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Construct an index from a pandas or polars `DataFrame`, with a row per observation.
+    ///
+    /// The names of the columns holding the IDs, positions and covariance terms may be given, and
+    /// default to `id`, `x`, `y`, `xx`, `xy` and `yy`. Columns are converted to `uint64` and
+    /// `float64` as required, and the GIL is released while the index is built.
+    ///
+    /// Raises `KeyError` if a column is missing, and `ValueError` if any observation is invalid.
+    #[staticmethod]
+    #[pyo3(signature = (
+        frame,
+        chi2 = CHI2_2D_CONFIDENCE_95,
+        *,
+        id = "id",
+        x = "x",
+        y = "y",
+        xx = "xx",
+        xy = "xy",
+        yy = "yy",
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn from_dataframe(
+        py: Python<'_>,
+        frame: &Bound<'_, PyAny>,
+        chi2: f64,
+        id: &str,
+        x: &str,
+        y: &str,
+        xx: &str,
+        xy: &str,
+        yy: &str,
+    ) -> PyResult<Self> {
+        let ids: PyArrayLike1<'_, u64, AllowTypeChange> = frame.get_item(id)?.extract()?;
+        let [x, y, xx, xy, yy] = [x, y, xx, xy, yy].map(|name| {
+            frame
+                .get_item(name)
+                .and_then(|column| column.extract::<PyArrayLike1<'_, f64, AllowTypeChange>>())
+        });
+        let (x, y, xx, xy, yy) = (x?, y?, xx?, xy?, yy?);
+        let columns = Columns {
+            ids: &contiguous(&ids),
+            x: &contiguous(&x),
+            y: &contiguous(&y),
+            xx: &contiguous(&xx),
+            xy: &contiguous(&xy),
+            yy: &contiguous(&yy),
+        };
+        py.detach(|| columns.build_index(chi2))
+            .map(Self)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Insert an observation.
+    ///
+    /// Raises `ValueError` if an observation with the same ID is already in the index.
+    fn insert(&mut self, id: u64, observation: Observation) -> PyResult<()> {
+        if self.0.contains(&id) {
+            return Err(duplicate_id(id));
+        }
+        self.0.insert(Unique {
+            id,
+            data: observation.0,
+        });
+        Ok(())
+    }
+
+    /// Remove an observation, returning it (or `None` if it was not in the index).
+    fn remove(&mut self, id: u64) -> Option<Observation> {
+        self.0.remove(&id).map(Observation)
+    }
+
+    /// Look up an observation by ID.
+    fn get(&self, id: u64) -> Option<Observation> {
+        self.0.get(&id).cloned().map(Observation)
+    }
+
+    /// The chi-squared threshold used to test compatibility.
+    #[getter]
+    const fn chi2(&self) -> f64 {
+        self.0.chi2()
+    }
+
+    /// Change the chi-squared threshold, recomputing all cliques.
+    fn set_chi2(&mut self, chi2: f64) -> PyResult<()> {
+        self.0.set_chi2(chi2).map_err(value_error)
+    }
+
+    /// The current maximal cliques, as sets of observation IDs.
+    fn cliques(&self) -> Vec<HashSet<u64>> {
+        self.0.cliques().iter().cloned().collect()
+    }
+
+    /// The current maximal cliques as a pair of NumPy arrays `(members, offsets)`.
+    ///
+    /// The members of clique `i` are `members[offsets[i]:offsets[i + 1]]`, so `offsets` has one
+    /// more element than there are cliques.
+    fn cliques_arrays<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> (Bound<'py, PyArray1<u64>>, Bound<'py, PyArray1<usize>>) {
+        let cliques = self.0.cliques();
+        let mut members = Vec::with_capacity(cliques.iter().map(HashSet::len).sum());
+        let mut offsets = Vec::with_capacity(cliques.len() + 1);
+        offsets.push(0);
+        for clique in cliques {
+            members.extend(clique);
+            offsets.push(members.len());
+        }
+        (members.into_pyarray(py), offsets.into_pyarray(py))
+    }
+
+    /// The fused estimate of each of the current cliques, in the same order as `cliques()`.
+    fn fused_estimates(&self) -> Vec<FusedEstimate> {
+        self.0
+            .fused_estimates()
+            .into_iter()
+            .map(|estimate| FusedEstimate {
+                members: estimate.members,
+                x: estimate.x,
+                y: estimate.y,
+                error: CovarianceMatrix(estimate.covariance),
+                sensors: estimate.sensors,
+                chi2_statistic: estimate.goodness_of_fit.statistic,
+                degrees_of_freedom: estimate.goodness_of_fit.degrees_of_freedom,
+                p_value: estimate.goodness_of_fit.p_value,
+            })
+            .collect()
+    }
+
+    /// The current maximal cliques as a tidy `DataFrame`, with a row per member of each clique.
+    ///
+    /// The `clique` column is the position of the clique in `cliques()`, and the `id` column is
+    /// the ID of the member. `library` is either `"pandas"` or `"polars"`.
+    #[pyo3(signature = (library = "pandas"))]
+    fn cliques_dataframe<'py>(
+        &self,
+        py: Python<'py>,
+        library: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        dataframe(py, library, clique_columns(&self.0))
+    }
+
+    /// The fused estimate of each of the current cliques as a `DataFrame`, with a row per clique.
+    ///
+    /// The `clique` column is the position of the clique in `cliques()`, and the remaining columns
+    /// are the fused position (`x`, `y`), its covariance (`xx`, `xy`, `yy`), the number of
+    /// `members`, and the goodness-of-fit test (`chi2_statistic`, `degrees_of_freedom`,
+    /// `p_value`). `library` is either `"pandas"` or `"polars"`.
+    #[pyo3(signature = (library = "pandas"))]
+    fn fused_estimates_dataframe<'py>(
+        &self,
+        py: Python<'py>,
+        library: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        dataframe(py, library, fused_estimate_columns(&self.0))
+    }
+
+    /// The total number of observations in the index.
+    fn total_observations(&self) -> usize {
+        self.0.total_observations()
+    }
+
+    /// The number of observations which are compatible with at least one other observation.
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    fn __contains__(&self, id: u64) -> bool {
+        self.0.contains(&id)
+    }
+}
+
+/// Parallel columns of observation data, borrowed from Python.
+struct Columns<'a> {
+    ids: &'a [u64],
+    x: &'a [f64],
+    y: &'a [f64],
+    xx: &'a [f64],
+    xy: &'a [f64],
+    yy: &'a [f64],
+}
+
+impl Columns<'_> {
+    /// Build an index from the columns.
+    ///
+    /// This doesn't touch any Python objects, so may be called without holding the GIL.
+    fn build_index(&self, chi2: f64) -> Result<Index<u64>, String> {
+        let len = self.ids.len();
+        let lengths = [
+            self.x.len(),
+            self.y.len(),
+            self.xx.len(),
+            self.xy.len(),
+            self.yy.len(),
+        ];
+        if lengths.iter().any(|&other| other != len) {
+            return Err("all arrays must have the same length".to_string());
+        }
+        let config = clique_fusion::Config::try_new(chi2).map_err(|e| e.to_string())?;
+
+        let mut seen = HashSet::with_capacity(len);
+        let mut observations = Vec::with_capacity(len);
+        for (i, &id) in self.ids.iter().enumerate() {
+            if !seen.insert(id) {
+                return Err(duplicate_id_message(id));
+            }
+            let data = Covariance::new(self.xx[i], self.yy[i], self.xy[i])
+                .map_err(|e| e.to_string())
+                .and_then(|error| {
+                    Obs::builder(self.x[i], self.y[i])
+                        .error(error)
+                        .try_build()
+                        .map_err(|e| e.to_string())
+                })
+                .map_err(|e| format!("observation {i}: {e}"))?;
+            observations.push(Unique { id, data });
+        }
+        Ok(Index::from_observations_with_config(observations, config))
+    }
+}
+
+/// Borrow the elements of an array, copying them only if they aren't contiguous.
+fn contiguous<'a, T>(array: &'a PyArrayLike1<'_, T, AllowTypeChange>) -> Cow<'a, [T]>
+where
+    T: Element + Clone,
+{
+    array
+        .as_slice()
+        .map_or_else(|_| Cow::Owned(array.as_array().to_vec()), Cow::Borrowed)
+}
+
+/// A named column of a `DataFrame`.
+#[derive(Debug, PartialEq)]
+enum Column {
+    Integer(Vec<u64>),
+    Float(Vec<f64>),
+}
+
+/// The tidy columns of the cliques of an index, with a row per member of each clique.
+fn clique_columns(index: &Index<u64>) -> Vec<(&'static str, Column)> {
+    let mut cliques = Vec::new();
+    let mut ids = Vec::new();
+    for (position, clique) in (0..).zip(index.cliques()) {
+        cliques.extend(std::iter::repeat_n(position, clique.len()));
+        ids.extend(clique);
+    }
+    vec![
+        ("clique", Column::Integer(cliques)),
+        ("id", Column::Integer(ids)),
+    ]
+}
+
+/// The columns of the fused estimates of an index, with a row per clique.
+fn fused_estimate_columns(index: &Index<u64>) -> Vec<(&'static str, Column)> {
+    let estimates = index.fused_estimates();
+    let float = |field: fn(&clique_fusion::FusedEstimate<u64>) -> f64| {
+        Column::Float(estimates.iter().map(field).collect())
+    };
+    let integer = |field: fn(&clique_fusion::FusedEstimate<u64>) -> u64| {
+        Column::Integer(estimates.iter().map(field).collect())
+    };
+    vec![
+        (
+            "clique",
+            Column::Integer((0..).take(estimates.len()).collect()),
+        ),
+        ("x", float(|estimate| estimate.x)),
+        ("y", float(|estimate| estimate.y)),
+        ("xx", float(|estimate| estimate.covariance.xx())),
+        ("xy", float(|estimate| estimate.covariance.xy())),
+        ("yy", float(|estimate| estimate.covariance.yy())),
+        ("members", integer(|estimate| estimate.members.len() as u64)),
+        (
+            "chi2_statistic",
+            float(|estimate| estimate.goodness_of_fit.statistic),
+        ),
+        (
+            "degrees_of_freedom",
+            integer(|estimate| estimate.goodness_of_fit.degrees_of_freedom.into()),
+        ),
+        (
+            "p_value",
+            float(|estimate| estimate.goodness_of_fit.p_value),
+        ),
+    ]
+}
+
+/// Construct a pandas or polars `DataFrame` from named columns, in order.
+fn dataframe<'py>(
+    py: Python<'py>,
+    library: &str,
+    columns: Vec<(&'static str, Column)>,
+) -> PyResult<Bound<'py, PyAny>> {
+    if !matches!(library, "pandas" | "polars") {
+        return Err(PyValueError::new_err(format!(
+            "unsupported DataFrame library '{library}' (expected 'pandas' or 'polars')"
+        )));
+    }
+    let data = PyDict::new(py);
+    for (name, column) in columns {
+        match column {
+            Column::Integer(values) => data.set_item(name, values.into_pyarray(py))?,
+            Column::Float(values) => data.set_item(name, values.into_pyarray(py))?,
+        }
+    }
+    py.import(library)?.getattr("DataFrame")?.call1((data,))
+}
+
+fn build_index(observations: Vec<Unique<Obs, u64>>, chi2: f64) -> PyResult<CliqueIndex> {
+    let config = clique_fusion::Config::try_new(chi2).map_err(value_error)?;
+    let mut ids = HashSet::with_capacity(observations.len());
+    if let Some(duplicate) = observations.iter().find(|obs| !ids.insert(obs.id)) {
+        return Err(duplicate_id(duplicate.id));
+    }
+    Ok(CliqueIndex(Index::from_observations_with_config(
+        observations,
+        config,
+    )))
+}
+
+fn duplicate_id_message(id: u64) -> String {
+    format!("an observation with ID {id} already exists")
+}
+
+fn duplicate_id(id: u64) -> PyErr {
+    PyValueError::new_err(duplicate_id_message(id))
+}
+
+/// Compute the chi-squared threshold for an arbitrary confidence level (a probability in `(0, 1)`).
+#[pyfunction]
+#[pyo3(signature = (confidence, degrees_of_freedom = 2))]
+fn chi2_threshold(confidence: f64, degrees_of_freedom: u32) -> PyResult<f64> {
+    clique_fusion::chi2_threshold(confidence, degrees_of_freedom).map_err(value_error)
+}
+
+/// Spatial fusion of uncertain observations of physical objects.
+#[pymodule]
+#[pyo3(name = "clique_fusion")]
+fn clique_fusion_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<CovarianceMatrix>()?;
+    module.add_class::<Observation>()?;
+    module.add_class::<FusedEstimate>()?;
+    module.add_class::<CliqueIndex>()?;
+    module.add_function(wrap_pyfunction!(chi2_threshold, module)?)?;
+    module.add("CHI2_2D_CONFIDENCE_90", CHI2_2D_CONFIDENCE_90)?;
+    module.add("CHI2_2D_CONFIDENCE_95", CHI2_2D_CONFIDENCE_95)?;
+    module.add("CHI2_2D_CONFIDENCE_99", CHI2_2D_CONFIDENCE_99)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_build_index() {
+        let index = Columns {
+            ids: &[1, 2, 3],
+            x: &[0.0, 0.5, 100.0],
+            y: &[0.0, 0.0, 100.0],
+            xx: &[1.0; 3],
+            xy: &[0.0; 3],
+            yy: &[1.0; 3],
+        }
+        .build_index(CHI2_2D_CONFIDENCE_95)
+        .unwrap();
+
+        assert_eq!(index.total_observations(), 3);
+        assert_eq!(index.cliques(), &[HashSet::from([1, 2])]);
+        let estimates = index.fused_estimates();
+        assert_eq!(estimates.len(), 1);
+        assert!((estimates[0].x - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn columns_reject_invalid_input() {
+        let mismatched = Columns {
+            ids: &[1, 2],
+            x: &[0.0],
+            y: &[0.0, 0.0],
+            xx: &[1.0; 2],
+            xy: &[0.0; 2],
+            yy: &[1.0; 2],
+        };
+        assert!(mismatched.build_index(CHI2_2D_CONFIDENCE_95).is_err());
+
+        let duplicate = Columns {
+            ids: &[1, 1],
+            ..mismatched
+        };
+        assert!(duplicate.build_index(CHI2_2D_CONFIDENCE_95).is_err());
+
+        let invalid_covariance = Columns {
+            ids: &[1],
+            x: &[0.0],
+            y: &[0.0],
+            xx: &[-1.0],
+            xy: &[0.0],
+            yy: &[1.0],
+        };
+        let error = invalid_covariance
+            .build_index(CHI2_2D_CONFIDENCE_95)
+            .unwrap_err();
+        assert!(error.starts_with("observation 0:"));
+    }
+
+    #[test]
+    fn dataframe_columns_are_tidy() {
+        let index = Columns {
+            ids: &[1, 2, 3, 4, 5],
+            x: &[0.0, 0.5, 100.0, 100.5, 200.0],
+            y: &[0.0; 5],
+            xx: &[1.0; 5],
+            xy: &[0.0; 5],
+            yy: &[1.0; 5],
+        }
+        .build_index(CHI2_2D_CONFIDENCE_95)
+        .unwrap();
+
+        let columns = clique_columns(&index);
+        assert_eq!(columns.len(), 2);
+        let (Column::Integer(cliques), Column::Integer(ids)) = (&columns[0].1, &columns[1].1)
+        else {
+            panic!("clique columns must be integers");
+        };
+        assert_eq!(cliques.len(), 4);
+        for (position, clique) in (0..).zip(index.cliques()) {
+            let members: HashSet<u64> = cliques
+                .iter()
+                .zip(ids)
+                .filter(|(c, _)| **c == position)
+                .map(|(_, id)| *id)
+                .collect();
+            assert_eq!(&members, clique);
+        }
+
+        let columns = fused_estimate_columns(&index);
+        let names: Vec<_> = columns.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            [
+                "clique",
+                "x",
+                "y",
+                "xx",
+                "xy",
+                "yy",
+                "members",
+                "chi2_statistic",
+                "degrees_of_freedom",
+                "p_value"
+            ]
+        );
+        assert_eq!(columns[6].1, Column::Integer(vec![2, 2]));
+    }
+
+    #[test]
+    fn module_exposes_classes() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "clique_fusion").unwrap();
+            clique_fusion_py(&module).unwrap();
+            let locals = pyo3::types::PyDict::new(py);
+            locals.set_item("cf", module).unwrap();
+            py.run(
+                c"
+index = cf.CliqueIndex(cf.CHI2_2D_CONFIDENCE_95)
+index.insert(1, cf.Observation(0.0, 0.0, cf.CovarianceMatrix.identity()))
+index.insert(2, cf.Observation(0.5, 0.0, cf.CovarianceMatrix(1.0, 1.0)))
+assert index.cliques() == [{1, 2}]
+assert 1 in index and len(index) == 2
+batch = cf.CliqueIndex.from_arrays([1, 2], [0.0, 0.5], [0.0, 0.0], [1.0, 1.0], [0.0, 0.0], [1.0, 1.0])
+assert batch.cliques() == [{1, 2}]
+try:
+    index.insert(1, index.get(1))
+    raise AssertionError('expected ValueError')
+except ValueError:
+    pass
+",
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,288 @@
+//! Storage for the compatibility graph of an index.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::{BuildHasher, Hash},
+    ops::Index,
+};
+
+/// The adjacency of a compatibility graph: the set of compatible neighbours of each observation
+/// which has any, keyed by its ID.
+///
+/// A [`CliqueIndex`](crate::CliqueIndex) stores its graph in a [`HashMap`] by default, which
+/// suits any ID type. [`DenseAdjacency`] stores it in a vector indexed by the ID instead, which is
+/// faster and more compact when the IDs are small integers (see
+/// [`IntegerCliqueIndex`](crate::IntegerCliqueIndex)).
+pub trait Adjacency<Id>:
+    Default + Clone + fmt::Debug + for<'a> Index<&'a Id, Output = HashSet<Id>>
+{
+    /// The neighbours of an observation, or `None` if it is not in the graph.
+    fn get(&self, id: &Id) -> Option<&HashSet<Id>>;
+
+    /// The neighbours of an observation, mutably, or `None` if it is not in the graph.
+    fn get_mut(&mut self, id: &Id) -> Option<&mut HashSet<Id>>;
+
+    /// The neighbours of an observation, mutably, adding it to the graph without any neighbours if
+    /// it is not already present.
+    fn entry(&mut self, id: Id) -> &mut HashSet<Id>;
+
+    /// Set the neighbours of an observation, returning its previous neighbours, if any.
+    fn insert(&mut self, id: Id, neighbours: HashSet<Id>) -> Option<HashSet<Id>>;
+
+    /// Remove an observation from the graph, returning its neighbours, if it was present.
+    ///
+    /// The observation is not removed from the neighbours of the others.
+    fn remove(&mut self, id: &Id) -> Option<HashSet<Id>>;
+
+    /// Keep only the observations for which `keep` returns `true`.
+    fn retain(&mut self, keep: impl FnMut(&Id, &mut HashSet<Id>) -> bool);
+
+    /// The number of observations in the graph.
+    fn len(&self) -> usize;
+
+    /// Iterate over the observations in the graph, with their neighbours, in an arbitrary order.
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a Id, &'a HashSet<Id>)>
+    where
+        Id: 'a;
+
+    /// Iterate over the observations in the graph, with their neighbours mutably, in an arbitrary
+    /// order.
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = (&'a Id, &'a mut HashSet<Id>)>
+    where
+        Id: 'a;
+
+    /// Returns `true` if the observation is in the graph.
+    fn contains_key(&self, id: &Id) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Returns `true` if the graph has no observations.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the observations in the graph, in an arbitrary order.
+    fn keys<'a>(&'a self) -> impl Iterator<Item = &'a Id>
+    where
+        Id: 'a,
+    {
+        self.iter().map(|(id, _)| id)
+    }
+}
+
+// the sets of neighbours are handed to the clique enumeration as-is, so use the default hasher
+#[allow(clippy::implicit_hasher)]
+impl<Id, S> Adjacency<Id> for HashMap<Id, HashSet<Id>, S>
+where
+    Id: Eq + Hash + Clone + fmt::Debug,
+    S: BuildHasher + Default + Clone,
+{
+    fn get(&self, id: &Id) -> Option<&HashSet<Id>> {
+        Self::get(self, id)
+    }
+
+    fn get_mut(&mut self, id: &Id) -> Option<&mut HashSet<Id>> {
+        Self::get_mut(self, id)
+    }
+
+    fn entry(&mut self, id: Id) -> &mut HashSet<Id> {
+        Self::entry(self, id).or_default()
+    }
+
+    fn insert(&mut self, id: Id, neighbours: HashSet<Id>) -> Option<HashSet<Id>> {
+        Self::insert(self, id, neighbours)
+    }
+
+    fn remove(&mut self, id: &Id) -> Option<HashSet<Id>> {
+        Self::remove(self, id)
+    }
+
+    fn retain(&mut self, keep: impl FnMut(&Id, &mut HashSet<Id>) -> bool) {
+        Self::retain(self, keep);
+    }
+
+    fn len(&self) -> usize {
+        Self::len(self)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a Id, &'a HashSet<Id>)>
+    where
+        Id: 'a,
+    {
+        Self::iter(self)
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = (&'a Id, &'a mut HashSet<Id>)>
+    where
+        Id: 'a,
+    {
+        Self::iter_mut(self)
+    }
+
+    fn contains_key(&self, id: &Id) -> bool {
+        Self::contains_key(self, id)
+    }
+}
+
+/// The adjacency of a compatibility graph over integer IDs, stored in a vector indexed by the ID.
+///
+/// Looking up the neighbours of an observation is a bounds-checked index rather than a hash, and
+/// the observations are iterated in the order of their IDs. The vector is as long as the largest
+/// ID in the graph, so this is only suitable for IDs which are allocated densely from zero, such as
+/// those of [`IntegerCliqueIndex::from_sequential`](crate::IntegerCliqueIndex::from_sequential).
+#[derive(Debug, Clone, Default)]
+pub struct DenseAdjacency {
+    /// The neighbours of each observation in the graph, at the position of its ID, alongside the
+    /// ID itself.
+    slots: Vec<Option<(u64, HashSet<u64>)>>,
+
+    /// The number of occupied slots.
+    len: usize,
+}
+
+impl DenseAdjacency {
+    fn slot(&self, id: u64) -> Option<&(u64, HashSet<u64>)> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|slot| self.slots.get(slot))
+            .and_then(Option::as_ref)
+    }
+
+    fn slot_mut(&mut self, id: u64) -> Option<&mut Option<(u64, HashSet<u64>)>> {
+        usize::try_from(id)
+            .ok()
+            .and_then(|slot| self.slots.get_mut(slot))
+    }
+}
+
+impl Adjacency<u64> for DenseAdjacency {
+    fn get(&self, id: &u64) -> Option<&HashSet<u64>> {
+        self.slot(*id).map(|(_, neighbours)| neighbours)
+    }
+
+    fn get_mut(&mut self, id: &u64) -> Option<&mut HashSet<u64>> {
+        self.slot_mut(*id)
+            .and_then(Option::as_mut)
+            .map(|(_, neighbours)| neighbours)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the ID doesn't fit in a `usize`.
+    fn entry(&mut self, id: u64) -> &mut HashSet<u64> {
+        let slot = usize::try_from(id).expect("dense IDs fit in a usize");
+        if slot >= self.slots.len() {
+            self.slots.resize_with(slot + 1, || None);
+        }
+        let entry = &mut self.slots[slot];
+        if entry.is_none() {
+            self.len += 1;
+        }
+        &mut entry.get_or_insert_with(|| (id, HashSet::new())).1
+    }
+
+    fn insert(&mut self, id: u64, neighbours: HashSet<u64>) -> Option<HashSet<u64>> {
+        let previous = self.remove(&id);
+        *self.entry(id) = neighbours;
+        previous
+    }
+
+    fn remove(&mut self, id: &u64) -> Option<HashSet<u64>> {
+        let (_, neighbours) = self.slot_mut(*id)?.take()?;
+        self.len -= 1;
+        Some(neighbours)
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&u64, &mut HashSet<u64>) -> bool) {
+        for slot in &mut self.slots {
+            if slot
+                .as_mut()
+                .is_some_and(|(id, neighbours)| !keep(id, neighbours))
+            {
+                *slot = None;
+                self.len -= 1;
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a u64, &'a HashSet<u64>)>
+    where
+        u64: 'a,
+    {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|(id, neighbours)| (id, neighbours))
+    }
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = (&'a u64, &'a mut HashSet<u64>)>
+    where
+        u64: 'a,
+    {
+        self.slots
+            .iter_mut()
+            .flatten()
+            .map(|(id, neighbours)| (&*id, neighbours))
+    }
+}
+
+impl Index<&u64> for DenseAdjacency {
+    type Output = HashSet<u64>;
+
+    /// # Panics
+    ///
+    /// Panics if the observation is not in the graph.
+    fn index(&self, id: &u64) -> &Self::Output {
+        self.get(id).expect("the observation is in the graph")
+    }
+}
+
+impl PartialEq for DenseAdjacency {
+    /// Graphs are equal if they have the same observations and edges, however much space they have
+    /// reserved.
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .all(|(id, neighbours)| other.get(id) == Some(neighbours))
+    }
+}
+
+impl Eq for DenseAdjacency {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_adjacency_matches_a_hash_map() {
+        let mut dense = DenseAdjacency::default();
+        let mut sparse: HashMap<u64, HashSet<u64>> = HashMap::new();
+        for (a, b) in [(0, 1), (1, 2), (5, 2), (5, 0)] {
+            dense.entry(a).insert(b);
+            dense.entry(b).insert(a);
+            sparse.entry(a).or_default().insert(b);
+            sparse.entry(b).or_default().insert(a);
+        }
+        assert_eq!(Adjacency::len(&dense), sparse.len());
+        assert!(!dense.contains_key(&3));
+
+        assert_eq!(Adjacency::remove(&mut dense, &5), sparse.remove(&5));
+        assert_eq!(Adjacency::remove(&mut dense, &5), None);
+        Adjacency::retain(&mut dense, |id, _| *id != 1);
+        sparse.retain(|id, _| *id != 1);
+
+        let mut entries: Vec<_> = Adjacency::iter(&dense).collect();
+        entries.sort_by_key(|(id, _)| **id);
+        let mut expected: Vec<_> = sparse.iter().collect();
+        expected.sort_by_key(|(id, _)| **id);
+        assert_eq!(entries, expected);
+        assert_eq!(Adjacency::len(&dense), sparse.len());
+        assert_eq!(dense[&2], sparse[&2]);
+    }
+}
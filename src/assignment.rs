@@ -0,0 +1,585 @@
+//! Optimal one-to-one assignment between the observations of two contexts.
+
+use std::{
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+};
+
+/// The optimal one-to-one assignment between the observations of two contexts, such as
+/// consecutive frames from a sensor.
+///
+/// Only pairs of compatible observations may be assigned to each other. Each observation which is
+/// left unassigned incurs a cost of half the chi-squared threshold, so that a pair is assigned
+/// exactly when doing so is cheaper than leaving both of its observations unassigned. The
+/// assignment minimises the total cost.
+///
+/// See [`CliqueIndex::assign`](crate::CliqueIndex::assign) and
+/// [`CliqueIndex::assign_k_best`](crate::CliqueIndex::assign_k_best).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Assignment<Id> {
+    /// The assigned pairs, as `(first, second, d²)`, where `d²` is the squared Mahalanobis
+    /// distance between the two observations.
+    pub pairs: Vec<(Id, Id, f64)>,
+
+    /// The observations of the first context which are not assigned.
+    pub unassigned_first: Vec<Id>,
+
+    /// The observations of the second context which are not assigned.
+    pub unassigned_second: Vec<Id>,
+
+    /// The total cost of the assignment: the sum of the squared distances of the pairs, plus the
+    /// cost of each unassigned observation.
+    pub cost: f64,
+}
+
+impl<Id> Assignment<Id>
+where
+    Id: Copy,
+{
+    const fn empty() -> Self {
+        Self {
+            pairs: Vec::new(),
+            unassigned_first: Vec::new(),
+            unassigned_second: Vec::new(),
+            cost: 0.0,
+        }
+    }
+
+    /// Add the assignment of a disjoint set of observations.
+    fn extend(&mut self, other: &Self) {
+        self.pairs.extend_from_slice(&other.pairs);
+        self.unassigned_first
+            .extend_from_slice(&other.unassigned_first);
+        self.unassigned_second
+            .extend_from_slice(&other.unassigned_second);
+        self.cost += other.cost;
+    }
+}
+
+/// The squared distance from each observation of a first set to each compatible observation of a
+/// second.
+pub type Edges<Id> = HashMap<Id, Vec<(Id, f64)>>;
+
+/// Solve the optimal assignment between two sets of observations. See [`k_best`].
+pub fn assign<Id>(first: &[Id], second: &[Id], edges: &Edges<Id>, miss_cost: f64) -> Assignment<Id>
+where
+    Id: Eq + Hash + Copy,
+{
+    k_best(first, second, edges, miss_cost, 1)
+        .pop()
+        .expect("leaving every observation unassigned is always feasible")
+}
+
+/// Find the `k` best assignments between two sets of observations, in order of increasing cost.
+///
+/// The problem is split into the connected components of the edges,
+/// whose best assignments are found independently and then combined.
+pub fn k_best<Id>(
+    first: &[Id],
+    second: &[Id],
+    edges: &Edges<Id>,
+    miss_cost: f64,
+    k: usize,
+) -> Vec<Assignment<Id>>
+where
+    Id: Eq + Hash + Copy,
+{
+    // built in the order of the first set, so that the search below is reproducible
+    let mut reverse: HashMap<Id, Vec<Id>> = HashMap::new();
+    for a in first {
+        for (b, _) in edges.get(a).into_iter().flatten() {
+            reverse.entry(*b).or_default().push(*a);
+        }
+    }
+
+    // observations without any compatible observation are always unassigned
+    let mut isolated = Assignment::empty();
+    isolated.unassigned_second = second
+        .iter()
+        .filter(|b| !reverse.contains_key(b))
+        .copied()
+        .collect();
+    let mut components = Vec::new();
+
+    let mut visited: HashMap<Id, bool> = HashMap::new();
+    for &start in first {
+        if visited.contains_key(&start) {
+            continue;
+        }
+
+        // collect the component by breadth-first search, alternating between the two sets
+        let mut component_first = vec![start];
+        let mut component_second = Vec::new();
+        visited.insert(start, true);
+        let (mut next_first, mut next_second) = (0, 0);
+        while next_first < component_first.len() || next_second < component_second.len() {
+            while let Some(&a) = component_first.get(next_first) {
+                next_first += 1;
+                for (b, _) in edges.get(&a).into_iter().flatten() {
+                    if visited.insert(*b, false).is_none() {
+                        component_second.push(*b);
+                    }
+                }
+            }
+            while let Some(&b) = component_second.get(next_second) {
+                next_second += 1;
+                for a in reverse.get(&b).into_iter().flatten() {
+                    if visited.insert(*a, true).is_none() {
+                        component_first.push(*a);
+                    }
+                }
+            }
+        }
+
+        if component_second.is_empty() {
+            isolated.unassigned_first.push(start);
+        } else {
+            let component = Component::new(&component_first, &component_second, edges, miss_cost);
+            components.push(component.k_best(k));
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let isolated_count =
+        (isolated.unassigned_first.len() + isolated.unassigned_second.len()) as f64;
+    isolated.cost = miss_cost * isolated_count;
+    combine(&isolated, &components, k)
+}
+
+/// Combine the best assignments of independent components into the `k` best assignments overall.
+///
+/// Each component's assignments must be in order of increasing cost.
+fn combine<Id>(
+    isolated: &Assignment<Id>,
+    components: &[Vec<Assignment<Id>>],
+    k: usize,
+) -> Vec<Assignment<Id>>
+where
+    Id: Copy,
+{
+    // A combination is identified by the rank of the assignment chosen for each component. The
+    // successors of a combination increment the rank of a single component, at or after the one
+    // last incremented, so that each combination is generated exactly once.
+    let cost = |ranks: &[usize]| -> f64 {
+        ranks
+            .iter()
+            .zip(components)
+            .map(|(rank, component)| component[*rank].cost)
+            .sum()
+    };
+    let mut queue = BinaryHeap::new();
+    let initial = vec![0; components.len()];
+    queue.push(Candidate {
+        cost: cost(&initial),
+        item: (initial, 0),
+    });
+
+    let mut best = Vec::new();
+    while best.len() < k {
+        let Some(Candidate {
+            item: (ranks, last),
+            ..
+        }) = queue.pop()
+        else {
+            break;
+        };
+        let mut assignment = isolated.clone();
+        for (rank, component) in ranks.iter().zip(components) {
+            assignment.extend(&component[*rank]);
+        }
+        best.push(assignment);
+
+        for position in last..components.len() {
+            if ranks[position] + 1 < components[position].len() {
+                let mut next = ranks.clone();
+                next[position] += 1;
+                queue.push(Candidate {
+                    cost: cost(&next),
+                    item: (next, position),
+                });
+            }
+        }
+    }
+    best
+}
+
+/// An entry in a priority queue, ordered so that the least cost is popped first.
+#[derive(Debug)]
+struct Candidate<T> {
+    cost: f64,
+    item: T,
+}
+
+impl<T> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl<T> Eq for Candidate<T> {}
+
+impl<T> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Candidate<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// The assignment problem of a single connected component, as a square cost matrix.
+///
+/// Rows are the first set, followed by a 'miss' for each of the second set. Columns are the second
+/// set, followed by a 'miss' for each of the first set. A miss can only be paired with its own
+/// observation, and misses can be freely paired with each other.
+#[derive(Debug)]
+struct Component<'a, Id> {
+    first: &'a [Id],
+    second: &'a [Id],
+    costs: Vec<Vec<f64>>,
+}
+
+impl<'a, Id> Component<'a, Id>
+where
+    Id: Eq + Hash + Copy,
+{
+    fn new(first: &'a [Id], second: &'a [Id], edges: &Edges<Id>, miss_cost: f64) -> Self {
+        let (n, m) = (first.len(), second.len());
+        let columns: HashMap<Id, usize> = second.iter().enumerate().map(|(j, b)| (*b, j)).collect();
+        let mut costs = vec![vec![f64::INFINITY; n + m]; n + m];
+        for (i, a) in first.iter().enumerate() {
+            for (b, distance) in edges.get(a).into_iter().flatten() {
+                costs[i][columns[b]] = *distance;
+            }
+            costs[i][m + i] = miss_cost;
+        }
+        for j in 0..m {
+            costs[n + j][j] = miss_cost;
+            costs[n + j][m..].fill(0.0);
+        }
+        Self {
+            first,
+            second,
+            costs,
+        }
+    }
+
+    fn cost(&self, solution: &[usize]) -> f64 {
+        solution
+            .iter()
+            .enumerate()
+            .map(|(row, &column)| self.costs[row][column])
+            .sum()
+    }
+
+    fn assignment(&self, solution: &[usize]) -> Assignment<Id> {
+        let (n, m) = (self.first.len(), self.second.len());
+        let mut assignment = Assignment::empty();
+        for (i, &a) in self.first.iter().enumerate() {
+            let column = solution[i];
+            if column < m {
+                assignment
+                    .pairs
+                    .push((a, self.second[column], self.costs[i][column]));
+            } else {
+                assignment.unassigned_first.push(a);
+            }
+        }
+        for (j, &b) in self.second.iter().enumerate() {
+            if solution[n + j] < m {
+                assignment.unassigned_second.push(b);
+            }
+        }
+        assignment.cost = self.cost(solution);
+        assignment
+    }
+
+    /// The `k` best assignments of the component, in order of increasing cost, by Murty's
+    /// algorithm.
+    ///
+    /// The solutions of a subproblem, other than its best, are partitioned into further
+    /// subproblems, each of which fixes the choices of the best solution for some rows and excludes
+    /// the choice for the next. Only the rows of the first set are partitioned on, since the rest
+    /// of a solution is determined by them (up to exchanging misses, at no cost).
+    fn k_best(&self, k: usize) -> Vec<Assignment<Id>> {
+        let mut queue = BinaryHeap::new();
+        if let Some(solution) = hungarian(&self.costs) {
+            queue.push(Candidate {
+                cost: self.cost(&solution),
+                item: (self.costs.clone(), solution, 0),
+            });
+        }
+
+        let mut best = Vec::new();
+        while best.len() < k {
+            let Some(Candidate {
+                item: (mut costs, solution, fixed),
+                ..
+            }) = queue.pop()
+            else {
+                break;
+            };
+            best.push(self.assignment(&solution));
+            if best.len() == k {
+                break;
+            }
+
+            for row in fixed..self.first.len() {
+                let mut excluded = costs.clone();
+                excluded[row][solution[row]] = f64::INFINITY;
+                if let Some(next) = hungarian(&excluded) {
+                    queue.push(Candidate {
+                        cost: self.cost(&next),
+                        item: (excluded, next, row),
+                    });
+                }
+
+                // fix the choice for this row in the subsequent subproblems
+                let column = solution[row];
+                for (i, costs) in costs.iter_mut().enumerate() {
+                    for (j, cost) in costs.iter_mut().enumerate() {
+                        if (i == row) != (j == column) {
+                            *cost = f64::INFINITY;
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Find the assignment of rows to columns of a square cost matrix with the least total cost,
+/// using the Hungarian algorithm (in its `O(n³)` shortest augmenting path form).
+///
+/// Infinite costs are forbidden. Returns the column assigned to each row, or `None` if every
+/// assignment includes a forbidden cost.
+pub fn hungarian(costs: &[Vec<f64>]) -> Option<Vec<usize>> {
+    let n = costs.len();
+    // potentials of the rows and columns, and the row matched to each column (all one-based, with
+    // zero as a sentinel)
+    let mut row_potential = vec![0.0; n + 1];
+    let mut column_potential = vec![0.0; n + 1];
+    let mut matched = vec![0; n + 1];
+    let mut previous = vec![0; n + 1];
+
+    for row in 1..=n {
+        matched[0] = row;
+        let mut column = 0;
+        let mut slack = vec![f64::INFINITY; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[column] = true;
+            let current = matched[column];
+            let mut delta = f64::INFINITY;
+            let mut next = 0;
+            for j in 1..=n {
+                if used[j] {
+                    continue;
+                }
+                let reduced =
+                    costs[current - 1][j - 1] - row_potential[current] - column_potential[j];
+                if reduced < slack[j] {
+                    slack[j] = reduced;
+                    previous[j] = column;
+                }
+                if slack[j] < delta {
+                    delta = slack[j];
+                    next = j;
+                }
+            }
+            if !delta.is_finite() {
+                return None;
+            }
+            for j in 0..=n {
+                if used[j] {
+                    row_potential[matched[j]] += delta;
+                    column_potential[j] -= delta;
+                } else {
+                    slack[j] -= delta;
+                }
+            }
+            column = next;
+            if matched[column] == 0 {
+                break;
+            }
+        }
+        // augment along the alternating path
+        while column != 0 {
+            let prior = previous[column];
+            matched[column] = matched[prior];
+            column = prior;
+        }
+    }
+
+    let mut solution = vec![0; n];
+    for (column, &row) in matched.iter().enumerate().skip(1) {
+        solution[row - 1] = column - 1;
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    fn total(costs: &[Vec<f64>], solution: &[usize]) -> f64 {
+        solution
+            .iter()
+            .enumerate()
+            .map(|(row, &column)| costs[row][column])
+            .sum()
+    }
+
+    /// The least total cost over every permutation, by brute force.
+    fn brute_force(costs: &[Vec<f64>], row: usize, used: &mut Vec<bool>) -> f64 {
+        if row == costs.len() {
+            return 0.0;
+        }
+        let mut best = f64::INFINITY;
+        for column in 0..costs.len() {
+            if !used[column] {
+                used[column] = true;
+                best = best.min(costs[row][column] + brute_force(costs, row + 1, used));
+                used[column] = false;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn hungarian_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(9);
+        for _ in 0..200 {
+            let n = rng.random_range(1..7);
+            let costs: Vec<Vec<f64>> = (0..n)
+                .map(|_| {
+                    (0..n)
+                        .map(|_| {
+                            if rng.random_bool(0.2) {
+                                f64::INFINITY
+                            } else {
+                                rng.random_range(0.0..10.0)
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+
+            let best = brute_force(&costs, 0, &mut vec![false; n]);
+            match hungarian(&costs) {
+                Some(solution) => {
+                    approx::assert_relative_eq!(total(&costs, &solution), best, epsilon = 1e-9);
+                }
+                None => assert!(best.is_infinite()),
+            }
+        }
+    }
+
+    /// The cost of every matching, by brute force.
+    fn all_matchings(
+        first: &[u32],
+        edges: &Edges<u32>,
+        used: &mut Vec<u32>,
+        miss_cost: f64,
+        second_count: usize,
+        costs: &mut Vec<f64>,
+        cost: f64,
+    ) {
+        let Some((&a, rest)) = first.split_first() else {
+            #[allow(clippy::cast_precision_loss)]
+            let unmatched = (second_count - used.len()) as f64;
+            costs.push(miss_cost.mul_add(unmatched, cost));
+            return;
+        };
+        all_matchings(
+            rest,
+            edges,
+            used,
+            miss_cost,
+            second_count,
+            costs,
+            cost + miss_cost,
+        );
+        for &(b, distance) in edges.get(&a).into_iter().flatten() {
+            if !used.contains(&b) {
+                used.push(b);
+                all_matchings(
+                    rest,
+                    edges,
+                    used,
+                    miss_cost,
+                    second_count,
+                    costs,
+                    cost + distance,
+                );
+                used.pop();
+            }
+        }
+    }
+
+    #[test]
+    fn k_best_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..100 {
+            let first: Vec<u32> = (0..rng.random_range(1..6)).collect();
+            let second: Vec<u32> = (10..rng.random_range(11..16)).collect();
+            let mut edges: Edges<u32> = HashMap::new();
+            for &a in &first {
+                for &b in &second {
+                    if rng.random_bool(0.4) {
+                        edges
+                            .entry(a)
+                            .or_default()
+                            .push((b, rng.random_range(0.0..6.0)));
+                    }
+                }
+            }
+
+            let mut expected = Vec::new();
+            all_matchings(
+                &first,
+                &edges,
+                &mut Vec::new(),
+                3.0,
+                second.len(),
+                &mut expected,
+                0.0,
+            );
+            expected.sort_by(f64::total_cmp);
+
+            let best = k_best(&first, &second, &edges, 3.0, 10);
+            assert_eq!(best.len(), expected.len().min(10));
+            for (assignment, cost) in best.iter().zip(&expected) {
+                approx::assert_relative_eq!(assignment.cost, *cost, epsilon = 1e-9);
+                assert_eq!(
+                    assignment.pairs.len() * 2
+                        + assignment.unassigned_first.len()
+                        + assignment.unassigned_second.len(),
+                    first.len() + second.len()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn components_are_assigned_independently() {
+        // 1 and 2 compete for 10, 3 has no compatible observation, and 11 is alone
+        let edges = HashMap::from([(1, vec![(10, 1.0)]), (2, vec![(10, 0.5)])]);
+        let assignment = assign(&[1, 2, 3], &[10, 11], &edges, 3.0);
+
+        assert_eq!(assignment.pairs, vec![(2, 10, 0.5)]);
+        let mut unassigned = assignment.unassigned_first.clone();
+        unassigned.sort_unstable();
+        assert_eq!(unassigned, vec![1, 3]);
+        assert_eq!(assignment.unassigned_second, vec![11]);
+        // one pair, and three unassigned observations
+        approx::assert_relative_eq!(assignment.cost, 9.5);
+    }
+}
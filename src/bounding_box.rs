@@ -0,0 +1,47 @@
+/// An axis-aligned rectangular region of 2D cartesian space.
+///
+/// Bounds are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// The minimum x ordinate.
+    pub min_x: f64,
+    /// The minimum y ordinate.
+    pub min_y: f64,
+    /// The maximum x ordinate.
+    pub max_x: f64,
+    /// The maximum y ordinate.
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    /// Construct a bounding box from two opposite corners.
+    ///
+    /// The corners may be given in any order.
+    #[must_use]
+    pub const fn from_corners(a: (f64, f64), b: (f64, f64)) -> Self {
+        Self {
+            min_x: a.0.min(b.0),
+            min_y: a.1.min(b.1),
+            max_x: a.0.max(b.0),
+            max_y: a.1.max(b.1),
+        }
+    }
+
+    /// The extent of the bounding box in the x direction.
+    #[must_use]
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    /// The extent of the bounding box in the y direction.
+    #[must_use]
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// Returns `true` if the given point lies within the bounding box (inclusive).
+    #[must_use]
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
+}
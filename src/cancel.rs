@@ -0,0 +1,135 @@
+//! Cooperative cancellation of, and progress reporting for, long-running operations.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// A handle for cancelling a long-running operation, such as building an index from a large batch
+/// of observations.
+///
+/// Clones share the same state, so one clone can be passed to the operation while another is kept
+/// to cancel it (for example, from another thread). The operation checks the token periodically,
+/// and stops soon after it is cancelled.
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CancellationToken, CliqueIndex, Config, Error};
+///
+/// let cancellation = CancellationToken::new();
+/// cancellation.cancel();
+///
+/// let result = CliqueIndex::<u32>::from_observations_cancellable(
+///     Vec::new(),
+///     Config::new(CHI2_2D_CONFIDENCE_95),
+///     &cancellation,
+/// );
+/// assert!(matches!(result, Err(Error::Cancelled)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// A token which has not been cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the operations using this token (or any of its clones).
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// An operation was stopped by its [`CancellationToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+/// A phase of building an index from a batch of observations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildPhase {
+    /// Bulk-loading the observations into the spatial index.
+    SpatialIndex,
+
+    /// Finding the compatible pairs of observations.
+    CompatibilityGraph,
+
+    /// Enumerating the cliques of the compatibility graph.
+    CliqueEnumeration,
+}
+
+/// The progress of building an index from a batch of observations.
+///
+/// See [`CliqueIndex::from_observations_with_progress`](crate::CliqueIndex::from_observations_with_progress).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The current phase. The phases are reported in order.
+    pub phase: BuildPhase,
+
+    /// The fraction of the current phase which is complete, between 0 and 1.
+    pub fraction: f64,
+}
+
+/// The cancellation token and progress callback of a long-running operation, either of which may
+/// be absent.
+#[derive(Default)]
+pub struct Monitor<'a> {
+    cancellation: Option<&'a CancellationToken>,
+    progress: Option<&'a mut dyn FnMut(Progress)>,
+}
+
+impl<'a> Monitor<'a> {
+    pub fn new(
+        cancellation: Option<&'a CancellationToken>,
+        progress: Option<&'a mut dyn FnMut(Progress)>,
+    ) -> Self {
+        Self {
+            cancellation,
+            progress,
+        }
+    }
+
+    pub const fn cancellation(&self) -> Option<&'a CancellationToken> {
+        self.cancellation
+    }
+
+    /// Returns `true` if there is a token, and it has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        is_cancelled(self.cancellation)
+    }
+
+    /// Report that `done` of the `total` steps of a phase are complete, and check for
+    /// cancellation.
+    ///
+    /// Progress is reported at most once per percent, so this can be called for every step.
+    pub fn step(&mut self, phase: BuildPhase, done: usize, total: usize) -> Result<(), Cancelled> {
+        if let Some(progress) = &mut self.progress {
+            let stride = (total / 100).max(1);
+            if done % stride == 0 || done == total {
+                #[allow(clippy::cast_precision_loss)]
+                let fraction = if total == 0 {
+                    1.0
+                } else {
+                    done as f64 / total as f64
+                };
+                progress(Progress { phase, fraction });
+            }
+        }
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Returns `true` if there is a token, and it has been cancelled.
+pub fn is_cancelled(cancellation: Option<&CancellationToken>) -> bool {
+    cancellation.is_some_and(CancellationToken::is_cancelled)
+}
@@ -0,0 +1,283 @@
+//! Candidate neighbours found by bucketing observations into spatial cells.
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::Arc,
+};
+
+use crate::{BoundingBox, CandidateSource, Observation, Unique};
+
+/// A partition of the plane into cells, such as a regular grid, or a hierarchical geodetic
+/// system like H3 or S2.
+///
+/// Implement this trait to bucket observations using the same cells as the rest of a geospatial
+/// stack, for example by mapping each position to its cell in a hierarchical grid at a fixed
+/// resolution, and covering a bounding box with the cells which intersect it. With the `h3`
+/// feature, [`H3Cells`](crate::H3Cells) does this for the H3 grid.
+pub trait CellSystem {
+    /// The identifier of a cell.
+    type Cell: Eq + Hash + Copy;
+
+    /// The cell containing the given position.
+    fn cell(&self, x: f64, y: f64) -> Self::Cell;
+
+    /// The cells which together cover the given bounding box.
+    ///
+    /// Every cell containing a position within the bounds must be included.
+    fn covering(&self, bounds: &BoundingBox) -> Vec<Self::Cell>;
+
+    /// An estimate of the number of cells in the [covering](Self::covering) of the bounds, which
+    /// is cheap to compute without building it.
+    ///
+    /// If this exceeds the number of occupied cells, the occupied cells are searched instead.
+    fn covering_size(&self, bounds: &BoundingBox) -> usize;
+
+    /// Returns `true` if the cell may contain a position within the bounds.
+    ///
+    /// This must be `true` for every cell in the [covering](Self::covering) of the bounds, but may
+    /// be `true` for others. By default, every cell may intersect the bounds.
+    fn intersects(&self, _cell: &Self::Cell, _bounds: &BoundingBox) -> bool {
+        true
+    }
+}
+
+/// A regular grid of square cells, aligned with the axes.
+///
+/// The cell size should be comparable to the typical compatibility radius of the observations.
+/// Much smaller cells mean that many (mostly empty) cells are searched for each query, and much
+/// larger cells mean that many incompatible candidates are tested.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SquareCells {
+    size: f64,
+}
+
+impl SquareCells {
+    /// A grid of cells with the given width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is not finite and strictly positive.
+    #[must_use]
+    pub fn new(size: f64) -> Self {
+        assert!(
+            size.is_finite() && size > 0.0,
+            "cell size must be finite and positive (got {size})"
+        );
+        Self { size }
+    }
+
+    /// The width of each cell.
+    #[must_use]
+    pub const fn size(&self) -> f64 {
+        self.size
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn bin(self, value: f64) -> i64 {
+        (value / self.size).floor() as i64
+    }
+}
+
+impl CellSystem for SquareCells {
+    /// The column and row of the cell.
+    type Cell = (i64, i64);
+
+    fn cell(&self, x: f64, y: f64) -> Self::Cell {
+        (self.bin(x), self.bin(y))
+    }
+
+    fn covering(&self, bounds: &BoundingBox) -> Vec<Self::Cell> {
+        let columns = self.bin(bounds.min_x)..=self.bin(bounds.max_x);
+        let rows = self.bin(bounds.min_y)..=self.bin(bounds.max_y);
+        columns
+            .flat_map(|column| rows.clone().map(move |row| (column, row)))
+            .collect()
+    }
+
+    fn covering_size(&self, bounds: &BoundingBox) -> usize {
+        // the bins saturate for huge bounds, so the span is computed without overflow
+        let span = |min, max| {
+            let span = i128::from(self.bin(max)) - i128::from(self.bin(min)) + 1;
+            usize::try_from(span.max(0)).unwrap_or(usize::MAX)
+        };
+        span(bounds.min_x, bounds.max_x).saturating_mul(span(bounds.min_y, bounds.max_y))
+    }
+
+    fn intersects(&self, &(column, row): &Self::Cell, bounds: &BoundingBox) -> bool {
+        (self.bin(bounds.min_x)..=self.bin(bounds.max_x)).contains(&column)
+            && (self.bin(bounds.min_y)..=self.bin(bounds.max_y)).contains(&row)
+    }
+}
+
+/// A [`CandidateSource`] which buckets observations into the cells of a [`CellSystem`].
+///
+/// The candidate neighbours of an observation are the members of the cells covering the region
+/// within its compatibility radius.
+///
+/// ```
+/// use clique_fusion::{
+///     CHI2_2D_CONFIDENCE_95, CellCandidates, CliqueIndex, Config, Observation, SquareCells, Unique,
+/// };
+///
+/// let source = CellCandidates::new(SquareCells::new(10.0));
+/// let mut index = CliqueIndex::with_candidate_source(Config::new(CHI2_2D_CONFIDENCE_95), source);
+/// for (id, x) in [(1, 9.5), (2, 10.5), (3, 100.0)] {
+///     let observation = Observation::builder(x, 0.0)
+///         .circular_95_confidence_error(1.0)
+///         .unwrap()
+///         .build();
+///     index.insert(Unique { data: observation, id });
+/// }
+///
+/// assert_eq!(index.cliques().len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CellCandidates<S, Id>
+where
+    S: CellSystem,
+{
+    system: S,
+    members: HashMap<S::Cell, Vec<Id>>,
+}
+
+impl<S, Id> CellCandidates<S, Id>
+where
+    S: CellSystem,
+{
+    /// An empty set of cells.
+    #[must_use]
+    pub fn new(system: S) -> Self {
+        Self {
+            system,
+            members: HashMap::new(),
+        }
+    }
+
+    /// The system of cells into which observations are bucketed.
+    pub const fn system(&self) -> &S {
+        &self.system
+    }
+
+    /// Iterate over the occupied cells, with the IDs of the observations within them.
+    ///
+    /// This can be used to partition the observations by cell, for example to distribute them
+    /// between several processes.
+    pub fn cells(&self) -> impl Iterator<Item = (S::Cell, &[Id])> {
+        self.members
+            .iter()
+            .map(|(cell, members)| (*cell, members.as_slice()))
+    }
+}
+
+impl<S, Id> CandidateSource<Id> for CellCandidates<S, Id>
+where
+    S: CellSystem,
+    Id: Eq + Copy,
+    Self: Send + Sync + UnwindSafe + RefUnwindSafe,
+{
+    fn insert(&mut self, observation: &Unique<Arc<Observation>, Id>) {
+        let (x, y) = observation.data.position();
+        self.members
+            .entry(self.system.cell(x, y))
+            .or_default()
+            .push(observation.id);
+    }
+
+    fn remove(&mut self, observation: &Unique<Arc<Observation>, Id>) {
+        let (x, y) = observation.data.position();
+        let cell = self.system.cell(x, y);
+        if let Some(members) = self.members.get_mut(&cell) {
+            members.retain(|id| *id != observation.id);
+            if members.is_empty() {
+                self.members.remove(&cell);
+            }
+        }
+    }
+
+    fn locate(&self, bounds: &BoundingBox) -> Vec<Id> {
+        // A large query (for example, under a motion model with a long time span) can be covered
+        // by far more cells than are occupied, so only the occupied cells are searched instead
+        if self.system.covering_size(bounds) > self.members.len() {
+            return self
+                .members
+                .iter()
+                .filter(|(cell, _)| self.system.intersects(cell, bounds))
+                .flat_map(|(_, members)| members)
+                .copied()
+                .collect();
+        }
+        self.system
+            .covering(bounds)
+            .into_iter()
+            .filter_map(|cell| self.members.get(&cell))
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_cells_cover_the_bounds() {
+        let cells = SquareCells::new(10.0);
+        assert_eq!(cells.cell(-0.5, 25.0), (-1, 2));
+
+        let bounds = BoundingBox::from_corners((-5.0, 5.0), (15.0, 5.0));
+        let covering = cells.covering(&bounds);
+        assert_eq!(covering, vec![(-1, 0), (0, 0), (1, 0)]);
+        assert_eq!(cells.covering_size(&bounds), covering.len());
+        assert!(covering.iter().all(|cell| cells.intersects(cell, &bounds)));
+        assert!(!cells.intersects(&(2, 0), &bounds));
+    }
+
+    #[test]
+    fn huge_bounds_search_the_occupied_cells() {
+        let observation = |id, x| Unique {
+            data: Arc::new(
+                Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .build(),
+            ),
+            id,
+        };
+        let mut source = CellCandidates::new(SquareCells::new(1e-6));
+        source.insert(&observation(1, -1e9));
+        source.insert(&observation(2, 1e9));
+        source.insert(&observation(3, 5e9));
+
+        let bounds = BoundingBox::from_corners((-2e9, -2e9), (2e9, 2e9));
+        assert_eq!(source.system().covering_size(&bounds), usize::MAX);
+        let mut located = source.locate(&bounds);
+        located.sort_unstable();
+        assert_eq!(located, vec![1, 2]);
+    }
+
+    #[test]
+    fn removed_observations_are_not_located() {
+        let observation = |id, x| Unique {
+            data: Arc::new(
+                Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .build(),
+            ),
+            id,
+        };
+        let mut source = CellCandidates::new(SquareCells::new(1.0));
+        source.insert(&observation(1, 0.5));
+        source.insert(&observation(2, 0.6));
+        source.remove(&observation(1, 0.5));
+
+        let bounds = BoundingBox::from_corners((0.0, 0.0), (1.0, 1.0));
+        assert_eq!(source.locate(&bounds), vec![2]);
+
+        source.remove(&observation(2, 0.6));
+        assert_eq!(source.cells().count(), 0);
+    }
+}
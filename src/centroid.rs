@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use nalgebra::{Matrix2, Vector2};
+
+use crate::{CovarianceMatrix, Observation};
+
+/// The geometric centre and extent of the members of a clique.
+///
+/// This ignores the observations' errors, so is much cheaper to compute than a
+/// [`FusedEstimate`](crate::FusedEstimate). It is intended for display (such as placing a single
+/// marker per clique on a map), not as an estimate of the object's position.
+///
+/// See [`CliqueIndex::clique_centroids`](crate::CliqueIndex::clique_centroids).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Id: serde::Serialize",
+        deserialize = "Id: serde::Deserialize<'de> + Eq + std::hash::Hash"
+    ))
+)]
+pub struct CliqueCentroid<Id> {
+    /// The members of the clique.
+    pub members: HashSet<Id>,
+
+    /// The x ordinate of the mean of the member positions.
+    pub x: f64,
+
+    /// The y ordinate of the mean of the member positions.
+    pub y: f64,
+
+    /// The shape of an ellipse, centred on the centroid, which bounds the member positions.
+    ///
+    /// Every member position `p` satisfies `(p - c)ᵀ E⁻¹ (p - c) ≤ 1`, where `c` is the centroid
+    /// and `E` is this matrix. The ellipse is the scatter of the positions, scaled to just contain
+    /// the outermost member. It is degenerate (a line segment, or a point) if the members are
+    /// collinear (or coincident).
+    pub extent: CovarianceMatrix,
+}
+
+impl<Id> CliqueCentroid<Id> {
+    /// The centroid (x, y).
+    #[must_use]
+    pub const fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    /// The principal axes of the bounding ellipse.
+    ///
+    /// Returns the semi-major axis length, the semi-minor axis length, and the orientation of the
+    /// semi-major axis (in radians, anticlockwise from the x axis).
+    #[must_use]
+    pub fn axes(&self) -> (f64, f64, f64) {
+        self.extent.ellipse_axes(1.0)
+    }
+}
+
+/// Compute the centroid and bounding ellipse of a set of observations' positions.
+///
+/// Returns `None` if `observations` is empty.
+pub fn centroid<'a>(
+    observations: impl IntoIterator<Item = &'a Observation>,
+) -> Option<(f64, f64, CovarianceMatrix)> {
+    let positions: Vec<Vector2<f64>> = observations
+        .into_iter()
+        .map(|observation| Vector2::new(observation.x(), observation.y()))
+        .collect();
+    if positions.is_empty() {
+        return None;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let n = positions.len() as f64;
+    let mean = positions.iter().sum::<Vector2<f64>>() / n;
+    let scatter = positions
+        .iter()
+        .map(|position| {
+            let offset = position - mean;
+            offset * offset.transpose()
+        })
+        .sum::<Matrix2<f64>>()
+        / n;
+
+    let scatter = CovarianceMatrix::from_matrix(scatter);
+    let scale = scatter.safe_inverse().map_or(0.0, |inverse| {
+        positions
+            .iter()
+            .map(|position| {
+                let offset = position - mean;
+                (offset.transpose() * inverse * offset)[(0, 0)]
+            })
+            .fold(0.0, f64::max)
+    });
+
+    Some((
+        mean.x,
+        mean.y,
+        CovarianceMatrix::from_matrix(Matrix2::from(scatter) * scale),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn observation(x: f64, y: f64) -> Observation {
+        Observation::builder(x, y)
+            .error(CovarianceMatrix::identity())
+            .build()
+    }
+
+    #[test]
+    fn square_is_bounded_by_its_circumcircle() {
+        let observations = [
+            observation(0.0, 0.0),
+            observation(2.0, 0.0),
+            observation(2.0, 2.0),
+            observation(0.0, 2.0),
+        ];
+
+        let (x, y, extent) = centroid(&observations).unwrap();
+
+        assert_relative_eq!(x, 1.0);
+        assert_relative_eq!(y, 1.0);
+        assert_relative_eq!(extent.xx(), 2.0, epsilon = 1e-12);
+        assert_relative_eq!(extent.yy(), 2.0, epsilon = 1e-12);
+        assert_relative_eq!(extent.xy(), 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn members_lie_within_the_ellipse() {
+        let observations = [
+            observation(0.0, 0.0),
+            observation(4.0, 1.0),
+            observation(1.0, 3.0),
+            observation(2.0, 1.5),
+        ];
+
+        let (x, y, extent) = centroid(&observations).unwrap();
+        let inverse = extent.safe_inverse().unwrap();
+
+        let distances: Vec<f64> = observations
+            .iter()
+            .map(|observation| {
+                let offset = Vector2::new(observation.x() - x, observation.y() - y);
+                (offset.transpose() * inverse * offset)[(0, 0)]
+            })
+            .collect();
+        assert!(distances.iter().all(|&d| d <= 1.0 + 1e-12));
+        assert_relative_eq!(distances.iter().copied().fold(0.0, f64::max), 1.0);
+    }
+
+    #[test]
+    fn degenerate_inputs() {
+        let (x, y, extent) = centroid(&[observation(3.0, 4.0)]).unwrap();
+        assert_relative_eq!(x, 3.0);
+        assert_relative_eq!(y, 4.0);
+        assert_relative_eq!(extent.max_variance(), 0.0);
+
+        // collinear members give a line segment through both points
+        let (_, _, extent) = centroid(&[observation(0.0, 0.0), observation(2.0, 0.0)]).unwrap();
+        assert_relative_eq!(extent.xx(), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(extent.yy(), 0.0, epsilon = 1e-12);
+
+        assert!(centroid([]).is_none());
+    }
+}
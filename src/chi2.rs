@@ -0,0 +1,237 @@
+use std::f64::consts::PI;
+
+/// Compute the chi-squared threshold for an arbitrary confidence level.
+///
+/// This is the quantile function (inverse CDF) of the chi-squared distribution with
+/// `degrees_of_freedom` degrees of freedom, evaluated at `confidence`. Observations in 2D have two
+/// degrees of freedom, so `chi2_threshold(0.95, 2)` is (approximately) [`CHI2_2D_CONFIDENCE_95`].
+///
+/// [`CHI2_2D_CONFIDENCE_95`]: crate::CHI2_2D_CONFIDENCE_95
+///
+/// # Example
+///
+/// ```
+/// use clique_fusion::{CliqueIndex, chi2_threshold};
+///
+/// let chi2 = chi2_threshold(0.975, 2)?;
+/// let index: CliqueIndex<u32> = CliqueIndex::new(chi2);
+/// # Ok::<(), clique_fusion::InvalidConfidence>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if `confidence` is not strictly between 0 and 1, or if `degrees_of_freedom` is 0.
+pub fn chi2_threshold(confidence: f64, degrees_of_freedom: u32) -> Result<f64, InvalidConfidence> {
+    if !(confidence > 0.0 && confidence < 1.0) || degrees_of_freedom == 0 {
+        return Err(InvalidConfidence {
+            confidence,
+            degrees_of_freedom,
+        });
+    }
+
+    let shape = f64::from(degrees_of_freedom) / 2.0;
+    let cdf = |x: f64| lower_regularized_gamma(shape, x / 2.0);
+
+    // Bracket the quantile, then bisect. The CDF is monotonic, so this always converges.
+    let mut low = 0.0;
+    let mut high = f64::from(degrees_of_freedom).max(1.0);
+    // The quantile is finite for any confidence below 1, so this terminates long before `high`
+    // could overflow
+    loop {
+        if cdf(high) >= confidence {
+            break;
+        }
+        low = high;
+        high *= 2.0;
+    }
+    for _ in 0..200 {
+        let mid = f64::midpoint(low, high);
+        if mid <= low || mid >= high {
+            break;
+        }
+        if cdf(mid) < confidence {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Ok(f64::midpoint(low, high))
+}
+
+/// The probability of a chi-squared statistic at least as large as `statistic`, with
+/// `degrees_of_freedom` degrees of freedom (the survival function of the distribution).
+///
+/// With no degrees of freedom the statistic should be exactly zero, so any positive value has
+/// probability zero.
+pub fn chi2_p_value(statistic: f64, degrees_of_freedom: u32) -> f64 {
+    if statistic <= 0.0 {
+        return 1.0;
+    }
+    if degrees_of_freedom == 0 || !statistic.is_finite() {
+        return 0.0;
+    }
+    1.0 - lower_regularized_gamma(f64::from(degrees_of_freedom) / 2.0, statistic / 2.0)
+}
+
+/// The error returned when a chi-squared threshold is requested for an invalid confidence level.
+#[derive(Debug, thiserror::Error, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[error(
+    "confidence must be strictly between 0 and 1, with at least one degree of freedom (got confidence {confidence}, {degrees_of_freedom} degrees of freedom)"
+)]
+pub struct InvalidConfidence {
+    confidence: f64,
+    degrees_of_freedom: u32,
+}
+
+/// The regularized lower incomplete gamma function, `P(a, x)`.
+///
+/// Evaluated by its series expansion for `x < a + 1`, and by a continued fraction otherwise.
+fn lower_regularized_gamma(shape: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-15;
+    const MAX_ITERATIONS: usize = 1000;
+
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    let log_prefactor = shape.mul_add(x.ln(), -x) - ln_gamma(shape);
+
+    if x < shape + 1.0 {
+        let mut term = 1.0 / shape;
+        let mut sum = term;
+        let mut denominator = shape;
+        for _ in 0..MAX_ITERATIONS {
+            denominator += 1.0;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        (sum * log_prefactor.exp()).min(1.0)
+    } else {
+        // Modified Lentz's method for the continued fraction of the upper function Q(a, x)
+        let tiny = f64::MIN_POSITIVE / EPSILON;
+        let mut denominator = x + 1.0 - shape;
+        let mut numerator_ratio = 1.0 / tiny;
+        let mut denominator_ratio = 1.0 / denominator;
+        let mut fraction = denominator_ratio;
+        let mut step = 1.0;
+        for _ in 0..MAX_ITERATIONS {
+            let coefficient = -step * (step - shape);
+            denominator += 2.0;
+            denominator_ratio = coefficient.mul_add(denominator_ratio, denominator);
+            if denominator_ratio.abs() < tiny {
+                denominator_ratio = tiny;
+            }
+            numerator_ratio = denominator + coefficient / numerator_ratio;
+            if numerator_ratio.abs() < tiny {
+                numerator_ratio = tiny;
+            }
+            denominator_ratio = 1.0 / denominator_ratio;
+            let delta = denominator_ratio * numerator_ratio;
+            fraction *= delta;
+            if (delta - 1.0).abs() < EPSILON {
+                break;
+            }
+            step += 1.0;
+        }
+        log_prefactor.exp().mul_add(-fraction, 1.0).max(0.0)
+    }
+}
+
+/// The natural logarithm of the gamma function, by the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    let mut offset = x;
+    for coefficient in &COEFFICIENTS[1..] {
+        offset += 1.0;
+        sum += coefficient / offset;
+    }
+    let t = x + G + 0.5;
+    0.5f64.mul_add((2.0 * PI).ln(), (x + 0.5) * t.ln()) - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{CHI2_2D_CONFIDENCE_90, CHI2_2D_CONFIDENCE_95, CHI2_2D_CONFIDENCE_99};
+
+    #[test]
+    fn matches_tabulated_2d_thresholds() {
+        assert_relative_eq!(
+            chi2_threshold(0.90, 2).unwrap(),
+            CHI2_2D_CONFIDENCE_90,
+            epsilon = 1e-3
+        );
+        assert_relative_eq!(
+            chi2_threshold(0.95, 2).unwrap(),
+            CHI2_2D_CONFIDENCE_95,
+            epsilon = 1e-3
+        );
+        assert_relative_eq!(
+            chi2_threshold(0.99, 2).unwrap(),
+            CHI2_2D_CONFIDENCE_99,
+            epsilon = 1e-3
+        );
+
+        // With two degrees of freedom the quantile has the closed form -2 ln(1 - p)
+        assert_relative_eq!(
+            chi2_threshold(0.5, 2).unwrap(),
+            -2.0 * 0.5f64.ln(),
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn p_values_invert_thresholds() {
+        assert_relative_eq!(chi2_p_value(CHI2_2D_CONFIDENCE_95, 2), 0.05, epsilon = 1e-4);
+        assert_relative_eq!(chi2_p_value(23.209_251, 10), 0.01, epsilon = 1e-6);
+        assert_relative_eq!(chi2_p_value(0.0, 4), 1.0);
+        assert_relative_eq!(chi2_p_value(1.0, 0), 0.0);
+        assert_relative_eq!(chi2_p_value(f64::INFINITY, 2), 0.0);
+    }
+
+    #[test]
+    fn matches_tabulated_thresholds_for_other_dimensions() {
+        assert_relative_eq!(chi2_threshold(0.95, 1).unwrap(), 3.841_459, epsilon = 1e-5);
+        assert_relative_eq!(chi2_threshold(0.95, 3).unwrap(), 7.814_728, epsilon = 1e-5);
+        assert_relative_eq!(
+            chi2_threshold(0.99, 10).unwrap(),
+            23.209_251,
+            epsilon = 1e-5
+        );
+        assert_relative_eq!(chi2_threshold(0.05, 4).unwrap(), 0.710_723, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        assert!(chi2_threshold(0.0, 2).is_err());
+        assert!(chi2_threshold(1.0, 2).is_err());
+        assert!(chi2_threshold(f64::NAN, 2).is_err());
+        assert!(chi2_threshold(0.95, 0).is_err());
+    }
+}
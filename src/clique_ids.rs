@@ -0,0 +1,184 @@
+//! Allocation of the stable identifiers of cliques.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    panic::{RefUnwindSafe, UnwindSafe},
+};
+
+use uuid::Uuid;
+
+/// How a [`CliqueIndex`](crate::CliqueIndex) allocates [clique identifiers](crate::CliqueId).
+///
+/// An identifier is released when its clique is dissolved (by a merge, a split, or the removal of
+/// its members). Whether released identifiers are reused is determined by the policy.
+///
+/// See [`Config::clique_ids`](crate::Config::clique_ids). To allocate identifiers some other way,
+/// see [`CliqueIndex::set_clique_id_allocator`](crate::CliqueIndex::set_clique_id_allocator).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CliqueIdAllocation {
+    /// Identifiers are allocated in increasing order from zero, and are never reused. See
+    /// [`MonotonicIds`].
+    #[default]
+    Monotonic,
+
+    /// The lowest released identifier is reused, if there is one, so that identifiers stay small.
+    /// See [`RecyclingIds`].
+    ///
+    /// A downstream store which keys records by clique identifier must not assume that an
+    /// identifier refers to the same clique forever.
+    Recycling,
+
+    /// Identifiers are drawn at random, so that they are very unlikely to collide with the
+    /// identifiers allocated by other indices. See [`RandomIds`].
+    Random,
+}
+
+impl CliqueIdAllocation {
+    pub(crate) fn allocator(self) -> Box<dyn CliqueIdAllocator> {
+        match self {
+            Self::Monotonic => Box::new(MonotonicIds::default()),
+            Self::Recycling => Box::new(RecyclingIds::default()),
+            Self::Random => Box::new(RandomIds::default()),
+        }
+    }
+}
+
+/// A source of [clique identifiers](crate::CliqueId).
+///
+/// Implement this trait to allocate identifiers from a caller-side sequence, such as the keys of
+/// a downstream store.
+///
+/// Allocators must be thread- and unwind-safe, so that the index which owns them is too.
+///
+/// See [`CliqueIndex::set_clique_id_allocator`](crate::CliqueIndex::set_clique_id_allocator).
+pub trait CliqueIdAllocator: Send + Sync + UnwindSafe + RefUnwindSafe {
+    /// Allocate the identifier of a new clique.
+    ///
+    /// This must not return the identifier of a clique which is still live (that is, which has
+    /// been allocated and not since released).
+    fn allocate(&mut self) -> u64;
+
+    /// Called when the clique with the given identifier is dissolved.
+    ///
+    /// The identifier may be returned by a later call to [`Self::allocate`]. Allocators which
+    /// never reuse identifiers may ignore this.
+    fn release(&mut self, _id: u64) {}
+}
+
+/// Allocates identifiers in increasing order, and never reuses them.
+///
+/// ```
+/// use clique_fusion::{CliqueIdAllocator, MonotonicIds};
+///
+/// // continue from the identifiers allocated before a restart
+/// let mut ids = MonotonicIds::starting_at(1000);
+/// assert_eq!(ids.allocate(), 1000);
+/// ids.release(1000);
+/// assert_eq!(ids.allocate(), 1001);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MonotonicIds {
+    next: u64,
+}
+
+impl MonotonicIds {
+    /// An allocator whose first identifier is `next`.
+    ///
+    /// This can be used to continue the sequence of a previous index, so that the identifiers of
+    /// the two never collide.
+    #[must_use]
+    pub const fn starting_at(next: u64) -> Self {
+        Self { next }
+    }
+}
+
+impl CliqueIdAllocator for MonotonicIds {
+    fn allocate(&mut self) -> u64 {
+        let id = self.next;
+        self.next += 1;
+        id
+    }
+}
+
+/// Allocates the lowest released identifier, or else the next in increasing order.
+///
+/// ```
+/// use clique_fusion::{CliqueIdAllocator, RecyclingIds};
+///
+/// let mut ids = RecyclingIds::default();
+/// assert_eq!((ids.allocate(), ids.allocate(), ids.allocate()), (0, 1, 2));
+/// ids.release(1);
+/// assert_eq!(ids.allocate(), 1);
+/// assert_eq!(ids.allocate(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RecyclingIds {
+    next: u64,
+    released: BTreeSet<u64>,
+}
+
+impl CliqueIdAllocator for RecyclingIds {
+    fn allocate(&mut self) -> u64 {
+        self.released.pop_first().unwrap_or_else(|| {
+            let id = self.next;
+            self.next += 1;
+            id
+        })
+    }
+
+    fn release(&mut self, id: u64) {
+        if id < self.next {
+            self.released.insert(id);
+        }
+    }
+}
+
+/// Allocates identifiers from the random bits of version 4 UUIDs.
+///
+/// Live identifiers are never duplicated, and a released identifier is only reused by chance,
+/// with negligible probability.
+#[derive(Debug, Clone, Default)]
+pub struct RandomIds {
+    live: HashSet<u64>,
+}
+
+impl CliqueIdAllocator for RandomIds {
+    fn allocate(&mut self) -> u64 {
+        loop {
+            let (id, _) = Uuid::new_v4().as_u64_pair();
+            if self.live.insert(id) {
+                return id;
+            }
+        }
+    }
+
+    fn release(&mut self, id: u64) {
+        self.live.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn live_identifiers_are_unique() {
+        for policy in [
+            CliqueIdAllocation::Monotonic,
+            CliqueIdAllocation::Recycling,
+            CliqueIdAllocation::Random,
+        ] {
+            let mut allocator = policy.allocator();
+            let mut live: HashSet<u64> = (0..100).map(|_| allocator.allocate()).collect();
+            assert_eq!(live.len(), 100);
+            for id in live.clone().into_iter().take(50) {
+                allocator.release(id);
+                live.remove(&id);
+            }
+            for _ in 0..100 {
+                assert!(live.insert(allocator.allocate()), "{policy:?}");
+            }
+        }
+    }
+}
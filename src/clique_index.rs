@@ -1,17 +1,100 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{BuildHasher, BuildHasherDefault, Hasher},
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+    time::{Duration, Instant},
+};
 
-use crate::{Observation, Unique, cliques::find_maximal_cliques, spatial_index::SpatialIndex};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use uuid::Uuid;
+
+#[cfg(feature = "validate")]
+use crate::InvariantViolation;
+
+use crate::{
+    Adjacency, Assignment, BoundingBox, BuildPhase, CancellationToken, CandidateSource,
+    CliqueCentroid, CliqueEventSink, CliqueHistory, CliqueId, CliqueIdAllocator, CliqueOverflow,
+    CliqueRelation, CliqueSet, Compatibility, Config, CovarianceMatrix, DenseAdjacency,
+    DensityGrid, DiscardLog, Error, FlatCliques, FusedClique, FusedEstimate, FusionMethod,
+    GraphExport, Health, InvalidChi2, Observation, ProbeResult, TimedCliqueEvent, Unique,
+    assignment,
+    cancel::{Cancelled, Monitor},
+    centroid::centroid,
+    clique_diff,
+    cliques::{
+        complete_linkage_clusters, component_containing, connected_components, density,
+        find_maximal_cliques, find_maximal_cliques_containing, find_maximal_cliques_monitored,
+        mutual_nearest_neighbours,
+    },
+    duplicates::Duplicates,
+    events::CliqueEvents,
+    eviction::Arrivals,
+    fusion::{fuse_members, goodness_of_fit},
+    history::Histories,
+    profiling::Phase,
+    spatial_index::SpatialIndex,
+    stable_hash::StableHasher,
+    union_find::UnionFind,
+};
 
 /// An index which tracks the 'cliques' in the set of observations.
 ///
 /// A 'clique' in this case represents a cluster of observations which lie mutually within each other's error ellipses,
 /// and are therefore consistent with being observations of the same underlying object.
+///
+/// The compatibility graph is stored as `G`, which is a [`HashMap`] by default, so that any type of
+/// ID can be used. An index over small integer IDs can store it more compactly, as a
+/// [`DenseAdjacency`](crate::DenseAdjacency) (see [`IntegerCliqueIndex`]).
 #[derive(Debug)]
-pub struct CliqueIndex<Id> {
+pub struct CliqueIndex<Id, G = HashMap<Id, HashSet<Id>>> {
     spatial_index: SpatialIndex<Id>,
-    compatibility_graph: HashMap<Id, HashSet<Id>>,
-    cliques: Vec<HashSet<Id>>,
-    chi2: f64,
+    compatibility_graph: G,
+    cliques: CliqueSet<Id>,
+
+    /// The stable identifiers of the cliques, in the same order.
+    clique_ids: Vec<CliqueId>,
+
+    histories: Histories<Id>,
+
+    /// Where the events describing changes to the cliques are delivered, if anywhere.
+    events: CliqueEvents<Id>,
+
+    /// Observations whose cliques have been approximated by the density fallback.
+    approximate: HashSet<Id>,
+
+    /// The number of cliques dropped to stay within the clique capacity, since the cliques were
+    /// last enumerated in full.
+    dropped_cliques: usize,
+
+    duplicates: Duplicates<Id>,
+
+    /// The order in which the observations arrived, if the index has a bounded capacity.
+    arrivals: Option<Arrivals<Id>>,
+
+    /// The connected components of the compatibility graph.
+    ///
+    /// This is built on first use, then maintained as edges are added. Removing an edge may split
+    /// a component, which a union-find can't represent, so it is discarded and rebuilt lazily.
+    components: OnceLock<UnionFind<Id>>,
+
+    /// The fused summaries of the current cliques, by identifier.
+    ///
+    /// These are built on first use, and discarded when their cliques are removed, so that
+    /// only new or changed cliques are fused again.
+    results: Mutex<HashMap<CliqueId, FusedClique<Id>>>,
+
+    /// The cliques in a flat layout, updated as cliques are added and removed.
+    flat_cliques: FlatCliques<Id>,
+
+    config: Config,
+
+    /// Incremented each time the index is modified.
+    generation: u64,
+
+    /// The time taken by the most recent insertion.
+    last_insert_latency: Option<Duration>,
 }
 
 impl<Id> CliqueIndex<Id>
@@ -19,13 +102,86 @@ where
     Id: Eq + std::hash::Hash + Copy + std::fmt::Debug,
 {
     /// Construct a new index with a given confidence interval, defined by a Chi2 parameter
+    ///
+    /// For untrusted input, use [`Self::try_new`].
+    ///
+    /// # Panics
+    ///
+    /// This method panics in debug builds if [`Self::try_new`] would have returned an error.
     #[must_use]
     pub fn new(chi2: f64) -> Self {
+        Self::with_config(Config::new(chi2))
+    }
+
+    /// Construct a new index with a given confidence interval, defined by a Chi2 parameter,
+    /// checking that the parameter is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `chi2` is not finite and strictly positive.
+    pub fn try_new(chi2: f64) -> Result<Self, InvalidChi2> {
+        Config::try_new(chi2).map(Self::with_config)
+    }
+
+    /// Construct a new index with the given configuration.
+    #[must_use]
+    pub fn with_config(config: Config) -> Self {
+        Self::empty(config)
+    }
+
+    /// Construct a new index with the given configuration, which finds the candidate neighbours of
+    /// each observation using a custom [`CandidateSource`], rather than the built-in R-tree.
+    ///
+    /// Observations can then be added with [`Self::insert`] or [`Self::insert_batch`].
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use clique_fusion::{
+    ///     BoundingBox, CHI2_2D_CONFIDENCE_95, CandidateSource, CliqueIndex, Config, Observation,
+    ///     Unique,
+    /// };
+    ///
+    /// /// Every observation is a candidate neighbour of every other.
+    /// struct Exhaustive(Vec<u32>);
+    ///
+    /// impl CandidateSource<u32> for Exhaustive {
+    ///     fn insert(&mut self, observation: &Unique<Arc<Observation>, u32>) {
+    ///         self.0.push(observation.id);
+    ///     }
+    ///
+    ///     fn remove(&mut self, observation: &Unique<Arc<Observation>, u32>) {
+    ///         self.0.retain(|id| *id != observation.id);
+    ///     }
+    ///
+    ///     fn locate(&self, _bounds: &BoundingBox) -> Vec<u32> {
+    ///         self.0.clone()
+    ///     }
+    /// }
+    ///
+    /// let config = Config::new(CHI2_2D_CONFIDENCE_95);
+    /// let mut index = CliqueIndex::with_candidate_source(config, Exhaustive(Vec::new()));
+    /// for (id, x) in [(1, 0.0), (2, 0.5), (3, 100.0)] {
+    ///     let observation = Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    ///
+    /// assert_eq!(index.cliques().len(), 1);
+    /// ```
+    #[must_use]
+    pub fn with_candidate_source(
+        config: Config,
+        source: impl CandidateSource<Id> + 'static,
+    ) -> Self {
         Self {
-            spatial_index: SpatialIndex::default(),
-            compatibility_graph: HashMap::default(),
-            cliques: Vec::default(),
-            chi2,
+            spatial_index: SpatialIndex::with_candidate_source(
+                Box::new(source),
+                config.search_chi2(),
+            ),
+            ..Self::with_config(config)
         }
     }
 
@@ -39,15 +195,408 @@ where
     /// separate objects.
     #[must_use]
     pub fn from_observations(observations: Vec<Unique<Observation, Id>>, chi2: f64) -> Self {
-        let spatial_index = SpatialIndex::from_observations(observations);
-        let compatibility_graph = spatial_index.compatibility_graph(chi2).collect();
-        let cliques = find_maximal_cliques(&compatibility_graph);
+        Self::from_observations_with_config(observations, Config::new(chi2))
+    }
+
+    /// Construct a new index with the given configuration, populated with an initial vector of observations.
+    ///
+    /// See [`Self::from_observations`].
+    #[must_use]
+    pub fn from_observations_with_config(
+        observations: Vec<Unique<Observation, Id>>,
+        config: Config,
+    ) -> Self {
+        let Ok(index) = Self::build(observations, config, &mut Monitor::default()) else {
+            unreachable!("a build without a cancellation token can't be cancelled")
+        };
+        index
+    }
+
+    /// Construct a new index with the given configuration, populated with an initial vector of
+    /// observations, unless the construction is cancelled.
+    ///
+    /// The token is checked periodically while the compatibility graph is built and the cliques
+    /// are enumerated, so that building an index from a large batch of observations can be
+    /// abandoned (for example, when a request times out). See
+    /// [`Self::from_observations_with_config`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Cancelled`] if `cancellation` is cancelled before the index is complete.
+    pub fn from_observations_cancellable(
+        observations: Vec<Unique<Observation, Id>>,
+        config: Config,
+        cancellation: &CancellationToken,
+    ) -> Result<Self, Error> {
+        let mut monitor = Monitor::new(Some(cancellation), None);
+        Ok(Self::build(observations, config, &mut monitor)?)
+    }
+
+    /// Construct a new index with the given configuration, populated with an initial vector of
+    /// observations, reporting progress as it goes.
+    ///
+    /// `progress` is called as each [phase](BuildPhase) of the construction advances, at most
+    /// once per percent of the phase, so that a long construction can be displayed by a user
+    /// interface or job runner. See [`Self::from_observations_cancellable`].
+    ///
+    /// ```
+    /// use clique_fusion::{
+    ///     BuildPhase, CHI2_2D_CONFIDENCE_95, CancellationToken, CliqueIndex, Config, Observation,
+    ///     Unique,
+    /// };
+    ///
+    /// let observations = (0..10)
+    ///     .map(|id| Unique {
+    ///         data: Observation::builder(f64::from(id), 0.0)
+    ///             .circular_95_confidence_error(1.0)
+    ///             .unwrap()
+    ///             .build(),
+    ///         id,
+    ///     })
+    ///     .collect();
+    ///
+    /// let mut phases = Vec::new();
+    /// let index = CliqueIndex::from_observations_with_progress(
+    ///     observations,
+    ///     Config::new(CHI2_2D_CONFIDENCE_95),
+    ///     &CancellationToken::new(),
+    ///     |progress| {
+    ///         if progress.fraction == 1.0 {
+    ///             phases.push(progress.phase);
+    ///         }
+    ///     },
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     phases,
+    ///     [
+    ///         BuildPhase::SpatialIndex,
+    ///         BuildPhase::CompatibilityGraph,
+    ///         BuildPhase::CliqueEnumeration,
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Cancelled`] if `cancellation` is cancelled before the index is complete.
+    pub fn from_observations_with_progress(
+        observations: Vec<Unique<Observation, Id>>,
+        config: Config,
+        cancellation: &CancellationToken,
+        mut progress: impl FnMut(crate::Progress),
+    ) -> Result<Self, Error> {
+        let mut monitor = Monitor::new(Some(cancellation), Some(&mut progress));
+        Ok(Self::build(observations, config, &mut monitor)?)
+    }
+}
+
+impl<Id, G> CliqueIndex<Id, G>
+where
+    Id: Eq + std::hash::Hash + Copy + std::fmt::Debug,
+    G: Adjacency<Id>,
+{
+    /// An empty index with the given configuration.
+    fn empty(config: Config) -> Self {
         Self {
-            spatial_index,
-            compatibility_graph,
-            cliques,
-            chi2,
+            spatial_index: SpatialIndex::new(config.search_chi2()),
+            compatibility_graph: G::default(),
+            cliques: CliqueSet::default(),
+            clique_ids: Vec::default(),
+            histories: Histories::new(config.clique_history, config.clique_ids),
+            events: CliqueEvents::default(),
+            approximate: HashSet::default(),
+            dropped_cliques: 0,
+            duplicates: Duplicates::new(config.duplicate_policy),
+            arrivals: config.capacity.map(|_| Arrivals::new()),
+            components: OnceLock::new(),
+            results: Mutex::default(),
+            flat_cliques: FlatCliques::default(),
+            config,
+            generation: 0,
+            last_insert_latency: None,
+        }
+    }
+
+    fn build(
+        observations: Vec<Unique<Observation, Id>>,
+        config: Config,
+        monitor: &mut Monitor,
+    ) -> Result<Self, Cancelled> {
+        monitor.step(BuildPhase::SpatialIndex, 0, 1)?;
+        let mut duplicates = Duplicates::new(config.duplicate_policy);
+        let observations: Vec<Unique<Arc<Observation>, Id>> = observations
+            .into_iter()
+            .filter(|obs| !duplicates.check(obs))
+            .map(Unique::from)
+            .collect();
+        let arrivals = config.capacity.map(|_| {
+            let mut arrivals = Arrivals::new();
+            for observation in &observations {
+                arrivals.record(observation.id);
+            }
+            arrivals
+        });
+
+        let mut index = Self {
+            spatial_index: SpatialIndex::from_observations(observations, config.search_chi2()),
+            compatibility_graph: G::default(),
+            cliques: CliqueSet::default(),
+            clique_ids: Vec::default(),
+            histories: Histories::new(config.clique_history, config.clique_ids),
+            events: CliqueEvents::default(),
+            approximate: HashSet::default(),
+            dropped_cliques: 0,
+            duplicates,
+            arrivals,
+            components: OnceLock::new(),
+            results: Mutex::default(),
+            flat_cliques: FlatCliques::default(),
+            config,
+            generation: 0,
+            last_insert_latency: None,
+        };
+        monitor.step(BuildPhase::SpatialIndex, 1, 1)?;
+        index.try_rebuild(monitor)?;
+        index.enforce_capacity();
+        Ok(index)
+    }
+
+    /// The chi-squared threshold used to test compatibility.
+    ///
+    /// Under a [directional gate](Config::directional_gate), compatibility is tested against the
+    /// gate's own thresholds instead, except for pairs with no track direction, or whose combined
+    /// error is singular.
+    ///
+    /// See [`Self::set_chi2`].
+    #[must_use]
+    pub const fn chi2(&self) -> f64 {
+        self.config.chi2
+    }
+
+    /// The configuration of the index.
+    ///
+    /// This reflects any changes made since construction, such as by [`Self::set_chi2`]. Its
+    /// settings can be read back through [`Config::view`].
+    #[must_use = "the configuration is only borrowed, so this has no effect unless it is read"]
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Change the chi-squared threshold used to test compatibility.
+    ///
+    /// This recomputes the compatibility graph and cliques for every observation in the index, so
+    /// is comparable in cost to constructing a new index with [`Self::from_observations`]. It
+    /// avoids the need to retain the observations in order to do so.
+    ///
+    /// Tightening the threshold (to a smaller value) is much cheaper, since every edge of the new
+    /// graph is already an edge of the current one. The current edges are re-tested, rather than
+    /// searching the spatial index for compatible pairs, so only the cliques are recomputed in
+    /// full.
+    ///
+    /// Under a [directional gate](Config::directional_gate), the gate's thresholds replace `chi2`,
+    /// so changing it only affects pairs which are tested against `chi2` as usual (those with no
+    /// track direction, or whose combined error is singular).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `chi2` is not finite and strictly positive, in which case the index is
+    /// left unchanged.
+    pub fn set_chi2(&mut self, chi2: f64) -> Result<(), InvalidChi2> {
+        let _ = Config::try_new(chi2)?;
+        let tightened = chi2 <= self.config.chi2;
+        self.config.chi2 = chi2;
+        self.generation += 1;
+        if tightened {
+            // the envelopes in the spatial index remain wide enough for the stricter threshold
+            self.retain_compatible_edges();
+            self.recluster();
+        } else {
+            self.spatial_index.set_chi2(self.config.search_chi2());
+            self.rebuild();
+        }
+        Ok(())
+    }
+
+    /// Change the chi-squared threshold used to test compatibility, unless the change is
+    /// cancelled.
+    ///
+    /// This is the same as [`Self::set_chi2`], except that `cancellation` is checked periodically
+    /// while the compatibility graph and cliques are recomputed, so that a change which is taking
+    /// too long (such as one step of a sweep over thresholds on a large index) can be abandoned.
+    /// A copy of the compatibility graph is kept until the change is complete, so that it can be
+    /// restored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `chi2` is not finite and strictly positive, or [`Error::Cancelled`] if
+    /// `cancellation` is cancelled before the change is complete. In either case the index is left
+    /// unchanged.
+    pub fn set_chi2_cancellable(
+        &mut self,
+        chi2: f64,
+        cancellation: &CancellationToken,
+    ) -> Result<(), Error> {
+        let _ = Config::try_new(chi2)?;
+        let previous_chi2 = self.config.chi2;
+        let previous_graph = self.compatibility_graph.clone();
+        let tightened = chi2 <= previous_chi2;
+        self.config.chi2 = chi2;
+        self.generation += 1;
+
+        let mut monitor = Monitor::new(Some(cancellation), None);
+        let result = if tightened {
+            self.retain_compatible_edges();
+            self.try_recluster(&mut monitor)
+        } else {
+            self.spatial_index.set_chi2(self.config.search_chi2());
+            self.try_rebuild(&mut monitor)
+        };
+
+        if result.is_err() {
+            self.config.chi2 = previous_chi2;
+            self.generation -= 1;
+            self.compatibility_graph = previous_graph;
+            if !tightened {
+                self.spatial_index.set_chi2(self.config.search_chi2());
+            }
+        }
+        Ok(result?)
+    }
+
+    /// Enumerate the cliques of the whole compatibility graph, reporting progress to the
+    /// monitor, unless it is cancelled.
+    fn enumerate_cliques(&mut self, monitor: &mut Monitor) -> Result<Vec<HashSet<Id>>, Cancelled> {
+        if !self.config.clusters_components() {
+            return self
+                .spatial_index
+                .profiling()
+                .time(Phase::CliqueEnumeration, || {
+                    find_maximal_cliques_monitored(
+                        &self.compatibility_graph,
+                        self.config.enumeration_strategy,
+                        monitor,
+                    )
+                });
+        }
+
+        let components = connected_components(&self.compatibility_graph);
+        let mut cliques = Vec::new();
+        for (done, component) in components.iter().enumerate() {
+            monitor.step(BuildPhase::CliqueEnumeration, done, components.len())?;
+            cliques.extend(self.cluster_component(component));
+        }
+        monitor.step(
+            BuildPhase::CliqueEnumeration,
+            components.len(),
+            components.len(),
+        )?;
+        Ok(cliques)
+    }
+
+    /// Remove the edges of the compatibility graph between observations which are no longer
+    /// compatible under the configured threshold.
+    fn retain_compatible_edges(&mut self) {
+        let config = &self.config;
+        let spatial_index = &self.spatial_index;
+        for (id, neighbours) in self.compatibility_graph.iter_mut() {
+            let Some(observation) = spatial_index.get(id) else {
+                continue;
+            };
+            neighbours.retain(|neighbour| {
+                spatial_index.get(neighbour).is_some_and(|other| {
+                    observation.gated_distance_squared(other, config) <= config.chi2
+                })
+            });
+        }
+        self.compatibility_graph
+            .retain(|_, neighbours| !neighbours.is_empty());
+    }
+
+    /// Replace the error of every observation in the index, and recompute the compatibility graph
+    /// and cliques.
+    ///
+    /// `covariance` is called once for each observation, and returns its new error. This is useful
+    /// after recalibrating a sensor, and is much faster than removing and re-inserting each
+    /// observation.
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, CovarianceMatrix, Observation};
+    ///
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// index.insert_new(Observation::builder(0.0, 0.0).error(CovarianceMatrix::identity()).build());
+    ///
+    /// // every error is inflated by 30%
+    /// index.recalibrate(|observation| {
+    ///     let error = observation.error_covariance();
+    ///     CovarianceMatrix::new(error.xx() * 1.3, error.yy() * 1.3, error.xy() * 1.3).unwrap()
+    /// });
+    /// ```
+    pub fn recalibrate(&mut self, mut covariance: impl FnMut(&Observation) -> CovarianceMatrix) {
+        let observations: Vec<_> = self
+            .spatial_index
+            .iter()
+            .map(|(id, observation)| Unique {
+                data: Arc::new(observation.clone().with_error(covariance(observation))),
+                id: *id,
+            })
+            .collect();
+        self.duplicates.reindex(&observations);
+        self.spatial_index.reload(observations);
+        self.generation += 1;
+        self.rebuild();
+    }
+
+    /// Recompute the compatibility graph and cliques from the observations in the spatial index.
+    fn rebuild(&mut self) {
+        let Ok(()) = self.try_rebuild(&mut Monitor::default()) else {
+            unreachable!("a rebuild without a cancellation token can't be cancelled")
+        };
+    }
+
+    /// Recompute every clique from the compatibility graph.
+    fn recluster(&mut self) {
+        let Ok(()) = self.try_recluster(&mut Monitor::default()) else {
+            unreachable!("a rebuild without a cancellation token can't be cancelled")
+        };
+    }
+
+    /// As for [`Self::rebuild`], reporting progress to the monitor, unless it is cancelled.
+    ///
+    /// A cancelled rebuild leaves the cliques unchanged, but not necessarily the compatibility
+    /// graph, so the caller must restore (or discard) it.
+    fn try_rebuild(&mut self, monitor: &mut Monitor) -> Result<(), Cancelled> {
+        if monitor.is_cancelled() {
+            return Err(Cancelled);
         }
+        self.compatibility_graph = match self.config.approximate_search {
+            Some(miss_rate) => self.spatial_index.approximate_compatibility_graph(
+                &self.config,
+                miss_rate,
+                monitor,
+            )?,
+            None => self
+                .spatial_index
+                .compatibility_graph(&self.config, monitor)?,
+        };
+        self.try_recluster(monitor)
+    }
+
+    /// As for [`Self::recluster`], reporting progress to the monitor, unless it is cancelled.
+    ///
+    /// A cancelled recluster leaves the cliques unchanged.
+    fn try_recluster(&mut self, monitor: &mut Monitor) -> Result<(), Cancelled> {
+        let approximate = std::mem::take(&mut self.approximate);
+        let cliques = self
+            .enumerate_cliques(monitor)
+            .inspect_err(|_| self.approximate = approximate)?;
+
+        let previous = self.take_cliques(|_| true);
+        self.components.take();
+        self.dropped_cliques = 0;
+        self.replace_cliques(previous, cliques);
+        Ok(())
     }
 
     /// Inserts a new observation, updating the spatial index, compatibility graph,
@@ -57,17 +606,66 @@ where
     /// they are assumed to have negligible relative error between them, and hence are distinguishable as
     /// separate objects.
     ///
+    /// Exact duplicates of existing observations are handled according to the configured
+    /// [`DuplicatePolicy`](crate::DuplicatePolicy).
+    ///
     /// # Panics
     ///
     /// Panics on debug builds if an observation with the same ID already exists in the index.
     pub fn insert(&mut self, observation: Unique<Observation, Id>) {
+        self.insert_shared(observation.into());
+    }
+
+    /// Inserts a new observation, as for [`Self::insert`], sharing its storage with the caller.
+    ///
+    /// The index holds a reference to the observation, rather than a copy of it, so a caller which
+    /// also keeps its own collection of observations doesn't need to clone them. Observations are
+    /// never modified in place, so the caller's view of them remains valid.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// let observation = Arc::new(
+    ///     Observation::builder(0.0, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build(),
+    /// );
+    ///
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// index.insert_shared(Unique { data: Arc::clone(&observation), id: 1 });
+    ///
+    /// assert!(Arc::ptr_eq(index.get_shared(&1).unwrap(), &observation));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics on debug builds if an observation with the same ID already exists in the index.
+    pub fn insert_shared(&mut self, observation: Unique<Arc<Observation>, Id>) {
+        let start = Instant::now();
+        if self.duplicates.check(&observation) {
+            return;
+        }
+        self.generation += 1;
+        self.insert_unchecked(observation);
+        self.enforce_capacity();
+        self.last_insert_latency = Some(start.elapsed());
+    }
+
+    /// Insert an observation which is known not to be a duplicate.
+    fn insert_unchecked(&mut self, observation: Unique<Arc<Observation>, Id>) {
         let id = observation.id;
+        if let Some(arrivals) = &mut self.arrivals {
+            arrivals.record(id);
+        }
 
         // 1. Identify mutually compatible neighbours
         let direct_neighbours: HashSet<Id> = self
             .spatial_index
-            .find_compatible(&observation, self.chi2)
-            .map(|obs| obs.id)
+            .find_compatible(&observation, &self.config)
+            .map(|(obs, _)| obs.id)
             .collect();
 
         // 2. Insert into spatial index
@@ -82,29 +680,452 @@ where
 
             // Add the new node to all its neighbors' adjacency lists
             for &neighbour in &direct_neighbours {
-                self.compatibility_graph
-                    .entry(neighbour)
-                    .or_default()
-                    .insert(id);
+                self.compatibility_graph.entry(neighbour).insert(id);
+            }
+
+            if let Some(components) = self.components.get_mut() {
+                for &neighbour in &direct_neighbours {
+                    components.union(id, neighbour);
+                }
+            }
+
+            // Dense regions (or those around a hub) are clustered approximately as a whole, if so
+            // configured. Regions which were previously approximated are re-clustered in full,
+            // since they may no longer need to be. A new observation may also displace the nearest
+            // neighbours of those around it, so pruned regions are re-clustered in full too.
+            if self.config.clusters_components() {
+                let component = component_containing(&self.compatibility_graph, id);
+                if self.config.mutual_nearest.is_some()
+                    || self.needs_approximation(&self.compatibility_graph, &component)
+                    || !self.approximate.is_disjoint(&component)
+                {
+                    let new_cliques = self.cluster_component(&component);
+                    self.update_cliques(&component, new_cliques);
+                    return;
+                }
+            }
+
+            // Every existing clique remains a clique, but those which lie entirely within the new
+            // node's neighbourhood are no longer maximal, since the new node extends them.
+            let stale = self.take_cliques(|clique| clique.is_subset(&direct_neighbours));
+
+            // The new maximal cliques are exactly the new node together with each maximal clique
+            // of the subgraph induced by its neighbours (any node which could extend one would
+            // itself be a neighbour, contradicting its maximality within the subgraph).
+            let subgraph: HashMap<Id, HashSet<Id>> =
+                self.extract_subgraph(&direct_neighbours).collect();
+            let new_cliques = self
+                .spatial_index
+                .profiling()
+                .time(Phase::CliqueEnumeration, || {
+                    find_maximal_cliques(&subgraph, self.config.enumeration_strategy)
+                })
+                .into_iter()
+                .map(|mut clique| {
+                    clique.insert(id);
+                    clique
+                })
+                .collect();
+            self.replace_cliques(stale, new_cliques);
+        }
+    }
+
+    /// Inserts a new observation, as for [`Self::insert`], unless an observation with the same ID
+    /// is already in the index.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DuplicateId`] if an observation with the same ID is already in the index, or
+    /// [`Error::SingularCovariance`] if the observation's covariance is singular and such
+    /// observations are [rejected](crate::SingularCovariance::Reject) (and it isn't an anchor), in
+    /// which case the index is unchanged.
+    ///
+    /// Returns [`Error::TooManyCliques`] if the observation would take the index over its
+    /// [clique capacity](Config::clique_capacity) under [`CliqueOverflow::Error`]. This is judged
+    /// before any observations are evicted to make room for it, and the index is left unchanged.
+    pub fn try_insert(&mut self, observation: Unique<Observation, Id>) -> Result<(), Error> {
+        if self.contains(&observation.id) {
+            return Err(Error::DuplicateId);
+        }
+        self.check_singular(&observation)?;
+        if let Some((max_cliques, CliqueOverflow::Error)) = self.config.clique_capacity {
+            if self.cliques_after_insert(&observation) > max_cliques {
+                return Err(Error::TooManyCliques);
+            }
+        }
+        self.insert(observation);
+        Ok(())
+    }
+
+    /// Reject an observation with a singular covariance, under [`SingularCovariance::Reject`](crate::SingularCovariance::Reject).
+    fn check_singular(&self, observation: &Unique<Observation, Id>) -> Result<(), Error> {
+        if self.config.singular_covariance == crate::SingularCovariance::Reject
+            && !observation.data.is_anchor()
+            && self
+                .config
+                .is_singular(&observation.data.error_covariance())
+        {
+            return Err(Error::SingularCovariance);
+        }
+        Ok(())
+    }
+
+    /// Replaces an observation already in the index with a refined version of it (for example,
+    /// with an updated position or error), returning the previous version.
+    ///
+    /// The cliques of the affected regions are repaired as if the previous version had been
+    /// removed, and the new version inserted, but in a single call. The new version keeps the ID
+    /// and [duplicate count](Self::duplicate_count) of the previous one: it is never discarded as
+    /// a [duplicate](crate::DuplicatePolicy), even if it is identical to another observation.
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Error, Observation, Unique};
+    ///
+    /// let observation = |id, x| Unique {
+    ///     data: Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build(),
+    ///     id,
+    /// };
+    ///
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// index.insert(observation(1, 0.0));
+    /// index.insert(observation(2, 10.0));
+    /// assert!(index.cliques().is_empty());
+    ///
+    /// // a refined position brings the observations together
+    /// let previous = index.update(observation(2, 0.5)).unwrap();
+    /// assert_eq!(previous.x(), 10.0);
+    /// assert_eq!(index.cliques().len(), 1);
+    ///
+    /// assert!(matches!(index.update(observation(3, 0.0)), Err(Error::NotFound)));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] if no observation with the same ID is in the index, or any of
+    /// the errors of [`Self::try_insert`] (other than [`Error::DuplicateId`]) if the new version
+    /// would be rejected, in which case the index is unchanged.
+    pub fn update(&mut self, observation: Unique<Observation, Id>) -> Result<Observation, Error> {
+        if !self.contains(&observation.id) {
+            return Err(Error::NotFound);
+        }
+        self.check_singular(&observation)?;
+        if let Some((max_cliques, CliqueOverflow::Error)) = self.config.clique_capacity {
+            if self.cliques_after_update(&observation) > max_cliques {
+                return Err(Error::TooManyCliques);
+            }
+        }
+
+        let start = Instant::now();
+        let count = self.duplicates.count(&observation.id);
+        let Some(previous) = self.remove(&observation.id) else {
+            return Err(Error::NotFound);
+        };
+        self.duplicates.restore(&observation, count);
+        self.generation += 1;
+        self.insert_unchecked(observation.into());
+        self.last_insert_latency = Some(start.elapsed());
+        Ok(previous)
+    }
+
+    /// Inserts an observation, replacing any observation with the same ID which is already in the
+    /// index, as for [`Self::update`].
+    ///
+    /// Returns the previous version of the observation, if there was one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the observation would be rejected by [`Self::update`] (if there is an
+    /// observation with the same ID) or [`Self::try_insert`] (if there isn't), in which case the
+    /// index is unchanged.
+    pub fn upsert(
+        &mut self,
+        observation: Unique<Observation, Id>,
+    ) -> Result<Option<Observation>, Error> {
+        if self.contains(&observation.id) {
+            self.update(observation).map(Some)
+        } else {
+            self.try_insert(observation).map(|()| None)
+        }
+    }
+
+    /// Inserts a batch of new observations, with the same result as inserting each in turn with
+    /// [`Self::insert`].
+    ///
+    /// The compatibility tests and clique enumeration for each new observation are independent of
+    /// those for the others, so with the `rayon` feature they are run in parallel. This makes
+    /// ingesting a burst of observations (particularly one scattered over a wide area) much faster
+    /// than inserting them one at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics on debug builds if an observation with the same ID already exists in the index.
+    pub fn insert_batch(&mut self, observations: impl IntoIterator<Item = Unique<Observation, Id>>)
+    where
+        Id: MaybeSendSync,
+        G: MaybeSendSync,
+    {
+        if self.config.clusters_components() {
+            // Approximated and pruned regions are re-clustered as a whole, which doesn't decompose
+            // by observation
+            for observation in observations {
+                self.insert(observation);
+            }
+            return;
+        }
+
+        let observations: Vec<Unique<Arc<Observation>, Id>> = observations
+            .into_iter()
+            .filter(|observation| !self.duplicates.check(observation))
+            .map(Unique::from)
+            .collect();
+        if observations.is_empty() {
+            return;
+        }
+        self.generation += 1;
+        let ids: Vec<Id> = observations
+            .iter()
+            .map(|observation| observation.id)
+            .collect();
+        for observation in observations {
+            if let Some(arrivals) = &mut self.arrivals {
+                arrivals.record(observation.id);
+            }
+            self.spatial_index.insert(observation);
+        }
+
+        // 1. Identify the compatible neighbours of each new observation, among both the existing
+        //    and the new observations
+        #[cfg(feature = "rayon")]
+        let queries = ids.par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let queries = ids.iter();
+        let neighbourhoods: Vec<HashSet<Id>> = queries
+            .map(|id| {
+                let query = Unique {
+                    data: self
+                        .spatial_index
+                        .get_shared(id)
+                        .cloned()
+                        .unwrap_or_else(|| {
+                            unreachable!(
+                                "new observations have been inserted into the spatial index"
+                            )
+                        }),
+                    id: *id,
+                };
+                self.spatial_index
+                    .find_compatible(&query, &self.config)
+                    .map(|(obs, _)| obs.id)
+                    .collect()
+            })
+            .collect();
+
+        // 2. Update the compatibility graph
+        for (&id, neighbours) in ids.iter().zip(neighbourhoods) {
+            for &neighbour in &neighbours {
+                self.compatibility_graph.entry(neighbour).insert(id);
+                if let Some(components) = self.components.get_mut() {
+                    components.union(id, neighbour);
+                }
+            }
+            if !neighbours.is_empty() {
+                self.compatibility_graph.entry(id).extend(neighbours);
+            }
+        }
+
+        // 3. Existing cliques which lie entirely within the neighbourhood of a new observation are
+        //    extended by it, so are no longer maximal
+        let order: HashMap<Id, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let graph = std::mem::take(&mut self.compatibility_graph);
+        let stale = self.take_cliques(|clique| {
+            let Some(adjacent) = clique.iter().next().and_then(|member| graph.get(member)) else {
+                return false;
+            };
+            adjacent
+                .iter()
+                .filter(|candidate| order.contains_key(candidate))
+                .any(|new| clique.is_subset(&graph[new]))
+        });
+        self.compatibility_graph = graph;
+
+        // 4. The new maximal cliques are those containing a new observation. Those containing each
+        //    new observation, but none of the new observations before it, are found independently.
+        #[cfg(feature = "rayon")]
+        let vertices = ids.par_iter().enumerate();
+        #[cfg(not(feature = "rayon"))]
+        let vertices = ids.iter().enumerate();
+        let new_cliques: Vec<Vec<HashSet<Id>>> =
+            self.spatial_index
+                .profiling()
+                .time(Phase::CliqueEnumeration, || {
+                    vertices
+                        .filter(|(_, id)| self.compatibility_graph.contains_key(id))
+                        .map(|(i, id)| {
+                            find_maximal_cliques_containing(
+                                &self.compatibility_graph,
+                                *id,
+                                |neighbour| order.get(neighbour).is_some_and(|j| *j < i),
+                                self.config.enumeration_strategy,
+                            )
+                        })
+                        .collect()
+                });
+        self.replace_cliques(stale, new_cliques.into_iter().flatten().collect());
+        self.enforce_capacity();
+    }
+
+    /// Removes an observation from the index, updating the spatial index, compatibility graph,
+    /// and recomputing cliques in the affected subgraph.
+    ///
+    /// Returns the removed observation, or `None` if no observation with the given ID was present.
+    pub fn remove(&mut self, id: &Id) -> Option<Observation> {
+        self.remove_all(&[*id])
+            .pop()
+            .map(|removed| Arc::unwrap_or_clone(removed.data))
+    }
+
+    /// Removes every observation in the given context from the index, returning them.
+    ///
+    /// This is useful when an upstream frame is retracted (or re-sent with corrections). The cliques
+    /// are recomputed once for the whole affected region, which is much faster than removing the
+    /// observations one at a time.
+    pub fn remove_context(&mut self, context: Uuid) -> Vec<Unique<Observation, Id>> {
+        let ids: Vec<Id> = self
+            .spatial_index
+            .iter()
+            .filter(|(_, observation)| observation.context() == Some(context))
+            .map(|(id, _)| *id)
+            .collect();
+        self.remove_all(&ids)
+            .into_iter()
+            .map(|removed| Unique {
+                data: Arc::unwrap_or_clone(removed.data),
+                id: removed.id,
+            })
+            .collect()
+    }
+
+    /// Remove the observations with the given IDs, recomputing the cliques of the affected region
+    /// once. IDs which are not in the index are ignored.
+    fn remove_all(&mut self, ids: &[Id]) -> Vec<Unique<Arc<Observation>, Id>> {
+        let removed: Vec<_> = ids
+            .iter()
+            .filter_map(|id| {
+                self.spatial_index
+                    .remove(id)
+                    .map(|data| Unique { data, id: *id })
+            })
+            .collect();
+        if removed.is_empty() {
+            return removed;
+        }
+        self.generation += 1;
+
+        let mut former_neighbours = Vec::new();
+        let mut was_approximate = false;
+        for observation in &removed {
+            let id = &observation.id;
+            self.duplicates.forget(observation);
+            if let Some(arrivals) = &mut self.arrivals {
+                arrivals.forget(id);
+            }
+            was_approximate |= self.approximate.remove(id);
+
+            // An isolated observation is not a member of any clique
+            let Some(neighbours) = self.compatibility_graph.remove(id) else {
+                continue;
+            };
+            for neighbour in &neighbours {
+                if let Some(adjacent) = self.compatibility_graph.get_mut(neighbour) {
+                    adjacent.remove(id);
+                    if adjacent.is_empty() {
+                        self.compatibility_graph.remove(neighbour);
+                    }
+                }
             }
+            former_neighbours.push(neighbours);
+        }
+        if former_neighbours.is_empty() {
+            return removed;
+        }
+        self.components.take();
+
+        let removed_ids: HashSet<Id> = removed.iter().map(|observation| observation.id).collect();
+        let mut stale = self.take_cliques(|clique| !clique.is_disjoint(&removed_ids));
+        let neighbours: HashSet<Id> = former_neighbours
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|neighbour| !removed_ids.contains(neighbour))
+            .collect();
 
-            // Calculate affected region: new node + its direct neighbors (1-hop)
-            // This is sufficient because:
-            // - New node can only participate in cliques with its direct neighbors
-            // - Only cliques containing the new node's neighbors can be affected
-            // - Mutual compatibility ensures no "action at a distance" effects
-            let mut affected = direct_neighbours;
-            affected.insert(id); // New node is guaranteed to be in the graph at this point
+        // Regions which were approximated or pruned are re-clustered in full. Removal may have
+        // split the region into several components.
+        if was_approximate || self.config.mutual_nearest.is_some() {
+            let mut reclustered = HashSet::new();
+            let mut new_cliques = Vec::new();
+            for neighbour in &neighbours {
+                if reclustered.contains(neighbour)
+                    || !self.compatibility_graph.contains_key(neighbour)
+                {
+                    continue;
+                }
+                let component = component_containing(&self.compatibility_graph, *neighbour);
+                new_cliques.extend(self.cluster_component(&component));
+                stale.extend(self.take_cliques(|clique| !clique.is_disjoint(&component)));
+                reclustered.extend(component);
+            }
+            self.replace_cliques(stale, new_cliques);
+            self.approximate
+                .retain(|id| !neighbours.contains(id) || reclustered.contains(id));
+            return removed;
+        }
 
-            // Extract subgraph containing only affected nodes and their internal connections
-            let subgraph = self.extract_subgraph(&affected).collect();
+        // Cliques which did not contain a removed observation remain maximal. Cliques which did
+        // contain one are replaced by the maximal cliques among its former neighbours, unless they
+        // are contained within a larger clique elsewhere in the graph. A clique which is not within
+        // the former neighbourhood of a single removed observation was already maximal, so is
+        // not replaced.
+        let remaining: HashSet<Id> = neighbours
+            .into_iter()
+            .filter(|neighbour| self.compatibility_graph.contains_key(neighbour))
+            .collect();
+        let subgraph: HashMap<Id, HashSet<Id>> = self.extract_subgraph(&remaining).collect();
+        let candidates = self
+            .spatial_index
+            .profiling()
+            .time(Phase::CliqueEnumeration, || {
+                find_maximal_cliques(&subgraph, self.config.enumeration_strategy)
+            });
+        let new_cliques = candidates
+            .into_iter()
+            .filter(|clique| {
+                former_neighbours
+                    .iter()
+                    .any(|neighbours| clique.is_subset(neighbours))
+                    && !self.is_extensible(clique)
+            })
+            .collect();
+        self.replace_cliques(stale, new_cliques);
 
-            // Recompute cliques in the affected subgraph
-            let new_cliques = find_maximal_cliques(&subgraph);
+        removed
+    }
 
-            // Update global clique set: remove stale cliques and add new ones
-            self.update_cliques(&affected, new_cliques);
+    /// Returns `true` if there is an observation outside of the clique which is compatible with
+    /// every member of it (and hence the clique is not maximal).
+    fn is_extensible(&self, clique: &HashSet<Id>) -> bool {
+        let mut members = clique.iter();
+        let Some(first) = members.next() else {
+            return false;
+        };
+        let mut common: HashSet<Id> = self.compatibility_graph[first].clone();
+        for member in members {
+            common.retain(|id| self.compatibility_graph[member].contains(id));
         }
+        !common.is_empty()
     }
 
     /// Extract subgraph containing only the specified nodes and edges between them
@@ -137,163 +1158,4138 @@ where
         })
     }
 
-    /// Update the global clique set by removing stale cliques and adding new ones
-    fn update_cliques(&mut self, affected_nodes: &HashSet<Id>, new_cliques: Vec<HashSet<Id>>) {
-        // Remove any existing cliques that overlap with the affected region
-        // We need to remove these because they may no longer be maximal or may have merged
-        self.cliques
-            .retain(|clique| clique.is_disjoint(affected_nodes));
-
-        // Add all newly computed cliques from the affected subgraph
-        self.cliques.extend(new_cliques);
+    /// Evict observations according to the configured [`EvictionPolicy`](crate::EvictionPolicy),
+    /// until the index is within its capacity.
+    fn enforce_capacity(&mut self) {
+        let (Some((max_observations, policy)), Some(arrivals)) =
+            (self.config.capacity, &self.arrivals)
+        else {
+            return;
+        };
+        let excess = self.total_observations().saturating_sub(max_observations);
+        if excess == 0 {
+            return;
+        }
+        let victims = arrivals.victims(
+            excess,
+            policy,
+            |id| self.spatial_index.get(id).map_or(0.0, Observation::weight),
+            |id| !self.compatibility_graph.contains_key(id),
+        );
+        self.remove_all(&victims);
     }
 
-    /// Get the current set of maximal cliques
-    #[must_use]
-    pub fn cliques(&self) -> &[HashSet<Id>] {
-        &self.cliques
+    /// Compute the cliques of a connected component of the compatibility graph.
+    ///
+    /// If the component exceeds the limits of the configured [`DensityFallback`](crate::DensityFallback)
+    /// or [maximum neighbourhood](Config::max_neighbourhood), its cliques are approximated by hierarchical clustering, and its observations are marked as
+    /// approximate. Otherwise, maximal cliques are enumerated exactly.
+    ///
+    /// If [mutual nearest neighbour](Config::mutual_nearest_neighbours) pruning is configured, the
+    /// component is pruned first.
+    fn cluster_component(&mut self, component: &HashSet<Id>) -> Vec<HashSet<Id>> {
+        let observations = self.spatial_index.get_all(component);
+        let subgraph = self.extract_subgraph(component).collect();
+        let (cliques, approximate) = self.component_cliques(subgraph, component, &observations);
+        if approximate {
+            self.approximate.extend(component.iter().copied());
+        } else {
+            self.approximate.retain(|id| !component.contains(id));
+        }
+        cliques
     }
 
-    /// Get the number of observations in the index
-    #[must_use]
-    pub fn len(&self) -> usize {
-        self.compatibility_graph.len()
+    /// Compute the cliques of a connected component, given the subgraph it induces and its
+    /// observations, without modifying the index.
+    ///
+    /// Also returns whether the cliques were approximated. See [`Self::cluster_component`].
+    fn component_cliques(
+        &self,
+        mut subgraph: HashMap<Id, HashSet<Id>>,
+        component: &HashSet<Id>,
+        observations: &HashMap<Id, &Observation>,
+    ) -> (Vec<HashSet<Id>>, bool) {
+        let config = &self.config;
+        let distance =
+            |a: Id, b: Id| observations[&a].gated_distance_squared(observations[&b], config);
+
+        // a component is closed under adjacency, so its subgraph holds every edge of its nodes
+        let approximate = self.needs_approximation(&subgraph, component);
+        if let Some(k) = self.config.mutual_nearest {
+            subgraph = mutual_nearest_neighbours(&subgraph, k, distance);
+        }
+
+        let profiling = self.spatial_index.profiling();
+        let cliques = if approximate {
+            profiling.time(Phase::CliqueEnumeration, || {
+                complete_linkage_clusters(&subgraph, distance, self.config.seeded_order())
+            })
+        } else {
+            profiling.time(Phase::CliqueEnumeration, || {
+                find_maximal_cliques(&subgraph, self.config.enumeration_strategy)
+            })
+        };
+        (cliques, approximate)
     }
 
-    /// Check if the index is empty
-    #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.compatibility_graph.is_empty()
+    /// Returns `true` if the cliques of a connected component of a compatibility graph should be
+    /// approximated, because it is too dense or contains an observation with too many neighbours.
+    fn needs_approximation(&self, graph: &impl Adjacency<Id>, component: &HashSet<Id>) -> bool {
+        let dense = self
+            .config
+            .density_fallback
+            .is_some_and(|fallback| fallback.applies(component.len(), density(graph, component)));
+        let hub = self.config.max_neighbourhood.is_some_and(|max_neighbours| {
+            component.iter().any(|id| graph[id].len() > max_neighbours)
+        });
+        dense || hub
     }
 
-    /// Get the compatibility graph (for debugging/analysis)
+    /// The number of cliques the index would hold after inserting an observation, before any
+    /// observations are evicted to make room for it, without modifying the index.
+    ///
+    /// This mirrors the clique maintenance of [`Self::insert`].
+    fn cliques_after_insert(&self, observation: &Unique<Observation, Id>) -> usize {
+        let id = observation.id;
+        let direct_neighbours: HashSet<Id> = self
+            .spatial_index
+            .find_compatible(observation, &self.config)
+            .map(|(obs, _)| obs.id)
+            .collect();
+        if direct_neighbours.is_empty() || self.duplicates.is_duplicate(observation) {
+            return self.cliques.len();
+        }
+
+        // the adjacency of a node once the observation has been inserted
+        let adjacency = |node: Id| -> HashSet<Id> {
+            if node == id {
+                return direct_neighbours.clone();
+            }
+            let mut neighbours = self
+                .compatibility_graph
+                .get(&node)
+                .cloned()
+                .unwrap_or_default();
+            if direct_neighbours.contains(&node) {
+                neighbours.insert(id);
+            }
+            neighbours
+        };
+
+        if self.config.clusters_components() {
+            let mut component = HashSet::from([id]);
+            for &neighbour in &direct_neighbours {
+                if !component.contains(&neighbour) {
+                    component.extend(component_containing(&self.compatibility_graph, neighbour));
+                }
+            }
+            let subgraph: HashMap<Id, HashSet<Id>> = component
+                .iter()
+                .map(|&node| (node, adjacency(node)))
+                .collect();
+            if self.config.mutual_nearest.is_some()
+                || self.needs_approximation(&subgraph, &component)
+                || !self.approximate.is_disjoint(&component)
+            {
+                let mut observations = self.spatial_index.get_all(&component);
+                observations.insert(id, &observation.data);
+                let (new_cliques, _) = self.component_cliques(subgraph, &component, &observations);
+                let stale = self
+                    .cliques
+                    .iter()
+                    .filter(|clique| !clique.is_disjoint(&component))
+                    .count();
+                return self.cliques.len() - stale + new_cliques.len();
+            }
+        }
+
+        let stale = self
+            .cliques
+            .iter()
+            .filter(|clique| clique.is_subset(&direct_neighbours))
+            .count();
+        let subgraph: HashMap<Id, HashSet<Id>> = direct_neighbours
+            .iter()
+            .map(|&node| {
+                let neighbours = adjacency(node)
+                    .intersection(&direct_neighbours)
+                    .copied()
+                    .collect();
+                (node, neighbours)
+            })
+            .collect();
+        let new_cliques = find_maximal_cliques(&subgraph, self.config.enumeration_strategy);
+        self.cliques.len() - stale + new_cliques.len()
+    }
+
+    /// The number of cliques the index would hold after replacing an observation with a refined
+    /// version of it, as for [`Self::update`], without modifying the index.
+    ///
+    /// The cliques of every connected component touched by the replacement are recomputed in
+    /// full.
+    fn cliques_after_update(&self, observation: &Unique<Observation, Id>) -> usize {
+        let id = observation.id;
+        let previous_neighbours = self
+            .compatibility_graph
+            .get(&id)
+            .cloned()
+            .unwrap_or_default();
+        let direct_neighbours: HashSet<Id> = self
+            .spatial_index
+            .find_compatible(observation, &self.config)
+            .map(|(obs, _)| obs.id)
+            .collect();
+
+        // the adjacency of a node once the observation has been replaced
+        let adjacency = |node: Id| -> HashSet<Id> {
+            if node == id {
+                return direct_neighbours.clone();
+            }
+            let mut neighbours = self
+                .compatibility_graph
+                .get(&node)
+                .cloned()
+                .unwrap_or_default();
+            neighbours.remove(&id);
+            if direct_neighbours.contains(&node) {
+                neighbours.insert(id);
+            }
+            neighbours
+        };
+
+        let mut graph = HashMap::new();
+        let mut frontier: Vec<Id> = std::iter::once(id)
+            .chain(previous_neighbours.iter().copied())
+            .chain(direct_neighbours.iter().copied())
+            .collect();
+        while let Some(node) = frontier.pop() {
+            if graph.contains_key(&node) {
+                continue;
+            }
+            let neighbours = adjacency(node);
+            if neighbours.is_empty() {
+                continue;
+            }
+            frontier.extend(neighbours.iter().copied());
+            graph.insert(node, neighbours);
+        }
+
+        let touched: HashSet<Id> = graph
+            .keys()
+            .copied()
+            .chain(std::iter::once(id))
+            .chain(previous_neighbours)
+            .collect();
+        let stale = self
+            .cliques
+            .iter()
+            .filter(|clique| !clique.is_disjoint(&touched))
+            .count();
+
+        let new_cliques = if self.config.clusters_components() {
+            let mut observations = self.spatial_index.get_all(&touched);
+            observations.insert(id, &observation.data);
+            connected_components(&graph)
+                .into_iter()
+                .map(|component| {
+                    let subgraph = component
+                        .iter()
+                        .map(|node| (*node, graph[node].clone()))
+                        .collect();
+                    self.component_cliques(subgraph, &component, &observations)
+                        .0
+                        .len()
+                })
+                .sum()
+        } else {
+            find_maximal_cliques(&graph, self.config.enumeration_strategy).len()
+        };
+        self.cliques.len() - stale + new_cliques
+    }
+
+    /// Update the global clique set by removing stale cliques and adding new ones
+    fn update_cliques(&mut self, affected_nodes: &HashSet<Id>, new_cliques: Vec<HashSet<Id>>) {
+        // Remove any existing cliques that overlap with the affected region
+        // We need to remove these because they may no longer be maximal or may have merged
+        let stale = self.take_cliques(|clique| !clique.is_disjoint(affected_nodes));
+
+        // Add all newly computed cliques from the affected subgraph
+        self.replace_cliques(stale, new_cliques);
+    }
+
+    /// Remove the cliques which match a predicate, returning them with their identifiers.
+    fn take_cliques(
+        &mut self,
+        mut predicate: impl FnMut(&HashSet<Id>) -> bool,
+    ) -> Vec<(CliqueId, HashSet<Id>)> {
+        let cliques = std::mem::take(&mut self.cliques.0);
+        let ids = std::mem::take(&mut self.clique_ids);
+        let mut keep = Vec::with_capacity(cliques.len());
+        let (taken, kept): (Vec<_>, Vec<_>) =
+            ids.into_iter().zip(cliques).partition(|(_, clique)| {
+                let taken = predicate(clique);
+                keep.push(!taken);
+                taken
+            });
+        (self.clique_ids, self.cliques.0) = kept.into_iter().unzip();
+        let results = self
+            .results
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        for (id, _) in &taken {
+            results.remove(id);
+        }
+        if !taken.is_empty() {
+            self.flat_cliques.retain(&keep);
+        }
+        taken
+    }
+
+    /// Add new cliques in place of stale ones.
+    ///
+    /// Each new clique which corresponds one-to-one with a stale clique inherits its identifier
+    /// (and history). The others are assigned new identifiers, and the remaining stale cliques
+    /// are dissolved.
+    fn replace_cliques(
+        &mut self,
+        stale: Vec<(CliqueId, HashSet<Id>)>,
+        mut new_cliques: Vec<HashSet<Id>>,
+    ) {
+        if let Some(order) = self.config.seeded_order() {
+            order.sort_sets(&mut new_cliques);
+        }
+        let (stale_ids, stale): (Vec<_>, Vec<_>) = stale.into_iter().unzip();
+        let relations = if stale.is_empty() || new_cliques.is_empty() {
+            (0..stale.len())
+                .map(CliqueRelation::Removed)
+                .chain((0..new_cliques.len()).map(CliqueRelation::Added))
+                .collect()
+        } else {
+            clique_diff(&stale, &new_cliques).relations
+        };
+        let mut ids: Vec<Option<CliqueId>> = vec![None; new_cliques.len()];
+        let mut dissolved: HashSet<CliqueId> = stale_ids.iter().copied().collect();
+        for relation in &relations {
+            if let CliqueRelation::Matched { a, b, .. } = *relation {
+                ids[b] = Some(stale_ids[a]);
+                dissolved.remove(&stale_ids[a]);
+                self.histories
+                    .update(stale_ids[a], self.generation, &stale[a], &new_cliques[b]);
+            }
+        }
+        for id in dissolved {
+            self.histories.dissolve(id);
+        }
+        let new_ids: Vec<CliqueId> = ids
+            .into_iter()
+            .zip(&new_cliques)
+            .map(|(id, clique)| {
+                id.unwrap_or_else(|| self.histories.create(self.generation, clique))
+            })
+            .collect();
+        if self.events.is_active() {
+            self.events.replaced(
+                self.generation,
+                (&stale_ids, &stale),
+                (&new_ids, &new_cliques),
+                &relations,
+            );
+        }
+        self.clique_ids.extend(new_ids);
+        for clique in &new_cliques {
+            self.flat_cliques.push(clique);
+        }
+        self.cliques.0.extend(new_cliques);
+        self.enforce_clique_capacity();
+    }
+
+    /// Drop cliques according to the configured [`CliqueOverflow`] policy, until the index is
+    /// within its clique capacity.
+    fn enforce_clique_capacity(&mut self) {
+        let Some((max_cliques, policy)) = self.config.clique_capacity else {
+            return;
+        };
+        let excess = self.cliques.len().saturating_sub(max_cliques);
+        if excess == 0 {
+            return;
+        }
+        // new cliques are at the end, and ties are dropped newest first
+        let mut order: Vec<usize> = (0..self.cliques.len()).rev().collect();
+        match policy {
+            CliqueOverflow::DropLowestScore => {
+                let scores = self.scores();
+                order.sort_by(|a, b| scores[*a].total_cmp(&scores[*b]));
+            }
+            CliqueOverflow::DropSmallest | CliqueOverflow::Error => {
+                order.sort_by_key(|index| self.cliques.0[*index].len());
+            }
+        }
+        let victims: HashSet<usize> = order.into_iter().take(excess).collect();
+        let mut index = 0;
+        let dropped = self.take_cliques(|_| {
+            let victim = victims.contains(&index);
+            index += 1;
+            victim
+        });
+        self.dropped_cliques += dropped.len();
+        for (id, _) in &dropped {
+            self.histories.dissolve(*id);
+        }
+        if self.events.is_active() {
+            let (ids, cliques): (Vec<_>, Vec<_>) = dropped.into_iter().unzip();
+            let relations: Vec<_> = (0..ids.len()).map(CliqueRelation::Removed).collect();
+            self.events
+                .replaced(self.generation, (&ids, &cliques), (&[], &[]), &relations);
+        }
+    }
+
+    /// The [score](FusedClique::score) of each of the current cliques, in the same order, reusing
+    /// the cached results where possible.
+    fn scores(&self) -> Vec<f64> {
+        let mut results = self.results.lock().unwrap_or_else(PoisonError::into_inner);
+        self.clique_ids
+            .iter()
+            .zip(&self.cliques)
+            .map(|(id, clique)| {
+                if let Some(result) = results.get(id) {
+                    return result.score;
+                }
+                self.fused_estimate(clique, FusionMethod::default(), |_| ())
+                    .map_or(0.0, |estimate| {
+                        let result = FusedClique::from((*id, estimate));
+                        let score = result.score;
+                        results.insert(*id, result);
+                        score
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns `true` if cliques have been dropped to keep the index within its
+    /// [clique capacity](Config::clique_capacity), since its cliques were last enumerated in full,
+    /// so that [`Self::cliques`] (and the results derived from it) may be incomplete.
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, CliqueOverflow, Config, Observation, Unique};
+    ///
+    /// let config = Config::new(CHI2_2D_CONFIDENCE_95).clique_capacity(1, CliqueOverflow::DropSmallest);
+    /// let mut index = CliqueIndex::with_config(config);
+    /// for (id, x) in [(1, 0.0), (2, 0.5), (3, 1.0), (4, 100.0), (5, 100.5)] {
+    ///     let observation = Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    ///
+    /// // the pair at x = 100 was dropped in favour of the larger clique
+    /// assert_eq!(index.cliques().len(), 1);
+    /// assert_eq!(index.cliques().largest().map(|clique| clique.len()), Some(3));
+    /// assert!(index.is_truncated());
+    /// ```
     #[must_use]
-    pub const fn compatibility_graph(&self) -> &HashMap<Id, HashSet<Id>> {
-        &self.compatibility_graph
+    pub const fn is_truncated(&self) -> bool {
+        self.dropped_cliques > 0
+    }
+
+    /// Get the current set of maximal cliques
+    #[must_use]
+    pub const fn cliques(&self) -> &CliqueSet<Id> {
+        &self.cliques
+    }
+
+    /// Iterate over the current cliques which satisfy a predicate, in the same order as
+    /// [`Self::cliques`].
+    ///
+    /// The predicate is called with the member observations of each clique, and the cliques are
+    /// filtered lazily as the iterator is consumed.
+    pub fn cliques_matching<'a>(
+        &'a self,
+        mut predicate: impl FnMut(&HashMap<Id, &Observation>) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a HashSet<Id>> + 'a {
+        self.cliques.iter().filter(move |clique| {
+            let members = clique
+                .iter()
+                .filter_map(|id| {
+                    self.spatial_index
+                        .get(id)
+                        .map(|observation| (*id, observation))
+                })
+                .collect();
+            predicate(&members)
+        })
+    }
+
+    /// Iterate over the current cliques with at least one member with the given
+    /// [label](Observation::labels), in the same order as [`Self::cliques`].
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// let observation = |id, x, classified| {
+    ///     let builder = Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap();
+    ///     let builder = if classified { builder.label("classified") } else { builder };
+    ///     Unique { data: builder.build(), id }
+    /// };
+    /// let observations = vec![
+    ///     observation(1, 0.0, true),
+    ///     observation(2, 0.1, false),
+    ///     observation(3, 10.0, false),
+    ///     observation(4, 10.1, false),
+    /// ];
+    /// let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+    ///
+    /// let classified: Vec<_> = index.cliques_with_label("classified").collect();
+    /// assert_eq!(classified.len(), 1);
+    /// assert!(classified[0].contains(&1));
+    /// ```
+    pub fn cliques_with_label<'a>(
+        &'a self,
+        label: &'a str,
+    ) -> impl Iterator<Item = &'a HashSet<Id>> + 'a {
+        self.cliques.iter().filter(move |clique| {
+            clique.iter().any(|id| {
+                self.spatial_index
+                    .get(id)
+                    .is_some_and(|observation| observation.has_label(label))
+            })
+        })
+    }
+
+    /// Get the current set of maximal cliques in a flat layout, in the same order as
+    /// [`Self::cliques`].
+    ///
+    /// This is kept up to date as cliques are added and removed, so reading it never allocates.
+    /// See [`FlatCliques`].
+    #[must_use]
+    pub const fn flat_cliques(&self) -> &FlatCliques<Id> {
+        &self.flat_cliques
+    }
+
+    /// Get the stable identifiers of the current cliques, in the same order as [`Self::cliques`].
+    ///
+    /// See [`CliqueId`].
+    #[must_use]
+    pub fn clique_ids(&self) -> &[CliqueId] {
+        &self.clique_ids
+    }
+
+    /// Allocate the identifiers of new cliques with a caller-provided allocator, instead of the
+    /// one selected by [`Config::clique_ids`].
+    ///
+    /// Existing cliques keep their identifiers, so this should usually be called before any
+    /// observations are inserted, and the allocator must not return the identifier of an
+    /// existing clique.
+    ///
+    /// ```
+    /// use clique_fusion::{
+    ///     CHI2_2D_CONFIDENCE_95, CliqueIndex, MonotonicIds, Observation, Unique,
+    /// };
+    ///
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// // continue the sequence of identifiers from before a restart
+    /// index.set_clique_id_allocator(MonotonicIds::starting_at(1000));
+    ///
+    /// for (id, x) in [(1, 0.0), (2, 0.1)] {
+    ///     let observation = Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    ///
+    /// assert_eq!(index.clique_ids()[0].as_u64(), 1000);
+    /// ```
+    pub fn set_clique_id_allocator(&mut self, allocator: impl CliqueIdAllocator + 'static) {
+        self.histories.set_allocator(Box::new(allocator));
+    }
+
+    /// Start recording the [`CliqueEvent`](crate::CliqueEvent)s of subsequent operations, to be
+    /// taken with [`Self::drain_clique_events`].
+    ///
+    /// Events accumulate until they are drained, so a long-running consumer should drain them
+    /// regularly (or use [`Self::set_clique_event_sink`] instead).
+    pub fn record_clique_events(&mut self) {
+        self.events.record();
+    }
+
+    /// Take the [`CliqueEvent`](crate::CliqueEvent)s recorded since the last call, oldest first.
+    ///
+    /// No events are recorded until [`Self::record_clique_events`] is called.
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueEvent, CliqueIndex, Observation, Unique};
+    ///
+    /// let observation = |id, x| Unique {
+    ///     data: Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build(),
+    ///     id,
+    /// };
+    ///
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// index.record_clique_events();
+    /// index.insert(observation(1, 0.0));
+    /// index.insert(observation(2, 0.5));
+    /// index.insert(observation(3, 1.0));
+    /// index.remove(&1);
+    ///
+    /// let events: Vec<_> = index.drain_clique_events().map(|timed| timed.event).collect();
+    /// assert!(matches!(events[0], CliqueEvent::Created { .. }));
+    /// assert!(matches!(events.last(), Some(CliqueEvent::Updated { .. })));
+    /// ```
+    pub fn drain_clique_events(&mut self) -> impl Iterator<Item = TimedCliqueEvent<Id>> + '_ {
+        self.events.drain()
+    }
+
+    /// Deliver the [`CliqueEvent`](crate::CliqueEvent)s of subsequent operations to the sink as
+    /// they occur, such as a closure which forwards them to a channel.
+    pub fn set_clique_event_sink(&mut self, sink: impl CliqueEventSink<Id> + 'static) {
+        self.events.set_sink(Box::new(sink));
+    }
+
+    /// Report the candidate neighbours which fail the statistical gate in subsequent operations to
+    /// the log, with their squared Mahalanobis distances and the threshold, for tuning.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// let near_misses = Arc::new(Mutex::new(Vec::new()));
+    /// let log = Arc::clone(&near_misses);
+    ///
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// index.set_discard_log(move |query, candidate, distance_squared: f64, threshold| {
+    ///     log.lock().unwrap().push((query, candidate, distance_squared / threshold));
+    /// });
+    ///
+    /// for (id, x) in [(1, 0.0), (2, 1.5)] {
+    ///     let observation = Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    ///
+    /// assert!(index.cliques().is_empty());
+    /// let near_misses = near_misses.lock().unwrap();
+    /// assert_eq!(near_misses.len(), 1);
+    /// assert_eq!((near_misses[0].0, near_misses[0].1), (2, 1));
+    /// ```
+    pub fn set_discard_log(&mut self, log: impl DiscardLog<Id> + 'static) {
+        self.spatial_index.set_discard_log(Box::new(log));
+    }
+
+    /// Report the time spent in each [`Phase`](crate::Phase) of the operations on the index to the
+    /// profiler, such as [`PhaseTimings`](crate::PhaseTimings).
+    ///
+    /// Without a profiler, the candidate neighbours of each observation are found lazily as they
+    /// are tested for compatibility. With one, they are all found first, so that the phases can be
+    /// timed separately.
+    #[cfg(feature = "profiling")]
+    pub fn set_profiler(&mut self, profiler: impl crate::Profiler + 'static) {
+        self.spatial_index.set_profiler(Arc::new(profiler));
+    }
+
+    /// Look up the members of a current clique by its identifier.
+    #[must_use]
+    pub fn clique(&self, id: CliqueId) -> Option<&HashSet<Id>> {
+        self.clique_ids
+            .iter()
+            .position(|clique_id| *clique_id == id)
+            .map(|index| &self.cliques.0[index])
+    }
+
+    /// Get the history of the membership of a current clique.
+    ///
+    /// Returns `None` if the clique no longer exists, or if histories are not recorded. See
+    /// [`Config::clique_history`].
+    #[must_use]
+    pub fn clique_history(&self, id: CliqueId) -> Option<&CliqueHistory<Id>> {
+        self.histories.get(id)
+    }
+
+    /// Get the fused estimate of the position of each of the current cliques.
+    ///
+    /// The estimates are returned in the same order as [`Self::cliques`]. See [`FusedEstimate`].
+    #[must_use]
+    pub fn fused_estimates(&self) -> Vec<FusedEstimate<Id>> {
+        self.fused_estimates_with(|_| ())
+    }
+
+    /// Get the fused estimate of each of the current cliques, together with attributes merged from
+    /// the metadata of its members.
+    ///
+    /// `merge` is called once for each clique, with its member observations, and returns the
+    /// attributes of the fused estimate. Caller-side metadata can be looked up by observation ID,
+    /// or by [tag](Observation::tag).
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// // the classification of each observation, indexed by tag
+    /// let classes = ["car", "car", "truck"];
+    ///
+    /// let observations = [0.0, 0.1, 0.2]
+    ///     .into_iter()
+    ///     .enumerate()
+    ///     .map(|(id, x)| Unique {
+    ///         data: Observation::builder(x, 0.0)
+    ///             .circular_95_confidence_error(1.0)
+    ///             .unwrap()
+    ///             .tag(id as u64)
+    ///             .build(),
+    ///         id,
+    ///     })
+    ///     .collect();
+    /// let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+    ///
+    /// // majority vote
+    /// let estimates = index.fused_estimates_with(|members| {
+    ///     let mut votes = HashMap::new();
+    ///     for observation in members.values() {
+    ///         let class = classes[observation.tag().unwrap() as usize];
+    ///         *votes.entry(class).or_insert(0) += 1;
+    ///     }
+    ///     votes.into_iter().max_by_key(|(_, count)| *count).map(|(class, _)| class)
+    /// });
+    ///
+    /// assert_eq!(estimates[0].attributes, Some("car"));
+    /// ```
+    pub fn fused_estimates_with<A>(
+        &self,
+        merge: impl FnMut(&HashMap<Id, &Observation>) -> A,
+    ) -> Vec<FusedEstimate<Id, A>> {
+        self.fused_estimates_by(FusionMethod::default(), merge)
+    }
+
+    /// Get the fused estimate of each of the current cliques, combining the members with the given
+    /// method.
+    ///
+    /// A robust method limits the influence of a mis-associated member on the fused position. See
+    /// [`FusionMethod`].
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, FusionMethod, Observation, Unique};
+    ///
+    /// // the last observation is only just compatible with the others
+    /// let observations = [(1, 0.0, 0.0), (2, 0.1, 0.0), (3, 0.2, 0.0), (4, 0.1, 1.41)]
+    ///     .into_iter()
+    ///     .map(|(id, x, y)| Unique {
+    ///         data: Observation::builder(x, y)
+    ///             .circular_95_confidence_error(1.0)
+    ///             .unwrap()
+    ///             .build(),
+    ///         id,
+    ///     })
+    ///     .collect();
+    /// let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+    ///
+    /// let fused = index.fused_estimates();
+    /// assert_eq!(fused.len(), 1);
+    /// assert!(!fused[0].goodness_of_fit.is_consistent(0.2));
+    ///
+    /// let trimmed = index.fused_estimates_using(FusionMethod::Trimmed { significance: 0.2 });
+    /// assert_eq!(trimmed[0].outliers, [4].into());
+    /// assert!(trimmed[0].y.abs() < 1e-9);
+    ///
+    /// let huber = index.fused_estimates_using(FusionMethod::Huber { threshold: 1.5 });
+    /// assert!(huber[0].y < fused[0].y);
+    /// ```
+    #[must_use]
+    pub fn fused_estimates_using(&self, method: FusionMethod) -> Vec<FusedEstimate<Id>> {
+        self.fused_estimates_by(method, |_| ())
+    }
+
+    fn fused_estimates_by<A>(
+        &self,
+        method: FusionMethod,
+        mut merge: impl FnMut(&HashMap<Id, &Observation>) -> A,
+    ) -> Vec<FusedEstimate<Id, A>> {
+        self.cliques
+            .iter()
+            .filter_map(|clique| self.fused_estimate(clique, method, &mut merge))
+            .collect()
+    }
+
+    /// Get the fused estimate of a single clique. See [`Self::fused_estimates_by`].
+    fn fused_estimate<A>(
+        &self,
+        clique: &HashSet<Id>,
+        method: FusionMethod,
+        merge: impl FnOnce(&HashMap<Id, &Observation>) -> A,
+    ) -> Option<FusedEstimate<Id, A>> {
+        let mut members = self.spatial_index.get_all(clique);
+        let fusion = fuse_members(&members, method)?;
+        let attributes = merge(&members);
+        members.retain(|id, _| !fusion.outliers.contains(id));
+        let (x, y) = (fusion.x, fusion.y);
+        Some(FusedEstimate {
+            members: clique.clone(),
+            x,
+            y,
+            covariance: fusion.covariance,
+            sensors: members.values().filter_map(|o| o.sensor()).collect(),
+            goodness_of_fit: goodness_of_fit(members.values().copied(), (x, y)),
+            outliers: fusion.outliers,
+            attributes,
+        })
+    }
+
+    /// Get a summary of each of the current cliques, with its identifier, members and fused
+    /// estimate, in the same order as [`Self::cliques`].
+    ///
+    /// This joins the [clique identifiers](Self::clique_ids) and the [fused
+    /// estimates](Self::fused_estimates) by clique. The summaries are cached, and only the cliques
+    /// which have changed since the last call are fused again, so this is cheap to call after each
+    /// update. See [`FusedClique`].
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// let observation = |id, x| Unique {
+    ///     data: Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build(),
+    ///     id,
+    /// };
+    /// let mut index = CliqueIndex::from_observations(
+    ///     vec![observation(1, 0.0), observation(2, 0.2)],
+    ///     CHI2_2D_CONFIDENCE_95,
+    /// );
+    ///
+    /// let results = index.results();
+    /// assert_eq!(results[0].id, index.clique_ids()[0]);
+    /// assert!((results[0].fused_position.0 - 0.1).abs() < 1e-9);
+    ///
+    /// index.insert(observation(3, 0.3));
+    /// assert_eq!(index.results()[0].members.len(), 3);
+    /// ```
+    #[must_use]
+    pub fn results(&self) -> Vec<FusedClique<Id>> {
+        let mut results = self.results.lock().unwrap_or_else(PoisonError::into_inner);
+        self.clique_ids
+            .iter()
+            .zip(&self.cliques)
+            .filter_map(|(id, clique)| {
+                if let Some(result) = results.get(id) {
+                    return Some(result.clone());
+                }
+                let estimate = self.fused_estimate(clique, FusionMethod::default(), |_| ())?;
+                let result = FusedClique::from((*id, estimate));
+                results.insert(*id, result.clone());
+                Some(result)
+            })
+            .collect()
+    }
+
+    /// Get the centroid and bounding ellipse of the member positions of each of the current cliques.
+    ///
+    /// This is much cheaper than [`Self::fused_estimates`], but ignores the observations' errors,
+    /// so is only suitable for display. The centroids are returned in the same order as
+    /// [`Self::cliques`]. See [`CliqueCentroid`].
+    #[must_use]
+    pub fn clique_centroids(&self) -> Vec<CliqueCentroid<Id>> {
+        self.cliques
+            .iter()
+            .filter_map(|clique| {
+                let (x, y, extent) =
+                    centroid(clique.iter().filter_map(|id| self.spatial_index.get(id)))?;
+                Some(CliqueCentroid {
+                    members: clique.clone(),
+                    x,
+                    y,
+                    extent,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the connected components of the compatibility graph.
+    ///
+    /// Each component is a maximal set of observations which are linked by chains of pairwise
+    /// compatibility. Every clique lies within a single component. Observations which are not
+    /// compatible with any other are not part of any component.
+    #[must_use]
+    pub fn components(&self) -> Vec<HashSet<Id>> {
+        let mut components = self.component_forest().sets();
+        if let Some(order) = self.config.seeded_order() {
+            order.sort_sets(&mut components);
+        }
+        components
+    }
+
+    /// Returns `true` if both observations belong to the same connected component of the
+    /// compatibility graph. See [`Self::components`].
+    ///
+    /// This takes near-constant time, except for the first query after an observation has been
+    /// removed, which rebuilds the components.
+    #[must_use]
+    pub fn same_component(&self, a: &Id, b: &Id) -> bool {
+        let components = self.component_forest();
+        components
+            .find(a)
+            .is_some_and(|root| components.find(b) == Some(root))
+    }
+
+    fn component_forest(&self) -> &UnionFind<Id> {
+        self.components
+            .get_or_init(|| UnionFind::from_graph(&self.compatibility_graph))
+    }
+
+    /// Returns `true` if any of the current cliques have been approximated by hierarchical clustering,
+    /// rather than enumerated exactly.
+    ///
+    /// This can only occur if a [`DensityFallback`](crate::DensityFallback) or a
+    /// [maximum neighbourhood](Config::max_neighbourhood) has been configured.
+    #[must_use]
+    pub fn is_approximate(&self) -> bool {
+        !self.approximate.is_empty()
+    }
+
+    /// A canonical hash of the state of the index: its observations, compatibility graph and cliques.
+    ///
+    /// With exact clique enumeration, the state of an index depends only on the set of observations
+    /// it contains, and its configuration. It is the same regardless of the order in which the
+    /// observations were inserted (or removed), and of whether the index was constructed in batch
+    /// or incrementally. This hash is likewise independent of order, so can be used to verify that
+    /// replicas of an index have converged. It doesn't depend on clique [identifiers](Self::clique_ids).
+    ///
+    /// The state does depend on the order of insertion if:
+    ///
+    /// - duplicates are [skipped](crate::DuplicatePolicy::Skip) or
+    ///   [counted](crate::DuplicatePolicy::Count), since the first of a set of identical
+    ///   observations is the one retained,
+    /// - a [clique capacity](Config::clique_capacity) drops cliques, since ties are broken in
+    ///   favour of older cliques, or
+    /// - cliques are approximated under a [`DensityFallback`](crate::DensityFallback) or a
+    ///   [maximum neighbourhood](Config::max_neighbourhood).
+    ///
+    /// The hash uses a fixed algorithm, independent of the platform and the version of Rust, so
+    /// equal states hash equally across processes and platforms, given the same `Id` type (whose
+    /// [`Hash`](std::hash::Hash) implementation must itself be platform-independent, as it is
+    /// for the integer types and [`Uuid`]) and the same version of this crate.
+    #[must_use]
+    pub fn state_hash(&self) -> u64 {
+        let hasher = BuildHasherDefault::<StableHasher>::default();
+        // hashes of the elements of each set are summed, so their order doesn't matter
+        let sum = |hashes: &mut dyn Iterator<Item = u64>| hashes.fold(0, u64::wrapping_add);
+
+        let observations = sum(&mut self.spatial_index.iter().map(|(id, observation)| {
+            let mut state = hasher.build_hasher();
+            id.hash(&mut state);
+            observation.hash_exact(&mut state);
+            state.finish()
+        }));
+        let edges = sum(&mut self
+            .compatibility_graph
+            .iter()
+            .flat_map(|(a, neighbours)| neighbours.iter().map(move |b| (a, b)))
+            .map(|edge| hasher.hash_one(edge)));
+        let cliques = sum(&mut self.cliques.iter().map(|clique| {
+            let members = sum(&mut clique.iter().map(|id| hasher.hash_one(id)));
+            hasher.hash_one((clique.len() as u64, members))
+        }));
+
+        hasher.hash_one((observations, edges, cliques))
+    }
+
+    /// A cheap summary of the state of the index, for monitoring.
+    ///
+    /// This is linear in the number of connected observations and cliques, and does not enumerate
+    /// cliques.
+    #[must_use]
+    pub fn health(&self) -> Health {
+        Health {
+            total_observations: self.total_observations(),
+            connected_observations: self.len(),
+            compatibility_edges: self
+                .compatibility_graph
+                .iter()
+                .map(|(_, neighbours)| neighbours.len())
+                .sum::<usize>()
+                / 2,
+            cliques: self.cliques.len(),
+            largest_clique: self
+                .cliques
+                .iter()
+                .map(HashSet::len)
+                .max()
+                .unwrap_or_default(),
+            approximate_observations: self.approximate.len(),
+            dropped_cliques: self.dropped_cliques,
+            generation: self.generation,
+            last_insert_latency: self.last_insert_latency,
+        }
+    }
+
+    /// The number of exact duplicates of the given observation which have been discarded on ingest.
+    ///
+    /// This is always zero unless the index is configured with [`DuplicatePolicy::Count`](crate::DuplicatePolicy::Count).
+    #[must_use]
+    pub fn duplicate_count(&self, id: &Id) -> usize {
+        self.duplicates.count(id)
+    }
+
+    /// Check whether an observation with the given ID is in the index
+    #[must_use]
+    pub fn contains(&self, id: &Id) -> bool {
+        self.spatial_index.get(id).is_some()
+    }
+
+    /// Look up the shared storage of an observation in the index by ID.
+    ///
+    /// The [`Arc`] can be cloned cheaply, and handed to other threads, to keep the observation
+    /// alive independently of the index. See [`Self::insert_shared`].
+    #[must_use]
+    pub fn get_shared(&self, id: &Id) -> Option<&Arc<Observation>> {
+        self.spatial_index.get_shared(id)
+    }
+
+    /// Look up an observation in the index by ID
+    #[must_use]
+    pub fn get(&self, id: &Id) -> Option<&Observation> {
+        self.spatial_index.get(id)
+    }
+
+    /// Get the total number of observations in the index, including those which are not
+    /// compatible with any other observation
+    #[must_use]
+    pub fn total_observations(&self) -> usize {
+        self.spatial_index.len()
+    }
+
+    /// Get the number of observations in the index which are compatible with at least one other
+    /// observation
+    ///
+    /// See also [`Self::total_observations`].
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.compatibility_graph.len()
+    }
+
+    /// Check if the index is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.compatibility_graph.is_empty()
+    }
+
+    /// Aggregate the observations and cliques within a bounding box into a regular grid of counts.
+    ///
+    /// Each observation is counted in the cell containing its position, and each clique is counted in
+    /// the cell containing the centroid of its members' positions. Observations outside the bounding
+    /// box are not counted, and nor are cliques whose centroids lie outside it, or which have no
+    /// members within it (see [`Self::cliques_in_region`]).
+    ///
+    /// This is intended for producing overviews (such as heatmaps) of datasets which are too large to
+    /// render individually.
+    ///
+    /// With the `geo` feature enabled, the bounds may also be given as a `geo_types::Rect`.
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    /// use clique_fusion::{BoundingBox, CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// let observations = [(0, 1.0, 1.0), (1, 1.2, 1.1), (2, 8.0, 8.0)]
+    ///     .into_iter()
+    ///     .map(|(id, x, y)| Unique {
+    ///         data: Observation::builder(x, y)
+    ///             .circular_95_confidence_error(1.0)
+    ///             .unwrap()
+    ///             .build(),
+    ///         id,
+    ///     })
+    ///     .collect();
+    /// let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+    ///
+    /// let bounds = BoundingBox::from_corners((0.0, 0.0), (10.0, 10.0));
+    /// let two = NonZeroUsize::new(2).unwrap();
+    /// let grid = index.density_grid(bounds, two, two);
+    ///
+    /// let cell = grid.cell(0, 0).unwrap();
+    /// assert_eq!(cell.observations, 2);
+    /// assert_eq!(cell.cliques, 1);
+    /// assert_eq!(grid.cell(1, 1).unwrap().observations, 1);
+    /// ```
+    #[must_use]
+    pub fn density_grid(
+        &self,
+        bounds: impl Into<BoundingBox>,
+        columns: NonZeroUsize,
+        rows: NonZeroUsize,
+    ) -> DensityGrid {
+        let bounds = bounds.into();
+        let mut grid = DensityGrid::new(bounds, columns, rows);
+
+        let mut within = HashSet::new();
+        for observation in self.spatial_index.locate_in_bounds(&bounds) {
+            grid.add_observation(observation.data.x(), observation.data.y());
+            if self.compatibility_graph.contains_key(&observation.id) {
+                within.insert(observation.id);
+            }
+        }
+        if within.is_empty() {
+            return grid;
+        }
+
+        for clique in self
+            .cliques
+            .iter()
+            .filter(|clique| !clique.is_disjoint(&within))
+        {
+            if let Some((x, y, _)) =
+                centroid(clique.iter().filter_map(|id| self.spatial_index.get(id)))
+            {
+                grid.add_clique(x, y);
+            }
+        }
+
+        grid
+    }
+
+    /// Get the cliques with at least one member within the given bounding box (inclusive), in
+    /// order.
+    ///
+    /// This is intended for rendering a viewport, without fetching every clique. Each clique is
+    /// returned in full, including any members outside the bounds.
+    #[must_use]
+    pub fn cliques_in_region(&self, bounds: impl Into<BoundingBox>) -> CliqueSet<Id> {
+        let within: HashSet<Id> = self
+            .spatial_index
+            .locate_in_bounds(&bounds.into())
+            .map(|observation| observation.id)
+            .filter(|id| self.compatibility_graph.contains_key(id))
+            .collect();
+        if within.is_empty() {
+            return CliqueSet::default();
+        }
+        self.cliques
+            .iter()
+            .filter(|clique| !clique.is_disjoint(&within))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the compatibility graph (for debugging/analysis)
+    #[must_use]
+    pub const fn compatibility_graph(&self) -> &G {
+        &self.compatibility_graph
+    }
+
+    /// Check the internal consistency of the index, for debugging.
+    ///
+    /// This verifies that:
+    /// - the compatibility graph is symmetric, and connects exactly the compatible pairs of
+    ///   observations in the index (which are found afresh, so this is as expensive as rebuilding
+    ///   the graph)
+    /// - every clique is a clique of the graph, and no clique is reported twice
+    /// - every clique is maximal, and every edge is within some clique, except in regions which
+    ///   were [approximated](Config::density_fallback) or
+    ///   [pruned](Config::mutual_nearest_neighbours)
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// for (id, x) in [(1, 0.0), (2, 0.5), (3, 1.0)] {
+    ///     let observation = Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    /// index.remove(&2);
+    ///
+    /// assert_eq!(index.validate(), Ok(()));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`InvariantViolation`] found, if any.
+    #[cfg(feature = "validate")]
+    pub fn validate(&self) -> Result<(), InvariantViolation<Id>> {
+        self.validate_graph()?;
+        self.validate_cliques()
+    }
+
+    /// Check that the compatibility graph is symmetric, and matches the observations.
+    #[cfg(feature = "validate")]
+    fn validate_graph(&self) -> Result<(), InvariantViolation<Id>> {
+        for (id, neighbours) in self.compatibility_graph.iter() {
+            if self.spatial_index.get(id).is_none() {
+                return Err(InvariantViolation::UnknownObservation(*id));
+            }
+            for neighbour in neighbours {
+                if neighbour == id {
+                    return Err(InvariantViolation::SelfLoop(*id));
+                }
+                if !self
+                    .compatibility_graph
+                    .get(neighbour)
+                    .is_some_and(|reverse| reverse.contains(id))
+                {
+                    return Err(InvariantViolation::AsymmetricEdge {
+                        from: *id,
+                        to: *neighbour,
+                    });
+                }
+            }
+        }
+
+        let Ok(expected) = self
+            .spatial_index
+            .compatibility_graph::<G>(&self.config, &mut Monitor::default())
+        else {
+            unreachable!("a graph built without a cancellation token can't be cancelled")
+        };
+        let connected = |graph: &G, a: &Id, b: &Id| {
+            graph
+                .get(a)
+                .is_some_and(|neighbours| neighbours.contains(b))
+        };
+        for (a, neighbours) in expected.iter() {
+            if let Some(b) = neighbours
+                .iter()
+                .find(|b| !connected(&self.compatibility_graph, a, b))
+            {
+                return Err(InvariantViolation::MissingEdge { a: *a, b: *b });
+            }
+        }
+        for (a, neighbours) in self.compatibility_graph.iter() {
+            if let Some(b) = neighbours.iter().find(|b| !connected(&expected, a, b)) {
+                return Err(InvariantViolation::SpuriousEdge { a: *a, b: *b });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the cliques are distinct maximal cliques of the compatibility graph, which
+    /// together cover its edges, where they were enumerated exactly.
+    #[cfg(feature = "validate")]
+    fn validate_cliques(&self) -> Result<(), InvariantViolation<Id>> {
+        let connected = |a: &Id, b: &Id| {
+            self.compatibility_graph
+                .get(a)
+                .is_some_and(|neighbours| neighbours.contains(b))
+        };
+        let cliques: Vec<&HashSet<Id>> = self.cliques.iter().collect();
+        let mut membership: HashMap<Id, Vec<usize>> = HashMap::new();
+        for (index, (clique, clique_id)) in cliques.iter().zip(&self.clique_ids).enumerate() {
+            let mut members = clique.iter();
+            while let Some(a) = members.next() {
+                if let Some(b) = members.clone().find(|b| !connected(a, b)) {
+                    return Err(InvariantViolation::NotAClique {
+                        clique: *clique_id,
+                        a: *a,
+                        b: *b,
+                    });
+                }
+            }
+            // a duplicate shares every member, so is among the earlier cliques of any one of them
+            if let Some(first) = clique.iter().next() {
+                let earlier = membership.get(first).map_or(&[][..], Vec::as_slice);
+                if earlier.iter().any(|other| cliques[*other] == *clique) {
+                    return Err(InvariantViolation::DuplicateClique(*clique_id));
+                }
+            }
+            for member in *clique {
+                membership.entry(*member).or_default().push(index);
+            }
+        }
+
+        let exact =
+            |id: &Id| self.config.mutual_nearest.is_none() && !self.approximate.contains(id);
+        // dropped cliques leave edges uncovered, but the others remain maximal
+        let complete = |a: &Id, b: &Id| exact(a) && exact(b) && self.dropped_cliques == 0;
+        for (clique, clique_id) in cliques.iter().zip(&self.clique_ids) {
+            if !clique.iter().all(exact) {
+                continue;
+            }
+            let mut members = clique.iter();
+            let Some(first) = members.next() else {
+                continue;
+            };
+            if let Some(extension) = self.compatibility_graph[first]
+                .iter()
+                .find(|candidate| members.clone().all(|member| connected(member, candidate)))
+            {
+                return Err(InvariantViolation::NotMaximal {
+                    clique: *clique_id,
+                    extension: *extension,
+                });
+            }
+        }
+        for (a, neighbours) in self.compatibility_graph.iter() {
+            for b in neighbours.iter().filter(|b| complete(a, b)) {
+                let covered = membership.get(a).is_some_and(|indices| {
+                    indices.iter().any(|clique| cliques[*clique].contains(b))
+                });
+                if !covered {
+                    return Err(InvariantViolation::UncoveredEdge { a: *a, b: *b });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get each edge of the compatibility graph exactly once, as `(a, b, d²)`.
+    ///
+    /// `d²` is the squared Mahalanobis distance between the two observations under the sum of
+    /// their covariances (the statistic compared against the chi-squared threshold). See
+    /// [`Observation::is_compatible_with`]. Under a [`MotionModel`](crate::MotionModel), this is
+    /// the distance after propagating the older observation to the time of the newer one.
+    #[must_use]
+    pub fn compatibility_edges(&self) -> Vec<(Id, Id, f64)> {
+        let mut visited = HashSet::with_capacity(self.compatibility_graph.len());
+        let mut edges = Vec::new();
+        for (a, neighbours) in self.compatibility_graph.iter() {
+            visited.insert(*a);
+            let Some(observation_a) = self.spatial_index.get(a) else {
+                continue;
+            };
+            for b in neighbours.iter().filter(|b| !visited.contains(*b)) {
+                if let Some(observation_b) = self.spatial_index.get(b) {
+                    edges.push((
+                        *a,
+                        *b,
+                        observation_a.gated_distance_squared(observation_b, &self.config),
+                    ));
+                }
+            }
+        }
+        edges
+    }
+
+    /// Export the compatibility graph, with features of each observation, for graph-learning
+    /// pipelines.
+    ///
+    /// See [`GraphExport`].
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// let observations = [(1, 0.0), (2, 0.5), (3, 100.0)]
+    ///     .into_iter()
+    ///     .map(|(id, x)| Unique {
+    ///         data: Observation::builder(x, 0.0)
+    ///             .circular_95_confidence_error(1.0)
+    ///             .unwrap()
+    ///             .build(),
+    ///         id,
+    ///     })
+    ///     .collect();
+    /// let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+    ///
+    /// let graph = index.graph_export();
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert_eq!(graph.edge_count(), 2);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn graph_export(&self) -> GraphExport<Id> {
+        let mut ids: Vec<Id> = self.spatial_index.iter().map(|(id, _)| *id).collect();
+        if let Some(order) = self.config.seeded_order() {
+            order.sort(&mut ids);
+        }
+        let nodes: HashMap<Id, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+        let mut export = GraphExport {
+            node_features: Vec::with_capacity(ids.len()),
+            sources: Vec::new(),
+            targets: Vec::new(),
+            distances: Vec::new(),
+            ids,
+        };
+        for (source, id) in export.ids.iter().enumerate() {
+            let Some(observation) = self.spatial_index.get(id) else {
+                continue;
+            };
+            let neighbours = self.compatibility_graph.get(id);
+            let error = observation.error_covariance();
+            export.node_features.push([
+                observation.x(),
+                observation.y(),
+                error.xx(),
+                error.xy(),
+                error.yy(),
+                neighbours.map_or(0, HashSet::len) as f64,
+            ]);
+            for neighbour in neighbours.into_iter().flatten() {
+                let (Some(&target), Some(other)) =
+                    (nodes.get(neighbour), self.spatial_index.get(neighbour))
+                else {
+                    continue;
+                };
+                export.sources.push(source);
+                export.targets.push(target);
+                export
+                    .distances
+                    .push(observation.gated_distance_squared(other, &self.config));
+            }
+        }
+        export
+    }
+
+    /// Classify the pair of observations with the given IDs as compatible, borderline, or
+    /// incompatible.
+    ///
+    /// Returns `None` if either observation is not in the index. See
+    /// [`Config::borderline_band`].
+    #[must_use]
+    pub fn compatibility(&self, a: &Id, b: &Id) -> Option<Compatibility> {
+        let (observation_a, observation_b) =
+            (self.spatial_index.get(a)?, self.spatial_index.get(b)?);
+        if observation_a.is_gated_out(observation_b, &self.config) {
+            return Some(Compatibility::Incompatible);
+        }
+        Some(
+            self.config
+                .classify(observation_a.gated_distance_squared(observation_b, &self.config)),
+        )
+    }
+
+    /// Find the observations which an observation would be compatible with, without inserting it.
+    ///
+    /// This previews the neighbours an observation would gain in the compatibility graph if it
+    /// were inserted, as `(id, d²)`, nearest first. An observation in the index with the same ID is
+    /// excluded, so this also previews the effect of an [update](Self::update). The index is not
+    /// modified.
+    #[must_use]
+    pub fn probe(&self, observation: &Unique<Observation, Id>) -> Vec<(Id, f64)> {
+        let mut compatible: Vec<(Id, f64)> = self
+            .spatial_index
+            .find_compatible(observation, &self.config)
+            .map(|(other, distance)| (other.id, distance))
+            .collect();
+        compatible.sort_by(|a, b| a.1.total_cmp(&b.1));
+        compatible
+    }
+
+    /// Probe the index with a batch of external observations, without inserting them.
+    ///
+    /// This is the same as calling [`Self::probe`] for each in turn, but with the `rayon` feature
+    /// the probes are run in parallel. This makes checking a large batch of candidates against an
+    /// existing catalogue much faster. The results are in the same order as the probes.
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    ///
+    /// let observation = |id, x| Unique {
+    ///     data: Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build(),
+    ///     id,
+    /// };
+    /// let index = CliqueIndex::from_observations(vec![observation(1, 0.0)], CHI2_2D_CONFIDENCE_95);
+    ///
+    /// let results = index.classify(&[observation(2, 0.5), observation(3, 10.0)]);
+    /// assert_eq!(results[0].nearest().map(|(id, _)| *id), Some(1));
+    /// assert!(results[1].is_novel());
+    /// ```
+    #[must_use]
+    pub fn classify(&self, probes: &[Unique<Observation, Id>]) -> Vec<ProbeResult<Id>>
+    where
+        Id: MaybeSendSync,
+        G: MaybeSendSync,
+    {
+        #[cfg(feature = "rayon")]
+        let probes = probes.par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let probes = probes.iter();
+        probes
+            .map(|probe| ProbeResult {
+                id: probe.id,
+                compatible: self.probe(probe),
+            })
+            .collect()
+    }
+
+    /// Get each borderline pair of observations exactly once, as `(a, b, d²)`.
+    ///
+    /// A pair is borderline if its squared Mahalanobis distance `d²` lies within the band around the
+    /// chi-squared threshold set by [`Config::borderline_band`]. This includes pairs on both sides
+    /// of the threshold, so some are edges of the compatibility graph and some are not. This is
+    /// empty if the band has zero width.
+    #[must_use]
+    pub fn borderline_pairs(&self) -> Vec<(Id, Id, f64)> {
+        let (lower, upper) = self.config.borderline_bounds();
+        if lower >= upper {
+            return Vec::new();
+        }
+        let mut visited = HashSet::with_capacity(self.spatial_index.len());
+        let mut pairs = Vec::new();
+        for (id, observation) in self.spatial_index.iter() {
+            visited.insert(*id);
+            let query = Unique {
+                data: observation,
+                id: *id,
+            };
+            for (other, distance) in self.spatial_index.find_within(&query, &self.config, upper) {
+                if visited.contains(&other.id) {
+                    continue;
+                }
+                if distance >= lower {
+                    pairs.push((*id, other.id, distance));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Get each pair of observations in the same context which are closer than the configured
+    /// minimum separation exactly once, as `(a, b)`.
+    ///
+    /// These are suspected duplicates, rather than distinct objects. This is empty if no minimum
+    /// separation is configured. See [`Config::min_separation`].
+    #[must_use]
+    pub fn suspected_duplicates(&self) -> Vec<(Id, Id)> {
+        let epsilon = self.config.min_separation;
+        if epsilon <= 0.0 {
+            return Vec::new();
+        }
+        let mut visited = HashSet::with_capacity(self.spatial_index.len());
+        let mut pairs = Vec::new();
+        for (id, observation) in self.spatial_index.iter() {
+            visited.insert(*id);
+            let Some(context) = observation.context() else {
+                continue;
+            };
+            let (x, y) = observation.position();
+            let bounds =
+                BoundingBox::from_corners((x - epsilon, y - epsilon), (x + epsilon, y + epsilon));
+            for other in self.spatial_index.locate_in_bounds(&bounds) {
+                if visited.contains(&other.id) || other.data.context() != Some(context) {
+                    continue;
+                }
+                if (other.data.x() - x).hypot(other.data.y() - y) < epsilon {
+                    pairs.push((*id, other.id));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Get each pair of observations in different contexts whose squared Mahalanobis distance is
+    /// at most `max_distance_squared` exactly once, as `(a, b, d²)`, nearest first.
+    ///
+    /// A pair this close is almost certainly the same report delivered twice by different paths,
+    /// rather than two independent observations of the same object. Only observations which both
+    /// have a [context](Observation::context) are reported. The threshold is capped at the
+    /// chi-squared threshold of the index, and should be close to zero (e.g. `1e-6`).
+    ///
+    /// This is independent of the cliques, so duplicates are reported whether or not they have
+    /// been fused.
+    #[must_use]
+    pub fn cross_context_duplicates(&self, max_distance_squared: f64) -> Vec<(Id, Id, f64)> {
+        let threshold = max_distance_squared.min(self.config.chi2);
+        let mut visited = HashSet::with_capacity(self.spatial_index.len());
+        let mut pairs = Vec::new();
+        for (id, observation) in self.spatial_index.iter() {
+            visited.insert(*id);
+            if observation.context().is_none() {
+                continue;
+            }
+            let query = Unique {
+                data: observation,
+                id: *id,
+            };
+            // observations in the same context are gated out
+            for (other, distance) in self
+                .spatial_index
+                .find_within(&query, &self.config, threshold)
+            {
+                if !visited.contains(&other.id) && other.data.context().is_some() {
+                    pairs.push((*id, other.id, distance));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| a.2.total_cmp(&b.2));
+        pairs
+    }
+
+    /// Solve the optimal one-to-one assignment between the observations of two contexts, such as
+    /// consecutive frames from a sensor, instead of enumerating cliques.
+    ///
+    /// Pairs are gated by the compatibility graph, and scored by their squared Mahalanobis
+    /// distance. See [`Assignment`].
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, CovarianceMatrix, Observation, Unique};
+    /// use uuid::Uuid;
+    ///
+    /// let (previous, current) = (Uuid::new_v4(), Uuid::new_v4());
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// let frames = [(1, 0.0, previous), (2, 2.0, previous), (3, 1.8, current), (4, 3.8, current)];
+    /// for (id, x, frame) in frames {
+    ///     let observation = Observation::builder(x, 0.0)
+    ///         .error(CovarianceMatrix::identity())
+    ///         .context(frame)
+    ///         .build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    ///
+    /// // greedily matching the closest pair (2, 3) would leave 1 and 4 unassigned
+    /// let assignment = index.assign(previous, current);
+    /// let mut pairs: Vec<_> = assignment.pairs.iter().map(|(a, b, _)| (*a, *b)).collect();
+    /// pairs.sort_unstable();
+    /// assert_eq!(pairs, vec![(1, 3), (2, 4)]);
+    /// ```
+    #[must_use]
+    pub fn assign(&self, first: Uuid, second: Uuid) -> Assignment<Id> {
+        let (first, second, edges) = self.assignment_problem(first, second);
+        assignment::assign(&first, &second, &edges, self.config.chi2 / 2.0)
+    }
+
+    /// Find the `k` best one-to-one assignments between the observations of two contexts, in order
+    /// of increasing cost, for trackers which need to account for ambiguous associations.
+    ///
+    /// Fewer than `k` assignments are returned if there are fewer distinct assignments. The first
+    /// is the same as that returned by [`Self::assign`] (or one of equal cost). See [`Assignment`].
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, CovarianceMatrix, Observation, Unique};
+    /// use uuid::Uuid;
+    ///
+    /// let (previous, current) = (Uuid::new_v4(), Uuid::new_v4());
+    /// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// for (id, x, frame) in [(1, 0.0, previous), (2, 0.5, current)] {
+    ///     let observation = Observation::builder(x, 0.0)
+    ///         .error(CovarianceMatrix::identity())
+    ///         .context(frame)
+    ///         .build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    ///
+    /// // either the pair is assigned, or both observations are left unassigned
+    /// let assignments = index.assign_k_best(previous, current, 5);
+    /// assert_eq!(assignments.len(), 2);
+    /// assert_eq!(assignments[0].pairs.len(), 1);
+    /// assert!(assignments[1].pairs.is_empty());
+    /// assert!(assignments[0].cost < assignments[1].cost);
+    /// ```
+    #[must_use]
+    pub fn assign_k_best(&self, first: Uuid, second: Uuid, k: usize) -> Vec<Assignment<Id>> {
+        let (first, second, edges) = self.assignment_problem(first, second);
+        assignment::k_best(&first, &second, &edges, self.config.chi2 / 2.0, k)
+    }
+
+    /// The observations of two contexts, and the squared distances of the compatible pairs
+    /// between them.
+    fn assignment_problem(
+        &self,
+        first: Uuid,
+        second: Uuid,
+    ) -> (Vec<Id>, Vec<Id>, assignment::Edges<Id>) {
+        let in_context = |context| {
+            self.spatial_index
+                .iter()
+                .filter(move |(_, observation)| observation.context() == Some(context))
+                .map(|(id, _)| *id)
+        };
+        let mut first_ids: Vec<Id> = in_context(first).collect();
+        let second_ids: HashSet<Id> = in_context(second).collect();
+        let order = self.config.seeded_order();
+
+        let mut edges = HashMap::new();
+        for a in &first_ids {
+            let Some(observation_a) = self.spatial_index.get(a) else {
+                continue;
+            };
+            let mut neighbours: Vec<(Id, f64)> = self
+                .compatibility_graph
+                .get(a)
+                .into_iter()
+                .flatten()
+                .filter(|b| second_ids.contains(b))
+                .filter_map(|b| {
+                    let observation_b = self.spatial_index.get(b)?;
+                    Some((
+                        *b,
+                        observation_a.gated_distance_squared(observation_b, &self.config),
+                    ))
+                })
+                .collect();
+            if let Some(order) = order {
+                neighbours.sort_by_cached_key(|(b, _)| order.key(b));
+            }
+            if !neighbours.is_empty() {
+                edges.insert(*a, neighbours);
+            }
+        }
+
+        let mut second_ids: Vec<Id> = second_ids.into_iter().collect();
+        if let Some(order) = order {
+            order.sort(&mut first_ids);
+            order.sort(&mut second_ids);
+        }
+        (first_ids, second_ids, edges)
+    }
+}
+
+#[cfg(feature = "geojson")]
+impl<Id, G> CliqueIndex<Id, G>
+where
+    Id: Eq + std::hash::Hash + Copy + std::fmt::Debug + std::fmt::Display,
+    G: Adjacency<Id>,
+{
+    /// Export the index as a GeoJSON `FeatureCollection`.
+    ///
+    /// The collection contains, for each observation, a `Point` feature (with `"kind":
+    /// "observation"`) and a `Polygon` approximating its confidence ellipse at the index's chi-squared
+    /// threshold (`"kind": "ellipse"`). Each clique is represented by a `Point` feature at its fused
+    /// estimate (`"kind": "clique"`), listing its `"members"`. IDs are written as strings.
+    /// Uncertainties are written using the property convention described by
+    /// [`Observation::from_geojson`], so observations can be read back.
+    ///
+    /// Coordinates are written as-is, so are only meaningful to GeoJSON consumers if the
+    /// observations are in longitude/latitude (or the consumer uses the same projected system).
+    ///
+    /// Requires the `geojson` feature.
+    #[must_use]
+    pub fn to_geojson(&self) -> String {
+        crate::geojson::feature_collection(
+            self.spatial_index.iter(),
+            &self.fused_estimates(),
+            self.config.chi2,
+        )
+        .to_string()
+    }
+}
+
+impl CliqueIndex<Uuid> {
+    /// Inserts a new observation with a newly generated, random (v4) UUID, and returns the UUID.
+    ///
+    /// See [`Self::insert`].
+    pub fn insert_new(&mut self, observation: Observation) -> Uuid {
+        let observation = Unique::new_v4(observation);
+        let id = observation.id;
+        self.insert(observation);
+        id
+    }
+}
+
+/// The bounds on the IDs (and compatibility graph) of an index which are needed to process
+/// observations in parallel.
+///
+/// With the `rayon` feature, this is `Send + Sync`, so that batches can be shared between threads
+/// (see [`CliqueIndex::insert_batch`] and [`CliqueIndex::classify`]). Without it, every type
+/// implements it.
+#[cfg(feature = "rayon")]
+pub trait MaybeSendSync: Send + Sync {}
+
+#[cfg(feature = "rayon")]
+impl<T> MaybeSendSync for T where T: Send + Sync {}
+
+/// The bounds on the IDs (and compatibility graph) of an index which are needed to process
+/// observations in parallel.
+///
+/// With the `rayon` feature, this is `Send + Sync`, so that batches can be shared between threads
+/// (see [`CliqueIndex::insert_batch`] and [`CliqueIndex::classify`]). Without it, every type
+/// implements it.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSendSync {}
+
+#[cfg(not(feature = "rayon"))]
+impl<T> MaybeSendSync for T {}
+
+/// A [`CliqueIndex`] keyed by small integer IDs, which stores its compatibility graph in a vector
+/// indexed by the ID, rather than a hash map.
+///
+/// This is a convenient choice for simulations and tests, where IDs can be assigned sequentially
+/// and results are reproducible from run to run. See [`Self::from_sequential`].
+///
+/// The graph takes space proportional to the largest ID in it (see [`DenseAdjacency`]), so IDs
+/// should be allocated densely from zero. For arbitrary integer IDs, use a `CliqueIndex<u64>`.
+pub type IntegerCliqueIndex = CliqueIndex<u64, DenseAdjacency>;
+
+impl CliqueIndex<u64, DenseAdjacency> {
+    /// Construct a new, empty index with the given configuration.
+    ///
+    /// Observations can then be added with [`Self::insert`] or [`Self::insert_batch`], and should
+    /// be given small integer IDs. See [`IntegerCliqueIndex`].
+    #[must_use]
+    pub fn dense(config: Config) -> Self {
+        Self::empty(config)
+    }
+
+    /// Construct a new index populated with observations, which are assigned the sequential IDs
+    /// `0, 1, 2, ...` in order.
+    ///
+    /// The cliques are the same as those found by assigning the IDs by hand and calling
+    /// [`CliqueIndex::from_observations`].
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, IntegerCliqueIndex, Observation};
+    ///
+    /// let observation = |x| {
+    ///     Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build()
+    /// };
+    ///
+    /// let index = IntegerCliqueIndex::from_sequential(
+    ///     [observation(0.0), observation(0.5), observation(10.0)],
+    ///     CHI2_2D_CONFIDENCE_95,
+    /// );
+    ///
+    /// assert!(index.same_component(&0, &1));
+    /// assert!(!index.same_component(&1, &2));
+    /// ```
+    #[must_use]
+    pub fn from_sequential(observations: impl IntoIterator<Item = Observation>, chi2: f64) -> Self {
+        Self::from_sequential_with_config(observations, Config::new(chi2))
+    }
+
+    /// Construct a new index with the given configuration, populated with observations which are
+    /// assigned the sequential IDs `0, 1, 2, ...` in order.
+    ///
+    /// See [`Self::from_sequential`].
+    #[must_use]
+    pub fn from_sequential_with_config(
+        observations: impl IntoIterator<Item = Observation>,
+        config: Config,
+    ) -> Self {
+        let observations = (0..)
+            .zip(observations)
+            .map(|(id, data)| Unique { data, id })
+            .collect();
+        let Ok(index) = Self::build(observations, config, &mut Monitor::default()) else {
+            unreachable!("a build without a cancellation token can't be cancelled")
+        };
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    };
+
+    use uuid::Uuid;
+
+    use crate::{
+        Adjacency, BoundingBox, CHI2_2D_CONFIDENCE_95, CancellationToken, CliqueIndex,
+        Compatibility, Config, CovarianceMatrix, DensityFallback, DuplicatePolicy,
+        EnumerationStrategy, Error, IntegerCliqueIndex, MotionModel, Observation, Unique,
+    };
+
+    /// Overlapping observations scattered at random over a small area, in three contexts.
+    fn scattered_observations(
+        rng: &mut impl rand::Rng,
+        count: usize,
+    ) -> Vec<Unique<Observation, usize>> {
+        use rand::RngExt;
+
+        let contexts = [Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3)];
+        (0..count)
+            .map(|id| Unique {
+                data: Observation::builder(
+                    rng.random_range(0.0..20.0),
+                    rng.random_range(0.0..20.0),
+                )
+                .circular_95_confidence_error(rng.random_range(1.0..4.0))
+                .unwrap()
+                .context(contexts[id % contexts.len()])
+                .build(),
+                id,
+            })
+            .collect()
+    }
+
+    /// An observation on the x axis, with a 95% confidence radius of 1.
+    fn observation(id: usize, x: f64) -> Unique<Observation, usize> {
+        Unique {
+            data: Observation::builder(x, 0.0)
+                .circular_95_confidence_error(1.0)
+                .unwrap()
+                .build(),
+            id,
+        }
+    }
+
+    #[test]
+    fn simple_cluster() {
+        let observations = vec![
+            Unique {
+                data: Observation::builder(0.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 0,
+            },
+            Unique {
+                data: Observation::builder(0.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 1,
+            },
+            Unique {
+                data: Observation::builder(0.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 2,
+            },
+        ];
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+
+        let expected = HashMap::from([
+            (0, HashSet::from([1, 2])),
+            (1, HashSet::from([0, 2])),
+            (2, HashSet::from([0, 1])),
+        ]);
+        assert_eq!(index.compatibility_graph(), &expected);
+    }
+
+    #[test]
+    fn no_overlap() {
+        let observations = vec![
+            Unique {
+                data: Observation::builder(10.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 0,
+            },
+            Unique {
+                data: Observation::builder(0.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 1,
+            },
+            Unique {
+                data: Observation::builder(-10.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 2,
+            },
+        ];
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+
+        let expected = HashMap::from([]);
+        assert_eq!(index.compatibility_graph(), &expected);
+    }
+
+    #[test]
+    fn insert_equivalence() {
+        let observations = vec![
+            Unique {
+                data: Observation::builder(10.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 0,
+            },
+            Unique {
+                data: Observation::builder(0.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 1,
+            },
+            Unique {
+                data: Observation::builder(-10.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 2,
+            },
+            Unique {
+                data: Observation::builder(10.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 3,
+            },
+            Unique {
+                data: Observation::builder(10.0, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id: 4,
+            },
+        ];
+
+        let index1 = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+
+        let mut index2 = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+
+        for obs in observations {
+            index2.insert(obs);
+        }
+
+        assert_eq!(index1.cliques, index2.cliques);
+        assert_eq!(index1.compatibility_graph, index2.compatibility_graph);
+    }
+
+    #[test]
+    fn compatibility_edges_are_listed_once_with_their_distances() {
+        let observations: Vec<_> = [(0, 0.0), (1, 0.5), (2, 1.0), (3, 50.0)]
+            .into_iter()
+            .map(|(id, x)| Unique {
+                data: Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect();
+        let index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+
+        let mut edges: Vec<_> = index
+            .compatibility_edges()
+            .into_iter()
+            .map(|(a, b, d2)| {
+                let expected = observations[a]
+                    .data
+                    .mahalanobis_distance_squared(&observations[b].data);
+                assert!((d2 - expected).abs() < 1e-12);
+                (a.min(b), a.max(b))
+            })
+            .collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(0, 1), (0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn fused_estimates_follow_cliques() {
+        let observations: Vec<_> = [(0, -0.1), (1, 0.1), (2, 100.0)]
+            .into_iter()
+            .map(|(id, x)| Unique {
+                data: Observation::builder(x, 1.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect();
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+
+        let estimates = index.fused_estimates();
+
+        assert_eq!(estimates.len(), 1);
+        assert_eq!(estimates[0].members, HashSet::from([0, 1]));
+        approx::assert_relative_eq!(estimates[0].x, 0.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(estimates[0].y, 1.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(
+            estimates[0].covariance.xx(),
+            0.5 / CHI2_2D_CONFIDENCE_95,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn fused_estimates_carry_sensors() {
+        let sensor = Uuid::from_u128(7);
+        let observations: Vec<_> = [(0, Some(sensor)), (1, Some(sensor)), (2, None)]
+            .into_iter()
+            .map(|(id, sensor)| {
+                let builder = Observation::builder(0.0, 0.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap();
+                Unique {
+                    data: match sensor {
+                        Some(sensor) => builder.sensor(sensor),
+                        None => builder,
+                    }
+                    .build(),
+                    id,
+                }
+            })
+            .collect();
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+
+        let estimates = index.fused_estimates();
+
+        assert_eq!(estimates.len(), 1);
+        assert_eq!(estimates[0].members, HashSet::from([0, 1, 2]));
+        assert_eq!(estimates[0].sensors, HashSet::from([sensor]));
+    }
+
+    #[test]
+    fn clique_centroids_follow_cliques() {
+        let observations: Vec<_> = [(0, -0.1), (1, 0.1), (2, 100.0)]
+            .into_iter()
+            .map(|(id, x)| Unique {
+                data: Observation::builder(x, 1.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect();
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+
+        let centroids = index.clique_centroids();
+
+        assert_eq!(centroids.len(), 1);
+        assert_eq!(centroids[0].members, HashSet::from([0, 1]));
+        assert_eq!(centroids[0].position(), (0.0, 1.0));
+        let (major, minor, _) = centroids[0].axes();
+        approx::assert_relative_eq!(major, 0.1, epsilon = 1e-12);
+        approx::assert_relative_eq!(minor, 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn cliques_can_be_filtered_by_their_members() {
+        let observations: Vec<_> = [(0, 0.0, "car"), (1, 0.1, "truck"), (2, 10.0, "car")]
+            .into_iter()
+            .chain([(3, 10.1, "car"), (4, 20.0, "car")])
+            .map(|(id, x, label)| Unique {
+                data: Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .label(label)
+                    .build(),
+                id,
+            })
+            .collect();
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+        assert_eq!(index.cliques().len(), 2);
+
+        let trucks: Vec<_> = index.cliques_with_label("truck").collect();
+        assert_eq!(trucks, vec![&HashSet::from([0, 1])]);
+        assert_eq!(index.cliques_with_label("boat").count(), 0);
+
+        let unanimous: Vec<_> = index
+            .cliques_matching(|members| members.values().all(|member| member.has_label("car")))
+            .collect();
+        assert_eq!(unanimous, vec![&HashSet::from([2, 3])]);
+    }
+
+    #[test]
+    fn cached_results_follow_the_cliques() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(17), 60);
+        let (initial, later) = observations.split_at(40);
+        let mut index = CliqueIndex::from_observations(initial.to_vec(), CHI2_2D_CONFIDENCE_95);
+
+        let check = |index: &CliqueIndex<usize>| {
+            let results = index.results();
+            let estimates = index.fused_estimates();
+            assert_eq!(results.len(), index.cliques().len());
+            for ((result, estimate), id) in results.iter().zip(estimates).zip(index.clique_ids()) {
+                assert_eq!(result.id, *id);
+                assert_eq!(result.members, estimate.members);
+                // the members are summed in an arbitrary order
+                approx::assert_relative_eq!(result.fused_position.0, estimate.x, epsilon = 1e-9);
+                approx::assert_relative_eq!(result.fused_position.1, estimate.y, epsilon = 1e-9);
+                approx::assert_relative_eq!(
+                    result.fused_covariance.xx(),
+                    estimate.covariance.xx(),
+                    epsilon = 1e-9
+                );
+            }
+        };
+
+        check(&index);
+        index.insert_batch(later.iter().cloned());
+        check(&index);
+        for id in 0..10 {
+            index.remove(&id);
+        }
+        check(&index);
+        index.recalibrate(|observation| {
+            CovarianceMatrix::from_matrix(
+                nalgebra::Matrix2::from(observation.error_covariance()) * 1.5,
+            )
+        });
+        check(&index);
+    }
+
+    #[test]
+    fn flat_cliques_follow_the_cliques() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(23), 60);
+        let (initial, later) = observations.split_at(40);
+        let mut index = CliqueIndex::from_observations(initial.to_vec(), CHI2_2D_CONFIDENCE_95);
+
+        let check = |index: &CliqueIndex<usize>| {
+            let flat: Vec<HashSet<usize>> = index
+                .flat_cliques()
+                .iter()
+                .map(|clique| clique.iter().copied().collect())
+                .collect();
+            assert_eq!(index.cliques(), &crate::CliqueSet(flat));
+        };
+
+        check(&index);
+        index.insert_batch(later.iter().cloned());
+        check(&index);
+        for id in 0..10 {
+            index.remove(&id);
+            check(&index);
+        }
+        for observation in &later[..10] {
+            let mut observation = observation.clone();
+            observation.data = Observation::builder(observation.data.x() + 0.5, 0.0)
+                .error(observation.data.error_covariance())
+                .build();
+            index.update(observation).unwrap();
+            check(&index);
+        }
+        index.set_chi2(CHI2_2D_CONFIDENCE_95 / 2.0).unwrap();
+        check(&index);
+    }
+
+    #[test]
+    fn graph_export_matches_the_compatibility_graph() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(29), 50);
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+        let graph = index.graph_export();
+
+        assert_eq!(graph.node_count(), index.total_observations());
+        assert_eq!(graph.node_features.len(), graph.node_count());
+        assert_eq!(graph.edge_count(), 2 * index.compatibility_edges().len());
+
+        let edges: HashSet<(usize, usize)> = graph
+            .sources
+            .iter()
+            .zip(&graph.targets)
+            .map(|(source, target)| (graph.ids[*source], graph.ids[*target]))
+            .collect();
+        for (a, b, _) in index.compatibility_edges() {
+            assert!(edges.contains(&(a, b)) && edges.contains(&(b, a)));
+        }
+        for (id, features) in graph.ids.iter().zip(&graph.node_features) {
+            let degree = index.compatibility_graph().get(id).map_or(0, HashSet::len);
+            let degree = f64::from(u32::try_from(degree).unwrap());
+            approx::assert_relative_eq!(features[5], degree);
+            approx::assert_relative_eq!(features[0], index.get(id).unwrap().x());
+        }
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn geojson_export() {
+        let observations = [(0, 0.0), (1, 0.3), (2, 50.0)]
+            .into_iter()
+            .map(|(id, x)| Unique {
+                data: Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(0.3)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect();
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+
+        let geojson: serde_json::Value = serde_json::from_str(&index.to_geojson()).unwrap();
+        assert_eq!(geojson["type"], "FeatureCollection");
+
+        let features = geojson["features"].as_array().unwrap();
+        let count = |kind: &str| {
+            features
+                .iter()
+                .filter(|feature| feature["properties"]["kind"] == kind)
+                .count()
+        };
+        assert_eq!(count("observation"), 3);
+        assert_eq!(count("ellipse"), 3);
+        assert_eq!(count("clique"), 1);
+
+        let clique = features
+            .iter()
+            .find(|feature| feature["properties"]["kind"] == "clique")
+            .unwrap();
+        assert_eq!(
+            clique["properties"]["members"],
+            serde_json::json!(["0", "1"])
+        );
+
+        let ellipse = features
+            .iter()
+            .find(|feature| feature["properties"]["kind"] == "ellipse")
+            .unwrap();
+        let ring = ellipse["geometry"]["coordinates"][0].as_array().unwrap();
+        assert_eq!(ring.first(), ring.last());
+    }
+
+    #[test]
+    fn cliques_in_region_include_those_straddling_the_bounds() {
+        let observation = |x: f64| {
+            Observation::builder(x, 0.0)
+                .circular_95_confidence_error(1.0)
+                .unwrap()
+                .build()
+        };
+        // a clique inside the bounds, one straddling them, and one outside them
+        let index = CliqueIndex::from_sequential(
+            [0.0, 0.5, 9.8, 10.3, 20.0, 20.5, 5.0].map(observation),
+            CHI2_2D_CONFIDENCE_95,
+        );
+        assert_eq!(index.cliques().len(), 3);
+
+        let region = index.cliques_in_region(BoundingBox::from_corners((-1.0, -1.0), (10.0, 1.0)));
+        assert_eq!(region.len(), 2);
+        assert!(region.iter().any(|clique| *clique == HashSet::from([0, 1])));
+        assert!(region.iter().any(|clique| *clique == HashSet::from([2, 3])));
+
+        // an isolated observation doesn't belong to any clique
+        let isolated = BoundingBox::from_corners((4.0, -1.0), (6.0, 1.0));
+        assert!(index.cliques_in_region(isolated).is_empty());
+    }
+
+    #[test]
+    fn lookup_and_counts() {
+        let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        let observation = Observation::builder(0.0, 0.0)
+            .circular_95_confidence_error(1.0)
+            .unwrap()
+            .build();
+        index.insert(Unique {
+            data: observation.clone(),
+            id: 0,
+        });
+
+        assert!(index.contains(&0));
+        assert!(!index.contains(&1));
+        assert_eq!(index.get(&0), Some(&observation));
+        assert_eq!(index.get(&1), None);
+
+        // An isolated observation is counted in the total, but not in the compatibility graph
+        assert_eq!(index.total_observations(), 1);
+        assert_eq!(index.len(), 0);
+    }
+
+    #[test]
+    fn components_track_insertion_and_removal() {
+        let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        let insert = |index: &mut CliqueIndex<u32>, id, x| {
+            index.insert(Unique {
+                data: Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .build(),
+                id,
+            });
+        };
+
+        // A chain 0 - 1 - 2, and an isolated observation
+        insert(&mut index, 0, 0.0);
+        insert(&mut index, 1, 1.0);
+        insert(&mut index, 3, 50.0);
+        assert_eq!(index.components(), vec![HashSet::from([0, 1])]);
+        assert!(!index.same_component(&0, &3));
+
+        // Insertion after the components have been built extends them in place
+        insert(&mut index, 2, 2.0);
+        assert!(index.same_component(&0, &2));
+
+        // Removing the middle of the chain splits the component
+        index.remove(&1);
+        assert!(!index.same_component(&0, &2));
+        assert!(index.components().is_empty());
+    }
+
+    #[test]
+    fn enumeration_strategies_agree() {
+        // A row of overlapping observations, so that each is compatible with its near neighbours
+        let observations: Vec<_> = (0..20_u32)
+            .map(|id| Unique {
+                data: Observation::builder(f64::from(id) * 0.4, 0.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect();
+
+        let sorted_cliques = |strategy| {
+            let config = Config::new(CHI2_2D_CONFIDENCE_95).enumeration_strategy(strategy);
+            let index = CliqueIndex::from_observations_with_config(observations.clone(), config);
+            let mut cliques: Vec<Vec<u32>> = index
+                .cliques()
+                .iter()
+                .map(|clique| {
+                    let mut clique: Vec<_> = clique.iter().copied().collect();
+                    clique.sort_unstable();
+                    clique
+                })
+                .collect();
+            cliques.sort_unstable();
+            cliques
+        };
+
+        let expected = sorted_cliques(EnumerationStrategy::MaxDegreePivot);
+        assert!(expected.len() > 1);
+        for strategy in [
+            EnumerationStrategy::RandomPivot,
+            EnumerationStrategy::DegeneracyOrdering,
+            EnumerationStrategy::NoPivot,
+        ] {
+            assert_eq!(sorted_cliques(strategy), expected, "{strategy:?}");
+        }
+    }
+
+    #[test]
+    fn altitude_gates_compatibility() {
+        let observation = |altitude: Option<f64>| {
+            let builder = Observation::builder(0.0, 0.0)
+                .circular_95_confidence_error(10.0)
+                .unwrap();
+            match altitude {
+                Some(z) => builder.altitude(z, 25.0).unwrap(),
+                None => builder,
+            }
+            .build()
+        };
+        // a surface contact, an aircraft overhead, and a contact with unknown altitude
+        let observations = vec![
+            Unique {
+                id: 0,
+                data: observation(Some(0.0)),
+            },
+            Unique {
+                id: 1,
+                data: observation(Some(3000.0)),
+            },
+            Unique {
+                id: 2,
+                data: observation(None),
+            },
+        ];
+
+        let index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        let cliques: HashSet<_> = index
+            .cliques()
+            .iter()
+            .map(|clique| {
+                let mut clique: Vec<_> = clique.iter().copied().collect();
+                clique.sort_unstable();
+                clique
+            })
+            .collect();
+        assert_eq!(cliques, HashSet::from([vec![0, 2], vec![1, 2]]));
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).altitude_gate(f64::INFINITY);
+        let index = CliqueIndex::from_observations_with_config(observations, config);
+        assert_eq!(index.cliques(), &[HashSet::from([0, 1, 2])]);
+    }
+
+    #[test]
+    fn motion_model_joins_moving_targets() {
+        let error = CovarianceMatrix::new(0.01, 0.01, 0.0).unwrap();
+        let observations: Vec<_> = [(0, 0.0, 0.0), (1, 1.0, 0.1)]
+            .into_iter()
+            .map(|(id, x, t)| Unique {
+                data: Observation::builder(x, 0.0)
+                    .error(error)
+                    .timestamp(t)
+                    .velocity(10.0, 0.0, error)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect();
+
+        let index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        assert!(index.cliques().is_empty());
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95)
+            .motion_model(MotionModel::ConstantVelocity { process_noise: 0.1 });
+        let index = CliqueIndex::from_observations_with_config(observations, config);
+        assert_eq!(index.cliques(), &[HashSet::from([0, 1])]);
+    }
+
+    #[test]
+    fn ageing_loosens_the_gate_between_old_and_new_observations() {
+        let error = CovarianceMatrix::new(0.01, 0.01, 0.0).unwrap();
+        let observations: Vec<_> = [(0, 0.0, 0.0), (1, 1.0, 10.0)]
+            .into_iter()
+            .map(|(id, x, t)| Unique {
+                data: Observation::builder(x, 0.0)
+                    .error(error)
+                    .timestamp(t)
+                    .build(),
+                id,
+            })
+            .collect();
+
+        let index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        assert!(index.cliques().is_empty());
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95)
+            .motion_model(MotionModel::Ageing { process_noise: 0.1 });
+        let index = CliqueIndex::from_observations_with_config(observations, config);
+        assert_eq!(index.cliques(), &[HashSet::from([0, 1])]);
+
+        // the stored errors are unchanged
+        let stored = index.get(&0).unwrap().error_covariance();
+        assert_eq!(stored, error);
+    }
+
+    #[test]
+    fn registration_error_is_added_once_per_pair() {
+        let error = CovarianceMatrix::new(0.01, 0.01, 0.0).unwrap();
+        let observations: Vec<_> = [(0, 0.0), (1, 3.0)]
+            .into_iter()
+            .map(|(id, x)| Unique {
+                data: Observation::builder(x, 0.0).error(error).build(),
+                id,
+            })
+            .collect();
+
+        let index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        assert!(index.cliques().is_empty());
+
+        // d² = 9 / (0.01 + 0.01 + 2.0)
+        let registration = CovarianceMatrix::new(2.0, 2.0, 0.0).unwrap();
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).registration_error(registration);
+        let index = CliqueIndex::from_observations_with_config(observations, config);
+        assert_eq!(index.cliques(), &[HashSet::from([0, 1])]);
+        approx::assert_relative_eq!(index.compatibility_edges()[0].2, 9.0 / 2.02);
+        assert_eq!(index.get(&0).unwrap().error_covariance(), error);
+    }
+
+    #[test]
+    fn singular_covariance_follows_the_policy() {
+        let exact = |id, x| Unique {
+            data: Observation::builder(x, 0.0)
+                .error(crate::CovarianceMatrix::new(0.0, 0.0, 0.0).unwrap())
+                .build(),
+            id,
+        };
+        // a degenerate error along the diagonal, offset perpendicular to it, where the
+        // pseudo-inverse ignores the offset
+        let line = |id, offset| Unique {
+            data: Observation::builder(offset, -offset)
+                .error(crate::CovarianceMatrix::new(1.0, 1.0, 1.0).unwrap())
+                .build(),
+            id,
+        };
+        let config = Config::new(CHI2_2D_CONFIDENCE_95);
+
+        let index = CliqueIndex::from_observations_with_config(
+            vec![exact(0, 0.0), exact(1, 0.0)],
+            config.clone(),
+        );
+        assert!(index.cliques().is_empty());
+        let index = CliqueIndex::from_observations_with_config(
+            vec![exact(0, 0.0), line(1, 0.5)],
+            config.clone(),
+        );
+        assert_eq!(index.cliques().len(), 1);
+
+        let exact_config = config
+            .clone()
+            .singular_covariance(crate::SingularCovariance::Exact { epsilon: 1e-6 });
+        let index = CliqueIndex::from_observations_with_config(
+            vec![exact(0, 0.0), exact(1, 0.001), exact(2, 1.0)],
+            exact_config.clone(),
+        );
+        assert_eq!(index.cliques().len(), 1);
+        assert_eq!(
+            index.cliques().iter().next().unwrap(),
+            &HashSet::from([0, 1])
+        );
+        let index = CliqueIndex::from_observations_with_config(
+            vec![exact(0, 0.0), line(1, 0.5)],
+            exact_config,
+        );
+        assert!(index.cliques().is_empty());
+
+        let mut index =
+            CliqueIndex::with_config(config.singular_covariance(crate::SingularCovariance::Reject));
+        assert!(matches!(
+            index.try_insert(exact(0, 0.0)),
+            Err(Error::SingularCovariance)
+        ));
+        assert!(matches!(
+            index.try_insert(line(1, 0.0)),
+            Err(Error::SingularCovariance)
+        ));
+        assert_eq!(index.total_observations(), 0);
+
+        // anchors are known exactly, so are accepted
+        let anchor = Observation::builder(0.0, 0.0)
+            .error(crate::CovarianceMatrix::new(0.0, 0.0, 0.0).unwrap())
+            .anchor()
+            .build();
+        assert!(
+            index
+                .try_insert(Unique {
+                    data: anchor,
+                    id: 2
+                })
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn directional_gate_separates_along_and_cross_track_thresholds() {
+        use crate::{CHI2_2D_CONFIDENCE_99, TrackDirection};
+
+        let observation = |id, (x, y), velocity: Option<(f64, f64)>| {
+            let builder = Observation::builder(x, y)
+                .circular_95_confidence_error(1.0)
+                .unwrap();
+            let builder = match velocity {
+                Some((vx, vy)) => builder
+                    .velocity(vx, vy, CovarianceMatrix::identity())
+                    .unwrap(),
+                None => builder,
+            };
+            Unique {
+                data: builder.build(),
+                id,
+            }
+        };
+        let pairs = |velocity| {
+            vec![
+                // 1.6 apart along x, beyond the isotropic threshold
+                observation(0, (0.0, 0.0), velocity),
+                observation(1, (1.6, 0.0), velocity),
+                // 0.9 apart along y, within it
+                observation(2, (100.0, 0.0), velocity),
+                observation(3, (100.0, 0.9), velocity),
+            ]
+        };
+        let cliques = |observations, direction| {
+            let config = Config::new(CHI2_2D_CONFIDENCE_95).directional_gate(
+                CHI2_2D_CONFIDENCE_99,
+                2.0,
+                direction,
+            );
+            let index = CliqueIndex::from_observations_with_config(observations, config);
+            let mut cliques: Vec<Vec<usize>> = index
+                .cliques()
+                .iter()
+                .map(|clique| {
+                    let mut members: Vec<usize> = clique.iter().copied().collect();
+                    members.sort_unstable();
+                    members
+                })
+                .collect();
+            cliques.sort();
+            cliques
+        };
+
+        let isotropic = CliqueIndex::from_observations(pairs(None), CHI2_2D_CONFIDENCE_95);
+        assert_eq!(isotropic.cliques().len(), 1);
+        assert_eq!(
+            isotropic.cliques().iter().next().unwrap(),
+            &HashSet::from([2, 3])
+        );
+
+        let along_x = TrackDirection::Fixed { x: 2.0, y: 0.0 };
+        assert_eq!(cliques(pairs(None), along_x), [vec![0, 1]]);
+
+        // moving along y, the looser threshold applies to the other pair
+        let along_y = cliques(pairs(Some((0.0, 3.0))), TrackDirection::Velocity);
+        assert_eq!(along_y, [vec![2, 3]]);
+        let stationary = cliques(pairs(None), TrackDirection::Velocity);
+        assert_eq!(stationary, [vec![2, 3]]);
+    }
+
+    #[test]
+    fn directional_gate_is_independent_of_the_candidate_source() {
+        use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+        use crate::{
+            CHI2_2D_CONFIDENCE_90, CHI2_2D_CONFIDENCE_99, CandidateSource, TrackDirection,
+        };
+
+        /// Every observation is a candidate.
+        struct Exhaustive(HashSet<usize>);
+
+        impl CandidateSource<usize> for Exhaustive {
+            fn insert(&mut self, observation: &Unique<Arc<Observation>, usize>) {
+                self.0.insert(observation.id);
+            }
+
+            fn remove(&mut self, observation: &Unique<Arc<Observation>, usize>) {
+                self.0.remove(&observation.id);
+            }
+
+            fn locate(&self, _: &BoundingBox) -> Vec<usize> {
+                self.0.iter().copied().collect()
+            }
+        }
+
+        // elongated errors, which are not aligned with the track
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut observations: Vec<_> = (0..60)
+            .map(|id| {
+                let (major, minor): (f64, f64) =
+                    (rng.random_range(5.0..50.0), rng.random_range(0.01..1.0));
+                let (sin, cos) = rng.random_range(0.0..std::f64::consts::PI).sin_cos();
+                let error = CovarianceMatrix::new(
+                    major.mul_add(cos * cos, minor * sin * sin),
+                    major.mul_add(sin * sin, minor * cos * cos),
+                    (major - minor) * sin * cos,
+                )
+                .unwrap();
+                Unique {
+                    data: Observation::builder(
+                        rng.random_range(0.0..40.0),
+                        rng.random_range(0.0..40.0),
+                    )
+                    .error(error)
+                    .build(),
+                    id,
+                }
+            })
+            .collect();
+        // a pair far apart under their combined error, which is correlated in the frame of the track
+        let error = CovarianceMatrix::new(0.01, 50.0, 0.0).unwrap();
+        for (id, (x, y)) in [(100, (0.0, 0.0)), (101, (2.57, 14.97))] {
+            observations.push(Unique {
+                data: Observation::builder(x, y).error(error).build(),
+                id,
+            });
+        }
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).directional_gate(
+            CHI2_2D_CONFIDENCE_99,
+            CHI2_2D_CONFIDENCE_90,
+            TrackDirection::Fixed { x: 1.0, y: 1.0 },
+        );
+        let edges = |index: &CliqueIndex<usize>| {
+            let mut edges: Vec<(usize, usize)> = index
+                .compatibility_edges()
+                .into_iter()
+                .map(|(a, b, _)| (a.min(b), a.max(b)))
+                .collect();
+            edges.sort_unstable();
+            edges
+        };
+        let expected =
+            CliqueIndex::from_observations_with_config(observations.clone(), config.clone());
+        let mut index = CliqueIndex::with_candidate_source(config, Exhaustive(HashSet::new()));
+        for observation in observations {
+            index.insert(observation);
+        }
+        assert!(!edges(&index).is_empty());
+        assert_eq!(edges(&index), edges(&expected));
+        assert_eq!(index.state_hash(), expected.state_hash());
+        assert_eq!(
+            index.compatibility(&100, &101),
+            Some(Compatibility::Incompatible)
+        );
+    }
+
+    #[test]
+    fn one_dimensional_observations_are_gated_along_the_track() {
+        let along_track = |id, chainage| Unique {
+            data: Observation::along_track(chainage, 1.0).unwrap().build(),
+            id,
+        };
+        let config = Config::new(crate::CHI2_1D_CONFIDENCE_95)
+            .one_dimensional()
+            .singular_covariance(crate::SingularCovariance::Reject);
+
+        // 1.9 apart is within the 1-dof threshold (3.61 / 2 < 3.841), 2.9 apart is not
+        let mut index = CliqueIndex::with_config(config);
+        for (id, chainage) in [(0, 0.0), (1, 1.9), (2, 4.8)] {
+            index.try_insert(along_track(id, chainage)).unwrap();
+        }
+        assert_eq!(index.cliques().len(), 1);
+        assert_eq!(
+            index.cliques().iter().next().unwrap(),
+            &HashSet::from([0, 1])
+        );
+
+        let (chainage, offset) = index.fused_estimates()[0].position();
+        assert!((chainage - 0.95).abs() < 1e-9);
+        assert!(offset.abs() < 1e-9);
+
+        // a zero variance along the track is still singular
+        assert!(matches!(
+            index.try_insert(Unique {
+                data: Observation::along_track(10.0, 0.0).unwrap().build(),
+                id: 3
+            }),
+            Err(Error::SingularCovariance)
+        ));
+    }
+
+    #[test]
+    fn borderline_pairs_straddle_the_threshold() {
+        // unit variances, so d² = x² / 2
+        let observations: Vec<_> = [(0, 0.0), (1, 3.0), (2, 3.6), (3, 10.0)]
+            .into_iter()
+            .map(|(id, x)| Unique {
+                data: Observation::builder(x, 0.0)
+                    .error(CovarianceMatrix::identity())
+                    .build(),
+                id,
+            })
+            .collect();
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).borderline_band(0.25);
+        let index = CliqueIndex::from_observations_with_config(observations, config);
+
+        let mut pairs: Vec<_> = index
+            .borderline_pairs()
+            .into_iter()
+            .map(|(a, b, _)| (a.min(b), a.max(b)))
+            .collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 1), (0, 2)]);
+
+        assert_eq!(index.compatibility(&1, &2), Some(Compatibility::Compatible));
+        assert_eq!(index.compatibility(&0, &1), Some(Compatibility::Borderline));
+        assert_eq!(index.compatibility(&0, &2), Some(Compatibility::Borderline));
+        assert_eq!(
+            index.compatibility(&0, &3),
+            Some(Compatibility::Incompatible)
+        );
+        assert_eq!(index.compatibility(&0, &4), None);
+        // (0, 1) is just inside the threshold, and (0, 2) just outside
+        assert!(index.compatibility_graph()[&0].contains(&1));
+        assert!(!index.compatibility_graph()[&0].contains(&2));
+    }
+
+    #[test]
+    fn borderline_pairs_follow_the_directional_gate() {
+        use crate::{CHI2_2D_CONFIDENCE_90, TrackDirection};
+
+        // offset across an x-directed track, where the gate is tighter than chi2
+        let observations: Vec<_> = [(0, 0.0), (1, 2.9), (2, 3.2), (3, 10.0), (4, 10.001)]
+            .into_iter()
+            .map(|(id, y)| {
+                let builder = Observation::builder(0.0, y).error(CovarianceMatrix::identity());
+                let data = match id {
+                    3 | 4 => builder.context(Uuid::from_u128(id as u128)).build(),
+                    _ => builder.build(),
+                };
+                Unique { data, id }
+            })
+            .collect();
+        let config = Config::new(CHI2_2D_CONFIDENCE_95)
+            .borderline_band(0.25)
+            .directional_gate(
+                CHI2_2D_CONFIDENCE_95,
+                CHI2_2D_CONFIDENCE_90,
+                TrackDirection::Fixed { x: 1.0, y: 0.0 },
+            );
+        let index = CliqueIndex::from_observations_with_config(observations.clone(), config);
+        let distance = |a: usize, b: usize| {
+            observations[a]
+                .data
+                .gated_distance_squared(&observations[b].data, index.config())
+        };
+
+        let mut pairs: Vec<_> = index
+            .borderline_pairs()
+            .into_iter()
+            .map(|(a, b, d2)| {
+                assert!((d2 - distance(a, b)).abs() < 1e-9);
+                (a.min(b), a.max(b))
+            })
+            .collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 1), (0, 2)]);
+        assert_eq!(index.compatibility(&0, &1), Some(Compatibility::Borderline));
+        assert_eq!(index.compatibility(&0, &2), Some(Compatibility::Borderline));
+        assert!(index.compatibility_graph()[&0].contains(&1));
+        assert!(!index.compatibility_graph()[&0].contains(&2));
+
+        let duplicates = index.cross_context_duplicates(1e-3);
+        assert_eq!(duplicates.len(), 1);
+        let (a, b, d2) = duplicates[0];
+        assert_eq!((a.min(b), a.max(b)), (3, 4));
+        assert!((d2 - distance(3, 4)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn close_pairs_in_the_same_context_are_suspected_duplicates() {
+        let (context, other_context) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let observation = |id, x, context| Unique {
+            data: Observation::builder(x, 0.0)
+                .error(CovarianceMatrix::identity())
+                .context(context)
+                .build(),
+            id,
+        };
+        let observations = vec![
+            observation(0, 0.0, context),
+            observation(1, 0.001, context),
+            observation(2, 0.5, context),
+            observation(3, 0.0005, other_context),
+        ];
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).min_separation(0.01);
+        let index = CliqueIndex::from_observations_with_config(observations.clone(), config);
+
+        let pairs: Vec<_> = index
+            .suspected_duplicates()
+            .into_iter()
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        assert_eq!(pairs, vec![(0, 1)]);
+
+        let unconfigured = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+        assert!(unconfigured.suspected_duplicates().is_empty());
+    }
+
+    #[test]
+    fn cross_context_duplicates_are_near_identical_pairs() {
+        let (first, second) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let observations: Vec<_> = [
+            (0, 0.0, Some(first)),
+            (1, 0.0, Some(second)),
+            (2, 0.0, Some(first)),
+            (3, 0.0, None),
+            (4, 0.5, Some(second)),
+        ]
+        .into_iter()
+        .map(|(id, x, context)| {
+            let builder = Observation::builder(x, 0.0).error(CovarianceMatrix::identity());
+            let data = match context {
+                Some(context) => builder.context(context).build(),
+                None => builder.build(),
+            };
+            Unique { data, id }
+        })
+        .collect();
+        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+
+        let mut pairs: Vec<_> = index
+            .cross_context_duplicates(1e-6)
+            .into_iter()
+            .map(|(a, b, _)| (a.min(b), a.max(b)))
+            .collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn remove_restores_maximal_cliques() {
+        // Three observations along a line; the ends are incompatible with each other, but both
+        // are compatible with the middle.
+        let observations: Vec<_> = [(0, -0.3), (1, 0.0), (2, 0.3)]
+            .into_iter()
+            .map(|(id, x)| Unique {
+                data: Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(0.3)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect();
+        let mut index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        assert_eq!(index.cliques().len(), 2);
+
+        assert_eq!(index.remove(&1), Some(observations[1].data.clone()));
+        assert_eq!(index.remove(&1), None);
+        assert!(index.cliques().is_empty());
+        assert!(index.compatibility_graph().is_empty());
+
+        // A clique of three, reduced to a clique of two
+        let observations: Vec<_> = (0..3)
+            .map(|id| Unique {
+                data: Observation::builder(0.0, 0.0)
+                    .circular_95_confidence_error(3.0)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect();
+        let mut index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+        index.remove(&0);
+        assert_eq!(index.cliques(), &[HashSet::from([1, 2])]);
+    }
+
+    #[test]
+    fn remove_context_removes_every_observation_in_the_context() {
+        // Along a line, adjacent observations are compatible, but those 1.5 apart are not.
+        let (retracted, b, c) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+        let observation = |id, x, context| Unique {
+            data: Observation::builder(x, 0.0)
+                .circular_95_confidence_error(0.8)
+                .unwrap()
+                .context(context)
+                .build(),
+            id,
+        };
+        let observations = vec![
+            observation(0, -1.0, retracted),
+            observation(1, -0.5, b),
+            observation(2, 0.5, c),
+            observation(3, 1.0, retracted),
+        ];
+        let mut index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+        assert_eq!(index.cliques().len(), 3);
+
+        let mut removed: Vec<_> = index
+            .remove_context(retracted)
+            .into_iter()
+            .map(|observation| observation.id)
+            .collect();
+        removed.sort_unstable();
+        assert_eq!(removed, [0, 3]);
+        assert_eq!(index.total_observations(), 2);
+        assert_eq!(index.cliques(), &[HashSet::from([1, 2])]);
+        assert!(index.remove_context(retracted).is_empty());
+    }
+
+    #[test]
+    fn updates_match_a_rebuilt_index() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(17);
+        let observations = scattered_observations(&mut rng, 60);
+        let refined = scattered_observations(&mut rng, 80);
+        let mut index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+
+        for observation in refined[..20].iter().cloned() {
+            let id = observation.id;
+            assert_eq!(index.update(observation).unwrap(), observations[id].data);
+        }
+        // observations 50 to 59 are replaced, and the remainder are new
+        for observation in refined[50..].iter().cloned() {
+            let previous = observations.get(observation.id).map(|o| o.data.clone());
+            assert_eq!(index.upsert(observation).unwrap(), previous);
+        }
+
+        let expected: Vec<_> = refined[..20]
+            .iter()
+            .chain(&observations[20..50])
+            .chain(&refined[50..])
+            .cloned()
+            .collect();
+        let rebuilt = CliqueIndex::from_observations(expected, CHI2_2D_CONFIDENCE_95);
+        assert_eq!(index.state_hash(), rebuilt.state_hash());
+
+        let missing = Unique {
+            data: observations[0].data.clone(),
+            id: 100,
+        };
+        assert!(matches!(index.update(missing), Err(crate::Error::NotFound)));
+        assert_eq!(index.state_hash(), rebuilt.state_hash());
+    }
+
+    #[test]
+    fn updates_are_never_discarded_as_duplicates() {
+        use crate::DuplicatePolicy;
+
+        for policy in [DuplicatePolicy::Skip, DuplicatePolicy::Count] {
+            let config = Config::new(CHI2_2D_CONFIDENCE_95).duplicate_policy(policy);
+            let mut index = CliqueIndex::with_config(config);
+            index.insert(observation(1, 0.0));
+            index.insert(observation(2, 10.0));
+            index.insert(observation(3, 10.0));
+            assert!(!index.contains(&3));
+
+            // a refined version identical to another observation keeps its ID and count
+            index.update(observation(2, 0.0)).unwrap();
+            assert!(index.contains(&2));
+            assert_eq!(index.cliques(), &[HashSet::from([1, 2])]);
+            let count = usize::from(policy == DuplicatePolicy::Count);
+            assert_eq!(index.duplicate_count(&2), count);
+            index.upsert(observation(2, 5.0)).unwrap();
+            assert_eq!(index.duplicate_count(&2), count);
+            assert!(index.cliques().is_empty());
+        }
+    }
+
+    #[test]
+    fn updates_are_validated_as_insertions() {
+        use crate::{CliqueOverflow, SingularCovariance};
+
+        let config =
+            Config::new(CHI2_2D_CONFIDENCE_95).singular_covariance(SingularCovariance::Reject);
+        let mut index = CliqueIndex::with_config(config);
+        index.insert(observation(1, 0.0));
+        let state = index.state_hash();
+        let exact = Unique {
+            data: Observation::builder(0.0, 0.0)
+                .error(CovarianceMatrix::new(0.0, 0.0, 0.0).unwrap())
+                .build(),
+            id: 1,
+        };
+        assert!(matches!(
+            index.update(exact.clone()),
+            Err(Error::SingularCovariance)
+        ));
+        assert!(matches!(
+            index.upsert(exact),
+            Err(Error::SingularCovariance)
+        ));
+        assert_eq!(index.state_hash(), state);
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).clique_capacity(1, CliqueOverflow::Error);
+        let mut index = CliqueIndex::with_config(config);
+        for (id, x) in [(1, 0.0), (2, 0.5), (3, 100.0), (4, 200.0)] {
+            index.try_insert(observation(id, x)).unwrap();
+        }
+        let state = index.state_hash();
+        assert!(matches!(
+            index.update(observation(3, 200.5)),
+            Err(Error::TooManyCliques)
+        ));
+        assert!(matches!(
+            index.upsert(observation(3, 200.5)),
+            Err(Error::TooManyCliques)
+        ));
+        assert_eq!(index.state_hash(), state);
+
+        // joining the existing clique doesn't add one
+        index.update(observation(3, 0.2)).unwrap();
+        assert_eq!(index.cliques(), &[HashSet::from([1, 2, 3])]);
+    }
+
+    #[test]
+    fn update_predictions_match_the_updated_cliques() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(29);
+        let observations = scattered_observations(&mut rng, 60);
+        for config in [
+            Config::new(CHI2_2D_CONFIDENCE_95),
+            Config::new(CHI2_2D_CONFIDENCE_95).mutual_nearest_neighbours(3),
+        ] {
+            let mut index =
+                CliqueIndex::from_observations_with_config(observations.clone(), config);
+            for observation in scattered_observations(&mut rng, 30) {
+                let predicted = index.cliques_after_update(&observation);
+                index.update(observation).unwrap();
+                assert_eq!(index.cliques().len(), predicted);
+            }
+        }
+    }
+
+    #[test]
+    fn insert_new_returns_generated_id() {
+        let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        let observation = Observation::builder(0.0, 0.0)
+            .circular_95_confidence_error(5.0)
+            .unwrap()
+            .build();
+
+        let a = index.insert_new(observation.clone());
+        let b = index.insert_new(observation);
+
+        assert_ne!(a, b);
+        assert_eq!(index.cliques(), &[HashSet::from([a, b])]);
+    }
+
+    #[test]
+    fn insert_batch_matches_batch_construction() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(11), 80);
+        let batch = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+
+        // the new observations are compatible with each other, as well as the existing ones
+        let (existing, new) = observations.split_at(30);
+        let mut index = CliqueIndex::from_observations(existing.to_vec(), CHI2_2D_CONFIDENCE_95);
+        index.insert_batch(new.iter().cloned());
+        assert_eq!(index.state_hash(), batch.state_hash());
+
+        let mut empty = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        empty.insert_batch(observations);
+        assert_eq!(empty.state_hash(), batch.state_hash());
+    }
+
+    #[test]
+    fn dense_adjacency_matches_hashed_adjacency() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations: Vec<Unique<Observation, u64>> =
+            scattered_observations(&mut StdRng::seed_from_u64(31), 80)
+                .into_iter()
+                .map(|observation| Unique {
+                    data: observation.data,
+                    id: u64::try_from(observation.id).unwrap(),
+                })
+                .collect();
+        let (existing, new) = observations.split_at(40);
+        let mut hashed = CliqueIndex::from_observations(existing.to_vec(), CHI2_2D_CONFIDENCE_95);
+        let mut dense = IntegerCliqueIndex::from_sequential(
+            existing.iter().map(|observation| observation.data.clone()),
+            CHI2_2D_CONFIDENCE_95,
+        );
+        assert_eq!(dense.state_hash(), hashed.state_hash());
+
+        let (batch, single) = new.split_at(20);
+        hashed.insert_batch(batch.iter().cloned());
+        dense.insert_batch(batch.iter().cloned());
+        for observation in single {
+            hashed.insert(observation.clone());
+            dense.insert(observation.clone());
+        }
+        for id in (0..80).step_by(3) {
+            assert_eq!(dense.remove(&id), hashed.remove(&id));
+        }
+        assert_eq!(dense.state_hash(), hashed.state_hash());
+        assert_eq!(
+            dense.compatibility_graph().len(),
+            hashed.compatibility_graph().len()
+        );
+        for (id, neighbours) in hashed.compatibility_graph() {
+            assert_eq!(&dense.compatibility_graph()[id], neighbours);
+        }
+
+        let mut empty = IntegerCliqueIndex::dense(Config::new(CHI2_2D_CONFIDENCE_95));
+        empty.insert_batch(observations.iter().cloned());
+        let batch = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+        assert_eq!(empty.state_hash(), batch.state_hash());
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    #[test]
+    fn batches_only_need_thread_safe_ids_with_rayon() {
+        // raw pointers are neither `Send` nor `Sync`
+        let anchors = [0_u8; 3];
+        let observations: Vec<_> = [0.0, 0.5, 10.0]
+            .into_iter()
+            .zip(&anchors)
+            .map(|(x, anchor)| Unique {
+                data: observation(0, x).data,
+                id: std::ptr::from_ref(anchor),
+            })
+            .collect();
+
+        let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        index.insert_batch(observations.clone());
+        assert_eq!(index.cliques().len(), 1);
+        let results = index.classify(&observations);
+        assert!(!results[0].is_novel());
+        assert!(results[2].is_novel());
+    }
+
+    #[test]
+    fn probing_previews_insertion_without_modifying_the_index() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(23);
+        let observations = scattered_observations(&mut rng, 80);
+        let mut index =
+            CliqueIndex::from_observations(observations[..60].to_vec(), CHI2_2D_CONFIDENCE_95);
+
+        for observation in &observations[60..] {
+            let state = index.state_hash();
+            let probed = index.probe(observation);
+            assert_eq!(index.state_hash(), state);
+            assert!(probed.is_sorted_by(|a, b| a.1 <= b.1));
+
+            index.insert(observation.clone());
+            let neighbours: HashSet<usize> = probed.iter().map(|(id, _)| *id).collect();
+            assert_eq!(
+                index
+                    .compatibility_graph()
+                    .get(&observation.id)
+                    .cloned()
+                    .unwrap_or_default(),
+                neighbours
+            );
+        }
+    }
+
+    #[test]
+    fn classify_matches_probing_each_observation() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(13), 80);
+        let (catalogue, probes) = observations.split_at(50);
+        let index = CliqueIndex::from_observations(catalogue.to_vec(), CHI2_2D_CONFIDENCE_95);
+
+        let results = index.classify(probes);
+        assert_eq!(results.len(), probes.len());
+        for (result, probe) in results.iter().zip(probes) {
+            assert_eq!(result.id, probe.id);
+            assert_eq!(result.compatible, index.probe(probe));
+        }
+        assert!(results.iter().any(|result| !result.is_novel()));
+        assert_eq!(index.total_observations(), catalogue.len());
+    }
+
+    #[test]
+    fn incremental_and_batch_construction_reach_the_same_state() {
+        use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let observations = scattered_observations(&mut rng, 60);
+        let batch = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        assert!(batch.cliques().len() > 10);
+
+        for _ in 0..5 {
+            let mut shuffled = observations.clone();
+            shuffled.shuffle(&mut rng);
+            let mut incremental = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+            for observation in shuffled {
+                incremental.insert(observation);
+            }
+            assert_eq!(incremental.state_hash(), batch.state_hash());
+        }
+
+        // removal reaches the same state as never having inserted the observations
+        let mut index = batch;
+        for id in (0..60).step_by(4) {
+            index.remove(&id);
+        }
+        let remaining: Vec<_> = observations
+            .into_iter()
+            .filter(|observation| observation.id % 4 != 0)
+            .collect();
+        let rebuilt = CliqueIndex::from_observations(remaining, CHI2_2D_CONFIDENCE_95);
+        assert_eq!(index.state_hash(), rebuilt.state_hash());
+        assert_ne!(
+            index.state_hash(),
+            CliqueIndex::<usize>::new(CHI2_2D_CONFIDENCE_95).state_hash()
+        );
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn incremental_updates_preserve_the_invariants() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        use crate::InvariantViolation;
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(19), 150);
+        let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        for observation in observations {
+            index.insert(observation);
+        }
+        assert_eq!(index.validate(), Ok(()));
+        for id in (0..150).step_by(3) {
+            index.remove(&id);
+        }
+        assert_eq!(index.validate(), Ok(()));
+
+        // break the symmetry of an edge
+        let (a, b) = index
+            .compatibility_graph
+            .iter()
+            .find_map(|(a, neighbours)| neighbours.iter().next().map(|b| (*a, *b)))
+            .unwrap();
+        index.compatibility_graph.get_mut(&b).unwrap().remove(&a);
+        assert_eq!(
+            index.validate(),
+            Err(InvariantViolation::AsymmetricEdge { from: a, to: b })
+        );
+        index.compatibility_graph.get_mut(&b).unwrap().insert(a);
+
+        // lose the cliques
+        let taken = index.take_cliques(|_| true);
+        assert!(!taken.is_empty());
+        assert!(matches!(
+            index.validate(),
+            Err(InvariantViolation::UncoveredEdge { .. })
+        ));
+    }
+
+    #[test]
+    fn state_hash_is_independent_of_insertion_order_for_exact_configurations() {
+        use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+        let mut rng = StdRng::seed_from_u64(17);
+        let mut observations = scattered_observations(&mut rng, 40);
+        // identical observations are all kept by default
+        for id in 40..45 {
+            observations.push(Unique {
+                data: observations[id - 40].data.clone(),
+                id,
+            });
+        }
+
+        for config in [
+            Config::new(CHI2_2D_CONFIDENCE_95),
+            Config::new(CHI2_2D_CONFIDENCE_95).mutual_nearest_neighbours(3),
+        ] {
+            let batch =
+                CliqueIndex::from_observations_with_config(observations.clone(), config.clone());
+            for _ in 0..5 {
+                let mut shuffled = observations.clone();
+                shuffled.shuffle(&mut rng);
+                let mut incremental = CliqueIndex::with_config(config.clone());
+                for observation in shuffled {
+                    incremental.insert(observation);
+                }
+                assert_eq!(incremental.state_hash(), batch.state_hash());
+            }
+        }
+    }
+
+    #[test]
+    fn state_hash_is_stable_across_platforms() {
+        let index = CliqueIndex::from_observations(
+            vec![
+                observation(0_usize, 0.0),
+                observation(1, 0.5),
+                observation(2, 1.0),
+                observation(3, 10.0),
+            ],
+            CHI2_2D_CONFIDENCE_95,
+        );
+        // pinned, so that any change to the hashed representation is deliberate
+        assert_eq!(index.state_hash(), 15_444_032_813_665_450_000);
+    }
+
+    #[test]
+    fn seeded_outputs_are_reproducible() {
+        use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+        let mut rng = StdRng::seed_from_u64(19);
+        let observations = scattered_observations(&mut rng, 80);
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).seed(3);
+        let approximate = config.clone().density_fallback(DensityFallback {
+            max_density: 0.0,
+            min_component_size: 4,
+        });
+
+        for config in [config, approximate] {
+            let reference =
+                CliqueIndex::from_observations_with_config(observations.clone(), config.clone());
+            for _ in 0..3 {
+                // every index has its own hash maps, with their own iteration order
+                let mut shuffled = observations.clone();
+                shuffled.shuffle(&mut rng);
+                let index = CliqueIndex::from_observations_with_config(shuffled, config.clone());
+                assert_eq!(index.cliques(), reference.cliques());
+                assert_eq!(index.clique_ids(), reference.clique_ids());
+                assert_eq!(index.components(), reference.components());
+            }
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::collections::{HashMap, HashSet};
+    #[test]
+    fn custom_candidate_sources_match_the_r_tree() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        use crate::{BoundingBox, CandidateSource};
+
+        /// A linear scan over the positions of the observations.
+        struct Scan(HashMap<usize, (f64, f64)>);
+
+        impl CandidateSource<usize> for Scan {
+            fn insert(&mut self, observation: &Unique<Arc<Observation>, usize>) {
+                self.0.insert(observation.id, observation.data.position());
+            }
+
+            fn remove(&mut self, observation: &Unique<Arc<Observation>, usize>) {
+                self.0.remove(&observation.id);
+            }
+
+            fn locate(&self, bounds: &BoundingBox) -> Vec<usize> {
+                self.0
+                    .iter()
+                    .filter(|(_, (x, y))| bounds.contains(*x, *y))
+                    .map(|(id, _)| *id)
+                    .collect()
+            }
+        }
 
-    use crate::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(5), 60);
+        let mut expected =
+            CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        let mut index = CliqueIndex::with_candidate_source(
+            Config::new(CHI2_2D_CONFIDENCE_95),
+            Scan(HashMap::new()),
+        );
+        for observation in observations {
+            index.insert(observation);
+        }
+        assert_eq!(index.state_hash(), expected.state_hash());
+
+        for id in [3, 17, 42] {
+            index.remove(&id);
+            expected.remove(&id);
+        }
+        assert_eq!(index.state_hash(), expected.state_hash());
+
+        let inflate = |observation: &Observation| {
+            let error = observation.error_covariance();
+            CovarianceMatrix::new(error.xx() * 2.0, error.yy() * 2.0, error.xy() * 2.0).unwrap()
+        };
+        index.recalibrate(inflate);
+        expected.recalibrate(inflate);
+        assert_eq!(index.state_hash(), expected.state_hash());
+    }
 
     #[test]
-    fn simple_cluster() {
-        let observations = vec![
-            Unique {
-                data: Observation::builder(0.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 0,
-            },
-            Unique {
-                data: Observation::builder(0.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 1,
-            },
-            Unique {
-                data: Observation::builder(0.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 2,
-            },
-        ];
-        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+    fn approximate_search_only_misses_edges_of_the_largest_errors() {
+        use rand::{SeedableRng, rngs::StdRng};
 
-        let expected = HashMap::from([
-            (0, HashSet::from([1, 2])),
-            (1, HashSet::from([0, 2])),
-            (2, HashSet::from([0, 1])),
-        ]);
-        assert_eq!(index.compatibility_graph(), &expected);
+        let mut observations = scattered_observations(&mut StdRng::seed_from_u64(23), 200);
+        // a few observations with much larger errors than the rest
+        for observation in observations.iter_mut().step_by(50) {
+            let (x, y) = observation.data.position();
+            observation.data = Observation::builder(x, y)
+                .circular_95_confidence_error(15.0)
+                .unwrap()
+                .build();
+        }
+        let config = Config::new(CHI2_2D_CONFIDENCE_95);
+        let exact =
+            CliqueIndex::from_observations_with_config(observations.clone(), config.clone());
+
+        let complete = CliqueIndex::from_observations_with_config(
+            observations.clone(),
+            config.clone().approximate_search(0.0),
+        );
+        assert_eq!(complete.compatibility_graph(), exact.compatibility_graph());
+
+        let approximate = CliqueIndex::from_observations_with_config(
+            observations.clone(),
+            config.approximate_search(0.02),
+        );
+        let uncertain: HashSet<usize> = (0..200).step_by(50).collect();
+        let mut missed = 0;
+        for (a, neighbours) in exact.compatibility_graph() {
+            for b in neighbours {
+                if !approximate.compatibility_graph()[a].contains(b) {
+                    assert!(uncertain.contains(a) || uncertain.contains(b));
+                    missed += 1;
+                }
+            }
+        }
+        assert!(missed > 0);
+        for (a, neighbours) in approximate.compatibility_graph() {
+            assert!(neighbours.is_subset(&exact.compatibility_graph()[a]));
+        }
     }
 
+    #[cfg(feature = "profiling")]
     #[test]
-    fn no_overlap() {
+    fn profiling_times_each_phase_without_changing_the_cliques() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(17), 200);
+        let mut plain = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        let mut profiled = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        let timings = crate::PhaseTimings::new();
+        profiled.set_profiler(timings.clone());
+
+        let mut total = std::time::Duration::ZERO;
+        for observation in observations {
+            plain.insert(observation.clone());
+            profiled.insert(observation);
+            total += timings.take().total();
+        }
+        assert!(total > std::time::Duration::ZERO);
+        assert_eq!(timings.breakdown(), crate::PhaseBreakdown::default());
+
+        let canonical = |index: &CliqueIndex<usize>| {
+            let mut cliques: Vec<Vec<usize>> = index
+                .cliques()
+                .iter()
+                .map(|clique| {
+                    let mut members: Vec<_> = clique.iter().copied().collect();
+                    members.sort_unstable();
+                    members
+                })
+                .collect();
+            cliques.sort();
+            cliques
+        };
+        assert_eq!(canonical(&plain), canonical(&profiled));
+
+        profiled.remove(&0);
+        assert!(timings.breakdown().clique_enumeration > std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn recalibration_matches_a_rebuilt_index() {
         let observations = vec![
-            Unique {
-                data: Observation::builder(10.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 0,
-            },
-            Unique {
-                data: Observation::builder(0.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 1,
-            },
-            Unique {
-                data: Observation::builder(-10.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 2,
-            },
+            observation(0, 0.0),
+            observation(1, 4.0),
+            observation(2, 8.0),
         ];
-        let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+        let mut index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        assert!(index.cliques().is_empty());
 
-        let expected = HashMap::from([]);
-        assert_eq!(index.compatibility_graph(), &expected);
+        let inflated = CovarianceMatrix::new(4.0, 4.0, 0.0).unwrap();
+        index.recalibrate(|_| inflated);
+
+        let rebuilt = CliqueIndex::from_observations(
+            observations
+                .into_iter()
+                .map(|observation| Unique {
+                    data: observation.data.with_error(inflated),
+                    id: observation.id,
+                })
+                .collect(),
+            CHI2_2D_CONFIDENCE_95,
+        );
+        assert_eq!(index.compatibility_graph(), rebuilt.compatibility_graph());
+        assert_eq!(index.cliques().len(), 2);
+        assert_eq!(index.get(&1).unwrap().error_covariance(), inflated);
     }
 
     #[test]
-    fn insert_equivalence() {
-        let observations = vec![
-            Unique {
-                data: Observation::builder(10.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 0,
-            },
-            Unique {
-                data: Observation::builder(0.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 1,
-            },
-            Unique {
-                data: Observation::builder(-10.0, 0.0)
-                    .circular_95_confidence_error(5.0)
+    fn set_chi2_recomputes_cliques() {
+        let observations: Vec<_> = [(0, 0.0), (1, 0.3)]
+            .into_iter()
+            .map(|(id, x)| Unique {
+                data: Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(0.3)
                     .unwrap()
                     .build(),
-                id: 2,
-            },
-            Unique {
-                data: Observation::builder(10.0, 0.0)
-                    .circular_95_confidence_error(5.0)
-                    .unwrap()
-                    .build(),
-                id: 3,
-            },
-            Unique {
-                data: Observation::builder(10.0, 0.0)
-                    .circular_95_confidence_error(5.0)
+                id,
+            })
+            .collect();
+        let mut index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        assert_eq!(index.cliques().len(), 1);
+
+        // At a much stricter threshold the observations are no longer compatible
+        index.set_chi2(0.5).unwrap();
+        assert_eq!(index.chi2().to_bits(), 0.5_f64.to_bits());
+        assert_eq!(index.config().chi2().to_bits(), 0.5_f64.to_bits());
+        assert!(index.cliques().is_empty());
+        assert!(index.compatibility_graph().is_empty());
+        assert_eq!(index.total_observations(), 2);
+
+        index.set_chi2(CHI2_2D_CONFIDENCE_95).unwrap();
+        let rebuilt = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+        assert_eq!(index.cliques(), rebuilt.cliques());
+
+        assert!(index.set_chi2(f64::NAN).is_err());
+        assert_eq!(index.config(), rebuilt.config());
+        assert_eq!(index.cliques(), rebuilt.cliques());
+    }
+
+    #[test]
+    fn tightening_chi2_matches_a_rebuilt_index() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(13), 80);
+        let mut index = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+
+        for chi2 in [4.0, 2.0, 3.0] {
+            index.set_chi2(chi2).unwrap();
+            let rebuilt = CliqueIndex::from_observations(observations.clone(), chi2);
+            assert_eq!(index.compatibility_graph(), rebuilt.compatibility_graph());
+            assert_eq!(index.state_hash(), rebuilt.state_hash());
+        }
+    }
+
+    #[test]
+    fn cancelled_threshold_changes_leave_the_index_unchanged() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(17), 60);
+        let mut index = CliqueIndex::from_observations(observations.clone(), 3.0);
+        let state = index.state_hash();
+
+        let cancelled = CancellationToken::new();
+        cancelled.cancel();
+        for chi2 in [2.0, 4.0] {
+            assert!(matches!(
+                index.set_chi2_cancellable(chi2, &cancelled),
+                Err(Error::Cancelled)
+            ));
+            assert_eq!(index.chi2().to_bits(), 3.0_f64.to_bits());
+            assert_eq!(index.state_hash(), state);
+        }
+
+        index
+            .set_chi2_cancellable(4.0, &CancellationToken::new())
+            .unwrap();
+        let rebuilt = CliqueIndex::from_observations(observations, 4.0);
+        assert_eq!(index.compatibility_graph(), rebuilt.compatibility_graph());
+        assert_eq!(index.state_hash(), rebuilt.state_hash());
+    }
+
+    #[test]
+    fn configuration_can_be_read_back_from_an_index() {
+        use crate::{
+            CHI2_2D_CONFIDENCE_90, CliqueOverflow, DuplicatePolicy, EvictionPolicy, TrackDirection,
+        };
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95)
+            .duplicate_policy(DuplicatePolicy::Skip)
+            .capacity(100, EvictionPolicy::Oldest)
+            .clique_capacity(10, CliqueOverflow::Error)
+            .max_neighbourhood(20)
+            .directional_gate(
+                CHI2_2D_CONFIDENCE_95,
+                CHI2_2D_CONFIDENCE_90,
+                TrackDirection::Velocity,
+            )
+            .seed(3);
+        let mut index: CliqueIndex<usize> = CliqueIndex::with_config(config);
+        index.set_chi2(CHI2_2D_CONFIDENCE_90).unwrap();
+
+        // a wrapper reports the active settings, without keeping its own copy of them
+        let config = index.config().view();
+        assert_eq!(config.chi2().to_bits(), CHI2_2D_CONFIDENCE_90.to_bits());
+        assert_eq!(config.duplicate_policy(), DuplicatePolicy::Skip);
+        assert_eq!(config.capacity(), Some((100, EvictionPolicy::Oldest)));
+        assert_eq!(config.clique_capacity(), Some((10, CliqueOverflow::Error)));
+        assert_eq!(config.max_neighbourhood(), Some(20));
+        assert_eq!(
+            config.directional_gate(),
+            Some((
+                CHI2_2D_CONFIDENCE_95,
+                CHI2_2D_CONFIDENCE_90,
+                TrackDirection::Velocity
+            ))
+        );
+        assert_eq!(config.seed(), Some(3));
+        assert_eq!(config.density_fallback(), None);
+        assert!(!config.one_dimensional());
+    }
+
+    #[test]
+    fn dense_components_fall_back_to_hierarchical_clustering() {
+        // A tight cluster of observations along a line. The ends of the line are incompatible with
+        // each other, so exact enumeration finds several overlapping maximal cliques.
+        let observations: Vec<_> = (0..6)
+            .map(|i| Unique {
+                data: Observation::builder(f64::from(i), 0.0)
+                    .circular_95_confidence_error(2.5)
                     .unwrap()
                     .build(),
-                id: 4,
-            },
-        ];
+                id: i,
+            })
+            .collect();
 
-        let index1 = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        let exact = CliqueIndex::from_observations(observations.clone(), CHI2_2D_CONFIDENCE_95);
+        assert!(!exact.is_approximate());
+        assert!(exact.cliques().len() > 1);
 
-        let mut index2 = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).density_fallback(DensityFallback {
+            max_density: 0.1,
+            min_component_size: 3,
+        });
+
+        let batch =
+            CliqueIndex::from_observations_with_config(observations.clone(), config.clone());
+        assert!(batch.is_approximate());
 
+        // The approximate cliques partition the observations
+        let total: usize = batch.cliques().iter().map(HashSet::len).sum();
+        let distinct: HashSet<_> = batch.cliques().iter().flatten().collect();
+        assert_eq!(total, distinct.len());
+
+        let mut incremental = CliqueIndex::with_config(config);
         for obs in observations {
-            index2.insert(obs);
+            incremental.insert(obs);
         }
+        assert!(incremental.is_approximate());
+    }
 
-        assert_eq!(index1.cliques, index2.cliques);
-        assert_eq!(index1.compatibility_graph, index2.compatibility_graph);
+    #[test]
+    fn hubs_are_clustered_approximately() {
+        // a hub with a large error, surrounded by a ring of precise observations
+        let hub = Unique {
+            data: Observation::builder(0.0, 0.0)
+                .circular_95_confidence_error(50.0)
+                .unwrap()
+                .build(),
+            id: 0,
+        };
+        let ring: Vec<_> = (1..=12)
+            .map(|id| {
+                let angle = f64::from(id) * std::f64::consts::PI / 6.0;
+                Unique {
+                    data: Observation::builder(10.0 * angle.cos(), 10.0 * angle.sin())
+                        .circular_95_confidence_error(5.0)
+                        .unwrap()
+                        .build(),
+                    id,
+                }
+            })
+            .collect();
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).max_neighbourhood(8);
+
+        let mut incremental = CliqueIndex::with_config(config.clone());
+        incremental.insert_batch(ring.clone());
+        assert!(!incremental.is_approximate());
+        incremental.insert(hub.clone());
+        assert!(incremental.is_approximate());
+        assert_eq!(incremental.health().approximate_observations, 13);
+
+        let mut observations = ring;
+        observations.push(hub);
+        let batch = CliqueIndex::from_observations_with_config(observations, config);
+        assert!(batch.is_approximate());
+
+        // removing the hub restores exact enumeration
+        incremental.remove(&0);
+        assert!(!incremental.is_approximate());
+    }
+
+    #[test]
+    fn mutual_nearest_neighbours_prune_marginal_edges() {
+        let observations = [(0, 0.0), (1, 1.0), (2, 2.2)].map(|(id, x)| Unique {
+            data: Observation::builder(x, 0.0)
+                .circular_95_confidence_error(3.0)
+                .unwrap()
+                .build(),
+            id,
+        });
+
+        let unpruned = CliqueIndex::from_observations(observations.to_vec(), CHI2_2D_CONFIDENCE_95);
+        assert_eq!(unpruned.cliques().len(), 1);
+
+        // 0 and 1 are each other's nearest neighbour, but 1 is nearer to 0 than to 2
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).mutual_nearest_neighbours(1);
+        let pruned = CliqueIndex::from_observations_with_config(observations.to_vec(), config);
+        assert_eq!(pruned.cliques(), &[HashSet::from([0, 1])]);
+        assert_eq!(pruned.compatibility_graph(), unpruned.compatibility_graph());
+    }
+
+    #[test]
+    fn pruned_indices_are_maintained_incrementally() {
+        use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut observations = scattered_observations(&mut rng, 60);
+        observations.shuffle(&mut rng);
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).mutual_nearest_neighbours(2);
+
+        let mut incremental = CliqueIndex::with_config(config.clone());
+        for observation in observations.iter().cloned() {
+            incremental.insert(observation);
+        }
+        let batch =
+            CliqueIndex::from_observations_with_config(observations.clone(), config.clone());
+        assert_eq!(incremental.state_hash(), batch.state_hash());
+
+        for id in (0..60).step_by(3) {
+            incremental.remove(&id);
+        }
+        let remaining: Vec<_> = observations
+            .into_iter()
+            .filter(|observation| observation.id % 3 != 0)
+            .collect();
+        let rebuilt = CliqueIndex::from_observations_with_config(remaining, config);
+        assert_eq!(incremental.state_hash(), rebuilt.state_hash());
+    }
+
+    #[test]
+    fn exact_duplicates_are_counted_not_inserted() {
+        let observation = Observation::builder(0.0, 0.0)
+            .circular_95_confidence_error(5.0)
+            .unwrap()
+            .build();
+        let observations: Vec<_> = (0..3)
+            .map(|id| Unique {
+                data: observation.clone(),
+                id,
+            })
+            .collect();
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).duplicate_policy(DuplicatePolicy::Count);
+
+        let batch =
+            CliqueIndex::from_observations_with_config(observations.clone(), config.clone());
+        assert!(batch.cliques().is_empty());
+        assert_eq!(batch.duplicate_count(&0), 2);
+
+        let mut incremental = CliqueIndex::with_config(config);
+        for obs in observations {
+            incremental.insert(obs);
+        }
+        assert!(incremental.cliques().is_empty());
+        assert_eq!(incremental.duplicate_count(&0), 2);
+    }
+
+    #[test]
+    fn bounded_indices_evict_and_repair_incrementally() {
+        use rand::{SeedableRng, rngs::StdRng};
+
+        use crate::EvictionPolicy;
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(8), 80);
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).capacity(40, EvictionPolicy::Oldest);
+        let mut index = CliqueIndex::with_config(config.clone());
+        for observation in observations.iter().cloned() {
+            index.insert(observation);
+            assert!(index.total_observations() <= 40);
+        }
+
+        let survivors = observations[40..].to_vec();
+        let expected = CliqueIndex::from_observations(survivors, CHI2_2D_CONFIDENCE_95);
+        assert_eq!(index.state_hash(), expected.state_hash());
+
+        let mut batch = CliqueIndex::with_config(config);
+        batch.insert_batch(observations);
+        assert_eq!(batch.state_hash(), expected.state_hash());
+    }
+
+    #[test]
+    fn isolated_observations_are_evicted_first() {
+        use crate::EvictionPolicy;
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).capacity(2, EvictionPolicy::IsolatedFirst);
+        let index = CliqueIndex::from_observations_with_config(
+            vec![
+                observation(0, 0.0),
+                observation(1, 100.0),
+                observation(2, 0.5),
+            ],
+            config,
+        );
+
+        assert!(!index.contains(&1));
+        assert_eq!(index.cliques(), &[HashSet::from([0, 2])]);
+    }
+
+    #[test]
+    fn clique_capacity_follows_the_overflow_policy() {
+        use crate::CliqueOverflow;
+
+        // a triple, then a storm of loose pairs, then a tight pair
+        let mut observations = vec![
+            observation(0, 0.0),
+            observation(1, 0.5),
+            observation(2, 1.0),
+        ];
+        for (pair, x) in (100..=1000).step_by(100).map(f64::from).enumerate() {
+            observations.push(observation(10 + 2 * pair, x));
+            observations.push(observation(11 + 2 * pair, x + 0.8));
+        }
+        observations.push(observation(100, 5000.0));
+        observations.push(observation(101, 5000.0));
+
+        let insert_all = |policy| {
+            let config = Config::new(CHI2_2D_CONFIDENCE_95).clique_capacity(2, policy);
+            let mut index = CliqueIndex::with_config(config);
+            for observation in observations.clone() {
+                index.insert(observation);
+            }
+            assert_eq!(index.cliques().len(), 2);
+            assert!(index.is_truncated());
+            assert_eq!(index.health().dropped_cliques, 10);
+            index
+        };
+
+        let index = insert_all(CliqueOverflow::DropSmallest);
+        assert!(index.cliques().iter().any(|clique| clique.len() == 3));
+        // the newest of the equally small pairs were dropped first
+        assert!(index.cliques().iter().any(|clique| clique.contains(&10)));
+
+        let index = insert_all(CliqueOverflow::DropLowestScore);
+        assert!(
+            index
+                .cliques()
+                .iter()
+                .any(|clique| clique == &HashSet::from([100, 101]))
+        );
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).clique_capacity(1, CliqueOverflow::Error);
+        let mut index = CliqueIndex::with_config(config);
+        for observation in observations.iter().take(4).cloned() {
+            index.try_insert(observation).unwrap();
+        }
+        assert!(matches!(
+            index.try_insert(observations[4].clone()),
+            Err(Error::TooManyCliques)
+        ));
+        assert_eq!(index.total_observations(), 4);
+        assert_eq!(index.cliques().len(), 1);
+        assert!(!index.is_truncated());
+    }
+
+    #[test]
+    fn clique_capacity_error_is_judged_before_eviction() {
+        use crate::{CliqueOverflow, EvictionPolicy};
+
+        let config = Config::new(CHI2_2D_CONFIDENCE_95)
+            .capacity(4, EvictionPolicy::Oldest)
+            .clique_capacity(1, CliqueOverflow::Error);
+        let mut index = CliqueIndex::with_config(config);
+        for (id, x) in [(1, 500.0), (2, 0.0), (3, 0.1), (4, 100.0)] {
+            index.try_insert(observation(id, x)).unwrap();
+        }
+        let before = index.state_hash();
+
+        assert!(matches!(
+            index.try_insert(observation(5, 100.1)),
+            Err(Error::TooManyCliques)
+        ));
+        // the oldest observation was not evicted to make room for the rejected one
+        assert_eq!(index.total_observations(), 4);
+        assert!(index.contains(&1));
+        assert_eq!(index.cliques().len(), 1);
+        assert_eq!(index.state_hash(), before);
+    }
+
+    #[test]
+    fn clique_history_records_membership_changes() {
+        let mut index =
+            CliqueIndex::with_config(Config::new(CHI2_2D_CONFIDENCE_95).clique_history(8));
+        index.insert(observation(0, 0.0));
+        index.insert(observation(1, 0.5));
+        let [id] = index.clique_ids() else {
+            panic!("expected a single clique");
+        };
+        let id = *id;
+
+        // the clique keeps its identifier as it grows and shrinks
+        index.insert(observation(2, 0.2));
+        index.remove(&2);
+        assert_eq!(index.clique_ids(), [id]);
+        assert_eq!(index.clique(id), Some(&HashSet::from([0, 1])));
+
+        let history = index.clique_history(id).unwrap();
+        assert_eq!(history.created, 2);
+        assert_eq!(history.initial_members, HashSet::from([0, 1]));
+        let changes: Vec<_> = history
+            .changes
+            .iter()
+            .map(|change| {
+                (
+                    change.generation,
+                    change.added.clone(),
+                    change.removed.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (3, HashSet::from([2]), HashSet::new()),
+                (4, HashSet::new(), HashSet::from([2])),
+            ]
+        );
+
+        // the history is discarded when the clique dissolves
+        index.remove(&1);
+        assert!(index.clique_ids().is_empty());
+        assert!(index.clique_history(id).is_none());
+    }
+
+    #[test]
+    fn recycled_clique_ids_are_reused_once_dissolved() {
+        let config =
+            Config::new(CHI2_2D_CONFIDENCE_95).clique_ids(crate::CliqueIdAllocation::Recycling);
+        let mut index = CliqueIndex::with_config(config);
+        for (id, x) in [(0, 0.0), (1, 0.1), (2, 10.0), (3, 10.1)] {
+            index.insert(observation(id, x));
+        }
+        let ids: HashSet<u64> = index.clique_ids().iter().map(|id| id.as_u64()).collect();
+
+        // dissolve a clique, and form a new one elsewhere
+        index.remove(&0);
+        let dissolved: HashSet<u64> = ids
+            .difference(&index.clique_ids().iter().map(|id| id.as_u64()).collect())
+            .copied()
+            .collect();
+        assert_eq!(dissolved.len(), 1);
+        index.insert(observation(4, 50.0));
+        index.insert(observation(5, 50.1));
+        assert!(
+            index
+                .clique_ids()
+                .iter()
+                .any(|id| dissolved.contains(&id.as_u64()))
+        );
+    }
+
+    #[test]
+    fn clique_events_replay_to_the_cliques() {
+        use std::sync::{Arc, Mutex};
+
+        use rand::{SeedableRng, rngs::StdRng};
+
+        use crate::{CliqueEvent, CliqueId};
+
+        let observations = scattered_observations(&mut StdRng::seed_from_u64(29), 120);
+        let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        index.record_clique_events();
+        let delivered = Arc::new(Mutex::new(0));
+        let count = Arc::clone(&delivered);
+        index.set_clique_event_sink(move |_: &crate::TimedCliqueEvent<usize>| {
+            *count.lock().unwrap() += 1;
+        });
+        for observation in observations {
+            index.insert(observation);
+        }
+        for id in (0..120).step_by(4) {
+            index.remove(&id);
+        }
+
+        let events: Vec<_> = index.drain_clique_events().collect();
+        assert_eq!(events.len(), *delivered.lock().unwrap());
+        assert!(
+            events
+                .windows(2)
+                .all(|pair| pair[0].generation <= pair[1].generation)
+        );
+        assert!(index.drain_clique_events().next().is_none());
+
+        let mut replayed: HashMap<CliqueId, HashSet<usize>> = HashMap::new();
+        let (mut merges, mut splits) = (0, 0);
+        for timed in events {
+            match timed.event {
+                CliqueEvent::Created { id, members } => {
+                    assert!(replayed.insert(id, members).is_none());
+                }
+                CliqueEvent::Merged { id, from, members } => {
+                    merges += 1;
+                    for id in from {
+                        assert!(replayed.remove(&id).is_some());
+                    }
+                    replayed.insert(id, members);
+                }
+                CliqueEvent::Split { id, into } => {
+                    splits += 1;
+                    assert!(replayed.remove(&id).is_some());
+                    replayed.extend(into);
+                }
+                CliqueEvent::Updated { id, added, removed } => {
+                    let members = replayed.get_mut(&id).unwrap();
+                    members.retain(|member| !removed.contains(member));
+                    members.extend(added);
+                }
+                CliqueEvent::Dissolved { id } => {
+                    assert!(replayed.remove(&id).is_some());
+                }
+            }
+        }
+        assert!(merges > 0 && splits > 0);
+
+        let current: HashMap<CliqueId, HashSet<usize>> = index
+            .clique_ids()
+            .iter()
+            .copied()
+            .zip(index.cliques().iter().cloned())
+            .collect();
+        assert_eq!(replayed, current);
+    }
+
+    #[test]
+    fn health_tracks_modifications() {
+        let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        let health = index.health();
+        assert_eq!(health.generation, 0);
+        assert_eq!(health.last_insert_latency, None);
+
+        for (id, x) in [(1, 0.0), (2, 1.0), (3, 100.0)] {
+            index.insert(Unique {
+                data: Observation::builder(x, 0.0)
+                    .circular_95_confidence_error(5.0)
+                    .unwrap()
+                    .build(),
+                id,
+            });
+        }
+
+        let health = index.health();
+        assert_eq!(health.total_observations, 3);
+        assert_eq!(health.connected_observations, 2);
+        assert_eq!(health.compatibility_edges, 1);
+        assert_eq!(health.cliques, 1);
+        assert_eq!(health.largest_clique, 2);
+        assert!(health.within_budget());
+        assert_eq!(health.generation, 3);
+        assert!(health.last_insert_latency.is_some());
+
+        index.remove(&3);
+        assert!(index.remove(&3).is_none());
+        assert_eq!(index.health().generation, 4);
     }
 }
@@ -0,0 +1,309 @@
+use std::{collections::HashSet, hash::Hash, slice};
+
+use crate::{CliqueDiff, clique_diff};
+
+/// The maximal cliques of a [`CliqueIndex`](crate::CliqueIndex), each a set of observation IDs.
+///
+/// Cliques are kept in a stable order, so the `i`th clique corresponds to the `i`th entry of
+/// [`CliqueIndex::clique_ids`](crate::CliqueIndex::clique_ids) and
+/// [`CliqueIndex::fused_estimates`](crate::CliqueIndex::fused_estimates).
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+///
+/// let observations = [(1, 0.0), (2, 0.5), (3, 1.0), (4, 100.0)]
+///     .into_iter()
+///     .map(|(id, x)| Unique {
+///         data: Observation::builder(x, 0.0)
+///             .circular_95_confidence_error(1.0)
+///             .unwrap()
+///             .build(),
+///         id,
+///     })
+///     .collect();
+/// let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+/// let cliques = index.cliques();
+///
+/// assert_eq!(cliques.len(), 1);
+/// assert_eq!(cliques.containing(&2).count(), 1);
+/// assert_eq!(cliques.containing(&4).count(), 0);
+/// assert_eq!(cliques.largest().map(|clique| clique.len()), Some(3));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        transparent,
+        bound(
+            serialize = "Id: serde::Serialize",
+            deserialize = "Id: serde::Deserialize<'de> + Eq + std::hash::Hash"
+        )
+    )
+)]
+pub struct CliqueSet<Id>(pub(crate) Vec<HashSet<Id>>);
+
+impl<Id> Default for CliqueSet<Id> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<Id> PartialEq for CliqueSet<Id>
+where
+    Id: Eq + Hash,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<Id> Eq for CliqueSet<Id> where Id: Eq + Hash {}
+
+impl<Id, const N: usize> PartialEq<[HashSet<Id>; N]> for CliqueSet<Id>
+where
+    Id: Eq + Hash,
+{
+    fn eq(&self, other: &[HashSet<Id>; N]) -> bool {
+        self.0 == other
+    }
+}
+
+impl<Id> CliqueSet<Id> {
+    /// The number of cliques.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no cliques.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the cliques, in order.
+    pub fn iter(&self) -> slice::Iter<'_, HashSet<Id>> {
+        self.0.iter()
+    }
+
+    /// The clique at the given position, if any.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&HashSet<Id>> {
+        self.0.get(index)
+    }
+
+    /// The clique with the most members, if any.
+    ///
+    /// If several cliques are equally large, the first of them is returned.
+    #[must_use]
+    pub fn largest(&self) -> Option<&HashSet<Id>> {
+        self.0.iter().rev().max_by_key(|clique| clique.len())
+    }
+}
+
+impl<Id> CliqueSet<Id>
+where
+    Id: Eq + Hash,
+{
+    /// Iterate over the cliques which contain the given observation, in order.
+    pub fn containing<'a>(&'a self, id: &'a Id) -> impl Iterator<Item = &'a HashSet<Id>> + 'a {
+        self.0.iter().filter(move |clique| clique.contains(id))
+    }
+
+    /// Describe how these cliques have evolved into `other`.
+    ///
+    /// Cliques are referred to by their position in each set. See [`clique_diff`].
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> CliqueDiff {
+        clique_diff(&self.0, &other.0)
+    }
+}
+
+/// The cliques of a [`CliqueSet`] in a flat layout, with the members of every clique stored
+/// contiguously in a single array.
+///
+/// The members of the `i`th clique are `ids()[offsets()[i]..offsets()[i + 1]]`, so there is one
+/// more offset than there are cliques. This layout can be handed across a language boundary
+/// without any conversion. See [`CliqueIndex::flat_cliques`](crate::CliqueIndex::flat_cliques).
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+///
+/// let observations = [(1, 0.0), (2, 0.5), (3, 100.0), (4, 100.5)]
+///     .into_iter()
+///     .map(|(id, x)| Unique {
+///         data: Observation::builder(x, 0.0)
+///             .circular_95_confidence_error(1.0)
+///             .unwrap()
+///             .build(),
+///         id,
+///     })
+///     .collect();
+/// let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+/// let flat = index.flat_cliques();
+///
+/// assert_eq!(flat.len(), 2);
+/// assert_eq!(flat.offsets(), &[0, 2, 4]);
+/// assert_eq!(flat.ids().len(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlatCliques<Id> {
+    ids: Vec<Id>,
+    offsets: Vec<usize>,
+}
+
+impl<Id> Default for FlatCliques<Id> {
+    fn default() -> Self {
+        Self {
+            ids: Vec::new(),
+            offsets: vec![0],
+        }
+    }
+}
+
+impl<Id> FlatCliques<Id>
+where
+    Id: Copy,
+{
+    /// Add a clique after the others.
+    pub(crate) fn push(&mut self, clique: &HashSet<Id>) {
+        self.ids.extend(clique.iter().copied());
+        self.offsets.push(self.ids.len());
+    }
+
+    /// Keep only the cliques whose entries in `keep` are `true`, preserving their order.
+    ///
+    /// The members of the kept cliques are moved down in place over those of the removed ones.
+    pub(crate) fn retain(&mut self, keep: &[bool]) {
+        debug_assert_eq!(keep.len(), self.len());
+        let (mut kept, mut end) = (0, 0);
+        for (index, keep) in keep.iter().enumerate() {
+            let (start, next) = (self.offsets[index], self.offsets[index + 1]);
+            if *keep {
+                self.ids.copy_within(start..next, end);
+                end += next - start;
+                kept += 1;
+                self.offsets[kept] = end;
+            }
+        }
+        self.ids.truncate(end);
+        self.offsets.truncate(kept + 1);
+    }
+}
+
+impl<Id> FlatCliques<Id> {
+    /// The members of all of the cliques, in order.
+    #[must_use]
+    pub fn ids(&self) -> &[Id] {
+        &self.ids
+    }
+
+    /// The position in [`Self::ids`] at which each clique starts, followed by the total number of
+    /// members.
+    #[must_use]
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// The number of cliques.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Returns `true` if there are no cliques.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The members of the clique at the given position, if any.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&[Id]> {
+        let (start, end) = (*self.offsets.get(index)?, *self.offsets.get(index + 1)?);
+        Some(&self.ids[start..end])
+    }
+
+    /// Iterate over the members of each clique, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &[Id]> {
+        self.offsets
+            .windows(2)
+            .map(|window| &self.ids[window[0]..window[1]])
+    }
+}
+
+impl<Id> FromIterator<HashSet<Id>> for CliqueSet<Id> {
+    fn from_iter<T: IntoIterator<Item = HashSet<Id>>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<Id> IntoIterator for CliqueSet<Id> {
+    type Item = HashSet<Id>;
+    type IntoIter = std::vec::IntoIter<HashSet<Id>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, Id> IntoIterator for &'a CliqueSet<Id> {
+    type Item = &'a HashSet<Id>;
+    type IntoIter = slice::Iter<'a, HashSet<Id>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<Id> From<CliqueSet<Id>> for Vec<Vec<Id>> {
+    fn from(cliques: CliqueSet<Id>) -> Self {
+        cliques
+            .into_iter()
+            .map(|clique| clique.into_iter().collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_prefers_the_first_of_equal_cliques() {
+        let cliques: CliqueSet<u32> = [
+            HashSet::from([1, 2]),
+            HashSet::from([3, 4, 5]),
+            HashSet::from([5, 6, 7]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(cliques.largest(), Some(&HashSet::from([3, 4, 5])));
+        assert_eq!(cliques.containing(&5).count(), 2);
+        assert!(CliqueSet::<u32>::default().largest().is_none());
+    }
+
+    #[test]
+    fn converts_to_nested_vectors() {
+        let cliques: CliqueSet<u32> = [HashSet::from([1]), HashSet::from([2])]
+            .into_iter()
+            .collect();
+
+        assert_eq!(Vec::<Vec<u32>>::from(cliques), vec![vec![1], vec![2]]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_as_a_list_of_cliques() {
+        let cliques: CliqueSet<u32> = std::iter::once(HashSet::from([1])).collect();
+
+        let json = serde_json::to_value(&cliques).unwrap();
+        assert_eq!(json, serde_json::json!([[1]]));
+        assert_eq!(
+            serde_json::from_value::<CliqueSet<u32>>(json).unwrap(),
+            cliques
+        );
+    }
+}
@@ -1,91 +1,251 @@
 use std::collections::{HashMap, HashSet};
 
-/// Finds all maximal cliques in an undirected graph using the Bron-Kerbosch algorithm with pivoting.
+use crate::{
+    Adjacency, BuildPhase, CancellationToken,
+    cancel::{Cancelled, Monitor, is_cancelled},
+};
+
+mod hierarchical;
+pub use hierarchical::complete_linkage_clusters;
+
+/// The strategy used to enumerate the maximal cliques of the compatibility graph.
+///
+/// All strategies find the same set of maximal cliques, but their performance depends on the
+/// structure of the graph. The default is usually the best choice; the alternatives are provided
+/// for benchmarking against particular datasets.
+///
+/// See [`Config::enumeration_strategy`](crate::Config::enumeration_strategy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EnumerationStrategy {
+    /// Bron-Kerbosch, pivoting on the candidate with the most neighbours among the remaining
+    /// candidates (Tomita's pivot rule).
+    #[default]
+    MaxDegreePivot,
+
+    /// Bron-Kerbosch, pivoting on a pseudo-randomly chosen candidate.
+    ///
+    /// This is cheaper per step than [`Self::MaxDegreePivot`], but usually prunes less of the search.
+    RandomPivot,
+
+    /// Bron-Kerbosch with max-degree pivoting, with the outermost level processed in degeneracy
+    /// order (Eppstein, Löffler and Strash).
+    ///
+    /// This bounds the size of each top-level subproblem by the degeneracy of the graph, which
+    /// favours large, sparse graphs.
+    DegeneracyOrdering,
+
+    /// Bron-Kerbosch without pivoting.
+    ///
+    /// This is only competitive on very small or very sparse graphs.
+    NoPivot,
+}
+
+/// Finds all maximal cliques in an undirected graph using the Bron-Kerbosch algorithm.
 ///
 /// A maximal clique is a complete subgraph (all vertices connected to each other) that cannot
-/// be extended by adding another vertex. This implementation uses pivoting optimization to
-/// reduce the search space significantly.
+/// be extended by adding another vertex.
 ///
 /// # Arguments
 /// * `graph` - Adjacency list representation where each vertex maps to its neighbors
+/// * `strategy` - How pivots are chosen, and in which order the vertices are processed
 ///
 /// # Returns
 /// Vector of all maximal cliques, where each clique is represented as a [`HashSet`] of vertex IDs
 ///
 /// # Time Complexity
 /// O(3^(n/3)) worst case, but typically much better with pivoting for sparse graphs
-pub fn find_maximal_cliques<Id>(graph: &HashMap<Id, HashSet<Id>>) -> Vec<HashSet<Id>>
+pub fn find_maximal_cliques<Id>(
+    graph: &impl Adjacency<Id>,
+    strategy: EnumerationStrategy,
+) -> Vec<HashSet<Id>>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    let Ok(cliques) = find_maximal_cliques_monitored(graph, strategy, &mut Monitor::default())
+    else {
+        unreachable!("an enumeration without a cancellation token can't be cancelled")
+    };
+    cliques
+}
+
+/// Finds all maximal cliques in an undirected graph, as for [`find_maximal_cliques`], reporting
+/// progress to the monitor, and stopping early if it is cancelled.
+pub fn find_maximal_cliques_monitored<Id>(
+    graph: &impl Adjacency<Id>,
+    strategy: EnumerationStrategy,
+    monitor: &mut Monitor,
+) -> Result<Vec<HashSet<Id>>, Cancelled>
 where
     Id: Copy + Eq + std::hash::Hash,
 {
     if graph.is_empty() {
-        return Vec::new();
+        monitor.step(BuildPhase::CliqueEnumeration, 1, 1)?;
+        return Ok(Vec::new());
     }
 
     // Pre-allocate with reasonable capacity - empirically, most graphs have O(n) cliques
-    let mut cliques = Vec::with_capacity(graph.len().max(16));
-
-    // Initialize Bron-Kerbosch sets
-    let r = HashSet::new(); // Current clique (empty)
-    let p = graph.keys().copied().collect(); // All vertices as candidates
-    let x = HashSet::new(); // No excluded vertices initially
+    let mut enumerator = Enumerator::new(graph, strategy, graph.len().max(16));
+    enumerator.cancellation = monitor.cancellation();
+
+    if strategy == EnumerationStrategy::DegeneracyOrdering {
+        // the search from each vertex is independent, so progress can be reported between them
+        let mut earlier = HashSet::with_capacity(graph.len());
+        for (done, vertex) in degeneracy_ordering(graph).into_iter().enumerate() {
+            monitor.step(BuildPhase::CliqueEnumeration, done, graph.len())?;
+            enumerator.expand_vertex(vertex, |n| earlier.contains(n));
+            earlier.insert(vertex);
+        }
+    } else {
+        monitor.step(BuildPhase::CliqueEnumeration, 0, 1)?;
+        // Initialize Bron-Kerbosch sets
+        let r = HashSet::new(); // Current clique (empty)
+        let p = graph.keys().copied().collect(); // All vertices as candidates
+        let x = HashSet::new(); // No excluded vertices initially
+        enumerator.expand(r, p, x);
+    }
 
-    bron_kerbosch_pivot(graph, r, p, x, &mut cliques);
-    cliques
+    monitor.step(BuildPhase::CliqueEnumeration, 1, 1)?;
+    Ok(enumerator.cliques)
 }
 
-/// Optimized Bron-Kerbosch implementation with strategic pivoting.
+/// Finds the maximal cliques of an undirected graph which contain `vertex`, but none of the
+/// vertices which are `excluded`.
 ///
-/// This version includes several optimizations:
-/// - Early termination checks
-/// - Optimal pivot selection to minimize branching
-/// - Efficient set operations using iterators where possible
-/// - Memory-conscious cloning patterns
-fn bron_kerbosch_pivot<Id>(
-    graph: &HashMap<Id, HashSet<Id>>,
-    r: HashSet<Id>,
-    mut p: HashSet<Id>,
-    mut x: HashSet<Id>,
-    cliques: &mut Vec<HashSet<Id>>,
-) where
+/// Given an ordering of some vertices, finding the cliques containing each vertex but none of the
+/// vertices before it enumerates every maximal clique containing any of them exactly once. Each
+/// such search is independent, so they can be run in parallel.
+pub fn find_maximal_cliques_containing<Id>(
+    graph: &impl Adjacency<Id>,
+    vertex: Id,
+    excluded: impl Fn(&Id) -> bool,
+    strategy: EnumerationStrategy,
+) -> Vec<HashSet<Id>>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    let mut enumerator = Enumerator::new(graph, strategy, 0);
+    enumerator.expand_vertex(vertex, excluded);
+    enumerator.cliques
+}
+
+/// The state of a single clique enumeration.
+struct Enumerator<'a, Id, G> {
+    graph: &'a G,
+    strategy: EnumerationStrategy,
+    rng: SplitMix64,
+    cliques: Vec<HashSet<Id>>,
+    cancellation: Option<&'a CancellationToken>,
+}
+
+impl<'a, Id, G> Enumerator<'a, Id, G>
+where
     Id: Eq + std::hash::Hash + Copy,
+    G: Adjacency<Id>,
 {
-    // Base case: found a maximal clique
-    if p.is_empty() && x.is_empty() {
-        cliques.push(r);
-        return;
+    fn new(graph: &'a G, strategy: EnumerationStrategy, capacity: usize) -> Self {
+        Self {
+            graph,
+            strategy,
+            rng: SplitMix64(0x2545_F491_4F6C_DD1D),
+            cliques: Vec::with_capacity(capacity),
+            cancellation: None,
+        }
     }
 
-    // Early termination: if P is empty but X is not, no maximal cliques possible
-    if p.is_empty() {
-        return;
+    /// Enumerate the maximal cliques containing `vertex`, but none of the vertices which are
+    /// `excluded`.
+    fn expand_vertex(&mut self, vertex: Id, excluded: impl Fn(&Id) -> bool) {
+        let neighbours = self.graph.get(&vertex).cloned().unwrap_or_default();
+        let (x, p) = neighbours.into_iter().partition(|n| excluded(n));
+        self.expand(HashSet::from([vertex]), p, x);
     }
 
-    // Select optimal pivot to minimize the number of recursive calls
-    let candidates: Vec<_> = select_optimal_pivot(graph, &p, &x)
-        .and_then(|pivot| graph.get(&pivot))
-        // Process only vertices not connected to pivot (key optimization)
-        // Convert to Vec to avoid iterator invalidation during P modification
-        .map(|pivot_neighbors| p.difference(pivot_neighbors).copied().collect())
-        .unwrap_or_default();
+    /// Optimized Bron-Kerbosch implementation with strategic pivoting.
+    ///
+    /// This version includes several optimizations:
+    /// - Early termination checks
+    /// - Pivot selection to minimize branching
+    /// - Efficient set operations using iterators where possible
+    /// - Memory-conscious cloning patterns
+    fn expand(&mut self, r: HashSet<Id>, mut p: HashSet<Id>, mut x: HashSet<Id>) {
+        // Abandon the enumeration once it is cancelled (the caller discards the partial result)
+        if is_cancelled(self.cancellation) {
+            return;
+        }
 
-    for vertex in candidates {
-        // Get vertex neighbors, defaulting to empty set for robustness
-        let neighbors = graph.get(&vertex).cloned().unwrap_or_default();
+        // Base case: found a maximal clique
+        if p.is_empty() && x.is_empty() {
+            self.cliques.push(r);
+            return;
+        }
 
-        // Build next iteration state
-        let mut r_next = r.clone();
-        r_next.insert(vertex);
+        // Early termination: if P is empty but X is not, no maximal cliques possible
+        if p.is_empty() {
+            return;
+        }
 
-        let p_next = p.intersection(&neighbors).copied().collect();
-        let x_next = x.intersection(&neighbors).copied().collect();
+        // Process only vertices not connected to the pivot (key optimization)
+        // Convert to Vec to avoid iterator invalidation during P modification
+        let candidates: Vec<_> = match self.select_pivot(&p, &x) {
+            Some(pivot) => self
+                .graph
+                .get(&pivot)
+                .map(|pivot_neighbors| p.difference(pivot_neighbors).copied().collect())
+                .unwrap_or_default(),
+            None => p.iter().copied().collect(),
+        };
+
+        for vertex in candidates {
+            // Get vertex neighbors, defaulting to empty set for robustness
+            let neighbors = self.graph.get(&vertex).cloned().unwrap_or_default();
+
+            // Build next iteration state
+            let mut r_next = r.clone();
+            r_next.insert(vertex);
+
+            let p_next = p.intersection(&neighbors).copied().collect();
+            let x_next = x.intersection(&neighbors).copied().collect();
+
+            // Recurse
+            self.expand(r_next, p_next, x_next);
+
+            // Update P and X for next iteration (prevents duplicate cliques)
+            p.remove(&vertex);
+            x.insert(vertex);
+        }
+    }
 
-        // Recurse
-        bron_kerbosch_pivot(graph, r_next, p_next, x_next, cliques);
+    /// Selects the pivot vertex, or `None` if the strategy doesn't pivot.
+    fn select_pivot(&mut self, p: &HashSet<Id>, x: &HashSet<Id>) -> Option<Id> {
+        match self.strategy {
+            EnumerationStrategy::MaxDegreePivot | EnumerationStrategy::DegeneracyOrdering => {
+                select_optimal_pivot(self.graph, p, x)
+            }
+            EnumerationStrategy::RandomPivot => {
+                let len = p.len() + x.len();
+                #[allow(clippy::cast_possible_truncation)]
+                let index = (self.rng.next() % len as u64) as usize;
+                p.iter().chain(x).nth(index).copied()
+            }
+            EnumerationStrategy::NoPivot => None,
+        }
+    }
+}
 
-        // Update P and X for next iteration (prevents duplicate cliques)
-        p.remove(&vertex);
-        x.insert(vertex);
+/// A small, fast pseudo-random number generator, used to choose random pivots.
+///
+/// Statistical quality is unimportant here; this just avoids a dependency on a random number crate.
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    const fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
     }
 }
 
@@ -99,7 +259,7 @@ fn bron_kerbosch_pivot<Id>(
 /// - Caches the union computation for efficiency
 /// - Handles empty sets gracefully
 fn select_optimal_pivot<Id>(
-    graph: &HashMap<Id, HashSet<Id>>,
+    graph: &impl Adjacency<Id>,
     p: &HashSet<Id>,
     x: &HashSet<Id>,
 ) -> Option<Id>
@@ -126,6 +286,172 @@ where
         .copied()
 }
 
+/// Orders the vertices of a graph by repeatedly removing a vertex of minimum remaining degree.
+///
+/// Each vertex then has at most `d` neighbours later in the ordering, where `d` is the degeneracy
+/// of the graph.
+fn degeneracy_ordering<Id>(graph: &impl Adjacency<Id>) -> Vec<Id>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    let mut degrees: HashMap<Id, usize> = graph
+        .iter()
+        .map(|(&vertex, neighbours)| (vertex, neighbours.len()))
+        .collect();
+    let max_degree = degrees.values().copied().max().unwrap_or_default();
+
+    // Vertices bucketed by degree. Entries are not removed when a vertex's degree changes, so
+    // stale entries are skipped when they are popped.
+    let mut buckets = vec![Vec::new(); max_degree + 1];
+    for (&vertex, &degree) in &degrees {
+        buckets[degree].push(vertex);
+    }
+
+    let mut removed = HashSet::with_capacity(graph.len());
+    let mut ordering = Vec::with_capacity(graph.len());
+    let mut degree = 0_usize;
+
+    while ordering.len() < degrees.len() {
+        // Removing a vertex lowers its neighbours' degrees by at most one
+        degree = degree.saturating_sub(1);
+        while buckets[degree].is_empty() {
+            degree += 1;
+        }
+        let Some(vertex) = buckets[degree].pop() else {
+            continue;
+        };
+        if removed.contains(&vertex) || degrees[&vertex] != degree {
+            continue;
+        }
+
+        removed.insert(vertex);
+        ordering.push(vertex);
+        for neighbour in &graph[&vertex] {
+            if removed.contains(neighbour) {
+                continue;
+            }
+            if let Some(neighbour_degree) = degrees.get_mut(neighbour) {
+                *neighbour_degree -= 1;
+                buckets[*neighbour_degree].push(*neighbour);
+            }
+        }
+    }
+
+    ordering
+}
+
+/// Finds the connected components of an undirected graph.
+///
+/// Each component is returned as the set of its vertices.
+pub fn connected_components<Id>(graph: &impl Adjacency<Id>) -> Vec<HashSet<Id>>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    let mut visited = HashSet::with_capacity(graph.len());
+    let mut components = Vec::new();
+
+    for &start in graph.keys() {
+        if visited.contains(&start) {
+            continue;
+        }
+        let component = component_containing(graph, start);
+        visited.extend(component.iter().copied());
+        components.push(component);
+    }
+
+    components
+}
+
+/// Finds the set of vertices reachable from `start` (including `start` itself).
+pub fn component_containing<Id>(graph: &impl Adjacency<Id>, start: Id) -> HashSet<Id>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    let mut component = HashSet::from([start]);
+    let mut frontier = vec![start];
+
+    while let Some(vertex) = frontier.pop() {
+        for &neighbour in graph.get(&vertex).into_iter().flatten() {
+            if component.insert(neighbour) {
+                frontier.push(neighbour);
+            }
+        }
+    }
+
+    component
+}
+
+/// Sparsifies an undirected graph, keeping an edge only if each of its endpoints is among the `k`
+/// nearest neighbours of the other.
+///
+/// Neighbours are ranked by `distance`. Neighbours which are as near as the `k`th nearest are
+/// also counted, so that the result doesn't depend on how ties are broken. Vertices left without
+/// any neighbours are removed.
+pub fn mutual_nearest_neighbours<Id>(
+    graph: &HashMap<Id, HashSet<Id>>,
+    k: usize,
+    distance: impl Fn(Id, Id) -> f64,
+) -> HashMap<Id, HashSet<Id>>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    let nearest: HashMap<Id, HashSet<Id>> = graph
+        .iter()
+        .map(|(&vertex, neighbours)| {
+            let mut ranked: Vec<(f64, Id)> = neighbours
+                .iter()
+                .map(|&neighbour| (distance(vertex, neighbour), neighbour))
+                .collect();
+            ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let cutoff = ranked
+                .get(k.saturating_sub(1))
+                .map_or(f64::INFINITY, |(distance, _)| *distance);
+            let nearest = ranked
+                .into_iter()
+                .take_while(|(distance, _)| k > 0 && *distance <= cutoff)
+                .map(|(_, neighbour)| neighbour)
+                .collect();
+            (vertex, nearest)
+        })
+        .collect();
+
+    nearest
+        .iter()
+        .map(|(&vertex, candidates)| {
+            let mutual: HashSet<Id> = candidates
+                .iter()
+                .filter(|neighbour| nearest[*neighbour].contains(&vertex))
+                .copied()
+                .collect();
+            (vertex, mutual)
+        })
+        .filter(|(_, mutual)| !mutual.is_empty())
+        .collect()
+}
+
+/// Computes the density of the subgraph induced by `vertices`.
+///
+/// This is the fraction of all possible edges between the vertices which are present in the graph,
+/// in the range `[0.0, 1.0]`.
+#[allow(clippy::cast_precision_loss)]
+pub fn density<Id>(graph: &impl Adjacency<Id>, vertices: &HashSet<Id>) -> f64
+where
+    Id: Eq + std::hash::Hash,
+{
+    let n = vertices.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let degree_sum: usize = vertices
+        .iter()
+        .filter_map(|vertex| graph.get(vertex))
+        .map(|neighbours| neighbours.intersection(vertices).count())
+        .sum();
+
+    degree_sum as f64 / (n * (n - 1)) as f64
+}
+
 #[cfg(test)]
 mod tests {
     use uuid::Uuid;
@@ -170,9 +496,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn mutual_nearest_neighbours_keep_reciprocated_edges() {
+        // a path 0 - 1 - 2 - 3, with positions 0, 1, 2.5 and 3
+        let (graph, v) = GraphBuilder::with_vertices(4)
+            .add_edge(0, 1)
+            .add_edge(1, 2)
+            .add_edge(2, 3)
+            .build();
+        let position: HashMap<Uuid, f64> = v.iter().copied().zip([0.0, 1.0, 2.5, 3.0]).collect();
+        let distance = |a: Uuid, b: Uuid| (position[&a] - position[&b]).abs();
+
+        let pruned = mutual_nearest_neighbours(&graph, 1, distance);
+        assert_eq!(pruned.len(), 4);
+        assert_eq!(pruned[&v[0]], HashSet::from([v[1]]));
+        assert_eq!(pruned[&v[1]], HashSet::from([v[0]]));
+        assert_eq!(pruned[&v[2]], HashSet::from([v[3]]));
+
+        assert_eq!(mutual_nearest_neighbours(&graph, 2, distance), graph);
+        assert!(mutual_nearest_neighbours(&graph, 0, distance).is_empty());
+    }
+
     #[test]
     fn empty_graph_produces_no_cliques() {
-        let cliques = find_maximal_cliques::<i32>(&HashMap::new());
+        let cliques = find_maximal_cliques::<i32>(&HashMap::new(), EnumerationStrategy::default());
         assert!(cliques.is_empty());
     }
 
@@ -180,7 +527,7 @@ mod tests {
     fn isolated_vertex_forms_singleton_clique() {
         let (graph, vertices) = GraphBuilder::with_vertices(1).build();
 
-        let cliques = find_maximal_cliques(&graph);
+        let cliques = find_maximal_cliques(&graph, EnumerationStrategy::default());
         assert_eq!(cliques.len(), 1);
         assert_eq!(cliques[0].len(), 1);
         assert!(cliques[0].contains(&vertices[0]));
@@ -194,7 +541,7 @@ mod tests {
             .add_edge(2, 0)
             .build();
 
-        let cliques = find_maximal_cliques(&graph);
+        let cliques = find_maximal_cliques(&graph, EnumerationStrategy::default());
         assert_eq!(cliques.len(), 1);
         assert_eq!(cliques[0].len(), 3);
 
@@ -213,7 +560,7 @@ mod tests {
             .add_edge(2, 3)
             .build();
 
-        let cliques = find_maximal_cliques(&graph);
+        let cliques = find_maximal_cliques(&graph, EnumerationStrategy::default());
         assert_eq!(cliques.len(), 3);
 
         // All cliques should be edges (size 2)
@@ -230,7 +577,7 @@ mod tests {
             .add_edge(2, 3)
             .build();
 
-        let cliques = find_maximal_cliques(&graph);
+        let cliques = find_maximal_cliques(&graph, EnumerationStrategy::default());
         assert_eq!(cliques.len(), 2);
 
         for clique in &cliques {
@@ -250,7 +597,7 @@ mod tests {
             .add_edge(2, 3)
             .build();
 
-        let cliques = find_maximal_cliques(&graph);
+        let cliques = find_maximal_cliques(&graph, EnumerationStrategy::default());
         assert_eq!(cliques.len(), 1);
         assert_eq!(cliques[0].len(), 4);
 
@@ -269,12 +616,95 @@ mod tests {
         graph.insert(1, std::iter::once(2).collect());
         // v2 is missing entirely
 
-        let cliques = find_maximal_cliques(&graph);
+        let cliques = find_maximal_cliques(&graph, EnumerationStrategy::default());
 
         // Should handle gracefully without panicking
         assert!(!cliques.is_empty());
     }
 
+    #[test]
+    fn all_strategies_find_the_same_cliques() {
+        // A pseudo-random graph with plenty of overlapping cliques
+        let n = 40;
+        let mut builder = GraphBuilder::with_vertices(n);
+        let mut rng = SplitMix64(42);
+        for u in 0..n {
+            for v in (u + 1)..n {
+                if rng.next() % 4 == 0 {
+                    builder = builder.add_edge(u, v);
+                }
+            }
+        }
+        let (graph, _) = builder.build();
+
+        let normalise = |cliques: Vec<HashSet<Uuid>>| {
+            let mut cliques: Vec<Vec<Uuid>> = cliques
+                .into_iter()
+                .map(|clique| {
+                    let mut clique: Vec<_> = clique.into_iter().collect();
+                    clique.sort_unstable();
+                    clique
+                })
+                .collect();
+            cliques.sort_unstable();
+            cliques
+        };
+
+        let expected = normalise(find_maximal_cliques(
+            &graph,
+            EnumerationStrategy::MaxDegreePivot,
+        ));
+        assert!(expected.len() > n / 2);
+        for strategy in [
+            EnumerationStrategy::RandomPivot,
+            EnumerationStrategy::DegeneracyOrdering,
+            EnumerationStrategy::NoPivot,
+        ] {
+            assert_eq!(
+                normalise(find_maximal_cliques(&graph, strategy)),
+                expected,
+                "{strategy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn degeneracy_ordering_of_a_star() {
+        // The leaves of a star have degree one, so are all removed before the centre
+        let (graph, vertices) = GraphBuilder::with_vertices(5)
+            .add_edge(0, 1)
+            .add_edge(0, 2)
+            .add_edge(0, 3)
+            .add_edge(0, 4)
+            .build();
+
+        let ordering = degeneracy_ordering(&graph);
+
+        assert_eq!(ordering.len(), 5);
+        assert!(ordering[..3].iter().all(|vertex| *vertex != vertices[0]));
+    }
+
+    #[test]
+    fn connected_components_and_density() {
+        // A triangle and a separate edge
+        let (graph, vertices) = GraphBuilder::with_vertices(5)
+            .add_edge(0, 1)
+            .add_edge(1, 2)
+            .add_edge(2, 0)
+            .add_edge(3, 4)
+            .build();
+
+        let components = connected_components(&graph);
+        assert_eq!(components.len(), 2);
+
+        let triangle = component_containing(&graph, vertices[0]);
+        assert_eq!(triangle.len(), 3);
+        assert!((density(&graph, &triangle) - 1.0).abs() < f64::EPSILON);
+
+        let all: HashSet<Uuid> = vertices.into_iter().collect();
+        assert!((density(&graph, &all) - 0.4).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn large_sparse_graph_performance() {
         // Create a graph with multiple disconnected triangles
@@ -292,7 +722,7 @@ mod tests {
         }
 
         let (graph, _) = builder.build();
-        let cliques = find_maximal_cliques(&graph);
+        let cliques = find_maximal_cliques(&graph, EnumerationStrategy::default());
 
         // Should find exactly 333 triangular cliques (999/3)
         assert_eq!(cliques.len(), 333);
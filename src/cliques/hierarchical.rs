@@ -0,0 +1,263 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::seeded::SeededOrder;
+
+/// Partitions the vertices of an undirected graph using complete-linkage agglomerative clustering.
+///
+/// Adjacent vertices are separated by the dissimilarity given by `distance`. Non-adjacent vertices
+/// are treated as infinitely distant, so two clusters can only be merged if every pair of vertices
+/// across them is adjacent. Every resulting cluster is therefore a clique of `graph` (though not
+/// necessarily a maximal one).
+///
+/// Clusters are merged greedily in order of increasing complete-linkage distance until no further
+/// merge is possible. Unlike maximal-clique enumeration, the cost of this is polynomial in the size
+/// of the graph, so it remains tractable on dense graphs.
+///
+/// Clusters containing a single vertex are discarded.
+///
+/// Ties between equally close pairs of clusters are broken by the order of the vertices, which is
+/// arbitrary unless an `order` is given.
+pub fn complete_linkage_clusters<Id>(
+    graph: &HashMap<Id, HashSet<Id>>,
+    distance: impl Fn(Id, Id) -> f64,
+    order: Option<SeededOrder>,
+) -> Vec<HashSet<Id>>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    let mut vertices: Vec<Id> = graph.keys().copied().collect();
+    if let Some(order) = order {
+        order.sort(&mut vertices);
+    }
+    let positions: HashMap<Id, usize> = vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+
+    let mut clusters: Vec<Cluster<Id>> = vertices
+        .iter()
+        .map(|&vertex| Cluster {
+            members: vec![vertex],
+            links: HashMap::new(),
+            version: 0,
+            alive: true,
+        })
+        .collect();
+
+    for (i, vertex) in vertices.iter().enumerate() {
+        for neighbour in &graph[vertex] {
+            // Tolerate neighbours which are missing from the graph's key set
+            let Some(&j) = positions.get(neighbour) else {
+                continue;
+            };
+            if i != j {
+                clusters[i].links.insert(
+                    j,
+                    Link {
+                        max_distance: distance(*vertex, *neighbour),
+                        count: 1,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut candidates = BinaryHeap::new();
+    for (i, cluster) in clusters.iter().enumerate() {
+        for (&j, link) in &cluster.links {
+            if i < j && clusters[j].links.contains_key(&i) {
+                candidates.push(Candidate::new(link.max_distance, i, j, 0, 0));
+            }
+        }
+    }
+
+    while let Some(candidate) = candidates.pop() {
+        let Candidate { a, b, .. } = candidate;
+        if !clusters[a].alive
+            || !clusters[b].alive
+            || clusters[a].version != candidate.version_a
+            || clusters[b].version != candidate.version_b
+        {
+            // Stale candidate, superseded by a later merge
+            continue;
+        }
+
+        merge(&mut clusters, a, b);
+
+        let size_a = clusters[a].members.len();
+        for (&k, link) in &clusters[a].links {
+            if link.count == size_a * clusters[k].members.len() {
+                let (low, high) = if a < k { (a, k) } else { (k, a) };
+                candidates.push(Candidate::new(
+                    link.max_distance,
+                    low,
+                    high,
+                    clusters[low].version,
+                    clusters[high].version,
+                ));
+            }
+        }
+    }
+
+    clusters
+        .into_iter()
+        .filter(|cluster| cluster.alive && cluster.members.len() > 1)
+        .map(|cluster| cluster.members.into_iter().collect())
+        .collect()
+}
+
+/// Merge cluster `b` into cluster `a`, updating the linkage of every neighbouring cluster.
+fn merge<Id>(clusters: &mut [Cluster<Id>], a: usize, b: usize) {
+    let b_members = std::mem::take(&mut clusters[b].members);
+    let b_links = std::mem::take(&mut clusters[b].links);
+    clusters[b].alive = false;
+
+    clusters[a].members.extend(b_members);
+    clusters[a].links.remove(&b);
+    clusters[a].version += 1;
+
+    for (k, link) in b_links {
+        if k == a {
+            continue;
+        }
+        clusters[a]
+            .links
+            .entry(k)
+            .and_modify(|existing| existing.absorb(link))
+            .or_insert(link);
+    }
+
+    let links: Vec<(usize, Link)> = clusters[a].links.iter().map(|(&k, &l)| (k, l)).collect();
+    for (k, link) in links {
+        let neighbour = &mut clusters[k].links;
+        neighbour.remove(&b);
+        neighbour.insert(a, link);
+    }
+}
+
+#[derive(Debug)]
+struct Cluster<Id> {
+    members: Vec<Id>,
+    /// Linkage to each neighbouring cluster, keyed by cluster index
+    links: HashMap<usize, Link>,
+    version: usize,
+    alive: bool,
+}
+
+/// The complete linkage between two clusters.
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    /// The largest distance between any adjacent pair of vertices across the clusters.
+    max_distance: f64,
+    /// The number of adjacent pairs of vertices across the clusters.
+    ///
+    /// The clusters are fully connected if this equals the product of their sizes.
+    count: usize,
+}
+
+impl Link {
+    const fn absorb(&mut self, other: Self) {
+        self.max_distance = self.max_distance.max(other.max_distance);
+        self.count += other.count;
+    }
+}
+
+/// A candidate merge, ordered such that the closest pair is popped from a max-heap first.
+#[derive(Debug)]
+struct Candidate {
+    distance: f64,
+    a: usize,
+    b: usize,
+    version_a: usize,
+    version_b: usize,
+}
+
+impl Candidate {
+    const fn new(distance: f64, a: usize, b: usize, version_a: usize, version_b: usize) -> Self {
+        Self {
+            distance,
+            a,
+            b,
+            version_a,
+            version_b,
+        }
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.a.cmp(&self.a))
+            .then_with(|| other.b.cmp(&self.b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(u32, u32)]) -> HashMap<u32, HashSet<u32>> {
+        let mut graph: HashMap<u32, HashSet<u32>> = HashMap::new();
+        for &(u, v) in edges {
+            graph.entry(u).or_default().insert(v);
+            graph.entry(v).or_default().insert(u);
+        }
+        graph
+    }
+
+    #[test]
+    fn complete_graph_forms_single_cluster() {
+        let graph = graph(&[(0, 1), (0, 2), (1, 2)]);
+
+        let clusters = complete_linkage_clusters(&graph, |_, _| 1.0, None);
+
+        assert_eq!(clusters, vec![HashSet::from([0, 1, 2])]);
+    }
+
+    #[test]
+    fn clusters_partition_vertices_into_cliques() {
+        // Path 0-1-2-3. The closest pair (1, 2) is merged first, leaving 0 and 3 as singletons.
+        let graph = graph(&[(0, 1), (1, 2), (2, 3)]);
+
+        let clusters = complete_linkage_clusters(
+            &graph,
+            |a, b| {
+                if a.min(b) == 1 && a.max(b) == 2 {
+                    0.5
+                } else {
+                    1.0
+                }
+            },
+            None,
+        );
+
+        assert_eq!(clusters, vec![HashSet::from([1, 2])]);
+    }
+
+    #[test]
+    fn merges_closest_pairs_first() {
+        // Two tight pairs (0, 1) and (2, 3), fully connected to each other by longer edges
+        let graph = graph(&[(0, 1), (2, 3), (0, 2), (0, 3), (1, 2), (1, 3)]);
+
+        let clusters =
+            complete_linkage_clusters(&graph, |a, b| if a / 2 == b / 2 { 0.1 } else { 2.0 }, None);
+
+        assert_eq!(clusters, vec![HashSet::from([0, 1, 2, 3])]);
+    }
+}
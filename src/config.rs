@@ -0,0 +1,912 @@
+use nalgebra::{Matrix2, Vector2};
+
+use crate::{
+    CliqueIdAllocation, CliqueOverflow, CovarianceMatrix, DuplicatePolicy, EnumerationStrategy,
+    EvictionPolicy, Observation, seeded::SeededOrder,
+};
+
+/// Configuration for a [`CliqueIndex`](crate::CliqueIndex).
+///
+/// # Example
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Config, DensityFallback};
+///
+/// let config = Config::new(CHI2_2D_CONFIDENCE_95).density_fallback(DensityFallback {
+///     max_density: 0.5,
+///     min_component_size: 50,
+/// });
+///
+/// let index: CliqueIndex<u32> = CliqueIndex::with_config(config);
+/// ```
+#[must_use]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Config {
+    pub(crate) chi2: f64,
+    pub(crate) density_fallback: Option<DensityFallback>,
+    pub(crate) duplicate_policy: DuplicatePolicy,
+    pub(crate) enumeration_strategy: EnumerationStrategy,
+    pub(crate) altitude_gate: f64,
+    pub(crate) motion_model: MotionModel,
+    pub(crate) borderline_band: f64,
+    pub(crate) min_separation: f64,
+    pub(crate) clique_history: Option<usize>,
+    pub(crate) max_neighbourhood: Option<usize>,
+    pub(crate) mutual_nearest: Option<usize>,
+    pub(crate) capacity: Option<(usize, EvictionPolicy)>,
+    pub(crate) clique_capacity: Option<(usize, CliqueOverflow)>,
+    pub(crate) seed: Option<u64>,
+    pub(crate) registration_error: Option<CovarianceMatrix>,
+    pub(crate) clique_ids: CliqueIdAllocation,
+    pub(crate) singular_covariance: SingularCovariance,
+    pub(crate) approximate_search: Option<f64>,
+    pub(crate) one_dimensional: bool,
+    pub(crate) directional_gate: Option<DirectionalGate>,
+}
+
+impl Config {
+    /// Construct a new configuration with a given confidence interval, defined by a Chi2 parameter.
+    ///
+    /// For untrusted input, use [`Self::try_new`].
+    ///
+    /// # Panics
+    ///
+    /// This method panics in debug builds if [`Self::try_new`] would have returned an error.
+    ///
+    /// In release builds no checking is done.
+    pub const fn new(chi2: f64) -> Self {
+        debug_assert!(is_valid_chi2(chi2), "chi2 must be finite and positive");
+        Self::new_unchecked(chi2)
+    }
+
+    /// Construct a new configuration with a given confidence interval, defined by a Chi2 parameter,
+    /// checking that the parameter is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `chi2` is not finite and strictly positive.
+    pub const fn try_new(chi2: f64) -> Result<Self, InvalidChi2> {
+        if is_valid_chi2(chi2) {
+            Ok(Self::new_unchecked(chi2))
+        } else {
+            Err(InvalidChi2(chi2))
+        }
+    }
+
+    const fn new_unchecked(chi2: f64) -> Self {
+        Self {
+            chi2,
+            density_fallback: None,
+            duplicate_policy: DuplicatePolicy::Keep,
+            enumeration_strategy: EnumerationStrategy::MaxDegreePivot,
+            altitude_gate: 3.0,
+            motion_model: MotionModel::Static,
+            borderline_band: 0.0,
+            min_separation: 0.0,
+            clique_history: None,
+            max_neighbourhood: None,
+            mutual_nearest: None,
+            capacity: None,
+            clique_capacity: None,
+            seed: None,
+            registration_error: None,
+            clique_ids: CliqueIdAllocation::Monotonic,
+            singular_covariance: SingularCovariance::PseudoInverse,
+            approximate_search: None,
+            one_dimensional: false,
+            directional_gate: None,
+        }
+    }
+
+    /// Fall back to approximate clustering for regions of the compatibility graph which are too dense
+    /// to enumerate maximal cliques in a reasonable time.
+    ///
+    /// See [`DensityFallback`].
+    pub const fn density_fallback(mut self, fallback: DensityFallback) -> Self {
+        self.density_fallback = Some(fallback);
+        self
+    }
+
+    /// Limit the number of compatible neighbours of an observation for which cliques are
+    /// enumerated exactly. Unlimited by default.
+    ///
+    /// Inserting an observation recomputes the cliques among its neighbours, so an observation
+    /// next to a hub with thousands of neighbours can stall an insertion for seconds. Instead, a
+    /// connected component of the compatibility graph containing an observation with more than
+    /// `max_neighbours` neighbours is clustered approximately, as for a [`DensityFallback`].
+    pub const fn max_neighbourhood(mut self, max_neighbours: usize) -> Self {
+        self.max_neighbourhood = Some(max_neighbours);
+        self
+    }
+
+    /// Add a shared registration error to every pairwise compatibility test. None by default.
+    ///
+    /// This represents error which is common to a whole set of observations, such as the position
+    /// uncertainty of the platform they were made from, or the error in registering one map to
+    /// another. Unlike the error of each observation, which is counted once per observation, the
+    /// registration error is counted once per pair, so that it isn't double-counted in the combined
+    /// error `Σa + Σb + Σr`. It doesn't change the stored error of any observation, and plays no
+    /// part in fusion.
+    pub const fn registration_error(mut self, error: CovarianceMatrix) -> Self {
+        self.registration_error = Some(error);
+        self
+    }
+
+    /// How pairs of observations whose combined error is singular (such as two exact positions)
+    /// are tested for compatibility. See [`SingularCovariance`]. Inverted with the pseudo-inverse by
+    /// default.
+    pub const fn singular_covariance(mut self, policy: SingularCovariance) -> Self {
+        self.singular_covariance = policy;
+        self
+    }
+
+    /// Test pairs of observations against separate chi-squared thresholds along and across a
+    /// track, rather than a single isotropic threshold. Isotropic by default.
+    ///
+    /// This suits sensors whose error is well-modelled in one direction but not the other, such
+    /// as a vehicle whose along-track error is well understood but whose cross-track drift is
+    /// not.
+    ///
+    /// The squared Mahalanobis distance of a pair is the sum of two independent terms, each
+    /// chi-squared with one degree of freedom: that of the residual along the track, and that of
+    /// the residual across the track, conditioned on the residual along it. Each term is divided
+    /// by its own threshold, so that a pair is compatible if
+    ///
+    /// `d²_along / along_track + d²_cross|along / cross_track <= 1`.
+    ///
+    /// A looser threshold in one direction then admits proportionately larger residuals in that
+    /// direction. If both thresholds are equal, this is the usual test against that threshold.
+    /// The distance of a compatible pair is always within the larger of the two thresholds, so
+    /// the search for candidates is widened to that threshold (if it exceeds `chi2`).
+    ///
+    /// Once a gate is set, the thresholds replace `chi2`, which no longer affects compatibility:
+    /// changing it with [`CliqueIndex::set_chi2`](crate::CliqueIndex::set_chi2) only widens or
+    /// narrows the search. The squared distances reported for pairs are scaled by `chi2`, so that
+    /// they are compared against `chi2`, and the [borderline band](Self::borderline_band) is
+    /// relative to the thresholds. Pairs whose combined error is singular, or which have no track
+    /// direction, are tested against `chi2` as usual. Ignored for
+    /// [one-dimensional](Self::one_dimensional) configurations.
+    ///
+    /// ```
+    /// use clique_fusion::{
+    ///     CHI2_2D_CONFIDENCE_90, CHI2_2D_CONFIDENCE_95, CHI2_2D_CONFIDENCE_99, CliqueIndex, Config,
+    ///     Observation, TrackDirection, Unique,
+    /// };
+    ///
+    /// // a track along the x axis, with a looser gate along it than across it
+    /// let config = Config::new(CHI2_2D_CONFIDENCE_95).directional_gate(
+    ///     CHI2_2D_CONFIDENCE_99,
+    ///     CHI2_2D_CONFIDENCE_90,
+    ///     TrackDirection::Fixed { x: 1.0, y: 0.0 },
+    /// );
+    /// let mut index = CliqueIndex::with_config(config);
+    /// for (id, x) in [(1, 0.0), (2, 1.5)] {
+    ///     let observation = Observation::builder(x, 0.0)
+    ///         .circular_95_confidence_error(1.0)
+    ///         .unwrap()
+    ///         .build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    ///
+    /// // too far apart for the isotropic threshold, but within the along-track threshold
+    /// assert_eq!(index.cliques().len(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics in debug builds if either threshold is not finite and strictly positive.
+    pub const fn directional_gate(
+        mut self,
+        along_track: f64,
+        cross_track: f64,
+        direction: TrackDirection,
+    ) -> Self {
+        debug_assert!(
+            is_valid_chi2(along_track) && is_valid_chi2(cross_track),
+            "chi2 thresholds must be finite and positive"
+        );
+        self.directional_gate = Some(DirectionalGate {
+            along_track,
+            cross_track,
+            direction,
+        });
+        self
+    }
+
+    /// The squared Mahalanobis distance of a pair under the [directional
+    /// gate](Self::directional_gate), if one is set and applies to the pair.
+    pub(crate) fn directional_distance_squared(
+        &self,
+        observations: [&Observation; 2],
+        delta: Vector2<f64>,
+        error: CovarianceMatrix,
+    ) -> Option<f64> {
+        match self.directional_gate {
+            Some(gate) if !self.one_dimensional => {
+                gate.distance_squared(observations, delta, error, self.chi2)
+            }
+            _ => None,
+        }
+    }
+
+    /// The factor by which the squared Mahalanobis distance (under the combined error) of a
+    /// compatible pair may exceed `chi2`, under the [directional gate](Self::directional_gate).
+    ///
+    /// The spatial index widens its search by this factor, so that it finds every pair which is
+    /// compatible along or across the track.
+    pub(crate) fn gate_widening(&self) -> f64 {
+        self.directional_gate
+            .map_or(1.0, |gate| gate.widening(self.chi2))
+    }
+
+    /// The chi-squared threshold which the envelopes in the spatial index must allow for, including
+    /// the [borderline band](Self::borderline_band) and [directional gate](Self::directional_gate).
+    pub(crate) fn search_chi2(&self) -> f64 {
+        self.borderline_bounds().1 * self.gate_widening()
+    }
+
+    /// The error common to every pair of observations which the spatial index must allow for when
+    /// searching for candidates, including the regularisation of singular errors, if any.
+    pub(crate) fn shared_error(&self) -> Option<CovarianceMatrix> {
+        let SingularCovariance::Exact { epsilon } = self.singular_covariance else {
+            return self.registration_error;
+        };
+        let regularisation = CovarianceMatrix::new_unchecked(epsilon, epsilon, 0.0);
+        Some(
+            self.registration_error
+                .map_or(regularisation, |error| error + regularisation),
+        )
+    }
+
+    /// Prune the compatibility graph before cliques are enumerated, keeping an edge only if each
+    /// observation is among the `k` statistically nearest neighbours of the other. Unpruned by
+    /// default.
+    ///
+    /// In clutter, an observation may be compatible with many others which are only marginally
+    /// within the threshold. Pruning the edges to all but the nearest of them keeps cliques tight,
+    /// and bounds the degree of every observation by `k`. Neighbours are ranked by squared
+    /// Mahalanobis distance, and those tied with the `k`th nearest are kept too.
+    ///
+    /// Pruning only affects clique enumeration: the
+    /// [compatibility graph](crate::CliqueIndex::compatibility_graph) is unchanged.
+    pub const fn mutual_nearest_neighbours(mut self, k: usize) -> Self {
+        self.mutual_nearest = Some(k);
+        self
+    }
+
+    /// Set how observations which exactly duplicate an existing observation are handled on ingest.
+    ///
+    /// See [`DuplicatePolicy`].
+    pub const fn duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    /// Set the strategy used to enumerate maximal cliques.
+    ///
+    /// See [`EnumerationStrategy`].
+    pub const fn enumeration_strategy(mut self, strategy: EnumerationStrategy) -> Self {
+        self.enumeration_strategy = strategy;
+        self
+    }
+
+    /// Set the number of standard deviations by which the altitudes of two observations may differ
+    /// before they are considered incompatible. Defaults to `3.0`.
+    ///
+    /// This only applies to pairs of observations which both have an
+    /// [altitude](crate::Observation::altitude).
+    pub const fn altitude_gate(mut self, sigma_bound: f64) -> Self {
+        self.altitude_gate = sigma_bound;
+        self
+    }
+
+    /// Set the motion model used to compare observations made at different times.
+    ///
+    /// See [`MotionModel`].
+    pub const fn motion_model(mut self, model: MotionModel) -> Self {
+        self.motion_model = model;
+        self
+    }
+
+    /// Compare only the x ordinates of observations, for fusing one-dimensional observations
+    /// along a track, such as detections along a pipeline or road. Two-dimensional by default.
+    ///
+    /// Observations should be constructed with [`Observation::along_track`](crate::Observation::along_track),
+    /// and the threshold should have one degree of freedom, such as
+    /// [`CHI2_1D_CONFIDENCE_95`](crate::CHI2_1D_CONFIDENCE_95).
+    ///
+    /// The error of a one-dimensional observation is zero across the track, but this is not
+    /// treated as [singular](Self::singular_covariance): only a zero variance along the track is.
+    pub const fn one_dimensional(mut self) -> Self {
+        self.one_dimensional = true;
+        self
+    }
+
+    /// Returns `true` if the error is zero in some direction which is compared, so that the
+    /// Mahalanobis distance is undefined. See [`SingularCovariance`].
+    pub(crate) fn is_singular(&self, error: &CovarianceMatrix) -> bool {
+        if self.one_dimensional {
+            error.xx() <= 0.0
+        } else {
+            error.is_singular()
+        }
+    }
+
+    /// Build the compatibility graph of a batch of observations approximately, trading
+    /// completeness for speed on very large batches. Exact by default.
+    ///
+    /// Instead of searching the compatibility envelope of each observation, the observations are
+    /// bucketed into a grid, and each is only compared with those in its own and adjacent cells.
+    /// The cells are sized by the error of all but the `miss_rate` fraction of observations with
+    /// the largest errors, so that a few very uncertain observations don't inflate the search for
+    /// every other.
+    ///
+    /// A compatible pair can only be missed if one of its observations is among those with the
+    /// largest errors, so at most a `miss_rate` fraction of the observations can lose any edges.
+    /// Pairs of the other observations are found exactly.
+    ///
+    /// This applies when an index is built from a batch of observations (or rebuilt, for example
+    /// when its threshold changes), and only under [`MotionModel::Static`]. Observations inserted
+    /// individually are always compared exactly.
+    ///
+    /// # Panics
+    ///
+    /// This method panics in debug builds if `miss_rate` is not within `0.0..1.0`.
+    pub const fn approximate_search(mut self, miss_rate: f64) -> Self {
+        debug_assert!(
+            miss_rate >= 0.0 && miss_rate < 1.0,
+            "miss rate must be within 0.0..1.0"
+        );
+        self.approximate_search = Some(miss_rate);
+        self
+    }
+
+    /// Set the relative width of the band around the chi-squared threshold within which pairs of
+    /// observations are considered borderline. Defaults to `0.0`.
+    ///
+    /// A pair is borderline if its squared Mahalanobis distance is within `chi2 * (1 ± tolerance)`.
+    /// This does not change which observations are joined into cliques, but borderline pairs
+    /// (including those just outside the threshold) can be retrieved for review with
+    /// [`CliqueIndex::borderline_pairs`](crate::CliqueIndex::borderline_pairs).
+    pub const fn borderline_band(mut self, tolerance: f64) -> Self {
+        self.borderline_band = tolerance;
+        self
+    }
+
+    /// Set the distance within which two observations in the same context are flagged as suspected
+    /// duplicates. Defaults to `0.0` (disabled).
+    ///
+    /// Observations in the same context are never joined into cliques, but a pair which is
+    /// implausibly close (for example, a detection which has been replayed, or reported twice) is
+    /// likely to be a single detection. Such pairs can be retrieved for ingest-quality monitoring
+    /// with [`CliqueIndex::suspected_duplicates`](crate::CliqueIndex::suspected_duplicates).
+    pub const fn min_separation(mut self, epsilon: f64) -> Self {
+        self.min_separation = epsilon;
+        self
+    }
+
+    /// Record the history of the membership of each clique, retaining at most `max_changes` of the
+    /// most recent changes per clique. Histories are not recorded by default.
+    ///
+    /// See [`CliqueIndex::clique_history`](crate::CliqueIndex::clique_history).
+    pub const fn clique_history(mut self, max_changes: usize) -> Self {
+        self.clique_history = Some(max_changes);
+        self
+    }
+
+    /// Set how the stable identifiers of cliques are allocated, and whether they are reused once
+    /// their cliques dissolve. Monotonic by default, so identifiers are never reused.
+    ///
+    /// See [`CliqueIdAllocation`].
+    pub const fn clique_ids(mut self, allocation: CliqueIdAllocation) -> Self {
+        self.clique_ids = allocation;
+        self
+    }
+
+    /// Bound the number of observations in the index. Unbounded by default.
+    ///
+    /// Whenever an insertion takes the index over `max_observations`, observations are evicted
+    /// according to the policy until it is back within capacity, and the cliques of the affected
+    /// regions are repaired as for [`CliqueIndex::remove`](crate::CliqueIndex::remove). This
+    /// guarantees a ceiling on the memory used by the index.
+    ///
+    /// See [`EvictionPolicy`].
+    pub const fn capacity(mut self, max_observations: usize, policy: EvictionPolicy) -> Self {
+        self.capacity = Some((max_observations, policy));
+        self
+    }
+
+    /// Bound the number of cliques in the index. Unbounded by default.
+    ///
+    /// A burst of clutter can produce a very large number of small cliques. Whenever an operation
+    /// would take the index over `max_cliques`, cliques are dropped (or the operation refused)
+    /// according to the policy, so that memory use degrades predictably. Dropped cliques are
+    /// [dissolved](crate::CliqueEvent::Dissolved), and
+    /// [`CliqueIndex::is_truncated`](crate::CliqueIndex::is_truncated) reports that the cliques
+    /// are incomplete.
+    ///
+    /// See [`CliqueOverflow`].
+    pub const fn clique_capacity(mut self, max_cliques: usize, policy: CliqueOverflow) -> Self {
+        self.clique_capacity = Some((max_cliques, policy));
+        self
+    }
+
+    /// Make outputs exactly reproducible, by ordering them (and breaking ties between equally good
+    /// alternatives) according to `seed`. Unseeded by default.
+    ///
+    /// By default, the order of the cliques and components of an index, and the choices made by
+    /// heuristics such as the approximate clustering of a [`DensityFallback`], depend on the
+    /// iteration order of hash maps, which varies from run to run. With a seed, the same
+    /// observations and operations produce exactly the same outputs on every run and platform
+    /// (given a deterministic [`Hash`] implementation for the IDs). The order is arbitrary, but
+    /// fixed by the seed.
+    ///
+    /// This affects:
+    /// - the order of the [cliques](crate::CliqueIndex::cliques) (and hence of their identifiers,
+    ///   fused estimates and centroids), and of the [components](crate::CliqueIndex::components)
+    /// - the partition chosen by approximate clustering, when clusters are equally close
+    /// - the order of the observations in an [`Assignment`](crate::Assignment), and the choice
+    ///   between assignments of equal cost
+    pub const fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The chi-squared threshold used to test compatibility.
+    ///
+    /// This has no effect on compatibility under a [directional gate](Self::directional_gate),
+    /// whose thresholds replace it.
+    #[must_use]
+    pub const fn chi2(&self) -> f64 {
+        self.chi2
+    }
+
+    /// Read back the settings of the configuration.
+    ///
+    /// The builder methods take the plain names of the settings, so they are read through a
+    /// [`ConfigView`] instead.
+    #[must_use]
+    pub const fn view(&self) -> ConfigView<'_> {
+        ConfigView { config: self }
+    }
+
+    /// The order of outputs, if it is seeded. See [`Self::seed`].
+    pub(crate) const fn seeded_order(&self) -> Option<SeededOrder> {
+        match self.seed {
+            Some(seed) => Some(SeededOrder::new(seed)),
+            None => None,
+        }
+    }
+
+    /// Returns `true` if cliques may be approximated, rather than enumerated exactly.
+    pub(crate) const fn may_approximate(&self) -> bool {
+        self.density_fallback.is_some() || self.max_neighbourhood.is_some()
+    }
+
+    /// Returns `true` if the cliques of each connected component of the compatibility graph are
+    /// computed as a whole, rather than incrementally around each change.
+    pub(crate) const fn clusters_components(&self) -> bool {
+        self.may_approximate() || self.mutual_nearest.is_some()
+    }
+
+    /// The lower and upper bounds of the borderline band. See [`Self::borderline_band`].
+    pub(crate) fn borderline_bounds(&self) -> (f64, f64) {
+        let tolerance = self.borderline_band.max(0.0);
+        (self.chi2 * (1.0 - tolerance), self.chi2 * (1.0 + tolerance))
+    }
+
+    /// Classify a pair of observations by their squared Mahalanobis distance.
+    pub(crate) fn classify(&self, mahalanobis_squared: f64) -> Compatibility {
+        let (lower, upper) = self.borderline_bounds();
+        if mahalanobis_squared > upper {
+            Compatibility::Incompatible
+        } else if mahalanobis_squared < lower {
+            Compatibility::Compatible
+        } else {
+            Compatibility::Borderline
+        }
+    }
+}
+
+/// Read access to the settings of a [`Config`], whose builder methods take the plain names.
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, Config, DuplicatePolicy};
+///
+/// let config = Config::new(CHI2_2D_CONFIDENCE_95).duplicate_policy(DuplicatePolicy::Skip);
+/// assert_eq!(config.view().duplicate_policy(), DuplicatePolicy::Skip);
+/// ```
+///
+/// See [`Config::view`].
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigView<'a> {
+    config: &'a Config,
+}
+
+impl ConfigView<'_> {
+    /// The chi-squared threshold used to test compatibility. See [`Config::chi2`].
+    #[must_use]
+    pub const fn chi2(&self) -> f64 {
+        self.config.chi2
+    }
+
+    /// The density fallback, if any. See [`Config::density_fallback`].
+    #[must_use]
+    pub const fn density_fallback(&self) -> Option<DensityFallback> {
+        self.config.density_fallback
+    }
+
+    /// The maximum neighbourhood for exact clique enumeration, if any. See
+    /// [`Config::max_neighbourhood`].
+    #[must_use]
+    pub const fn max_neighbourhood(&self) -> Option<usize> {
+        self.config.max_neighbourhood
+    }
+
+    /// The registration error shared by every pair of observations, if any. See
+    /// [`Config::registration_error`].
+    #[must_use]
+    pub const fn registration_error(&self) -> Option<CovarianceMatrix> {
+        self.config.registration_error
+    }
+
+    /// How pairs whose combined error is singular are tested. See
+    /// [`Config::singular_covariance`].
+    #[must_use]
+    pub const fn singular_covariance(&self) -> SingularCovariance {
+        self.config.singular_covariance
+    }
+
+    /// The number of nearest neighbours kept by mutual nearest neighbour pruning, if any. See
+    /// [`Config::mutual_nearest_neighbours`].
+    #[must_use]
+    pub const fn mutual_nearest_neighbours(&self) -> Option<usize> {
+        self.config.mutual_nearest
+    }
+
+    /// How exact duplicates are handled on ingest. See [`Config::duplicate_policy`].
+    #[must_use]
+    pub const fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.config.duplicate_policy
+    }
+
+    /// The strategy for enumerating maximal cliques. See [`Config::enumeration_strategy`].
+    #[must_use]
+    pub const fn enumeration_strategy(&self) -> EnumerationStrategy {
+        self.config.enumeration_strategy
+    }
+
+    /// The number of standard deviations by which the altitudes of compatible observations may
+    /// differ. See [`Config::altitude_gate`].
+    #[must_use]
+    pub const fn altitude_gate(&self) -> f64 {
+        self.config.altitude_gate
+    }
+
+    /// The motion model used to test compatibility. See [`Config::motion_model`].
+    #[must_use]
+    pub const fn motion_model(&self) -> MotionModel {
+        self.config.motion_model
+    }
+
+    /// The miss rate of the approximate neighbour search, if enabled. See
+    /// [`Config::approximate_search`].
+    #[must_use]
+    pub const fn approximate_search(&self) -> Option<f64> {
+        self.config.approximate_search
+    }
+
+    /// The relative tolerance around the chi-squared threshold within which pairs are
+    /// borderline. See [`Config::borderline_band`].
+    #[must_use]
+    pub const fn borderline_band(&self) -> f64 {
+        self.config.borderline_band
+    }
+
+    /// The distance within which observations in the same context are suspected duplicates. See
+    /// [`Config::min_separation`].
+    #[must_use]
+    pub const fn min_separation(&self) -> f64 {
+        self.config.min_separation
+    }
+
+    /// The maximum number of membership changes recorded per clique, if history is kept. See
+    /// [`Config::clique_history`].
+    #[must_use]
+    pub const fn clique_history(&self) -> Option<usize> {
+        self.config.clique_history
+    }
+
+    /// How clique identifiers are allocated. See [`Config::clique_ids`].
+    #[must_use]
+    pub const fn clique_ids(&self) -> CliqueIdAllocation {
+        self.config.clique_ids
+    }
+
+    /// The maximum number of observations and the eviction policy, if bounded. See
+    /// [`Config::capacity`].
+    #[must_use]
+    pub const fn capacity(&self) -> Option<(usize, EvictionPolicy)> {
+        self.config.capacity
+    }
+
+    /// The maximum number of cliques and the overflow policy, if bounded. See
+    /// [`Config::clique_capacity`].
+    #[must_use]
+    pub const fn clique_capacity(&self) -> Option<(usize, CliqueOverflow)> {
+        self.config.clique_capacity
+    }
+
+    /// The seed for the order of outputs, if any. See [`Config::seed`].
+    #[must_use]
+    pub const fn seed(&self) -> Option<u64> {
+        self.config.seed
+    }
+
+    /// Whether observations are compared along the x axis only. See
+    /// [`Config::one_dimensional`].
+    #[must_use]
+    pub const fn one_dimensional(&self) -> bool {
+        self.config.one_dimensional
+    }
+
+    /// The along-track and cross-track thresholds and the track direction of the directional
+    /// gate, if any. See [`Config::directional_gate`].
+    #[must_use]
+    pub const fn directional_gate(&self) -> Option<(f64, f64, TrackDirection)> {
+        match self.config.directional_gate {
+            Some(DirectionalGate {
+                along_track,
+                cross_track,
+                direction,
+            }) => Some((along_track, cross_track, direction)),
+            None => None,
+        }
+    }
+}
+
+const fn is_valid_chi2(chi2: f64) -> bool {
+    chi2.is_finite() && chi2 > 0.0
+}
+
+/// The error returned when a chi-squared threshold is not finite and strictly positive.
+#[derive(Debug, thiserror::Error, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[error("chi2 threshold must be finite and positive (got {0})")]
+pub struct InvalidChi2(f64);
+
+/// Limits beyond which maximal-clique enumeration is replaced by hierarchical clustering.
+///
+/// The number of maximal cliques in a graph can grow exponentially with its size, and dense,
+/// near-complete regions of the compatibility graph (for example, in heavy clutter) can cause
+/// enumeration to effectively never terminate.
+///
+/// When a connected component of the compatibility graph has at least `min_component_size`
+/// observations and a density greater than `max_density`, its cliques are instead approximated by
+/// complete-linkage agglomerative clustering on the Mahalanobis distances between observations.
+/// This partitions the component into mutually compatible groups, in polynomial time.
+///
+/// The approximated cliques are always mutually compatible, but are not necessarily maximal, and
+/// each observation belongs to at most one of them.
+/// Use [`CliqueIndex::is_approximate`](crate::CliqueIndex::is_approximate) to determine whether
+/// the fallback is in effect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DensityFallback {
+    /// The maximum density (fraction of possible edges present, in the range `[0.0, 1.0]`) of a
+    /// connected component for which maximal cliques are enumerated exactly.
+    pub max_density: f64,
+
+    /// The minimum number of observations in a connected component before the fallback is considered.
+    ///
+    /// Small components are always enumerated exactly, since this is cheap regardless of their density.
+    pub min_component_size: usize,
+}
+
+impl DensityFallback {
+    /// Returns `true` if the component described should be clustered approximately.
+    pub(crate) fn applies(&self, component_size: usize, density: f64) -> bool {
+        component_size >= self.min_component_size && density > self.max_density
+    }
+}
+
+/// The classification of a pair of observations, relative to the borderline band around the
+/// chi-squared threshold.
+///
+/// See [`Config::borderline_band`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compatibility {
+    /// The pair is compatible, and clear of the borderline band.
+    Compatible,
+
+    /// The pair lies within the borderline band, so may be compatible or incompatible.
+    Borderline,
+
+    /// The pair is incompatible, and clear of the borderline band.
+    Incompatible,
+}
+
+/// How the motion of observed objects is accounted for when testing compatibility.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MotionModel {
+    /// Observed objects are assumed to be stationary, and observations are compared directly.
+    #[default]
+    Static,
+
+    /// Observed objects are assumed to move with constant velocity.
+    ///
+    /// When two observations both have a [timestamp](crate::Observation::timestamp), and at least
+    /// one has a [velocity](crate::Observation::velocity), the older observation is propagated to
+    /// the time of the newer one before they are compared. The uncertainty of the prediction grows
+    /// with the velocity error and the process noise.
+    ///
+    /// Other pairs of observations are compared directly, as for [`MotionModel::Static`].
+    ConstantVelocity {
+        /// The spectral density of the (white noise) acceleration of observed objects, in
+        /// position units² per time unit³. This accounts for deviation from constant velocity.
+        process_noise: f64,
+    },
+
+    /// Observed objects are assumed to wander, so that the older an observation is, the less it
+    /// says about the current position of the object.
+    ///
+    /// When two observations both have a [timestamp](crate::Observation::timestamp), the error of
+    /// the older one is grown by `Q ⋅ Δt` in each axis before they are compared, where `Δt` is the
+    /// difference between their timestamps. This loosens the gate between old and new
+    /// observations without changing the stored error of either, and is suited to fusing
+    /// asynchronous detections of slowly drifting objects, which don't report a velocity.
+    ///
+    /// Other pairs of observations are compared directly, as for [`MotionModel::Static`].
+    Ageing {
+        /// The growth `Q` in the variance of an observation's error per unit of its age, in
+        /// position units² per time unit.
+        process_noise: f64,
+    },
+}
+
+/// The direction of the track along which a [directional gate](Config::directional_gate) is
+/// oriented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TrackDirection {
+    /// A fixed direction, common to every pair of observations, such as the axis of a road or
+    /// survey line. The vector need not be normalised, but must be non-zero.
+    Fixed {
+        /// The x component of the direction.
+        x: f64,
+
+        /// The y component of the direction.
+        y: f64,
+    },
+
+    /// The direction of travel of the observations, given by the sum of their
+    /// [velocities](crate::Observation::velocity).
+    ///
+    /// Pairs where neither observation has a velocity (or whose velocities cancel out) are tested
+    /// against the isotropic threshold.
+    Velocity,
+}
+
+/// Separate chi-squared thresholds along and across a track. See [`Config::directional_gate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[allow(clippy::redundant_pub_crate)]
+pub(crate) struct DirectionalGate {
+    along_track: f64,
+    cross_track: f64,
+    direction: TrackDirection,
+}
+
+impl DirectionalGate {
+    /// The factor by which the squared Mahalanobis distance of a compatible pair may exceed
+    /// `chi2`.
+    ///
+    /// The gated distance lies between `chi2 / max(along_track, cross_track)` and
+    /// `chi2 / min(along_track, cross_track)` times the Mahalanobis distance, since each of its
+    /// terms is scaled by one or the other.
+    fn widening(&self, chi2: f64) -> f64 {
+        (self.along_track.max(self.cross_track) / chi2).max(1.0)
+    }
+
+    /// The squared distance of a pair, scaled so that it is compared against `chi2`.
+    ///
+    /// The combined error is rotated into the frame of the track and factorised, along the track
+    /// first, to whiten the residual. The two whitened components are independent standard normal
+    /// variables for a compatible pair, so dividing their squares by separate thresholds is a
+    /// consistent chi-squared test, and the result is bounded by the Mahalanobis distance as for
+    /// [`Self::widening`].
+    ///
+    /// Returns `None` if the direction of the track is undefined, or the combined error is
+    /// singular in the frame of the track.
+    fn distance_squared(
+        &self,
+        [a, b]: [&Observation; 2],
+        delta: Vector2<f64>,
+        error: CovarianceMatrix,
+        chi2: f64,
+    ) -> Option<f64> {
+        let direction = match self.direction {
+            TrackDirection::Fixed { x, y } => Vector2::new(x, y),
+            TrackDirection::Velocity => [a, b]
+                .into_iter()
+                .filter_map(Observation::velocity)
+                .map(|(vx, vy)| Vector2::new(vx, vy))
+                .sum(),
+        };
+        let along = direction.try_normalize(0.0)?;
+        let cross = Vector2::new(-along.y, along.x);
+        let error = Matrix2::from(error);
+        let along_variance = along.dot(&(error * along));
+        let covariance = along.dot(&(error * cross));
+        if along_variance <= 0.0 {
+            return None;
+        }
+        let conditional_variance =
+            covariance.mul_add(-covariance / along_variance, cross.dot(&(error * cross)));
+        if conditional_variance <= 0.0 {
+            return None;
+        }
+        let (along_residual, cross_residual) = (along.dot(&delta), cross.dot(&delta));
+        let along_term = along_residual * along_residual / along_variance;
+        let conditional_residual =
+            (covariance / along_variance).mul_add(-along_residual, cross_residual);
+        let cross_term = conditional_residual * conditional_residual / conditional_variance;
+        Some(chi2 * (along_term / self.along_track + cross_term / self.cross_track))
+    }
+}
+
+/// How pairs of observations whose combined error is singular are tested for compatibility.
+///
+/// The combined error of a pair is singular if it is zero in some direction, for example if both
+/// observations have a zero covariance (exact positions), or one is exact and the other's
+/// confidence ellipse is a degenerate line. The Mahalanobis distance is then undefined.
+///
+/// See [`Config::singular_covariance`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SingularCovariance {
+    /// The combined error is inverted with its pseudo-inverse, which ignores any difference
+    /// between the positions in the directions with zero error. If the combined error is zero,
+    /// the pair is never compatible, even if the positions coincide.
+    ///
+    /// This can make pairs compatible which are far apart, so is only suitable if singular errors
+    /// are known not to occur.
+    #[default]
+    PseudoInverse,
+
+    /// The observations are treated as exact in the directions with zero error, up to a small
+    /// variance `epsilon` which is added to the combined error in every direction.
+    ///
+    /// Two exact positions are then compatible only if they are within `√(χ² ε)` of each other.
+    /// Pairs whose combined error is not singular are unaffected.
+    Exact {
+        /// The variance added to a singular combined error in each axis, in position units².
+        epsilon: f64,
+    },
+
+    /// Observations with a singular covariance are rejected by
+    /// [`CliqueIndex::try_insert`](crate::CliqueIndex::try_insert), unless they are
+    /// [anchors](crate::Observation::is_anchor), whose positions are known exactly. The combined
+    /// error of every pair except two anchors is then invertible, and two exact anchors are never
+    /// compatible.
+    ///
+    /// Observations which are inserted without being checked are tested as for
+    /// [`SingularCovariance::PseudoInverse`].
+    Reject,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_new_rejects_invalid_chi2() {
+        assert!(Config::try_new(5.991).is_ok());
+        assert!(Config::try_new(0.0).is_err());
+        assert!(Config::try_new(-1.0).is_err());
+        assert!(Config::try_new(f64::NAN).is_err());
+        assert!(Config::try_new(f64::INFINITY).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{BuildHasher, Hash},
+};
+
+/// A relation between a clique in the 'before' set and one or more cliques in the 'after' set.
+///
+/// Cliques are referred to by their index into the slices passed to [`clique_diff`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CliqueRelation {
+    /// A clique in `a` corresponds one-to-one with a clique in `b`.
+    ///
+    /// A Jaccard index of `1.0` means the membership is unchanged.
+    Matched {
+        /// Index of the clique in `a`.
+        a: usize,
+        /// Index of the clique in `b`.
+        b: usize,
+        /// Jaccard index of the two memberships (`|a ∩ b| / |a ∪ b|`).
+        jaccard: f64,
+    },
+
+    /// Two or more cliques in `a` have been merged into a single clique in `b`.
+    Merged {
+        /// Indices of the merged cliques in `a`.
+        from: Vec<usize>,
+        /// Index of the resulting clique in `b`.
+        into: usize,
+    },
+
+    /// A clique in `a` has been split into two or more cliques in `b`.
+    Split {
+        /// Index of the split clique in `a`.
+        from: usize,
+        /// Indices of the resulting cliques in `b`.
+        into: Vec<usize>,
+    },
+
+    /// A clique in `b` with no counterpart in `a`.
+    Added(usize),
+
+    /// A clique in `a` with no counterpart in `b`.
+    Removed(usize),
+}
+
+/// The difference between two sets of cliques, as computed by [`clique_diff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CliqueDiff {
+    /// The relations between the two clique sets.
+    pub relations: Vec<CliqueRelation>,
+}
+
+impl CliqueDiff {
+    /// Returns `true` if every clique is matched to an identical clique in the other set.
+    #[must_use]
+    pub fn is_unchanged(&self) -> bool {
+        self.relations.iter().all(|relation| {
+            matches!(relation, CliqueRelation::Matched { jaccard, .. } if (*jaccard - 1.0).abs() < f64::EPSILON)
+        })
+    }
+}
+
+/// Compare two sets of cliques, and describe how the cliques in `a` relate to the cliques in `b`.
+///
+/// This is useful for detecting divergence between two indices built from the same observations
+/// (for example, a batch-built index and an incrementally maintained one), or for tracking how
+/// cliques evolve as observations are added.
+///
+/// Each clique is related to the clique in the other set with which it shares the most members
+/// (ties are broken by Jaccard index, then by position in the slice). From these 'best matches':
+///
+/// - a clique in `b` which is the best match for two or more cliques in `a` is reported as [`CliqueRelation::Merged`]
+/// - a clique in `a` which is the best match for two or more cliques in `b` is reported as [`CliqueRelation::Split`]
+/// - a pair of cliques which are each other's best match is reported as [`CliqueRelation::Matched`]
+/// - cliques which share no members with the other set are reported as [`CliqueRelation::Added`] or [`CliqueRelation::Removed`]
+///
+/// Each clique is part of exactly one relation. A clique which would be both merged and split is
+/// reported as merged. Cliques which overlap the other set but fit none of these patterns are also
+/// reported as added or removed.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashSet;
+/// use clique_fusion::{clique_diff, CliqueRelation};
+///
+/// let before = vec![HashSet::from([1, 2]), HashSet::from([3, 4])];
+/// let after = vec![HashSet::from([1, 2, 3, 4])];
+///
+/// let diff = clique_diff(&before, &after);
+/// assert_eq!(
+///     diff.relations,
+///     vec![CliqueRelation::Merged { from: vec![0, 1], into: 0 }]
+/// );
+/// ```
+#[must_use]
+pub fn clique_diff<Id, S>(a: &[HashSet<Id, S>], b: &[HashSet<Id, S>]) -> CliqueDiff
+where
+    Id: Eq + Hash,
+    S: BuildHasher,
+{
+    let successors = best_matches(a, b);
+    let predecessors = best_matches(b, a);
+
+    let mut a_used = vec![false; a.len()];
+    let mut b_used = vec![false; b.len()];
+    let mut relations = Vec::new();
+
+    for (into, from) in matched_to(&successors, b.len()).into_iter().enumerate() {
+        if from.len() >= 2 {
+            for &i in &from {
+                a_used[i] = true;
+            }
+            b_used[into] = true;
+            relations.push(CliqueRelation::Merged { from, into });
+        }
+    }
+
+    // merges take precedence, so a clique is never both merged and split
+    for (from, into) in matched_to(&predecessors, a.len()).into_iter().enumerate() {
+        let into: Vec<usize> = into.into_iter().filter(|&j| !b_used[j]).collect();
+        if !a_used[from] && into.len() >= 2 {
+            for &j in &into {
+                b_used[j] = true;
+            }
+            a_used[from] = true;
+            relations.push(CliqueRelation::Split { from, into });
+        }
+    }
+
+    for (i, successor) in successors.iter().enumerate() {
+        let Some(j) = *successor else { continue };
+        if !a_used[i] && !b_used[j] && predecessors[j] == Some(i) {
+            a_used[i] = true;
+            b_used[j] = true;
+            relations.push(CliqueRelation::Matched {
+                a: i,
+                b: j,
+                jaccard: jaccard(&a[i], &b[j]),
+            });
+        }
+    }
+
+    relations.extend(
+        a_used
+            .iter()
+            .enumerate()
+            .filter(|(_, used)| !**used)
+            .map(|(i, _)| CliqueRelation::Removed(i)),
+    );
+    relations.extend(
+        b_used
+            .iter()
+            .enumerate()
+            .filter(|(_, used)| !**used)
+            .map(|(j, _)| CliqueRelation::Added(j)),
+    );
+
+    CliqueDiff { relations }
+}
+
+/// Find the clique in `candidates` which shares the most members with each of `cliques`.
+///
+/// Ties are broken by Jaccard index, and then by the lowest index.
+fn best_matches<Id, S>(
+    cliques: &[HashSet<Id, S>],
+    candidates: &[HashSet<Id, S>],
+) -> Vec<Option<usize>>
+where
+    Id: Eq + Hash,
+    S: BuildHasher,
+{
+    // only candidates which share a member with a clique are considered, so each member is
+    // mapped to the candidates which contain it
+    let mut containing: HashMap<&Id, Vec<usize>> = HashMap::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        for member in candidate {
+            containing.entry(member).or_default().push(index);
+        }
+    }
+
+    cliques
+        .iter()
+        .map(|clique| {
+            let mut overlaps: HashMap<usize, usize> = HashMap::new();
+            for index in clique
+                .iter()
+                .filter_map(|member| containing.get(member))
+                .flatten()
+            {
+                *overlaps.entry(*index).or_default() += 1;
+            }
+            overlaps
+                .into_iter()
+                .map(|(index, overlap)| (index, overlap, jaccard(clique, &candidates[index])))
+                .max_by(|(i, overlap_i, score_i), (j, overlap_j, score_j)| {
+                    overlap_i
+                        .cmp(overlap_j)
+                        .then(score_i.total_cmp(score_j))
+                        .then(j.cmp(i))
+                })
+                .map(|(index, _, _)| index)
+        })
+        .collect()
+}
+
+/// Group the indices of `matches` by the index they are matched to.
+fn matched_to(matches: &[Option<usize>], len: usize) -> Vec<Vec<usize>> {
+    let mut groups = vec![Vec::new(); len];
+    for (index, target) in matches.iter().enumerate() {
+        if let Some(target) = target {
+            groups[*target].push(index);
+        }
+    }
+    groups
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn jaccard<Id, S>(a: &HashSet<Id, S>, b: &HashSet<Id, S>) -> f64
+where
+    Id: Eq + Hash,
+    S: BuildHasher,
+{
+    let intersection = a.intersection(b).count();
+    let union = a.len() + b.len() - intersection;
+    if union == 0 {
+        return 1.0;
+    }
+    intersection as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_sets_are_unchanged() {
+        let cliques = vec![
+            HashSet::from([0, 1]),
+            HashSet::from([1, 2]),
+            HashSet::from([2, 3]),
+        ];
+
+        let diff = clique_diff(&cliques, &cliques);
+
+        assert!(diff.is_unchanged());
+        assert_eq!(diff.relations.len(), 3);
+    }
+
+    #[test]
+    fn detects_split() {
+        let a = vec![HashSet::from([0, 1, 2, 3])];
+        let b = vec![HashSet::from([0, 1]), HashSet::from([2, 3])];
+
+        let diff = clique_diff(&a, &b);
+
+        assert_eq!(
+            diff.relations,
+            vec![CliqueRelation::Split {
+                from: 0,
+                into: vec![0, 1]
+            }]
+        );
+        assert!(!diff.is_unchanged());
+    }
+
+    #[test]
+    fn cliques_are_never_both_merged_and_split() {
+        let a = vec![HashSet::from([1, 2, 3]), HashSet::from([4])];
+        let b = vec![HashSet::from([1, 2, 4]), HashSet::from([3])];
+
+        let diff = clique_diff(&a, &b);
+
+        assert_eq!(
+            diff.relations,
+            vec![
+                CliqueRelation::Merged {
+                    from: vec![0, 1],
+                    into: 0
+                },
+                CliqueRelation::Added(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_added_and_removed() {
+        let a = vec![HashSet::from([0, 1])];
+        let b = vec![HashSet::from([2, 3])];
+
+        let diff = clique_diff(&a, &b);
+
+        assert_eq!(
+            diff.relations,
+            vec![CliqueRelation::Removed(0), CliqueRelation::Added(0)]
+        );
+    }
+
+    #[test]
+    fn partial_overlap_is_matched_with_jaccard() {
+        let a = vec![HashSet::from([0, 1, 2])];
+        let b = vec![HashSet::from([0, 1, 3])];
+
+        let diff = clique_diff(&a, &b);
+
+        assert_eq!(
+            diff.relations,
+            vec![CliqueRelation::Matched {
+                a: 0,
+                b: 0,
+                jaccard: 0.5
+            }]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use std::{borrow::Borrow, collections::HashMap, sync::Arc};
+
+use uuid::Uuid;
+
+use crate::{Observation, Unique};
+
+/// How observations which exactly duplicate an existing observation are handled on ingest.
+///
+/// An observation is an exact duplicate if every one of its fields (position, covariance,
+/// context, timestamp, weight and so on) is identical to that of an observation already in the
+/// index. This is common when upstream systems replay
+/// messages. Left alone, duplicates form perfectly overlapping nodes in the compatibility graph,
+/// which inflate the sizes of the cliques they belong to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DuplicatePolicy {
+    /// Duplicates are inserted as distinct observations.
+    #[default]
+    Keep,
+
+    /// Duplicates are silently discarded.
+    Skip,
+
+    /// Duplicates are discarded, but counted against the original observation.
+    ///
+    /// See [`CliqueIndex::duplicate_count`](crate::CliqueIndex::duplicate_count).
+    Count,
+}
+
+/// The exact bit patterns of every field of an observation.
+///
+/// The floating-point fields are those which feed the [state hash](crate::CliqueIndex::state_hash),
+/// in the same order, so an observation is only a duplicate if it is indistinguishable from the
+/// original.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Fingerprint {
+    values: Vec<u64>,
+    context: Option<Uuid>,
+    sensor: Option<Uuid>,
+    tag: Option<u64>,
+    labels: Vec<Arc<str>>,
+    present: [bool; 3],
+    anchor: bool,
+}
+
+impl From<&Observation> for Fingerprint {
+    fn from(observation: &Observation) -> Self {
+        Self {
+            values: observation.exact_bits().collect(),
+            context: observation.context(),
+            sensor: observation.sensor(),
+            tag: observation.tag(),
+            labels: observation.labels().map(Arc::from).collect(),
+            // the values of the optional fields are concatenated, so their presence is recorded
+            present: [
+                observation.altitude().is_some(),
+                observation.velocity().is_some(),
+                observation.timestamp().is_some(),
+            ],
+            anchor: observation.is_anchor(),
+        }
+    }
+}
+
+/// Tracks the observations in an index in order to detect exact duplicates.
+#[derive(Debug)]
+pub struct Duplicates<Id> {
+    policy: DuplicatePolicy,
+    originals: HashMap<Fingerprint, Id>,
+    counts: HashMap<Id, usize>,
+}
+
+impl<Id> Duplicates<Id>
+where
+    Id: Eq + std::hash::Hash + Copy,
+{
+    pub fn new(policy: DuplicatePolicy) -> Self {
+        Self {
+            policy,
+            originals: HashMap::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    /// Record an observation on ingest.
+    ///
+    /// Returns `true` if the observation should be discarded as a duplicate.
+    pub fn check(&mut self, observation: &Unique<impl Borrow<Observation>, Id>) -> bool {
+        if self.policy == DuplicatePolicy::Keep {
+            return false;
+        }
+
+        let fingerprint = Fingerprint::from(observation.data.borrow());
+        if let Some(original) = self.originals.get(&fingerprint) {
+            if self.policy == DuplicatePolicy::Count {
+                *self.counts.entry(*original).or_default() += 1;
+            }
+            return true;
+        }
+
+        self.originals.insert(fingerprint, observation.id);
+        false
+    }
+
+    /// Returns `true` if the observation would be discarded as a duplicate on ingest, without
+    /// recording it.
+    pub fn is_duplicate(&self, observation: &Unique<impl Borrow<Observation>, Id>) -> bool {
+        self.policy != DuplicatePolicy::Keep
+            && self
+                .originals
+                .contains_key(&Fingerprint::from(observation.data.borrow()))
+    }
+
+    /// Forget an observation which has been removed from the index.
+    ///
+    /// Subsequent observations identical to it will no longer be considered duplicates.
+    pub fn forget(&mut self, observation: &Unique<impl Borrow<Observation>, Id>) {
+        let fingerprint = Fingerprint::from(observation.data.borrow());
+        if self.originals.get(&fingerprint) == Some(&observation.id) {
+            self.originals.remove(&fingerprint);
+        }
+        self.counts.remove(&observation.id);
+    }
+
+    /// Record a refined version of an observation, after the previous version has been
+    /// [forgotten](Self::forget), restoring its count of discarded duplicates.
+    ///
+    /// The refined version replaces an observation already in the index, rather than arriving
+    /// anew, so it is never discarded, even if it is identical to another observation.
+    pub fn restore(&mut self, observation: &Unique<impl Borrow<Observation>, Id>, count: usize) {
+        if self.policy == DuplicatePolicy::Keep {
+            return;
+        }
+        self.originals
+            .entry(Fingerprint::from(observation.data.borrow()))
+            .or_insert(observation.id);
+        if count > 0 {
+            self.counts.insert(observation.id, count);
+        }
+    }
+
+    /// Re-record the observations in an index, after their data has been changed in place.
+    ///
+    /// The counts of discarded duplicates are retained.
+    pub fn reindex<'a, T>(&mut self, observations: impl IntoIterator<Item = &'a Unique<T, Id>>)
+    where
+        T: Borrow<Observation> + 'a,
+        Id: 'a,
+    {
+        if self.policy == DuplicatePolicy::Keep {
+            return;
+        }
+        self.originals = observations
+            .into_iter()
+            .map(|observation| (Fingerprint::from(observation.data.borrow()), observation.id))
+            .collect();
+    }
+
+    /// The number of duplicates discarded in favour of the given observation.
+    pub fn count(&self, id: &Id) -> usize {
+        self.counts.get(id).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CovarianceMatrix;
+
+    fn observation(id: u32, x: f64) -> Unique<Observation, u32> {
+        Unique {
+            data: Observation::builder(x, 0.0)
+                .error(CovarianceMatrix::identity())
+                .build(),
+            id,
+        }
+    }
+
+    #[test]
+    fn keep_never_discards() {
+        let mut duplicates = Duplicates::new(DuplicatePolicy::Keep);
+        assert!(!duplicates.check(&observation(0, 1.0)));
+        assert!(!duplicates.check(&observation(1, 1.0)));
+    }
+
+    #[test]
+    fn count_discards_and_counts_exact_duplicates() {
+        let mut duplicates = Duplicates::new(DuplicatePolicy::Count);
+        assert!(!duplicates.check(&observation(0, 0.0)));
+        assert!(duplicates.check(&observation(1, -0.0)));
+        assert!(duplicates.check(&observation(2, 0.0)));
+        assert!(!duplicates.check(&observation(3, 1e-12)));
+
+        assert_eq!(duplicates.count(&0), 2);
+        assert_eq!(duplicates.count(&3), 0);
+    }
+
+    #[test]
+    fn velocity_errors_distinguish_observations() {
+        let with_velocity_error = |id, variance| Unique {
+            data: Observation::builder(0.0, 0.0)
+                .error(CovarianceMatrix::identity())
+                .velocity(
+                    1.0,
+                    0.0,
+                    CovarianceMatrix::new(variance, variance, 0.0).unwrap(),
+                )
+                .unwrap()
+                .build(),
+            id,
+        };
+        let mut duplicates = Duplicates::new(DuplicatePolicy::Skip);
+        assert!(!duplicates.check(&with_velocity_error(0, 1.0)));
+        assert!(!duplicates.check(&with_velocity_error(1, 2.0)));
+        assert!(duplicates.check(&with_velocity_error(2, 1.0)));
+    }
+
+    #[test]
+    fn timestamps_distinguish_observations() {
+        let at = |id, timestamp| Unique {
+            data: Observation::builder(0.0, 0.0)
+                .error(CovarianceMatrix::identity())
+                .timestamp(timestamp)
+                .build(),
+            id,
+        };
+        let mut duplicates = Duplicates::new(DuplicatePolicy::Count);
+        assert!(!duplicates.check(&at(0, 1.0)));
+        assert!(!duplicates.check(&at(1, 2.0)));
+        assert!(duplicates.check(&at(2, 1.0)));
+        assert_eq!(duplicates.count(&0), 1);
+        assert_eq!(duplicates.count(&1), 0);
+    }
+}
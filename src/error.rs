@@ -0,0 +1,97 @@
+//! A crate-level error type, consolidating the errors of the individual constructors.
+
+use crate::{
+    InvalidAltitude, InvalidChi2, InvalidConfidence, InvalidCovarianceMatrix, InvalidPosition,
+    InvalidRadius, InvalidSensorModel, InvalidVelocity,
+};
+
+/// Any error returned while constructing observations or configuration, or ingesting observations.
+///
+/// Each of the more specific error types in this crate converts into this type, so that
+/// applications can handle every failure with a single `match` (or propagate them with `?`).
+///
+/// ```
+/// use clique_fusion::{CliqueIndex, Error, Observation, Unique};
+///
+/// fn ingest(index: &mut CliqueIndex<u32>, id: u32, x: f64, y: f64) -> Result<(), Error> {
+///     let observation = Observation::builder(x, y)
+///         .circular_95_confidence_error(1.0)?
+///         .try_build()?;
+///     index.try_insert(Unique { data: observation, id })
+/// }
+///
+/// let mut index = CliqueIndex::new(5.991);
+/// assert!(ingest(&mut index, 1, 0.0, 0.0).is_ok());
+/// assert!(matches!(ingest(&mut index, 1, 0.0, 0.0), Err(Error::DuplicateId)));
+/// assert!(matches!(ingest(&mut index, 2, f64::NAN, 0.0), Err(Error::InvalidPosition(_))));
+/// ```
+#[derive(Debug, thiserror::Error, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum Error {
+    /// A confidence radius was invalid.
+    #[error(transparent)]
+    InvalidRadius(#[from] InvalidRadius),
+
+    /// A covariance matrix was invalid.
+    #[error(transparent)]
+    InvalidCovariance(#[from] InvalidCovarianceMatrix),
+
+    /// The position of an observation was invalid.
+    #[error(transparent)]
+    InvalidPosition(#[from] InvalidPosition),
+
+    /// The altitude of an observation was invalid.
+    #[error(transparent)]
+    InvalidAltitude(#[from] InvalidAltitude),
+
+    /// The velocity of an observation was invalid.
+    #[error(transparent)]
+    InvalidVelocity(#[from] InvalidVelocity),
+
+    /// A chi-squared threshold was invalid.
+    #[error(transparent)]
+    InvalidChi2(#[from] InvalidChi2),
+
+    /// A confidence level was invalid.
+    #[error(transparent)]
+    InvalidConfidence(#[from] InvalidConfidence),
+
+    /// The parameters of a sensor model were invalid.
+    #[error(transparent)]
+    InvalidSensorModel(#[from] InvalidSensorModel),
+
+    /// A GeoJSON feature could not be read as an observation.
+    #[cfg(feature = "geojson")]
+    #[error(transparent)]
+    InvalidFeature(#[from] crate::InvalidFeature),
+
+    /// An observation with the same ID is already in the index.
+    #[error("an observation with the same ID is already in the index")]
+    DuplicateId,
+
+    /// An observation with a singular covariance (which isn't an anchor) was rejected, under
+    /// [`SingularCovariance::Reject`](crate::SingularCovariance::Reject).
+    #[error("the covariance of the observation is singular")]
+    SingularCovariance,
+
+    /// An observation was rejected, since it would take the index over its
+    /// [clique capacity](crate::Config::clique_capacity), under
+    /// [`CliqueOverflow::Error`](crate::CliqueOverflow::Error).
+    #[error("the observation would take the index over its clique capacity")]
+    TooManyCliques,
+
+    /// No observation with the given ID is in the index.
+    #[error("no observation with the given ID is in the index")]
+    NotFound,
+
+    /// The operation was stopped by its [`CancellationToken`](crate::CancellationToken).
+    #[error("the operation was cancelled")]
+    Cancelled,
+}
+
+impl From<crate::cancel::Cancelled> for Error {
+    fn from(_: crate::cancel::Cancelled) -> Self {
+        Self::Cancelled
+    }
+}
@@ -0,0 +1,212 @@
+//! A stream of events describing how the cliques of an index evolve.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    hash::Hash,
+    panic::{RefUnwindSafe, UnwindSafe},
+    time::SystemTime,
+};
+
+use crate::{CliqueId, CliqueRelation};
+
+/// A change to the cliques of an index.
+///
+/// Cliques keep their [identifiers](CliqueId) as their membership evolves, so a consumer can
+/// follow the identity of each observed object over time. The cliques resulting from a merge or a
+/// split are new cliques, and the cliques they replace are dissolved, but these are reported as a
+/// single [`CliqueEvent::Merged`] or [`CliqueEvent::Split`] rather than as separate creations
+/// and dissolutions.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CliqueEvent<Id> {
+    /// A new clique was formed, unrelated to any previous clique.
+    Created {
+        /// The identifier of the new clique.
+        id: CliqueId,
+
+        /// The members of the new clique.
+        members: HashSet<Id>,
+    },
+
+    /// Two or more cliques were merged into a new clique.
+    Merged {
+        /// The identifier of the new clique.
+        id: CliqueId,
+
+        /// The identifiers of the merged cliques, which no longer exist.
+        from: Vec<CliqueId>,
+
+        /// The members of the new clique.
+        members: HashSet<Id>,
+    },
+
+    /// A clique was split into two or more new cliques.
+    Split {
+        /// The identifier of the split clique, which no longer exists.
+        id: CliqueId,
+
+        /// The identifiers and members of the new cliques.
+        into: Vec<(CliqueId, HashSet<Id>)>,
+    },
+
+    /// The membership of a clique changed, but it kept its identifier.
+    Updated {
+        /// The identifier of the clique.
+        id: CliqueId,
+
+        /// The observations which joined the clique.
+        added: HashSet<Id>,
+
+        /// The observations which left the clique.
+        removed: HashSet<Id>,
+    },
+
+    /// A clique ceased to exist, without being merged or split.
+    Dissolved {
+        /// The identifier of the dissolved clique.
+        id: CliqueId,
+    },
+}
+
+/// A [`CliqueEvent`], with the time at which it occurred.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TimedCliqueEvent<Id> {
+    /// The [generation](crate::Health::generation) of the index at which the event occurred.
+    ///
+    /// Events from the same operation share a generation.
+    pub generation: u64,
+
+    /// The wall-clock time at which the event occurred.
+    pub time: SystemTime,
+
+    /// The event.
+    pub event: CliqueEvent<Id>,
+}
+
+/// Receives the [`CliqueEvent`]s of an index as they occur.
+///
+/// Closures taking a [`TimedCliqueEvent`] implement this trait. Sinks must be thread- and
+/// unwind-safe, so that the index which owns them is too.
+///
+/// See [`CliqueIndex::set_clique_event_sink`](crate::CliqueIndex::set_clique_event_sink).
+pub trait CliqueEventSink<Id>: Send + Sync + UnwindSafe + RefUnwindSafe {
+    /// Called for each event, in the order in which they occur.
+    fn event(&mut self, event: &TimedCliqueEvent<Id>);
+}
+
+impl<Id, F> CliqueEventSink<Id> for F
+where
+    F: FnMut(&TimedCliqueEvent<Id>) + Send + Sync + UnwindSafe + RefUnwindSafe,
+{
+    fn event(&mut self, event: &TimedCliqueEvent<Id>) {
+        self(event);
+    }
+}
+
+/// Where the clique events of an index are delivered, if anywhere.
+pub struct CliqueEvents<Id> {
+    buffer: Option<Vec<TimedCliqueEvent<Id>>>,
+    sink: Option<Box<dyn CliqueEventSink<Id>>>,
+}
+
+impl<Id> fmt::Debug for CliqueEvents<Id>
+where
+    Id: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CliqueEvents")
+            .field("buffer", &self.buffer)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Id> Default for CliqueEvents<Id> {
+    fn default() -> Self {
+        Self {
+            buffer: None,
+            sink: None,
+        }
+    }
+}
+
+impl<Id> CliqueEvents<Id>
+where
+    Id: Eq + Hash + Copy,
+{
+    /// Returns `true` if events are being buffered or delivered to a sink.
+    pub const fn is_active(&self) -> bool {
+        self.buffer.is_some() || self.sink.is_some()
+    }
+
+    /// Start buffering events, if they aren't already.
+    pub fn record(&mut self) {
+        self.buffer.get_or_insert_with(Vec::new);
+    }
+
+    /// Take the buffered events, oldest first.
+    pub fn drain(&mut self) -> std::vec::Drain<'_, TimedCliqueEvent<Id>> {
+        self.buffer.get_or_insert_with(Vec::new).drain(..)
+    }
+
+    pub fn set_sink(&mut self, sink: Box<dyn CliqueEventSink<Id>>) {
+        self.sink = Some(sink);
+    }
+
+    /// Report the events of replacing the `stale` cliques with the `new` ones.
+    ///
+    /// `relations` relates the stale cliques to the new ones, as for
+    /// [`clique_diff`](crate::clique_diff), and must describe every clique of each.
+    pub fn replaced(
+        &mut self,
+        generation: u64,
+        (stale_ids, stale): (&[CliqueId], &[HashSet<Id>]),
+        (new_ids, new): (&[CliqueId], &[HashSet<Id>]),
+        relations: &[CliqueRelation],
+    ) {
+        let time = SystemTime::now();
+        for relation in relations {
+            let event = match relation {
+                CliqueRelation::Matched { a, b, .. } => {
+                    if stale[*a] == new[*b] {
+                        continue;
+                    }
+                    CliqueEvent::Updated {
+                        id: new_ids[*b],
+                        added: new[*b].difference(&stale[*a]).copied().collect(),
+                        removed: stale[*a].difference(&new[*b]).copied().collect(),
+                    }
+                }
+                CliqueRelation::Merged { from, into } => CliqueEvent::Merged {
+                    id: new_ids[*into],
+                    from: from.iter().map(|a| stale_ids[*a]).collect(),
+                    members: new[*into].clone(),
+                },
+                CliqueRelation::Split { from, into } => CliqueEvent::Split {
+                    id: stale_ids[*from],
+                    into: into
+                        .iter()
+                        .map(|b| (new_ids[*b], new[*b].clone()))
+                        .collect(),
+                },
+                CliqueRelation::Added(b) => CliqueEvent::Created {
+                    id: new_ids[*b],
+                    members: new[*b].clone(),
+                },
+                CliqueRelation::Removed(a) => CliqueEvent::Dissolved { id: stale_ids[*a] },
+            };
+            let event = TimedCliqueEvent {
+                generation,
+                time,
+                event,
+            };
+            if let Some(sink) = &mut self.sink {
+                sink.event(&event);
+            }
+            if let Some(buffer) = &mut self.buffer {
+                buffer.push(event);
+            }
+        }
+    }
+}
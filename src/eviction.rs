@@ -0,0 +1,143 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Which observations are evicted when an index exceeds its capacity.
+///
+/// See [`Config::capacity`](crate::Config::capacity).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EvictionPolicy {
+    /// The observations which were inserted first are evicted first.
+    #[default]
+    Oldest,
+
+    /// The observations with the lowest [weight](crate::Observation::weight) are evicted first.
+    /// Observations with equal weights are evicted oldest first.
+    LowestWeight,
+
+    /// Observations which aren't compatible with any other are evicted first, oldest first,
+    /// followed by the oldest of the rest.
+    ///
+    /// This preserves the observations which contribute to cliques for as long as possible.
+    IsolatedFirst,
+}
+
+/// What happens when the cliques of an index would exceed its clique capacity.
+///
+/// See [`Config::clique_capacity`](crate::Config::clique_capacity).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CliqueOverflow {
+    /// The cliques with the fewest members are dropped first. Cliques of equal size are dropped
+    /// newest first.
+    #[default]
+    DropSmallest,
+
+    /// The cliques with the lowest [score](crate::FusedClique::score) are dropped first. Cliques
+    /// with equal scores are dropped newest first.
+    ///
+    /// Scoring requires fusing each clique, so this is more expensive than
+    /// [`Self::DropSmallest`] while the index is over capacity.
+    DropLowestScore,
+
+    /// [`CliqueIndex::try_insert`](crate::CliqueIndex::try_insert) fails with
+    /// [`Error::TooManyCliques`](crate::Error::TooManyCliques), rather than inserting an
+    /// observation which would take the index over capacity.
+    ///
+    /// Operations which can't fail, such as [`CliqueIndex::insert`](crate::CliqueIndex::insert),
+    /// drop the smallest cliques instead, as for [`Self::DropSmallest`].
+    Error,
+}
+
+/// Tracks the order in which the observations in an index arrived, for eviction.
+#[derive(Debug)]
+pub struct Arrivals<Id> {
+    next: u64,
+    order: BTreeMap<u64, Id>,
+    arrived: HashMap<Id, u64>,
+}
+
+impl<Id> Arrivals<Id>
+where
+    Id: Eq + std::hash::Hash + Copy,
+{
+    pub fn new() -> Self {
+        Self {
+            next: 0,
+            order: BTreeMap::new(),
+            arrived: HashMap::new(),
+        }
+    }
+
+    /// Record the arrival of an observation.
+    pub fn record(&mut self, id: Id) {
+        let arrival = self.next;
+        self.next += 1;
+        self.order.insert(arrival, id);
+        self.arrived.insert(id, arrival);
+    }
+
+    /// Forget an observation which has been removed from the index.
+    pub fn forget(&mut self, id: &Id) {
+        if let Some(arrival) = self.arrived.remove(id) {
+            self.order.remove(&arrival);
+        }
+    }
+
+    /// Choose `count` observations to evict, according to the policy.
+    ///
+    /// `weight` returns the weight of an observation, and `isolated` whether it is compatible with
+    /// no other.
+    pub fn victims(
+        &self,
+        count: usize,
+        policy: EvictionPolicy,
+        weight: impl Fn(&Id) -> f64,
+        isolated: impl Fn(&Id) -> bool,
+    ) -> Vec<Id> {
+        let oldest = self.order.values().copied();
+        match policy {
+            EvictionPolicy::Oldest => oldest.take(count).collect(),
+            EvictionPolicy::LowestWeight => {
+                let mut candidates: Vec<(f64, Id)> = oldest.map(|id| (weight(&id), id)).collect();
+                // the sort is stable, so equal weights remain oldest first
+                candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+                candidates
+                    .into_iter()
+                    .take(count)
+                    .map(|(_, id)| id)
+                    .collect()
+            }
+            EvictionPolicy::IsolatedFirst => {
+                let mut victims: Vec<Id> = oldest.clone().filter(&isolated).take(count).collect();
+                if victims.len() < count {
+                    let chosen: HashSet<Id> = victims.iter().copied().collect();
+                    let remaining = count - victims.len();
+                    victims.extend(oldest.filter(|id| !chosen.contains(id)).take(remaining));
+                }
+                victims
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn victims_follow_the_policy() {
+        let mut arrivals = Arrivals::new();
+        for id in 0..5 {
+            arrivals.record(id);
+        }
+        arrivals.forget(&0);
+
+        let weight = |id: &u32| if *id == 3 { 0.5 } else { 1.0 };
+        let isolated = |id: &u32| *id == 4;
+
+        let victims = |policy| arrivals.victims(2, policy, weight, isolated);
+        assert_eq!(victims(EvictionPolicy::Oldest), vec![1, 2]);
+        assert_eq!(victims(EvictionPolicy::LowestWeight), vec![3, 1]);
+        assert_eq!(victims(EvictionPolicy::IsolatedFirst), vec![4, 1]);
+    }
+}
@@ -0,0 +1,846 @@
+//! Fusion of estimates of the position of an object.
+//!
+//! The [fused estimates](crate::CliqueIndex::fused_estimates) of an index combine the members of
+//! each clique with [`fuse_independent`]. The functions of this module can also be used directly,
+//! to fuse arbitrary estimates with the same arithmetic, independently of an index.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use nalgebra::{Matrix2, Vector2};
+use uuid::Uuid;
+
+use crate::{CliqueId, CovarianceMatrix, Observation, chi2::chi2_p_value};
+
+/// The fused estimate of the position of the object observed by the members of a clique.
+///
+/// The estimate is the information-weighted combination of the member observations, which is the
+/// minimum-variance estimate under the assumption that they are independent, unbiased
+/// measurements of the same object.
+///
+/// The estimate may also carry `attributes` merged from the metadata of its members, such as a
+/// majority-vote classification. See
+/// [`CliqueIndex::fused_estimates_with`](crate::CliqueIndex::fused_estimates_with).
+///
+/// See [`CliqueIndex::fused_estimates`](crate::CliqueIndex::fused_estimates).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Id: serde::Serialize, A: serde::Serialize",
+        deserialize = "Id: serde::Deserialize<'de> + Eq + std::hash::Hash, A: serde::Deserialize<'de>"
+    ))
+)]
+pub struct FusedEstimate<Id, A = ()> {
+    /// The observations which were fused to produce the estimate.
+    pub members: HashSet<Id>,
+
+    /// The fused x ordinate.
+    pub x: f64,
+
+    /// The fused y ordinate.
+    pub y: f64,
+
+    /// The covariance of the fused position error.
+    pub covariance: CovarianceMatrix,
+
+    /// The distinct sensors which made the fused observations.
+    ///
+    /// Observations without a [sensor](Observation::sensor) are not represented.
+    pub sensors: HashSet<Uuid>,
+
+    /// How consistent the fused members are with the fused position, taken together.
+    pub goodness_of_fit: GoodnessOfFit,
+
+    /// The members which were excluded from the estimate as outliers.
+    ///
+    /// This is always empty, except for a [trimmed](FusionMethod::Trimmed) estimate.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub outliers: HashSet<Id>,
+
+    /// The attributes merged from the members of the clique.
+    pub attributes: A,
+}
+
+impl<Id, A> FusedEstimate<Id, A> {
+    /// The fused position (x, y).
+    #[must_use]
+    pub const fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    /// Convert the estimate into an [`Observation`], so that it can be inserted into another index.
+    ///
+    /// This allows hierarchical fusion, where the estimates of several indices (for example, one
+    /// per map tile) are fused again by a higher-level index.
+    ///
+    /// The estimates of a single index are distinct objects, so they should all be given the same
+    /// `context` to prevent them being fused with each other. If every member was made by the same
+    /// sensor, the observation inherits it.
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+    /// use uuid::Uuid;
+    ///
+    /// let observation = |x| {
+    ///     Unique::new_v4(
+    ///         Observation::builder(x, 0.0)
+    ///             .circular_95_confidence_error(1.0)
+    ///             .unwrap()
+    ///             .build(),
+    ///     )
+    /// };
+    /// let tile = CliqueIndex::from_observations(
+    ///     vec![observation(0.0), observation(0.1)],
+    ///     CHI2_2D_CONFIDENCE_95,
+    /// );
+    ///
+    /// let tile_context = Uuid::new_v4();
+    /// let mut national = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+    /// for estimate in tile.fused_estimates() {
+    ///     national.insert(Unique::new_v4(estimate.to_observation(tile_context)));
+    /// }
+    ///
+    /// assert_eq!(national.total_observations(), 1);
+    /// ```
+    #[must_use]
+    pub fn to_observation(&self, context: Uuid) -> Observation {
+        let mut builder = Observation::builder(self.x, self.y)
+            .error(self.covariance)
+            .context(context);
+        let mut sensors = self.sensors.iter();
+        if let (Some(&sensor), None) = (sensors.next(), sensors.next()) {
+            builder = builder.sensor(sensor);
+        }
+        builder.build()
+    }
+}
+
+/// A clique, together with the fused estimate of the position of the object it observed.
+///
+/// This is the summary of a clique which most consumers need, in one place. See
+/// [`CliqueIndex::results`](crate::CliqueIndex::results).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Id: serde::Serialize",
+        deserialize = "Id: serde::Deserialize<'de> + Eq + std::hash::Hash"
+    ))
+)]
+pub struct FusedClique<Id> {
+    /// The stable identifier of the clique.
+    pub id: CliqueId,
+
+    /// The observations in the clique.
+    pub members: HashSet<Id>,
+
+    /// The fused position (x, y).
+    pub fused_position: (f64, f64),
+
+    /// The covariance of the fused position error.
+    pub fused_covariance: CovarianceMatrix,
+
+    /// How consistent the members are with the fused position, as the
+    /// [p-value](GoodnessOfFit::p_value) of their goodness of fit.
+    ///
+    /// This lies between `0.0` and `1.0`, and is higher for more consistent cliques.
+    pub score: f64,
+}
+
+impl<Id> From<(CliqueId, FusedEstimate<Id>)> for FusedClique<Id> {
+    fn from((id, estimate): (CliqueId, FusedEstimate<Id>)) -> Self {
+        Self {
+            id,
+            members: estimate.members,
+            fused_position: (estimate.x, estimate.y),
+            fused_covariance: estimate.covariance,
+            score: estimate.goodness_of_fit.p_value,
+        }
+    }
+}
+
+/// How the members of a clique are combined into a [`FusedEstimate`].
+///
+/// The default information filter is optimal if the members are independent, unbiased
+/// measurements of the same object, but a single mis-associated member can pull the fused position
+/// far from the others. The robust methods limit the influence of such outliers.
+///
+/// See [`CliqueIndex::fused_estimates_using`](crate::CliqueIndex::fused_estimates_using).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FusionMethod {
+    /// The members are combined by an information filter, as for [`fuse_independent`].
+    #[default]
+    InformationFilter,
+
+    /// The information of each member is scaled by its [weight](Observation::weight), so that
+    /// members with a higher confidence have more influence.
+    ///
+    /// Members whose weight isn't strictly positive are ignored. A clique without any positively
+    /// weighted member has no estimate.
+    Weighted,
+
+    /// The member with the largest residual is discarded, repeatedly, until the remaining members
+    /// pass the [goodness-of-fit](GoodnessOfFit) test at the given significance (such as `0.05`),
+    /// or only two remain.
+    ///
+    /// The discarded members are reported as [outliers](FusedEstimate::outliers).
+    Trimmed {
+        /// The significance level of the goodness-of-fit test.
+        significance: f64,
+    },
+
+    /// The members are combined by iteratively reweighted least squares, with Huber weights.
+    ///
+    /// A member whose residual exceeds `threshold` standard deviations has its information scaled
+    /// down in proportion, so that its influence on the fused position is bounded. A threshold of
+    /// around `1.5` to `2.5` is typical. The fused covariance reflects the reduced weights.
+    Huber {
+        /// The residual (in standard deviations) beyond which members are down-weighted.
+        threshold: f64,
+    },
+}
+
+/// A chi-squared test of whether the members of a clique are jointly consistent with their fused
+/// estimate.
+///
+/// Pairwise compatibility doesn't imply joint consistency: three observations may each be
+/// compatible with the other two, yet be too widely spread to plausibly be measurements of a
+/// single object. The residual statistic `Σ (xᵢ - x)ᵀ Pᵢ⁻¹ (xᵢ - x)` tests the members together.
+/// If they are independent, unbiased measurements of the same object, it follows a chi-squared
+/// distribution with `2(n - 1)` degrees of freedom for `n` members (fewer, if any of their
+/// covariance matrices are singular).
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+///
+/// // each pair is compatible, but together they are spread too widely
+/// let observations = [(1, 0.0, 0.0), (2, 1.4, 0.0), (3, 0.7, 1.212)]
+///     .into_iter()
+///     .map(|(id, x, y)| Unique {
+///         data: Observation::builder(x, y)
+///             .circular_95_confidence_error(1.0)
+///             .unwrap()
+///             .build(),
+///         id,
+///     })
+///     .collect();
+/// let index = CliqueIndex::from_observations(observations, CHI2_2D_CONFIDENCE_95);
+///
+/// let fit = index.fused_estimates()[0].goodness_of_fit;
+/// assert_eq!(fit.degrees_of_freedom, 4);
+/// assert!(!fit.is_consistent(0.05));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GoodnessOfFit {
+    /// The residual chi-squared statistic of the members about the fused position.
+    ///
+    /// This is infinite if several members have zero error, but different positions.
+    pub statistic: f64,
+
+    /// The degrees of freedom of the statistic.
+    pub degrees_of_freedom: u32,
+
+    /// The probability of a statistic at least this large if the members are consistent.
+    pub p_value: f64,
+}
+
+impl GoodnessOfFit {
+    /// Returns `true` if the members are consistent at the given significance level (such as
+    /// `0.05`), that is if the [p-value](Self::p_value) is at least the significance.
+    #[must_use]
+    pub fn is_consistent(&self, significance: f64) -> bool {
+        self.p_value >= significance
+    }
+}
+
+/// Test the consistency of a set of observations with their fused position. See [`GoodnessOfFit`].
+pub(crate) fn goodness_of_fit<'a>(
+    observations: impl IntoIterator<Item = &'a Observation>,
+    (x, y): (f64, f64),
+) -> GoodnessOfFit {
+    let observations: Vec<_> = observations.into_iter().collect();
+    let fused = Vector2::new(x, y);
+    let mut statistic = 0.0;
+    let mut measured = 0_u32;
+
+    for observation in &observations {
+        let residual = Vector2::new(observation.x(), observation.y()) - fused;
+        if let Some(inverse) = observation.error_covariance().safe_inverse() {
+            statistic += residual.dot(&(inverse * residual));
+            measured += rank(&inverse);
+        } else {
+            measured += 2;
+            // the fused position may be the mean of several exact observations, so allow for
+            // rounding
+            if residual.norm() > 1e-9 * fused.norm().max(1.0) {
+                statistic = f64::INFINITY;
+            }
+        }
+    }
+
+    // the number of dimensions of the fused position which were estimated from the observations
+    let mut information = Matrix2::zeros();
+    let mut exact = false;
+    for observation in estimators(observations) {
+        match observation.error_covariance().safe_inverse() {
+            Some(inverse) => information += inverse,
+            // exact observations fix the fused position completely
+            None => exact = true,
+        }
+    }
+    let estimated = if exact { 2 } else { rank(&information) };
+    let degrees_of_freedom = measured.saturating_sub(estimated);
+
+    GoodnessOfFit {
+        statistic,
+        degrees_of_freedom,
+        p_value: chi2_p_value(statistic, degrees_of_freedom),
+    }
+}
+
+/// The observations which determine a fused estimate: the anchors if there are any, or else all
+/// of them.
+fn estimators<'a>(observations: impl IntoIterator<Item = &'a Observation>) -> Vec<&'a Observation> {
+    let mut observations: Vec<_> = observations.into_iter().collect();
+    if observations
+        .iter()
+        .any(|observation| observation.is_anchor())
+    {
+        observations.retain(|observation| observation.is_anchor());
+    }
+    observations
+}
+
+/// The numerical rank of a symmetric matrix.
+fn rank(matrix: &Matrix2<f64>) -> u32 {
+    let singular_values = matrix.singular_values();
+    let largest = singular_values.max();
+    let rank = singular_values
+        .iter()
+        .filter(|value| **value > largest * 1e-12)
+        .count();
+    u32::try_from(rank).expect("a 2x2 matrix has rank at most 2")
+}
+
+/// Fuse a set of observations, as for [`fuse_independent`].
+///
+/// If any of the observations are [anchors](Observation::is_anchor), only the anchors are fused.
+///
+/// Returns `None` if `observations` is empty.
+pub(crate) fn fuse<'a>(
+    observations: impl IntoIterator<Item = &'a Observation>,
+) -> Option<(f64, f64, CovarianceMatrix)> {
+    let estimates = estimators(observations)
+        .into_iter()
+        .map(|observation| (observation.position(), observation.error_covariance()));
+    fuse_independent(estimates).map(|((x, y), covariance)| (x, y, covariance))
+}
+
+/// A fused position, and the members which were excluded from it as outliers.
+pub(crate) struct Fusion<Id> {
+    pub x: f64,
+    pub y: f64,
+    pub covariance: CovarianceMatrix,
+    pub outliers: HashSet<Id>,
+}
+
+/// The greatest number of iterations of reweighted least squares.
+const MAX_REWEIGHTING_ITERATIONS: usize = 50;
+
+/// Fuse the members of a clique with the given method. See [`FusionMethod`].
+///
+/// Returns `None` if there are no members (or no members to fuse).
+pub(crate) fn fuse_members<Id>(
+    members: &HashMap<Id, &Observation>,
+    method: FusionMethod,
+) -> Option<Fusion<Id>>
+where
+    Id: Eq + Hash + Copy,
+{
+    let fusion = |(x, y, covariance), outliers| Fusion {
+        x,
+        y,
+        covariance,
+        outliers,
+    };
+    match method {
+        FusionMethod::InformationFilter => {
+            fuse(members.values().copied()).map(|fused| fusion(fused, HashSet::new()))
+        }
+        FusionMethod::Weighted => {
+            let estimates = estimators(members.values().copied())
+                .into_iter()
+                .map(|o| (o.position(), o.error_covariance(), o.weight()));
+            let ((x, y), covariance) = fuse_weighted(estimates)?;
+            Some(fusion((x, y, covariance), HashSet::new()))
+        }
+        FusionMethod::Trimmed { significance } => {
+            let mut remaining = members.clone();
+            let mut outliers = HashSet::new();
+            loop {
+                let (x, y, covariance) = fuse(remaining.values().copied())?;
+                if remaining.len() <= 2
+                    || goodness_of_fit(remaining.values().copied(), (x, y))
+                        .is_consistent(significance)
+                {
+                    return Some(fusion((x, y, covariance), outliers));
+                }
+                let worst = remaining
+                    .iter()
+                    .map(|(id, o)| (*id, o.mahalanobis_to_point(x, y)))
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(id, _)| id)?;
+                remaining.remove(&worst);
+                outliers.insert(worst);
+            }
+        }
+        FusionMethod::Huber { threshold } => {
+            let estimators = estimators(members.values().copied());
+            let mut weights = vec![1.0; estimators.len()];
+            let mut fused = None;
+            for _ in 0..MAX_REWEIGHTING_ITERATIONS {
+                let estimates = estimators
+                    .iter()
+                    .zip(&weights)
+                    .map(|(o, weight)| (o.position(), o.error_covariance(), *weight));
+                let ((x, y), covariance) = fuse_weighted(estimates)?;
+                let converged = fused.is_some_and(|(previous_x, previous_y, _): (f64, f64, _)| {
+                    (x - previous_x).hypot(y - previous_y) <= 1e-9 * x.hypot(y).max(1.0)
+                });
+                fused = Some((x, y, covariance));
+                if converged {
+                    break;
+                }
+                weights = estimators
+                    .iter()
+                    .map(|o| {
+                        let residual = o.mahalanobis_to_point(x, y);
+                        // exact members always take precedence
+                        if residual <= threshold || residual.is_infinite() {
+                            1.0
+                        } else {
+                            threshold / residual
+                        }
+                    })
+                    .collect();
+            }
+            fused.map(|fused| fusion(fused, HashSet::new()))
+        }
+    }
+}
+
+/// Fuse independent estimates of the same position using an information filter.
+///
+/// Each estimate is a position and the covariance of its error. The fused covariance is
+/// `P = (Σ Pᵢ⁻¹)⁻¹`, and the fused position is `x = P ⋅ Σ Pᵢ⁻¹ xᵢ`, which is the minimum-variance
+/// estimate if the estimates are independent and unbiased. Singular covariance matrices are
+/// handled using their pseudo-inverse.
+///
+/// Estimates with zero error are treated as exact, and take precedence over all others; if there
+/// are several, the fused position is their mean and the fused covariance is zero.
+///
+/// If the errors of the estimates may be correlated (for example, because they were derived from
+/// some of the same measurements), the fused covariance is overconfident. Use
+/// [`covariance_intersection`] instead.
+///
+/// Returns `None` if `estimates` is empty.
+///
+/// ```
+/// use clique_fusion::{CovarianceMatrix, fusion::fuse_independent};
+///
+/// let estimates = [
+///     ((0.0, 0.0), CovarianceMatrix::identity()),
+///     ((2.0, 0.0), CovarianceMatrix::identity()),
+/// ];
+/// let (position, covariance) = fuse_independent(estimates).unwrap();
+///
+/// assert_eq!(position, (1.0, 0.0));
+/// assert_eq!(covariance, CovarianceMatrix::new(0.5, 0.5, 0.0).unwrap());
+/// ```
+pub fn fuse_independent(
+    estimates: impl IntoIterator<Item = ((f64, f64), CovarianceMatrix)>,
+) -> Option<((f64, f64), CovarianceMatrix)> {
+    fuse_weighted(
+        estimates
+            .into_iter()
+            .map(|(position, covariance)| (position, covariance, 1.0)),
+    )
+}
+
+/// Fuse independent estimates, as for [`fuse_independent`], scaling the information of each by
+/// its weight.
+///
+/// Estimates whose weight isn't strictly positive are ignored.
+fn fuse_weighted(
+    estimates: impl IntoIterator<Item = ((f64, f64), CovarianceMatrix, f64)>,
+) -> Option<((f64, f64), CovarianceMatrix)> {
+    let mut information = Matrix2::zeros();
+    let mut information_vector = Vector2::zeros();
+    let mut exact_sum = Vector2::zeros();
+    let mut exact_count = 0_u32;
+    let mut count = 0_usize;
+
+    for ((x, y), covariance, weight) in estimates {
+        if weight <= 0.0 || weight.is_nan() {
+            continue;
+        }
+        count += 1;
+        let position = Vector2::new(x, y);
+        if let Some(inverse) = covariance.safe_inverse() {
+            information += inverse * weight;
+            information_vector += inverse * weight * position;
+        } else {
+            exact_sum += position;
+            exact_count += 1;
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+
+    if exact_count > 0 {
+        let mean = exact_sum / f64::from(exact_count);
+        return Some((
+            (mean.x, mean.y),
+            CovarianceMatrix::from_matrix(Matrix2::zeros()),
+        ));
+    }
+
+    let covariance = CovarianceMatrix::from_matrix(information)
+        .safe_inverse()
+        .unwrap_or_else(Matrix2::zeros);
+    let position = covariance * information_vector;
+
+    Some((
+        (position.x, position.y),
+        CovarianceMatrix::from_matrix(covariance),
+    ))
+}
+
+/// Fuse two estimates of the same position whose errors may be correlated, by covariance
+/// intersection.
+///
+/// The fused information is the convex combination `P⁻¹ = ω Pₐ⁻¹ + (1 - ω) P_b⁻¹`, and the fused
+/// position is `x = P ⋅ (ω Pₐ⁻¹ xₐ + (1 - ω) P_b⁻¹ x_b)`, where the weight `ω` in `[0, 1]` is
+/// chosen to minimise the determinant of the fused covariance. The fused covariance is consistent
+/// whatever the (unknown) correlation between the errors, so this is suitable for fusing
+/// estimates which may share information, such as the outputs of two trackers fed by some of the
+/// same sensors. For estimates which are known to be independent, [`fuse_independent`] is
+/// tighter.
+///
+/// An estimate with zero error is exact, and is returned as-is (or the mean of the two, if both
+/// are exact).
+///
+/// ```
+/// use clique_fusion::{CovarianceMatrix, fusion::covariance_intersection};
+///
+/// let estimate = ((1.0, 2.0), CovarianceMatrix::new(2.0, 1.0, 0.5).unwrap());
+///
+/// // fusing an estimate with itself doesn't make it any more certain
+/// let (position, covariance) = covariance_intersection(estimate, estimate);
+/// assert!((position.0 - 1.0).abs() < 1e-9 && (position.1 - 2.0).abs() < 1e-9);
+/// assert!((covariance.determinant() - estimate.1.determinant()).abs() < 1e-9);
+/// ```
+#[must_use]
+pub fn covariance_intersection(
+    a: ((f64, f64), CovarianceMatrix),
+    b: ((f64, f64), CovarianceMatrix),
+) -> ((f64, f64), CovarianceMatrix) {
+    let ((a_position, a_covariance), (b_position, b_covariance)) = (a, b);
+    let (a_information, b_information) =
+        match (a_covariance.safe_inverse(), b_covariance.safe_inverse()) {
+            (Some(a_information), Some(b_information)) => (a_information, b_information),
+            (None, Some(_)) => return a,
+            (Some(_), None) => return b,
+            (None, None) => {
+                let mean = (
+                    f64::midpoint(a_position.0, b_position.0),
+                    f64::midpoint(a_position.1, b_position.1),
+                );
+                return (mean, a_covariance);
+            }
+        };
+
+    let weight = intersection_weight(&a_information, &b_information);
+    let information = a_information * weight + b_information * (1.0 - weight);
+    let covariance = CovarianceMatrix::from_matrix(information)
+        .safe_inverse()
+        .unwrap_or_else(Matrix2::zeros);
+    let position = covariance
+        * (a_information * weight * Vector2::new(a_position.0, a_position.1)
+            + b_information * (1.0 - weight) * Vector2::new(b_position.0, b_position.1));
+
+    (
+        (position.x, position.y),
+        CovarianceMatrix::from_matrix(covariance),
+    )
+}
+
+/// The weight `ω` in `[0, 1]` which maximises `det(ω A + (1 - ω) B)` (and so minimises the
+/// determinant of the fused covariance).
+///
+/// For 2x2 matrices, `det(B + ω (A - B))` is a quadratic in `ω`, so the maximum is at one of the
+/// ends of the interval, or at the vertex of the quadratic.
+fn intersection_weight(a: &Matrix2<f64>, b: &Matrix2<f64>) -> f64 {
+    let difference = a - b;
+    let quadratic = difference.determinant();
+    let adjugate = Matrix2::new(b[(1, 1)], -b[(0, 1)], -b[(1, 0)], b[(0, 0)]);
+    let linear = (adjugate * difference).trace();
+    let determinant = |weight: f64| quadratic.mul_add(weight * weight, linear * weight);
+
+    let mut candidates = vec![0.0, 1.0];
+    if quadratic < 0.0 {
+        candidates.push((-linear / (2.0 * quadratic)).clamp(0.0, 1.0));
+    }
+    candidates
+        .into_iter()
+        .max_by(|x, y| determinant(*x).total_cmp(&determinant(*y)))
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn observation(x: f64, y: f64, xx: f64, yy: f64) -> Observation {
+        Observation::builder(x, y)
+            .error(CovarianceMatrix::new(xx, yy, 0.0).unwrap())
+            .build()
+    }
+
+    #[test]
+    fn estimates_convert_to_observations() {
+        let context = Uuid::from_u128(1);
+        let mut estimate = FusedEstimate {
+            members: HashSet::from([0, 1]),
+            x: 1.0,
+            y: 2.0,
+            covariance: CovarianceMatrix::new(0.5, 0.25, 0.1).unwrap(),
+            sensors: HashSet::from([Uuid::from_u128(2)]),
+            goodness_of_fit: GoodnessOfFit {
+                statistic: 0.5,
+                degrees_of_freedom: 2,
+                p_value: 0.78,
+            },
+            attributes: (),
+            outliers: HashSet::new(),
+        };
+
+        let observation = estimate.to_observation(context);
+        assert_eq!(observation.position(), (1.0, 2.0));
+        assert_eq!(observation.error_covariance(), estimate.covariance);
+        assert_eq!(observation.context(), Some(context));
+        assert_eq!(observation.sensor(), Some(Uuid::from_u128(2)));
+
+        estimate.sensors.insert(Uuid::from_u128(3));
+        assert_eq!(estimate.to_observation(context).sensor(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn estimates_round_trip_through_json() {
+        let estimate = crate::Unique {
+            data: FusedEstimate {
+                members: HashSet::from([0, 1]),
+                x: 1.0,
+                y: 2.0,
+                covariance: CovarianceMatrix::new(0.5, 0.25, 0.1).unwrap(),
+                sensors: HashSet::from([Uuid::from_u128(2)]),
+                goodness_of_fit: GoodnessOfFit {
+                    statistic: 0.5,
+                    degrees_of_freedom: 2,
+                    p_value: 0.78,
+                },
+                attributes: (),
+                outliers: HashSet::from([2]),
+            },
+            id: 7,
+        };
+
+        let json = serde_json::to_value(&estimate).unwrap();
+        assert_eq!(
+            json["data"]["covariance"],
+            serde_json::json!({ "xx": 0.5, "xy": 0.1, "yy": 0.25 })
+        );
+
+        let parsed: crate::Unique<FusedEstimate<i32>, i32> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.id, 7);
+        assert_eq!(parsed.data.members, estimate.data.members);
+        assert_eq!(parsed.data.covariance, estimate.data.covariance);
+        assert_eq!(parsed.data.sensors, estimate.data.sensors);
+    }
+
+    #[test]
+    fn equal_errors_fuse_to_the_mean() {
+        let observations = [
+            observation(0.0, 0.0, 2.0, 2.0),
+            observation(2.0, 4.0, 2.0, 2.0),
+        ];
+
+        let (x, y, covariance) = fuse(&observations).unwrap();
+
+        assert_relative_eq!(x, 1.0, epsilon = 1e-12);
+        assert_relative_eq!(y, 2.0, epsilon = 1e-12);
+        assert_relative_eq!(covariance.xx(), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(covariance.yy(), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(covariance.xy(), 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn fusion_weights_by_information() {
+        // The first observation is precise in x, the second is precise in y
+        let observations = [
+            observation(0.0, 0.0, 1.0, 100.0),
+            observation(10.0, 10.0, 100.0, 1.0),
+        ];
+
+        let (x, y, covariance) = fuse(&observations).unwrap();
+
+        assert_relative_eq!(x, 10.0 / 101.0, epsilon = 1e-12);
+        assert_relative_eq!(y, 1000.0 / 101.0, epsilon = 1e-12);
+        assert_relative_eq!(covariance.xx(), 100.0 / 101.0, epsilon = 1e-12);
+        assert_relative_eq!(covariance.yy(), 100.0 / 101.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn exact_observations_take_precedence() {
+        let observations = [
+            observation(0.0, 0.0, 1.0, 1.0),
+            observation(5.0, 5.0, 0.0, 0.0),
+        ];
+
+        let (x, y, covariance) = fuse(&observations).unwrap();
+
+        assert_relative_eq!(x, 5.0);
+        assert_relative_eq!(y, 5.0);
+        assert_relative_eq!(covariance.max_variance(), 0.0);
+    }
+
+    #[test]
+    fn goodness_of_fit_counts_degrees_of_freedom() {
+        // two unit-variance observations, each one unit from the midpoint
+        let observations = [
+            observation(-1.0, 0.0, 1.0, 1.0),
+            observation(1.0, 0.0, 1.0, 1.0),
+        ];
+        let fit = goodness_of_fit(&observations, (0.0, 0.0));
+        assert_relative_eq!(fit.statistic, 2.0);
+        assert_eq!(fit.degrees_of_freedom, 2);
+        assert_relative_eq!(fit.p_value, (-1.0_f64).exp(), epsilon = 1e-12);
+
+        // a single observation is trivially consistent
+        let fit = goodness_of_fit(&observations[..1], (-1.0, 0.0));
+        assert_eq!(fit.degrees_of_freedom, 0);
+        assert_relative_eq!(fit.p_value, 1.0);
+
+        // an observation with no error in y constrains only x
+        let observations = [
+            observation(0.0, 0.0, 1.0, 1.0),
+            observation(0.0, 0.0, 1.0, 0.0),
+        ];
+        assert_eq!(
+            goodness_of_fit(&observations, (0.0, 0.0)).degrees_of_freedom,
+            1
+        );
+    }
+
+    #[test]
+    fn conflicting_exact_observations_are_inconsistent() {
+        let observations = [
+            observation(0.0, 0.0, 0.0, 0.0),
+            observation(1.0, 0.0, 0.0, 0.0),
+        ];
+        let fit = goodness_of_fit(&observations, (0.5, 0.0));
+        assert!(fit.statistic.is_infinite());
+        assert!(!fit.is_consistent(0.05));
+    }
+
+    #[test]
+    fn anchors_pin_the_estimate() {
+        let anchor = Observation::builder(1.0, 1.0)
+            .error(CovarianceMatrix::new(0.01, 0.01, 0.0).unwrap())
+            .anchor()
+            .build();
+        let observations = [
+            observation(0.0, 0.0, 1.0, 1.0),
+            anchor,
+            observation(2.0, 0.0, 0.001, 0.001),
+        ];
+
+        let (x, y, covariance) = fuse(&observations).unwrap();
+        assert_relative_eq!(x, 1.0);
+        assert_relative_eq!(y, 1.0);
+        assert_relative_eq!(covariance.xx(), 0.01, epsilon = 1e-15);
+
+        // the anchor's position is given, rather than estimated from the detections
+        let fit = goodness_of_fit(&observations, (x, y));
+        assert_eq!(fit.degrees_of_freedom, 4);
+        assert_relative_eq!(fit.statistic, 2.0 + 2000.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn empty_input_has_no_estimate() {
+        assert!(fuse([]).is_none());
+    }
+
+    #[test]
+    fn robust_methods_limit_the_influence_of_outliers() {
+        let weighted = |x, weight| {
+            Observation::builder(x, 0.0)
+                .error(CovarianceMatrix::identity())
+                .weight(weight)
+                .build()
+        };
+        let (confident, doubtful, ignored) =
+            (weighted(0.0, 3.0), weighted(4.0, 1.0), weighted(100.0, 0.0));
+        let members = HashMap::from([(1, &confident), (2, &doubtful), (3, &ignored)]);
+
+        let fusion = fuse_members(&members, FusionMethod::Weighted).unwrap();
+        assert_relative_eq!(fusion.x, 1.0, epsilon = 1e-9);
+        assert_relative_eq!(fusion.covariance.xx(), 0.25, epsilon = 1e-9);
+        assert!(fusion.outliers.is_empty());
+
+        let (left, right) = (weighted(-0.5, 1.0), weighted(0.5, 1.0));
+        let members = HashMap::from([(1, &confident), (2, &left), (3, &right), (4, &doubtful)]);
+        let plain = fuse_members(&members, FusionMethod::InformationFilter).unwrap();
+        let huber = fuse_members(&members, FusionMethod::Huber { threshold: 1.0 }).unwrap();
+        assert_relative_eq!(plain.x, 1.0, epsilon = 1e-9);
+        assert!(huber.x < plain.x);
+        assert!(huber.covariance.xx() > plain.covariance.xx());
+    }
+
+    #[test]
+    fn covariance_intersection_weighs_complementary_estimates_equally() {
+        // each estimate is precise along the axis on which the other is imprecise
+        let a = ((0.0, 0.0), CovarianceMatrix::new(1.0, 4.0, 0.0).unwrap());
+        let b = ((1.0, 0.0), CovarianceMatrix::new(4.0, 1.0, 0.0).unwrap());
+
+        let ((x, y), covariance) = covariance_intersection(a, b);
+        assert_relative_eq!(x, 0.2, epsilon = 1e-9);
+        assert_relative_eq!(y, 0.0, epsilon = 1e-9);
+        assert_relative_eq!(covariance.xx(), 1.6, epsilon = 1e-9);
+        assert_relative_eq!(covariance.yy(), 1.6, epsilon = 1e-9);
+        assert_relative_eq!(covariance.xy(), 0.0, epsilon = 1e-9);
+
+        // a much better estimate is used on its own
+        let precise = ((5.0, 5.0), CovarianceMatrix::new(0.01, 0.01, 0.0).unwrap());
+        let ((x, y), covariance) = covariance_intersection(a, precise);
+        assert_relative_eq!(x, 5.0, epsilon = 1e-9);
+        assert_relative_eq!(y, 5.0, epsilon = 1e-9);
+        assert_eq!(covariance, precise.1);
+
+        // an exact estimate takes precedence
+        let exact = ((3.0, 3.0), CovarianceMatrix::new(0.0, 0.0, 0.0).unwrap());
+        assert_eq!(covariance_intersection(a, exact), exact);
+    }
+}
@@ -0,0 +1,103 @@
+//! Interoperability with the [`geo`](https://docs.rs/geo) ecosystem.
+
+use geo_types::{Coord, LineString, Point, Polygon, Rect};
+
+use crate::{BoundingBox, Observation, observation::ObservationBuilder};
+
+impl From<&Observation> for Point<f64> {
+    fn from(observation: &Observation) -> Self {
+        Self::new(observation.x(), observation.y())
+    }
+}
+
+impl From<Rect<f64>> for BoundingBox {
+    fn from(rect: Rect<f64>) -> Self {
+        Self::from_corners(rect.min().x_y(), rect.max().x_y())
+    }
+}
+
+impl From<BoundingBox> for Rect<f64> {
+    fn from(bounds: BoundingBox) -> Self {
+        Self::new(
+            Coord {
+                x: bounds.min_x,
+                y: bounds.min_y,
+            },
+            Coord {
+                x: bounds.max_x,
+                y: bounds.max_y,
+            },
+        )
+    }
+}
+
+impl Observation {
+    /// Construct a new observation positioned at a [`geo_types::Point`].
+    ///
+    /// See [`Observation::builder`].
+    pub const fn builder_at(point: Point<f64>) -> ObservationBuilder<()> {
+        Self::builder(point.0.x, point.0.y)
+    }
+
+    /// The position of the observation, as a [`geo_types::Point`].
+    #[must_use]
+    pub fn point(&self) -> Point<f64> {
+        self.into()
+    }
+
+    /// A polygonal approximation of the observation's confidence ellipse.
+    ///
+    /// The ellipse is the region within which the squared Mahalanobis distance from the observation
+    /// is no greater than `chi2` (for example, [`CHI2_2D_CONFIDENCE_95`](crate::CHI2_2D_CONFIDENCE_95)),
+    /// approximated with `n_points` vertices.
+    #[must_use]
+    pub fn confidence_ellipse(&self, chi2: f64, n_points: usize) -> Polygon<f64> {
+        let exterior: LineString<f64> = self
+            .error_covariance()
+            .ellipse_points(self.position(), chi2, n_points)
+            .collect();
+        Polygon::new(exterior, Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::{CHI2_2D_CONFIDENCE_95, CovarianceMatrix};
+
+    #[test]
+    fn point_round_trip() {
+        let observation = Observation::builder_at(Point::new(1.0, 2.0))
+            .error(CovarianceMatrix::identity())
+            .build();
+
+        assert_eq!(observation.point(), Point::new(1.0, 2.0));
+    }
+
+    #[test]
+    fn rect_round_trip() {
+        let rect = Rect::new(Coord { x: 4.0, y: 1.0 }, Coord { x: 0.0, y: 3.0 });
+        let bounds = BoundingBox::from(rect);
+
+        assert_eq!(bounds, BoundingBox::from_corners((0.0, 1.0), (4.0, 3.0)));
+        assert_eq!(Rect::from(bounds), rect);
+    }
+
+    #[test]
+    fn circular_confidence_ellipse_has_expected_radius() {
+        let observation = Observation::builder(5.0, 5.0)
+            .circular_95_confidence_error(2.0)
+            .unwrap()
+            .build();
+
+        let ellipse = observation.confidence_ellipse(CHI2_2D_CONFIDENCE_95, 32);
+
+        // The exterior ring is closed, so has one more coordinate than requested
+        assert_eq!(ellipse.exterior().0.len(), 33);
+        for coord in ellipse.exterior() {
+            assert_relative_eq!((coord.x - 5.0).hypot(coord.y - 5.0), 2.0, epsilon = 1e-9);
+        }
+    }
+}
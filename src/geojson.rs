@@ -0,0 +1,361 @@
+//! Export of observations and cliques as [GeoJSON](https://geojson.org/).
+//!
+//! # Uncertainty properties
+//!
+//! The uncertainty of a position is written to the properties of its `Point` feature in two
+//! equivalent forms:
+//!
+//! - `cov_xx`, `cov_xy` and `cov_yy`: the components of the covariance matrix.
+//! - `smaj`, `smin` and `orient`: the one-sigma (standard deviation) semi-major and semi-minor axes
+//!   of the error ellipse, and the orientation of the semi-major axis in degrees clockwise from
+//!   the y axis (north), in `[0, 180)`.
+//!
+//! When reading, the covariance form takes precedence over the ellipse form. `cov_xy` defaults to
+//! zero, and `orient` defaults to zero. A `covariance` array of `[xx, xy, yy]` is also accepted.
+
+use std::{collections::HashSet, fmt::Display};
+
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
+
+use crate::{
+    CovarianceMatrix, FusedEstimate, InvalidCovarianceMatrix, InvalidPosition, Observation,
+};
+
+/// The number of vertices used to approximate each confidence ellipse.
+const ELLIPSE_VERTICES: usize = 36;
+
+/// Build a GeoJSON `FeatureCollection` describing a set of observations and the cliques among them.
+///
+/// Coordinates are written as-is, so are only meaningful to GeoJSON consumers if the observations
+/// are in longitude/latitude (or the consumer is configured for the same projected system).
+pub fn feature_collection<'a, Id>(
+    observations: impl IntoIterator<Item = (&'a Id, &'a Observation)>,
+    estimates: &[FusedEstimate<Id>],
+    chi2: f64,
+) -> Value
+where
+    Id: Display + 'a,
+{
+    let mut features = Vec::new();
+
+    for (id, observation) in observations {
+        let mut properties = Map::new();
+        properties.insert("kind".into(), json!("observation"));
+        properties.insert("id".into(), json!(id.to_string()));
+        if let Some(context) = observation.context() {
+            properties.insert("context".into(), json!(context.to_string()));
+        }
+        if let Some(sensor) = observation.sensor() {
+            properties.insert("sensor".into(), json!(sensor.to_string()));
+        }
+        if let Some(timestamp) = observation.timestamp() {
+            properties.insert("timestamp".into(), json!(timestamp));
+        }
+        if observation.is_anchor() {
+            properties.insert("anchor".into(), json!(true));
+        }
+        let error = observation.error_covariance();
+        insert_uncertainty(&mut properties, error);
+        let coordinates = match observation.altitude() {
+            Some((z, _)) => json!([observation.x(), observation.y(), z]),
+            None => json!([observation.x(), observation.y()]),
+        };
+        features.push(feature(
+            json!({ "type": "Point", "coordinates": coordinates }),
+            Value::Object(properties),
+        ));
+
+        let mut ring: Vec<[f64; 2]> = error
+            .ellipse_points(observation.position(), chi2, ELLIPSE_VERTICES)
+            .map(<[f64; 2]>::from)
+            .collect();
+        // GeoJSON requires linear rings to be explicitly closed
+        if let Some(&first) = ring.first() {
+            ring.push(first);
+        }
+        features.push(feature(
+            json!({ "type": "Polygon", "coordinates": [ring] }),
+            json!({ "kind": "ellipse", "id": id.to_string(), "chi2": chi2 }),
+        ));
+    }
+
+    for estimate in estimates {
+        let mut properties = Map::new();
+        properties.insert("kind".into(), json!("clique"));
+        properties.insert("members".into(), json!(members(&estimate.members)));
+        properties.insert("sensors".into(), json!(members(&estimate.sensors)));
+        insert_uncertainty(&mut properties, estimate.covariance);
+        features.push(feature(
+            json!({ "type": "Point", "coordinates": [estimate.x, estimate.y] }),
+            Value::Object(properties),
+        ));
+    }
+
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+fn feature(geometry: Value, properties: Value) -> Value {
+    Value::Object(Map::from_iter([
+        ("type".into(), json!("Feature")),
+        ("geometry".into(), geometry),
+        ("properties".into(), properties),
+    ]))
+}
+
+fn members<Id: Display>(ids: &HashSet<Id>) -> Vec<String> {
+    let mut members: Vec<String> = ids.iter().map(ToString::to_string).collect();
+    members.sort_unstable();
+    members
+}
+
+/// Write the uncertainty properties (see the [module documentation](self)).
+fn insert_uncertainty(properties: &mut Map<String, Value>, error: CovarianceMatrix) {
+    properties.insert("cov_xx".into(), json!(error.xx()));
+    properties.insert("cov_xy".into(), json!(error.xy()));
+    properties.insert("cov_yy".into(), json!(error.yy()));
+
+    let (major, minor, orientation) = error.ellipse_axes(1.0);
+    let orient = (90.0 - orientation.to_degrees()).rem_euclid(180.0);
+    properties.insert("smaj".into(), json!(major));
+    properties.insert("smin".into(), json!(minor));
+    properties.insert("orient".into(), json!(orient));
+}
+
+/// Read the uncertainty properties (see the [module documentation](self)).
+fn read_uncertainty(properties: &Map<String, Value>) -> Result<CovarianceMatrix, InvalidFeature> {
+    let number = |key: &'static str| {
+        properties
+            .get(key)
+            .map(|value| value.as_f64().ok_or(InvalidFeature::InvalidProperty(key)))
+            .transpose()
+    };
+
+    if let (Some(xx), Some(yy)) = (number("cov_xx")?, number("cov_yy")?) {
+        let xy = number("cov_xy")?.unwrap_or(0.0);
+        return Ok(CovarianceMatrix::new(xx, yy, xy)?);
+    }
+
+    if let (Some(major), Some(minor)) = (number("smaj")?, number("smin")?) {
+        let orientation = (90.0 - number("orient")?.unwrap_or(0.0)).to_radians();
+        let (sin, cos) = orientation.sin_cos();
+        let (major_variance, minor_variance) = (major * major, minor * minor);
+        let xx = major_variance.mul_add(cos * cos, minor_variance * sin * sin);
+        let yy = major_variance.mul_add(sin * sin, minor_variance * cos * cos);
+        let xy = (major_variance - minor_variance) * sin * cos;
+        return Ok(CovarianceMatrix::new(xx, yy, xy)?);
+    }
+
+    if let Some(covariance) = properties.get("covariance") {
+        let components: Option<Vec<f64>> = covariance
+            .as_array()
+            .map(|values| values.iter().filter_map(Value::as_f64).collect());
+        let Some(&[xx, xy, yy]) = components.as_deref() else {
+            return Err(InvalidFeature::InvalidProperty("covariance"));
+        };
+        return Ok(CovarianceMatrix::new(xx, yy, xy)?);
+    }
+
+    Err(InvalidFeature::MissingUncertainty)
+}
+
+impl Observation {
+    /// Read an observation from a GeoJSON `Point` feature.
+    ///
+    /// The uncertainty is read from the feature's properties, following the convention described
+    /// below. The optional `context` and `sensor` properties (UUID strings), `timestamp` property
+    /// (a number) and [`anchor`](Self::is_anchor) property (a boolean) are also read. Any other
+    /// properties, including an `id`, are ignored.
+    ///
+    /// The uncertainty may be given either as the components of a covariance matrix (`cov_xx`,
+    /// `cov_xy` and `cov_yy`), or as a one-sigma error ellipse (`smaj`, `smin` and `orient`, where
+    /// the orientation of the semi-major axis is in degrees clockwise from the y axis). If both
+    /// forms are present, the covariance form takes precedence. This is the same convention used by
+    /// [`CliqueIndex::to_geojson`](crate::CliqueIndex::to_geojson).
+    ///
+    /// ```
+    /// use clique_fusion::Observation;
+    /// use serde_json::json;
+    ///
+    /// let feature = json!({
+    ///     "type": "Feature",
+    ///     "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+    ///     "properties": { "smaj": 3.0, "smin": 2.0, "orient": 90.0 },
+    /// });
+    ///
+    /// let observation = Observation::from_geojson(&feature)?;
+    /// assert_eq!(observation.position(), (1.0, 2.0));
+    /// assert!((observation.error_covariance().xx() - 9.0).abs() < 1e-9);
+    /// # Ok::<(), clique_fusion::InvalidFeature>(())
+    /// ```
+    ///
+    /// Requires the `geojson` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the feature is not a `Point`, if it has no uncertainty properties, or if
+    /// any of the properties it does have are invalid.
+    pub fn from_geojson(feature: &Value) -> Result<Self, InvalidFeature> {
+        let geometry = &feature["geometry"];
+        if geometry["type"] != "Point" {
+            return Err(InvalidFeature::NotAPoint);
+        }
+        let coordinates = geometry["coordinates"]
+            .as_array()
+            .ok_or(InvalidFeature::NotAPoint)?;
+        let (Some(x), Some(y)) = (
+            coordinates.first().and_then(Value::as_f64),
+            coordinates.get(1).and_then(Value::as_f64),
+        ) else {
+            return Err(InvalidFeature::NotAPoint);
+        };
+
+        let empty = Map::new();
+        let properties = feature["properties"].as_object().unwrap_or(&empty);
+        let uuid = |key: &'static str| {
+            properties
+                .get(key)
+                .map(|value| {
+                    value
+                        .as_str()
+                        .and_then(|id| Uuid::parse_str(id).ok())
+                        .ok_or(InvalidFeature::InvalidProperty(key))
+                })
+                .transpose()
+        };
+
+        let mut builder = Self::builder(x, y).error(read_uncertainty(properties)?);
+        if let Some(context) = uuid("context")? {
+            builder = builder.context(context);
+        }
+        if let Some(sensor) = uuid("sensor")? {
+            builder = builder.sensor(sensor);
+        }
+        if let Some(timestamp) = properties.get("timestamp") {
+            let timestamp = timestamp
+                .as_f64()
+                .ok_or(InvalidFeature::InvalidProperty("timestamp"))?;
+            builder = builder.timestamp(timestamp);
+        }
+        if let Some(anchor) = properties.get("anchor") {
+            if anchor
+                .as_bool()
+                .ok_or(InvalidFeature::InvalidProperty("anchor"))?
+            {
+                builder = builder.anchor();
+            }
+        }
+        Ok(builder.try_build()?)
+    }
+}
+
+/// The error returned when an [`Observation`] cannot be read from a GeoJSON feature.
+///
+/// See [`Observation::from_geojson`].
+#[derive(Debug, thiserror::Error, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum InvalidFeature {
+    /// The feature does not have a `Point` geometry with at least two coordinates.
+    #[error("feature geometry must be a point")]
+    NotAPoint,
+
+    /// The feature has neither covariance nor error ellipse properties.
+    #[error("feature has no uncertainty properties")]
+    MissingUncertainty,
+
+    /// A property has the wrong type or format.
+    #[error("feature property '{0}' is invalid")]
+    InvalidProperty(&'static str),
+
+    /// The uncertainty properties do not describe a valid covariance matrix.
+    #[error(transparent)]
+    InvalidCovariance(#[from] InvalidCovarianceMatrix),
+
+    /// The position is not finite.
+    #[error(transparent)]
+    InvalidPosition(#[from] InvalidPosition),
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn point(properties: Value) -> Value {
+        let mut feature = json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+        });
+        feature["properties"] = properties;
+        feature
+    }
+
+    #[test]
+    fn uncertainty_round_trips_in_both_forms() {
+        let error = CovarianceMatrix::new(4.0, 1.0, 1.5).unwrap();
+        let mut properties = Map::new();
+        insert_uncertainty(&mut properties, error);
+
+        let covariance_form = read_uncertainty(&properties).unwrap();
+        properties.retain(|key, _| !key.starts_with("cov_"));
+        let ellipse_form = read_uncertainty(&properties).unwrap();
+
+        for read in [covariance_form, ellipse_form] {
+            assert_relative_eq!(read.xx(), error.xx(), epsilon = 1e-9);
+            assert_relative_eq!(read.xy(), error.xy(), epsilon = 1e-9);
+            assert_relative_eq!(read.yy(), error.yy(), epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn ellipse_orientation_is_clockwise_from_north() {
+        let observation =
+            Observation::from_geojson(&point(json!({ "smaj": 2.0, "smin": 1.0, "orient": 45.0 })))
+                .unwrap();
+
+        let error = observation.error_covariance();
+        assert_relative_eq!(error.xx(), 2.5, epsilon = 1e-9);
+        assert_relative_eq!(error.yy(), 2.5, epsilon = 1e-9);
+        assert_relative_eq!(error.xy(), 1.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn features_are_read_tolerantly() {
+        let context = Uuid::from_u128(1);
+        let observation = Observation::from_geojson(&point(json!({
+            "covariance": [2.0, 0.5, 1.0],
+            "context": context.to_string(),
+            "timestamp": 10.0,
+            "anchor": true,
+            "name": "ignored",
+        })))
+        .unwrap();
+        assert_eq!(observation.position(), (1.0, 2.0));
+        assert_relative_eq!(observation.error_covariance().xy(), 0.5);
+        assert_eq!(observation.context(), Some(context));
+        assert_eq!(observation.timestamp(), Some(10.0));
+        assert!(observation.is_anchor());
+
+        assert!(matches!(
+            Observation::from_geojson(&point(json!({ "cov_xx": 1.0, "cov_yy": 1.0, "anchor": 1 }))),
+            Err(InvalidFeature::InvalidProperty("anchor"))
+        ));
+        assert!(matches!(
+            Observation::from_geojson(&point(json!({}))),
+            Err(InvalidFeature::MissingUncertainty)
+        ));
+        assert!(matches!(
+            Observation::from_geojson(&point(json!({ "cov_xx": "1", "cov_yy": 1.0 }))),
+            Err(InvalidFeature::InvalidProperty("cov_xx"))
+        ));
+        assert!(matches!(
+            Observation::from_geojson(&point(json!({ "cov_xx": -1.0, "cov_yy": 1.0 }))),
+            Err(InvalidFeature::InvalidCovariance(_))
+        ));
+        assert!(matches!(
+            Observation::from_geojson(&json!({ "geometry": { "type": "LineString" } })),
+            Err(InvalidFeature::NotAPoint)
+        ));
+    }
+}
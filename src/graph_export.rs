@@ -0,0 +1,46 @@
+/// The names of the columns of [`GraphExport::node_features`], in order.
+pub const NODE_FEATURES: [&str; 6] = ["x", "y", "cov_xx", "cov_xy", "cov_yy", "degree"];
+
+/// The compatibility graph of an index, in a layout suited to graph-learning frameworks.
+///
+/// Nodes are numbered by their position in [`Self::ids`]. Each row of [`Self::node_features`]
+/// describes the node with the same number, with the columns named by [`NODE_FEATURES`]. Edges are
+/// listed in coordinate (COO) format: the `k`th edge runs from `sources[k]` to `targets[k]`, and
+/// has the squared Mahalanobis distance `distances[k]`. The graph is undirected, so each edge is
+/// listed once in each direction, as most frameworks expect.
+///
+/// Every observation in the index is a node, including those with no compatible neighbours.
+///
+/// See [`CliqueIndex::graph_export`](crate::CliqueIndex::graph_export).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GraphExport<Id> {
+    /// The ID of the observation at each node.
+    pub ids: Vec<Id>,
+
+    /// The features of each node. See [`NODE_FEATURES`].
+    pub node_features: Vec<[f64; NODE_FEATURES.len()]>,
+
+    /// The node at which each edge starts.
+    pub sources: Vec<usize>,
+
+    /// The node at which each edge ends.
+    pub targets: Vec<usize>,
+
+    /// The squared Mahalanobis distance between the observations joined by each edge.
+    pub distances: Vec<f64>,
+}
+
+impl<Id> GraphExport<Id> {
+    /// The number of nodes.
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// The number of directed edges, which is twice the number of compatible pairs.
+    #[must_use]
+    pub fn edge_count(&self) -> usize {
+        self.sources.len()
+    }
+}
@@ -0,0 +1,168 @@
+use std::num::NonZeroUsize;
+
+use crate::BoundingBox;
+
+/// A regular grid of observation and clique counts over a bounding box.
+///
+/// This is intended for summarising very large datasets (for example, as a heatmap), where
+/// rendering individual observations or cliques is infeasible.
+///
+/// See [`CliqueIndex::density_grid`](crate::CliqueIndex::density_grid).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityGrid {
+    bounds: BoundingBox,
+    columns: NonZeroUsize,
+    rows: NonZeroUsize,
+    observations: Vec<usize>,
+    cliques: Vec<usize>,
+}
+
+/// A single cell of a [`DensityGrid`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridCell {
+    /// The column of the cell (increasing with x).
+    pub column: usize,
+    /// The row of the cell (increasing with y).
+    pub row: usize,
+    /// The region covered by the cell.
+    pub bounds: BoundingBox,
+    /// The number of observations positioned within the cell.
+    pub observations: usize,
+    /// The number of cliques whose centroid lies within the cell.
+    pub cliques: usize,
+}
+
+impl DensityGrid {
+    pub(crate) fn new(bounds: BoundingBox, columns: NonZeroUsize, rows: NonZeroUsize) -> Self {
+        let len = columns.get() * rows.get();
+        Self {
+            bounds,
+            columns,
+            rows,
+            observations: vec![0; len],
+            cliques: vec![0; len],
+        }
+    }
+
+    /// Count an observation at the given position. Positions outside the grid are ignored.
+    pub(crate) fn add_observation(&mut self, x: f64, y: f64) {
+        if let Some(i) = self.offset_of(x, y) {
+            self.observations[i] += 1;
+        }
+    }
+
+    /// Count a clique with its centroid at the given position. Positions outside the grid are ignored.
+    pub(crate) fn add_clique(&mut self, x: f64, y: f64) {
+        if let Some(i) = self.offset_of(x, y) {
+            self.cliques[i] += 1;
+        }
+    }
+
+    /// The region covered by the grid.
+    #[must_use]
+    pub const fn bounds(&self) -> BoundingBox {
+        self.bounds
+    }
+
+    /// The number of columns in the grid.
+    #[must_use]
+    pub const fn columns(&self) -> usize {
+        self.columns.get()
+    }
+
+    /// The number of rows in the grid.
+    #[must_use]
+    pub const fn rows(&self) -> usize {
+        self.rows.get()
+    }
+
+    /// Get a single cell of the grid, or `None` if the column or row is out of range.
+    #[must_use]
+    pub fn cell(&self, column: usize, row: usize) -> Option<GridCell> {
+        if column >= self.columns() || row >= self.rows() {
+            return None;
+        }
+        let i = row * self.columns() + column;
+        Some(GridCell {
+            column,
+            row,
+            bounds: self.cell_bounds(column, row),
+            observations: self.observations[i],
+            cliques: self.cliques[i],
+        })
+    }
+
+    /// Iterate over all cells of the grid, in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = GridCell> + '_ {
+        (0..self.rows()).flat_map(move |row| {
+            (0..self.columns()).filter_map(move |column| self.cell(column, row))
+        })
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn cell_bounds(&self, column: usize, row: usize) -> BoundingBox {
+        let cell_width = self.bounds.width() / self.columns() as f64;
+        let cell_height = self.bounds.height() / self.rows() as f64;
+        let min_x = (column as f64).mul_add(cell_width, self.bounds.min_x);
+        let min_y = (row as f64).mul_add(cell_height, self.bounds.min_y);
+        BoundingBox {
+            min_x,
+            min_y,
+            max_x: min_x + cell_width,
+            max_y: min_y + cell_height,
+        }
+    }
+
+    /// The offset into the cell buffers of the cell containing the given position.
+    ///
+    /// Positions on the maximum edge of the grid are assigned to the last column/row.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    fn offset_of(&self, x: f64, y: f64) -> Option<usize> {
+        if !self.bounds.contains(x, y) {
+            return None;
+        }
+
+        let bin = |value: f64, min: f64, extent: f64, count: usize| {
+            if extent <= 0.0 {
+                return 0;
+            }
+            let bin = ((value - min) / extent * count as f64).floor() as usize;
+            bin.min(count - 1)
+        };
+
+        let column = bin(x, self.bounds.min_x, self.bounds.width(), self.columns());
+        let row = bin(y, self.bounds.min_y, self.bounds.height(), self.rows());
+        Some(row * self.columns() + column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_on_the_maximum_edge_are_binned_into_the_last_cell() {
+        let bounds = BoundingBox::from_corners((0.0, 0.0), (4.0, 2.0));
+        let mut grid = DensityGrid::new(
+            bounds,
+            NonZeroUsize::new(4).unwrap(),
+            NonZeroUsize::new(2).unwrap(),
+        );
+
+        grid.add_observation(4.0, 2.0);
+        grid.add_observation(0.0, 0.0);
+        grid.add_observation(5.0, 0.0); // out of bounds
+
+        assert_eq!(grid.cell(3, 1).unwrap().observations, 1);
+        assert_eq!(grid.cell(0, 0).unwrap().observations, 1);
+        assert_eq!(grid.cells().map(|cell| cell.observations).sum::<usize>(), 2);
+        assert_eq!(
+            grid.cell(1, 0).unwrap().bounds,
+            BoundingBox::from_corners((1.0, 0.0), (2.0, 1.0))
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Bucketing of geodetic observations into the cells of the [H3](https://h3geo.org) grid.
+
+use geo_types::{Coord, Rect};
+use h3o::{
+    CellIndex, LatLng, Resolution,
+    geom::{ContainmentMode, Tiler, TilerBuilder},
+};
+
+use crate::{BoundingBox, CellSystem};
+
+/// The hexagonal cells of the H3 grid at a fixed resolution.
+///
+/// Positions are interpreted as longitude (x) and latitude (y), in degrees, and the errors of the
+/// observations must be expressed in square degrees accordingly. Bounds which extend beyond the
+/// antimeridian are wrapped around it, and bounds which extend beyond the poles are clamped to them.
+///
+/// The resolution should be chosen so that the cells are comparable in size to the typical
+/// compatibility radius of the observations, as for [`SquareCells`](crate::SquareCells). The
+/// covering of a search region is then only a handful of cells, which are also the natural unit
+/// for distributing the observations between processes (see
+/// [`CellCandidates::cells`](crate::CellCandidates::cells)).
+///
+/// ```
+/// use clique_fusion::{
+///     CHI2_2D_CONFIDENCE_95, CellCandidates, CliqueIndex, Config, CovarianceMatrix, H3Cells,
+///     Observation, Unique,
+/// };
+/// use h3o::Resolution;
+///
+/// let source = CellCandidates::new(H3Cells::new(Resolution::Nine));
+/// let mut index = CliqueIndex::with_candidate_source(Config::new(CHI2_2D_CONFIDENCE_95), source);
+/// // an error of roughly 10 metres, in square degrees
+/// let error = CovarianceMatrix::new(1e-8, 1e-8, 0.0).unwrap();
+/// for (id, (lng, lat)) in [(1, (-0.1276, 51.5072)), (2, (-0.12761, 51.50721)), (3, (2.35, 48.86))] {
+///     let observation = Observation::builder(lng, lat).error(error).build();
+///     index.insert(Unique { data: observation, id });
+/// }
+///
+/// assert_eq!(index.cliques().len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct H3Cells {
+    resolution: Resolution,
+}
+
+impl H3Cells {
+    /// The cells of the H3 grid at the given resolution.
+    #[must_use]
+    pub const fn new(resolution: Resolution) -> Self {
+        Self { resolution }
+    }
+
+    /// The resolution of the cells.
+    #[must_use]
+    pub const fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    /// A tiler covering the bounds, split at the antimeridian.
+    fn tiler(self, bounds: &BoundingBox) -> Tiler {
+        let mut tiler = TilerBuilder::new(self.resolution)
+            .containment_mode(ContainmentMode::Covers)
+            // the bounds are split at the antimeridian below
+            .disable_transmeridian_heuristic()
+            .build();
+        let (min_lat, max_lat) = (bounds.min_y.max(-90.0), bounds.max_y.min(90.0));
+        for (min_lng, max_lng) in longitude_ranges(bounds.min_x, bounds.max_x) {
+            let rect = Rect::new(
+                Coord {
+                    x: min_lng,
+                    y: min_lat,
+                },
+                Coord {
+                    x: max_lng,
+                    y: max_lat,
+                },
+            );
+            // the bounds of a search region are always finite, so the polygon is valid
+            let _ = tiler.add(rect.to_polygon());
+        }
+        tiler
+    }
+}
+
+/// The ranges of longitude within `[-180, 180]` covered by `[min, max]`, wrapping around the
+/// antimeridian.
+fn longitude_ranges(min: f64, max: f64) -> Vec<(f64, f64)> {
+    if max - min >= 360.0 {
+        return vec![(-180.0, 180.0)];
+    }
+    let wrap = |lng: f64| (lng + 180.0).rem_euclid(360.0) - 180.0;
+    let (min, max) = (wrap(min), wrap(max));
+    if min <= max {
+        vec![(min, max)]
+    } else {
+        vec![(min, 180.0), (-180.0, max)]
+    }
+}
+
+impl CellSystem for H3Cells {
+    type Cell = CellIndex;
+
+    /// # Panics
+    ///
+    /// Panics if the position is not finite.
+    fn cell(&self, x: f64, y: f64) -> Self::Cell {
+        LatLng::new(y, x)
+            .expect("positions of observations are finite")
+            .to_cell(self.resolution)
+    }
+
+    fn covering(&self, bounds: &BoundingBox) -> Vec<Self::Cell> {
+        let mut cells: Vec<_> = self.tiler(bounds).into_coverage().collect();
+        // the corners are included in case the bounds are degenerate (for example, a point)
+        for (x, y) in [
+            (bounds.min_x, bounds.min_y),
+            (bounds.min_x, bounds.max_y),
+            (bounds.max_x, bounds.min_y),
+            (bounds.max_x, bounds.max_y),
+        ] {
+            if let Ok(corner) = LatLng::new(y.clamp(-90.0, 90.0), x) {
+                cells.push(corner.to_cell(self.resolution));
+            }
+        }
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+
+    fn covering_size(&self, bounds: &BoundingBox) -> usize {
+        self.tiler(bounds).coverage_size_hint()
+    }
+
+    fn intersects(&self, cell: &Self::Cell, bounds: &BoundingBox) -> bool {
+        let boundary = cell.boundary();
+        let (lngs, lats): (Vec<_>, Vec<_>) = boundary.iter().map(|ll| (ll.lng(), ll.lat())).unzip();
+        let extent = |values: &[f64]| {
+            values
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                    (min.min(value), max.max(value))
+                })
+        };
+        let (min_lng, max_lng) = extent(&lngs);
+        let (min_lat, max_lat) = extent(&lats);
+        // Cells which contain a pole or straddle the antimeridian span half of the longitudes, and
+        // the edges of a cell bulge beyond its vertices, so the extent is padded by its own size
+        if max_lng - min_lng > 180.0 {
+            return true;
+        }
+        let (pad_lng, pad_lat) = (max_lng - min_lng, max_lat - min_lat);
+        let overlaps_lat = min_lat - pad_lat <= bounds.max_y && bounds.min_y <= max_lat + pad_lat;
+        overlaps_lat
+            && longitude_ranges(bounds.min_x, bounds.max_x)
+                .into_iter()
+                .any(|(min, max)| min_lng - pad_lng <= max && min <= max_lng + pad_lng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverings_contain_every_position_within_the_bounds() {
+        let cells = H3Cells::new(Resolution::Seven);
+        for (min, max) in [
+            ((-0.2, 51.4), (0.1, 51.6)),
+            ((179.9, -10.0), (180.1, -9.9)),
+            ((10.0, 89.95), (10.0, 90.1)),
+            ((2.35, 48.86), (2.35, 48.86)),
+            ((2.35, 48.0), (2.35, 49.0)),
+        ] {
+            let bounds = BoundingBox::from_corners(min, max);
+            let covering = cells.covering(&bounds);
+            for i in 0..=10 {
+                for j in 0..=10 {
+                    let t = |a: f64, b: f64, k: i32| a + (b - a) * f64::from(k) / 10.0;
+                    let (x, y) = (t(min.0, max.0, i), t(min.1, max.1, j).min(90.0));
+                    let cell = cells.cell(x, y);
+                    assert!(
+                        covering.contains(&cell),
+                        "{cell} at ({x}, {y}) is not covered"
+                    );
+                    assert!(cells.intersects(&cell, &bounds));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn distant_cells_do_not_intersect() {
+        let cells = H3Cells::new(Resolution::Seven);
+        let bounds = BoundingBox::from_corners((-0.2, 51.4), (0.1, 51.6));
+        assert!(!cells.intersects(&cells.cell(2.35, 48.86), &bounds));
+        assert!(!cells.intersects(&cells.cell(-179.0, 51.5), &bounds));
+    }
+}
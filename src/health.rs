@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+/// A cheap summary of the state of a [`CliqueIndex`](crate::CliqueIndex), for monitoring.
+///
+/// See [`CliqueIndex::health`](crate::CliqueIndex::health).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Health {
+    /// The total number of observations in the index.
+    pub total_observations: usize,
+
+    /// The number of observations which are compatible with at least one other observation.
+    pub connected_observations: usize,
+
+    /// The number of edges in the compatibility graph.
+    pub compatibility_edges: usize,
+
+    /// The number of maximal cliques.
+    pub cliques: usize,
+
+    /// The number of members of the largest clique, or zero if there are no cliques.
+    pub largest_clique: usize,
+
+    /// The number of observations whose cliques have been approximated, because their region
+    /// exceeded the enumeration budget of the [`DensityFallback`](crate::DensityFallback) or
+    /// [maximum neighbourhood](crate::Config::max_neighbourhood).
+    pub approximate_observations: usize,
+
+    /// The number of cliques which have been dropped to keep the index within its
+    /// [clique capacity](crate::Config::clique_capacity), since its cliques were last enumerated
+    /// in full.
+    pub dropped_cliques: usize,
+
+    /// A counter which is incremented each time the index is modified.
+    ///
+    /// Two snapshots of the same index with the same generation have the same observations and cliques.
+    pub generation: u64,
+
+    /// The time taken by the most recent call to [`CliqueIndex::insert`](crate::CliqueIndex::insert),
+    /// or `None` if no observation has been inserted since the index was constructed.
+    pub last_insert_latency: Option<Duration>,
+}
+
+impl Health {
+    /// Returns `true` if every clique has been enumerated exactly, within the enumeration budget.
+    #[must_use]
+    pub const fn within_budget(&self) -> bool {
+        self.approximate_observations == 0
+    }
+
+    /// Returns `true` if cliques have been dropped to keep the index within its
+    /// [clique capacity](crate::Config::clique_capacity).
+    #[must_use]
+    pub const fn is_truncated(&self) -> bool {
+        self.dropped_cliques > 0
+    }
+}
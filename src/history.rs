@@ -0,0 +1,158 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+};
+
+use crate::{CliqueIdAllocation, CliqueIdAllocator};
+
+/// A stable identifier of a clique.
+///
+/// A clique keeps its identifier as its membership evolves, for as long as it can be matched
+/// one-to-one with its successor (as for [`CliqueRelation::Matched`](crate::CliqueRelation::Matched)).
+/// The cliques resulting from a merge or a split are new cliques, with new identifiers.
+/// By default, identifiers are never reused by an index (see [`CliqueIdAllocation`]).
+///
+/// See [`CliqueIndex::clique_ids`](crate::CliqueIndex::clique_ids).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CliqueId(u64);
+
+impl CliqueId {
+    /// The identifier as an integer.
+    #[must_use]
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for CliqueId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The history of the membership of a clique.
+///
+/// See [`CliqueIndex::clique_history`](crate::CliqueIndex::clique_history).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CliqueHistory<Id> {
+    /// The [generation](crate::Health::generation) of the index at which the clique was created.
+    pub created: u64,
+
+    /// The members of the clique when it was created.
+    pub initial_members: HashSet<Id>,
+
+    /// The changes to the membership of the clique since it was created, oldest first.
+    ///
+    /// Only the most recent changes are retained, up to the limit set by
+    /// [`Config::clique_history`](crate::Config::clique_history). Once older changes have been
+    /// discarded, the membership before the earliest retained change can't be reconstructed from
+    /// `initial_members`.
+    pub changes: VecDeque<MembershipChange<Id>>,
+}
+
+/// A change to the membership of a clique.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MembershipChange<Id> {
+    /// The [generation](crate::Health::generation) of the index at which the change occurred.
+    pub generation: u64,
+
+    /// The observations which joined the clique.
+    pub added: HashSet<Id>,
+
+    /// The observations which left the clique.
+    pub removed: HashSet<Id>,
+}
+
+/// Allocates clique identifiers, and records the histories of live cliques.
+pub struct Histories<Id> {
+    allocator: Box<dyn CliqueIdAllocator>,
+
+    /// The maximum number of changes retained per clique, or `None` if histories are not recorded.
+    limit: Option<usize>,
+
+    live: HashMap<CliqueId, CliqueHistory<Id>>,
+}
+
+impl<Id> fmt::Debug for Histories<Id>
+where
+    Id: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histories")
+            .field("limit", &self.limit)
+            .field("live", &self.live)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Id> Histories<Id>
+where
+    Id: Eq + Hash + Copy,
+{
+    pub fn new(limit: Option<usize>, allocation: CliqueIdAllocation) -> Self {
+        Self {
+            allocator: allocation.allocator(),
+            limit,
+            live: HashMap::new(),
+        }
+    }
+
+    /// Replace the allocator of the identifiers of new cliques.
+    pub fn set_allocator(&mut self, allocator: Box<dyn CliqueIdAllocator>) {
+        self.allocator = allocator;
+    }
+
+    /// Allocate an identifier for a new clique.
+    pub fn create(&mut self, generation: u64, members: &HashSet<Id>) -> CliqueId {
+        let id = CliqueId(self.allocator.allocate());
+        if self.limit.is_some() {
+            self.live.insert(
+                id,
+                CliqueHistory {
+                    created: generation,
+                    initial_members: members.clone(),
+                    changes: VecDeque::new(),
+                },
+            );
+        }
+        id
+    }
+
+    /// Record a change in the membership of a clique, if it has changed.
+    pub fn update(
+        &mut self,
+        id: CliqueId,
+        generation: u64,
+        before: &HashSet<Id>,
+        after: &HashSet<Id>,
+    ) {
+        let (Some(limit), Some(history)) = (self.limit, self.live.get_mut(&id)) else {
+            return;
+        };
+        if before == after {
+            return;
+        }
+        history.changes.push_back(MembershipChange {
+            generation,
+            added: after.difference(before).copied().collect(),
+            removed: before.difference(after).copied().collect(),
+        });
+        while history.changes.len() > limit {
+            history.changes.pop_front();
+        }
+    }
+
+    /// Discard the history of a clique which no longer exists.
+    pub fn dissolve(&mut self, id: CliqueId) {
+        self.live.remove(&id);
+        self.allocator.release(id.0);
+    }
+
+    pub fn get(&self, id: CliqueId) -> Option<&CliqueHistory<Id>> {
+        self.live.get(&id)
+    }
+}
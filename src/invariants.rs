@@ -0,0 +1,92 @@
+//! Violations of the internal consistency of an index, found by
+//! [`CliqueIndex::validate`](crate::CliqueIndex::validate).
+
+use std::fmt;
+
+use crate::CliqueId;
+
+/// An inconsistency between the observations, compatibility graph and cliques of an index.
+///
+/// Each of these indicates a bug in the incremental maintenance of the index, rather than a
+/// problem with its input.
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvariantViolation<Id>
+where
+    Id: fmt::Debug,
+{
+    /// The graph contains an observation which is not in the index.
+    #[error("the graph contains observation {0:?}, which is not in the index")]
+    UnknownObservation(Id),
+
+    /// The graph connects an observation to itself.
+    #[error("the graph connects observation {0:?} to itself")]
+    SelfLoop(Id),
+
+    /// The graph contains an edge, but not its reverse.
+    #[error("the graph connects {from:?} to {to:?}, but not {to:?} to {from:?}")]
+    AsymmetricEdge {
+        /// The observation whose neighbours include the other.
+        from: Id,
+
+        /// The observation whose neighbours don't include the other.
+        to: Id,
+    },
+
+    /// Two compatible observations are not connected in the graph.
+    #[error("observations {a:?} and {b:?} are compatible, but not connected in the graph")]
+    MissingEdge {
+        /// The first observation.
+        a: Id,
+
+        /// The second observation.
+        b: Id,
+    },
+
+    /// Two observations are connected in the graph, but are not compatible.
+    #[error("observations {a:?} and {b:?} are connected in the graph, but not compatible")]
+    SpuriousEdge {
+        /// The first observation.
+        a: Id,
+
+        /// The second observation.
+        b: Id,
+    },
+
+    /// Two members of a clique are not connected in the graph.
+    #[error("members {a:?} and {b:?} of clique {clique} are not connected in the graph")]
+    NotAClique {
+        /// The identifier of the clique.
+        clique: CliqueId,
+
+        /// The first member.
+        a: Id,
+
+        /// The second member.
+        b: Id,
+    },
+
+    /// A clique which was enumerated exactly could be extended by another observation.
+    #[error("clique {clique} is not maximal, since it could be extended by {extension:?}")]
+    NotMaximal {
+        /// The identifier of the clique.
+        clique: CliqueId,
+
+        /// An observation connected to every member of the clique.
+        extension: Id,
+    },
+
+    /// The same set of observations is reported as more than one clique.
+    #[error("clique {0} is a duplicate of another clique")]
+    DuplicateClique(CliqueId),
+
+    /// An edge of a region which was enumerated exactly is not within any clique.
+    #[error("the edge between {a:?} and {b:?} is not within any clique")]
+    UncoveredEdge {
+        /// The first observation.
+        a: Id,
+
+        /// The second observation.
+        b: Id,
+    },
+}
@@ -3,13 +3,128 @@
 mod observation;
 pub use observation::Observation;
 pub use observation::{
-    CHI2_2D_CONFIDENCE_90, CHI2_2D_CONFIDENCE_95, CHI2_2D_CONFIDENCE_99, CovarianceMatrix,
-    InvalidCovarianceMatrix,
+    CHI2_1D_CONFIDENCE_90, CHI2_1D_CONFIDENCE_95, CHI2_1D_CONFIDENCE_99, CHI2_2D_CONFIDENCE_90,
+    CHI2_2D_CONFIDENCE_95, CHI2_2D_CONFIDENCE_99, CovarianceMatrix, InvalidAltitude,
+    InvalidCovarianceMatrix, InvalidPosition, InvalidRadius, InvalidVelocity,
 };
 
+mod chi2;
+pub use chi2::{InvalidConfidence, chi2_threshold};
+
+mod bounding_box;
+pub use bounding_box::BoundingBox;
+
+mod grid;
+pub use grid::{DensityGrid, GridCell};
+
+#[cfg(feature = "geo")]
+mod geo;
+
+#[cfg(feature = "geojson")]
+mod geojson;
+#[cfg(feature = "geojson")]
+pub use geojson::InvalidFeature;
+
 mod spatial_index;
-pub use spatial_index::Unique;
+pub use spatial_index::{CandidateSource, DiscardLog, Unique};
+
+mod cells;
+pub use cells::{CellCandidates, CellSystem, SquareCells};
+
+#[cfg(feature = "h3")]
+mod h3;
+#[cfg(feature = "h3")]
+pub use h3::H3Cells;
+
+mod sharding;
+pub use sharding::{Shard, Sharder, merge_shards};
+
+mod config;
+pub use config::{
+    Compatibility, Config, ConfigView, DensityFallback, InvalidChi2, MotionModel,
+    SingularCovariance, TrackDirection,
+};
+
+mod duplicates;
+pub use duplicates::DuplicatePolicy;
+
+mod eviction;
+pub use eviction::{CliqueOverflow, EvictionPolicy};
+
+mod assignment;
+pub use assignment::Assignment;
+
+pub mod fusion;
+pub use fusion::{FusedClique, FusedEstimate, FusionMethod, GoodnessOfFit};
+
+mod sensor;
+pub use sensor::{InvalidSensorModel, SensorModel, SensorModels};
+
+mod centroid;
+pub use centroid::CliqueCentroid;
+
+mod adjacency;
+pub use adjacency::{Adjacency, DenseAdjacency};
 
 mod clique_index;
+mod clique_set;
 mod cliques;
-pub use clique_index::CliqueIndex;
+mod union_find;
+pub use clique_index::{CliqueIndex, IntegerCliqueIndex, MaybeSendSync};
+pub use clique_set::{CliqueSet, FlatCliques};
+pub use cliques::EnumerationStrategy;
+
+mod events;
+pub use events::{CliqueEvent, CliqueEventSink, TimedCliqueEvent};
+
+mod history;
+pub use history::{CliqueHistory, CliqueId, MembershipChange};
+
+mod clique_ids;
+pub use clique_ids::{
+    CliqueIdAllocation, CliqueIdAllocator, MonotonicIds, RandomIds, RecyclingIds,
+};
+
+mod health;
+pub use health::Health;
+
+mod error;
+pub use error::Error;
+
+#[cfg(feature = "validate")]
+mod invariants;
+#[cfg(feature = "validate")]
+pub use invariants::InvariantViolation;
+
+mod cancel;
+pub use cancel::{BuildPhase, CancellationToken, Progress};
+
+#[cfg(feature = "tokio")]
+mod spawn;
+
+mod seeded;
+mod stable_hash;
+
+mod precluster;
+pub use precluster::{Representative, precluster_contexts};
+
+mod graph_export;
+pub use graph_export::{GraphExport, NODE_FEATURES};
+
+mod profiling;
+#[cfg(feature = "profiling")]
+pub use profiling::{Phase, PhaseBreakdown, PhaseTimings, Profiler};
+
+mod probe;
+pub use probe::ProbeResult;
+
+mod diff;
+pub use diff::{CliqueDiff, CliqueRelation, clique_diff};
+
+#[cfg(feature = "test-vectors")]
+mod test_vectors;
+#[cfg(feature = "test-vectors")]
+pub use test_vectors::{InvalidTestVector, TEST_VECTOR_VERSION, TestVector, TestVectorMismatch};
+
+#[cfg(feature = "synthetic")]
+pub mod synthetic;
@@ -1,11 +1,14 @@
-use nalgebra::{Point2, Vector2};
+use std::sync::Arc;
+
+use nalgebra::{Matrix2, Point2, Vector2};
 
 mod covariance_matrix;
 pub use covariance_matrix::CovarianceMatrix;
 pub use covariance_matrix::InvalidCovarianceMatrix;
+pub use covariance_matrix::InvalidRadius;
 use uuid::Uuid;
 
-use crate::observation::covariance_matrix::InvalidRadius;
+use crate::{Config, InvalidConfidence, MotionModel, SingularCovariance, chi2_threshold};
 
 /// Chi-squared threshold for 90% confidence in 2D (2 degrees of freedom)
 pub const CHI2_2D_CONFIDENCE_90: f64 = 4.605;
@@ -16,12 +19,29 @@ pub const CHI2_2D_CONFIDENCE_95: f64 = 5.991;
 /// Chi-squared threshold for 99% confidence in 2D (2 degrees of freedom)
 pub const CHI2_2D_CONFIDENCE_99: f64 = 9.210;
 
+/// Chi-squared threshold for 90% confidence in 1D (1 degree of freedom)
+pub const CHI2_1D_CONFIDENCE_90: f64 = 2.706;
+
+/// Chi-squared threshold for 95% confidence in 1D (1 degree of freedom)
+pub const CHI2_1D_CONFIDENCE_95: f64 = 3.841;
+
+/// Chi-squared threshold for 99% confidence in 1D (1 degree of freedom)
+pub const CHI2_1D_CONFIDENCE_99: f64 = 6.635;
+
 #[must_use]
 #[derive(Debug)]
 pub struct ObservationBuilder<E> {
     position: Point2<f64>,
     error: E,
     context: Option<Uuid>,
+    sensor: Option<Uuid>,
+    altitude: Option<(f64, f64)>,
+    velocity: Option<(Vector2<f64>, CovarianceMatrix)>,
+    timestamp: Option<f64>,
+    weight: f64,
+    tag: Option<u64>,
+    labels: Vec<Arc<str>>,
+    anchor: bool,
 }
 
 impl ObservationBuilder<()> {
@@ -30,15 +50,31 @@ impl ObservationBuilder<()> {
             position: Point2::new(x, y),
             error: (),
             context: None,
+            sensor: None,
+            altitude: None,
+            velocity: None,
+            timestamp: None,
+            weight: 1.0,
+            tag: None,
+            labels: Vec::new(),
+            anchor: false,
         }
     }
 
     /// Sets the positional error for the [`Observation`].
-    pub const fn error(self, error: CovarianceMatrix) -> ObservationBuilder<CovarianceMatrix> {
+    pub fn error(self, error: CovarianceMatrix) -> ObservationBuilder<CovarianceMatrix> {
         ObservationBuilder {
             position: self.position,
             error,
             context: self.context,
+            sensor: self.sensor,
+            altitude: self.altitude,
+            velocity: self.velocity,
+            timestamp: self.timestamp,
+            weight: self.weight,
+            tag: self.tag,
+            labels: self.labels,
+            anchor: self.anchor,
         }
     }
 
@@ -56,6 +92,14 @@ impl ObservationBuilder<()> {
             position: self.position,
             error,
             context: self.context,
+            sensor: self.sensor,
+            altitude: self.altitude,
+            velocity: self.velocity,
+            timestamp: self.timestamp,
+            weight: self.weight,
+            tag: self.tag,
+            labels: self.labels,
+            anchor: self.anchor,
         })
     }
 }
@@ -68,19 +112,177 @@ impl<E> ObservationBuilder<E> {
         self.context = Some(id);
         self
     }
+
+    /// Set the sensor which made the [`Observation`].
+    ///
+    /// See [`Observation::sensor`].
+    pub const fn sensor(mut self, id: Uuid) -> Self {
+        self.sensor = Some(id);
+        self
+    }
+
+    /// Set the altitude of the [`Observation`], and the variance of its error.
+    ///
+    /// See [`Observation::altitude`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the altitude is not finite, or if the variance is negative or not finite.
+    pub fn altitude(mut self, z: f64, variance: f64) -> Result<Self, InvalidAltitude> {
+        if z.is_finite() && variance.is_finite() && variance >= 0.0 {
+            self.altitude = Some((z, variance));
+            Ok(self)
+        } else {
+            Err(InvalidAltitude { z, variance })
+        }
+    }
+
+    /// Set the velocity of the observed object, and the covariance of its error.
+    ///
+    /// See [`Observation::velocity`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either component of the velocity is not finite.
+    pub fn velocity(
+        mut self,
+        vx: f64,
+        vy: f64,
+        error: CovarianceMatrix,
+    ) -> Result<Self, InvalidVelocity> {
+        if vx.is_finite() && vy.is_finite() {
+            self.velocity = Some((Vector2::new(vx, vy), error));
+            Ok(self)
+        } else {
+            Err(InvalidVelocity { vx, vy })
+        }
+    }
+
+    /// Set the time at which the [`Observation`] was made.
+    ///
+    /// See [`Observation::timestamp`].
+    pub const fn timestamp(mut self, timestamp: f64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Set the weight of the [`Observation`].
+    ///
+    /// See [`Observation::weight`].
+    pub const fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set the payload tag of the [`Observation`].
+    ///
+    /// See [`Observation::tag`].
+    pub const fn tag(mut self, tag: u64) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Add a label to the [`Observation`], such as its classification.
+    ///
+    /// This can be called repeatedly to add several labels. See [`Observation::labels`].
+    pub fn label(mut self, label: impl Into<Arc<str>>) -> Self {
+        let label = label.into();
+        if !self.labels.contains(&label) {
+            self.labels.push(label);
+        }
+        self
+    }
+
+    /// Mark the [`Observation`] as an anchor.
+    ///
+    /// See [`Observation::is_anchor`].
+    pub const fn anchor(mut self) -> Self {
+        self.anchor = true;
+        self
+    }
 }
 
 impl ObservationBuilder<CovarianceMatrix> {
     /// Finalise the builder and return an [`Observation`].
-    pub const fn build(self) -> Observation {
+    ///
+    /// For untrusted input, use [`Self::try_build`].
+    ///
+    /// # Panics
+    ///
+    /// This method panics in debug builds if [`Self::try_build`] would have returned an error.
+    ///
+    /// In release builds no checking is done.
+    pub fn build(self) -> Observation {
+        if cfg!(debug_assertions) {
+            self.try_build().unwrap()
+        } else {
+            self.build_unchecked()
+        }
+    }
+
+    /// Finalise the builder and return an [`Observation`], checking that its position is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either ordinate of the position is not finite (ie. is NaN or infinite).
+    pub fn try_build(self) -> Result<Observation, InvalidPosition> {
+        let Point2 { coords } = self.position;
+        if coords.iter().all(|ordinate| ordinate.is_finite()) {
+            Ok(self.build_unchecked())
+        } else {
+            Err(InvalidPosition {
+                x: self.position.x,
+                y: self.position.y,
+            })
+        }
+    }
+
+    fn build_unchecked(self) -> Observation {
         Observation {
             position: self.position,
             error: self.error,
             context: self.context,
+            sensor: self.sensor,
+            altitude: self.altitude,
+            velocity: self.velocity,
+            timestamp: self.timestamp,
+            weight: self.weight,
+            tag: self.tag,
+            labels: self.labels,
+            anchor: self.anchor,
         }
     }
 }
 
+/// The error returned when an observation is constructed with a non-finite position.
+#[derive(Debug, thiserror::Error, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[error("observation position must be finite (got x: {x}, y: {y})")]
+pub struct InvalidPosition {
+    x: f64,
+    y: f64,
+}
+
+/// The error returned when an observation is given a non-finite velocity.
+#[derive(Debug, thiserror::Error, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[error("velocity must be finite (got vx: {vx}, vy: {vy})")]
+pub struct InvalidVelocity {
+    vx: f64,
+    vy: f64,
+}
+
+/// The error returned when an observation is given an invalid altitude.
+#[derive(Debug, thiserror::Error, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[error(
+    "altitude must be finite, with a finite, non-negative variance (got z: {z}, variance: {variance})"
+)]
+pub struct InvalidAltitude {
+    z: f64,
+    variance: f64,
+}
+
 /// Represents an observation of an object at a fixed location.
 ///
 /// The observation has some measurement error associated with it.
@@ -144,6 +346,22 @@ pub struct Observation {
     error: CovarianceMatrix,
 
     context: Option<Uuid>,
+
+    sensor: Option<Uuid>,
+
+    altitude: Option<(f64, f64)>,
+
+    velocity: Option<(Vector2<f64>, CovarianceMatrix)>,
+
+    timestamp: Option<f64>,
+
+    weight: f64,
+
+    tag: Option<u64>,
+
+    labels: Vec<Arc<str>>,
+
+    anchor: bool,
 }
 
 impl Observation {
@@ -184,11 +402,163 @@ impl Observation {
         self.context
     }
 
+    /// The sensor which made the observation, if known.
+    ///
+    /// Unlike [`Self::context`], this has no effect on compatibility testing. It identifies the
+    /// provenance of an observation, so that observations from the same sensor can be recognised
+    /// across contexts (for example, over several passes of the same platform).
+    #[must_use]
+    pub const fn sensor(&self) -> Option<Uuid> {
+        self.sensor
+    }
+
+    /// The altitude of the observation and the variance of its error, if known.
+    ///
+    /// Altitude is only used to gate compatibility: two observations which both have an altitude
+    /// are incompatible if their altitudes differ by more than the configured number of standard
+    /// deviations (see [`Config::altitude_gate`](crate::Config::altitude_gate)). It plays no part in
+    /// the geometry of cliques or in fusion, which remain two-dimensional.
+    #[must_use]
+    pub const fn altitude(&self) -> Option<(f64, f64)> {
+        self.altitude
+    }
+
+    /// The velocity (vx, vy) of the observed object, if known.
+    ///
+    /// Velocity is only used to gate compatibility, when the index is configured with a motion model
+    /// (see [`MotionModel`](crate::MotionModel)).
+    #[must_use]
+    pub fn velocity(&self) -> Option<(f64, f64)> {
+        self.velocity.map(|(velocity, _)| (velocity.x, velocity.y))
+    }
+
+    /// The covariance of the error in the [velocity](Self::velocity), if known.
+    #[must_use]
+    pub fn velocity_covariance(&self) -> Option<CovarianceMatrix> {
+        self.velocity.map(|(_, error)| error)
+    }
+
+    /// The time at which the observation was made, if known.
+    ///
+    /// The units and epoch are chosen by the caller, but must be consistent across an index.
+    #[must_use]
+    pub const fn timestamp(&self) -> Option<f64> {
+        self.timestamp
+    }
+
+    /// The relative weight (or confidence) of the observation, which defaults to `1.0`.
+    ///
+    /// This is carried alongside the observation for the caller's benefit, and does not affect
+    /// compatibility testing or fusion.
+    #[must_use]
+    pub const fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    /// An opaque tag identifying a caller-side payload associated with the observation, if any.
+    #[must_use]
+    pub const fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+
+    /// The labels attached to the observation, in the order they were added.
+    ///
+    /// Labels are carried alongside the observation for the caller's benefit, and do not affect
+    /// compatibility testing or fusion. They can be used to filter cliques, with
+    /// [`CliqueIndex::cliques_with_label`](crate::CliqueIndex::cliques_with_label).
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.labels.iter().map(AsRef::as_ref)
+    }
+
+    /// Returns `true` if the observation has the given label.
+    #[must_use]
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels.iter().any(|other| **other == *label)
+    }
+
+    /// Returns `true` if the observation is an anchor, such as a surveyed landmark with an
+    /// authoritative position.
+    ///
+    /// Anchors are tested for compatibility and form cliques like any other observation, but if a
+    /// clique contains an anchor, its [fused estimate](crate::FusedEstimate) is determined by its
+    /// anchors alone, so that it isn't dragged around by noisy detections. An anchor may have zero
+    /// error. Anchors of distinct objects should share a [context](Self::context), so that they
+    /// are never fused with each other.
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, CovarianceMatrix, Observation, Unique};
+    ///
+    /// let landmark = Observation::builder(0.0, 0.0)
+    ///     .error(CovarianceMatrix::new(0.01, 0.01, 0.0).unwrap())
+    ///     .anchor()
+    ///     .build();
+    /// let detection = Observation::builder(0.5, 0.5)
+    ///     .circular_95_confidence_error(2.0)
+    ///     .unwrap()
+    ///     .build();
+    /// let index = CliqueIndex::from_observations(
+    ///     vec![Unique { data: landmark, id: 1 }, Unique { data: detection, id: 2 }],
+    ///     CHI2_2D_CONFIDENCE_95,
+    /// );
+    ///
+    /// let estimate = &index.fused_estimates()[0];
+    /// assert_eq!(estimate.position(), (0.0, 0.0));
+    /// ```
+    #[must_use]
+    pub const fn is_anchor(&self) -> bool {
+        self.anchor
+    }
+
+    /// Replace the error of the observation.
+    pub(crate) const fn with_error(mut self, error: CovarianceMatrix) -> Self {
+        self.error = error;
+        self
+    }
+
+    /// The same observation, at a different position.
+    pub(crate) const fn with_position(mut self, x: f64, y: f64) -> Self {
+        self.position = Point2::new(x, y);
+        self
+    }
+
     /// Construct a new observation
     pub const fn builder(x: f64, y: f64) -> ObservationBuilder<()> {
         ObservationBuilder::new(x, y)
     }
 
+    /// Construct a new one-dimensional observation, at a distance `chainage` along a track (such
+    /// as a pipeline or road), with the given variance.
+    ///
+    /// The chainage is stored as the x ordinate, with a y ordinate of zero and no error across the
+    /// track. Index one-dimensional observations with a configuration which is
+    /// [one-dimensional](Config::one_dimensional), and a threshold with one degree of freedom,
+    /// such as [`CHI2_1D_CONFIDENCE_95`].
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_1D_CONFIDENCE_95, CliqueIndex, Config, Observation, Unique};
+    ///
+    /// let config = Config::new(CHI2_1D_CONFIDENCE_95).one_dimensional();
+    /// let mut index = CliqueIndex::with_config(config);
+    /// for (id, chainage) in [(1, 100.0), (2, 101.0), (3, 120.0)] {
+    ///     let observation = Observation::along_track(chainage, 1.0).unwrap().build();
+    ///     index.insert(Unique { data: observation, id });
+    /// }
+    ///
+    /// assert_eq!(index.cliques().len(), 1);
+    /// let (chainage, _) = index.fused_estimates()[0].position();
+    /// assert_eq!(chainage, 100.5);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `variance` is negative or not finite.
+    pub fn along_track(
+        chainage: f64,
+        variance: f64,
+    ) -> Result<ObservationBuilder<CovarianceMatrix>, InvalidCovarianceMatrix> {
+        Ok(Self::builder(chainage, 0.0).error(CovarianceMatrix::new(variance, 0.0, 0.0)?))
+    }
+
     /// Determines whether two observations are statistically compatible under the assumption
     /// that they represent independent measurements of the same underlying object.
     ///
@@ -222,13 +592,220 @@ impl Observation {
     /// - [Chi-squared distribution](https://en.wikipedia.org/wiki/Chi-squared_distribution)
     #[must_use]
     pub fn is_compatible_with(&self, other: &Self, chi2_threshold: f64) -> bool {
+        self.mahalanobis_distance_squared(other) <= chi2_threshold
+    }
+
+    /// The [Mahalanobis distance](https://en.wikipedia.org/wiki/Mahalanobis_distance) from the
+    /// observation to the point (x, y), under the observation's error.
+    ///
+    /// This is the number of standard deviations by which the point is separated from the
+    /// observation. Its square may be compared against a chi-squared threshold (with two degrees
+    /// of freedom). The distance is infinite if the observation has zero error, and the point is
+    /// elsewhere.
+    #[must_use]
+    pub fn mahalanobis_to_point(&self, x: f64, y: f64) -> f64 {
+        let delta = Vector2::new(x - self.position.x, y - self.position.y);
+        if delta == Vector2::zeros() {
+            return 0.0;
+        }
+        mahalanobis_squared(delta, self.error).sqrt()
+    }
+
+    /// Returns `true` if the point (x, y) lies within the observation's confidence ellipse at the
+    /// given `confidence`, in `(0, 1)`.
+    ///
+    /// This is useful for hit-testing, for example of a location clicked on a map.
+    ///
+    /// ```
+    /// use clique_fusion::Observation;
+    ///
+    /// let observation = Observation::builder(0.0, 0.0)
+    ///     .circular_95_confidence_error(10.0)
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// assert!(observation.contains_point(9.0, 0.0, 0.95)?);
+    /// assert!(!observation.contains_point(11.0, 0.0, 0.95)?);
+    /// # Ok::<(), clique_fusion::InvalidConfidence>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `confidence` is not strictly between 0 and 1.
+    pub fn contains_point(
+        &self,
+        x: f64,
+        y: f64,
+        confidence: f64,
+    ) -> Result<bool, InvalidConfidence> {
+        let chi2 = chi2_threshold(confidence, 2)?;
+        Ok(self.mahalanobis_to_point(x, y).powi(2) <= chi2)
+    }
+
+    /// The exact bit patterns of every floating-point field of the observation, in a fixed order.
+    ///
+    /// Negative zero is normalised, so that `-0.0` and `0.0` have the same bits.
+    pub(crate) fn exact_bits(&self) -> impl Iterator<Item = u64> + '_ {
+        let covariance = |error: &CovarianceMatrix| [error.xx(), error.yy(), error.xy()];
+        [self.position.x, self.position.y]
+            .into_iter()
+            .chain(covariance(&self.error))
+            .chain(self.altitude.into_iter().flat_map(<[f64; 2]>::from))
+            .chain(self.velocity.iter().flat_map(move |(velocity, error)| {
+                [velocity.x, velocity.y]
+                    .into_iter()
+                    .chain(covariance(error))
+            }))
+            .chain(self.timestamp)
+            .chain([self.weight])
+            .map(|value| (value + 0.0).to_bits())
+    }
+
+    /// Feed the exact bit patterns of every field of the observation into a hasher.
+    ///
+    /// Negative zero is normalised, so that `-0.0` and `0.0` hash equally.
+    pub(crate) fn hash_exact(&self, state: &mut impl std::hash::Hasher) {
+        for bits in self.exact_bits() {
+            state.write_u64(bits);
+        }
+        std::hash::Hash::hash(
+            &(
+                self.context,
+                self.sensor,
+                self.tag,
+                &self.labels,
+                self.altitude.is_some(),
+                self.velocity.is_some(),
+                self.timestamp.is_some(),
+            ),
+            state,
+        );
+    }
+
+    /// Returns `true` if the observations can never be compatible, regardless of their positions.
+    ///
+    /// This is the case if they share a context, or if they both have an altitude and these differ
+    /// by more than the configured number of standard deviations of their combined error.
+    pub(crate) fn is_gated_out(&self, other: &Self, config: &Config) -> bool {
+        // Observations in the same context (e.g. same measurement or snapshot) are assumed to be
+        // distinct with negligible relative error, and therefore should never be fused.
+        let same_context = matches!((self.context, other.context), (Some(a), Some(b)) if a == b);
+        let altitude_separated = match (self.altitude, other.altitude) {
+            (Some((z1, variance1)), Some((z2, variance2))) => {
+                (z1 - z2).abs() > config.altitude_gate * (variance1 + variance2).sqrt()
+            }
+            _ => false,
+        };
+        same_context || altitude_separated
+    }
+
+    /// The squared Mahalanobis distance between two observations, under the sum of their covariance matrices.
+    ///
+    /// See [`Self::is_compatible_with`].
+    pub(crate) fn mahalanobis_distance_squared(&self, other: &Self) -> f64 {
         let delta = self.position - other.position;
         let delta_vec = Vector2::new(delta.x, delta.y);
 
         let combined_covariance = self.error + other.error;
 
-        let d2 = mahalanobis_squared(delta_vec, combined_covariance);
-        d2 <= chi2_threshold
+        mahalanobis_squared(delta_vec, combined_covariance)
+    }
+
+    /// The squared Mahalanobis distance between two observations under the motion model and
+    /// registration error of the configuration.
+    ///
+    /// Under [`MotionModel::ConstantVelocity`], if both observations have a timestamp, the older
+    /// observation is propagated to the time of the newer one using its velocity (or the newer one
+    /// is propagated back using its velocity, if only it has one). Under [`MotionModel::Ageing`],
+    /// the error of the older observation is grown by its age relative to the newer one.
+    /// The [registration error](Config::registration_error), if any, is added once to the combined
+    /// error, and a singular combined error is handled according to the
+    /// [policy](Config::singular_covariance). Otherwise, this is the same as
+    /// [`Self::mahalanobis_distance_squared`].
+    ///
+    /// If the configuration is [one-dimensional](Config::one_dimensional), only the differences
+    /// along the x axis are compared.
+    pub(crate) fn gated_distance_squared(&self, other: &Self, config: &Config) -> f64 {
+        let (delta, error) = self.residual(other, config.motion_model);
+        let error = config
+            .registration_error
+            .map_or(error, |registration| error + registration);
+        if config.one_dimensional {
+            let variance = match config.singular_covariance {
+                SingularCovariance::Exact { epsilon } if error.xx() <= 0.0 => epsilon,
+                _ => error.xx(),
+            };
+            return if variance > 0.0 {
+                delta.x * delta.x / variance
+            } else {
+                f64::INFINITY
+            };
+        }
+        let error = match config.singular_covariance {
+            SingularCovariance::Exact { epsilon } if error.is_singular() => {
+                error + CovarianceMatrix::new_unchecked(epsilon, epsilon, 0.0)
+            }
+            _ => error,
+        };
+        config
+            .directional_distance_squared([self, other], delta, error)
+            .unwrap_or_else(|| mahalanobis_squared(delta, error))
+    }
+
+    /// The difference between the positions of two observations under the given motion model,
+    /// and the combined covariance of its error.
+    fn residual(
+        &self,
+        other: &Self,
+        motion_model: MotionModel,
+    ) -> (Vector2<f64>, CovarianceMatrix) {
+        let direct = || (self.position - other.position, self.error + other.error);
+        let (Some(t1), Some(t2)) = (self.timestamp, other.timestamp) else {
+            return direct();
+        };
+        let process_noise = match motion_model {
+            MotionModel::Static => return direct(),
+            MotionModel::Ageing { process_noise } => {
+                let growth = Matrix2::identity() * (process_noise * (t1 - t2).abs());
+                let error = CovarianceMatrix::from_matrix(
+                    Matrix2::from(self.error) + Matrix2::from(other.error) + growth,
+                );
+                return (self.position - other.position, error);
+            }
+            MotionModel::ConstantVelocity { process_noise } => process_noise,
+        };
+        let (older, newer, dt) = if t1 <= t2 {
+            (self, other, t2 - t1)
+        } else {
+            (other, self, t1 - t2)
+        };
+        let (moving, fixed, dt) = match (older.velocity, newer.velocity) {
+            (Some(_), _) => (older, newer, dt),
+            (None, Some(_)) => (newer, older, -dt),
+            (None, None) => return direct(),
+        };
+        let (position, error) = moving.propagate(dt, process_noise);
+        (position - fixed.position.coords, error + fixed.error)
+    }
+
+    /// Predict the position of the observed object `dt` after the observation, and the covariance
+    /// of the error in that prediction, under a constant-velocity model.
+    ///
+    /// The process noise is the spectral density of a white-noise acceleration, which contributes a
+    /// variance of `process_noise * |dt|³ / 3` in each axis. The correlation between the position
+    /// and velocity errors is assumed to be zero.
+    fn propagate(&self, dt: f64, process_noise: f64) -> (Vector2<f64>, CovarianceMatrix) {
+        let Some((velocity, velocity_error)) = self.velocity else {
+            return (self.position.coords, self.error);
+        };
+        let position = self.position.coords + velocity * dt;
+        let noise = process_noise * dt.abs().powi(3) / 3.0;
+        let error = CovarianceMatrix::from_matrix(
+            Matrix2::from(self.error)
+                + Matrix2::from(velocity_error) * (dt * dt)
+                + Matrix2::identity() * noise,
+        );
+        (position, error)
     }
 
     /// Computes a conservative maximum radius for spatial filtering to identify potentially
@@ -243,12 +820,24 @@ impl Observation {
     /// # Parameters
     /// - `chi2_threshold`: Chi-squared threshold for compatibility (e.g., 5.991 for 95% confidence in 2D)
     /// - `max_other_variance`: Assumed upper bound on the largest eigenvalue of the candidate observation's covariance
+    ///
+    /// The radius is a Euclidean distance (not squared). This is the bound used by the index to
+    /// shortlist candidates, so it can be used to prefilter observations before they are inserted.
+    ///
+    /// ```
+    /// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CovarianceMatrix, Observation};
+    ///
+    /// let a = Observation::builder(0.0, 0.0).error(CovarianceMatrix::identity()).build();
+    /// let b = Observation::builder(3.0, 0.0).error(CovarianceMatrix::identity()).build();
+    ///
+    /// // every observation with a variance no greater than 1.0 which is compatible with `a` lies
+    /// // within this radius
+    /// let radius = a.max_compatibility_radius(CHI2_2D_CONFIDENCE_95, 1.0);
+    /// assert!(a.is_compatible_with(&b, CHI2_2D_CONFIDENCE_95));
+    /// assert!(3.0 <= radius);
+    /// ```
     #[must_use]
-    pub(crate) fn max_compatibility_radius(
-        &self,
-        chi2_threshold: f64,
-        max_other_variance: f64,
-    ) -> f64 {
+    pub fn max_compatibility_radius(&self, chi2_threshold: f64, max_other_variance: f64) -> f64 {
         let combined_max_variance = self.error.max_variance() + max_other_variance;
         (chi2_threshold * combined_max_variance).sqrt()
     }
@@ -269,6 +858,39 @@ mod tests {
     use approx::assert_relative_eq;
     use nalgebra::Matrix2;
 
+    #[test]
+    fn metadata_defaults_and_builder() {
+        let plain = Observation::builder(0.0, 0.0)
+            .error(CovarianceMatrix::identity())
+            .build();
+        assert_eq!(plain.timestamp(), None);
+        assert_relative_eq!(plain.weight(), 1.0);
+        assert_eq!(plain.tag(), None);
+
+        let observation = Observation::builder(0.0, 0.0)
+            .timestamp(12.5)
+            .weight(0.25)
+            .tag(7)
+            .error(CovarianceMatrix::identity())
+            .build();
+        assert_eq!(observation.timestamp(), Some(12.5));
+        assert_relative_eq!(observation.weight(), 0.25);
+        assert_eq!(observation.tag(), Some(7));
+
+        let labelled = Observation::builder(0.0, 0.0)
+            .label("vehicle")
+            .error(CovarianceMatrix::identity())
+            .label("classified")
+            .label("vehicle")
+            .build();
+        assert_eq!(
+            labelled.labels().collect::<Vec<_>>(),
+            vec!["vehicle", "classified"]
+        );
+        assert!(labelled.has_label("classified"));
+        assert!(!plain.has_label("classified"));
+    }
+
     #[test]
     fn observation_with_circular_error_constructs_correctly() {
         let radius = 3.0;
@@ -283,6 +905,70 @@ mod tests {
         assert_relative_eq!(actual_variance, expected, epsilon = f64::EPSILON);
     }
 
+    #[test]
+    fn try_build_rejects_non_finite_positions() {
+        let build = |x, y| {
+            Observation::builder(x, y)
+                .error(CovarianceMatrix::identity())
+                .try_build()
+        };
+
+        assert!(build(1.0, 2.0).is_ok());
+        assert!(build(f64::NAN, 2.0).is_err());
+        assert!(build(1.0, f64::INFINITY).is_err());
+        assert!(build(f64::NEG_INFINITY, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn constant_velocity_propagates_the_older_observation() {
+        let error = CovarianceMatrix::new_unchecked(0.01, 0.01, 0.0);
+        let moving = |x: f64, t: f64, velocity: bool| {
+            let builder = Observation::builder(x, 0.0).error(error).timestamp(t);
+            if velocity {
+                builder.velocity(10.0, 0.0, error).unwrap().build()
+            } else {
+                builder.build()
+            }
+        };
+        let config = &Config::new(CHI2_2D_CONFIDENCE_95)
+            .motion_model(MotionModel::ConstantVelocity { process_noise: 0.0 });
+
+        // the older observation carries the velocity
+        let (older, newer) = (moving(0.0, 0.0, true), moving(20.0, 2.0, false));
+        assert!(older.mahalanobis_distance_squared(&newer) > CHI2_2D_CONFIDENCE_95);
+        assert_relative_eq!(older.gated_distance_squared(&newer, config), 0.0);
+        assert_relative_eq!(newer.gated_distance_squared(&older, config), 0.0);
+
+        // only the newer observation carries the velocity, so it is propagated backwards
+        let (older, newer) = (moving(0.0, 0.0, false), moving(20.0, 2.0, true));
+        assert_relative_eq!(older.gated_distance_squared(&newer, config), 0.0);
+
+        // without timestamps, observations are compared directly
+        let stationary = Observation::builder(20.0, 0.0).error(error).build();
+        assert_relative_eq!(
+            older.gated_distance_squared(&stationary, config),
+            older.mahalanobis_distance_squared(&stationary)
+        );
+    }
+
+    #[test]
+    fn mahalanobis_to_point_scales_with_the_error() {
+        let observation = Observation::builder(1.0, 1.0)
+            .error(CovarianceMatrix::new(4.0, 9.0, 0.0).unwrap())
+            .build();
+
+        assert_relative_eq!(observation.mahalanobis_to_point(1.0, 1.0), 0.0);
+        assert_relative_eq!(observation.mahalanobis_to_point(3.0, 1.0), 1.0);
+        assert_relative_eq!(observation.mahalanobis_to_point(1.0, -5.0), 2.0);
+
+        let exact = Observation::builder(0.0, 0.0)
+            .error(CovarianceMatrix::new(0.0, 0.0, 0.0).unwrap())
+            .build();
+        assert_relative_eq!(exact.mahalanobis_to_point(0.0, 0.0), 0.0);
+        assert!(exact.mahalanobis_to_point(1.0, 0.0).is_infinite());
+        assert!(exact.contains_point(1.0, 0.0, 2.0).is_err());
+    }
+
     #[test]
     fn mahalanobis_distance_zero_for_same_position() {
         let cov = CovarianceMatrix::new_unchecked(2.0, 1.0, 0.0);
@@ -1,15 +1,53 @@
 use std::ops::Add;
 
 use super::CHI2_2D_CONFIDENCE_95;
+use crate::{InvalidConfidence, chi2_threshold};
 use nalgebra::Matrix2;
 
 /// Relative error to use for checking matrices are positive semi-definite
 const PSD_EPS_REL: f64 = 1e-12;
 
 /// A covariance matrix, used to represent the positional error ellipse of an observation.
+///
+/// With the `serde` feature, this is (de)serialized as its components `{ "xx", "xy", "yy" }`, and
+/// validated as for [`Self::new`] when deserialized.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Components", try_from = "Components")
+)]
 pub struct CovarianceMatrix(Matrix2<f64>);
 
+/// The serialized form of a [`CovarianceMatrix`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Components {
+    xx: f64,
+    xy: f64,
+    yy: f64,
+}
+
+#[cfg(feature = "serde")]
+impl From<CovarianceMatrix> for Components {
+    fn from(matrix: CovarianceMatrix) -> Self {
+        Self {
+            xx: matrix.xx(),
+            xy: matrix.xy(),
+            yy: matrix.yy(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Components> for CovarianceMatrix {
+    type Error = InvalidCovarianceMatrix;
+
+    fn try_from(components: Components) -> Result<Self, Self::Error> {
+        Self::new(components.xx, components.yy, components.xy)
+    }
+}
+
 impl CovarianceMatrix {
     /// construct a new covariance matrix from its components.
     ///
@@ -97,6 +135,34 @@ impl CovarianceMatrix {
         self.0.determinant()
     }
 
+    /// Returns `true` if the error is zero in some direction, so that the matrix has no inverse.
+    ///
+    /// This includes the zero matrix (an exact position), and matrices whose confidence ellipses
+    /// are degenerate lines. The determinant is compared relative to the scale of the matrix, so
+    /// that matrices which are singular but for rounding error are included.
+    ///
+    /// ```
+    /// use clique_fusion::CovarianceMatrix;
+    ///
+    /// assert!(CovarianceMatrix::new(0.0, 0.0, 0.0).unwrap().is_singular());
+    /// assert!(CovarianceMatrix::new(1.0, 1.0, 1.0).unwrap().is_singular());
+    /// assert!(!CovarianceMatrix::identity().is_singular());
+    /// ```
+    #[must_use]
+    pub fn is_singular(&self) -> bool {
+        let scale = self.0.trace();
+        self.0.determinant() <= 1e-12 * scale * scale
+    }
+
+    /// Construct a covariance matrix from a matrix which is known to be symmetric and positive
+    /// semi-definite (for example, the result of combining valid covariance matrices).
+    ///
+    /// The off-diagonal terms are averaged to remove any asymmetry due to rounding error.
+    pub(crate) fn from_matrix(matrix: Matrix2<f64>) -> Self {
+        let xy = 0.5 * (matrix[(0, 1)] + matrix[(1, 0)]);
+        Self(Matrix2::new(matrix[(0, 0)], xy, xy, matrix[(1, 1)]))
+    }
+
     /// The identity matrix
     #[must_use]
     pub fn identity() -> Self {
@@ -139,6 +205,73 @@ impl CovarianceMatrix {
         0.5 * (trace + discrim)
     }
 
+    /// The principal axes of the confidence ellipse at the given chi-squared threshold.
+    ///
+    /// Returns the semi-major axis length, the semi-minor axis length, and the orientation of the
+    /// semi-major axis (in radians, anticlockwise from the x axis).
+    pub(crate) fn ellipse_axes(&self, chi2: f64) -> (f64, f64, f64) {
+        let major_variance = self.max_variance();
+        let minor_variance = (self.0.trace() - major_variance).max(0.0);
+        let orientation = 0.5 * (2.0 * self.xy()).atan2(self.xx() - self.yy());
+        (
+            (chi2 * major_variance).sqrt(),
+            (chi2 * minor_variance).sqrt(),
+            orientation,
+        )
+    }
+
+    /// Approximate the confidence ellipse centred at `center` by a polygon with `n_points` vertices.
+    ///
+    /// `confidence` is the probability mass enclosed by the ellipse, in `(0, 1)`. The vertices are
+    /// evenly spaced by parametric angle, in anticlockwise order starting at the end of the
+    /// semi-major axis. The polygon is not explicitly closed (the first vertex is not repeated).
+    ///
+    /// ```
+    /// use clique_fusion::CovarianceMatrix;
+    ///
+    /// let error = CovarianceMatrix::new(4.0, 1.0, 0.5).unwrap();
+    /// let polygon = error.ellipse_polygon((10.0, 20.0), 0.95, 32)?;
+    ///
+    /// assert_eq!(polygon.len(), 32);
+    /// # Ok::<(), clique_fusion::InvalidConfidence>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `confidence` is not strictly between 0 and 1.
+    pub fn ellipse_polygon(
+        &self,
+        center: (f64, f64),
+        confidence: f64,
+        n_points: usize,
+    ) -> Result<Vec<(f64, f64)>, InvalidConfidence> {
+        let chi2 = chi2_threshold(confidence, 2)?;
+        Ok(self.ellipse_points(center, chi2, n_points).collect())
+    }
+
+    /// Sample `n` points evenly (by parametric angle) around the confidence ellipse centred at `center`.
+    pub(crate) fn ellipse_points(
+        &self,
+        center: (f64, f64),
+        chi2: f64,
+        n: usize,
+    ) -> impl Iterator<Item = (f64, f64)> {
+        let (major, minor, orientation) = self.ellipse_axes(chi2);
+        let (sin, cos) = orientation.sin_cos();
+        #[allow(clippy::cast_precision_loss)]
+        let step = std::f64::consts::TAU / n as f64;
+        (0..n).map(move |i| {
+            #[allow(clippy::cast_precision_loss)]
+            let (sin_t, cos_t) = (step * i as f64).sin_cos();
+            let u = major * cos_t;
+            let v = minor * sin_t;
+            (
+                u.mul_add(cos, -(v * sin)) + center.0,
+                u.mul_add(sin, v * cos) + center.1,
+            )
+        })
+    }
+
     /// Safely compute the inverse of the covariance matrix, handling different cases gracefully
     ///
     /// # Returns
@@ -174,12 +307,15 @@ impl CovarianceMatrix {
     }
 }
 
+/// The error returned when a confidence radius is negative or not finite
 #[derive(Debug, thiserror::Error, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[error("radius must be >=0.0 (got {0})")]
 pub struct InvalidRadius(f64);
 
 /// The error returned when the given variances do not form a valid covariance matrix
 #[derive(Debug, thiserror::Error, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[error("not a valid positive semi-definite matrix (xx: {xx}, yy: {yy}, xy: {xy})")]
 pub struct InvalidCovarianceMatrix {
     xx: f64,
@@ -212,6 +348,18 @@ mod tests {
         assert!(CovarianceMatrix::new(2.0, 1.0, 0.0).is_ok());
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialization_is_validated() {
+        let valid: CovarianceMatrix =
+            serde_json::from_str(r#"{ "xx": 2.0, "xy": 0.5, "yy": 1.0 }"#).unwrap();
+        assert_eq!(valid, CovarianceMatrix::new(2.0, 1.0, 0.5).unwrap());
+
+        let invalid =
+            serde_json::from_str::<CovarianceMatrix>(r#"{ "xx": -1.0, "xy": 0.0, "yy": 1.0 }"#);
+        assert!(invalid.is_err());
+    }
+
     #[test]
     fn covariance_matrix_accepts_singular() {
         assert!(CovarianceMatrix::new(1.0, 0.0, 0.0).is_ok()); // rank-deficient but valid
@@ -283,6 +431,32 @@ mod tests {
         assert_relative_eq!(approx_a, a, epsilon = 1e-10);
     }
 
+    #[test]
+    fn ellipse_points_lie_on_the_confidence_boundary() {
+        let cov = CovarianceMatrix::new(4.0, 1.0, 1.5).unwrap();
+        let inverse = cov.safe_inverse().unwrap();
+
+        for (x, y) in cov.ellipse_points((10.0, -5.0), CHI2_2D_CONFIDENCE_95, 16) {
+            let delta = nalgebra::Vector2::new(x - 10.0, y + 5.0);
+            let d2 = (delta.transpose() * inverse * delta)[(0, 0)];
+            assert_relative_eq!(d2, CHI2_2D_CONFIDENCE_95, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn ellipse_polygon_matches_the_chi2_threshold() {
+        let cov = CovarianceMatrix::new(4.0, 1.0, 1.5).unwrap();
+        let chi2 = chi2_threshold(0.9, 2).unwrap();
+
+        let polygon = cov.ellipse_polygon((1.0, 2.0), 0.9, 8).unwrap();
+
+        assert_eq!(
+            polygon,
+            cov.ellipse_points((1.0, 2.0), chi2, 8).collect::<Vec<_>>()
+        );
+        assert!(cov.ellipse_polygon((1.0, 2.0), 1.0, 8).is_err());
+    }
+
     #[test]
     fn from_circular_95_confidence_accepts_positive_radius() {
         let radius = 2.0;
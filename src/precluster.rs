@@ -0,0 +1,201 @@
+//! Merging of duplicate marks of the same object within a context, before cliques are found.
+
+use std::collections::HashMap;
+
+use nalgebra::Matrix2;
+use uuid::Uuid;
+
+use crate::{CovarianceMatrix, Observation, Unique, union_find::UnionFind};
+
+/// A single observation standing in for a cluster of observations in the same context, as
+/// returned by [`precluster_contexts`].
+#[derive(Debug, Clone)]
+pub struct Representative<Id> {
+    /// The representative observation.
+    ///
+    /// It has the ID and metadata of the first member, the mean position of the members, and the
+    /// mean of their errors.
+    pub observation: Unique<Observation, Id>,
+
+    /// The IDs of the observations it replaces (including its own), in their original order.
+    pub members: Vec<Id>,
+}
+
+/// Merge observations in the same context which lie within `tolerance` of each other (by
+/// Euclidean distance) into a single representative.
+///
+/// This is an optional pre-pass before cliques are found across contexts. Several marks of the
+/// same object in one context (for example, by different annotators of the same image) are not
+/// independent observations, so indexing them separately inflates the cliques they belong to,
+/// and over-tightens the fused estimate. Clusters are formed by single linkage, so marks are
+/// merged if they are connected by a chain of marks each within the tolerance of the next. The
+/// error of a representative is the mean of the errors of its members, rather than their fusion,
+/// since the members are not independent.
+///
+/// Observations without a context are passed through unchanged. The representatives are returned
+/// in the order of their first members.
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique, precluster_contexts};
+/// use uuid::Uuid;
+///
+/// let (image, other) = (Uuid::new_v4(), Uuid::new_v4());
+/// let observations = [(1, 0.0, image), (2, 0.05, image), (3, 0.1, other)]
+///     .into_iter()
+///     .map(|(id, x, context)| Unique {
+///         data: Observation::builder(x, 0.0)
+///             .circular_95_confidence_error(1.0)
+///             .unwrap()
+///             .context(context)
+///             .build(),
+///         id,
+///     });
+///
+/// // the two marks in the same image are merged
+/// let representatives = precluster_contexts(observations, 0.1);
+/// assert_eq!(representatives[0].members, vec![1, 2]);
+///
+/// let index = CliqueIndex::from_observations(
+///     representatives.into_iter().map(|representative| representative.observation).collect(),
+///     CHI2_2D_CONFIDENCE_95,
+/// );
+/// assert_eq!(index.cliques().len(), 1);
+/// ```
+pub fn precluster_contexts<Id>(
+    observations: impl IntoIterator<Item = Unique<Observation, Id>>,
+    tolerance: f64,
+) -> Vec<Representative<Id>>
+where
+    Id: Copy,
+{
+    let observations: Vec<Unique<Observation, Id>> = observations.into_iter().collect();
+
+    let mut contexts: HashMap<Uuid, Vec<usize>> = HashMap::new();
+    for (i, observation) in observations.iter().enumerate() {
+        if let Some(context) = observation.data.context() {
+            contexts.entry(context).or_default().push(i);
+        }
+    }
+
+    // sweep along the x axis, so that only marks within the tolerance in x are compared
+    let mut forest = UnionFind::default();
+    for mut members in contexts.into_values() {
+        members.sort_by(|a, b| {
+            observations[*a]
+                .data
+                .x()
+                .total_cmp(&observations[*b].data.x())
+        });
+        for (position, &a) in members.iter().enumerate() {
+            let first = &observations[a].data;
+            for &b in &members[position + 1..] {
+                let second = &observations[b].data;
+                if second.x() - first.x() > tolerance {
+                    break;
+                }
+                if (second.x() - first.x()).hypot(second.y() - first.y()) <= tolerance {
+                    forest.union(a, b);
+                }
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+    let mut cluster_of: HashMap<usize, usize> = HashMap::new();
+    for i in 0..observations.len() {
+        let root = forest.find(&i).unwrap_or(i);
+        let cluster = *cluster_of.entry(root).or_insert_with(|| {
+            clusters.push(Vec::new());
+            clusters.len() - 1
+        });
+        clusters[cluster].push(i);
+    }
+
+    clusters
+        .into_iter()
+        .map(|cluster| representative(&observations, &cluster))
+        .collect()
+}
+
+/// The representative of a non-empty cluster of observations.
+#[allow(clippy::cast_precision_loss)]
+fn representative<Id>(
+    observations: &[Unique<Observation, Id>],
+    cluster: &[usize],
+) -> Representative<Id>
+where
+    Id: Copy,
+{
+    let first = &observations[cluster[0]];
+    let members = cluster.iter().map(|i| observations[*i].id).collect();
+    if cluster.len() == 1 {
+        return Representative {
+            observation: first.clone(),
+            members,
+        };
+    }
+
+    let count = cluster.len() as f64;
+    let (mut x, mut y, mut error) = (0.0, 0.0, Matrix2::zeros());
+    for observation in cluster.iter().map(|i| &observations[*i].data) {
+        x += observation.x();
+        y += observation.y();
+        error += Matrix2::from(observation.error_covariance());
+    }
+    let data = first
+        .data
+        .clone()
+        .with_position(x / count, y / count)
+        .with_error(CovarianceMatrix::from_matrix(error / count));
+    Representative {
+        observation: Unique { data, id: first.id },
+        members,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn marks_are_merged_within_each_context() {
+        let (image, other) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let observation = |id, x: f64, error: f64, context: Option<Uuid>| {
+            let builder = Observation::builder(x, 0.0)
+                .error(CovarianceMatrix::new(error, error, 0.0).unwrap());
+            let data = match context {
+                Some(context) => builder.context(context).build(),
+                None => builder.build(),
+            };
+            Unique { data, id }
+        };
+        let observations = vec![
+            observation(0, 0.0, 1.0, Some(image)),
+            observation(1, 5.0, 1.0, Some(image)),
+            observation(2, 0.2, 3.0, Some(image)),
+            observation(3, 0.1, 1.0, Some(other)),
+            observation(4, 0.0, 1.0, None),
+            observation(5, 0.1, 1.0, None),
+            // chained to the first cluster through the third
+            observation(6, 0.35, 1.0, Some(image)),
+        ];
+
+        let representatives = precluster_contexts(observations, 0.25);
+        let members: Vec<_> = representatives
+            .iter()
+            .map(|representative| representative.members.clone())
+            .collect();
+        assert_eq!(
+            members,
+            vec![vec![0, 2, 6], vec![1], vec![3], vec![4], vec![5]]
+        );
+
+        let merged = &representatives[0].observation;
+        assert_eq!(merged.id, 0);
+        assert_relative_eq!(merged.data.x(), 0.55 / 3.0);
+        assert_relative_eq!(merged.data.error_covariance().xx(), 5.0 / 3.0);
+        assert_eq!(merged.data.context(), Some(image));
+    }
+}
@@ -0,0 +1,28 @@
+/// The result of probing the index with an external observation, as returned by
+/// [`CliqueIndex::classify`](crate::CliqueIndex::classify).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProbeResult<Id> {
+    /// The ID of the probe.
+    pub id: Id,
+
+    /// The observations in the index which the probe is compatible with, as `(id, d²)`, nearest
+    /// first.
+    pub compatible: Vec<(Id, f64)>,
+}
+
+impl<Id> ProbeResult<Id> {
+    /// Returns `true` if the probe is compatible with no observation in the index.
+    ///
+    /// A novel probe would be a singleton if it were inserted.
+    #[must_use]
+    pub fn is_novel(&self) -> bool {
+        self.compatible.is_empty()
+    }
+
+    /// The nearest observation in the index which the probe is compatible with, as `(id, d²)`.
+    #[must_use]
+    pub fn nearest(&self) -> Option<&(Id, f64)> {
+        self.compatible.first()
+    }
+}
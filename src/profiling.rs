@@ -0,0 +1,175 @@
+//! Timing of the phases of each operation on an index, for tuning.
+//!
+//! Without the `profiling` feature, no time is measured.
+
+#[cfg(feature = "profiling")]
+use std::{
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// A phase of an operation on an index, whose time is reported to a [`Profiler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Finding the candidate neighbours of an observation, in the R-tree or a custom
+    /// [`CandidateSource`](crate::CandidateSource).
+    SpatialQuery,
+
+    /// Testing the compatibility of each candidate neighbour with an observation.
+    Gating,
+
+    /// Enumerating (or approximating) the cliques of the compatibility graph.
+    CliqueEnumeration,
+}
+
+/// Receives the time spent in each [`Phase`] of the operations on an index.
+///
+/// See [`CliqueIndex::set_profiler`](crate::CliqueIndex::set_profiler).
+///
+/// Each operation reports many short intervals (for example, a spatial query and gating for each
+/// inserted observation), so implementations should be cheap. With the `rayon` feature, intervals
+/// may be reported concurrently from several threads.
+#[cfg(feature = "profiling")]
+pub trait Profiler: Send + Sync + UnwindSafe + RefUnwindSafe {
+    /// Record the time spent in a phase.
+    fn record(&self, phase: Phase, elapsed: Duration);
+}
+
+/// A [`Profiler`] which totals the time spent in each [`Phase`].
+///
+/// Clones share the same totals, so one clone can be given to an index while another is kept to
+/// read them. Taking the totals after each operation gives its breakdown.
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, PhaseTimings, Unique};
+///
+/// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+/// let timings = PhaseTimings::new();
+/// index.set_profiler(timings.clone());
+///
+/// for (id, x) in [(1, 0.0), (2, 0.5)] {
+///     let observation = Observation::builder(x, 0.0)
+///         .circular_95_confidence_error(1.0)
+///         .unwrap()
+///         .build();
+///     index.insert(Unique { data: observation, id });
+///     let breakdown = timings.take();
+///     println!("{id}: {breakdown:?}");
+/// }
+/// ```
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTimings(Arc<[AtomicU64; 3]>);
+
+#[cfg(feature = "profiling")]
+impl PhaseTimings {
+    /// Timings with nothing recorded.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The total time recorded in each phase.
+    #[must_use]
+    pub fn breakdown(&self) -> PhaseBreakdown {
+        let total =
+            |phase: Phase| Duration::from_nanos(self.0[phase as usize].load(Ordering::Relaxed));
+        PhaseBreakdown {
+            spatial_query: total(Phase::SpatialQuery),
+            gating: total(Phase::Gating),
+            clique_enumeration: total(Phase::CliqueEnumeration),
+        }
+    }
+
+    /// The total time recorded in each phase, resetting the totals to zero.
+    #[must_use]
+    pub fn take(&self) -> PhaseBreakdown {
+        let take =
+            |phase: Phase| Duration::from_nanos(self.0[phase as usize].swap(0, Ordering::Relaxed));
+        PhaseBreakdown {
+            spatial_query: take(Phase::SpatialQuery),
+            gating: take(Phase::Gating),
+            clique_enumeration: take(Phase::CliqueEnumeration),
+        }
+    }
+}
+
+#[cfg(feature = "profiling")]
+impl Profiler for PhaseTimings {
+    fn record(&self, phase: Phase, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.0[phase as usize].fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// The total time spent in each [`Phase`], as recorded by [`PhaseTimings`].
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PhaseBreakdown {
+    /// The time spent finding candidate neighbours.
+    pub spatial_query: Duration,
+
+    /// The time spent testing the compatibility of candidate neighbours.
+    pub gating: Duration,
+
+    /// The time spent enumerating cliques.
+    pub clique_enumeration: Duration,
+}
+
+#[cfg(feature = "profiling")]
+impl PhaseBreakdown {
+    /// The total time spent in all phases.
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.spatial_query + self.gating + self.clique_enumeration
+    }
+}
+
+/// The profiler of an index, if any.
+#[derive(Clone, Default)]
+pub struct Profiling {
+    #[cfg(feature = "profiling")]
+    profiler: Option<Arc<dyn Profiler>>,
+}
+
+#[cfg_attr(not(feature = "profiling"), allow(clippy::unused_self))]
+impl Profiling {
+    #[cfg(feature = "profiling")]
+    pub fn set(&mut self, profiler: Arc<dyn Profiler>) {
+        self.profiler = Some(profiler);
+    }
+
+    /// Returns `true` if phases are being timed.
+    pub const fn is_active(&self) -> bool {
+        #[cfg(feature = "profiling")]
+        return self.profiler.is_some();
+        #[cfg(not(feature = "profiling"))]
+        false
+    }
+
+    /// Run `f`, reporting the time it takes as spent in the given phase.
+    #[cfg_attr(not(feature = "profiling"), allow(unused_variables))]
+    pub fn time<T>(&self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "profiling")]
+        if let Some(profiler) = &self.profiler {
+            let start = Instant::now();
+            let result = f();
+            profiler.record(phase, start.elapsed());
+            return result;
+        }
+        f()
+    }
+}
+
+impl std::fmt::Debug for Profiling {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Profiling")
+            .field("active", &self.is_active())
+            .finish_non_exhaustive()
+    }
+}
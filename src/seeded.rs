@@ -0,0 +1,124 @@
+//! Reproducible ordering of outputs, and of choices between equally good alternatives.
+
+use std::{
+    collections::HashSet,
+    hash::{Hash, Hasher},
+};
+
+/// An arbitrary but reproducible total order, determined by a seed.
+///
+/// Values are ordered by a seeded hash which, unlike the standard library's hashers, is the same
+/// across runs, releases of Rust and platforms (so long as the value's [`Hash`] implementation
+/// is).
+///
+/// See [`Config::seed`](crate::Config::seed).
+#[derive(Debug, Clone, Copy)]
+pub struct SeededOrder(u64);
+
+impl SeededOrder {
+    pub const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// The sort key of a value.
+    pub fn key<T: Hash + ?Sized>(self, value: &T) -> u64 {
+        let mut hasher = StableHasher(mix(self.0));
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The sort key of a set, which doesn't depend on the order of its members.
+    pub fn set_key<Id: Hash>(self, set: &HashSet<Id>) -> u64 {
+        let mut keys: Vec<u64> = set.iter().map(|member| self.key(member)).collect();
+        keys.sort_unstable();
+        self.key(&keys)
+    }
+
+    /// Sort values into the seeded order.
+    pub fn sort<T: Hash>(self, values: &mut [T]) {
+        values.sort_by_cached_key(|value| self.key(value));
+    }
+
+    /// Sort sets into the seeded order.
+    pub fn sort_sets<Id: Hash>(self, sets: &mut [HashSet<Id>]) {
+        sets.sort_by_cached_key(|set| self.set_key(set));
+    }
+}
+
+/// A hasher whose output depends only on the values written to it (and not on the platform's
+/// endianness or pointer width).
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        mix(self.0)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.write_u64(i.into());
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write_u64(i.into());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write_u64(i.into());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = mix(self.0 ^ i);
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn write_u128(&mut self, i: u128) {
+        self.write_u64(i as u64);
+        self.write_u64((i >> 64) as u64);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// The `SplitMix64` finaliser.
+const fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_stable() {
+        // these must never change, or outputs which were reproducible would change between releases
+        let order = SeededOrder::new(7);
+        assert_eq!(order.key(&1_u32), order.key(&1_u64));
+        assert_eq!(order.key(&1_usize), order.key(&1_u64));
+        assert_eq!(order.key(&0_u64), 0x9C01_4791_61BC_5D78);
+        assert_ne!(SeededOrder::new(8).key(&0_u64), order.key(&0_u64));
+
+        let mut values = vec![3_u32, 1, 2];
+        order.sort(&mut values);
+        let mut reversed = vec![2_u32, 1, 3];
+        order.sort(&mut reversed);
+        assert_eq!(values, reversed);
+
+        assert_eq!(
+            order.set_key(&HashSet::from([1_u32, 2, 3])),
+            order.set_key(&HashSet::from([3_u32, 2, 1]))
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Default error models for the sensors which make observations.
+
+use std::collections::HashMap;
+
+use nalgebra::{Matrix2, Rotation2};
+use uuid::Uuid;
+
+use crate::{CovarianceMatrix, Observation, observation::ObservationBuilder};
+
+/// A model of the positional error of the observations made by a sensor.
+///
+/// Used to assign a covariance to observations which arrive without one. See [`SensorModels`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SensorModel(Kind);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Fixed(CovarianceMatrix),
+    RangeBearing {
+        origin: (f64, f64),
+        range_variance: f64,
+        bearing_variance: f64,
+    },
+}
+
+impl SensorModel {
+    /// A sensor whose observations all have the same error, regardless of position.
+    #[must_use]
+    pub const fn fixed(error: CovarianceMatrix) -> Self {
+        Self(Kind::Fixed(error))
+    }
+
+    /// A sensor at `origin` which measures range and bearing independently, such as a radar.
+    ///
+    /// `range_sigma` is the standard deviation of the range error, in the same units as the
+    /// positions. `bearing_sigma` is the standard deviation of the bearing error, in radians, so
+    /// the cross-range error grows in proportion to the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the origin or either standard deviation is not finite, or if either
+    /// standard deviation is negative.
+    pub fn range_bearing(
+        origin: (f64, f64),
+        range_sigma: f64,
+        bearing_sigma: f64,
+    ) -> Result<Self, InvalidSensorModel> {
+        let valid_sigma = |sigma: f64| sigma.is_finite() && sigma >= 0.0;
+        if origin.0.is_finite()
+            && origin.1.is_finite()
+            && valid_sigma(range_sigma)
+            && valid_sigma(bearing_sigma)
+        {
+            Ok(Self(Kind::RangeBearing {
+                origin,
+                range_variance: range_sigma * range_sigma,
+                bearing_variance: bearing_sigma * bearing_sigma,
+            }))
+        } else {
+            Err(InvalidSensorModel {
+                range_sigma,
+                bearing_sigma,
+            })
+        }
+    }
+
+    /// The error of an observation made by this sensor at the given position.
+    #[must_use]
+    pub fn covariance(&self, x: f64, y: f64) -> CovarianceMatrix {
+        match self.0 {
+            Kind::Fixed(error) => error,
+            Kind::RangeBearing {
+                origin,
+                range_variance,
+                bearing_variance,
+            } => {
+                let (dx, dy) = (x - origin.0, y - origin.1);
+                let range_squared = dx.mul_add(dx, dy * dy);
+                let rotation = Rotation2::new(dy.atan2(dx));
+                let polar =
+                    Matrix2::new(range_variance, 0.0, 0.0, range_squared * bearing_variance);
+                CovarianceMatrix::from_matrix(rotation * polar * rotation.transpose())
+            }
+        }
+    }
+}
+
+/// The error returned when a [`SensorModel`] is constructed with invalid parameters.
+#[derive(Debug, thiserror::Error, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[error(
+    "sensor model parameters must be finite, and standard deviations non-negative (got range sigma {range_sigma}, bearing sigma {bearing_sigma})"
+)]
+pub struct InvalidSensorModel {
+    range_sigma: f64,
+    bearing_sigma: f64,
+}
+
+/// A registry of the error models of known sensors.
+///
+/// This allows observations to be ingested without an explicit covariance, by looking up the
+/// model of the sensor which made them.
+///
+/// ```
+/// use clique_fusion::{CovarianceMatrix, SensorModel, SensorModels};
+/// use uuid::Uuid;
+///
+/// let radar = Uuid::new_v4();
+/// let mut models = SensorModels::new();
+/// models.insert(radar, SensorModel::range_bearing((0.0, 0.0), 5.0, 0.01).unwrap());
+///
+/// let observation = models.observation(radar, 1000.0, 0.0).unwrap().build();
+///
+/// assert_eq!(observation.sensor(), Some(radar));
+/// assert_eq!(observation.error_covariance().xx(), 25.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SensorModels {
+    models: HashMap<Uuid, SensorModel>,
+}
+
+impl SensorModels {
+    /// Construct an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the model of a sensor, returning its previous model, if any.
+    pub fn insert(&mut self, sensor: Uuid, model: SensorModel) -> Option<SensorModel> {
+        self.models.insert(sensor, model)
+    }
+
+    /// Remove the model of a sensor, returning it if it was registered.
+    pub fn remove(&mut self, sensor: &Uuid) -> Option<SensorModel> {
+        self.models.remove(sensor)
+    }
+
+    /// The model of a sensor, if registered.
+    #[must_use]
+    pub fn get(&self, sensor: &Uuid) -> Option<&SensorModel> {
+        self.models.get(sensor)
+    }
+
+    /// The number of registered sensors.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Returns `true` if no sensors are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// The error of an observation made by a sensor at the given position.
+    ///
+    /// Returns `None` if the sensor is not registered.
+    #[must_use]
+    pub fn covariance(&self, sensor: &Uuid, x: f64, y: f64) -> Option<CovarianceMatrix> {
+        self.get(sensor).map(|model| model.covariance(x, y))
+    }
+
+    /// Begin building an observation made by a sensor at the given position.
+    ///
+    /// The [sensor](Observation::sensor) is set, and the error is assigned from the sensor's model.
+    ///
+    /// Returns `None` if the sensor is not registered.
+    #[must_use]
+    pub fn observation(
+        &self,
+        sensor: Uuid,
+        x: f64,
+        y: f64,
+    ) -> Option<ObservationBuilder<CovarianceMatrix>> {
+        let error = self.covariance(&sensor, x, y)?;
+        Some(Observation::builder(x, y).error(error).sensor(sensor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn range_bearing_error_is_aligned_with_the_bearing() {
+        let model = SensorModel::range_bearing((10.0, 10.0), 2.0, 0.01).unwrap();
+
+        // due north of the sensor, range error is in y and cross-range error is in x
+        let error = model.covariance(10.0, 110.0);
+        assert_relative_eq!(error.xx(), 1.0, epsilon = 1e-9);
+        assert_relative_eq!(error.yy(), 4.0, epsilon = 1e-9);
+        assert_relative_eq!(error.xy(), 0.0, epsilon = 1e-9);
+
+        // at 45 degrees, the principal axes are diagonal
+        let error = model.covariance(110.0, 110.0);
+        let cross_range_variance = 20_000.0 * 0.0001;
+        assert_relative_eq!(error.xx(), error.yy(), epsilon = 1e-9);
+        assert_relative_eq!(
+            error.xy(),
+            (4.0 - cross_range_variance) / 2.0,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn range_bearing_rejects_invalid_parameters() {
+        assert!(SensorModel::range_bearing((0.0, 0.0), -1.0, 0.01).is_err());
+        assert!(SensorModel::range_bearing((0.0, 0.0), 1.0, f64::NAN).is_err());
+        assert!(SensorModel::range_bearing((f64::INFINITY, 0.0), 1.0, 0.01).is_err());
+    }
+
+    #[test]
+    fn unknown_sensors_have_no_model() {
+        let sensor = Uuid::from_u128(1);
+        let mut models = SensorModels::new();
+        models.insert(sensor, SensorModel::fixed(CovarianceMatrix::identity()));
+
+        assert!(models.observation(Uuid::from_u128(2), 0.0, 0.0).is_none());
+        assert_eq!(
+            models.covariance(&sensor, 5.0, 5.0),
+            Some(CovarianceMatrix::identity())
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Partitioning of observations between independent indices, by spatial cell.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::Hash,
+};
+
+use crate::{
+    BoundingBox, CellSystem, CliqueIndex, CliqueSet, Config, Observation, Unique,
+    spatial_index::SearchBounds,
+};
+
+/// Splits observations into per-cell shards, which can be indexed independently (for example, by
+/// separate processes or machines), and whose cliques can then be merged with [`merge_shards`].
+///
+/// Each observation belongs to the shard of the cell containing it (its 'home' cell). It is also
+/// copied into the 'halo' of every other shard whose cell lies within its maximum compatibility
+/// radius, so that each shard holds every observation which could be compatible with one of its
+/// own. A clique is then found in full by the shard of each of its members, and is reported by
+/// exactly one of them.
+///
+/// The compatibility radius is the one an index with the same configuration searches, so it
+/// allows for any registration error, directional gate and borderline band. Under a
+/// [`MotionModel`](crate::MotionModel) other than the static one, it also depends on how far
+/// apart in time the observations are, and how fast they move. [`Self::partition`] bounds these
+/// from the observations it is given, but to route a stream of observations with
+/// [`Self::cells_of`], they must be bounded up front (see [`Self::time_range`] and
+/// [`Self::max_velocity`]).
+///
+/// ```
+/// use clique_fusion::{
+///     CHI2_2D_CONFIDENCE_95, CliqueIndex, Config, Observation, Sharder, SquareCells, Unique,
+///     merge_shards,
+/// };
+///
+/// let observations: Vec<_> = [(1, 9.5), (2, 10.5), (3, 30.0)]
+///     .into_iter()
+///     .map(|(id, x)| Unique {
+///         data: Observation::builder(x, 0.0)
+///             .circular_95_confidence_error(1.0)
+///             .unwrap()
+///             .build(),
+///         id,
+///     })
+///     .collect();
+///
+/// // every observation has a variance of at most 1.0
+/// let config = Config::new(CHI2_2D_CONFIDENCE_95);
+/// let sharder = Sharder::new(SquareCells::new(10.0), &config, 1.0);
+/// let shards = sharder.partition(observations);
+///
+/// // each shard could be indexed by a separate process
+/// let cliques = merge_shards(shards.values().map(|shard| shard.cliques(config.clone())));
+///
+/// assert_eq!(cliques.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sharder<S> {
+    system: S,
+    config: Config,
+    bounds: SearchBounds,
+}
+
+impl<S> Sharder<S>
+where
+    S: CellSystem,
+{
+    /// Construct a sharder for indices with the given configuration.
+    ///
+    /// `max_variance` must be an upper bound on the [maximum
+    /// variance](crate::CovarianceMatrix::max_variance) of every observation, since it determines
+    /// the width of the halos.
+    #[must_use]
+    pub fn new(system: S, config: &Config, max_variance: f64) -> Self {
+        Self {
+            system,
+            config: config.clone(),
+            bounds: SearchBounds::new(max_variance),
+        }
+    }
+
+    /// Bound the timestamps of every observation by the earliest and latest, for routing a stream
+    /// of observations under a motion model. None by default.
+    #[must_use]
+    pub const fn time_range(mut self, start: f64, end: f64) -> Self {
+        self.bounds = self.bounds.with_time_range(start, end);
+        self
+    }
+
+    /// Bound the speed, and the [maximum variance](crate::CovarianceMatrix::max_variance) of the
+    /// velocity error, of every observation with a velocity, for routing a stream of observations
+    /// under a motion model. None by default.
+    #[must_use]
+    pub const fn max_velocity(mut self, speed: f64, variance: f64) -> Self {
+        self.bounds = self.bounds.with_max_velocity(speed, variance);
+        self
+    }
+
+    /// The home cell of an observation, and the other cells in whose halos it belongs.
+    ///
+    /// This can be used to route a stream of observations to the processes responsible for each
+    /// cell.
+    pub fn cells_of(&self, observation: &Observation) -> (S::Cell, Vec<S::Cell>) {
+        self.route(observation, &self.bounds)
+    }
+
+    fn route(&self, observation: &Observation, bounds: &SearchBounds) -> (S::Cell, Vec<S::Cell>) {
+        let (x, y) = observation.position();
+        let home = self.system.cell(x, y);
+        let (_, threshold) = self.config.borderline_bounds();
+        let radius = bounds.search_radius(observation, &self.config, threshold);
+        let bounds = BoundingBox::from_corners((x - radius, y - radius), (x + radius, y + radius));
+        let halo = self
+            .system
+            .covering(&bounds)
+            .into_iter()
+            .filter(|cell| *cell != home)
+            .collect();
+        (home, halo)
+    }
+
+    /// Split a collection of observations into shards, keyed by cell.
+    ///
+    /// The bounds of the sharder are widened to include the observations, so the shards are exact
+    /// even if they exceed them.
+    pub fn partition<Id>(
+        &self,
+        observations: impl IntoIterator<Item = Unique<Observation, Id>>,
+    ) -> HashMap<S::Cell, Shard<Id>>
+    where
+        Id: Eq + Hash + Copy,
+    {
+        let observations: Vec<_> = observations.into_iter().collect();
+        let mut bounds = self.bounds;
+        for observation in &observations {
+            bounds.include(&observation.data);
+        }
+        let mut shards: HashMap<S::Cell, Shard<Id>> = HashMap::new();
+        for observation in observations {
+            let (home, halo) = self.route(&observation.data, &bounds);
+            for cell in halo {
+                shards
+                    .entry(cell)
+                    .or_default()
+                    .observations
+                    .push(observation.clone());
+            }
+            let shard = shards.entry(home).or_default();
+            shard.home.insert(observation.id);
+            shard.observations.push(observation);
+        }
+        shards
+    }
+}
+
+/// The observations of a single cell, together with those of its halo. See [`Sharder`].
+#[derive(Debug, Clone)]
+pub struct Shard<Id> {
+    home: HashSet<Id>,
+    observations: Vec<Unique<Observation, Id>>,
+}
+
+impl<Id> Default for Shard<Id> {
+    fn default() -> Self {
+        Self {
+            home: HashSet::new(),
+            observations: Vec::new(),
+        }
+    }
+}
+
+impl<Id> Shard<Id>
+where
+    Id: Ord + Hash + Copy + fmt::Debug,
+{
+    /// The IDs of the observations whose home is the cell of this shard.
+    #[must_use]
+    pub const fn home(&self) -> &HashSet<Id> {
+        &self.home
+    }
+
+    /// The observations of the shard, including those of its halo.
+    #[must_use]
+    pub fn observations(&self) -> &[Unique<Observation, Id>] {
+        &self.observations
+    }
+
+    /// Index the observations of the shard, returning the cliques it is responsible for.
+    #[must_use]
+    pub fn cliques(&self, config: Config) -> CliqueSet<Id> {
+        let index = CliqueIndex::from_observations_with_config(self.observations.clone(), config);
+        self.owned(index.cliques())
+    }
+
+    /// Select the cliques this shard is responsible for, from the cliques of an index of its
+    /// observations.
+    ///
+    /// Each clique is reported by the shard which is home to its least member, so that the
+    /// cliques found in the overlapping halos of neighbouring shards are reported only once.
+    #[must_use]
+    pub fn owned(&self, cliques: &CliqueSet<Id>) -> CliqueSet<Id> {
+        cliques
+            .iter()
+            .filter(|clique| {
+                clique
+                    .iter()
+                    .min()
+                    .is_some_and(|least| self.home.contains(least))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Merge the cliques reported by each [`Shard`] into the cliques of all of the observations.
+///
+/// The order of the merged cliques is unspecified.
+pub fn merge_shards<Id>(shards: impl IntoIterator<Item = CliqueSet<Id>>) -> CliqueSet<Id> {
+    shards.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::{CHI2_2D_CONFIDENCE_95, CovarianceMatrix, SquareCells};
+
+    fn observations(seed: u64) -> Vec<Unique<Observation, u32>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..300_u32)
+            .map(|id| Unique {
+                data: Observation::builder(
+                    rng.random_range(0.0..50.0),
+                    rng.random_range(0.0..50.0),
+                )
+                .circular_95_confidence_error(rng.random_range(1.0..3.0))
+                .unwrap()
+                .build(),
+                id,
+            })
+            .collect()
+    }
+
+    fn assert_sharded_cliques_match(observations: Vec<Unique<Observation, u32>>, config: &Config) {
+        let max_variance = observations
+            .iter()
+            .map(|observation| observation.data.error_covariance().max_variance())
+            .fold(0.0, f64::max);
+
+        let sharder = Sharder::new(SquareCells::new(5.0), config, max_variance);
+        for observation in &observations {
+            let (home, halo) = sharder.cells_of(&observation.data);
+            assert!(!halo.contains(&home));
+        }
+        let shards = sharder.partition(observations.clone());
+        assert!(shards.len() > 1);
+        let merged = merge_shards(shards.values().map(|shard| shard.cliques(config.clone())));
+
+        let index = CliqueIndex::from_observations_with_config(observations, config.clone());
+        let canonical = |cliques: &CliqueSet<u32>| {
+            let mut cliques: Vec<Vec<u32>> = cliques
+                .iter()
+                .map(|clique| {
+                    let mut members: Vec<_> = clique.iter().copied().collect();
+                    members.sort_unstable();
+                    members
+                })
+                .collect();
+            cliques.sort();
+            cliques
+        };
+        assert_eq!(canonical(&merged), canonical(index.cliques()));
+    }
+
+    #[test]
+    fn sharded_cliques_match_a_single_index() {
+        assert_sharded_cliques_match(observations(3), &Config::new(CHI2_2D_CONFIDENCE_95));
+    }
+
+    #[test]
+    fn halos_allow_for_the_registration_error() {
+        // the registration error dominates the errors of the observations
+        let registration_error = CovarianceMatrix::new(9.0, 9.0, 0.0).unwrap();
+        let config = Config::new(CHI2_2D_CONFIDENCE_95).registration_error(registration_error);
+        assert_sharded_cliques_match(observations(5), &config);
+    }
+}
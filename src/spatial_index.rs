@@ -1,11 +1,24 @@
-use std::collections::HashSet;
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    fmt,
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::Arc,
+};
 
 use rstar::{AABB, PointDistance, RTree, RTreeObject};
+use uuid::Uuid;
 
-use crate::Observation;
+use crate::{
+    Adjacency, BoundingBox, BuildPhase, CellSystem, Config, CovarianceMatrix, MotionModel,
+    Observation, SquareCells,
+    cancel::{Cancelled, Monitor},
+    profiling::{Phase, Profiling},
+};
 
 /// A wrapper type that assigns a unique identifier to its payload.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Unique<T, Id> {
     /// The wrapped payload.
     pub data: T,
@@ -14,58 +27,245 @@ pub struct Unique<T, Id> {
     pub id: Id,
 }
 
-impl<Id> RTreeObject for Unique<Observation, Id> {
+impl<T> Unique<T, Uuid> {
+    /// Wrap a payload with a newly generated, random (v4) UUID.
+    ///
+    /// ```
+    /// use clique_fusion::{CovarianceMatrix, Observation, Unique};
+    ///
+    /// let observation = Observation::builder(1.0, 2.0)
+    ///     .error(CovarianceMatrix::identity())
+    ///     .build();
+    ///
+    /// let a = Unique::new_v4(observation.clone());
+    /// let b = Unique::new_v4(observation);
+    /// assert_ne!(a.id, b.id);
+    /// ```
+    #[must_use]
+    pub fn new_v4(data: T) -> Self {
+        Self {
+            data,
+            id: Uuid::new_v4(),
+        }
+    }
+}
+
+impl<Id> From<Unique<Observation, Id>> for Unique<Arc<Observation>, Id> {
+    /// Move an observation into shared storage, as held by a [`CliqueIndex`](crate::CliqueIndex).
+    fn from(observation: Unique<Observation, Id>) -> Self {
+        Self {
+            data: Arc::new(observation.data),
+            id: observation.id,
+        }
+    }
+}
+
+/// An observation stored in the R-tree.
+///
+/// Its envelope is the box within which it could be compatible with another observation,
+/// extending from its position by `sqrt(chi2 * variance)` along each axis. This is the greatest
+/// displacement along that axis at which it lies within its own confidence ellipse. Since the
+/// confidence ellipse of the sum of two covariance matrices is no wider along either axis than the
+/// sum of their individual widths, two observations can only be compatible if their envelopes
+/// intersect.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry<Id> {
+    observation: Unique<Arc<Observation>, Id>,
+    envelope: AABB<[f64; 2]>,
+}
+
+impl<Id> Entry<Id> {
+    fn new(observation: Unique<Arc<Observation>, Id>, chi2: f64) -> Self {
+        let envelope = compatibility_envelope(&observation.data, chi2);
+        Self {
+            observation,
+            envelope,
+        }
+    }
+}
+
+/// The box within which an observation could be compatible with another. See [`Entry`].
+fn compatibility_envelope(observation: &Observation, chi2: f64) -> AABB<[f64; 2]> {
+    let (x, y) = observation.position();
+    let error = observation.error_covariance();
+    let (half_width, half_height) = ((chi2 * error.xx()).sqrt(), (chi2 * error.yy()).sqrt());
+    AABB::from_corners(
+        [x - half_width, y - half_height],
+        [x + half_width, y + half_height],
+    )
+}
+
+/// Pad an envelope by the extent of an error shared by every pair, such as a registration error.
+///
+/// Two observations can only be compatible if the combined extent of their envelopes in each axis
+/// is at least `√(χ² (σa² + σb² + σr²))`, which is at most `√(χ² σa²) + √(χ² σb²) + √(χ² σr²)`. So
+/// padding the envelope of one of them by the last term is enough to find every compatible pair.
+fn padded(
+    envelope: AABB<[f64; 2]>,
+    shared_error: Option<CovarianceMatrix>,
+    chi2: f64,
+) -> AABB<[f64; 2]> {
+    let Some(error) = shared_error else {
+        return envelope;
+    };
+    let (dx, dy) = ((chi2 * error.xx()).sqrt(), (chi2 * error.yy()).sqrt());
+    let ([min_x, min_y], [max_x, max_y]) = (envelope.lower(), envelope.upper());
+    AABB::from_corners([min_x - dx, min_y - dy], [max_x + dx, max_y + dy])
+}
+
+impl<Id> RTreeObject for Entry<Id> {
     type Envelope = AABB<[f64; 2]>;
 
     fn envelope(&self) -> Self::Envelope {
-        AABB::from_point(self.data.position().into())
+        self.envelope
     }
 }
 
-impl<Id> PointDistance for Unique<Observation, Id> {
+impl<Id> PointDistance for Entry<Id> {
     fn distance_2(&self, point: &[f64; 2]) -> f64 {
-        let (x, y) = self.data.position();
+        let (x, y) = self.observation.data.position();
         let dx = x - point[0];
         let dy = y - point[1];
         dx.mul_add(dx, dy * dy)
     }
 }
 
-/// A spatial index supporting efficient nearest-neighbour and mutual-compatibility queries.
-#[derive(Debug)]
-pub struct SpatialIndex<Id> {
-    tree: RTree<Unique<Observation, Id>>,
-
-    /// The maximum variance of all observations in the index.
+/// A source of candidate neighbours for compatibility testing.
+///
+/// By default, a [`CliqueIndex`](crate::CliqueIndex) finds the neighbours of each observation
+/// using an in-memory R-tree. Implement this trait to find them some other way instead (for
+/// example, with a spatial query against a database which already holds the observations, or by
+/// bucketing them into grid cells). The candidates are then tested for statistical compatibility,
+/// and connected into cliques, as usual.
+///
+/// Sources must be thread- and unwind-safe, so that the index which owns them is too.
+///
+/// See [`CliqueIndex::with_candidate_source`](crate::CliqueIndex::with_candidate_source).
+pub trait CandidateSource<Id>: Send + Sync + UnwindSafe + RefUnwindSafe {
+    /// Called when an observation is added to the index.
     ///
-    /// This is used to determine the search radius needed to guarantee that all possible
-    /// compatible neighbours have been considered when searching for neighbours.
-    ///
-    /// TODO: this could be optimised further by:
+    /// Sources which track the observations independently of the index may ignore this.
+    fn insert(&mut self, _observation: &Unique<Arc<Observation>, Id>) {}
+
+    /// Called when an observation is removed from the index.
     ///
-    /// - using a heap to track the variances in order
-    /// - searching in descending order of variance
-    /// - popping elements from the heap as they are searched
-    /// - shrinking the search radius to match the updated maximum variance as you go
+    /// Sources which track the observations independently of the index may ignore this.
+    fn remove(&mut self, _observation: &Unique<Arc<Observation>, Id>) {}
+
+    /// The IDs of the observations whose positions lie within `bounds` (inclusive).
     ///
-    /// benchmarking on large, representative datasets needed to determine whether this is worth it!
-    max_variance: f64,
+    /// This must include every such observation in the index, but may include others (which are
+    /// discarded when they are tested for compatibility). Each ID should be returned at most once.
+    fn locate(&self, bounds: &BoundingBox) -> Vec<Id>;
 }
 
-impl<Id> Default for SpatialIndex<Id> {
-    fn default() -> Self {
-        let tree = RTree::default();
-        Self {
-            tree,
-            max_variance: 0.0,
+/// Receives the candidate neighbours which were discarded by the statistical gate.
+///
+/// Each candidate found by the spatial prefilter is tested for compatibility with the query. Those
+/// whose squared Mahalanobis distance exceeds the chi-squared threshold are reported here,
+/// together with the distance and the threshold, so that near-misses can be inspected when tuning
+/// the threshold. Candidates discarded because they share a context with the query, or are
+/// separated in altitude, are not reported.
+///
+/// Closures taking the query ID, candidate ID, squared distance and threshold implement this
+/// trait. Logs must be thread- and unwind-safe, so that the index which owns them is too. With the
+/// `rayon` feature, discards may be reported concurrently from several threads.
+///
+/// See [`CliqueIndex::set_discard_log`](crate::CliqueIndex::set_discard_log).
+pub trait DiscardLog<Id>: Send + Sync + UnwindSafe + RefUnwindSafe {
+    /// Called when a candidate fails the statistical gate.
+    fn discarded(&self, query: Id, candidate: Id, distance_squared: f64, threshold: f64);
+}
+
+impl<Id, F> DiscardLog<Id> for F
+where
+    F: Fn(Id, Id, f64, f64) + Send + Sync + UnwindSafe + RefUnwindSafe,
+{
+    fn discarded(&self, query: Id, candidate: Id, distance_squared: f64, threshold: f64) {
+        self(query, candidate, distance_squared, threshold);
+    }
+}
+
+/// The [`DiscardLog`] of a [`SpatialIndex`], if any.
+struct Discards<Id>(Option<Box<dyn DiscardLog<Id>>>);
+
+impl<Id> fmt::Debug for Discards<Id> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Discards").field(&self.0.is_some()).finish()
+    }
+}
+
+/// How a [`SpatialIndex`] finds candidate neighbours.
+enum Candidates<Id> {
+    Tree(RTree<Entry<Id>>),
+    Custom(Box<dyn CandidateSource<Id>>),
+}
+
+impl<Id> fmt::Debug for Candidates<Id>
+where
+    Id: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tree(tree) => f.debug_tuple("Tree").field(tree).finish(),
+            Self::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
         }
     }
 }
 
+/// A spatial index supporting efficient nearest-neighbour and mutual-compatibility queries.
+#[derive(Debug)]
+pub struct SpatialIndex<Id> {
+    candidates: Candidates<Id>,
+
+    /// The chi-squared threshold used to compute the envelopes of the observations in the tree.
+    chi2: f64,
+
+    /// Observations keyed by ID, for efficient lookup.
+    ///
+    /// These are shared with the R-tree (and with callers), rather than copied.
+    observations: HashMap<Id, Unique<Arc<Observation>, Id>>,
+
+    /// Bounds on the errors, velocities and timestamps of all observations in the index.
+    ///
+    /// When a [`MotionModel`] or [`CandidateSource`] is in use, these are used to determine the
+    /// search radius needed to guarantee that all possible compatible neighbours have been
+    /// considered. Otherwise, each observation's own envelope is used instead.
+    bounds: SearchBounds,
+
+    /// Times the spatial queries and gating of compatibility queries, if a profiler is set.
+    profiling: Profiling,
+
+    /// Receives the candidates which fail the statistical gate, if set.
+    discards: Discards<Id>,
+}
+
 impl<Id> SpatialIndex<Id>
 where
-    Id: PartialEq,
+    Id: Eq + std::hash::Hash + Copy,
 {
+    /// Construct an empty spatial index for compatibility queries at the given chi-squared
+    /// threshold.
+    pub fn new(chi2: f64) -> Self {
+        Self::with_candidates(Candidates::Tree(RTree::default()), chi2)
+    }
+
+    /// Construct an empty spatial index which finds candidate neighbours using the given source.
+    pub fn with_candidate_source(source: Box<dyn CandidateSource<Id>>, chi2: f64) -> Self {
+        Self::with_candidates(Candidates::Custom(source), chi2)
+    }
+
+    fn with_candidates(candidates: Candidates<Id>, chi2: f64) -> Self {
+        Self {
+            candidates,
+            chi2,
+            observations: HashMap::default(),
+            bounds: SearchBounds::default(),
+            profiling: Profiling::default(),
+            discards: Discards(None),
+        }
+    }
+
     /// Construct a spatial index from an initial list of observations.
     ///
     /// This is significantly faster than inserting observations individually via [`Self::insert`],
@@ -73,14 +273,55 @@ where
     ///
     /// See also: [`Self::insert`] for incremental use cases.
     #[must_use]
-    pub fn from_observations(observations: Vec<Unique<Observation, Id>>) -> Self {
-        let max_variance = observations
-            .iter()
-            .map(|obs| obs.data.error_covariance().max_variance())
-            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .unwrap_or(0.0);
-        let tree = RTree::bulk_load(observations);
-        Self { tree, max_variance }
+    pub fn from_observations(
+        observations: impl IntoIterator<Item = impl Into<Unique<Arc<Observation>, Id>>>,
+        chi2: f64,
+    ) -> Self {
+        let mut index = Self::new(chi2);
+        index.reload(observations.into_iter().map(Into::into).collect());
+        index
+    }
+
+    /// Replace every observation in the index, keeping its source of candidates.
+    ///
+    /// If candidates are found using the R-tree, it is bulk loaded with the new observations.
+    pub fn reload(&mut self, observations: Vec<Unique<Arc<Observation>, Id>>) {
+        if let Candidates::Custom(source) = &mut self.candidates {
+            for observation in self.observations.values() {
+                source.remove(observation);
+            }
+            for observation in &observations {
+                source.insert(observation);
+            }
+        }
+        self.bounds = SearchBounds::default();
+        for observation in &observations {
+            self.bounds.include(&observation.data);
+        }
+        if let Candidates::Tree(tree) = &mut self.candidates {
+            *tree = RTree::bulk_load(
+                observations
+                    .iter()
+                    .map(|observation| Entry::new(observation.clone(), self.chi2))
+                    .collect(),
+            );
+        }
+        self.observations = observations
+            .into_iter()
+            .map(|observation| (observation.id, observation))
+            .collect();
+    }
+
+    /// Change the chi-squared threshold for compatibility queries, rebuilding the R-tree.
+    pub fn set_chi2(&mut self, chi2: f64) {
+        self.chi2 = chi2;
+        if let Candidates::Tree(tree) = &mut self.candidates {
+            let entries = std::mem::take(tree)
+                .into_iter()
+                .map(|entry| Entry::new(entry.observation, chi2))
+                .collect();
+            *tree = RTree::bulk_load(entries);
+        }
     }
 
     /// Insert a single observation into the spatial index.
@@ -93,18 +334,101 @@ where
     /// # Panics
     ///
     /// Panics in debug builds if an observation with the same ID already exists in the index.
-    pub fn insert(&mut self, observation: Unique<Observation, Id>) {
+    pub fn insert(&mut self, observation: impl Into<Unique<Arc<Observation>, Id>>) {
+        let observation = observation.into();
         debug_assert!(
-            !self.tree.contains(&observation),
+            !self.observations.contains_key(&observation.id),
             "attempted to insert duplicate observation"
         );
 
-        // Update the maximum variance
-        self.max_variance = self
-            .max_variance
-            .max(observation.data.error_covariance().max_variance());
+        self.bounds.include(&observation.data);
+
+        match &mut self.candidates {
+            Candidates::Tree(tree) => tree.insert(Entry::new(observation.clone(), self.chi2)),
+            Candidates::Custom(source) => source.insert(&observation),
+        }
+        self.observations.insert(observation.id, observation);
+    }
+
+    /// Remove an observation from the spatial index, returning it if it was present.
+    ///
+    /// Note that the maximum variance (and the bounds on velocity and time) of the index are not
+    /// reduced by removal, so they remain conservative upper bounds for the observations in the
+    /// index.
+    pub fn remove(&mut self, id: &Id) -> Option<Arc<Observation>> {
+        let observation = self.observations.remove(id)?;
+        match &mut self.candidates {
+            Candidates::Tree(tree) => {
+                let entry = Entry::new(observation, self.chi2);
+                tree.remove(&entry);
+                Some(entry.observation.data)
+            }
+            Candidates::Custom(source) => {
+                source.remove(&observation);
+                Some(observation.data)
+            }
+        }
+    }
+
+    /// The number of observations in the index.
+    pub fn len(&self) -> usize {
+        self.observations.len()
+    }
 
-        self.tree.insert(observation);
+    /// Iterate over every observation in the index, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Id, &Observation)> {
+        self.observations
+            .iter()
+            .map(|(id, observation)| (id, observation.data.as_ref()))
+    }
+
+    /// Look up an observation by ID.
+    pub fn get(&self, id: &Id) -> Option<&Observation> {
+        self.get_shared(id).map(AsRef::as_ref)
+    }
+
+    /// Look up the shared storage of an observation by ID.
+    pub fn get_shared(&self, id: &Id) -> Option<&Arc<Observation>> {
+        self.observations
+            .get(id)
+            .map(|observation| &observation.data)
+    }
+
+    /// Look up the observations with the given IDs.
+    ///
+    /// IDs which are not in the index are ignored.
+    pub fn get_all(&self, ids: &HashSet<Id>) -> HashMap<Id, &Observation> {
+        ids.iter()
+            .filter_map(|id| self.get(id).map(|obs| (*id, obs)))
+            .collect()
+    }
+
+    /// Iterate over the observations which lie within the given bounding box (inclusive).
+    pub fn locate_in_bounds(
+        &self,
+        bounds: &BoundingBox,
+    ) -> impl Iterator<Item = &Unique<Arc<Observation>, Id>> {
+        let (intersecting, located) = match &self.candidates {
+            Candidates::Tree(tree) => {
+                let envelope =
+                    AABB::from_corners([bounds.min_x, bounds.min_y], [bounds.max_x, bounds.max_y]);
+                (Some(tree.locate_in_envelope_intersecting(envelope)), None)
+            }
+            Candidates::Custom(source) => (None, Some(source.locate(bounds))),
+        };
+        // the envelopes extend beyond the positions, and custom sources may return extra
+        // candidates, so they must be filtered by position
+        intersecting
+            .into_iter()
+            .flatten()
+            .map(|entry| &entry.observation)
+            .chain(
+                located
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| self.observations.get(&id)),
+            )
+            .filter(|observation| bounds.contains(observation.data.x(), observation.data.y()))
     }
 }
 
@@ -122,32 +446,289 @@ impl<Id> SpatialIndex<Id> {
     /// snapshot or measurement — then although absolute positioning error (e.g., platform GPS error)
     /// might be high, the *relative* error between those observations is negligible. In such cases,
     /// fusion is never appropriate, as we can perfectly distinguish them as separate entities.
+    ///
+    /// Observations whose altitudes differ by more than the configured gate are also excluded.
+    ///
+    /// Each compatible observation is yielded with its squared Mahalanobis distance from the query,
+    /// as computed by [`Observation::gated_distance_squared`], so that callers need not recompute it.
     pub fn find_compatible<'a>(
         &'a self,
-        query: &Unique<Observation, Id>,
+        query: &Unique<impl Borrow<Observation>, Id>,
+        config: &Config,
+    ) -> impl Iterator<Item = (&'a Unique<Arc<Observation>, Id>, f64)>
+    where
+        Id: Eq + std::hash::Hash + Copy,
+    {
+        self.find_compatible_among(query, config, config.chi2, |_| true)
+    }
+
+    /// Find the observations whose squared Mahalanobis distance from the query is at most
+    /// `threshold`, rather than the chi-squared threshold of the configuration, as for
+    /// [`Self::find_compatible`].
+    ///
+    /// The distances are computed under the configuration as usual (including any
+    /// [directional gate](Config::directional_gate)), so they are comparable with those of
+    /// compatible pairs. The threshold must be within the threshold the index was built for.
+    pub fn find_within<'a>(
+        &'a self,
+        query: &Unique<impl Borrow<Observation>, Id>,
+        config: &Config,
+        threshold: f64,
+    ) -> impl Iterator<Item = (&'a Unique<Arc<Observation>, Id>, f64)>
+    where
+        Id: Eq + std::hash::Hash + Copy,
+    {
+        self.find_compatible_among(query, config, threshold, |_| true)
+    }
+
+    /// Find the observations within `chi2_threshold` of the query, as for [`Self::find_within`],
+    /// among only those candidates whose IDs satisfy `include`.
+    ///
+    /// Excluded candidates are skipped before their compatibility is tested.
+    fn find_compatible_among<'a>(
+        &'a self,
+        query: &Unique<impl Borrow<Observation>, Id>,
+        config: &Config,
         chi2_threshold: f64,
-    ) -> impl Iterator<Item = &'a Unique<Observation, Id>>
+        include: impl Fn(&Id) -> bool,
+    ) -> impl Iterator<Item = (&'a Unique<Arc<Observation>, Id>, f64)>
     where
-        Id: PartialEq,
+        Id: Eq + std::hash::Hash + Copy,
     {
-        let radius = query
-            .data
-            .max_compatibility_radius(chi2_threshold, self.max_variance);
-        let p = query.data.position();
-
-        self.tree
-            .locate_within_distance(p.into(), radius)
-            .filter(|other| query.id != other.id) // Exclude self
-            .filter(|other| {
-                // Skip observations from the same context (e.g. same measurement or snapshot).
-                // If both observations have the same context, we assume they are distinct with negligible relative error,
-                // and therefore should never be fused.
-                !matches!((query.data.context(), other.data.context()), (Some(ctx1), Some(ctx2)) if ctx1 == ctx2)
-            })
-            .filter(move |obs| {
-                obs.data
-                    .is_compatible_with(&query.data, chi2_threshold)
-            })
+        let motion_model = config.motion_model;
+        let (query_id, query): (&Id, &Observation) = (&query.id, query.data.borrow());
+        debug_assert!(
+            chi2_threshold <= self.chi2,
+            "the envelopes in the R-tree are too small for the chi-squared threshold"
+        );
+
+        // Without a motion model, candidates are those whose envelopes intersect the query's. A
+        // motion model can carry compatible observations out of their envelopes, so the search is
+        // widened to a radius which accounts for the displacement instead. Custom sources are
+        // always searched by radius.
+        let (intersecting, within_radius, located) = match (&self.candidates, motion_model) {
+            (Candidates::Tree(tree), MotionModel::Static) => {
+                let envelope = padded(
+                    compatibility_envelope(query, self.chi2),
+                    config.shared_error(),
+                    self.chi2,
+                );
+                (
+                    Some(tree.locate_in_envelope_intersecting(envelope)),
+                    None,
+                    None,
+                )
+            }
+            (
+                Candidates::Tree(tree),
+                MotionModel::ConstantVelocity { .. } | MotionModel::Ageing { .. },
+            ) => {
+                let radius = self.bounds.search_radius(query, config, chi2_threshold);
+                let p = query.position();
+                // the R-tree expects a squared distance
+                (
+                    None,
+                    Some(tree.locate_within_distance(p.into(), radius * radius)),
+                    None,
+                )
+            }
+            (Candidates::Custom(source), _) => {
+                let radius = self.bounds.search_radius(query, config, chi2_threshold);
+                let (x, y) = query.position();
+                let bounds =
+                    BoundingBox::from_corners((x - radius, y - radius), (x + radius, y + radius));
+                (None, None, Some(source.locate(&bounds)))
+            }
+        };
+
+        let candidates = intersecting
+            .into_iter()
+            .flatten()
+            .chain(within_radius.into_iter().flatten())
+            .map(|entry| &entry.observation)
+            .chain(
+                located
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|id| self.observations.get(&id)),
+            )
+            .filter(move |other| *query_id != other.id && include(&other.id)); // Exclude self
+        let gate = move |obs: &'a Unique<Arc<Observation>, Id>| {
+            if obs.data.is_gated_out(query, config) {
+                return None;
+            }
+            let distance = obs.data.gated_distance_squared(query, config);
+            if distance > chi2_threshold {
+                if let Some(log) = &self.discards.0 {
+                    log.discarded(*query_id, obs.id, distance, chi2_threshold);
+                }
+                return None;
+            }
+            Some((obs, distance))
+        };
+
+        // The candidates are usually found lazily, as each is tested. To time the phases
+        // separately, all of the candidates are found before any are tested.
+        if self.profiling.is_active() {
+            let candidates: Vec<_> = self
+                .profiling
+                .time(Phase::SpatialQuery, || candidates.collect());
+            let compatible: Vec<_> = self.profiling.time(Phase::Gating, || {
+                candidates.into_iter().filter_map(gate).collect()
+            });
+            Compatible::Timed(compatible.into_iter())
+        } else {
+            Compatible::Lazy(candidates.filter_map(gate))
+        }
+    }
+
+    /// The profiler timing the queries of the index.
+    pub const fn profiling(&self) -> &Profiling {
+        &self.profiling
+    }
+
+    /// Report the candidates which fail the statistical gate to the log.
+    pub fn set_discard_log(&mut self, log: Box<dyn DiscardLog<Id>>) {
+        self.discards = Discards(Some(log));
+    }
+
+    /// Report the time spent in the queries of the index to the profiler.
+    #[cfg(feature = "profiling")]
+    pub fn set_profiler(&mut self, profiler: Arc<dyn crate::Profiler>) {
+        self.profiling.set(profiler);
+    }
+}
+
+/// The observations found by a compatibility query, either lazily, or all at once if the query
+/// is being timed.
+enum Compatible<L, T> {
+    Lazy(L),
+    Timed(std::vec::IntoIter<T>),
+}
+
+impl<L, T> Iterator for Compatible<L, T>
+where
+    L: Iterator<Item = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self {
+            Self::Lazy(iter) => iter.next(),
+            Self::Timed(iter) => iter.next(),
+        }
+    }
+}
+
+/// Upper bounds on the errors, velocities and timestamps of a set of observations, which bound the
+/// distance from a query to any of them which is compatible with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchBounds {
+    /// The maximum variance of the observations.
+    max_variance: f64,
+
+    /// Upper bounds on the speed and velocity variance of the observations with a velocity, if any.
+    max_velocity: Option<(f64, f64)>,
+
+    /// The earliest and latest timestamps of the observations, if any.
+    time_range: Option<(f64, f64)>,
+}
+
+impl SearchBounds {
+    /// Bounds for observations whose variance is at most `max_variance`, without velocities or
+    /// timestamps.
+    pub const fn new(max_variance: f64) -> Self {
+        Self {
+            max_variance,
+            max_velocity: None,
+            time_range: None,
+        }
+    }
+
+    /// Bound the velocities of the observations by a speed and a velocity variance.
+    pub const fn with_max_velocity(mut self, speed: f64, variance: f64) -> Self {
+        self.max_velocity = Some((speed, variance));
+        self
+    }
+
+    /// Bound the timestamps of the observations by the earliest and latest.
+    pub const fn with_time_range(mut self, start: f64, end: f64) -> Self {
+        self.time_range = Some((start, end));
+        self
+    }
+
+    /// Widen the bounds to include the given observation.
+    pub fn include(&mut self, observation: &Observation) {
+        self.max_variance = self
+            .max_variance
+            .max(observation.error_covariance().max_variance());
+        if let (Some((vx, vy)), Some(error)) =
+            (observation.velocity(), observation.velocity_covariance())
+        {
+            let (speed, variance) = (vx.hypot(vy), error.max_variance());
+            self.max_velocity = Some(
+                self.max_velocity
+                    .map_or((speed, variance), |(s, v)| (s.max(speed), v.max(variance))),
+            );
+        }
+        if let Some(t) = observation.timestamp() {
+            self.time_range = Some(
+                self.time_range
+                    .map_or((t, t), |(start, end)| (start.min(t), end.max(t))),
+            );
+        }
+    }
+
+    /// The radius around the query within which all observations within `chi2_threshold` of it
+    /// must lie.
+    ///
+    /// Under [`MotionModel::ConstantVelocity`], this is widened to account for the greatest
+    /// displacement, and growth in uncertainty, of any observation propagated to or from the query.
+    /// Under [`MotionModel::Ageing`], it is widened to account for the greatest growth in the error
+    /// of any observation due to its age.
+    ///
+    /// It is also widened to account for the [registration
+    /// error](Config::registration_error), and the regularisation of [singular
+    /// errors](Config::singular_covariance), if any.
+    pub fn search_radius(&self, query: &Observation, config: &Config, chi2_threshold: f64) -> f64 {
+        let chi2_threshold = chi2_threshold * config.gate_widening();
+        let motion_model = config.motion_model;
+        let max_other_variance = self.max_variance
+            + config
+                .shared_error()
+                .map_or(0.0, |error| error.max_variance());
+        let static_radius = query.max_compatibility_radius(chi2_threshold, max_other_variance);
+
+        let (Some(t), Some((start, end))) = (query.timestamp(), self.time_range) else {
+            return static_radius;
+        };
+        let dt = (t - start).abs().max((end - t).abs());
+        let process_noise = match motion_model {
+            MotionModel::Static => return static_radius,
+            MotionModel::Ageing { process_noise } => {
+                let combined_max_variance = process_noise.mul_add(
+                    dt,
+                    query.error_covariance().max_variance() + max_other_variance,
+                );
+                return (chi2_threshold * combined_max_variance).sqrt();
+            }
+            MotionModel::ConstantVelocity { process_noise } => process_noise,
+        };
+        let query_velocity = query
+            .velocity()
+            .zip(query.velocity_covariance())
+            .map(|((vx, vy), error)| (vx.hypot(vy), error.max_variance()));
+        let (speed, velocity_variance) = match (self.max_velocity, query_velocity) {
+            (Some((s1, v1)), Some((s2, v2))) => (s1.max(s2), v1.max(v2)),
+            (Some(bound), None) | (None, Some(bound)) => bound,
+            (None, None) => return static_radius,
+        };
+
+        let propagated_variance =
+            (dt * dt).mul_add(velocity_variance, process_noise * dt.powi(3) / 3.0);
+        let combined_max_variance =
+            query.error_covariance().max_variance() + max_other_variance + propagated_variance;
+        speed.mul_add(dt, (chi2_threshold * combined_max_variance).sqrt())
     }
 }
 
@@ -159,23 +740,119 @@ where
     ///
     /// The result is an undirected graph represented as an adjacency list, where each node is an
     /// observation ID and edges represent pairs of observations whose error ellipses mutually include
-    /// the other's position under the given chi-squared threshold.
-    pub fn compatibility_graph(
+    /// the other's position under the configured chi-squared threshold.
+    ///
+    /// Progress is reported to the monitor, and an error is returned if it is cancelled before the
+    /// graph is complete.
+    pub fn compatibility_graph<G: Adjacency<Id>>(
         &self,
-        chi2_threshold: f64,
-    ) -> impl Iterator<Item = (Id, HashSet<Id>)> {
-        self.tree.iter().filter_map(move |obs| {
-            let compatibles: HashSet<_> = self
-                .find_compatible(obs, chi2_threshold)
-                .map(|other| other.id)
-                .collect();
+        config: &Config,
+        monitor: &mut Monitor,
+    ) -> Result<G, Cancelled> {
+        // Compatibility is symmetric, so each pair is only tested by whichever of its observations
+        // comes first in an arbitrary order, and the edge is added to the adjacency of both.
+        let order: HashMap<Id, usize> = self
+            .observations
+            .keys()
+            .enumerate()
+            .map(|(rank, id)| (*id, rank))
+            .collect();
+
+        let mut graph = G::default();
+        let total = self.observations.len();
+        for (done, obs) in self.observations.values().enumerate() {
+            monitor.step(BuildPhase::CompatibilityGraph, done, total)?;
+            let rank = order[&obs.id];
+            for (other, _) in
+                self.find_compatible_among(obs, config, config.chi2, |id| order[id] > rank)
+            {
+                graph.entry(obs.id).insert(other.id);
+                graph.entry(other.id).insert(obs.id);
+            }
+        }
+        monitor.step(BuildPhase::CompatibilityGraph, total, total)?;
+        Ok(graph)
+    }
+
+    /// Build a graph connecting mutually compatible observations approximately, as for
+    /// [`Config::approximate_search`].
+    ///
+    /// A compatible pair lies within `√(χ² λ)` of each other, where `λ` is the largest eigenvalue of
+    /// their combined error, which is at most the sum of their maximum variances (plus that of any
+    /// shared error). So if both of their maximum variances are within the cap, they lie within one
+    /// cell of each other, in a grid of cells with that width.
+    ///
+    /// Under a [`MotionModel`] other than [`MotionModel::Static`], the graph is built exactly.
+    pub fn approximate_compatibility_graph<G: Adjacency<Id>>(
+        &self,
+        config: &Config,
+        miss_rate: f64,
+        monitor: &mut Monitor,
+    ) -> Result<G, Cancelled> {
+        let mut variances: Vec<f64> = self
+            .observations
+            .values()
+            .map(|obs| obs.data.error_covariance().max_variance())
+            .collect();
+        if variances.is_empty() || config.motion_model != MotionModel::Static {
+            return self.compatibility_graph(config, monitor);
+        }
+        // at most ⌊miss_rate ⋅ n⌋ observations have a variance above the cap
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let excluded = (miss_rate * variances.len() as f64).floor() as usize;
+        let rank = variances.len() - 1 - excluded.min(variances.len() - 1);
+        let (_, cap, _) = variances.select_nth_unstable_by(rank, f64::total_cmp);
+        let shared = config
+            .shared_error()
+            .map_or(0.0, |error| error.max_variance());
+        let width = (config.chi2 * config.gate_widening() * 2.0f64.mul_add(*cap, shared)).sqrt();
+        if !(width.is_finite() && width > 0.0) {
+            return self.compatibility_graph(config, monitor);
+        }
+
+        let cells = SquareCells::new(width);
+        let mut grid: HashMap<_, Vec<&Unique<Arc<Observation>, Id>>> = HashMap::new();
+        for obs in self.observations.values() {
+            let (x, y) = obs.data.position();
+            grid.entry(cells.cell(x, y)).or_default().push(obs);
+        }
+
+        // as for the exact graph, each pair is only tested by whichever comes first
+        let order: HashMap<Id, usize> = self
+            .observations
+            .keys()
+            .enumerate()
+            .map(|(rank, id)| (*id, rank))
+            .collect();
 
-            if compatibles.is_empty() {
-                None
-            } else {
-                Some((obs.id, compatibles))
+        let mut graph = G::default();
+        let total = self.observations.len();
+        for (done, obs) in self.observations.values().enumerate() {
+            monitor.step(BuildPhase::CompatibilityGraph, done, total)?;
+            let rank = order[&obs.id];
+            let (x, y) = obs.data.position();
+            let (column, row) = cells.cell(x, y);
+            let neighbours = (column - 1..=column + 1)
+                .flat_map(|column| (row - 1..=row + 1).map(move |row| (column, row)))
+                .filter_map(|cell| grid.get(&cell))
+                .flatten()
+                .filter(|other| order[&other.id] > rank);
+            for other in neighbours {
+                if other.data.is_gated_out(&obs.data, config) {
+                    continue;
+                }
+                if other.data.gated_distance_squared(&obs.data, config) <= config.chi2 {
+                    graph.entry(obs.id).insert(other.id);
+                    graph.entry(other.id).insert(obs.id);
+                }
             }
-        })
+        }
+        monitor.step(BuildPhase::CompatibilityGraph, total, total)?;
+        Ok(graph)
     }
 }
 
@@ -198,12 +875,12 @@ mod tests {
         };
 
         // Create an index with just this one observation
-        let mut index = SpatialIndex::default();
+        let mut index = SpatialIndex::new(crate::CHI2_2D_CONFIDENCE_95);
         index.insert(query_obs.clone());
 
         // Find compatible observations
         let compatibles = index
-            .find_compatible(&query_obs, crate::CHI2_2D_CONFIDENCE_95)
+            .find_compatible(&query_obs, &Config::new(crate::CHI2_2D_CONFIDENCE_95))
             .count();
 
         // Should be empty - the observation should not be compatible with itself
@@ -234,11 +911,14 @@ mod tests {
             id: 3,
         };
 
-        let index = SpatialIndex::from_observations(vec![obs1.clone(), obs2.clone(), obs3.clone()]);
+        let index = SpatialIndex::from_observations(
+            vec![obs1.clone(), obs2.clone(), obs3.clone()],
+            crate::CHI2_2D_CONFIDENCE_95,
+        );
 
         // Find compatible observations for obs1
         let compatibles: Vec<_> = index
-            .find_compatible(&obs1, crate::CHI2_2D_CONFIDENCE_95)
+            .find_compatible(&obs1, &Config::new(crate::CHI2_2D_CONFIDENCE_95))
             .collect();
 
         // Should find obs2 and obs3, but not obs1 itself
@@ -248,19 +928,68 @@ mod tests {
             "Should find 2 compatible observations"
         );
         assert!(
-            !compatibles.iter().any(|obs| obs.id == obs1.id),
+            !compatibles.iter().any(|(obs, _)| obs.id == obs1.id),
             "Should not include the query observation"
         );
         assert!(
-            compatibles.iter().any(|obs| obs.id == obs2.id),
+            compatibles.iter().any(|(obs, _)| obs.id == obs2.id),
             "Should include obs2"
         );
         assert!(
-            compatibles.iter().any(|obs| obs.id == obs3.id),
+            compatibles.iter().any(|(obs, _)| obs.id == obs3.id),
             "Should include obs3"
         );
     }
 
+    #[test]
+    fn find_compatible_searches_the_full_radius() {
+        // compatible (d² = 2), but further apart than the square root of the search radius
+        let error = CovarianceMatrix::new(100.0, 100.0, 0.0).unwrap();
+        let obs1 = Unique {
+            data: Observation::builder(0.0, 0.0).error(error).build(),
+            id: 1,
+        };
+        let obs2 = Unique {
+            data: Observation::builder(20.0, 0.0).error(error).build(),
+            id: 2,
+        };
+        let index =
+            SpatialIndex::from_observations(vec![obs1.clone(), obs2], crate::CHI2_2D_CONFIDENCE_95);
+
+        let compatibles: Vec<_> = index
+            .find_compatible(&obs1, &Config::new(crate::CHI2_2D_CONFIDENCE_95))
+            .map(|(obs, _)| obs.id)
+            .collect();
+
+        assert_eq!(compatibles, vec![2]);
+    }
+
+    #[test]
+    fn find_compatible_finds_candidates_by_their_own_envelope() {
+        // the query is precise, so only the candidate's large envelope reaches it
+        let precise = Unique {
+            data: Observation::builder(0.0, 0.0)
+                .error(CovarianceMatrix::new(0.01, 0.01, 0.0).unwrap())
+                .build(),
+            id: 1,
+        };
+        let imprecise = Unique {
+            data: Observation::builder(15.0, 0.0)
+                .error(CovarianceMatrix::new(100.0, 100.0, 0.0).unwrap())
+                .build(),
+            id: 2,
+        };
+        let mut index = SpatialIndex::new(crate::CHI2_2D_CONFIDENCE_95);
+        index.insert(imprecise);
+
+        let compatibles: Vec<_> = index
+            .find_compatible(&precise, &Config::new(crate::CHI2_2D_CONFIDENCE_95))
+            .map(|(obs, _)| obs.id)
+            .collect();
+
+        assert_eq!(compatibles, vec![2]);
+    }
+
     #[test]
     fn find_compatible_with_overlapping_error_ellipses() {
         // Create observations that are close enough to be mutually compatible
@@ -279,33 +1008,103 @@ mod tests {
             id: 3,
         };
 
-        let index = SpatialIndex::from_observations(vec![obs1.clone(), obs2.clone(), obs3.clone()]);
+        let index = SpatialIndex::from_observations(
+            vec![obs1.clone(), obs2.clone(), obs3.clone()],
+            crate::CHI2_2D_CONFIDENCE_95,
+        );
 
         // Find compatible observations for obs1
         let compatibles: Vec<_> = index
-            .find_compatible(&obs1, crate::CHI2_2D_CONFIDENCE_95)
+            .find_compatible(&obs1, &Config::new(crate::CHI2_2D_CONFIDENCE_95))
             .collect();
 
         // Should find obs2 but not obs3 (too far) and not obs1 itself
         assert_eq!(compatibles.len(), 1, "Should find 1 compatible observation");
         assert!(
-            !compatibles.iter().any(|obs| obs.id == obs1.id),
+            !compatibles.iter().any(|(obs, _)| obs.id == obs1.id),
             "Should not include the query observation"
         );
         assert!(
-            compatibles.iter().any(|obs| obs.id == obs2.id),
+            compatibles.iter().any(|(obs, _)| obs.id == obs2.id),
             "Should include obs2"
         );
         assert!(
-            !compatibles.iter().any(|obs| obs.id == obs3.id),
+            !compatibles.iter().any(|(obs, _)| obs.id == obs3.id),
             "Should not include obs3 (too far)"
         );
+
+        // the distance is under the sum of the covariances, 2I
+        approx::assert_relative_eq!(compatibles[0].1, 0.5);
+    }
+
+    #[test]
+    fn removed_observations_are_no_longer_found() {
+        let data = Observation::builder(0.0, 0.0)
+            .circular_95_confidence_error(2.0)
+            .unwrap()
+            .build();
+        let obs1 = Unique {
+            data: data.clone(),
+            id: 1,
+        };
+        let obs2 = Unique { data, id: 2 };
+
+        let mut index =
+            SpatialIndex::from_observations(vec![obs1.clone(), obs2], crate::CHI2_2D_CONFIDENCE_95);
+
+        assert_eq!(index.remove(&2).as_deref(), Some(&obs1.data));
+        assert_eq!(index.remove(&2), None);
+        assert!(index.get(&2).is_none());
+        assert_eq!(
+            index
+                .find_compatible(&obs1, &Config::new(crate::CHI2_2D_CONFIDENCE_95))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn compatibility_graph_matches_find_compatible() {
+        use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let observations: Vec<_> = (0..200_u32)
+            .map(|id| Unique {
+                data: Observation::builder(
+                    rng.random_range(0.0..40.0),
+                    rng.random_range(0.0..40.0),
+                )
+                .circular_95_confidence_error(rng.random_range(1.0..4.0))
+                .unwrap()
+                .build(),
+                id,
+            })
+            .collect();
+        let config = Config::new(crate::CHI2_2D_CONFIDENCE_95);
+        let index = SpatialIndex::from_observations(observations.clone(), config.chi2());
+
+        let graph: HashMap<u32, HashSet<u32>> = index
+            .compatibility_graph(&config, &mut Monitor::default())
+            .unwrap();
+        let expected: HashMap<u32, HashSet<u32>> = observations
+            .iter()
+            .map(|obs| {
+                let compatibles: HashSet<u32> = index
+                    .find_compatible(obs, &config)
+                    .map(|(other, _)| other.id)
+                    .collect();
+                (obs.id, compatibles)
+            })
+            .filter(|(_, compatibles)| !compatibles.is_empty())
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(graph, expected);
     }
 
     #[test]
     #[should_panic(expected = "attempted to insert duplicate observation")]
     fn disallows_duplicates() {
-        let mut spatial_index = SpatialIndex::default();
+        let mut spatial_index = SpatialIndex::new(crate::CHI2_2D_CONFIDENCE_95);
         let observation = Unique {
             data: Observation::builder(0.0, 0.0)
                 .circular_95_confidence_error(5.0)
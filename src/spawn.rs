@@ -0,0 +1,133 @@
+//! Batch construction of indices on tokio's blocking thread pool.
+
+use std::{fmt, hash::Hash};
+
+use tokio::task::JoinHandle;
+
+use crate::{CancellationToken, CliqueIndex, Config, Error, Observation, Unique};
+
+impl<Id> CliqueIndex<Id>
+where
+    Id: Eq + Hash + Copy + fmt::Debug + Send + Sync + 'static,
+{
+    /// Construct a new index from a batch of observations on tokio's blocking thread pool.
+    ///
+    /// Building an index from a large batch of observations can take long enough to stall an async
+    /// runtime, so this moves the work to a thread where blocking is allowed. Await the returned
+    /// handle for the index.
+    ///
+    /// Blocking tasks can't be aborted, so aborting the handle doesn't stop the construction. Cancel
+    /// `cancellation` instead, and the handle resolves to [`Error::Cancelled`]. See
+    /// [`Self::from_observations_cancellable`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    #[must_use = "the index is only available by awaiting the handle"]
+    pub fn spawn_build(
+        observations: Vec<Unique<Observation, Id>>,
+        config: Config,
+        cancellation: CancellationToken,
+    ) -> JoinHandle<Result<Self, Error>> {
+        tokio::task::spawn_blocking(move || {
+            Self::from_observations_cancellable(observations, config, &cancellation)
+        })
+    }
+
+    /// Construct a new index from a batch of observations, without blocking the async runtime.
+    ///
+    /// The construction runs on tokio's blocking thread pool (see [`Self::spawn_build`]). If the
+    /// returned future is dropped before it completes, `cancellation` is cancelled, so that the
+    /// construction stops soon after.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Cancelled`] if `cancellation` is cancelled before the index is complete,
+    /// or if the runtime is shut down first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime. A panic during construction is propagated.
+    pub async fn from_observations_async(
+        observations: Vec<Unique<Observation, Id>>,
+        config: Config,
+        cancellation: CancellationToken,
+    ) -> Result<Self, Error> {
+        let mut guard = CancelOnDrop(Some(cancellation.clone()));
+        let result = Self::spawn_build(observations, config, cancellation).await;
+        guard.0 = None;
+
+        match result {
+            Ok(result) => result,
+            Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+            Err(_) => Err(Error::Cancelled),
+        }
+    }
+}
+
+/// Cancels a token when dropped, unless it has been disarmed (by taking the token).
+struct CancelOnDrop(Option<CancellationToken>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(cancellation) = &self.0 {
+            cancellation.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CHI2_2D_CONFIDENCE_95;
+
+    fn observations() -> Vec<Unique<Observation, u32>> {
+        (0..20)
+            .map(|id| Unique {
+                data: Observation::builder(f64::from(id % 5), 0.0)
+                    .circular_95_confidence_error(1.0)
+                    .unwrap()
+                    .build(),
+                id,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn async_construction_matches_blocking_construction() {
+        let config = Config::new(CHI2_2D_CONFIDENCE_95);
+        let index = CliqueIndex::from_observations_async(
+            observations(),
+            config.clone(),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        let expected = CliqueIndex::from_observations_with_config(observations(), config);
+        assert_eq!(index.state_hash(), expected.state_hash());
+    }
+
+    #[tokio::test]
+    async fn cancelled_construction_returns_an_error() {
+        let cancellation = CancellationToken::new();
+        let handle = CliqueIndex::spawn_build(
+            observations(),
+            Config::new(CHI2_2D_CONFIDENCE_95),
+            cancellation.clone(),
+        );
+        cancellation.cancel();
+
+        // the construction may complete before it is cancelled
+        let result = handle.await.unwrap();
+        assert!(matches!(result, Ok(_) | Err(Error::Cancelled)));
+
+        let result = CliqueIndex::from_observations_async(
+            observations(),
+            Config::new(CHI2_2D_CONFIDENCE_95),
+            cancellation,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+}
@@ -0,0 +1,102 @@
+use std::hash::Hasher;
+
+/// A 64-bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hasher.
+///
+/// Unlike [`DefaultHasher`](std::hash::DefaultHasher), its algorithm is fixed, and integers are
+/// written in little-endian order (with `usize` and `isize` widened to 64 bits), so the same values
+/// hash equally on every platform and with every version of Rust.
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+    use super::StableHasher;
+
+    #[test]
+    fn matches_the_reference_fnv_1a_vectors() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = StableHasher::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn integers_are_independent_of_the_platform() {
+        let hasher = BuildHasherDefault::<StableHasher>::default();
+        assert_eq!(hasher.hash_one(7_usize), hasher.hash_one(7_u64));
+        assert_eq!(hasher.hash_one(-7_isize), hasher.hash_one(-7_i64));
+
+        let mut little_endian = StableHasher::default();
+        little_endian.write(&[4, 3, 2, 1]);
+        assert_eq!(hasher.hash_one(0x0102_0304_u32), little_endian.finish());
+    }
+}
@@ -0,0 +1,510 @@
+//! Generation of reproducible synthetic observations, for benchmarking and testing.
+//!
+//! [`generate_observations`] produces a static batch of clustered and scattered observations.
+//! [`Scenario`] scripts a time-ordered stream of insertions and removals from several imperfect
+//! sensors observing a set of stationary targets, together with the ground truth, for exercising
+//! the incremental path under realistic conditions.
+
+use std::{collections::HashMap, f64::consts::PI, num::NonZeroUsize};
+
+use rand::prelude::*;
+use uuid::Uuid;
+
+use crate::{CHI2_2D_CONFIDENCE_95, CliqueIndex, Observation, Unique};
+
+/// Configuration for generating synthetic observation data for benchmarking.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Maximum offset from reference point for scattered points, in meters.
+    pub spread: f64,
+    /// Percentage of points to generate in clusters (0–100).
+    pub cluster_pct: f64,
+    /// Maximum radius of each cluster in meters.
+    pub cluster_size: f64,
+    /// Number of observations per cluster.
+    pub observations_per_cluster: NonZeroUsize,
+    /// Total number of observations to generate.
+    pub total_count: usize,
+    /// The circular positional error of each observation's position in metres (95% confidence interval)
+    pub error_radius: f64,
+    /// Seed used by the random number generator
+    pub random_seed: u64,
+}
+
+/// Generate a single point randomly distributed within a circle of a given radius
+fn generate_scattered_point(radius: f64, rng: &mut impl Rng) -> (f64, f64) {
+    fn limit_precision(value: f64) -> f64 {
+        (value * 1e10).round() / 1e10
+    }
+
+    let distance = radius * rng.random::<f64>().sqrt();
+    let angle = rng.random_range(0.0..2.0 * PI);
+
+    let x = limit_precision(distance * angle.cos());
+    let y = limit_precision(distance * angle.sin());
+    (x, y)
+}
+
+/// Generates a random locations within a circular cluster.
+fn generate_scatter(radius: f64, rng: &mut impl Rng) -> impl Iterator<Item = (f64, f64)> {
+    std::iter::repeat_with(move || generate_scattered_point(radius, rng))
+}
+
+/// Generate a random ID, drawn from the seeded generator so that IDs are reproducible.
+fn generate_id(rng: &mut impl Rng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.random()).into_uuid()
+}
+
+/// Sample from a normal distribution with zero mean, using the Box-Muller transform.
+fn sample_normal(standard_deviation: f64, rng: &mut impl Rng) -> f64 {
+    // shift the first sample away from zero, so that its logarithm is finite
+    let u1 = 1.0 - rng.random::<f64>();
+    let u2 = rng.random::<f64>();
+    standard_deviation * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+/// Sample from a Poisson distribution, using Knuth's algorithm.
+///
+/// This is only efficient for small means, which is all that's needed for clutter.
+fn sample_poisson(mean: f64, rng: &mut impl Rng) -> usize {
+    let limit = (-mean).exp();
+    let mut count = 0;
+    let mut product = rng.random::<f64>();
+    loop {
+        if product <= limit {
+            return count;
+        }
+        count += 1;
+        product *= rng.random::<f64>();
+    }
+}
+
+/// Iterator over clustered positions. Each cluster contains a fixed number of points, centred around a randomly chosen location.
+///
+/// This is a needlessly complicated approach, but it does mean that clustered positions can
+/// be created entirely lazily, with no intermediate memory allocations.
+#[derive(Debug)]
+struct ClusteredPositionIter<'a, R: Rng> {
+    radius: f64,
+    cluster_radius: f64,
+    // This uses a non-zero integer since semantically a zero-sized cluster doesn't make sense.
+    // This means we don't have to handle this case later in the iterator logic.
+    cluster_count: NonZeroUsize,
+    cluster_centre: (f64, f64),
+    points_remaining: usize,
+    rng: &'a mut R,
+}
+
+impl<'a, R> ClusteredPositionIter<'a, R>
+where
+    R: Rng,
+{
+    const fn new(
+        radius: f64,
+        cluster_radius: f64,
+        cluster_count: NonZeroUsize,
+        rng: &'a mut R,
+    ) -> Self {
+        Self {
+            radius,
+            cluster_radius,
+            cluster_count,
+            cluster_centre: (0.0, 0.0),
+            points_remaining: 0,
+            rng,
+        }
+    }
+}
+
+impl<R> Iterator for ClusteredPositionIter<'_, R>
+where
+    R: Rng,
+{
+    type Item = (f64, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // If the cluster is exhausted, define a new cluster centre
+        if self.points_remaining == 0 {
+            self.points_remaining = self.cluster_count.into(); // always greater than 0!
+            self.cluster_centre = generate_scattered_point(self.radius, self.rng);
+        }
+
+        // Generate a point within the cluster and return it
+        let (dx, dy) = generate_scattered_point(self.cluster_radius, self.rng);
+        self.points_remaining -= 1;
+        Some((self.cluster_centre.0 + dx, self.cluster_centre.1 + dy))
+    }
+}
+
+/// Generates synthetic observations in local (x, y) coordinates for benchmarking.
+///
+/// The output includes a mix of clustered and scattered observations.
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn generate_observations(config: &Config) -> Vec<Unique<Observation, Uuid>> {
+    let mut rng = StdRng::seed_from_u64(config.random_seed);
+
+    // calculate distribution
+    #[allow(
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss
+    )]
+    let desired_clustered =
+        ((config.total_count as f64) * (config.cluster_pct / 100.0)).round() as usize;
+    #[allow(
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss
+    )]
+    let num_clusters = (desired_clustered as f64
+        / usize::from(config.observations_per_cluster) as f64)
+        .ceil() as usize;
+    let actual_clustered = num_clusters * usize::from(config.observations_per_cluster);
+
+    // If we calculated more clustered than total, adjust
+    let final_clustered = actual_clustered.min(config.total_count);
+    let scattered_count = config.total_count - final_clustered;
+
+    let mut positions: Vec<(f64, f64)> = ClusteredPositionIter::new(
+        config.spread,
+        config.cluster_size,
+        config.observations_per_cluster,
+        &mut rng,
+    )
+    .take(final_clustered)
+    .collect();
+    positions.extend(generate_scatter(config.spread, &mut rng).take(scattered_count));
+
+    let mut observations: Vec<_> = positions
+        .into_iter()
+        .map(|(x, y)| Unique {
+            data: Observation::builder(x, y)
+                .circular_95_confidence_error(config.error_radius)
+                .unwrap()
+                .build(),
+            id: generate_id(&mut rng),
+        })
+        .collect();
+
+    // Verify we have the expected count
+    assert_eq!(observations.len(), config.total_count);
+
+    observations.shuffle(&mut rng);
+    observations
+}
+
+/// An imperfect sensor in a [`Scenario`].
+#[derive(Debug, Clone)]
+pub struct SimulatedSensor {
+    /// The circular positional error of each detection in metres (95% confidence interval).
+    pub error_radius: f64,
+    /// The probability (0–1) that the sensor fails to detect a given target in a scan.
+    pub miss_probability: f64,
+    /// The probability (0–1) that the sensor drops out for an entire scan, reporting nothing.
+    pub dropout_probability: f64,
+    /// The standard deviation of the change in the sensor's bias between scans, in metres.
+    ///
+    /// The bias is a random walk, starting at zero, which is added to every detection. It isn't
+    /// reflected in the reported error, so a drifting sensor gradually becomes incompatible with
+    /// the others.
+    pub bias_drift: f64,
+    /// The mean number of false detections (clutter) per scan, scattered uniformly.
+    pub clutter_rate: f64,
+}
+
+/// A script for a time-ordered stream of observations, from several sensors repeatedly scanning a
+/// set of stationary targets.
+///
+/// Each scan of each sensor is a separate [context](crate::Observation::context), and every
+/// detection is timestamped with the time of its scan. Observations may be retained for a fixed
+/// number of scans, after which they are removed, as a tracker with a sliding window would.
+///
+/// ```
+/// use clique_fusion::{
+///     CHI2_2D_CONFIDENCE_95, CliqueIndex,
+///     synthetic::{Scenario, SimulatedSensor},
+/// };
+///
+/// let sensor = SimulatedSensor {
+///     error_radius: 2.0,
+///     miss_probability: 0.1,
+///     dropout_probability: 0.05,
+///     bias_drift: 0.1,
+///     clutter_rate: 1.0,
+/// };
+/// let scenario = Scenario {
+///     spread: 500.0,
+///     targets: 20,
+///     sensors: vec![sensor.clone(), sensor],
+///     scans: 10,
+///     scan_interval: 1.0,
+///     retention: Some(3),
+///     random_seed: 7,
+/// };
+/// let simulation = scenario.generate();
+///
+/// let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+/// simulation.replay(&mut index);
+///
+/// // only the observations of the last three scans are retained
+/// let mut retained = simulation.truth.origins.keys().filter_map(|id| index.get(id));
+/// assert!(retained.all(|observation| observation.timestamp() >= Some(7.0)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// The radius of the circular region containing the targets and clutter, in metres.
+    pub spread: f64,
+    /// The number of targets.
+    pub targets: usize,
+    /// The sensors observing the targets.
+    pub sensors: Vec<SimulatedSensor>,
+    /// The number of scans made by each sensor.
+    pub scans: usize,
+    /// The time between consecutive scans.
+    pub scan_interval: f64,
+    /// The number of scans for which observations are retained before being removed, or `None`
+    /// if they are never removed.
+    pub retention: Option<usize>,
+    /// Seed used by the random number generator
+    pub random_seed: u64,
+}
+
+/// A single step of a [`Simulation`].
+#[derive(Debug, Clone)]
+// most events are insertions, so boxing them would only add indirection
+#[allow(clippy::large_enum_variant)]
+pub enum Event {
+    /// A new observation is inserted.
+    Insert(Unique<Observation, Uuid>),
+    /// A previously inserted observation is removed.
+    Remove(Uuid),
+}
+
+impl Event {
+    /// Apply the event to an index.
+    pub fn apply(&self, index: &mut CliqueIndex<Uuid>) {
+        match self {
+            Self::Insert(observation) => index.insert(observation.clone()),
+            Self::Remove(id) => {
+                index.remove(id);
+            }
+        }
+    }
+}
+
+/// The source of a simulated observation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Origin {
+    /// A detection of the target at the given position of [`GroundTruth::targets`].
+    Target(usize),
+    /// A false detection.
+    Clutter,
+}
+
+/// The truth behind a [`Simulation`].
+#[derive(Debug, Clone, Default)]
+pub struct GroundTruth {
+    /// The true positions of the targets.
+    pub targets: Vec<(f64, f64)>,
+    /// The IDs of the sensors, in the order of [`Scenario::sensors`].
+    pub sensors: Vec<Uuid>,
+    /// The source of every observation.
+    pub origins: HashMap<Uuid, Origin>,
+}
+
+/// The output of a [`Scenario`]: a time-ordered stream of events, and the truth behind it.
+#[derive(Debug, Clone, Default)]
+pub struct Simulation {
+    /// The insertions and removals, in order.
+    pub events: Vec<Event>,
+    /// The truth behind the observations.
+    pub truth: GroundTruth,
+}
+
+impl Simulation {
+    /// Apply every event to an index, in order.
+    pub fn replay(&self, index: &mut CliqueIndex<Uuid>) {
+        for event in &self.events {
+            event.apply(index);
+        }
+    }
+}
+
+impl Scenario {
+    /// Run the scenario, generating its events and ground truth.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the error radius of any sensor is not finite and strictly positive.
+    #[must_use]
+    pub fn generate(&self) -> Simulation {
+        let mut rng = StdRng::seed_from_u64(self.random_seed);
+
+        let targets: Vec<_> = generate_scatter(self.spread, &mut rng)
+            .take(self.targets)
+            .collect();
+        let sensors: Vec<_> = self.sensors.iter().map(|_| generate_id(&mut rng)).collect();
+        let mut biases = vec![(0.0, 0.0); self.sensors.len()];
+
+        let mut simulation = Simulation {
+            events: Vec::new(),
+            truth: GroundTruth {
+                targets,
+                sensors,
+                origins: HashMap::new(),
+            },
+        };
+        // the observations made in each scan, so that they can be removed when they expire
+        let mut scans: Vec<Vec<Uuid>> = Vec::with_capacity(self.scans);
+
+        for scan in 0..self.scans {
+            if let Some(expired) = self
+                .retention
+                .and_then(|retention| scan.checked_sub(retention))
+            {
+                simulation
+                    .events
+                    .extend(scans[expired].drain(..).map(Event::Remove));
+            }
+
+            #[allow(clippy::cast_precision_loss)]
+            let time = scan as f64 * self.scan_interval;
+            let mut detected = Vec::new();
+
+            for (sensor_index, sensor) in self.sensors.iter().enumerate() {
+                let bias = &mut biases[sensor_index];
+                bias.0 += sample_normal(sensor.bias_drift, &mut rng);
+                bias.1 += sample_normal(sensor.bias_drift, &mut rng);
+                let bias = *bias;
+
+                if rng.random_bool(sensor.dropout_probability) {
+                    continue;
+                }
+
+                let sigma = sensor.error_radius / CHI2_2D_CONFIDENCE_95.sqrt();
+                let context = generate_id(&mut rng);
+
+                let mut detections = Vec::new();
+                for (target, &(x, y)) in simulation.truth.targets.iter().enumerate() {
+                    if rng.random_bool(sensor.miss_probability) {
+                        continue;
+                    }
+                    let position = (
+                        x + bias.0 + sample_normal(sigma, &mut rng),
+                        y + bias.1 + sample_normal(sigma, &mut rng),
+                    );
+                    detections.push((position, Origin::Target(target)));
+                }
+                let clutter = sample_poisson(sensor.clutter_rate, &mut rng);
+                detections.extend(
+                    generate_scatter(self.spread, &mut rng)
+                        .take(clutter)
+                        .map(|position| (position, Origin::Clutter)),
+                );
+                detections.shuffle(&mut rng);
+
+                for ((x, y), origin) in detections {
+                    let id = generate_id(&mut rng);
+                    let observation = Observation::builder(x, y)
+                        .circular_95_confidence_error(sensor.error_radius)
+                        .expect("error radius must be finite and positive")
+                        .context(context)
+                        .sensor(simulation.truth.sensors[sensor_index])
+                        .timestamp(time)
+                        .build();
+                    simulation.truth.origins.insert(id, origin);
+                    simulation.events.push(Event::Insert(Unique {
+                        data: observation,
+                        id,
+                    }));
+                    detected.push(id);
+                }
+            }
+
+            scans.push(detected);
+        }
+
+        simulation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn perfect_sensor() -> SimulatedSensor {
+        SimulatedSensor {
+            error_radius: 1.0,
+            miss_probability: 0.0,
+            dropout_probability: 0.0,
+            bias_drift: 0.0,
+            clutter_rate: 0.0,
+        }
+    }
+
+    #[test]
+    fn perfect_sensors_find_every_target() {
+        let scenario = Scenario {
+            spread: 1000.0,
+            targets: 5,
+            sensors: vec![perfect_sensor(), perfect_sensor()],
+            scans: 6,
+            scan_interval: 0.5,
+            retention: Some(2),
+            random_seed: 11,
+        };
+        let simulation = scenario.generate();
+
+        let mut index = CliqueIndex::new(CHI2_2D_CONFIDENCE_95);
+        simulation.replay(&mut index);
+
+        // two sensors, for the two most recent scans
+        assert_eq!(index.len(), 5 * 2 * 2);
+
+        // an occasional pair of detections falls outside the 95% threshold, splitting a clique,
+        // but no clique mixes targets
+        let mut found = HashSet::new();
+        for clique in index.cliques() {
+            let origins: HashSet<_> = clique
+                .iter()
+                .map(|id| simulation.truth.origins[id])
+                .collect();
+            assert_eq!(origins.len(), 1);
+            found.extend(origins);
+        }
+        assert_eq!(found.len(), 5);
+    }
+
+    #[test]
+    fn imperfect_sensors_drop_detections_and_add_clutter() {
+        let sensor = SimulatedSensor {
+            miss_probability: 0.2,
+            clutter_rate: 2.0,
+            ..perfect_sensor()
+        };
+        let scenario = Scenario {
+            spread: 1000.0,
+            targets: 50,
+            sensors: vec![sensor],
+            scans: 20,
+            scan_interval: 1.0,
+            retention: None,
+            random_seed: 5,
+        };
+        let simulation = scenario.generate();
+
+        let (clutter, detections): (Vec<Origin>, Vec<Origin>) = simulation
+            .truth
+            .origins
+            .values()
+            .copied()
+            .partition(|origin| *origin == Origin::Clutter);
+        assert!(!clutter.is_empty());
+        assert!(detections.len() < 50 * 20);
+        assert_eq!(simulation.events.len(), simulation.truth.origins.len());
+        assert_eq!(scenario.generate().truth.origins, simulation.truth.origins);
+    }
+}
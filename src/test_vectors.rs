@@ -0,0 +1,479 @@
+//! Reproducible test vectors, for validating other implementations of gating and clique
+//! enumeration against this one.
+//!
+//! See [`TestVector`] for the format.
+
+use std::collections::{BTreeSet, HashSet};
+
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::{
+    CliqueIndex, CovarianceMatrix, InvalidCovarianceMatrix, InvalidPosition, Observation, Unique,
+};
+
+/// The version of the test vector format written by [`TestVector::to_json`].
+pub const TEST_VECTOR_VERSION: u64 = 1;
+
+/// A set of observations, with the compatibility graph and cliques expected of them.
+///
+/// Generate a vector from this implementation with [`TestVector::generate`], write it with
+/// [`TestVector::to_json`], and compare the output of another implementation against it with
+/// [`TestVector::check`].
+///
+/// ```
+/// use clique_fusion::{CHI2_2D_CONFIDENCE_95, Observation, TestVector};
+///
+/// let observations = [0.0, 0.5, 100.0].map(|x| {
+///     Observation::builder(x, 0.0)
+///         .circular_95_confidence_error(1.0)
+///         .unwrap()
+///         .build()
+/// });
+/// let vector = TestVector::generate(CHI2_2D_CONFIDENCE_95, observations);
+/// assert_eq!(vector.edges(), [(0, 1)]);
+///
+/// // read by another implementation
+/// let json = vector.to_json();
+/// let read = TestVector::from_json(&json)?;
+///
+/// // and its results checked
+/// let edges = [(1, 0)];
+/// let cliques = [vec![1, 0]];
+/// assert!(read.check(edges, cliques).is_ok());
+/// # Ok::<(), clique_fusion::InvalidTestVector>(())
+/// ```
+///
+/// # Format
+///
+/// A test vector is a JSON object:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "chi2": 5.991,
+///   "observations": [
+///     { "x": 0.0, "y": 0.0, "covariance": [1.0, 0.0, 1.0] },
+///     { "x": 1.0, "y": 0.0, "covariance": [1.0, 0.0, 1.0], "context": "…" }
+///   ],
+///   "edges": [[0, 1]],
+///   "cliques": [[0, 1]]
+/// }
+/// ```
+///
+/// - Observations are identified by their position in the `observations` array.
+/// - `covariance` holds the components `[xx, xy, yy]` of the error covariance matrix.
+/// - `context` is an optional UUID string. Observations sharing a context are never compatible.
+/// - `edges` lists each pair of compatible observations once, as `[a, b]` with `a < b`, in
+///   ascending order.
+/// - `cliques` lists the maximal cliques of two or more observations, each in ascending order, and
+///   the cliques in lexicographic order.
+///
+/// Two observations are compatible if the squared Mahalanobis distance between them, under the
+/// sum of their covariances, is at most `chi2`. See [`Observation::is_compatible_with`].
+#[derive(Debug, Clone)]
+pub struct TestVector {
+    chi2: f64,
+    observations: Vec<Observation>,
+    edges: Vec<(usize, usize)>,
+    cliques: Vec<Vec<usize>>,
+}
+
+impl TestVector {
+    /// Generate a test vector, computing the expected edges and cliques of the observations.
+    ///
+    /// Only the position, error covariance and context of each observation are recorded, and the
+    /// expectations are computed from these alone.
+    #[must_use]
+    pub fn generate(chi2: f64, observations: impl IntoIterator<Item = Observation>) -> Self {
+        let observations: Vec<Observation> = observations
+            .into_iter()
+            .map(|observation| recorded(&observation))
+            .collect();
+        let index = CliqueIndex::from_observations(
+            observations
+                .iter()
+                .enumerate()
+                .map(|(id, observation)| Unique {
+                    data: observation.clone(),
+                    id,
+                })
+                .collect(),
+            chi2,
+        );
+        let edges = index
+            .compatibility_edges()
+            .into_iter()
+            .map(|(a, b, _)| (a, b));
+        let cliques = index.cliques().iter().cloned();
+        Self {
+            chi2,
+            observations,
+            edges: canonical_edges(edges),
+            cliques: canonical_cliques(cliques),
+        }
+    }
+
+    /// The chi-squared threshold.
+    #[must_use]
+    pub const fn chi2(&self) -> f64 {
+        self.chi2
+    }
+
+    /// The observations, identified by their position.
+    #[must_use]
+    pub fn observations(&self) -> &[Observation] {
+        &self.observations
+    }
+
+    /// The expected edges of the compatibility graph, in canonical order.
+    #[must_use]
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// The expected maximal cliques, in canonical order.
+    #[must_use]
+    pub fn cliques(&self) -> &[Vec<usize>] {
+        &self.cliques
+    }
+
+    /// Compare the edges and cliques found by an implementation against those expected.
+    ///
+    /// The edges and cliques may be given in any order, and each edge in either direction.
+    /// Cliques of a single observation are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns the differences, if there are any.
+    pub fn check(
+        &self,
+        edges: impl IntoIterator<Item = (usize, usize)>,
+        cliques: impl IntoIterator<Item = impl IntoIterator<Item = usize>>,
+    ) -> Result<(), TestVectorMismatch> {
+        let edges = canonical_edges(edges);
+        let cliques = canonical_cliques(
+            cliques
+                .into_iter()
+                .map(|clique| clique.into_iter().collect()),
+        );
+        let mismatch = TestVectorMismatch {
+            missing_edges: difference(&self.edges, &edges),
+            unexpected_edges: difference(&edges, &self.edges),
+            missing_cliques: difference(&self.cliques, &cliques),
+            unexpected_cliques: difference(&cliques, &self.cliques),
+        };
+        if mismatch.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatch)
+        }
+    }
+
+    /// Check this implementation against the expectations of the vector, for example to detect
+    /// regressions against vectors generated by an earlier version.
+    ///
+    /// # Errors
+    ///
+    /// Returns the differences, if there are any.
+    pub fn verify(&self) -> Result<(), TestVectorMismatch> {
+        let actual = Self::generate(self.chi2, self.observations.iter().cloned());
+        self.check(actual.edges, actual.cliques)
+    }
+
+    /// Write the test vector as JSON, in the format described on
+    /// [`TestVector`].
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let observations: Vec<Value> = self
+            .observations
+            .iter()
+            .map(|observation| {
+                let error = observation.error_covariance();
+                let mut value = json!({
+                    "x": observation.x(),
+                    "y": observation.y(),
+                    "covariance": [error.xx(), error.xy(), error.yy()],
+                });
+                if let Some(context) = observation.context() {
+                    value["context"] = json!(context.to_string());
+                }
+                value
+            })
+            .collect();
+        json!({
+            "version": TEST_VECTOR_VERSION,
+            "chi2": self.chi2,
+            "observations": observations,
+            "edges": self.edges.iter().map(|&edge| <[usize; 2]>::from(edge)).collect::<Vec<_>>(),
+            "cliques": self.cliques,
+        })
+    }
+
+    /// Read a test vector from JSON, in the format described on
+    /// [`TestVector`].
+    ///
+    /// The expected edges and cliques are read as written, so that a vector generated by an
+    /// earlier version can be [verified](Self::verify) against this one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is not a test vector of a supported version.
+    pub fn from_json(value: &Value) -> Result<Self, InvalidTestVector> {
+        match value["version"].as_u64() {
+            Some(TEST_VECTOR_VERSION) => {}
+            Some(version) => return Err(InvalidTestVector::UnsupportedVersion(version)),
+            None => return Err(InvalidTestVector::InvalidField("version")),
+        }
+        let chi2 = value["chi2"]
+            .as_f64()
+            .ok_or(InvalidTestVector::InvalidField("chi2"))?;
+        let observations = value["observations"]
+            .as_array()
+            .ok_or(InvalidTestVector::InvalidField("observations"))?
+            .iter()
+            .map(read_observation)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let count = observations.len();
+        let indices = |value: &Value, field| {
+            value
+                .as_array()
+                .and_then(|values| {
+                    values
+                        .iter()
+                        .map(|index| {
+                            index
+                                .as_u64()
+                                .and_then(|index| usize::try_from(index).ok())
+                                .filter(|index| *index < count)
+                        })
+                        .collect::<Option<Vec<usize>>>()
+                })
+                .ok_or(InvalidTestVector::InvalidField(field))
+        };
+        let edges = value["edges"]
+            .as_array()
+            .ok_or(InvalidTestVector::InvalidField("edges"))?
+            .iter()
+            .map(|edge| match indices(edge, "edges")?.as_slice() {
+                &[a, b] => Ok((a, b)),
+                _ => Err(InvalidTestVector::InvalidField("edges")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let cliques = value["cliques"]
+            .as_array()
+            .ok_or(InvalidTestVector::InvalidField("cliques"))?
+            .iter()
+            .map(|clique| indices(clique, "cliques"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            chi2,
+            observations,
+            edges,
+            cliques,
+        })
+    }
+}
+
+/// The differences between the edges and cliques expected by a [`TestVector`], and those found by
+/// an implementation.
+///
+/// Edges and cliques are in the canonical form described on [`TestVector`].
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[error(
+    "{} missing and {} unexpected edges, {} missing and {} unexpected cliques",
+    missing_edges.len(),
+    unexpected_edges.len(),
+    missing_cliques.len(),
+    unexpected_cliques.len()
+)]
+pub struct TestVectorMismatch {
+    /// Expected edges which were not found.
+    pub missing_edges: Vec<(usize, usize)>,
+
+    /// Edges which were found, but not expected.
+    pub unexpected_edges: Vec<(usize, usize)>,
+
+    /// Expected cliques which were not found.
+    pub missing_cliques: Vec<Vec<usize>>,
+
+    /// Cliques which were found, but not expected.
+    pub unexpected_cliques: Vec<Vec<usize>>,
+}
+
+impl TestVectorMismatch {
+    fn is_empty(&self) -> bool {
+        self.missing_edges.is_empty()
+            && self.unexpected_edges.is_empty()
+            && self.missing_cliques.is_empty()
+            && self.unexpected_cliques.is_empty()
+    }
+}
+
+/// The error returned when a [`TestVector`] cannot be read from JSON.
+#[derive(Debug, thiserror::Error, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum InvalidTestVector {
+    /// The vector was written in a version of the format which is not supported.
+    #[error("unsupported test vector version {0}")]
+    UnsupportedVersion(u64),
+
+    /// A field is missing, has the wrong type or format, or refers to an observation which does
+    /// not exist.
+    #[error("test vector field '{0}' is invalid")]
+    InvalidField(&'static str),
+
+    /// The covariance of an observation is invalid.
+    #[error(transparent)]
+    InvalidCovariance(#[from] InvalidCovarianceMatrix),
+
+    /// The position of an observation is not finite.
+    #[error(transparent)]
+    InvalidPosition(#[from] InvalidPosition),
+}
+
+/// The parts of an observation which are recorded in a test vector.
+fn recorded(observation: &Observation) -> Observation {
+    let (x, y) = observation.position();
+    let builder = Observation::builder(x, y).error(observation.error_covariance());
+    match observation.context() {
+        Some(context) => builder.context(context).build(),
+        None => builder.build(),
+    }
+}
+
+fn read_observation(value: &Value) -> Result<Observation, InvalidTestVector> {
+    let number = |key: &'static str| {
+        value[key]
+            .as_f64()
+            .ok_or(InvalidTestVector::InvalidField("observations"))
+    };
+    let components: Option<Vec<f64>> = value["covariance"]
+        .as_array()
+        .map(|values| values.iter().filter_map(Value::as_f64).collect());
+    let Some(&[xx, xy, yy]) = components.as_deref() else {
+        return Err(InvalidTestVector::InvalidField("observations"));
+    };
+    let mut builder =
+        Observation::builder(number("x")?, number("y")?).error(CovarianceMatrix::new(xx, yy, xy)?);
+    if let Some(context) = value.get("context") {
+        let context = context
+            .as_str()
+            .and_then(|context| Uuid::parse_str(context).ok())
+            .ok_or(InvalidTestVector::InvalidField("observations"))?;
+        builder = builder.context(context);
+    }
+    Ok(builder.try_build()?)
+}
+
+fn canonical_edges(edges: impl IntoIterator<Item = (usize, usize)>) -> Vec<(usize, usize)> {
+    let edges: BTreeSet<(usize, usize)> = edges
+        .into_iter()
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+    edges.into_iter().collect()
+}
+
+fn canonical_cliques(cliques: impl IntoIterator<Item = HashSet<usize>>) -> Vec<Vec<usize>> {
+    let cliques: BTreeSet<Vec<usize>> = cliques
+        .into_iter()
+        .filter(|clique| clique.len() > 1)
+        .map(|clique| {
+            let mut members: Vec<usize> = clique.into_iter().collect();
+            members.sort_unstable();
+            members
+        })
+        .collect();
+    cliques.into_iter().collect()
+}
+
+/// The items of `a` which are not in `b`, both in canonical order.
+fn difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    a.iter()
+        .filter(|item| b.binary_search(item).is_err())
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::CHI2_2D_CONFIDENCE_95;
+
+    #[test]
+    fn vectors_round_trip_through_json() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let contexts = [Uuid::from_u128(1), Uuid::from_u128(2)];
+        let observations: Vec<Observation> = (0..40)
+            .map(|i| {
+                Observation::builder(rng.random_range(0.0..20.0), rng.random_range(0.0..20.0))
+                    .circular_95_confidence_error(rng.random_range(1.0..4.0))
+                    .unwrap()
+                    .context(contexts[i % 2])
+                    .build()
+            })
+            .collect();
+        let vector = TestVector::generate(CHI2_2D_CONFIDENCE_95, observations);
+        assert!(!vector.cliques().is_empty());
+
+        let json = serde_json::to_string(&vector.to_json()).unwrap();
+        let read = TestVector::from_json(&serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(read.edges(), vector.edges());
+        assert_eq!(read.cliques(), vector.cliques());
+        assert!(read.verify().is_ok());
+    }
+
+    #[test]
+    fn differences_are_reported() {
+        let observations = [0.0, 0.5, 1.0, 100.0].map(|x| {
+            Observation::builder(x, 0.0)
+                .circular_95_confidence_error(1.0)
+                .unwrap()
+                .build()
+        });
+        let vector = TestVector::generate(CHI2_2D_CONFIDENCE_95, observations);
+        assert_eq!(vector.cliques(), [vec![0, 1, 2]]);
+
+        let mismatch = vector
+            .check([(1, 0), (2, 1), (3, 0)], [vec![0, 1], vec![1, 2], vec![3]])
+            .unwrap_err();
+        assert_eq!(
+            mismatch,
+            TestVectorMismatch {
+                missing_edges: vec![(0, 2)],
+                unexpected_edges: vec![(0, 3)],
+                missing_cliques: vec![vec![0, 1, 2]],
+                unexpected_cliques: vec![vec![0, 1], vec![1, 2]],
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_vectors_are_rejected() {
+        let vector = TestVector::generate(
+            CHI2_2D_CONFIDENCE_95,
+            [Observation::builder(0.0, 0.0)
+                .circular_95_confidence_error(1.0)
+                .unwrap()
+                .build()],
+        );
+        let mut json = vector.to_json();
+        json["version"] = json!(2);
+        assert!(matches!(
+            TestVector::from_json(&json),
+            Err(InvalidTestVector::UnsupportedVersion(2))
+        ));
+
+        let mut json = vector.to_json();
+        json["edges"] = json!([[0, 1]]);
+        assert!(matches!(
+            TestVector::from_json(&json),
+            Err(InvalidTestVector::InvalidField("edges"))
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet, hash_map::Entry};
+
+use crate::Adjacency;
+
+/// A disjoint-set forest, used to track the connected components of the compatibility graph.
+///
+/// Uses union by size, so the depth of every tree is logarithmic in the size of its set, and path
+/// halving on mutable lookups, so repeated operations take near-constant amortised time.
+#[derive(Debug, Clone)]
+pub struct UnionFind<Id> {
+    parent: HashMap<Id, Id>,
+    size: HashMap<Id, usize>,
+}
+
+impl<Id> Default for UnionFind<Id> {
+    fn default() -> Self {
+        Self {
+            parent: HashMap::new(),
+            size: HashMap::new(),
+        }
+    }
+}
+
+impl<Id> UnionFind<Id>
+where
+    Id: Copy + Eq + std::hash::Hash,
+{
+    /// Build the forest of the connected components of a graph.
+    pub fn from_graph(graph: &impl Adjacency<Id>) -> Self {
+        let mut forest = Self::default();
+        for (&vertex, neighbours) in graph.iter() {
+            forest.insert(vertex);
+            for &neighbour in neighbours {
+                forest.union(vertex, neighbour);
+            }
+        }
+        forest
+    }
+
+    /// Add `id` as a singleton set, if it isn't already present.
+    fn insert(&mut self, id: Id) {
+        if let Entry::Vacant(entry) = self.parent.entry(id) {
+            entry.insert(id);
+            self.size.insert(id, 1);
+        }
+    }
+
+    /// Merge the sets containing `a` and `b`, adding either if not already present.
+    pub fn union(&mut self, a: Id, b: Id) {
+        let a = self.find_mut(a);
+        let b = self.find_mut(b);
+        if a == b {
+            return;
+        }
+
+        let (small, large) = if self.size[&a] < self.size[&b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent.insert(small, large);
+        let small_size = self.size.remove(&small).unwrap_or_default();
+        *self.size.entry(large).or_default() += small_size;
+    }
+
+    /// Find the representative of the set containing `id` (adding it if not present), halving the
+    /// path to the root as it goes.
+    fn find_mut(&mut self, id: Id) -> Id {
+        self.insert(id);
+        let mut current = id;
+        loop {
+            let parent = self.parent[&current];
+            if parent == current {
+                return current;
+            }
+            let grandparent = self.parent[&parent];
+            self.parent.insert(current, grandparent);
+            current = grandparent;
+        }
+    }
+
+    /// Find the representative of the set containing `id`, or `None` if it isn't present.
+    pub fn find(&self, id: &Id) -> Option<Id> {
+        let mut current = *self.parent.get(id)?;
+        loop {
+            let parent = self.parent[&current];
+            if parent == current {
+                return Some(current);
+            }
+            current = parent;
+        }
+    }
+
+    /// All of the sets in the forest.
+    pub fn sets(&self) -> Vec<HashSet<Id>> {
+        let mut sets: HashMap<Id, HashSet<Id>> = HashMap::with_capacity(self.size.len());
+        for &id in self.parent.keys() {
+            if let Some(root) = self.find(&id) {
+                sets.entry(root).or_default().insert(id);
+            }
+        }
+        sets.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unions_merge_sets() {
+        let mut forest = UnionFind::default();
+        forest.union(1, 2);
+        forest.union(3, 4);
+        assert_eq!(forest.find(&1), forest.find(&2));
+        assert_ne!(forest.find(&1), forest.find(&3));
+        assert_eq!(forest.find(&5), None);
+
+        forest.union(2, 4);
+        assert_eq!(forest.find(&1), forest.find(&3));
+        assert_eq!(forest.sets(), vec![HashSet::from([1, 2, 3, 4])]);
+    }
+
+    #[test]
+    fn from_graph_matches_connected_components() {
+        let graph = HashMap::from([
+            (1, HashSet::from([2])),
+            (2, HashSet::from([1, 3])),
+            (3, HashSet::from([2])),
+            (4, HashSet::from([5])),
+            (5, HashSet::from([4])),
+        ]);
+
+        let mut sets = UnionFind::from_graph(&graph).sets();
+        sets.sort_by_key(HashSet::len);
+
+        assert_eq!(sets, vec![HashSet::from([4, 5]), HashSet::from([1, 2, 3])]);
+    }
+}
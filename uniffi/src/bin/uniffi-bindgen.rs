@@ -0,0 +1,9 @@
+//! Generates the foreign-language bindings for this crate.
+//!
+//! ```sh
+//! cargo run --features cli --bin uniffi-bindgen -- generate --library <path to cdylib> --language kotlin --out-dir out
+//! ```
+
+fn main() {
+    uniffi::uniffi_bindgen_main();
+}
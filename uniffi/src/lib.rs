@@ -0,0 +1,401 @@
+//! [UniFFI](https://mozilla.github.io/uniffi-rs/) bindings for the `clique_fusion` crate.
+//!
+//! The interface is defined by the `#[uniffi::export]` annotations in this crate, from which
+//! Kotlin, Swift and Python bindings are generated with the `uniffi-bindgen` binary (see the
+//! README).
+//!
+//! Observations are identified by UUIDs, which cross the boundary as strings.
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use clique_fusion::{
+    CliqueIndex as Index, Config, CovarianceMatrix, InvalidChi2, InvalidConfidence,
+    InvalidCovarianceMatrix, InvalidPosition, Observation as Obs, Unique,
+};
+use uuid::Uuid;
+
+uniffi::setup_scaffolding!();
+
+/// The errors which may be returned to foreign callers.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CliqueFusionError {
+    /// The chi-squared threshold was not finite and strictly positive.
+    #[error(transparent)]
+    InvalidChi2(#[from] InvalidChi2),
+
+    /// An observation's covariance terms did not describe a positive semi-definite matrix.
+    #[error(transparent)]
+    InvalidCovariance(#[from] InvalidCovarianceMatrix),
+
+    /// An observation's position was not finite.
+    #[error(transparent)]
+    InvalidPosition(#[from] InvalidPosition),
+
+    /// A confidence level was out of range.
+    #[error(transparent)]
+    InvalidConfidence(#[from] InvalidConfidence),
+
+    /// A string could not be parsed as a UUID.
+    #[error("'{0}' is not a valid UUID")]
+    InvalidId(String),
+
+    /// An observation with the same ID already exists.
+    #[error("an observation with ID {0} already exists")]
+    DuplicateId(Uuid),
+}
+
+fn parse_id(id: &str) -> Result<Uuid, CliqueFusionError> {
+    Uuid::parse_str(id).map_err(|_| CliqueFusionError::InvalidId(id.to_string()))
+}
+
+/// An observation of an object at a fixed location, with uncertainty.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Observation {
+    /// The UUID of the observation.
+    pub id: String,
+    /// The x ordinate.
+    pub x: f64,
+    /// The y ordinate.
+    pub y: f64,
+    /// The variance in x.
+    pub cov_xx: f64,
+    /// The covariance of x and y.
+    pub cov_xy: f64,
+    /// The variance in y.
+    pub cov_yy: f64,
+    /// The UUID of the context (such as the sensor or survey) which made the observation, if any.
+    ///
+    /// Observations which share a context are never fused.
+    #[uniffi(default = None)]
+    pub context: Option<String>,
+    /// The UUID of the sensor which made the observation, if known.
+    ///
+    /// Unlike the context, this does not affect which observations are fused.
+    #[uniffi(default = None)]
+    pub sensor: Option<String>,
+    /// The time at which the observation was made, if known.
+    #[uniffi(default = None)]
+    pub timestamp: Option<f64>,
+    /// The relative weight of the observation.
+    #[uniffi(default = 1.0)]
+    pub weight: f64,
+    /// A caller-defined tag, if any.
+    #[uniffi(default = None)]
+    pub tag: Option<u64>,
+}
+
+impl Observation {
+    fn from_observation(id: Uuid, observation: &Obs) -> Self {
+        let error = observation.error_covariance();
+        Self {
+            id: id.to_string(),
+            x: observation.x(),
+            y: observation.y(),
+            cov_xx: error.xx(),
+            cov_xy: error.xy(),
+            cov_yy: error.yy(),
+            context: observation.context().map(|context| context.to_string()),
+            sensor: observation.sensor().map(|sensor| sensor.to_string()),
+            timestamp: observation.timestamp(),
+            weight: observation.weight(),
+            tag: observation.tag(),
+        }
+    }
+
+    fn to_observation(&self) -> Result<Unique<Obs, Uuid>, CliqueFusionError> {
+        let id = parse_id(&self.id)?;
+        let error = CovarianceMatrix::new(self.cov_xx, self.cov_yy, self.cov_xy)?;
+        let mut builder = Obs::builder(self.x, self.y)
+            .error(error)
+            .weight(self.weight);
+        if let Some(context) = &self.context {
+            builder = builder.context(parse_id(context)?);
+        }
+        if let Some(sensor) = &self.sensor {
+            builder = builder.sensor(parse_id(sensor)?);
+        }
+        if let Some(timestamp) = self.timestamp {
+            builder = builder.timestamp(timestamp);
+        }
+        if let Some(tag) = self.tag {
+            builder = builder.tag(tag);
+        }
+        Ok(Unique {
+            id,
+            data: builder.try_build()?,
+        })
+    }
+}
+
+/// The fused estimate of the position of the object observed by the members of a clique.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct FusedEstimate {
+    /// The UUIDs of the observations which were fused.
+    pub members: Vec<String>,
+    /// The fused x ordinate.
+    pub x: f64,
+    /// The fused y ordinate.
+    pub y: f64,
+    /// The fused variance in x.
+    pub cov_xx: f64,
+    /// The fused covariance of x and y.
+    pub cov_xy: f64,
+    /// The fused variance in y.
+    pub cov_yy: f64,
+    /// The UUIDs of the distinct sensors which made the fused observations.
+    pub sensors: Vec<String>,
+    /// The residual chi-squared statistic of the members about the fused position.
+    pub chi2_statistic: f64,
+    /// The degrees of freedom of the residual statistic.
+    pub degrees_of_freedom: u32,
+    /// The probability of a residual statistic at least this large if the members are consistent.
+    pub p_value: f64,
+}
+
+fn members(ids: &HashSet<Uuid>) -> Vec<String> {
+    ids.iter().map(ToString::to_string).collect()
+}
+
+/// An index which tracks the cliques of mutually compatible observations.
+///
+/// The index is guarded by a mutex, so may be shared between threads.
+#[derive(Debug, uniffi::Object)]
+pub struct CliqueIndex(Mutex<Index<Uuid>>);
+
+impl CliqueIndex {
+    fn lock(&self) -> MutexGuard<'_, Index<Uuid>> {
+        // a panic is reported to the foreign caller, which may carry on using the index
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[uniffi::export]
+impl CliqueIndex {
+    /// Construct an empty index with the given chi-squared threshold.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CliqueFusionError::InvalidChi2`] if `chi2` is not finite and strictly positive.
+    #[uniffi::constructor]
+    pub fn new(chi2: f64) -> Result<Arc<Self>, CliqueFusionError> {
+        Ok(Arc::new(Self(Mutex::new(Index::try_new(chi2)?))))
+    }
+
+    /// Construct an index from a batch of observations.
+    ///
+    /// This is much faster than inserting the observations one at a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `chi2` or any observation is invalid, or if two observations share an ID.
+    #[uniffi::constructor]
+    pub fn from_observations(
+        chi2: f64,
+        observations: &[Observation],
+    ) -> Result<Arc<Self>, CliqueFusionError> {
+        let config = Config::try_new(chi2)?;
+        let mut ids = HashSet::with_capacity(observations.len());
+        let observations = observations
+            .iter()
+            .map(|observation| {
+                let observation = observation.to_observation()?;
+                if !ids.insert(observation.id) {
+                    return Err(CliqueFusionError::DuplicateId(observation.id));
+                }
+                Ok(observation)
+            })
+            .collect::<Result<_, _>>()?;
+        let index = Index::from_observations_with_config(observations, config);
+        Ok(Arc::new(Self(Mutex::new(index))))
+    }
+
+    /// Insert an observation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the observation is invalid, or if an observation with the same ID is
+    /// already in the index. The index is not modified if an error is returned.
+    pub fn insert(&self, observation: &Observation) -> Result<(), CliqueFusionError> {
+        let observation = observation.to_observation()?;
+        let mut index = self.lock();
+        if index.contains(&observation.id) {
+            return Err(CliqueFusionError::DuplicateId(observation.id));
+        }
+        index.insert(observation);
+        drop(index);
+        Ok(())
+    }
+
+    /// Remove an observation, returning it (or `None` if it was not in the index).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CliqueFusionError::InvalidId`] if `id` is not a valid UUID.
+    pub fn remove(&self, id: &str) -> Result<Option<Observation>, CliqueFusionError> {
+        let id = parse_id(id)?;
+        let removed = self.lock().remove(&id);
+        Ok(removed.map(|observation| Observation::from_observation(id, &observation)))
+    }
+
+    /// Look up an observation by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CliqueFusionError::InvalidId`] if `id` is not a valid UUID.
+    pub fn get(&self, id: &str) -> Result<Option<Observation>, CliqueFusionError> {
+        let id = parse_id(id)?;
+        Ok(self
+            .lock()
+            .get(&id)
+            .map(|observation| Observation::from_observation(id, observation)))
+    }
+
+    /// Returns `true` if an observation with the given ID is in the index.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CliqueFusionError::InvalidId`] if `id` is not a valid UUID.
+    pub fn contains(&self, id: &str) -> Result<bool, CliqueFusionError> {
+        let id = parse_id(id)?;
+        Ok(self.lock().contains(&id))
+    }
+
+    /// The chi-squared threshold used to test compatibility.
+    pub fn chi2(&self) -> f64 {
+        self.lock().chi2()
+    }
+
+    /// Change the chi-squared threshold, recomputing all cliques.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CliqueFusionError::InvalidChi2`] if `chi2` is not finite and strictly positive.
+    pub fn set_chi2(&self, chi2: f64) -> Result<(), CliqueFusionError> {
+        self.lock().set_chi2(chi2)?;
+        Ok(())
+    }
+
+    /// The number of observations which are compatible with at least one other observation.
+    pub fn len(&self) -> u64 {
+        self.lock().len() as u64
+    }
+
+    /// Returns `true` if no observation is compatible with any other.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// The total number of observations in the index.
+    pub fn total_observations(&self) -> u64 {
+        self.lock().total_observations() as u64
+    }
+
+    /// The current maximal cliques, as lists of observation UUIDs.
+    pub fn cliques(&self) -> Vec<Vec<String>> {
+        self.lock().cliques().iter().map(members).collect()
+    }
+
+    /// The fused estimate of each of the current cliques, in the same order as `cliques()`.
+    pub fn fused_estimates(&self) -> Vec<FusedEstimate> {
+        self.lock()
+            .fused_estimates()
+            .into_iter()
+            .map(|estimate| FusedEstimate {
+                members: members(&estimate.members),
+                x: estimate.x,
+                y: estimate.y,
+                cov_xx: estimate.covariance.xx(),
+                cov_xy: estimate.covariance.xy(),
+                cov_yy: estimate.covariance.yy(),
+                sensors: members(&estimate.sensors),
+                chi2_statistic: estimate.goodness_of_fit.statistic,
+                degrees_of_freedom: estimate.goodness_of_fit.degrees_of_freedom,
+                p_value: estimate.goodness_of_fit.p_value,
+            })
+            .collect()
+    }
+}
+
+/// Compute the chi-squared threshold for an arbitrary confidence level (a probability in `(0, 1)`).
+///
+/// # Errors
+///
+/// Returns [`CliqueFusionError::InvalidConfidence`] if the confidence or degrees of freedom are out
+/// of range.
+#[uniffi::export(default(degrees_of_freedom = 2))]
+pub fn chi2_threshold(confidence: f64, degrees_of_freedom: u32) -> Result<f64, CliqueFusionError> {
+    Ok(clique_fusion::chi2_threshold(
+        confidence,
+        degrees_of_freedom,
+    )?)
+}
+
+/// Returns the version of this library.
+#[uniffi::export]
+#[must_use]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(id: u128, x: f64) -> Observation {
+        Observation {
+            id: Uuid::from_u128(id).to_string(),
+            x,
+            y: 0.0,
+            cov_xx: 1.0,
+            cov_xy: 0.0,
+            cov_yy: 1.0,
+            context: None,
+            sensor: Some(Uuid::from_u128(99).to_string()),
+            timestamp: Some(12.5),
+            weight: 1.0,
+            tag: Some(7),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let index = CliqueIndex::new(clique_fusion::CHI2_2D_CONFIDENCE_95).unwrap();
+        let first = observation(1, 0.0);
+        index.insert(&first).unwrap();
+        index.insert(&observation(2, 0.5)).unwrap();
+
+        assert_eq!(index.get(&first.id).unwrap(), Some(first.clone()));
+        assert_eq!(index.cliques().len(), 1);
+        assert_eq!(index.fused_estimates()[0].members.len(), 2);
+        assert!(matches!(
+            index.insert(&first),
+            Err(CliqueFusionError::DuplicateId(_))
+        ));
+
+        assert_eq!(index.remove(&first.id).unwrap(), Some(first));
+        assert!(index.is_empty());
+        assert_eq!(index.total_observations(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let index = CliqueIndex::new(clique_fusion::CHI2_2D_CONFIDENCE_95).unwrap();
+        assert!(matches!(
+            index.contains("not a uuid"),
+            Err(CliqueFusionError::InvalidId(_))
+        ));
+        let duplicates = [observation(1, 0.0), observation(1, 0.5)];
+        assert!(matches!(
+            CliqueIndex::from_observations(1.0, &duplicates),
+            Err(CliqueFusionError::DuplicateId(_))
+        ));
+        assert!(matches!(
+            CliqueIndex::new(-1.0),
+            Err(CliqueFusionError::InvalidChi2(_))
+        ));
+    }
+}